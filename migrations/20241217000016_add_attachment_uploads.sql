@@ -0,0 +1,25 @@
+-- ============================================
+-- Migration: Allow attachments to be uploaded before posting
+-- Description: Attachments are uploaded first and associated with a
+--              message when it is sent, so message_id becomes nullable
+--              and the uploader is recorded for ownership checks.
+-- ============================================
+
+ALTER TABLE attachments
+    ALTER COLUMN message_id DROP NOT NULL,
+    ADD COLUMN IF NOT EXISTS uploader_id BIGINT NULL REFERENCES users(id) ON DELETE CASCADE;
+
+COMMENT ON COLUMN attachments.message_id IS
+    'Message this attachment belongs to (NULL until the message is sent)';
+COMMENT ON COLUMN attachments.uploader_id IS
+    'User who uploaded the attachment';
+
+-- Index for looking up a user's pending (unattached) uploads
+CREATE INDEX IF NOT EXISTS idx_attachments_pending_uploader
+    ON attachments(uploader_id)
+    WHERE message_id IS NULL;
+
+-- Messages may now consist of attachments only; the application
+-- enforces that a message has content or at least one attachment.
+ALTER TABLE messages
+    DROP CONSTRAINT IF EXISTS messages_content_length;
@@ -0,0 +1,12 @@
+-- ============================================
+-- Migration: Add message embeds
+-- Description: Stores rich embeds as a JSON array on each message.
+--              Limits (count, field lengths) are enforced by the
+--              application.
+-- ============================================
+
+ALTER TABLE messages
+    ADD COLUMN IF NOT EXISTS message_embeds JSONB NOT NULL DEFAULT '[]'::jsonb;
+
+COMMENT ON COLUMN messages.message_embeds IS
+    'Rich embeds attached to the message (JSON array, max 10)';
@@ -0,0 +1,15 @@
+-- ============================================
+-- Migration: Create DM recipients
+-- Description: Users taking part in DM and group DM channels. Only
+--              recipients may read or post in a DM channel.
+-- ============================================
+
+CREATE TABLE IF NOT EXISTS dm_recipients (
+    channel_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (channel_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dm_recipients_user_id ON dm_recipients(user_id);
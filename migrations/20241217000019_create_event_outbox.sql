@@ -0,0 +1,20 @@
+-- ============================================
+-- Migration: Create event outbox
+-- Description: Gateway events written in the same transaction as the
+--              change they describe. A background relay publishes unsent
+--              rows and marks them sent, so events survive a crash between
+--              commit and publish. dedup_key makes enqueueing and relaying
+--              idempotent.
+-- ============================================
+
+CREATE TABLE IF NOT EXISTS event_outbox (
+    id BIGSERIAL PRIMARY KEY,
+    dedup_key VARCHAR(128) NOT NULL UNIQUE,
+    event_type VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sent_at TIMESTAMPTZ
+);
+
+-- Relay scans only unsent rows, oldest first
+CREATE INDEX IF NOT EXISTS idx_event_outbox_unsent ON event_outbox(id) WHERE sent_at IS NULL;
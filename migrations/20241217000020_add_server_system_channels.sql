@@ -0,0 +1,10 @@
+-- ============================================
+-- Migration: Add server system and AFK channels
+-- Description: Channel for system messages (member joins, boosts) and the
+--              voice channel idle members are moved to. Cleared when the
+--              channel is deleted.
+-- ============================================
+
+ALTER TABLE servers
+    ADD COLUMN IF NOT EXISTS system_channel_id BIGINT REFERENCES channels(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS afk_channel_id BIGINT REFERENCES channels(id) ON DELETE SET NULL;
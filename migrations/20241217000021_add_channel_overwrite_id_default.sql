@@ -0,0 +1,9 @@
+-- ============================================
+-- Migration: Generate channel overwrite IDs
+-- Description: Overwrites are addressed by (channel_id, target_type,
+--              target_id); give the surrogate id a generated default so
+--              inserts and upserts need not supply one.
+-- ============================================
+
+ALTER TABLE channel_permission_overwrites
+    ALTER COLUMN id ADD GENERATED BY DEFAULT AS IDENTITY;
@@ -0,0 +1,14 @@
+-- ============================================
+-- Migration: Add server notification and content filter defaults
+-- Description: Default notification level for members without their own
+--              setting, and which members' messages are scanned for
+--              explicit content.
+-- ============================================
+
+CREATE TYPE message_notification_level AS ENUM ('all', 'mentions');
+
+CREATE TYPE explicit_content_filter_level AS ENUM ('disabled', 'members_without_roles', 'all');
+
+ALTER TABLE servers
+    ADD COLUMN IF NOT EXISTS default_message_notifications message_notification_level NOT NULL DEFAULT 'all',
+    ADD COLUMN IF NOT EXISTS explicit_content_filter explicit_content_filter_level NOT NULL DEFAULT 'disabled';
@@ -0,0 +1,19 @@
+-- ============================================
+-- Migration: Create channel overrides
+-- Description: Per-user notification settings for a channel. A missing
+--              row means the channel is unmuted and follows the guild's
+--              default notification level.
+-- ============================================
+
+CREATE TYPE channel_notification_level AS ENUM ('all', 'mentions', 'nothing');
+
+CREATE TABLE IF NOT EXISTS channel_overrides (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    muted BOOLEAN NOT NULL DEFAULT FALSE,
+    -- NULL inherits the guild default
+    notification_level channel_notification_level,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (user_id, channel_id)
+);
@@ -0,0 +1,8 @@
+-- ============================================
+-- Migration: Add server auto role
+-- Description: Role given to members when they join through an invite.
+--              Cleared if the role is deleted.
+-- ============================================
+
+ALTER TABLE servers
+    ADD COLUMN IF NOT EXISTS auto_role_id BIGINT REFERENCES roles(id) ON DELETE SET NULL;
@@ -0,0 +1,8 @@
+-- ============================================
+-- Migration: Add member timeouts
+-- Description: Members can be timed out until a given time, during which
+--              they cannot communicate in the server.
+-- ============================================
+
+ALTER TABLE server_members
+    ADD COLUMN IF NOT EXISTS communication_disabled_until TIMESTAMPTZ;
@@ -0,0 +1,8 @@
+-- ============================================
+-- Migration: Add server discoverability
+-- Description: Discoverable servers can be previewed by anyone, including
+--              users who are not members and are not logged in.
+-- ============================================
+
+ALTER TABLE servers
+    ADD COLUMN IF NOT EXISTS discoverable BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,9 @@
+-- ============================================
+-- Migration: Add text-to-speech messages
+-- Description: Flags messages that clients should read aloud. Sending
+--              them requires SEND_TTS_MESSAGES, checked by the
+--              application.
+-- ============================================
+
+ALTER TABLE messages
+    ADD COLUMN IF NOT EXISTS tts BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,8 @@
+-- ============================================
+-- Migration: Add user banners
+-- Description: Profile banner image, shown alongside the avatar. Values
+--              are validated by the application like avatar URLs.
+-- ============================================
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS banner_url TEXT;
@@ -0,0 +1,9 @@
+-- ============================================
+-- Migration: Add persistent sessions
+-- Description: Whether a login asked to be remembered. Short-lived
+--              sessions get a shorter refresh token lifetime; existing
+--              sessions keep the long one.
+-- ============================================
+
+ALTER TABLE user_sessions
+    ADD COLUMN IF NOT EXISTS persistent BOOLEAN NOT NULL DEFAULT TRUE;
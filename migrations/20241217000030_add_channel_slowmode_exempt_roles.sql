@@ -0,0 +1,9 @@
+-- ============================================
+-- Migration: Add slowmode-exempt roles
+-- Description: Roles whose members skip a channel's slowmode, on top of
+--              the MANAGE_MESSAGES bypass. Stored as a JSON array of role
+--              IDs; the application checks they belong to the server.
+-- ============================================
+
+ALTER TABLE channels
+    ADD COLUMN IF NOT EXISTS slowmode_exempt_roles JSONB NOT NULL DEFAULT '[]';
@@ -0,0 +1,9 @@
+-- ============================================
+-- Migration: Add member join order index
+-- Description: Serves member lists ordered by join time. user_id breaks
+--              ties between members who joined at the same instant so the
+--              listing can be paginated with a (joined_at, user_id) cursor.
+-- ============================================
+
+CREATE INDEX IF NOT EXISTS idx_server_members_join_order
+    ON server_members(server_id, joined_at, user_id);
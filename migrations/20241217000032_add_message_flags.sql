@@ -0,0 +1,9 @@
+-- ============================================
+-- Migration: Add message flags
+-- Description: Bitfield of per-message flags: CROSSPOSTED (1), SUPPRESS_EMBEDS
+--              (4) and EPHEMERAL (64). Ephemeral messages are left out of
+--              channel history.
+-- ============================================
+
+ALTER TABLE messages
+    ADD COLUMN IF NOT EXISTS flags BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,10 @@
+-- ============================================
+-- Migration: Add user presence visibility
+-- Description: Who may see a user's presence: everyone, friends or none.
+--              Hidden users appear offline to other guild members.
+-- ============================================
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS presence_visibility VARCHAR(10) NOT NULL DEFAULT 'everyone'
+        CONSTRAINT users_presence_visibility_check
+        CHECK (presence_visibility IN ('everyone', 'friends', 'none'));
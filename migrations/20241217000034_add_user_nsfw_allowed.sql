@@ -0,0 +1,8 @@
+-- ============================================
+-- Migration: Add user NSFW opt-in
+-- Description: Whether a user may view NSFW channels. Off by default;
+--              moderators can still open NSFW channels they moderate.
+-- ============================================
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS nsfw_allowed BOOLEAN NOT NULL DEFAULT FALSE;
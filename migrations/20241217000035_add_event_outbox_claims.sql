@@ -0,0 +1,12 @@
+-- ============================================
+-- Migration: Add event outbox claims
+-- Description: Relays claim unsent rows for a lease before publishing them,
+--              so several instances never publish the same row at once. A
+--              crashed relay's claims lapse and the rows are picked up again.
+--              Sent rows are deleted once past the retention period.
+-- ============================================
+
+ALTER TABLE event_outbox ADD COLUMN IF NOT EXISTS claimed_until TIMESTAMPTZ;
+
+-- Retention sweep deletes sent rows by age
+CREATE INDEX IF NOT EXISTS idx_event_outbox_sent_at ON event_outbox(sent_at) WHERE sent_at IS NOT NULL;
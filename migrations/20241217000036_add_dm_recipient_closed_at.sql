@@ -0,0 +1,8 @@
+-- ============================================
+-- Migration: Add DM close state
+-- Description: When a recipient closed the DM. Closing hides the DM for
+--              that recipient only; opening it again clears the flag.
+-- ============================================
+
+ALTER TABLE dm_recipients
+    ADD COLUMN IF NOT EXISTS closed_at TIMESTAMPTZ;
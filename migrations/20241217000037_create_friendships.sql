@@ -0,0 +1,17 @@
+-- ============================================
+-- Migration: Create friendships
+-- Description: Accepted friendships between users. Each friendship is
+--              stored in both directions so either side can be looked up
+--              by user_id alone.
+-- ============================================
+
+CREATE TABLE IF NOT EXISTS friendships (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    friend_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (user_id, friend_id),
+    CONSTRAINT friendships_not_self CHECK (user_id <> friend_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_friendships_friend_id ON friendships(friend_id);
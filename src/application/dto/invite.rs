@@ -54,6 +54,12 @@ pub struct InviteResponse {
     pub expires_at: Option<String>,
     /// Creation timestamp (ISO 8601).
     pub created_at: String,
+    /// Remaining uses (null if unlimited).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_uses: Option<i32>,
+    /// Seconds until expiration (null if never).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_in_seconds: Option<i64>,
 }
 
 impl From<InviteDto> for InviteResponse {
@@ -69,6 +75,8 @@ impl From<InviteDto> for InviteResponse {
             temporary: dto.temporary,
             expires_at: dto.expires_at,
             created_at: dto.created_at,
+            remaining_uses: dto.remaining_uses,
+            expires_in_seconds: dto.expires_in,
         }
     }
 }
@@ -106,8 +114,9 @@ pub struct InviteGuildPreview {
 pub struct InviteChannelPreview {
     /// Channel ID.
     pub id: String,
-    /// Channel name.
-    pub name: String,
+    /// Channel name (omitted if the channel was deleted).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 }
 
 /// Inviter preview in invite response.
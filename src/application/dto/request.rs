@@ -2,9 +2,11 @@
 //!
 //! Data structures for API request bodies.
 
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 use validator::Validate;
 
+use crate::domain::value_objects::Permissions;
+use crate::domain::{Embed, MemberOrder, PresenceVisibility};
 use crate::shared::validation::validate_password_strength;
 
 /// Login request
@@ -15,6 +17,10 @@ pub struct LoginRequest {
 
     #[validate(length(min = 8, message = "Password must be at least 8 characters"))]
     pub password: String,
+
+    /// Keep the session across restarts; otherwise it is short-lived
+    #[serde(default)]
+    pub remember_me: bool,
 }
 
 /// Registration request
@@ -45,10 +51,37 @@ pub struct UpdateUserRequest {
     #[validate(length(max = 32, message = "Display name must be at most 32 characters"))]
     pub display_name: Option<String>,
 
+    /// Storage key of an uploaded file or an https URL; empty removes it
     pub avatar_url: Option<String>,
 
+    /// Storage key of an uploaded file or an https URL; empty removes it
+    pub banner_url: Option<String>,
+
     #[validate(length(max = 190, message = "Bio must be at most 190 characters"))]
     pub bio: Option<String>,
+
+    /// Who may see the user's presence
+    pub presence_visibility: Option<PresenceVisibility>,
+
+    /// Whether the user may view NSFW channels
+    pub nsfw_allowed: Option<bool>,
+}
+
+/// Delete account request
+#[derive(Debug, Deserialize, Validate)]
+pub struct DeleteAccountRequest {
+    #[validate(length(min = 1, message = "Password is required"))]
+    pub password: String,
+}
+
+/// Change password request
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1, message = "Current password is required"))]
+    pub current_password: String,
+
+    #[validate(custom(function = "validate_password_strength"))]
+    pub new_password: String,
 }
 
 /// Create guild request
@@ -59,6 +92,10 @@ pub struct CreateGuildRequest {
 
     pub icon_url: Option<String>,
     pub description: Option<String>,
+
+    /// Client-chosen value; repeating it shortly after rejects the create
+    #[validate(length(min = 1, max = 64, message = "Nonce must be 1-64 characters"))]
+    pub nonce: Option<String>,
 }
 
 /// Update guild request
@@ -67,8 +104,44 @@ pub struct UpdateGuildRequest {
     #[validate(length(min = 2, max = 100, message = "Name must be 2-100 characters"))]
     pub name: Option<String>,
 
-    pub icon_url: Option<String>,
-    pub description: Option<String>,
+    /// Icon URL (use null to remove the icon)
+    #[serde(default, deserialize_with = "nullable")]
+    pub icon_url: Option<Option<String>>,
+
+    /// Description (use null to remove it)
+    #[serde(default, deserialize_with = "nullable")]
+    pub description: Option<Option<String>>,
+
+    /// System message channel ID (use null to disable system messages)
+    #[serde(default, deserialize_with = "nullable")]
+    pub system_channel_id: Option<Option<String>>,
+
+    /// AFK voice channel ID (use null to disable)
+    #[serde(default, deserialize_with = "nullable")]
+    pub afk_channel_id: Option<Option<String>>,
+
+    /// Role given to members joining through an invite (use null to disable)
+    #[serde(default, deserialize_with = "nullable")]
+    pub auto_role_id: Option<Option<String>>,
+
+    /// Default notification level: `all` or `mentions`
+    pub default_message_notifications: Option<String>,
+
+    /// Explicit content filter: `disabled`, `members_without_roles` or `all`
+    pub explicit_content_filter: Option<String>,
+
+    /// Whether anyone may preview the guild without joining
+    pub discoverable: Option<bool>,
+}
+
+/// Deserialize a nullable field so that an explicit `null` becomes
+/// `Some(None)`, distinct from an absent field (`None`).
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::deserialize(deserializer).map(Some)
 }
 
 /// Create channel request
@@ -84,6 +157,9 @@ pub struct CreateChannelRequest {
     pub parent_id: Option<String>,
     pub position: Option<i32>,
     pub nsfw: Option<bool>,
+
+    /// Copy the parent category's permission overwrites (default true)
+    pub sync_permissions: Option<bool>,
 }
 
 /// Update channel request
@@ -97,18 +173,72 @@ pub struct UpdateChannelRequest {
     pub parent_id: Option<Option<String>>,
     pub nsfw: Option<bool>,
     pub rate_limit_per_user: Option<i32>,
+
+    /// Role IDs whose members skip slowmode; replaces the current list
+    pub slowmode_exempt_roles: Option<Vec<String>>,
+}
+
+/// Set a single channel permission overwrite
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetOverwriteRequest {
+    /// "role" or "member"
+    #[serde(rename = "type")]
+    pub target_type: String,
+
+    /// Allowed permissions, as a bitfield string or a list of flag names
+    #[serde(default)]
+    pub allow: Option<Permissions>,
+
+    /// Denied permissions, as a bitfield string or a list of flag names
+    #[serde(default)]
+    pub deny: Option<Permissions>,
+}
+
+/// Clone channel request
+#[derive(Debug, Deserialize, Validate)]
+pub struct CloneChannelRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+}
+
+/// Open DM channel request
+#[derive(Debug, Deserialize)]
+pub struct CreateDmRequest {
+    pub recipient_id: String,
 }
 
 /// Send message request
 #[derive(Debug, Deserialize, Validate)]
 pub struct SendMessageRequest {
-    #[validate(length(min = 1, max = 2000, message = "Content must be 1-2000 characters"))]
+    /// May be empty when the message carries attachments
+    #[serde(default)]
+    #[validate(length(max = 2000, message = "Content must be at most 2000 characters"))]
     pub content: String,
 
     pub reply_to: Option<String>,
 
+    /// IDs of previously uploaded attachments
     #[serde(default)]
     pub attachments: Vec<String>,
+
+    /// Rich embeds (requires `EMBED_LINKS`)
+    #[serde(default)]
+    pub embeds: Vec<Embed>,
+
+    /// Read the message aloud (requires `SEND_TTS_MESSAGES`)
+    #[serde(default)]
+    pub tts: bool,
+
+    /// Message flags; only suppress embeds (`1 << 2`) may be set
+    #[serde(default)]
+    pub flags: i64,
+}
+
+/// Bulk delete messages request
+#[derive(Debug, Deserialize)]
+pub struct BulkDeleteMessagesRequest {
+    /// IDs of the messages to delete
+    pub messages: Vec<String>,
 }
 
 /// Message query parameters
@@ -125,6 +255,23 @@ pub struct MessageQueryParams {
 pub struct MembersQueryParams {
     pub after: Option<String>,
     pub limit: Option<i32>,
+    /// `user_id` (default) or `joined_at`
+    #[serde(default)]
+    pub sort: MemberOrder,
+}
+
+/// Reactors query parameters
+#[derive(Debug, Deserialize)]
+pub struct ReactorsQueryParams {
+    pub after: Option<String>,
+    pub limit: Option<i32>,
+}
+
+/// Guild invites query parameters
+#[derive(Debug, Deserialize)]
+pub struct InviteQueryParams {
+    pub before_code: Option<String>,
+    pub limit: Option<i32>,
 }
 
 /// Create invite request
@@ -159,8 +306,8 @@ pub struct CreateRoleRequest {
     #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
     pub name: String,
 
-    /// Permission bitfield (as string for `JavaScript` `BigInt` compatibility)
-    pub permissions: Option<String>,
+    /// Permissions, as a bitfield string or a list of flag names
+    pub permissions: Option<Permissions>,
 
     /// Role color as RGB integer
     pub color: Option<i32>,
@@ -178,8 +325,8 @@ pub struct UpdateRoleRequest {
     #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
     pub name: Option<String>,
 
-    /// Permission bitfield (as string for `JavaScript` `BigInt` compatibility)
-    pub permissions: Option<String>,
+    /// Permissions, as a bitfield string or a list of flag names
+    pub permissions: Option<Permissions>,
 
     /// Role color as RGB integer (use null to remove color)
     pub color: Option<Option<i32>>,
@@ -214,3 +361,53 @@ pub struct AssignRoleRequest {
     /// User ID to assign the role to
     pub user_id: String,
 }
+
+/// Replace a member's roles request
+#[derive(Debug, Deserialize)]
+pub struct SetMemberRolesRequest {
+    /// Role IDs the member should have
+    pub roles: Vec<String>,
+}
+
+/// Moderate a member's voice state request
+#[derive(Debug, Deserialize)]
+pub struct ModifyVoiceStateRequest {
+    /// Voice channel to move the member to
+    pub channel_id: Option<String>,
+
+    /// Server mute the member
+    pub mute: Option<bool>,
+
+    /// Server deafen the member
+    pub deaf: Option<bool>,
+}
+
+/// Update channel override request
+#[derive(Debug, Deserialize)]
+pub struct ChannelOverrideRequest {
+    /// Mute the channel
+    pub muted: Option<bool>,
+
+    /// Notification level: `all`, `mentions` or `nothing` (use null to
+    /// follow the guild default)
+    #[serde(default, deserialize_with = "nullable")]
+    pub notification_level: Option<Option<String>>,
+}
+
+/// Change log filter request (admin)
+#[derive(Debug, Deserialize, Validate)]
+pub struct LogLevelRequest {
+    #[validate(length(min = 1, max = 1024, message = "Filter must be 1-1024 characters"))]
+    pub filter: String,
+}
+
+/// Reset rate limit request (admin)
+#[derive(Debug, Deserialize, Validate)]
+pub struct RateLimitResetRequest {
+    /// Limiter identifier, `user:{id}` or `ip:{addr}`
+    #[validate(length(min = 1, max = 64, message = "Identifier must be 1-64 characters"))]
+    pub identifier: String,
+
+    /// Endpoint type: `auth`, `api`, `websocket`, `high_frequency` or `guild`
+    pub endpoint_type: String,
+}
@@ -4,8 +4,9 @@
 
 use serde::Serialize;
 
-use crate::application::services::{AuthTokens, UserDto, GuildDto, ChannelDto, MessageDto, MemberDto, RoleDto};
-use crate::domain::User;
+use crate::application::services::{AuthTokens, ChannelOverrideDto, SessionDto, VoiceStateDto, UserDto, GuildDto, GuildPreviewDto, ChannelDto, MessageDto, MessageMemberDto, AttachmentDto, MemberDto, MemberDetailDto, RoleDto};
+use crate::domain::{DefaultMessageNotifications, Embed, ExplicitContentFilter, PresenceVisibility, User};
+use crate::domain::NotificationLevel;
 
 /// Authentication tokens response
 #[derive(Debug, Serialize)]
@@ -14,6 +15,8 @@ pub struct TokenResponse {
     pub refresh_token: String,
     pub expires_in: i64,
     pub token_type: String,
+    /// False for short-lived sessions the client shouldn't persist
+    pub persistent: bool,
 }
 
 impl From<AuthTokens> for TokenResponse {
@@ -23,6 +26,7 @@ impl From<AuthTokens> for TokenResponse {
             refresh_token: tokens.refresh_token,
             expires_in: tokens.expires_in,
             token_type: tokens.token_type,
+            persistent: tokens.persistent,
         }
     }
 }
@@ -37,6 +41,54 @@ pub struct RegisterResponse {
     pub token_type: String,
 }
 
+/// Active session response
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    pub id: String,
+    pub device_type: String,
+    pub device_info: Option<String>,
+    pub os_info: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: String,
+    pub last_used_at: String,
+    pub persistent: bool,
+    pub current: bool,
+}
+
+impl From<SessionDto> for SessionResponse {
+    fn from(dto: SessionDto) -> Self {
+        Self {
+            id: dto.id,
+            device_type: dto.device_type,
+            device_info: dto.device_info,
+            os_info: dto.os_info,
+            ip_address: dto.ip_address,
+            created_at: dto.created_at,
+            last_used_at: dto.last_used_at,
+            persistent: dto.persistent,
+            current: dto.current,
+        }
+    }
+}
+
+/// Channel override response
+#[derive(Debug, Serialize)]
+pub struct ChannelOverrideResponse {
+    pub channel_id: String,
+    pub muted: bool,
+    pub notification_level: Option<NotificationLevel>,
+}
+
+impl From<ChannelOverrideDto> for ChannelOverrideResponse {
+    fn from(dto: ChannelOverrideDto) -> Self {
+        Self {
+            channel_id: dto.channel_id,
+            muted: dto.muted,
+            notification_level: dto.notification_level,
+        }
+    }
+}
+
 /// User response
 #[derive(Debug, Serialize)]
 pub struct UserResponse {
@@ -46,8 +98,15 @@ pub struct UserResponse {
     pub email: Option<String>,
     pub display_name: Option<String>,
     pub avatar_url: Option<String>,
+    pub banner_url: Option<String>,
     pub status: String,
     pub bio: Option<String>,
+    /// Only shown to the user themselves
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_visibility: Option<PresenceVisibility>,
+    /// Only shown to the user themselves
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nsfw_allowed: Option<bool>,
     pub created_at: String,
 }
 
@@ -59,8 +118,11 @@ impl UserResponse {
             email: if include_email { Some(user.email) } else { None },
             display_name: user.display_name,
             avatar_url: user.avatar_url,
+            banner_url: user.banner_url,
             status: user.status.as_str().to_string(),
             bio: user.bio,
+            presence_visibility: include_email.then_some(user.presence_visibility),
+            nsfw_allowed: include_email.then_some(user.nsfw_allowed),
             created_at: user.created_at.to_rfc3339(),
         }
     }
@@ -72,8 +134,11 @@ impl UserResponse {
             email: if include_email { Some(dto.email) } else { None },
             display_name: dto.display_name,
             avatar_url: dto.avatar_url,
+            banner_url: dto.banner_url,
             status: dto.status,
             bio: dto.bio,
+            presence_visibility: include_email.then_some(dto.presence_visibility),
+            nsfw_allowed: include_email.then_some(dto.nsfw_allowed),
             created_at: dto.created_at,
         }
     }
@@ -87,6 +152,12 @@ pub struct GuildResponse {
     pub owner_id: String,
     pub icon_url: Option<String>,
     pub description: Option<String>,
+    pub system_channel_id: Option<String>,
+    pub afk_channel_id: Option<String>,
+    pub default_message_notifications: DefaultMessageNotifications,
+    pub explicit_content_filter: ExplicitContentFilter,
+    pub auto_role_id: Option<String>,
+    pub features: Vec<String>,
     pub member_count: i64,
     pub created_at: String,
 }
@@ -99,12 +170,44 @@ impl From<GuildDto> for GuildResponse {
             owner_id: dto.owner_id,
             icon_url: dto.icon_url,
             description: dto.description,
+            system_channel_id: dto.system_channel_id,
+            afk_channel_id: dto.afk_channel_id,
+            default_message_notifications: dto.default_message_notifications,
+            explicit_content_filter: dto.explicit_content_filter,
+            auto_role_id: dto.auto_role_id,
+            features: dto.features,
             member_count: dto.member_count,
             created_at: dto.created_at,
         }
     }
 }
 
+/// Public guild preview response
+#[derive(Debug, Serialize)]
+pub struct GuildPreviewResponse {
+    pub id: String,
+    pub name: String,
+    pub icon_url: Option<String>,
+    pub description: Option<String>,
+    pub approximate_member_count: i64,
+    pub approximate_presence_count: i64,
+    pub features: Vec<String>,
+}
+
+impl From<GuildPreviewDto> for GuildPreviewResponse {
+    fn from(dto: GuildPreviewDto) -> Self {
+        Self {
+            id: dto.id,
+            name: dto.name,
+            icon_url: dto.icon_url,
+            description: dto.description,
+            approximate_member_count: dto.approximate_member_count,
+            approximate_presence_count: dto.approximate_presence_count,
+            features: dto.features,
+        }
+    }
+}
+
 /// Channel response
 #[derive(Debug, Serialize)]
 pub struct ChannelResponse {
@@ -118,6 +221,7 @@ pub struct ChannelResponse {
     pub parent_id: Option<String>,
     pub nsfw: bool,
     pub rate_limit_per_user: i32,
+    pub slowmode_exempt_roles: Vec<String>,
     pub created_at: String,
 }
 
@@ -133,6 +237,7 @@ impl From<ChannelDto> for ChannelResponse {
             parent_id: dto.parent_id,
             nsfw: dto.nsfw,
             rate_limit_per_user: dto.rate_limit_per_user,
+            slowmode_exempt_roles: dto.slowmode_exempt_roles,
             created_at: dto.created_at,
         }
     }
@@ -149,8 +254,19 @@ pub struct MessageResponse {
     pub message_type: String,
     pub reply_to_id: Option<String>,
     pub pinned: bool,
+    pub tts: bool,
+    pub flags: i64,
     pub edited_at: Option<String>,
     pub created_at: String,
+    pub attachments: Vec<AttachmentResponse>,
+    pub embeds: Vec<Embed>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<UserResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member: Option<MessageMemberResponse>,
+    /// The message replied to; null when it was deleted
+    pub referenced_message: Option<Box<MessageResponse>>,
+    pub referenced_message_deleted: bool,
 }
 
 impl From<MessageDto> for MessageResponse {
@@ -163,8 +279,60 @@ impl From<MessageDto> for MessageResponse {
             message_type: dto.message_type,
             reply_to_id: dto.reply_to_id,
             pinned: dto.pinned,
+            tts: dto.tts,
+            flags: dto.flags,
             edited_at: dto.edited_at,
             created_at: dto.created_at,
+            attachments: dto.attachments.into_iter().map(AttachmentResponse::from).collect(),
+            embeds: dto.embeds,
+            author: dto.author.map(|author| UserResponse::from_dto(author, false)),
+            member: dto.member.map(MessageMemberResponse::from),
+            referenced_message: dto.referenced_message.map(|m| Box::new(MessageResponse::from(*m))),
+            referenced_message_deleted: dto.referenced_message_deleted,
+        }
+    }
+}
+
+/// Guild member details of a message author
+#[derive(Debug, Serialize)]
+pub struct MessageMemberResponse {
+    pub nickname: Option<String>,
+    pub roles: Vec<String>,
+}
+
+impl From<MessageMemberDto> for MessageMemberResponse {
+    fn from(dto: MessageMemberDto) -> Self {
+        Self {
+            nickname: dto.nickname,
+            roles: dto.roles,
+        }
+    }
+}
+
+/// Message attachment response
+#[derive(Debug, Serialize)]
+pub struct AttachmentResponse {
+    pub id: String,
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub size: i32,
+    pub url: String,
+    pub proxy_url: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
+impl From<AttachmentDto> for AttachmentResponse {
+    fn from(dto: AttachmentDto) -> Self {
+        Self {
+            id: dto.id,
+            filename: dto.filename,
+            content_type: dto.content_type,
+            size: dto.size,
+            url: dto.url,
+            proxy_url: dto.proxy_url,
+            width: dto.width,
+            height: dto.height,
         }
     }
 }
@@ -191,6 +359,34 @@ impl From<MemberDto> for MemberResponse {
     }
 }
 
+/// Member with profile, roles and permissions
+#[derive(Debug, Serialize)]
+pub struct MemberDetailResponse {
+    pub user: UserResponse,
+    pub guild_id: String,
+    pub nickname: Option<String>,
+    pub roles: Vec<String>,
+    pub joined_at: String,
+    pub permissions: String,
+    pub communication_disabled_until: Option<String>,
+    pub timed_out: bool,
+}
+
+impl From<MemberDetailDto> for MemberDetailResponse {
+    fn from(dto: MemberDetailDto) -> Self {
+        Self {
+            user: UserResponse::from_dto(dto.user, false),
+            guild_id: dto.server_id,
+            nickname: dto.nickname,
+            roles: dto.roles,
+            joined_at: dto.joined_at,
+            permissions: dto.permissions,
+            communication_disabled_until: dto.communication_disabled_until,
+            timed_out: dto.timed_out,
+        }
+    }
+}
+
 /// Message author (partial user)
 #[derive(Debug, Serialize)]
 pub struct MessageAuthor {
@@ -241,6 +437,12 @@ pub struct InviteResponse {
     pub expires_at: Option<String>,
     /// When the invite was created (ISO 8601 format)
     pub created_at: String,
+    /// Uses left before the invite is exhausted (null if unlimited)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_uses: Option<i32>,
+    /// Seconds until the invite expires (null if never)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_in: Option<i64>,
 }
 
 /// Invite preview response (public info for potential joiners)
@@ -372,3 +574,51 @@ impl From<RoleDto> for PartialRoleResponse {
         }
     }
 }
+
+/// Log filter change response (admin)
+#[derive(Debug, Serialize)]
+pub struct LogLevelResponse {
+    /// Filter directives that were active before the change
+    pub previous: String,
+    /// Filter directives now in effect
+    pub filter: String,
+}
+
+/// Rate limit reset response (admin)
+#[derive(Debug, Serialize)]
+pub struct RateLimitResetResponse {
+    pub identifier: String,
+    pub endpoint_type: String,
+    /// Number of rate limit counters removed
+    pub keys_removed: u64,
+}
+
+/// Voice state response
+#[derive(Debug, Serialize)]
+pub struct VoiceStateResponse {
+    pub user_id: String,
+    pub guild_id: String,
+    pub channel_id: Option<String>,
+    pub session_id: String,
+    pub self_mute: bool,
+    pub self_deaf: bool,
+    pub mute: bool,
+    pub deaf: bool,
+    pub suppress: bool,
+}
+
+impl From<VoiceStateDto> for VoiceStateResponse {
+    fn from(dto: VoiceStateDto) -> Self {
+        Self {
+            user_id: dto.user_id,
+            guild_id: dto.server_id,
+            channel_id: dto.channel_id,
+            session_id: dto.session_id,
+            self_mute: dto.self_mute,
+            self_deaf: dto.self_deaf,
+            mute: dto.mute,
+            deaf: dto.deaf,
+            suppress: dto.suppress,
+        }
+    }
+}
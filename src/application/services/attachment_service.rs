@@ -0,0 +1,284 @@
+//! Attachment Service
+//!
+//! Handles attachment uploads: files are written to the configured
+//! [`FileStorage`] under a key derived from the attachment's snowflake, and
+//! recorded unattached until a message claims them.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::application::services::AttachmentDto;
+use crate::domain::{Attachment, MAX_ATTACHMENT_SIZE};
+use crate::infrastructure::repositories::{AttachmentEntity, AttachmentRepository, CreateAttachment};
+use crate::infrastructure::storage::{attachment_key, FileStorage};
+use crate::shared::image::image_info;
+use crate::shared::snowflake::SnowflakeGenerator;
+use crate::shared::error::AppError;
+
+/// Longest accepted filename
+const MAX_FILENAME_LENGTH: usize = 255;
+
+/// Attachment service trait
+#[async_trait]
+pub trait AttachmentService: Send + Sync {
+    /// Store an uploaded file and record it as an unattached attachment
+    async fn upload(&self, uploader_id: i64, upload: UploadAttachmentDto) -> Result<AttachmentDto, AttachmentError>;
+
+    /// Delete an attachment the user uploaded that no message uses yet
+    async fn delete(&self, attachment_id: i64, actor_id: i64) -> Result<(), AttachmentError>;
+}
+
+/// Uploaded file
+#[derive(Debug, Clone)]
+pub struct UploadAttachmentDto {
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub data: Bytes,
+}
+
+/// Attachment service errors
+#[derive(Debug, thiserror::Error)]
+pub enum AttachmentError {
+    #[error("Attachment not found")]
+    NotFound,
+
+    #[error("Not the uploader of this attachment")]
+    Forbidden,
+
+    #[error("Attachment is already attached to a message")]
+    AlreadyAttached,
+
+    #[error("File is empty")]
+    Empty,
+
+    #[error("File exceeds the {} byte limit", MAX_ATTACHMENT_SIZE)]
+    TooLarge,
+
+    #[error("Filename must be 1-{} characters", MAX_FILENAME_LENGTH)]
+    InvalidFilename,
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+
+    /// No database connection was free in time; the request may succeed on retry
+    #[error("Service unavailable")]
+    Unavailable,
+}
+
+impl From<AppError> for AttachmentError {
+    fn from(e: AppError) -> Self {
+        if e.is_unavailable() {
+            AttachmentError::Unavailable
+        } else {
+            AttachmentError::Internal(e.to_string())
+        }
+    }
+}
+
+impl From<AttachmentEntity> for AttachmentDto {
+    fn from(entity: AttachmentEntity) -> Self {
+        Attachment {
+            id: entity.id,
+            message_id: entity.message_id,
+            uploader_id: entity.uploader_id,
+            filename: entity.filename,
+            content_type: entity.content_type,
+            size: entity.size,
+            url: entity.url,
+            proxy_url: entity.proxy_url,
+            width: entity.width,
+            height: entity.height,
+            created_at: entity.created_at,
+        }
+        .into()
+    }
+}
+
+/// Check an upload before it is stored, returning its size
+fn validate_upload(upload: &UploadAttachmentDto) -> Result<i32, AttachmentError> {
+    let filename_length = upload.filename.chars().count();
+    if filename_length == 0 || filename_length > MAX_FILENAME_LENGTH {
+        return Err(AttachmentError::InvalidFilename);
+    }
+    if upload.data.is_empty() {
+        return Err(AttachmentError::Empty);
+    }
+
+    i32::try_from(upload.data.len())
+        .ok()
+        .filter(|size| *size <= MAX_ATTACHMENT_SIZE)
+        .ok_or(AttachmentError::TooLarge)
+}
+
+/// Content type and dimensions to store for an upload.
+///
+/// Uploads declared as something other than an image are left alone.
+/// Anything else is sniffed: a recognised image takes its detected type and
+/// size, while an unreadable one keeps its declared type with no size.
+fn image_metadata(upload: &UploadAttachmentDto) -> (Option<String>, Option<i32>, Option<i32>) {
+    let declared = upload.content_type.clone();
+    if declared.as_deref().is_some_and(|ct| !ct.starts_with("image/")) {
+        return (declared, None, None);
+    }
+
+    match image_info(&upload.data) {
+        Some(info) => (
+            Some(info.content_type.to_string()),
+            i32::try_from(info.width).ok(),
+            i32::try_from(info.height).ok(),
+        ),
+        None => (declared, None, None),
+    }
+}
+
+/// AttachmentService implementation
+pub struct AttachmentServiceImpl<A>
+where
+    A: AttachmentRepository,
+{
+    attachment_repo: Arc<A>,
+    storage: Arc<dyn FileStorage>,
+    id_generator: Arc<SnowflakeGenerator>,
+}
+
+impl<A> AttachmentServiceImpl<A>
+where
+    A: AttachmentRepository,
+{
+    /// Create a new AttachmentServiceImpl
+    pub fn new(
+        attachment_repo: Arc<A>,
+        storage: Arc<dyn FileStorage>,
+        id_generator: Arc<SnowflakeGenerator>,
+    ) -> Self {
+        Self {
+            attachment_repo,
+            storage,
+            id_generator,
+        }
+    }
+}
+
+#[async_trait]
+impl<A> AttachmentService for AttachmentServiceImpl<A>
+where
+    A: AttachmentRepository + 'static,
+{
+    async fn upload(&self, uploader_id: i64, upload: UploadAttachmentDto) -> Result<AttachmentDto, AttachmentError> {
+        let size = validate_upload(&upload)?;
+
+        let (content_type, width, height) = image_metadata(&upload);
+
+        let id = self.id_generator.generate();
+        let key = attachment_key(id, &upload.filename);
+
+        self.storage
+            .put(&key, upload.data, content_type.as_deref())
+            .await
+            .map_err(AttachmentError::from)?;
+
+        let create = CreateAttachment {
+            id,
+            message_id: None,
+            uploader_id: Some(uploader_id),
+            filename: upload.filename,
+            content_type,
+            size,
+            url: self.storage.get_url(&key),
+            proxy_url: None,
+            width,
+            height,
+        };
+
+        match self.attachment_repo.create(&create).await {
+            Ok(attachment) => Ok(attachment.into()),
+            Err(e) => {
+                // Don't leave a file behind that nothing refers to
+                if let Err(delete_error) = self.storage.delete(&key).await {
+                    tracing::warn!(key = %key, error = %delete_error, "Failed to remove orphaned upload");
+                }
+                Err(AttachmentError::from(e))
+            }
+        }
+    }
+
+    async fn delete(&self, attachment_id: i64, actor_id: i64) -> Result<(), AttachmentError> {
+        let attachment = self
+            .attachment_repo
+            .find_by_id(attachment_id)
+            .await
+            .map_err(AttachmentError::from)?
+            .ok_or(AttachmentError::NotFound)?;
+
+        if attachment.uploader_id != Some(actor_id) {
+            return Err(AttachmentError::Forbidden);
+        }
+        if attachment.message_id.is_some() {
+            return Err(AttachmentError::AlreadyAttached);
+        }
+
+        self.attachment_repo
+            .delete(attachment_id)
+            .await
+            .map_err(AttachmentError::from)?;
+
+        // The row is gone, so a leftover file is unreachable; don't fail the request
+        let key = attachment_key(attachment.id, &attachment.filename);
+        if let Err(e) = self.storage.delete(&key).await {
+            tracing::warn!(key = %key, error = %e, "Failed to delete attachment file");
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Signature and IHDR of a 1x1 PNG
+    const PNG_HEADER: &[u8] = &[
+        0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F,
+        0x15, 0xC4, 0x89,
+    ];
+
+    fn upload(content_type: Option<&str>, data: &'static [u8]) -> UploadAttachmentDto {
+        UploadAttachmentDto {
+            filename: "file".to_string(),
+            content_type: content_type.map(str::to_string),
+            data: Bytes::from_static(data),
+        }
+    }
+
+    #[test]
+    fn test_image_uploads_get_dimensions() {
+        let expected = (Some("image/png".to_string()), Some(1), Some(1));
+
+        assert_eq!(
+            image_metadata(&upload(Some("image/png"), PNG_HEADER)),
+            expected
+        );
+        // Undeclared or mislabelled images take the detected type
+        assert_eq!(image_metadata(&upload(None, PNG_HEADER)), expected);
+        assert_eq!(
+            image_metadata(&upload(Some("image/jpeg"), PNG_HEADER)),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_corrupt_and_non_image_uploads_have_no_dimensions() {
+        assert_eq!(
+            image_metadata(&upload(Some("image/png"), &PNG_HEADER[..20])),
+            (Some("image/png".to_string()), None, None)
+        );
+        assert_eq!(
+            image_metadata(&upload(Some("text/plain"), PNG_HEADER)),
+            (Some("text/plain".to_string()), None, None)
+        );
+        assert_eq!(image_metadata(&upload(None, b"hello")), (None, None, None));
+    }
+}
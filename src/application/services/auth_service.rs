@@ -2,6 +2,7 @@
 //!
 //! Handles user authentication, JWT token management, and session handling.
 
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use argon2::{
@@ -10,13 +11,21 @@ use argon2::{
 };
 use async_trait::async_trait;
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{decode, decode_header, encode, DecodingKey, EncodingKey, Header, TokenData, Validation};
+use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+use uuid::Uuid;
+
 use crate::config::JwtSettings;
-use crate::domain::{Session, SessionRepository, User, UserRepository};
+use crate::domain::{os_from_user_agent, DeviceType, Session, SessionRepository, User, UserRepository};
+use crate::infrastructure::cache::{Cache, LoginThrottle, RedisCache, TokenBlacklist};
+use crate::shared::error::{AppError, UniqueField};
 use crate::shared::snowflake::SnowflakeGenerator;
+use crate::shared::validation::{check_password_strength, ValidationError};
 
 /// Authentication service trait for dependency injection
 #[async_trait]
@@ -27,10 +36,20 @@ pub trait AuthService: Send + Sync {
         username: &str,
         email: &str,
         password: &str,
+        client: &ClientInfo,
     ) -> Result<(User, AuthTokens), AuthError>;
 
-    /// Authenticate user with credentials
-    async fn authenticate(&self, email: &str, password: &str) -> Result<AuthTokens, AuthError>;
+    /// Authenticate user with credentials.
+    ///
+    /// With `remember_me` the session is persistent and gets the long
+    /// refresh token lifetime; otherwise it is short-lived.
+    async fn authenticate(
+        &self,
+        email: &str,
+        password: &str,
+        remember_me: bool,
+        client: &ClientInfo,
+    ) -> Result<AuthTokens, AuthError>;
 
     /// Refresh access token using refresh token
     async fn refresh_token(&self, refresh_token: &str) -> Result<AuthTokens, AuthError>;
@@ -43,6 +62,46 @@ pub trait AuthService: Send + Sync {
 
     /// Get current user from access token
     async fn get_current_user(&self, access_token: &str) -> Result<User, AuthError>;
+
+    /// List a user's active sessions, flagging the one making the request
+    async fn list_sessions(
+        &self,
+        user_id: i64,
+        current_session_id: Option<Uuid>,
+    ) -> Result<Vec<SessionDto>, AuthError>;
+
+    /// Revoke one of the user's sessions and invalidate its tokens
+    async fn revoke_session(&self, user_id: i64, session_id: Uuid) -> Result<(), AuthError>;
+
+    /// Revoke all of the user's sessions except the current one ("log out everywhere")
+    ///
+    /// Returns the IDs of the revoked sessions.
+    async fn revoke_other_sessions(
+        &self,
+        user_id: i64,
+        current_session_id: Option<Uuid>,
+    ) -> Result<Vec<Uuid>, AuthError>;
+
+    /// Change the user's password after verifying the current one.
+    ///
+    /// Revokes every other session and rotates the current session's
+    /// refresh token, returning fresh tokens for it.
+    async fn change_password(
+        &self,
+        user_id: i64,
+        current_session_id: Option<Uuid>,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<PasswordChangeDto, AuthError>;
+}
+
+/// Result of a password change
+#[derive(Debug, Clone)]
+pub struct PasswordChangeDto {
+    /// Fresh tokens for the session that changed the password
+    pub tokens: AuthTokens,
+    /// IDs of the sessions revoked by the change
+    pub revoked_sessions: Vec<Uuid>,
 }
 
 /// Authentication tokens response
@@ -52,6 +111,82 @@ pub struct AuthTokens {
     pub refresh_token: String,
     pub expires_in: i64,
     pub token_type: String,
+    /// Whether the client may keep the refresh token across restarts
+    pub persistent: bool,
+}
+
+/// Maximum stored user agent length
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// Client details recorded on sessions created at login and registration
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    /// `User-Agent` header
+    pub user_agent: Option<String>,
+    /// Explicit device type (`X-Device-Type` header), overriding the user agent
+    pub device_type: Option<String>,
+    pub ip_address: Option<IpAddr>,
+}
+
+impl ClientInfo {
+    /// Device type from the explicit hint if recognized, otherwise
+    /// classified from the user agent
+    pub fn device_type(&self) -> DeviceType {
+        self.device_type
+            .as_deref()
+            .map(DeviceType::from_str)
+            .filter(|d| *d != DeviceType::Unknown)
+            .or_else(|| self.user_agent.as_deref().map(DeviceType::from_user_agent))
+            .unwrap_or_default()
+    }
+
+    /// Record the client on a new session
+    pub fn apply_to(&self, session: &mut Session) {
+        session.device_type = Some(self.device_type());
+        session.device_info = self
+            .user_agent
+            .as_deref()
+            .map(|ua| ua.chars().take(MAX_USER_AGENT_LENGTH).collect());
+        session.os_info = self
+            .user_agent
+            .as_deref()
+            .and_then(os_from_user_agent)
+            .map(str::to_string);
+        session.ip_address = self.ip_address;
+    }
+}
+
+/// Active session as shown to its owner
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionDto {
+    pub id: String,
+    pub device_type: String,
+    pub device_info: Option<String>,
+    pub os_info: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: String,
+    pub last_used_at: String,
+    /// Whether the login asked to be remembered
+    pub persistent: bool,
+    /// Whether this is the session making the request
+    pub current: bool,
+}
+
+impl SessionDto {
+    /// Build a DTO, flagging the session matching `current_session_id`
+    pub fn from_session(session: Session, current_session_id: Option<Uuid>) -> Self {
+        Self {
+            id: session.id.to_string(),
+            device_type: session.device_type.unwrap_or_default().to_string(),
+            device_info: session.device_info,
+            os_info: session.os_info,
+            ip_address: session.ip_address.map(|ip| ip.to_string()),
+            created_at: session.created_at.to_rfc3339(),
+            last_used_at: session.last_used_at.to_rfc3339(),
+            persistent: session.persistent,
+            current: current_session_id == Some(session.id),
+        }
+    }
 }
 
 /// JWT claims structure
@@ -66,6 +201,33 @@ pub struct Claims {
     /// JWT ID for token revocation tracking
     #[serde(skip_serializing_if = "Option::is_none")]
     pub jti: Option<String>,
+    /// Auth session ID the token was issued for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+}
+
+/// Sign claims with the active JWT key, naming it in the header's `kid`.
+pub fn encode_token<T: Serialize>(settings: &JwtSettings, claims: &T) -> jsonwebtoken::errors::Result<String> {
+    let (kid, secret) = settings.signing_key();
+    let header = Header {
+        kid: kid.map(str::to_owned),
+        ..Header::default()
+    };
+
+    encode(&header, claims, &EncodingKey::from_secret(secret.as_bytes()))
+}
+
+/// Verify a token with the configured key named by its `kid`.
+///
+/// Tokens signed by a key that has since been removed are rejected as
+/// having an invalid signature.
+pub fn decode_token<T: DeserializeOwned>(settings: &JwtSettings, token: &str) -> jsonwebtoken::errors::Result<TokenData<T>> {
+    let header = decode_header(token)?;
+    let secret = settings
+        .verification_secret(header.kid.as_deref())
+        .ok_or(ErrorKind::InvalidSignature)?;
+
+    decode(token, &DecodingKey::from_secret(secret.as_bytes()), &Validation::default())
 }
 
 /// Authentication errors
@@ -92,20 +254,65 @@ pub enum AuthError {
     #[error("Session not found or expired")]
     SessionNotFound,
 
+    #[error("{}", .0.first().map(|e| e.message.as_str()).unwrap_or("Password is too weak"))]
+    WeakPassword(Vec<ValidationError>),
+
+    #[error("New password must differ from the current password")]
+    PasswordUnchanged,
+
+    #[error("Too many failed logins; retry after {retry_after} seconds")]
+    AccountLocked { retry_after: u64 },
+
     #[error("Internal error: {0}")]
     Internal(String),
+
+    /// No database connection was free in time; the request may succeed on retry
+    #[error("Service unavailable")]
+    Unavailable,
+}
+
+impl From<AppError> for AuthError {
+    fn from(e: AppError) -> Self {
+        if e.is_unavailable() {
+            AuthError::Unavailable
+        } else {
+            AuthError::Internal(e.to_string())
+        }
+    }
+}
+
+/// Error for a failed insert of a new account. Conflicts on the email or
+/// username become the matching taken error.
+fn registration_error(error: AppError) -> AuthError {
+    match error {
+        AppError::AlreadyExists(UniqueField::Email) => AuthError::EmailExists,
+        AppError::AlreadyExists(UniqueField::Username) => AuthError::UsernameExists,
+        e => AuthError::from(e),
+    }
 }
 
+/// Hash checked when a login names an unknown email, so the response takes
+/// as long as a wrong password for an existing account
+static DUMMY_PASSWORD_HASH: Lazy<String> = Lazy::new(|| {
+    Argon2::default()
+        .hash_password(b"dummy-password", &SaltString::generate(&mut OsRng))
+        .expect("hashing a constant password cannot fail")
+        .to_string()
+});
+
 /// AuthService implementation
-pub struct AuthServiceImpl<U, S>
+pub struct AuthServiceImpl<U, S, C = RedisCache>
 where
     U: UserRepository,
     S: SessionRepository,
+    C: Cache,
 {
     user_repo: Arc<U>,
     session_repo: Arc<S>,
     id_generator: Arc<SnowflakeGenerator>,
     jwt_settings: JwtSettings,
+    token_blacklist: Option<TokenBlacklist>,
+    login_throttle: Option<LoginThrottle<C>>,
 }
 
 impl<U, S> AuthServiceImpl<U, S>
@@ -125,6 +332,68 @@ where
             session_repo,
             id_generator,
             jwt_settings,
+            token_blacklist: None,
+            login_throttle: None,
+        }
+    }
+}
+
+impl<U, S, C> AuthServiceImpl<U, S, C>
+where
+    U: UserRepository,
+    S: SessionRepository,
+    C: Cache,
+{
+    /// Attach a token blacklist so revoked sessions' access tokens stop working
+    pub fn with_token_blacklist(mut self, token_blacklist: TokenBlacklist) -> Self {
+        self.token_blacklist = Some(token_blacklist);
+        self
+    }
+
+    /// Attach a login throttle that locks accounts out after repeated failed logins
+    pub fn with_login_throttle<T: Cache>(self, login_throttle: LoginThrottle<T>) -> AuthServiceImpl<U, S, T> {
+        AuthServiceImpl {
+            user_repo: self.user_repo,
+            session_repo: self.session_repo,
+            id_generator: self.id_generator,
+            jwt_settings: self.jwt_settings,
+            token_blacklist: self.token_blacklist,
+            login_throttle: Some(login_throttle),
+        }
+    }
+
+    /// Reject the login if the account is locked out.
+    ///
+    /// A throttle failure lets the attempt through rather than locking
+    /// everyone out while Redis is unavailable.
+    async fn check_login_throttle(&self, email: &str) -> Result<(), AuthError> {
+        let Some(throttle) = &self.login_throttle else {
+            return Ok(());
+        };
+
+        match throttle.retry_after(email).await {
+            Ok(Some(retry_after)) => Err(AuthError::AccountLocked { retry_after }),
+            Ok(None) => Ok(()),
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to check login throttle");
+                Ok(())
+            }
+        }
+    }
+
+    /// Count a failed login, locking the account out once there are too many
+    async fn record_login_failure(&self, email: &str) -> AuthError {
+        let Some(throttle) = &self.login_throttle else {
+            return AuthError::InvalidCredentials;
+        };
+
+        match throttle.record_failure(email).await {
+            Ok(Some(retry_after)) => AuthError::AccountLocked { retry_after },
+            Ok(None) => AuthError::InvalidCredentials,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to record failed login");
+                AuthError::InvalidCredentials
+            }
         }
     }
 
@@ -141,16 +410,11 @@ where
 
     /// Verify a password against its hash
     fn verify_password(&self, password: &str, hash: &str) -> Result<bool, AuthError> {
-        let parsed_hash = PasswordHash::new(hash)
-            .map_err(|e| AuthError::Internal(format!("Invalid password hash: {}", e)))?;
-
-        Ok(Argon2::default()
-            .verify_password(password.as_bytes(), &parsed_hash)
-            .is_ok())
+        verify_password_hash(password, hash).map_err(AuthError::Internal)
     }
 
-    /// Generate access and refresh tokens
-    fn generate_tokens(&self, user_id: i64) -> Result<AuthTokens, AuthError> {
+    /// Generate access and refresh tokens for an auth session
+    fn generate_tokens(&self, user_id: i64, session_id: Uuid, persistent: bool) -> Result<AuthTokens, AuthError> {
         let now = Utc::now();
         let access_expiry = now + Duration::minutes(self.jwt_settings.access_token_expiry_minutes);
 
@@ -163,14 +427,11 @@ where
             exp: access_expiry.timestamp(),
             iat: now.timestamp(),
             jti: Some(jti),
+            sid: Some(session_id.to_string()),
         };
 
-        let access_token = encode(
-            &Header::default(),
-            &access_claims,
-            &EncodingKey::from_secret(self.jwt_settings.secret.as_bytes()),
-        )
-        .map_err(|e| AuthError::Internal(format!("Token generation failed: {}", e)))?;
+        let access_token = encode_token(&self.jwt_settings, &access_claims)
+            .map_err(|e| AuthError::Internal(format!("Token generation failed: {}", e)))?;
 
         // Generate opaque refresh token (no sensitive data exposed)
         // Format: random_uuid.random_uuid (completely opaque, no user info)
@@ -185,9 +446,28 @@ where
             refresh_token,
             expires_in: self.jwt_settings.access_token_expiry_minutes * 60,
             token_type: "Bearer".to_string(),
+            persistent,
         })
     }
 
+    /// Build the session row for a new login, recording the client
+    fn new_session(
+        &self,
+        user_id: i64,
+        session_id: Uuid,
+        tokens: &AuthTokens,
+        client: &ClientInfo,
+    ) -> Session {
+        let expires_at = Utc::now() + self.jwt_settings.refresh_token_ttl(tokens.persistent);
+        let mut session = Session {
+            id: session_id,
+            persistent: tokens.persistent,
+            ..Session::new(user_id, self.hash_refresh_token(&tokens.refresh_token), expires_at)
+        };
+        client.apply_to(&mut session);
+        session
+    }
+
     /// Hash refresh token for storage
     fn hash_refresh_token(&self, token: &str) -> String {
         let mut hasher = Sha256::new();
@@ -197,52 +477,106 @@ where
 
     /// Decode and validate access token
     fn decode_access_token(&self, token: &str) -> Result<Claims, AuthError> {
-        let token_data = decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(self.jwt_settings.secret.as_bytes()),
-            &Validation::default(),
-        )
-        .map_err(|e| match e.kind() {
-            jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+        let token_data = decode_token::<Claims>(&self.jwt_settings, token).map_err(|e| match e.kind() {
+            ErrorKind::ExpiredSignature => AuthError::TokenExpired,
             _ => AuthError::InvalidToken,
         })?;
 
         Ok(token_data.claims)
     }
+
+    /// Delete a session and blacklist its outstanding access tokens
+    async fn delete_session(&self, session_id: Uuid) -> Result<(), AuthError> {
+        self.session_repo
+            .delete(session_id)
+            .await
+            .map_err(AuthError::from)?;
+
+        self.blacklist_session(session_id).await;
+        Ok(())
+    }
+
+    /// Stop a deleted session's access tokens from working.
+    async fn blacklist_session(&self, session_id: Uuid) {
+        if let Some(blacklist) = &self.token_blacklist {
+            // The session row is gone, so refresh is already impossible; a
+            // blacklist failure only leaves the access token alive until expiry.
+            if let Err(e) = blacklist.revoke_session(&session_id.to_string()).await {
+                tracing::warn!(session_id = %session_id, error = %e, "Failed to blacklist session");
+            }
+        }
+    }
+}
+
+/// Verify a password against an Argon2 hash.
+pub fn verify_password_hash(password: &str, hash: &str) -> Result<bool, String> {
+    let parsed_hash =
+        PasswordHash::new(hash).map_err(|e| format!("Invalid password hash: {}", e))?;
+
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &parsed_hash)
+        .is_ok())
+}
+
+/// Check a password change request against the stored hash.
+///
+/// The current password must match `hash`, and the new password must be
+/// strong enough and different from the current one.
+fn check_password_change(
+    current_password: &str,
+    new_password: &str,
+    hash: &str,
+) -> Result<(), AuthError> {
+    if !verify_password_hash(current_password, hash).map_err(AuthError::Internal)? {
+        return Err(AuthError::InvalidCredentials);
+    }
+
+    if new_password == current_password {
+        return Err(AuthError::PasswordUnchanged);
+    }
+
+    let errors = check_password_strength("new_password", new_password);
+    if !errors.is_empty() {
+        return Err(AuthError::WeakPassword(errors));
+    }
+
+    Ok(())
+}
+
+/// Sessions to revoke when keeping only the current one
+fn other_session_ids(sessions: &[Session], current_session_id: Option<Uuid>) -> Vec<Uuid> {
+    sessions
+        .iter()
+        .map(|s| s.id)
+        .filter(|id| Some(*id) != current_session_id)
+        .collect()
+}
+
+/// Ensure a session belongs to the user acting on it.
+///
+/// Sessions owned by someone else are reported as not found so their
+/// existence is not revealed.
+fn ensure_session_owner(session: &Session, user_id: i64) -> Result<(), AuthError> {
+    if session.user_id != user_id {
+        return Err(AuthError::SessionNotFound);
+    }
+    Ok(())
 }
 
 #[async_trait]
-impl<U, S> AuthService for AuthServiceImpl<U, S>
+impl<U, S, C> AuthService for AuthServiceImpl<U, S, C>
 where
     U: UserRepository + 'static,
     S: SessionRepository + 'static,
+    C: Cache + 'static,
 {
     async fn register(
         &self,
         username: &str,
         email: &str,
         password: &str,
+        client: &ClientInfo,
     ) -> Result<(User, AuthTokens), AuthError> {
-        // Check if email already exists
-        if self
-            .user_repo
-            .email_exists(email)
-            .await
-            .map_err(|e| AuthError::Internal(e.to_string()))?
-        {
-            return Err(AuthError::EmailExists);
-        }
-
-        // Check if username already exists
-        if self
-            .user_repo
-            .username_exists(username)
-            .await
-            .map_err(|e| AuthError::Internal(e.to_string()))?
-        {
-            return Err(AuthError::UsernameExists);
-        }
-
         // Hash password
         let password_hash = self.hash_password(password)?;
 
@@ -258,66 +592,79 @@ where
             password_hash,
             display_name: None,
             avatar_url: None,
+            banner_url: None,
             status: crate::domain::UserStatus::Online,
             bio: None,
+            presence_visibility: crate::domain::PresenceVisibility::default(),
+            nsfw_allowed: false,
             created_at: now,
             updated_at: now,
         };
 
-        let created_user = self
-            .user_repo
-            .create(&user)
-            .await
-            .map_err(|e| AuthError::Internal(e.to_string()))?;
+        // The users table's unique constraints catch taken emails and
+        // usernames, including concurrent registrations of the same one
+        let created_user = self.user_repo.create(&user).await.map_err(registration_error)?;
 
         // Generate tokens
-        let tokens = self.generate_tokens(created_user.id)?;
+        let session_id = Uuid::new_v4();
+        let tokens = self.generate_tokens(created_user.id, session_id, true)?;
 
         // Create session for refresh token
-        let token_hash = self.hash_refresh_token(&tokens.refresh_token);
-        let session = Session::new(
-            created_user.id,
-            token_hash,
-            Utc::now() + Duration::days(self.jwt_settings.refresh_token_expiry_days),
-        );
+        let session = self.new_session(created_user.id, session_id, &tokens, client);
 
         self.session_repo
             .create(&session)
             .await
-            .map_err(|e| AuthError::Internal(e.to_string()))?;
+            .map_err(AuthError::from)?;
 
         Ok((created_user, tokens))
     }
 
-    async fn authenticate(&self, email: &str, password: &str) -> Result<AuthTokens, AuthError> {
+    async fn authenticate(
+        &self,
+        email: &str,
+        password: &str,
+        remember_me: bool,
+        client: &ClientInfo,
+    ) -> Result<AuthTokens, AuthError> {
+        self.check_login_throttle(email).await?;
+
         // Find user by email
         let user = self
             .user_repo
             .find_by_email(email)
             .await
-            .map_err(|e| AuthError::Internal(e.to_string()))?
-            .ok_or(AuthError::InvalidCredentials)?;
+            .map_err(AuthError::from)?;
+
+        // Verify password; unknown emails are checked against a dummy hash so
+        // they take as long to reject as a wrong password
+        let hash = user
+            .as_ref()
+            .map_or(DUMMY_PASSWORD_HASH.as_str(), |u| u.password_hash.as_str());
+        let verified = self.verify_password(password, hash)?;
+
+        let user = match user {
+            Some(user) if verified => user,
+            _ => return Err(self.record_login_failure(email).await),
+        };
 
-        // Verify password
-        if !self.verify_password(password, &user.password_hash)? {
-            return Err(AuthError::InvalidCredentials);
+        if let Some(throttle) = &self.login_throttle {
+            if let Err(e) = throttle.reset(email).await {
+                tracing::warn!(user_id = user.id, error = %e, "Failed to reset login throttle");
+            }
         }
 
         // Generate tokens
-        let tokens = self.generate_tokens(user.id)?;
+        let session_id = Uuid::new_v4();
+        let tokens = self.generate_tokens(user.id, session_id, remember_me)?;
 
         // Create session
-        let token_hash = self.hash_refresh_token(&tokens.refresh_token);
-        let session = Session::new(
-            user.id,
-            token_hash,
-            Utc::now() + Duration::days(self.jwt_settings.refresh_token_expiry_days),
-        );
+        let session = self.new_session(user.id, session_id, &tokens, client);
 
         self.session_repo
             .create(&session)
             .await
-            .map_err(|e| AuthError::Internal(e.to_string()))?;
+            .map_err(AuthError::from)?;
 
         Ok(tokens)
     }
@@ -330,7 +677,7 @@ where
             .session_repo
             .find_by_token_hash(&token_hash)
             .await
-            .map_err(|e| AuthError::Internal(e.to_string()))?
+            .map_err(AuthError::from)?
             .ok_or(AuthError::SessionNotFound)?;
 
         // Check if session is still valid
@@ -339,15 +686,16 @@ where
         }
 
         // Generate new tokens (TOKEN ROTATION for security)
-        let new_tokens = self.generate_tokens(session.user_id)?;
+        // Rotated tokens keep the session's lifetime class
+        let new_tokens = self.generate_tokens(session.user_id, session.id, session.persistent)?;
         let new_token_hash = self.hash_refresh_token(&new_tokens.refresh_token);
-        let new_expires_at = Utc::now() + Duration::days(self.jwt_settings.refresh_token_expiry_days);
+        let new_expires_at = Utc::now() + self.jwt_settings.refresh_token_ttl(session.persistent);
 
         // Update session with new refresh token hash (token rotation)
         self.session_repo
             .update_token_hash(session.id, &new_token_hash, new_expires_at)
             .await
-            .map_err(|e| AuthError::Internal(e.to_string()))?;
+            .map_err(AuthError::from)?;
 
         Ok(new_tokens)
     }
@@ -360,13 +708,13 @@ where
             .session_repo
             .find_by_token_hash(&token_hash)
             .await
-            .map_err(|e| AuthError::Internal(e.to_string()))?
+            .map_err(AuthError::from)?
             .ok_or(AuthError::SessionNotFound)?;
 
         self.session_repo
             .revoke(session.id)
             .await
-            .map_err(|e| AuthError::Internal(e.to_string()))?;
+            .map_err(AuthError::from)?;
 
         Ok(())
     }
@@ -386,17 +734,543 @@ where
         self.user_repo
             .find_by_id(user_id)
             .await
-            .map_err(|e| AuthError::Internal(e.to_string()))?
+            .map_err(AuthError::from)?
             .ok_or(AuthError::UserNotFound)
     }
+
+    async fn list_sessions(
+        &self,
+        user_id: i64,
+        current_session_id: Option<Uuid>,
+    ) -> Result<Vec<SessionDto>, AuthError> {
+        let sessions = self
+            .session_repo
+            .find_by_user_id(user_id)
+            .await
+            .map_err(AuthError::from)?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|s| SessionDto::from_session(s, current_session_id))
+            .collect())
+    }
+
+    async fn revoke_session(&self, user_id: i64, session_id: Uuid) -> Result<(), AuthError> {
+        let session = self
+            .session_repo
+            .find_by_id(session_id)
+            .await
+            .map_err(AuthError::from)?
+            .ok_or(AuthError::SessionNotFound)?;
+
+        ensure_session_owner(&session, user_id)?;
+
+        self.delete_session(session.id).await
+    }
+
+    async fn revoke_other_sessions(
+        &self,
+        user_id: i64,
+        current_session_id: Option<Uuid>,
+    ) -> Result<Vec<Uuid>, AuthError> {
+        let sessions = self
+            .session_repo
+            .find_by_user_id(user_id)
+            .await
+            .map_err(AuthError::from)?;
+
+        let revoked = other_session_ids(&sessions, current_session_id);
+        for session_id in &revoked {
+            self.delete_session(*session_id).await?;
+        }
+
+        Ok(revoked)
+    }
+
+    async fn change_password(
+        &self,
+        user_id: i64,
+        current_session_id: Option<Uuid>,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<PasswordChangeDto, AuthError> {
+        let user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await
+            .map_err(AuthError::from)?
+            .ok_or(AuthError::UserNotFound)?;
+
+        check_password_change(current_password, new_password, &user.password_hash)?;
+
+        // The caller's session is kept and re-issued tokens, so it must exist
+        // before anything is changed
+        let session_id = current_session_id.ok_or(AuthError::SessionNotFound)?;
+        let session = self
+            .session_repo
+            .find_by_id(session_id)
+            .await
+            .map_err(AuthError::from)?
+            .ok_or(AuthError::SessionNotFound)?;
+        ensure_session_owner(&session, user_id)?;
+
+        let password_hash = self.hash_password(new_password)?;
+        let tokens = self.generate_tokens(user_id, session.id, session.persistent)?;
+        let token_hash = self.hash_refresh_token(&tokens.refresh_token);
+        let expires_at = Utc::now() + self.jwt_settings.refresh_token_ttl(session.persistent);
+
+        // The new password, the other sessions' removal and the current
+        // session's rotated refresh token are committed together
+        let revoked_sessions = self
+            .user_repo
+            .change_password(user_id, &password_hash, session.id, &token_hash, expires_at)
+            .await
+            .map_err(|e| match e {
+                AppError::NotFound(_) => AuthError::SessionNotFound,
+                e => AuthError::from(e),
+            })?;
+
+        for session_id in &revoked_sessions {
+            self.blacklist_session(*session_id).await;
+        }
+
+        Ok(PasswordChangeDto {
+            tokens,
+            revoked_sessions,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::{OwnedGuildAction, UserStatus};
+    use crate::infrastructure::cache::{InMemoryCache, LOCKOUT_THRESHOLD};
+    use chrono::DateTime;
 
     #[test]
     fn test_password_hashing() {
         // Create a minimal test - actual integration tests would need mocks
     }
+
+    #[test]
+    fn test_session_listing_flags_current_session() {
+        let current = Session::new(1, "a".into(), Utc::now() + Duration::days(1));
+        let mut other = Session::new(1, "b".into(), Utc::now() + Duration::days(1));
+        other.device_type = Some(crate::domain::DeviceType::Mobile);
+        other.ip_address = Some("10.0.0.1".parse().unwrap());
+        let current_id = current.id;
+
+        let dtos: Vec<SessionDto> = vec![current, other]
+            .into_iter()
+            .map(|s| SessionDto::from_session(s, Some(current_id)))
+            .collect();
+
+        assert!(dtos[0].current);
+        assert_eq!(dtos[0].device_type, "unknown");
+        assert!(!dtos[1].current);
+        assert_eq!(dtos[1].device_type, "mobile");
+        assert_eq!(dtos[1].ip_address.as_deref(), Some("10.0.0.1"));
+    }
+
+    #[test]
+    fn test_revoking_non_owned_session_is_rejected() {
+        let session = Session::new(1, "hash".into(), Utc::now() + Duration::days(1));
+
+        assert!(ensure_session_owner(&session, 1).is_ok());
+        assert!(matches!(
+            ensure_session_owner(&session, 2),
+            Err(AuthError::SessionNotFound)
+        ));
+    }
+
+    fn password_hash(password: &str) -> String {
+        Argon2::default()
+            .hash_password(password.as_bytes(), &SaltString::generate(&mut OsRng))
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_password_change_requires_current_password() {
+        let hash = password_hash("OldPassw0rd!");
+
+        assert!(matches!(
+            check_password_change("WrongPassw0rd!", "NewPassw0rd!", &hash),
+            Err(AuthError::InvalidCredentials)
+        ));
+        assert!(check_password_change("OldPassw0rd!", "NewPassw0rd!", &hash).is_ok());
+    }
+
+    #[test]
+    fn test_password_change_rejects_same_or_weak_password() {
+        let hash = password_hash("OldPassw0rd!");
+
+        assert!(matches!(
+            check_password_change("OldPassw0rd!", "OldPassw0rd!", &hash),
+            Err(AuthError::PasswordUnchanged)
+        ));
+        assert!(matches!(
+            check_password_change("OldPassw0rd!", "weak", &hash),
+            Err(AuthError::WeakPassword(_))
+        ));
+    }
+
+    #[test]
+    fn test_only_other_sessions_are_revoked() {
+        let sessions: Vec<Session> = (0..3)
+            .map(|i| Session::new(1, format!("hash{}", i), Utc::now() + Duration::days(1)))
+            .collect();
+        let current = sessions[1].id;
+
+        let revoked = other_session_ids(&sessions, Some(current));
+        assert_eq!(revoked, vec![sessions[0].id, sessions[2].id]);
+
+        // Without a current session everything goes
+        assert_eq!(other_session_ids(&sessions, None).len(), 3);
+    }
+
+    #[test]
+    fn test_client_info_is_recorded_on_session() {
+        let client = ClientInfo {
+            user_agent: Some("ChatApp/2.3.1 (iOS 17.1; iPhone15,2) CFNetwork/1474".to_string()),
+            device_type: None,
+            ip_address: Some("203.0.113.7".parse().unwrap()),
+        };
+        let mut session = Session::new(1, "hash".into(), Utc::now() + Duration::days(1));
+
+        client.apply_to(&mut session);
+
+        assert_eq!(session.device_type, Some(DeviceType::Mobile));
+        assert_eq!(session.os_info.as_deref(), Some("iOS"));
+        assert_eq!(session.ip_address, client.ip_address);
+        assert_eq!(session.device_info, client.user_agent);
+    }
+
+    #[test]
+    fn test_explicit_device_type_overrides_user_agent() {
+        let client = ClientInfo {
+            user_agent: Some("Mozilla/5.0 (Windows NT 10.0; Win64; x64) Chrome/120.0".to_string()),
+            device_type: Some("desktop".to_string()),
+            ip_address: None,
+        };
+        assert_eq!(client.device_type(), DeviceType::Desktop);
+
+        // Unrecognized hints fall back to the user agent
+        let client = ClientInfo {
+            device_type: Some("toaster".to_string()),
+            ..client
+        };
+        assert_eq!(client.device_type(), DeviceType::Browser);
+
+        assert_eq!(ClientInfo::default().device_type(), DeviceType::Unknown);
+    }
+
+    /// Repository holding a single user; supports email lookups and
+    /// creating other users
+    struct SingleUserRepository {
+        user: User,
+    }
+
+    #[async_trait]
+    impl UserRepository for SingleUserRepository {
+        async fn find_by_id(&self, _id: i64) -> Result<Option<User>, AppError> { unimplemented!() }
+        async fn find_by_ids(&self, _ids: &[i64]) -> Result<Vec<User>, AppError> { unimplemented!() }
+        async fn find_mutual_friends(&self, _user_id: i64, _other_user_id: i64) -> Result<Vec<User>, AppError> { unimplemented!() }
+        async fn find_by_email(&self, email: &str) -> Result<Option<User>, AppError> {
+            Ok(Some(self.user.clone()).filter(|u| u.email == email))
+        }
+        async fn find_by_username(&self, _username: &str) -> Result<Option<User>, AppError> { unimplemented!() }
+        async fn create(&self, user: &User) -> Result<User, AppError> {
+            // As the users table's unique constraints would
+            if user.email == self.user.email {
+                return Err(AppError::AlreadyExists(UniqueField::Email));
+            }
+            if user.username == self.user.username {
+                return Err(AppError::AlreadyExists(UniqueField::Username));
+            }
+            Ok(user.clone())
+        }
+        async fn update(&self, _user: &User) -> Result<User, AppError> { unimplemented!() }
+        async fn delete(&self, _id: i64) -> Result<(), AppError> { unimplemented!() }
+        async fn email_exists(&self, _email: &str) -> Result<bool, AppError> { unimplemented!() }
+        async fn username_exists(&self, _username: &str) -> Result<bool, AppError> { unimplemented!() }
+        async fn update_status(&self, _id: i64, _status: UserStatus) -> Result<(), AppError> { unimplemented!() }
+        async fn change_password(&self, _id: i64, _password_hash: &str, _session_id: Uuid, _refresh_token_hash: &str, _refresh_expires_at: DateTime<Utc>) -> Result<Vec<Uuid>, AppError> { unimplemented!() }
+        async fn delete_account(&self, _user: &User, _owned_guilds: &[OwnedGuildAction]) -> Result<Vec<Uuid>, AppError> { unimplemented!() }
+    }
+
+    /// Repository that accepts new sessions; nothing else is supported
+    struct AcceptingSessionRepository;
+
+    #[async_trait]
+    impl SessionRepository for AcceptingSessionRepository {
+        async fn find_by_id(&self, _: Uuid) -> Result<Option<Session>, AppError> { unimplemented!() }
+        async fn find_by_token_hash(&self, _: &str) -> Result<Option<Session>, AppError> { unimplemented!() }
+        async fn find_by_user_id(&self, _: i64) -> Result<Vec<Session>, AppError> { unimplemented!() }
+        async fn create(&self, session: &Session) -> Result<Session, AppError> {
+            Ok(session.clone())
+        }
+        async fn touch(&self, _: Uuid) -> Result<(), AppError> { unimplemented!() }
+        async fn update_token_hash(&self, _: Uuid, _: &str, _: DateTime<Utc>) -> Result<(), AppError> { unimplemented!() }
+        async fn revoke(&self, _: Uuid) -> Result<(), AppError> { unimplemented!() }
+        async fn revoke_all_for_user(&self, _: i64, _: Option<Uuid>) -> Result<i64, AppError> { unimplemented!() }
+        async fn delete(&self, _: Uuid) -> Result<(), AppError> { unimplemented!() }
+        async fn cleanup_expired(&self) -> Result<i64, AppError> { unimplemented!() }
+        async fn count_active(&self, _: i64) -> Result<i64, AppError> { unimplemented!() }
+        async fn find_by_ip(&self, _: IpAddr) -> Result<Vec<Session>, AppError> { unimplemented!() }
+    }
+
+    const EMAIL: &str = "alice@example.com";
+    const PASSWORD: &str = "Passw0rd!";
+
+    fn jwt_settings() -> JwtSettings {
+        JwtSettings {
+            secret: "test-secret".to_string(),
+            keys: Default::default(),
+            active_key_id: None,
+            access_token_expiry_minutes: 15,
+            refresh_token_expiry_days: 30,
+            session_refresh_token_expiry_hours: 12,
+        }
+    }
+
+    fn throttled_service(
+    ) -> AuthServiceImpl<SingleUserRepository, AcceptingSessionRepository, InMemoryCache> {
+        let user = User {
+            id: 1,
+            username: "alice".to_string(),
+            email: EMAIL.to_string(),
+            password_hash: password_hash(PASSWORD),
+            ..Default::default()
+        };
+
+        AuthServiceImpl::new(
+            Arc::new(SingleUserRepository { user }),
+            Arc::new(AcceptingSessionRepository),
+            Arc::new(SnowflakeGenerator::new(1, 1)),
+            jwt_settings(),
+        )
+        .with_login_throttle(LoginThrottle::with_cache(InMemoryCache::new()))
+    }
+
+    async fn login(service: &impl AuthService, email: &str, password: &str) -> Result<AuthTokens, AuthError> {
+        service.authenticate(email, password, false, &ClientInfo::default()).await
+    }
+
+    #[tokio::test]
+    async fn test_repeated_failed_logins_lock_the_account() {
+        let service = throttled_service();
+
+        for _ in 1..LOCKOUT_THRESHOLD {
+            assert!(matches!(
+                login(&service, EMAIL, "Wrong").await,
+                Err(AuthError::InvalidCredentials)
+            ));
+        }
+        assert!(matches!(
+            login(&service, EMAIL, "Wrong").await,
+            Err(AuthError::AccountLocked { retry_after: 30 })
+        ));
+
+        // Even the right password is refused during the cooldown
+        assert!(matches!(
+            login(&service, EMAIL, PASSWORD).await,
+            Err(AuthError::AccountLocked { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_email_is_throttled_like_a_real_account() {
+        let service = throttled_service();
+
+        for _ in 1..LOCKOUT_THRESHOLD {
+            assert!(matches!(
+                login(&service, "nobody@example.com", "Wrong").await,
+                Err(AuthError::InvalidCredentials)
+            ));
+        }
+        assert!(matches!(
+            login(&service, "nobody@example.com", "Wrong").await,
+            Err(AuthError::AccountLocked { retry_after: 30 })
+        ));
+    }
+
+    #[test]
+    fn test_unique_violations_map_to_taken_errors() {
+        let error = |field| registration_error(AppError::AlreadyExists(field));
+
+        assert!(matches!(error(UniqueField::Email), AuthError::EmailExists));
+        assert!(matches!(error(UniqueField::Username), AuthError::UsernameExists));
+        // A conflict that names neither field isn't reported as one of them
+        assert!(matches!(
+            registration_error(AppError::Conflict("Server with this ID already exists".into())),
+            AuthError::Internal(_)
+        ));
+        assert!(matches!(
+            registration_error(AppError::Internal("db down".into())),
+            AuthError::Internal(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_registering_a_taken_email_or_username_is_rejected() {
+        let service = throttled_service();
+        let register = |username: &'static str, email: &'static str| {
+            let service = &service;
+            async move { service.register(username, email, PASSWORD, &ClientInfo::default()).await }
+        };
+
+        assert!(matches!(register("bob", EMAIL).await, Err(AuthError::EmailExists)));
+        assert!(matches!(
+            register("alice", "bob@example.com").await,
+            Err(AuthError::UsernameExists)
+        ));
+    }
+
+    fn tokens(persistent: bool) -> AuthTokens {
+        AuthTokens {
+            access_token: "access".to_string(),
+            refresh_token: "refresh".to_string(),
+            expires_in: 900,
+            token_type: "Bearer".to_string(),
+            persistent,
+        }
+    }
+
+    #[test]
+    fn test_refresh_ttl_depends_on_remember_me() {
+        let service = throttled_service();
+        let before = Utc::now();
+
+        let remembered = service.new_session(1, Uuid::new_v4(), &tokens(true), &ClientInfo::default());
+        let ephemeral = service.new_session(1, Uuid::new_v4(), &tokens(false), &ClientInfo::default());
+
+        assert!(remembered.expires_at >= before + Duration::days(30));
+        assert!(ephemeral.expires_at >= before + Duration::hours(12));
+        assert!(ephemeral.expires_at < before + Duration::days(1));
+    }
+
+    #[test]
+    fn test_session_records_remember_me() {
+        let service = throttled_service();
+
+        let remembered = service.new_session(1, Uuid::new_v4(), &tokens(true), &ClientInfo::default());
+        let ephemeral = service.new_session(1, Uuid::new_v4(), &tokens(false), &ClientInfo::default());
+
+        assert!(remembered.persistent);
+        assert!(!ephemeral.persistent);
+        assert_eq!(ephemeral.refresh_token_hash, service.hash_refresh_token("refresh"));
+        assert!(!SessionDto::from_session(ephemeral, None).persistent);
+    }
+
+    #[test]
+    fn test_token_from_removed_key_is_rejected() {
+        // Header names key "removed", which isn't configured
+        let token = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCIsImtpZCI6InJlbW92ZWQifQ.\
+                     eyJzdWIiOiIxIiwiZXhwIjo0MTAyNDQ0ODAwLCJpYXQiOjB9.c2ln";
+        let service = throttled_service();
+
+        let result = decode_token::<Claims>(&service.jwt_settings, token);
+        assert!(matches!(result.map_err(|e| e.into_kind()), Err(ErrorKind::InvalidSignature)));
+        assert!(matches!(service.decode_access_token(token), Err(AuthError::InvalidToken)));
+    }
+
+    #[cfg(feature = "db-tests")]
+    mod db {
+        use super::*;
+        use crate::infrastructure::repositories::{test_db, PgSessionRepository, PgUserRepository};
+
+        const OLD_PASSWORD: &str = "OldPassw0rd!";
+        const NEW_PASSWORD: &str = "NewPassw0rd!";
+
+        fn service(pool: sqlx::PgPool) -> AuthServiceImpl<PgUserRepository, PgSessionRepository> {
+            AuthServiceImpl::new(
+                Arc::new(PgUserRepository::new(pool.clone())),
+                Arc::new(PgSessionRepository::new(pool)),
+                Arc::new(SnowflakeGenerator::new(1, 1)),
+                jwt_settings(),
+            )
+        }
+
+        /// A user with `OLD_PASSWORD` and two sessions
+        async fn user_with_sessions(pool: &sqlx::PgPool) -> (i64, Uuid, Uuid) {
+            let user_id = test_db::user(pool).await;
+            sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1")
+                .bind(user_id)
+                .bind(password_hash(OLD_PASSWORD))
+                .execute(pool)
+                .await
+                .unwrap();
+
+            let sessions = PgSessionRepository::new(pool.clone());
+            let mut ids = Vec::new();
+            for _ in 0..2 {
+                let session = Session::new(user_id, Uuid::new_v4().to_string(), Utc::now() + Duration::days(1));
+                ids.push(sessions.create(&session).await.unwrap().id);
+            }
+            (user_id, ids[0], ids[1])
+        }
+
+        async fn stored_hash(pool: &sqlx::PgPool, user_id: i64) -> String {
+            PgUserRepository::new(pool.clone())
+                .find_by_id(user_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .password_hash
+        }
+
+        #[tokio::test]
+        async fn test_password_change_keeps_only_the_current_session() {
+            let pool = test_db::pool().await;
+            let (user_id, current, other) = user_with_sessions(&pool).await;
+            let service = service(pool.clone());
+
+            let changed = service
+                .change_password(user_id, Some(current), OLD_PASSWORD, NEW_PASSWORD)
+                .await
+                .unwrap();
+
+            assert_eq!(changed.revoked_sessions, vec![other]);
+            let sessions = PgSessionRepository::new(pool.clone());
+            assert!(sessions.find_by_id(other).await.unwrap().is_none());
+            // The current session carries on with the re-issued refresh token
+            assert!(service.refresh_token(&changed.tokens.refresh_token).await.is_ok());
+            assert!(verify_password_hash(NEW_PASSWORD, &stored_hash(&pool, user_id).await).unwrap());
+        }
+
+        #[tokio::test]
+        async fn test_wrong_current_password_changes_nothing() {
+            let pool = test_db::pool().await;
+            let (user_id, current, other) = user_with_sessions(&pool).await;
+
+            let result = service(pool.clone())
+                .change_password(user_id, Some(current), "WrongPassw0rd!", NEW_PASSWORD)
+                .await;
+
+            assert!(matches!(result, Err(AuthError::InvalidCredentials)));
+            let sessions = PgSessionRepository::new(pool.clone());
+            assert!(sessions.find_by_id(other).await.unwrap().is_some());
+            assert!(verify_password_hash(OLD_PASSWORD, &stored_hash(&pool, user_id).await).unwrap());
+        }
+
+        #[tokio::test]
+        async fn test_password_change_rolls_back_without_the_kept_session() {
+            let pool = test_db::pool().await;
+            let (user_id, _, other) = user_with_sessions(&pool).await;
+            let (_, stranger_session, _) = user_with_sessions(&pool).await;
+            let users = PgUserRepository::new(pool.clone());
+
+            // Someone else's session can't be kept, so nothing is applied
+            let result = users
+                .change_password(user_id, &password_hash(NEW_PASSWORD), stranger_session, "rotated", Utc::now())
+                .await;
+
+            assert!(matches!(result, Err(AppError::NotFound(_))));
+            let sessions = PgSessionRepository::new(pool.clone());
+            assert!(sessions.find_by_id(other).await.unwrap().is_some());
+            assert!(verify_password_hash(OLD_PASSWORD, &stored_hash(&pool, user_id).await).unwrap());
+        }
+    }
 }
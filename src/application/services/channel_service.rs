@@ -5,12 +5,17 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 
+use super::permission_resolver::{self, GuildPermissions};
+use crate::domain::services::PermissionService;
+use crate::domain::value_objects::Permissions;
 use crate::domain::{
     Channel, ChannelRepository, ChannelType, MemberRepository,
-    PermissionOverwrite, ServerRepository,
+    PermissionOverwrite, Role, RoleRepository, ServerRepository, UserRepository,
 };
+use crate::infrastructure::cache::{Cache, PermissionCacheService};
+use crate::shared::error::AppError;
 use crate::shared::snowflake::SnowflakeGenerator;
 
 /// Channel service trait
@@ -19,15 +24,24 @@ pub trait ChannelService: Send + Sync {
     /// Create a new channel in a guild
     async fn create_channel(&self, guild_id: i64, actor_id: i64, request: CreateChannelDto) -> Result<ChannelDto, ChannelError>;
 
-    /// Get channel by ID
-    async fn get_channel(&self, channel_id: i64) -> Result<ChannelDto, ChannelError>;
+    /// Get a channel the actor can view.
+    ///
+    /// Channels the actor can't view are reported as not found.
+    async fn get_channel(&self, channel_id: i64, actor_id: i64) -> Result<ChannelDto, ChannelError>;
 
     /// Update channel
     async fn update_channel(&self, channel_id: i64, actor_id: i64, update: UpdateChannelDto) -> Result<ChannelDto, ChannelError>;
 
-    /// Delete channel
+    /// Delete channel with its messages, overwrites and invites
+    /// (requires MANAGE_CHANNELS); children of a category move to the top level.
+    ///
+    /// A DM is not deleted but closed for the actor only.
     async fn delete_channel(&self, channel_id: i64, actor_id: i64) -> Result<(), ChannelError>;
 
+    /// Open the DM channel between the actor and another user, creating
+    /// it on first use and reopening it if the actor closed it
+    async fn open_dm(&self, actor_id: i64, recipient_id: i64) -> Result<ChannelDto, ChannelError>;
+
     /// Get channels for a guild
     async fn get_guild_channels(&self, guild_id: i64) -> Result<Vec<ChannelDto>, ChannelError>;
 
@@ -41,6 +55,29 @@ pub trait ChannelService: Send + Sync {
         actor_id: i64,
         overwrites: Vec<PermissionOverwriteDto>,
     ) -> Result<(), ChannelError>;
+
+    /// Create or replace the overwrite for a single role or member.
+    ///
+    /// Requires MANAGE_ROLES in the channel; the actor cannot allow or deny
+    /// permissions they do not have there.
+    async fn set_overwrite(
+        &self,
+        channel_id: i64,
+        actor_id: i64,
+        overwrite: PermissionOverwriteDto,
+    ) -> Result<(), ChannelError>;
+
+    /// Remove the overwrite for a single role or member, under the same
+    /// rules as `set_overwrite`.
+    async fn delete_overwrite(&self, channel_id: i64, actor_id: i64, target_id: i64) -> Result<(), ChannelError>;
+
+    /// Duplicate a guild channel's settings and permission overwrites into a
+    /// new channel placed just after it. Messages are not copied.
+    async fn clone_channel(&self, channel_id: i64, actor_id: i64, new_name: String) -> Result<ChannelDto, ChannelError>;
+
+    /// Replace a channel's permission overwrites with a copy of its
+    /// category's, under the same rules as `set_overwrite`.
+    async fn sync_to_category(&self, channel_id: i64, actor_id: i64) -> Result<(), ChannelError>;
 }
 
 /// Create channel request
@@ -52,6 +89,8 @@ pub struct CreateChannelDto {
     pub parent_id: Option<i64>,
     pub position: Option<i32>,
     pub nsfw: Option<bool>,
+    /// Start with a copy of the parent category's permission overwrites
+    pub sync_permissions: bool,
 }
 
 /// Channel data transfer object
@@ -66,6 +105,7 @@ pub struct ChannelDto {
     pub parent_id: Option<String>,
     pub nsfw: bool,
     pub rate_limit_per_user: i32,
+    pub slowmode_exempt_roles: Vec<String>,
     pub created_at: String,
 }
 
@@ -81,6 +121,7 @@ impl From<Channel> for ChannelDto {
             parent_id: channel.parent_id.map(|id| id.to_string()),
             nsfw: channel.nsfw,
             rate_limit_per_user: channel.rate_limit_per_user,
+            slowmode_exempt_roles: channel.slowmode_exempt_roles.iter().map(|id| id.to_string()).collect(),
             created_at: channel.created_at.to_rfc3339(),
         }
     }
@@ -95,6 +136,8 @@ pub struct UpdateChannelDto {
     pub parent_id: Option<Option<i64>>,
     pub nsfw: Option<bool>,
     pub rate_limit_per_user: Option<i32>,
+    /// Replaces the roles exempt from slowmode
+    pub slowmode_exempt_roles: Option<Vec<i64>>,
 }
 
 /// Permission overwrite DTO
@@ -121,50 +164,283 @@ pub enum ChannelError {
     #[error("Invalid channel type")]
     InvalidChannelType,
 
+    #[error("Invalid parent channel: {0}")]
+    InvalidParent(String),
+
+    #[error("Invalid permission overwrite: {0}")]
+    InvalidOverwrite(String),
+
+    #[error("Overwrite target not found")]
+    OverwriteTargetNotFound,
+
+    #[error("Slowmode-exempt roles must be roles in this guild")]
+    InvalidExemptRole,
+
+    #[error("Channel is not in a category")]
+    NotInCategory,
+
+    #[error("This channel is age-restricted")]
+    NsfwRestricted,
+
+    #[error("Cannot open a DM with yourself")]
+    InvalidRecipient,
+
+    #[error("Recipient not found")]
+    RecipientNotFound,
+
     #[error("Internal error: {0}")]
     Internal(String),
+
+    /// No database connection was free in time; the request may succeed on retry
+    #[error("Service unavailable")]
+    Unavailable,
+}
+
+impl From<AppError> for ChannelError {
+    fn from(e: AppError) -> Self {
+        if e.is_unavailable() {
+            ChannelError::Unavailable
+        } else {
+            ChannelError::Internal(e.to_string())
+        }
+    }
+}
+
+/// Show a channel only to a user whose `permissions` there include
+/// `VIEW_CHANNEL`; hidden channels are reported as missing so their
+/// existence isn't revealed.
+///
+/// NSFW channels additionally need the user to have allowed NSFW content
+/// or to moderate the channel.
+fn visible_channel(channel: Channel, permissions: i64, nsfw_allowed: bool) -> Result<ChannelDto, ChannelError> {
+    if !Permissions::new(permissions).has(Permissions::VIEW_CHANNEL) {
+        return Err(ChannelError::NotFound);
+    }
+    if !PermissionService::can_view_nsfw(&channel, permissions, nsfw_allowed) {
+        return Err(ChannelError::NsfwRestricted);
+    }
+    Ok(ChannelDto::from(channel))
+}
+
+/// Check that `parent` may contain a channel of `channel_type` in `server_id`.
+///
+/// Parents must be categories in the same server, and categories cannot be
+/// nested.
+fn validate_parent(
+    channel_type: &ChannelType,
+    server_id: Option<i64>,
+    parent: &Channel,
+) -> Result<(), ChannelError> {
+    if *channel_type == ChannelType::Category {
+        return Err(ChannelError::InvalidParent("categories cannot have a parent".into()));
+    }
+    if parent.channel_type != ChannelType::Category {
+        return Err(ChannelError::InvalidParent("parent must be a category".into()));
+    }
+    if server_id.is_none() || parent.server_id != server_id {
+        return Err(ChannelError::InvalidParent("parent must be in the same server".into()));
+    }
+    Ok(())
+}
+
+/// Validate an overwrite's target type and bits.
+fn validate_overwrite(overwrite: &PermissionOverwriteDto) -> Result<(), ChannelError> {
+    if overwrite.target_type != "role" && overwrite.target_type != "member" {
+        return Err(ChannelError::InvalidOverwrite(
+            "target type must be \"role\" or \"member\"".into(),
+        ));
+    }
+    if overwrite.allow & overwrite.deny != 0 {
+        return Err(ChannelError::InvalidOverwrite(
+            "a permission cannot be both allowed and denied".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check that an actor holding `actor_permissions` may allow or deny
+/// every bit of an overwrite.
+fn check_grantable(actor_permissions: i64, allow: i64, deny: i64) -> Result<(), ChannelError> {
+    // Owners and administrators get every channel permission, so they pass
+    if !PermissionService::can_grant(actor_permissions, allow | deny, false) {
+        return Err(ChannelError::Forbidden);
+    }
+    Ok(())
+}
+
+/// Cached channel permissions affected by changing an overwrite
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OverwriteScope {
+    /// A member overwrite only affects that member
+    Member(i64),
+    /// A role overwrite may affect anyone in the channel
+    AllMembers,
+}
+
+impl OverwriteScope {
+    fn of(target_type: &str, target_id: i64) -> Self {
+        if target_type == "member" {
+            Self::Member(target_id)
+        } else {
+            Self::AllMembers
+        }
+    }
+}
+
+/// Drop cached channel permissions affected by an overwrite change.
+///
+/// The overwrite is already stored at this point, so failures are logged
+/// rather than surfaced; stale entries expire with their TTL.
+async fn invalidate_overwrite<C: Cache>(
+    cache: Option<&PermissionCacheService<C>>,
+    guild_id: i64,
+    channel_id: i64,
+    scope: OverwriteScope,
+) {
+    let Some(cache) = cache else {
+        return;
+    };
+
+    let result = match scope {
+        OverwriteScope::Member(user_id) => cache
+            .invalidate_channel_permissions(guild_id, channel_id, user_id)
+            .await
+            .map(|_| ()),
+        OverwriteScope::AllMembers => cache
+            .invalidate_all_channel_permissions(guild_id, channel_id)
+            .await
+            .map(|_| ()),
+    };
+
+    if let Err(e) = result {
+        tracing::warn!(channel_id, error = %e, "Failed to invalidate channel permissions");
+    }
+}
+
+/// Build the copy of `source` created by a clone.
+///
+/// The copy sits directly after the source, under the same parent.
+fn cloned_channel(source: &Channel, id: i64, name: String, now: DateTime<Utc>) -> Channel {
+    Channel {
+        id,
+        server_id: source.server_id,
+        name,
+        channel_type: source.channel_type,
+        topic: source.topic.clone(),
+        position: source.position + 1,
+        parent_id: source.parent_id,
+        nsfw: source.nsfw,
+        rate_limit_per_user: source.rate_limit_per_user,
+        slowmode_exempt_roles: source.slowmode_exempt_roles.clone(),
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+/// Check that every slowmode-exempt role is one of the guild's roles,
+/// returning the IDs sorted and deduplicated.
+///
+/// DM channels have no roles, so they can only be given none.
+fn validate_exempt_roles(mut role_ids: Vec<i64>, guild_roles: &[Role]) -> Result<Vec<i64>, ChannelError> {
+    role_ids.sort_unstable();
+    role_ids.dedup();
+
+    if role_ids.iter().all(|id| guild_roles.iter().any(|role| role.id == *id)) {
+        Ok(role_ids)
+    } else {
+        Err(ChannelError::InvalidExemptRole)
+    }
+}
+
+/// Re-target a channel's permission overwrites at a new channel.
+fn cloned_overwrites(overwrites: Vec<PermissionOverwrite>, channel_id: i64) -> Vec<PermissionOverwrite> {
+    overwrites
+        .into_iter()
+        .map(|o| PermissionOverwrite { channel_id, ..o })
+        .collect()
+}
+
+/// The category a new channel copies its permission overwrites from, if
+/// it is created in one with `sync_permissions` set.
+fn synced_category(request: &CreateChannelDto) -> Option<i64> {
+    request.parent_id.filter(|_| request.sync_permissions)
+}
+
+/// Position changes that make room for a clone inserted after `source`.
+///
+/// Siblings (same parent) positioned after the source move down one slot so
+/// the clone can take `source.position + 1` without colliding.
+fn clone_position_shifts(source: &Channel, channels: &[Channel]) -> Vec<(i64, i32)> {
+    channels
+        .iter()
+        .filter(|c| c.id != source.id && c.parent_id == source.parent_id && c.position > source.position)
+        .map(|c| (c.id, c.position + 1))
+        .collect()
 }
 
 /// ChannelService implementation
-pub struct ChannelServiceImpl<C, S, M>
+pub struct ChannelServiceImpl<C, S, M, R, U>
 where
     C: ChannelRepository,
     S: ServerRepository,
     M: MemberRepository,
+    R: RoleRepository,
+    U: UserRepository,
 {
     channel_repo: Arc<C>,
     server_repo: Arc<S>,
     member_repo: Arc<M>,
+    role_repo: Arc<R>,
+    user_repo: Arc<U>,
     id_generator: Arc<SnowflakeGenerator>,
+    permission_cache: Option<PermissionCacheService>,
 }
 
-impl<C, S, M> ChannelServiceImpl<C, S, M>
+impl<C, S, M, R, U> ChannelServiceImpl<C, S, M, R, U>
 where
     C: ChannelRepository,
     S: ServerRepository,
     M: MemberRepository,
+    R: RoleRepository,
+    U: UserRepository,
 {
     pub fn new(
         channel_repo: Arc<C>,
         server_repo: Arc<S>,
         member_repo: Arc<M>,
+        role_repo: Arc<R>,
+            user_repo: Arc<U>,
         id_generator: Arc<SnowflakeGenerator>,
     ) -> Self {
         Self {
             channel_repo,
             server_repo,
             member_repo,
+            role_repo,
+            user_repo,
             id_generator,
+            permission_cache: None,
         }
     }
 
+    /// Attach a permission cache to invalidate when overwrites change.
+    pub fn with_permission_cache(mut self, cache: PermissionCacheService) -> Self {
+        self.permission_cache = Some(cache);
+        self
+    }
+
+    /// Drop cached channel permissions affected by an overwrite change.
+    async fn invalidate_overwrite(&self, guild_id: i64, channel_id: i64, scope: OverwriteScope) {
+        invalidate_overwrite(self.permission_cache.as_ref(), guild_id, channel_id, scope).await;
+    }
+
     async fn check_guild_permission(&self, guild_id: i64, user_id: i64) -> Result<bool, ChannelError> {
         // First, check if user is a member of the guild
         let is_member = self
             .member_repo
             .is_member(guild_id, user_id)
             .await
-            .map_err(|e| ChannelError::Internal(e.to_string()))?;
+            .map_err(ChannelError::from)?;
 
         if !is_member {
             return Err(ChannelError::Forbidden);
@@ -175,12 +451,149 @@ where
             .server_repo
             .find_by_id(guild_id)
             .await
-            .map_err(|e| ChannelError::Internal(e.to_string()))?
+            .map_err(ChannelError::from)?
             .ok_or(ChannelError::GuildNotFound)?;
 
         Ok(server.owner_id == user_id)
     }
 
+    /// Require `permission` on a guild channel, taking roles and the
+    /// channel's overwrites into account.
+    async fn require_channel_permission(
+        &self,
+        channel: &Channel,
+        server_id: i64,
+        user_id: i64,
+        permission: i64,
+    ) -> Result<(), ChannelError> {
+        let permissions = self.channel_permissions(channel, server_id, user_id).await?;
+        if !Permissions::new(permissions).has(permission) {
+            return Err(ChannelError::Forbidden);
+        }
+        Ok(())
+    }
+
+    /// A member's effective permissions on a guild channel.
+    async fn channel_permissions(
+        &self,
+        channel: &Channel,
+        server_id: i64,
+        user_id: i64,
+    ) -> Result<i64, ChannelError> {
+        let guild = GuildPermissions::load(self.server_repo.as_ref(), self.role_repo.as_ref(), server_id)
+            .await
+            .map_err(ChannelError::from)?
+            .ok_or(ChannelError::GuildNotFound)?;
+
+        let member = self
+            .member_repo
+            .find(server_id, user_id)
+            .await
+            .map_err(ChannelError::from)?
+            .ok_or(ChannelError::Forbidden)?;
+
+        let overwrites = self
+            .channel_repo
+            .get_permission_overwrites(channel.id)
+            .await
+            .map_err(ChannelError::from)?;
+
+        Ok(guild.channel(&member, channel, &overwrites))
+    }
+
+    /// A user's permissions for viewing a channel: computed from roles and
+    /// overwrites for guild channels, from the recipients for DMs.
+    ///
+    /// Non-members of the channel's guild have none.
+    async fn view_permissions(&self, channel: &Channel, user_id: i64) -> Result<i64, ChannelError> {
+        let permissions = permission_resolver::channel_permissions(
+            self.server_repo.as_ref(),
+            self.member_repo.as_ref(),
+            self.role_repo.as_ref(),
+            self.channel_repo.as_ref(),
+            channel,
+            user_id,
+        )
+        .await
+        .map_err(ChannelError::from)?;
+
+        Ok(permissions.unwrap_or(0))
+    }
+
+    /// Load a guild channel whose overwrites `actor_id` may manage,
+    /// returning it with its server ID and the actor's permissions there.
+    async fn overwrite_channel(
+        &self,
+        channel_id: i64,
+        actor_id: i64,
+    ) -> Result<(Channel, i64, i64), ChannelError> {
+        let channel = self
+            .channel_repo
+            .find_by_id(channel_id)
+            .await
+            .map_err(ChannelError::from)?
+            .ok_or(ChannelError::NotFound)?;
+
+        let server_id = match channel.server_id {
+            Some(server_id) if !channel.is_dm() => server_id,
+            _ => return Err(ChannelError::InvalidChannelType),
+        };
+
+        let permissions = self.channel_permissions(&channel, server_id, actor_id).await?;
+        if !Permissions::new(permissions).has(Permissions::MANAGE_ROLES) {
+            return Err(ChannelError::Forbidden);
+        }
+
+        Ok((channel, server_id, permissions))
+    }
+
+    /// Check that an overwrite target is a role or member of the server.
+    async fn check_overwrite_target(
+        &self,
+        server_id: i64,
+        target_type: &str,
+        target_id: i64,
+    ) -> Result<(), ChannelError> {
+        let exists = if target_type == "role" {
+            self.role_repo
+                .find_by_id(target_id)
+                .await
+                .map_err(ChannelError::from)?
+                .is_some_and(|role| role.server_id == server_id)
+        } else {
+            self.member_repo
+                .is_member(server_id, target_id)
+                .await
+                .map_err(ChannelError::from)?
+        };
+
+        if !exists {
+            return Err(ChannelError::OverwriteTargetNotFound);
+        }
+        Ok(())
+    }
+
+    /// Validate an optional parent for a channel of `channel_type` in `server_id`.
+    async fn check_parent(
+        &self,
+        channel_type: &ChannelType,
+        server_id: Option<i64>,
+        parent_id: Option<i64>,
+    ) -> Result<(), ChannelError> {
+        let Some(parent_id) = parent_id else {
+            return Ok(());
+        };
+
+        let parent = self
+            .channel_repo
+            .find_by_id(parent_id)
+            .await
+            .map_err(ChannelError::from)?
+            .ok_or_else(|| ChannelError::InvalidParent("parent channel not found".into()))?;
+
+        validate_parent(channel_type, server_id, &parent)
+    }
+
     fn parse_channel_type(type_str: Option<&str>) -> ChannelType {
         match type_str {
             Some("voice") => ChannelType::Voice,
@@ -193,11 +606,13 @@ where
 }
 
 #[async_trait]
-impl<C, S, M> ChannelService for ChannelServiceImpl<C, S, M>
+impl<C, S, M, R, U> ChannelService for ChannelServiceImpl<C, S, M, R, U>
 where
     C: ChannelRepository + 'static,
     S: ServerRepository + 'static,
     M: MemberRepository + 'static,
+    R: RoleRepository + 'static,
+    U: UserRepository + 'static,
 {
     async fn create_channel(&self, guild_id: i64, actor_id: i64, request: CreateChannelDto) -> Result<ChannelDto, ChannelError> {
         // Check permission
@@ -207,7 +622,9 @@ where
 
         let now = Utc::now();
         let channel_type = Self::parse_channel_type(request.channel_type.as_deref());
+        self.check_parent(&channel_type, Some(guild_id), request.parent_id).await?;
 
+        let synced_from = synced_category(&request);
         let channel = Channel {
             id: self.id_generator.generate(),
             server_id: Some(guild_id),
@@ -218,28 +635,33 @@ where
             parent_id: request.parent_id,
             nsfw: request.nsfw.unwrap_or(false),
             rate_limit_per_user: 0,
+            slowmode_exempt_roles: Vec::new(),
             created_at: now,
             updated_at: now,
         };
 
         let created = self
             .channel_repo
-            .create(&channel)
+            .create_with_overwrites(&channel, synced_from, &[])
             .await
-            .map_err(|e| ChannelError::Internal(e.to_string()))?;
+            .map_err(ChannelError::from)?;
 
         Ok(ChannelDto::from(created))
     }
 
-    async fn get_channel(&self, channel_id: i64) -> Result<ChannelDto, ChannelError> {
+    async fn get_channel(&self, channel_id: i64, actor_id: i64) -> Result<ChannelDto, ChannelError> {
         let channel = self
             .channel_repo
             .find_by_id(channel_id)
             .await
-            .map_err(|e| ChannelError::Internal(e.to_string()))?
+            .map_err(ChannelError::from)?
             .ok_or(ChannelError::NotFound)?;
 
-        Ok(ChannelDto::from(channel))
+        let permissions = self.view_permissions(&channel, actor_id).await?;
+        let nsfw_allowed = permission_resolver::nsfw_allowed(self.user_repo.as_ref(), &channel, actor_id)
+            .await
+            .map_err(ChannelError::from)?;
+        visible_channel(channel, permissions, nsfw_allowed)
     }
 
     async fn update_channel(&self, channel_id: i64, actor_id: i64, update: UpdateChannelDto) -> Result<ChannelDto, ChannelError> {
@@ -247,7 +669,7 @@ where
             .channel_repo
             .find_by_id(channel_id)
             .await
-            .map_err(|e| ChannelError::Internal(e.to_string()))?
+            .map_err(ChannelError::from)?
             .ok_or(ChannelError::NotFound)?;
 
         // Check permission
@@ -268,6 +690,7 @@ where
             channel.position = position;
         }
         if let Some(parent_id) = update.parent_id {
+            self.check_parent(&channel.channel_type, channel.server_id, parent_id).await?;
             channel.parent_id = parent_id;
         }
         if let Some(nsfw) = update.nsfw {
@@ -276,12 +699,23 @@ where
         if let Some(rate_limit) = update.rate_limit_per_user {
             channel.rate_limit_per_user = rate_limit;
         }
+        if let Some(role_ids) = update.slowmode_exempt_roles {
+            let guild_roles = match channel.server_id {
+                Some(guild_id) => self
+                    .role_repo
+                    .find_by_server_id(guild_id)
+                    .await
+                    .map_err(ChannelError::from)?,
+                None => Vec::new(),
+            };
+            channel.slowmode_exempt_roles = validate_exempt_roles(role_ids, &guild_roles)?;
+        }
 
         let updated = self
             .channel_repo
             .update(&channel)
             .await
-            .map_err(|e| ChannelError::Internal(e.to_string()))?;
+            .map_err(ChannelError::from)?;
 
         Ok(ChannelDto::from(updated))
     }
@@ -291,30 +725,98 @@ where
             .channel_repo
             .find_by_id(channel_id)
             .await
-            .map_err(|e| ChannelError::Internal(e.to_string()))?
+            .map_err(ChannelError::from)?
             .ok_or(ChannelError::NotFound)?;
 
-        // Check permission
-        if let Some(guild_id) = channel.server_id {
-            if !self.check_guild_permission(guild_id, actor_id).await? {
-                return Err(ChannelError::Forbidden);
-            }
-        }
+        let Some(guild_id) = channel.server_id else {
+            // A DM belongs to both recipients, so it is only closed for the actor
+            return self
+                .channel_repo
+                .set_dm_closed(channel_id, actor_id, true)
+                .await
+                .map_err(|e| match e {
+                    AppError::NotFound(_) => ChannelError::Forbidden,
+                    e => ChannelError::from(e),
+                });
+        };
+        self.require_channel_permission(&channel, guild_id, actor_id, Permissions::MANAGE_CHANNELS)
+            .await?;
 
-        self.channel_repo
-            .delete(channel_id)
+        let deletion = self
+            .channel_repo
+            .delete_with_dependents(&channel)
             .await
-            .map_err(|e| ChannelError::Internal(e.to_string()))?;
+            .map_err(|e| match e {
+                AppError::NotFound(_) => ChannelError::NotFound,
+                e => ChannelError::from(e),
+            })?;
+        tracing::debug!(
+            channel_id,
+            reparented = deletion.reparented_children.len(),
+            invites = deletion.invalidated_invites.len(),
+            "Channel deleted"
+        );
+
+        self.invalidate_overwrite(guild_id, channel_id, OverwriteScope::AllMembers).await;
 
         Ok(())
     }
 
+    async fn open_dm(&self, actor_id: i64, recipient_id: i64) -> Result<ChannelDto, ChannelError> {
+        if actor_id == recipient_id {
+            return Err(ChannelError::InvalidRecipient);
+        }
+
+        self.user_repo
+            .find_by_id(recipient_id)
+            .await
+            .map_err(ChannelError::from)?
+            .ok_or(ChannelError::RecipientNotFound)?;
+
+        let existing = self
+            .channel_repo
+            .find_dm_channel(actor_id, recipient_id)
+            .await
+            .map_err(ChannelError::from)?;
+        if let Some(channel) = existing {
+            self.channel_repo
+                .set_dm_closed(channel.id, actor_id, false)
+                .await
+                .map_err(ChannelError::from)?;
+            return Ok(ChannelDto::from(channel));
+        }
+
+        let now = Utc::now();
+        let channel = Channel {
+            id: self.id_generator.generate(),
+            server_id: None,
+            name: String::new(),
+            channel_type: ChannelType::Dm,
+            topic: None,
+            position: 0,
+            parent_id: None,
+            nsfw: false,
+            rate_limit_per_user: 0,
+            slowmode_exempt_roles: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        };
+
+        let created = self
+            .channel_repo
+            .create_dm(&channel, &[actor_id, recipient_id])
+            .await
+            .map_err(ChannelError::from)?;
+
+        Ok(ChannelDto::from(created))
+    }
+
     async fn get_guild_channels(&self, guild_id: i64) -> Result<Vec<ChannelDto>, ChannelError> {
         let channels = self
             .channel_repo
             .find_by_server_id(guild_id)
             .await
-            .map_err(|e| ChannelError::Internal(e.to_string()))?;
+            .map_err(ChannelError::from)?;
 
         Ok(channels.into_iter().map(ChannelDto::from).collect())
     }
@@ -328,7 +830,7 @@ where
         self.channel_repo
             .update_positions(guild_id, positions)
             .await
-            .map_err(|e| ChannelError::Internal(e.to_string()))?;
+            .map_err(ChannelError::from)?;
 
         Ok(())
     }
@@ -343,7 +845,7 @@ where
             .channel_repo
             .find_by_id(channel_id)
             .await
-            .map_err(|e| ChannelError::Internal(e.to_string()))?
+            .map_err(ChannelError::from)?
             .ok_or(ChannelError::NotFound)?;
 
         // Check permission
@@ -367,8 +869,125 @@ where
         self.channel_repo
             .set_permission_overwrites(channel_id, domain_overwrites)
             .await
-            .map_err(|e| ChannelError::Internal(e.to_string()))?;
+            .map_err(ChannelError::from)?;
+
+        Ok(())
+    }
+
+    async fn set_overwrite(
+        &self,
+        channel_id: i64,
+        actor_id: i64,
+        overwrite: PermissionOverwriteDto,
+    ) -> Result<(), ChannelError> {
+        validate_overwrite(&overwrite)?;
+
+        let (_, server_id, permissions) = self.overwrite_channel(channel_id, actor_id).await?;
+        check_grantable(permissions, overwrite.allow, overwrite.deny)?;
+        self.check_overwrite_target(server_id, &overwrite.target_type, overwrite.target_id)
+            .await?;
+
+        let scope = OverwriteScope::of(&overwrite.target_type, overwrite.target_id);
+        self.channel_repo
+            .upsert_permission_overwrite(&PermissionOverwrite {
+                channel_id,
+                target_id: overwrite.target_id,
+                target_type: overwrite.target_type,
+                allow: overwrite.allow,
+                deny: overwrite.deny,
+            })
+            .await
+            .map_err(ChannelError::from)?;
+
+        self.invalidate_overwrite(server_id, channel_id, scope).await;
+        Ok(())
+    }
+
+    async fn delete_overwrite(&self, channel_id: i64, actor_id: i64, target_id: i64) -> Result<(), ChannelError> {
+        let (_, server_id, permissions) = self.overwrite_channel(channel_id, actor_id).await?;
+
+        let existing = self
+            .channel_repo
+            .get_permission_overwrites(channel_id)
+            .await
+            .map_err(ChannelError::from)?
+            .into_iter()
+            .find(|o| o.target_id == target_id)
+            .ok_or(ChannelError::OverwriteTargetNotFound)?;
+
+        // Removing a deny grants it back, so the same rule applies
+        check_grantable(permissions, existing.allow, existing.deny)?;
+
+        self.channel_repo
+            .delete_permission_overwrite(channel_id, &existing.target_type, target_id)
+            .await
+            .map_err(ChannelError::from)?;
 
+        self.invalidate_overwrite(server_id, channel_id, OverwriteScope::of(&existing.target_type, target_id))
+            .await;
+        Ok(())
+    }
+
+    async fn clone_channel(&self, channel_id: i64, actor_id: i64, new_name: String) -> Result<ChannelDto, ChannelError> {
+        let source = self
+            .channel_repo
+            .find_by_id(channel_id)
+            .await
+            .map_err(ChannelError::from)?
+            .ok_or(ChannelError::NotFound)?;
+
+        // Only guild channels can be cloned
+        let Some(guild_id) = source.server_id else {
+            return Err(ChannelError::InvalidChannelType);
+        };
+
+        self.require_channel_permission(&source, guild_id, actor_id, Permissions::MANAGE_CHANNELS)
+            .await?;
+
+        let siblings = self
+            .channel_repo
+            .find_by_server_id(guild_id)
+            .await
+            .map_err(ChannelError::from)?;
+
+        let channel = cloned_channel(&source, self.id_generator.generate(), new_name, Utc::now());
+        let shifts = clone_position_shifts(&source, &siblings);
+
+        let created = self
+            .channel_repo
+            .create_with_overwrites(&channel, Some(source.id), &shifts)
+            .await
+            .map_err(ChannelError::from)?;
+
+        Ok(ChannelDto::from(created))
+    }
+
+    async fn sync_to_category(&self, channel_id: i64, actor_id: i64) -> Result<(), ChannelError> {
+        let (channel, server_id, permissions) = self.overwrite_channel(channel_id, actor_id).await?;
+        let category_id = channel.parent_id.ok_or(ChannelError::NotInCategory)?;
+
+        let current = self
+            .channel_repo
+            .get_permission_overwrites(channel_id)
+            .await
+            .map_err(ChannelError::from)?;
+        let synced = self
+            .channel_repo
+            .get_permission_overwrites(category_id)
+            .await
+            .map_err(ChannelError::from)?;
+
+        // Both the overwrites dropped and the ones added change permissions
+        for overwrite in current.iter().chain(&synced) {
+            check_grantable(permissions, overwrite.allow, overwrite.deny)?;
+        }
+
+        self.channel_repo
+            .set_permission_overwrites(channel_id, cloned_overwrites(synced, channel_id))
+            .await
+            .map_err(ChannelError::from)?;
+
+        self.invalidate_overwrite(server_id, channel_id, OverwriteScope::AllMembers).await;
         Ok(())
     }
 }
@@ -377,5 +996,589 @@ where
 mod tests {
     use super::*;
 
-    // Tests would go here
+    fn channel(id: i64, server_id: i64, channel_type: ChannelType) -> Channel {
+        let now = Utc::now();
+        Channel {
+            id,
+            server_id: Some(server_id),
+            name: format!("channel-{}", id),
+            channel_type,
+            topic: None,
+            position: 0,
+            parent_id: None,
+            nsfw: false,
+            rate_limit_per_user: 0,
+            slowmode_exempt_roles: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_parent_category_in_same_server_is_valid() {
+        let category = channel(1, 100, ChannelType::Category);
+
+        assert!(validate_parent(&ChannelType::Text, Some(100), &category).is_ok());
+        assert!(validate_parent(&ChannelType::Voice, Some(100), &category).is_ok());
+    }
+
+    #[test]
+    fn test_parenting_to_non_category_is_rejected() {
+        let text = channel(1, 100, ChannelType::Text);
+
+        assert!(matches!(
+            validate_parent(&ChannelType::Text, Some(100), &text),
+            Err(ChannelError::InvalidParent(_))
+        ));
+    }
+
+    #[test]
+    fn test_cross_server_parenting_is_rejected() {
+        let category = channel(1, 200, ChannelType::Category);
+
+        assert!(matches!(
+            validate_parent(&ChannelType::Text, Some(100), &category),
+            Err(ChannelError::InvalidParent(_))
+        ));
+    }
+
+    #[test]
+    fn test_clone_duplicates_overwrites() {
+        let mut source = channel(1, 100, ChannelType::Text);
+        source.topic = Some("rules".to_string());
+        source.nsfw = true;
+        source.rate_limit_per_user = 10;
+        source.parent_id = Some(50);
+
+        let overwrites = vec![
+            PermissionOverwrite { channel_id: 1, target_id: 100, target_type: "role".into(), allow: 0, deny: Permissions::SEND_MESSAGES },
+            PermissionOverwrite { channel_id: 1, target_id: 7, target_type: "member".into(), allow: Permissions::SEND_MESSAGES, deny: 0 },
+        ];
+
+        let clone = cloned_channel(&source, 2, "rules-copy".to_string(), Utc::now());
+        let copied = cloned_overwrites(overwrites.clone(), clone.id);
+
+        assert_eq!(clone.name, "rules-copy");
+        assert_eq!(clone.topic.as_deref(), Some("rules"));
+        assert!(clone.nsfw);
+        assert_eq!(clone.rate_limit_per_user, 10);
+        assert_eq!(clone.parent_id, Some(50));
+        assert_eq!(copied.len(), overwrites.len());
+        for (copy, original) in copied.iter().zip(&overwrites) {
+            assert_eq!(copy.channel_id, 2);
+            assert_eq!(copy.target_id, original.target_id);
+            assert_eq!(copy.target_type, original.target_type);
+            assert_eq!((copy.allow, copy.deny), (original.allow, original.deny));
+        }
+    }
+
+    #[test]
+    fn test_clone_position_does_not_collide() {
+        let mut siblings: Vec<Channel> = (1..=4)
+            .map(|id| {
+                let mut c = channel(id, 100, ChannelType::Text);
+                c.position = id as i32 - 1;
+                c
+            })
+            .collect();
+        // Different category, same positions: must not move
+        let mut other = channel(9, 100, ChannelType::Text);
+        other.parent_id = Some(50);
+        other.position = 2;
+        siblings.push(other);
+
+        let source = siblings[1].clone();
+        let clone = cloned_channel(&source, 10, "copy".to_string(), Utc::now());
+        let shifts = clone_position_shifts(&source, &siblings);
+
+        assert_eq!(clone.position, 2);
+        assert_eq!(shifts, vec![(3, 3), (4, 4)]);
+
+        let mut positions: Vec<i32> = siblings
+            .iter()
+            .filter(|c| c.parent_id == source.parent_id)
+            .map(|c| shifts.iter().find(|(id, _)| *id == c.id).map_or(c.position, |(_, p)| *p))
+            .collect();
+        positions.push(clone.position);
+        positions.sort_unstable();
+        positions.dedup();
+        assert_eq!(positions.len(), 5);
+    }
+
+    #[test]
+    fn test_category_cannot_have_parent() {
+        let category = channel(1, 100, ChannelType::Category);
+
+        assert!(matches!(
+            validate_parent(&ChannelType::Category, Some(100), &category),
+            Err(ChannelError::InvalidParent(_))
+        ));
+    }
+
+    fn overwrite(target_type: &str, allow: i64, deny: i64) -> PermissionOverwriteDto {
+        PermissionOverwriteDto {
+            target_id: 7,
+            target_type: target_type.to_string(),
+            allow,
+            deny,
+        }
+    }
+
+    #[test]
+    fn test_cannot_grant_permissions_actor_lacks() {
+        let actor = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::MANAGE_ROLES;
+
+        assert!(check_grantable(actor, Permissions::SEND_MESSAGES, Permissions::VIEW_CHANNEL).is_ok());
+        assert!(matches!(
+            check_grantable(actor, Permissions::MANAGE_MESSAGES, 0),
+            Err(ChannelError::Forbidden)
+        ));
+        // Denying a permission the actor lacks is just as much an escalation
+        assert!(matches!(
+            check_grantable(actor, 0, Permissions::MANAGE_MESSAGES),
+            Err(ChannelError::Forbidden)
+        ));
+        assert!(check_grantable(Permissions::ALL, Permissions::MANAGE_MESSAGES, 0).is_ok());
+    }
+
+    #[test]
+    fn test_overwrite_validation() {
+        assert!(validate_overwrite(&overwrite("role", Permissions::SEND_MESSAGES, 0)).is_ok());
+        assert!(validate_overwrite(&overwrite("member", 0, Permissions::SEND_MESSAGES)).is_ok());
+        assert!(matches!(
+            validate_overwrite(&overwrite("user", 0, 0)),
+            Err(ChannelError::InvalidOverwrite(_))
+        ));
+        assert!(matches!(
+            validate_overwrite(&overwrite("role", Permissions::SEND_MESSAGES, Permissions::SEND_MESSAGES)),
+            Err(ChannelError::InvalidOverwrite(_))
+        ));
+    }
+
+    #[test]
+    fn test_invisible_channel_is_not_found() {
+        let text = channel(1, 100, ChannelType::Text);
+        let everyone = crate::domain::Role {
+            id: 100,
+            server_id: 100,
+            permissions: Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES,
+            ..Default::default()
+        };
+        let member = crate::domain::Member {
+            user_id: 7,
+            server_id: 100,
+            roles: vec![everyone.id],
+            ..Default::default()
+        };
+        let hidden = PermissionOverwrite {
+            channel_id: 1,
+            target_id: 100,
+            target_type: "role".into(),
+            allow: 0,
+            deny: Permissions::VIEW_CHANNEL,
+        };
+
+        let roles = [everyone];
+
+        let visible = PermissionService::calculate_channel_permissions(&member, &text, &[], &roles, 1);
+        let dto = visible_channel(text.clone(), visible, false).unwrap();
+        assert_eq!(dto.id, "1");
+
+        // Hidden channels look exactly like missing ones
+        let permissions = PermissionService::calculate_channel_permissions(&member, &text, &[hidden], &roles, 1);
+        assert!(matches!(visible_channel(text, permissions, false), Err(ChannelError::NotFound)));
+    }
+
+    #[test]
+    fn test_dm_is_visible_only_to_recipients() {
+        let dm = Channel {
+            server_id: None,
+            ..channel(1, 0, ChannelType::Dm)
+        };
+        let recipients = [7, 8];
+
+        let permissions = PermissionService::calculate_dm_permissions(&dm, &recipients, 7);
+        assert!(visible_channel(dm.clone(), permissions, false).is_ok());
+
+        let permissions = PermissionService::calculate_dm_permissions(&dm, &recipients, 9);
+        assert!(matches!(visible_channel(dm, permissions, false), Err(ChannelError::NotFound)));
+    }
+
+    #[test]
+    fn test_restricted_nsfw_channel_is_reported_only_when_visible() {
+        let nsfw = Channel {
+            nsfw: true,
+            ..channel(1, 100, ChannelType::Text)
+        };
+        let member = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+
+        assert!(matches!(visible_channel(nsfw.clone(), member, false), Err(ChannelError::NsfwRestricted)));
+        // Without VIEW_CHANNEL it stays hidden rather than restricted
+        assert!(matches!(visible_channel(nsfw, Permissions::SEND_MESSAGES, true), Err(ChannelError::NotFound)));
+    }
+
+    #[test]
+    fn test_overwrite_cache_invalidation_scope() {
+        // Member overwrites only change that member's permissions
+        assert_eq!(OverwriteScope::of("member", 7), OverwriteScope::Member(7));
+        // Role overwrites can change anyone's
+        assert_eq!(OverwriteScope::of("role", 7), OverwriteScope::AllMembers);
+    }
+
+    #[tokio::test]
+    async fn test_overwrite_changes_drop_cached_permissions() {
+        use crate::infrastructure::cache::failing_cache::FailingCache;
+        use crate::infrastructure::cache::{CachedChannelPermissions, InMemoryCache};
+
+        let cache = PermissionCacheService::with_cache(InMemoryCache::new());
+        for (channel_id, user_id) in [(1, 7), (1, 8), (2, 7)] {
+            let cached = CachedChannelPermissions {
+                user_id,
+                channel_id,
+                guild_id: 100,
+                permissions: Permissions::VIEW_CHANNEL as u64,
+                can_view: true,
+                can_send: false,
+                can_manage: false,
+            };
+            cache.set_channel_permissions(100, channel_id, user_id, &cached).await.unwrap();
+        }
+        let cached = |channel_id, user_id| {
+            let cache = cache.clone();
+            async move { cache.get_channel_permissions(100, channel_id, user_id).await.unwrap().is_some() }
+        };
+
+        invalidate_overwrite(Some(&cache), 100, 1, OverwriteScope::of("member", 7)).await;
+        assert!(!cached(1, 7).await);
+        assert!(cached(1, 8).await);
+
+        invalidate_overwrite(Some(&cache), 100, 1, OverwriteScope::of("role", 100)).await;
+        assert!(!cached(1, 8).await);
+        // Other channels keep their entries
+        assert!(cached(2, 7).await);
+
+        // An unavailable cache doesn't fail the overwrite change
+        let unavailable = PermissionCacheService::with_cache(FailingCache::default());
+        invalidate_overwrite(Some(&unavailable), 100, 1, OverwriteScope::AllMembers).await;
+    }
+
+    #[test]
+    fn test_exempt_roles_must_belong_to_the_guild() {
+        let roles: Vec<Role> = [100, 101, 102]
+            .into_iter()
+            .map(|id| Role { id, server_id: 100, ..Default::default() })
+            .collect();
+
+        assert_eq!(validate_exempt_roles(vec![102, 101, 102], &roles).unwrap(), vec![101, 102]);
+        assert!(validate_exempt_roles(Vec::new(), &[]).unwrap().is_empty());
+        assert!(matches!(
+            validate_exempt_roles(vec![101, 999], &roles),
+            Err(ChannelError::InvalidExemptRole)
+        ));
+        // DM channels have no roles to exempt
+        assert!(matches!(validate_exempt_roles(vec![101], &[]), Err(ChannelError::InvalidExemptRole)));
+    }
+
+    #[cfg(feature = "db-tests")]
+    mod db {
+        use super::*;
+        use crate::domain::Invite;
+        use crate::infrastructure::repositories::{
+            test_db, PgChannelRepository, PgMemberRepository, PgRoleRepository,
+            PgServerRepository, PgUserRepository,
+        };
+
+        fn service(pool: sqlx::PgPool) -> impl ChannelService {
+            ChannelServiceImpl::new(
+                Arc::new(PgChannelRepository::new(pool.clone())),
+                Arc::new(PgServerRepository::new(pool.clone())),
+                Arc::new(PgMemberRepository::new(pool.clone())),
+                Arc::new(PgRoleRepository::new(pool.clone())),
+                Arc::new(PgUserRepository::new(pool)),
+                Arc::new(SnowflakeGenerator::new(1, 1)),
+            )
+        }
+
+        fn create_request(parent_id: Option<i64>, sync_permissions: bool) -> CreateChannelDto {
+            CreateChannelDto {
+                name: "new".to_string(),
+                channel_type: None,
+                topic: None,
+                parent_id,
+                position: None,
+                nsfw: None,
+                sync_permissions,
+            }
+        }
+
+        /// A category in the guild with a role and a member overwrite
+        async fn category(pool: &sqlx::PgPool, server: i64, member: i64) -> i64 {
+            let category = test_db::channel(pool, server).await;
+            sqlx::query("UPDATE channels SET type = 'category' WHERE id = $1")
+                .bind(category)
+                .execute(pool)
+                .await
+                .unwrap();
+            test_db::overwrite(pool, category, "role", server, 0, Permissions::VIEW_CHANNEL).await;
+            test_db::overwrite(pool, category, "member", member, Permissions::VIEW_CHANNEL, 0).await;
+            category
+        }
+
+        async fn overwrites(pool: &sqlx::PgPool, channel_id: i64) -> Vec<(String, i64, i64, i64)> {
+            sqlx::query_as(
+                "SELECT target_type, target_id, allow, deny FROM channel_permission_overwrites \
+                 WHERE channel_id = $1 ORDER BY target_type DESC",
+            )
+            .bind(channel_id)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+        }
+
+        async fn set_position(pool: &sqlx::PgPool, channel_id: i64, position: i32) {
+            sqlx::query("UPDATE channels SET position = $2 WHERE id = $1")
+                .bind(channel_id)
+                .bind(position)
+                .execute(pool)
+                .await
+                .unwrap();
+        }
+
+        #[tokio::test]
+        async fn test_clone_channel_copies_overwrites_after_the_source() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let member = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            test_db::member(&pool, server, member).await;
+            test_db::set_permissions(&pool, server, Permissions::DEFAULT & !Permissions::MANAGE_CHANNELS).await;
+            let source = test_db::channel(&pool, server).await;
+            let next = test_db::channel(&pool, server).await;
+            set_position(&pool, next, 1).await;
+            test_db::overwrite(&pool, source, "role", server, 0, Permissions::SEND_MESSAGES).await;
+            let service = service(pool.clone());
+
+            let denied = service.clone_channel(source, member, "copy".to_string()).await;
+            assert!(matches!(denied, Err(ChannelError::Forbidden)));
+
+            // A member overwrite on the source is enough
+            test_db::overwrite(&pool, source, "member", member, Permissions::MANAGE_CHANNELS, 0).await;
+            let cloned = service.clone_channel(source, member, "copy".to_string()).await.unwrap();
+
+            let clone_id: i64 = cloned.id.parse().unwrap();
+            let copied: Vec<(String, i64, i64, i64)> = sqlx::query_as(
+                "SELECT target_type, target_id, allow, deny FROM channel_permission_overwrites \
+                 WHERE channel_id = $1 ORDER BY target_type DESC",
+            )
+            .bind(clone_id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+            assert_eq!(
+                copied,
+                vec![
+                    ("role".to_string(), server, 0, Permissions::SEND_MESSAGES),
+                    ("member".to_string(), member, Permissions::MANAGE_CHANNELS, 0),
+                ]
+            );
+
+            let positions: Vec<(i64, i32)> =
+                sqlx::query_as("SELECT id, position FROM channels WHERE server_id = $1 ORDER BY position")
+                    .bind(server)
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(positions, vec![(source, 0), (clone_id, 1), (next, 2)]);
+        }
+
+        #[tokio::test]
+        async fn test_synced_channel_inherits_its_category_overwrites() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let member = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            test_db::member(&pool, server, member).await;
+            let category = category(&pool, server, member).await;
+            let service = service(pool.clone());
+
+            let synced = service.create_channel(server, owner, create_request(Some(category), true)).await.unwrap();
+            assert_eq!(overwrites(&pool, synced.id.parse().unwrap()).await, overwrites(&pool, category).await);
+
+            // Opting out, or having no category, starts without overwrites
+            for request in [create_request(Some(category), false), create_request(None, true)] {
+                let created = service.create_channel(server, owner, request).await.unwrap();
+                assert!(overwrites(&pool, created.id.parse().unwrap()).await.is_empty());
+            }
+        }
+
+        #[tokio::test]
+        async fn test_sync_to_category_replaces_the_channel_overwrites() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let member = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            test_db::member(&pool, server, member).await;
+            let category = category(&pool, server, member).await;
+            let channel = test_db::channel(&pool, server).await;
+            sqlx::query("UPDATE channels SET parent_id = $2 WHERE id = $1")
+                .bind(channel)
+                .bind(category)
+                .execute(&pool)
+                .await
+                .unwrap();
+            test_db::overwrite(&pool, channel, "role", server, 0, Permissions::SEND_MESSAGES).await;
+            let service = service(pool.clone());
+
+            // Members who can't manage the overwrites can't sync them
+            let denied = service.sync_to_category(channel, member).await;
+            assert!(matches!(denied, Err(ChannelError::Forbidden)));
+
+            service.sync_to_category(channel, owner).await.unwrap();
+            assert_eq!(overwrites(&pool, channel).await, overwrites(&pool, category).await);
+
+            let top_level = test_db::channel(&pool, server).await;
+            let result = service.sync_to_category(top_level, owner).await;
+            assert!(matches!(result, Err(ChannelError::NotInCategory)));
+        }
+
+        #[tokio::test]
+        async fn test_open_dm_adds_both_recipients_and_is_reused() {
+            let pool = test_db::pool().await;
+            let alice = test_db::user(&pool).await;
+            let bob = test_db::user(&pool).await;
+            let service = service(pool.clone());
+
+            let opened = service.open_dm(alice, bob).await.unwrap();
+            let dm: i64 = opened.id.parse().unwrap();
+
+            let mut recipients = PgChannelRepository::new(pool).find_recipient_ids(dm).await.unwrap();
+            recipients.sort_unstable();
+            let mut expected = vec![alice, bob];
+            expected.sort_unstable();
+            assert_eq!(recipients, expected);
+
+            // Either side opening it again gets the same channel
+            assert_eq!(service.open_dm(bob, alice).await.unwrap().id, opened.id);
+
+            assert!(matches!(service.open_dm(alice, alice).await, Err(ChannelError::InvalidRecipient)));
+            assert!(matches!(
+                service.open_dm(alice, test_db::id()).await,
+                Err(ChannelError::RecipientNotFound)
+            ));
+        }
+
+        #[tokio::test]
+        async fn test_deleting_a_category_moves_children_and_drops_invites() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let member = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            test_db::member(&pool, server, member).await;
+            let category = test_db::channel(&pool, server).await;
+            let children = [test_db::channel(&pool, server).await, test_db::channel(&pool, server).await];
+            let other = test_db::channel(&pool, server).await;
+            sqlx::query("UPDATE channels SET type = 'category' WHERE id = $1")
+                .bind(category)
+                .execute(&pool)
+                .await
+                .unwrap();
+            sqlx::query("UPDATE channels SET parent_id = $1 WHERE id = ANY($2)")
+                .bind(category)
+                .bind(&children[..])
+                .execute(&pool)
+                .await
+                .unwrap();
+            let (category_invite, other_invite) = (Invite::generate_code(), Invite::generate_code());
+            for (code, channel_id) in [(&category_invite, category), (&other_invite, other)] {
+                sqlx::query("INSERT INTO invites (code, server_id, channel_id) VALUES ($1, $2, $3)")
+                    .bind(code)
+                    .bind(server)
+                    .bind(channel_id)
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+            }
+            let service = service(pool.clone());
+
+            assert!(matches!(service.delete_channel(category, member).await, Err(ChannelError::Forbidden)));
+
+            service.delete_channel(category, owner).await.unwrap();
+
+            let parents: Vec<Option<i64>> =
+                sqlx::query_scalar("SELECT parent_id FROM channels WHERE id = ANY($1)")
+                    .bind(&children[..])
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(parents, vec![None, None]);
+            let invites: Vec<String> = sqlx::query_scalar("SELECT code FROM invites WHERE server_id = $1")
+                .bind(server)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+            assert_eq!(invites, vec![other_invite]);
+
+            assert!(matches!(service.delete_channel(category, owner).await, Err(ChannelError::NotFound)));
+        }
+
+        #[tokio::test]
+        async fn test_deleting_a_dm_closes_it_for_the_caller_only() {
+            let pool = test_db::pool().await;
+            let alice = test_db::user(&pool).await;
+            let bob = test_db::user(&pool).await;
+            let outsider = test_db::user(&pool).await;
+            let service = service(pool.clone());
+            let dm: i64 = service.open_dm(alice, bob).await.unwrap().id.parse().unwrap();
+            let closed = |user_id: i64| {
+                sqlx::query_scalar::<_, bool>(
+                    "SELECT closed_at IS NOT NULL FROM dm_recipients WHERE channel_id = $1 AND user_id = $2",
+                )
+                .bind(dm)
+                .bind(user_id)
+                .fetch_one(&pool)
+            };
+
+            assert!(matches!(service.delete_channel(dm, outsider).await, Err(ChannelError::Forbidden)));
+
+            service.delete_channel(dm, alice).await.unwrap();
+            assert!(closed(alice).await.unwrap());
+            assert!(!closed(bob).await.unwrap());
+            assert_eq!(service.get_channel(dm, bob).await.unwrap().id, dm.to_string());
+
+            // Opening it again brings the same DM back
+            assert_eq!(service.open_dm(alice, bob).await.unwrap().id, dm.to_string());
+            assert!(!closed(alice).await.unwrap());
+        }
+
+        #[tokio::test]
+        async fn test_dm_recipients_get_the_dm_permission_set() {
+            let pool = test_db::pool().await;
+            let alice = test_db::user(&pool).await;
+            let bob = test_db::user(&pool).await;
+            let outsider = test_db::user(&pool).await;
+            let service = service(pool.clone());
+            let dm: i64 = service.open_dm(alice, bob).await.unwrap().id.parse().unwrap();
+
+            let channel_repo = PgChannelRepository::new(pool.clone());
+            let server_repo = PgServerRepository::new(pool.clone());
+            let member_repo = PgMemberRepository::new(pool.clone());
+            let role_repo = PgRoleRepository::new(pool.clone());
+            let channel = channel_repo.find_by_id(dm).await.unwrap().unwrap();
+            let permissions = |user_id| {
+                permission_resolver::channel_permissions(
+                    &server_repo,
+                    &member_repo,
+                    &role_repo,
+                    &channel_repo,
+                    &channel,
+                    user_id,
+                )
+            };
+            assert_eq!(permissions(bob).await.unwrap(), Some(Permissions::DM_PARTICIPANT));
+            assert_eq!(permissions(outsider).await.unwrap(), Some(0));
+
+            assert_eq!(service.get_channel(dm, bob).await.unwrap().id, dm.to_string());
+            assert!(matches!(service.get_channel(dm, outsider).await, Err(ChannelError::NotFound)));
+        }
+    }
 }
@@ -0,0 +1,147 @@
+//! Content Filter
+//!
+//! Pluggable hook for automod and content scanning. The message service
+//! runs the configured filter on every new message before it is stored.
+
+use async_trait::async_trait;
+
+use crate::domain::{Attachment, Embed};
+use crate::shared::error::AppError;
+
+/// Message about to be stored, as seen by a content filter
+#[derive(Debug, Clone)]
+pub struct MessageContext {
+    pub channel_id: i64,
+    /// None for DM channels
+    pub guild_id: Option<i64>,
+    pub author_id: i64,
+    pub content: String,
+    pub attachments: Vec<Attachment>,
+    pub embeds: Vec<Embed>,
+}
+
+/// Outcome of a content filter check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterDecision {
+    /// Store the message
+    Allow,
+    /// Reject the message
+    Block { reason: String },
+    /// Store the message, but log it for moderator review
+    Flag,
+}
+
+/// Content filter trait, implemented by automod/scanning integrations
+#[async_trait]
+pub trait ContentFilter: Send + Sync {
+    /// Decide whether a message may be stored
+    async fn check(&self, ctx: &MessageContext) -> Result<FilterDecision, AppError>;
+}
+
+/// Filter that allows every message (the default)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopContentFilter;
+
+#[async_trait]
+impl ContentFilter for NoopContentFilter {
+    async fn check(&self, _ctx: &MessageContext) -> Result<FilterDecision, AppError> {
+        Ok(FilterDecision::Allow)
+    }
+}
+
+/// Example filter blocking messages that contain any of a set of keywords
+///
+/// Matching is case-insensitive and covers the content plus embed titles
+/// and descriptions.
+#[derive(Debug, Clone)]
+pub struct KeywordFilter {
+    keywords: Vec<String>,
+}
+
+impl KeywordFilter {
+    /// Create a filter for the given keywords
+    pub fn new<I, S>(keywords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            keywords: keywords
+                .into_iter()
+                .map(|k| k.into().to_lowercase())
+                .filter(|k| !k.is_empty())
+                .collect(),
+        }
+    }
+
+    /// First blocked keyword found in the text, if any
+    fn find_keyword(&self, text: &str) -> Option<&str> {
+        let text = text.to_lowercase();
+        self.keywords
+            .iter()
+            .find(|keyword| text.contains(keyword.as_str()))
+            .map(String::as_str)
+    }
+}
+
+#[async_trait]
+impl ContentFilter for KeywordFilter {
+    async fn check(&self, ctx: &MessageContext) -> Result<FilterDecision, AppError> {
+        let embed_text = ctx
+            .embeds
+            .iter()
+            .flat_map(|e| [e.title.as_deref(), e.description.as_deref()])
+            .flatten();
+
+        let blocked = std::iter::once(ctx.content.as_str())
+            .chain(embed_text)
+            .find_map(|text| self.find_keyword(text));
+
+        Ok(match blocked {
+            Some(keyword) => FilterDecision::Block {
+                reason: format!("Message contains blocked keyword \"{}\"", keyword),
+            },
+            None => FilterDecision::Allow,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(content: &str) -> MessageContext {
+        MessageContext {
+            channel_id: 1,
+            guild_id: Some(2),
+            author_id: 3,
+            content: content.to_string(),
+            attachments: Vec::new(),
+            embeds: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_keyword_filter_is_case_insensitive() {
+        let filter = KeywordFilter::new(["spam"]);
+
+        let decision = filter.check(&context("Buy SPAM now")).await.unwrap();
+        assert!(matches!(decision, FilterDecision::Block { .. }));
+
+        let decision = filter.check(&context("hello")).await.unwrap();
+        assert_eq!(decision, FilterDecision::Allow);
+    }
+
+    #[tokio::test]
+    async fn test_keyword_filter_checks_embeds() {
+        let filter = KeywordFilter::new(["spam"]);
+        let mut ctx = context("");
+        ctx.embeds.push(Embed {
+            description: Some("spam inside".to_string()),
+            ..Default::default()
+        });
+
+        let decision = filter.check(&ctx).await.unwrap();
+        assert!(matches!(decision, FilterDecision::Block { .. }));
+    }
+}
@@ -2,16 +2,26 @@
 //!
 //! Handles guild/server management operations.
 
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
+use super::channel_service::ChannelDto;
+use super::permission_resolver::GuildPermissions;
 use crate::domain::{
-    Channel, ChannelRepository, ChannelType, Member, MemberRepository,
-    Role, RoleRepository, Server, ServerRepository,
+    Channel, ChannelRepository, ChannelType, DefaultMessageNotifications, ExplicitContentFilter,
+    Member, MemberOrder, MemberRepository, PermissionOverwrite, Role, RoleRepository, Server,
+    ServerRepository,
 };
 use crate::domain::value_objects::Permissions;
+use crate::infrastructure::cache::{
+    Cache, DistributedLock, GuildCache, GuildCounts, GuildCountsCache, RedisCache, SessionCacheService,
+};
+use crate::shared::error::AppError;
 use crate::shared::snowflake::SnowflakeGenerator;
 
 /// Guild service trait
@@ -23,19 +33,33 @@ pub trait GuildService: Send + Sync {
     /// Get guild by ID
     async fn get_guild(&self, guild_id: i64) -> Result<GuildDto, GuildError>;
 
-    /// Update guild settings
+    /// Public preview of a discoverable guild, available without membership
+    async fn get_guild_preview(&self, guild_id: i64) -> Result<GuildPreviewDto, GuildError>;
+
+    /// Update guild settings. Only fields present in `update` change.
+    ///
+    /// Requires MANAGE_GUILD.
     async fn update_guild(&self, guild_id: i64, actor_id: i64, update: UpdateGuildDto) -> Result<GuildDto, GuildError>;
 
     /// Delete guild
     async fn delete_guild(&self, guild_id: i64, actor_id: i64) -> Result<(), GuildError>;
 
-    /// Get guild members
-    async fn get_members(&self, guild_id: i64, after: Option<i64>, limit: i32) -> Result<Vec<MemberDto>, GuildError>;
+    /// Get guild members in `order`, starting after the member `after`
+    async fn get_members(
+        &self,
+        guild_id: i64,
+        order: MemberOrder,
+        after: Option<i64>,
+        limit: i32,
+    ) -> Result<Vec<MemberDto>, GuildError>;
 
     /// Join a guild (via invite)
     async fn join_guild(&self, guild_id: i64, user_id: i64) -> Result<MemberDto, GuildError>;
 
-    /// Leave a guild
+    /// Leave a guild, removing the membership and its roles.
+    ///
+    /// The owner must transfer ownership first; a guild is only left by its
+    /// owner when it is deleted.
     async fn leave_guild(&self, guild_id: i64, user_id: i64) -> Result<(), GuildError>;
 
     /// Kick a member
@@ -43,6 +67,9 @@ pub trait GuildService: Send + Sync {
 
     /// Transfer ownership
     async fn transfer_ownership(&self, guild_id: i64, owner_id: i64, new_owner_id: i64) -> Result<(), GuildError>;
+
+    /// Get the channels the actor can view, ordered as a category tree
+    async fn get_guild_channels(&self, server_id: i64, actor_id: i64) -> Result<Vec<ChannelDto>, GuildError>;
 }
 
 /// Create guild request
@@ -51,40 +78,90 @@ pub struct CreateGuildDto {
     pub name: String,
     pub icon_url: Option<String>,
     pub description: Option<String>,
+    /// Client-chosen value identifying this create; repeats of it are
+    /// rejected for a short while
+    pub nonce: Option<String>,
 }
 
 /// Guild data transfer object
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuildDto {
     pub id: String,
     pub name: String,
     pub owner_id: String,
     pub icon_url: Option<String>,
     pub description: Option<String>,
+    pub system_channel_id: Option<String>,
+    pub afk_channel_id: Option<String>,
+    pub default_message_notifications: DefaultMessageNotifications,
+    pub explicit_content_filter: ExplicitContentFilter,
+    pub auto_role_id: Option<String>,
+    pub features: Vec<String>,
     pub member_count: i64,
     pub created_at: String,
 }
 
 impl GuildDto {
     pub fn from_server(server: Server, member_count: i64) -> Self {
+        let features = server.features();
         Self {
             id: server.id.to_string(),
             name: server.name,
             owner_id: server.owner_id.to_string(),
             icon_url: server.icon_url,
             description: server.description,
+            system_channel_id: server.system_channel_id.map(|id| id.to_string()),
+            afk_channel_id: server.afk_channel_id.map(|id| id.to_string()),
+            default_message_notifications: server.default_message_notifications,
+            explicit_content_filter: server.explicit_content_filter,
+            auto_role_id: server.auto_role_id.map(|id| id.to_string()),
+            features,
             member_count,
             created_at: server.created_at.to_rfc3339(),
         }
     }
 }
 
+/// Public guild preview
+#[derive(Debug, Clone)]
+pub struct GuildPreviewDto {
+    pub id: String,
+    pub name: String,
+    pub icon_url: Option<String>,
+    pub description: Option<String>,
+    pub approximate_member_count: i64,
+    pub approximate_presence_count: i64,
+    pub features: Vec<String>,
+}
+
+impl GuildPreviewDto {
+    pub fn from_server(server: Server, counts: GuildCounts) -> Self {
+        Self {
+            id: server.id.to_string(),
+            features: server.features(),
+            name: server.name,
+            icon_url: server.icon_url,
+            description: server.description,
+            approximate_member_count: counts.member_count,
+            approximate_presence_count: counts.online_count,
+        }
+    }
+}
+
 /// Update guild request
+///
+/// `None` leaves a field unchanged; `Some(None)` clears a nullable field.
 #[derive(Debug, Clone, Default)]
 pub struct UpdateGuildDto {
     pub name: Option<String>,
-    pub icon_url: Option<String>,
-    pub description: Option<String>,
+    pub icon_url: Option<Option<String>>,
+    pub description: Option<Option<String>>,
+    pub system_channel_id: Option<Option<i64>>,
+    pub afk_channel_id: Option<Option<i64>>,
+    pub default_message_notifications: Option<DefaultMessageNotifications>,
+    pub explicit_content_filter: Option<ExplicitContentFilter>,
+    pub auto_role_id: Option<Option<i64>>,
+    pub discoverable: Option<bool>,
 }
 
 /// Member data transfer object
@@ -121,29 +198,364 @@ pub enum GuildError {
     #[error("Already a member")]
     AlreadyMember,
 
-    #[error("Cannot leave as owner")]
-    CannotLeaveAsOwner,
+    #[error("Guild owner must transfer ownership before leaving")]
+    OwnerCannotLeave,
 
     #[error("Member not found")]
     MemberNotFound,
 
+    #[error("Guild name must be 2-100 characters")]
+    InvalidName,
+
+    #[error("Invalid icon URL")]
+    InvalidIconUrl,
+
+    #[error("System channel must be a text channel in this guild")]
+    InvalidSystemChannel,
+
+    #[error("AFK channel must be a voice channel in this guild")]
+    InvalidAfkChannel,
+
+    #[error("Auto role must be a role in this guild other than @everyone")]
+    InvalidAutoRole,
+
+    #[error("Guild is not discoverable")]
+    NotDiscoverable,
+
+    #[error("A guild create with this nonce was already submitted")]
+    DuplicateCreate,
+
     #[error("Internal error: {0}")]
     Internal(String),
+
+    /// No database connection was free in time; the request may succeed on retry
+    #[error("Service unavailable")]
+    Unavailable,
+}
+
+impl From<AppError> for GuildError {
+    fn from(e: AppError) -> Self {
+        if e.is_unavailable() {
+            GuildError::Unavailable
+        } else {
+            GuildError::Internal(e.to_string())
+        }
+    }
+}
+
+/// Minimum guild name length in characters
+pub const MIN_GUILD_NAME_LENGTH: usize = 2;
+
+/// Maximum guild name length in characters
+pub const MAX_GUILD_NAME_LENGTH: usize = 100;
+
+/// Maximum icon URL length
+const MAX_ICON_URL_LENGTH: usize = 2048;
+
+/// Seconds a create nonce is held, rejecting repeats of it
+pub const GUILD_CREATE_NONCE_TTL_SECS: u64 = 30;
+
+/// Validate a guild name, returning it trimmed.
+fn validate_guild_name(name: &str) -> Result<String, GuildError> {
+    let name = name.trim();
+    let len = name.chars().count();
+    if !(MIN_GUILD_NAME_LENGTH..=MAX_GUILD_NAME_LENGTH).contains(&len) {
+        return Err(GuildError::InvalidName);
+    }
+    Ok(name.to_string())
+}
+
+/// Validate an icon URL: an absolute `http(s)` URL with a host and no
+/// whitespace.
+fn validate_icon_url(url: &str) -> Result<(), GuildError> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or(GuildError::InvalidIconUrl)?;
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+
+    if host.is_empty() || url.len() > MAX_ICON_URL_LENGTH || url.chars().any(char::is_whitespace) {
+        return Err(GuildError::InvalidIconUrl);
+    }
+    Ok(())
+}
+
+/// Run `create` at most once per owner and nonce while the nonce's lock
+/// lives, so a double-submitted create makes one guild.
+///
+/// Creates without a nonce or lock always run. If the lock can't be
+/// reached the create runs anyway, and a failed create releases the nonce
+/// so it can be retried.
+async fn create_once<C, F, Fut, T>(
+    lock: Option<&DistributedLock<C>>,
+    owner_id: i64,
+    nonce: Option<&str>,
+    create: F,
+) -> Result<T, GuildError>
+where
+    C: Cache,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, GuildError>>,
+{
+    let (Some(lock), Some(nonce)) = (lock, nonce) else {
+        return create().await;
+    };
+
+    let name = format!("guild_create:{}:{}", owner_id, nonce);
+    let token = match lock.try_acquire(&name, GUILD_CREATE_NONCE_TTL_SECS).await {
+        Ok(Some(token)) => token,
+        Ok(None) => return Err(GuildError::DuplicateCreate),
+        Err(e) => {
+            tracing::warn!(owner_id, error = %e, "Failed to lock guild create nonce");
+            return create().await;
+        }
+    };
+
+    let result = create().await;
+    if result.is_err() {
+        if let Err(e) = lock.release(&name, &token).await {
+            tracing::warn!(owner_id, error = %e, "Failed to release guild create nonce");
+        }
+    }
+    result
+}
+
+/// Read a guild through its cache entry, loading and caching it on a miss.
+///
+/// Cache failures only cost a reload.
+async fn cached_guild<C, F, Fut>(cache: Option<&GuildCache<C>>, guild_id: i64, load: F) -> Result<GuildDto, GuildError>
+where
+    C: Cache,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<GuildDto, GuildError>>,
+{
+    let Some(cache) = cache else {
+        return load().await;
+    };
+
+    match cache.get(guild_id).await {
+        Ok(Some(guild)) => return Ok(guild),
+        Ok(None) => {}
+        Err(e) => tracing::warn!(guild_id, error = %e, "Failed to read cached guild"),
+    }
+
+    let guild = load().await?;
+    if let Err(e) = cache.set(guild_id, &guild).await {
+        tracing::warn!(guild_id, error = %e, "Failed to cache guild");
+    }
+    Ok(guild)
+}
+
+/// Drop a guild's cached entry after it changed.
+///
+/// If the cache can't be reached the stale entry expires on its own.
+async fn forget_guild<C: Cache>(cache: Option<&GuildCache<C>>, guild_id: i64) {
+    if let Some(cache) = cache {
+        if let Err(e) = cache.invalidate(guild_id).await {
+            tracing::warn!(guild_id, error = %e, "Failed to invalidate cached guild");
+        }
+    }
+}
+
+/// Drop a guild's cached counts after its membership changed.
+///
+/// If the cache can't be reached the stale counts expire on their own.
+async fn forget_counts<C: Cache>(cache: Option<&GuildCountsCache<C>>, guild_id: i64) {
+    if let Some(cache) = cache {
+        if let Err(e) = cache.invalidate(guild_id).await {
+            tracing::warn!(guild_id, error = %e, "Failed to invalidate cached guild counts");
+        }
+    }
+}
+
+/// Check that a referenced channel exists in the guild and has the
+/// expected type.
+fn check_channel_reference(
+    channel: Option<&Channel>,
+    guild_id: i64,
+    channel_type: ChannelType,
+    error: GuildError,
+) -> Result<(), GuildError> {
+    match channel {
+        Some(c) if c.server_id == Some(guild_id) && c.channel_type == channel_type => Ok(()),
+        _ => Err(error),
+    }
+}
+
+/// Check that a role can be given to joining members: it must belong to
+/// the guild and not be @everyone, which every member holds implicitly.
+fn check_auto_role(role: Option<&Role>, guild_id: i64) -> Result<(), GuildError> {
+    match role {
+        Some(r) if r.server_id == guild_id && r.id != guild_id => Ok(()),
+        _ => Err(GuildError::InvalidAutoRole),
+    }
+}
+
+/// Roles a member joining the guild starts with.
+///
+/// `auto_role` is the guild's auto role as loaded; an auto role that no
+/// longer passes [`check_auto_role`] is skipped rather than blocking the join.
+fn initial_member_roles(server: &Server, auto_role: Option<&Role>) -> Vec<i64> {
+    match (server.auto_role_id, auto_role) {
+        (Some(role_id), Some(role))
+            if role.id == role_id && check_auto_role(Some(role), server.id).is_ok() =>
+        {
+            vec![role_id]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Membership created for a user joining the guild at `now`
+fn joining_member(server: &Server, user_id: i64, auto_role: Option<&Role>, now: DateTime<Utc>) -> Member {
+    Member {
+        server_id: server.id,
+        user_id,
+        nickname: None,
+        joined_at: now,
+        roles: initial_member_roles(server, auto_role),
+        communication_disabled_until: None,
+    }
+}
+
+/// Apply the fields present in a validated update to a server.
+fn apply_guild_update(server: &mut Server, update: UpdateGuildDto) {
+    if let Some(name) = update.name {
+        server.name = name;
+    }
+    if let Some(icon_url) = update.icon_url {
+        server.icon_url = icon_url;
+    }
+    if let Some(description) = update.description {
+        server.description = description;
+    }
+    if let Some(system_channel_id) = update.system_channel_id {
+        server.system_channel_id = system_channel_id;
+    }
+    if let Some(afk_channel_id) = update.afk_channel_id {
+        server.afk_channel_id = afk_channel_id;
+    }
+    if let Some(level) = update.default_message_notifications {
+        server.default_message_notifications = level;
+    }
+    if let Some(level) = update.explicit_content_filter {
+        server.explicit_content_filter = level;
+    }
+    if let Some(auto_role_id) = update.auto_role_id {
+        server.auto_role_id = auto_role_id;
+    }
+    if let Some(discoverable) = update.discoverable {
+        server.discoverable = discoverable;
+    }
+}
+
+/// Only discoverable guilds can be previewed by non-members.
+fn check_discoverable(server: &Server) -> Result<(), GuildError> {
+    if server.discoverable {
+        Ok(())
+    } else {
+        Err(GuildError::NotDiscoverable)
+    }
+}
+
+/// Count a guild's members, and how many of them are online by presence.
+///
+/// Without a presence source nobody is counted as online.
+async fn count_guild_members<M, P>(
+    member_repo: &M,
+    presence: Option<&SessionCacheService<P>>,
+    guild_id: i64,
+) -> Result<GuildCounts, AppError>
+where
+    M: MemberRepository + ?Sized,
+    P: Cache,
+{
+    let member_count = member_repo.count_by_server(guild_id).await?;
+    let online_count = match presence {
+        Some(presence) => presence.count_guild_online(guild_id).await? as i64,
+        None => 0,
+    };
+
+    Ok(GuildCounts { member_count, online_count })
+}
+
+/// Sort key for a channel tree entry:
+/// (category (position, id), category-before-children rank, position, id).
+type ChannelTreeKey = (Option<(i32, i64)>, u8, i32, i64);
+
+/// Order visible channels as a category tree.
+///
+/// Uncategorized channels come first, then each category followed by its
+/// children, sorted by (category position, channel position) with IDs as a
+/// tie-breaker. A category is shown only when it has a visible child, and
+/// children whose parent category does not exist are treated as uncategorized.
+fn order_channel_tree(channels: Vec<Channel>, visible: &HashSet<i64>) -> Vec<Channel> {
+    let categories: HashMap<i64, (i32, i64)> = channels
+        .iter()
+        .filter(|c| c.channel_type == ChannelType::Category)
+        .map(|c| (c.id, (c.position, c.id)))
+        .collect();
+
+    // Parent category of each visible child, if that category exists
+    let parent_of = |c: &Channel| c.parent_id.filter(|id| categories.contains_key(id));
+
+    let populated: HashSet<i64> = channels
+        .iter()
+        .filter(|c| c.channel_type != ChannelType::Category && visible.contains(&c.id))
+        .filter_map(parent_of)
+        .collect();
+
+    let mut tree: Vec<(ChannelTreeKey, Channel)> = channels
+        .into_iter()
+        .filter_map(|c| {
+            let key = if c.channel_type == ChannelType::Category {
+                if !populated.contains(&c.id) {
+                    return None;
+                }
+                (Some((c.position, c.id)), 0, 0, 0)
+            } else {
+                if !visible.contains(&c.id) {
+                    return None;
+                }
+                let group = parent_of(&c).and_then(|id| categories.get(&id).copied());
+                (group, 1, c.position, c.id)
+            };
+            Some((key, c))
+        })
+        .collect();
+
+    tree.sort_by_key(|(key, _)| *key);
+    tree.into_iter().map(|(_, c)| c).collect()
+}
+
+/// Check that a user may leave a guild; the owner has to hand the guild
+/// over first.
+fn check_can_leave(server: &Server, user_id: i64) -> Result<(), GuildError> {
+    if server.owner_id == user_id {
+        return Err(GuildError::OwnerCannotLeave);
+    }
+    Ok(())
 }
 
 /// GuildService implementation
-pub struct GuildServiceImpl<S, C, M, R>
+pub struct GuildServiceImpl<S, C, M, R, K = RedisCache, P = RedisCache>
 where
     S: ServerRepository,
     C: ChannelRepository,
     M: MemberRepository,
     R: RoleRepository,
+    K: Cache,
+    P: Cache,
 {
     server_repo: Arc<S>,
     channel_repo: Arc<C>,
     member_repo: Arc<M>,
     role_repo: Arc<R>,
     id_generator: Arc<SnowflakeGenerator>,
+    presence: Option<SessionCacheService<P>>,
+    counts_cache: Option<GuildCountsCache<K>>,
+    guild_cache: Option<GuildCache>,
+    create_lock: Option<DistributedLock>,
 }
 
 impl<S, C, M, R> GuildServiceImpl<S, C, M, R>
@@ -166,30 +578,93 @@ where
             member_repo,
             role_repo,
             id_generator,
+            presence: None,
+            counts_cache: None,
+            guild_cache: None,
+            create_lock: None,
         }
     }
 
-    async fn is_owner(&self, guild_id: i64, user_id: i64) -> Result<bool, GuildError> {
-        let server = self
-            .server_repo
-            .find_by_id(guild_id)
-            .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?
-            .ok_or(GuildError::NotFound)?;
-
-        Ok(server.owner_id == user_id)
-    }
 }
 
-#[async_trait]
-impl<S, C, M, R> GuildService for GuildServiceImpl<S, C, M, R>
+impl<S, C, M, R, K, P> GuildServiceImpl<S, C, M, R, K, P>
 where
-    S: ServerRepository + 'static,
-    C: ChannelRepository + 'static,
-    M: MemberRepository + 'static,
-    R: RoleRepository + 'static,
+    S: ServerRepository,
+    C: ChannelRepository,
+    M: MemberRepository,
+    R: RoleRepository,
+    K: Cache,
+    P: Cache,
 {
-    async fn create_guild(&self, owner_id: i64, request: CreateGuildDto) -> Result<GuildDto, GuildError> {
+    /// Count online members for previews from cached presences
+    pub fn with_presence<T: Cache>(self, presence: SessionCacheService<T>) -> GuildServiceImpl<S, C, M, R, K, T> {
+        GuildServiceImpl {
+            server_repo: self.server_repo,
+            channel_repo: self.channel_repo,
+            member_repo: self.member_repo,
+            role_repo: self.role_repo,
+            id_generator: self.id_generator,
+            presence: Some(presence),
+            counts_cache: self.counts_cache,
+            guild_cache: self.guild_cache,
+            create_lock: self.create_lock,
+        }
+    }
+
+    /// Reuse preview counts for a short while instead of recounting
+    pub fn with_counts_cache<T: Cache>(self, counts_cache: GuildCountsCache<T>) -> GuildServiceImpl<S, C, M, R, T, P> {
+        GuildServiceImpl {
+            server_repo: self.server_repo,
+            channel_repo: self.channel_repo,
+            member_repo: self.member_repo,
+            role_repo: self.role_repo,
+            id_generator: self.id_generator,
+            presence: self.presence,
+            counts_cache: Some(counts_cache),
+            guild_cache: self.guild_cache,
+            create_lock: self.create_lock,
+        }
+    }
+
+    /// Serve `get_guild` from the cache, invalidated by changes made here
+    pub fn with_guild_cache(mut self, guild_cache: GuildCache) -> Self {
+        self.guild_cache = Some(guild_cache);
+        self
+    }
+
+    /// Reject repeated creates with the same nonce while it is locked
+    pub fn with_create_lock(mut self, lock: DistributedLock) -> Self {
+        self.create_lock = Some(lock);
+        self
+    }
+
+    /// Approximate counts for a guild, from the cache when fresh.
+    ///
+    /// Cache failures only cost a recount.
+    async fn guild_counts(&self, guild_id: i64) -> Result<GuildCounts, GuildError> {
+        if let Some(cache) = &self.counts_cache {
+            match cache.get(guild_id).await {
+                Ok(Some(counts)) => return Ok(counts),
+                Ok(None) => {}
+                Err(e) => tracing::warn!(guild_id, error = %e, "Failed to read cached guild counts"),
+            }
+        }
+
+        let counts = count_guild_members(self.member_repo.as_ref(), self.presence.as_ref(), guild_id)
+            .await
+            .map_err(GuildError::from)?;
+
+        if let Some(cache) = &self.counts_cache {
+            if let Err(e) = cache.set(guild_id, &counts).await {
+                tracing::warn!(guild_id, error = %e, "Failed to cache guild counts");
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// Create a guild with its @everyone role and #general channel
+    async fn insert_guild(&self, owner_id: i64, request: CreateGuildDto) -> Result<GuildDto, GuildError> {
         let now = Utc::now();
         let server_id = self.id_generator.generate();
 
@@ -200,6 +675,12 @@ where
             owner_id,
             icon_url: request.icon_url,
             description: request.description,
+            system_channel_id: None,
+            afk_channel_id: None,
+            default_message_notifications: DefaultMessageNotifications::default(),
+            explicit_content_filter: ExplicitContentFilter::default(),
+            auto_role_id: None,
+            discoverable: false,
             created_at: now,
             updated_at: now,
         };
@@ -208,7 +689,7 @@ where
             .server_repo
             .create(&server)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+            .map_err(GuildError::from)?;
 
         // Create @everyone role (same ID as server)
         let everyone_role = Role {
@@ -227,7 +708,7 @@ where
         self.role_repo
             .create(&everyone_role)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+            .map_err(GuildError::from)?;
 
         // Create default #general text channel
         let general_channel = Channel {
@@ -240,6 +721,7 @@ where
             parent_id: None,
             nsfw: false,
             rate_limit_per_user: 0,
+            slowmode_exempt_roles: Vec::new(),
             created_at: now,
             updated_at: now,
         };
@@ -247,63 +729,216 @@ where
         self.channel_repo
             .create(&general_channel)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+            .map_err(GuildError::from)?;
 
         Ok(GuildDto::from_server(created_server, 1))
     }
 
-    async fn get_guild(&self, guild_id: i64) -> Result<GuildDto, GuildError> {
+    async fn is_owner(&self, guild_id: i64, user_id: i64) -> Result<bool, GuildError> {
         let server = self
             .server_repo
             .find_by_id(guild_id)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?
+            .map_err(GuildError::from)?
             .ok_or(GuildError::NotFound)?;
 
-        let member_count = self
+        Ok(server.owner_id == user_id)
+    }
+
+    /// Require a guild-level permission. The owner always passes.
+    async fn require_guild_permission(
+        &self,
+        server: &Server,
+        user_id: i64,
+        permission: i64,
+    ) -> Result<(), GuildError> {
+        if server.is_owner(user_id) {
+            return Ok(());
+        }
+
+        let member = self
             .member_repo
-            .count_by_server(guild_id)
+            .find(server.id, user_id)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+            .map_err(GuildError::from)?
+            .ok_or(GuildError::Forbidden)?;
 
-        Ok(GuildDto::from_server(server, member_count))
-    }
+        let roles = self
+            .role_repo
+            .find_by_server_id(server.id)
+            .await
+            .map_err(GuildError::from)?;
 
-    async fn update_guild(&self, guild_id: i64, actor_id: i64, update: UpdateGuildDto) -> Result<GuildDto, GuildError> {
-        // Check if actor is owner
-        if !self.is_owner(guild_id, actor_id).await? {
+        let permissions = GuildPermissions::new(server.id, server.owner_id, roles).base(&member);
+        if !Permissions::new(permissions).has(permission) {
             return Err(GuildError::Forbidden);
         }
+        Ok(())
+    }
+
+    async fn find_channel(&self, channel_id: i64) -> Result<Option<Channel>, GuildError> {
+        self.channel_repo
+            .find_by_id(channel_id)
+            .await
+            .map_err(GuildError::from)
+    }
+
+    /// IDs of the channels the member may view.
+    ///
+    /// Overwrites for all channels are fetched in a single query.
+    async fn viewable_channel_ids(
+        &self,
+        server: &Server,
+        member: &Member,
+        channels: &[Channel],
+    ) -> Result<HashSet<i64>, GuildError> {
+        let roles = self
+            .role_repo
+            .find_by_server_id(server.id)
+            .await
+            .map_err(GuildError::from)?;
+        let guild = GuildPermissions::new(server.id, server.owner_id, roles);
+
+        let channel_ids: Vec<i64> = channels.iter().map(|c| c.id).collect();
+        let overwrites = self
+            .channel_repo
+            .get_permission_overwrites_for_channels(&channel_ids)
+            .await
+            .map_err(GuildError::from)?;
+
+        let mut overwrites_by_channel: HashMap<i64, Vec<PermissionOverwrite>> = HashMap::new();
+        for overwrite in overwrites {
+            overwrites_by_channel
+                .entry(overwrite.channel_id)
+                .or_default()
+                .push(overwrite);
+        }
 
+        Ok(channels
+            .iter()
+            .filter(|channel| {
+                let overwrites = overwrites_by_channel
+                    .get(&channel.id)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                let permissions = guild.channel(member, channel, overwrites);
+                Permissions::new(permissions).has(Permissions::VIEW_CHANNEL)
+            })
+            .map(|channel| channel.id)
+            .collect())
+    }
+}
+
+#[async_trait]
+impl<S, C, M, R, K, P> GuildService for GuildServiceImpl<S, C, M, R, K, P>
+where
+    S: ServerRepository + 'static,
+    C: ChannelRepository + 'static,
+    M: MemberRepository + 'static,
+    R: RoleRepository + 'static,
+    K: Cache + 'static,
+    P: Cache + 'static,
+{
+    async fn create_guild(&self, owner_id: i64, request: CreateGuildDto) -> Result<GuildDto, GuildError> {
+        let nonce = request.nonce.clone();
+        create_once(self.create_lock.as_ref(), owner_id, nonce.as_deref(), || {
+            self.insert_guild(owner_id, request)
+        })
+        .await
+    }
+
+    async fn get_guild(&self, guild_id: i64) -> Result<GuildDto, GuildError> {
+        cached_guild(self.guild_cache.as_ref(), guild_id, || async {
+            let server = self
+                .server_repo
+                .find_by_id(guild_id)
+                .await
+                .map_err(GuildError::from)?
+                .ok_or(GuildError::NotFound)?;
+
+            let member_count = self
+                .member_repo
+                .count_by_server(guild_id)
+                .await
+                .map_err(GuildError::from)?;
+
+            Ok(GuildDto::from_server(server, member_count))
+        })
+        .await
+    }
+
+    async fn get_guild_preview(&self, guild_id: i64) -> Result<GuildPreviewDto, GuildError> {
+        let server = self
+            .server_repo
+            .find_by_id(guild_id)
+            .await
+            .map_err(GuildError::from)?
+            .ok_or(GuildError::NotFound)?;
+
+        check_discoverable(&server)?;
+
+        let counts = self.guild_counts(guild_id).await?;
+        Ok(GuildPreviewDto::from_server(server, counts))
+    }
+
+    async fn update_guild(&self, guild_id: i64, actor_id: i64, mut update: UpdateGuildDto) -> Result<GuildDto, GuildError> {
         let mut server = self
             .server_repo
             .find_by_id(guild_id)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?
+            .map_err(GuildError::from)?
             .ok_or(GuildError::NotFound)?;
 
-        // Apply updates
-        if let Some(name) = update.name {
-            server.name = name;
+        self.require_guild_permission(&server, actor_id, Permissions::MANAGE_GUILD)
+            .await?;
+
+        if let Some(name) = &update.name {
+            update.name = Some(validate_guild_name(name)?);
+        }
+        if let Some(Some(icon_url)) = &update.icon_url {
+            validate_icon_url(icon_url)?;
         }
-        if let Some(icon_url) = update.icon_url {
-            server.icon_url = Some(icon_url);
+        if let Some(Some(channel_id)) = update.system_channel_id {
+            let channel = self.find_channel(channel_id).await?;
+            check_channel_reference(
+                channel.as_ref(),
+                guild_id,
+                ChannelType::Text,
+                GuildError::InvalidSystemChannel,
+            )?;
         }
-        if let Some(description) = update.description {
-            server.description = Some(description);
+        if let Some(Some(channel_id)) = update.afk_channel_id {
+            let channel = self.find_channel(channel_id).await?;
+            check_channel_reference(
+                channel.as_ref(),
+                guild_id,
+                ChannelType::Voice,
+                GuildError::InvalidAfkChannel,
+            )?;
         }
+        if let Some(Some(role_id)) = update.auto_role_id {
+            let role = self
+                .role_repo
+                .find_by_id(role_id)
+                .await
+                .map_err(GuildError::from)?;
+            check_auto_role(role.as_ref(), guild_id)?;
+        }
+
+        apply_guild_update(&mut server, update);
 
         let updated = self
             .server_repo
             .update(&server)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+            .map_err(GuildError::from)?;
+        forget_guild(self.guild_cache.as_ref(), guild_id).await;
 
         let member_count = self
             .member_repo
             .count_by_server(guild_id)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+            .map_err(GuildError::from)?;
 
         Ok(GuildDto::from_server(updated, member_count))
     }
@@ -317,17 +952,24 @@ where
         self.server_repo
             .delete(guild_id)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+            .map_err(GuildError::from)?;
+        forget_guild(self.guild_cache.as_ref(), guild_id).await;
 
         Ok(())
     }
 
-    async fn get_members(&self, guild_id: i64, after: Option<i64>, limit: i32) -> Result<Vec<MemberDto>, GuildError> {
-        let members = self
-            .member_repo
-            .find_by_server_id(guild_id, after, limit)
-            .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+    async fn get_members(
+        &self,
+        guild_id: i64,
+        order: MemberOrder,
+        after: Option<i64>,
+        limit: i32,
+    ) -> Result<Vec<MemberDto>, GuildError> {
+        let members = match order {
+            MemberOrder::UserId => self.member_repo.find_by_server_id(guild_id, after, limit).await,
+            MemberOrder::JoinedAt => self.member_repo.find_by_server_id_joined(guild_id, after, limit).await,
+        }
+        .map_err(GuildError::from)?;
 
         Ok(members.into_iter().map(MemberDto::from).collect())
     }
@@ -338,40 +980,61 @@ where
             .member_repo
             .is_member(guild_id, user_id)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+            .map_err(GuildError::from)?;
 
         if is_member {
             return Err(GuildError::AlreadyMember);
         }
 
-        // Create member
-        let member = Member {
-            server_id: guild_id,
-            user_id,
-            nickname: None,
-            joined_at: Utc::now(),
-            roles: vec![],
+        let server = self
+            .server_repo
+            .find_by_id(guild_id)
+            .await
+            .map_err(GuildError::from)?
+            .ok_or(GuildError::NotFound)?;
+
+        let auto_role = match server.auto_role_id {
+            Some(role_id) => self
+                .role_repo
+                .find_by_id(role_id)
+                .await
+                .map_err(GuildError::from)?,
+            None => None,
         };
 
+        // Create member; initial roles are inserted in the same transaction
+        let member = joining_member(&server, user_id, auto_role.as_ref(), Utc::now());
+
         let created = self
             .member_repo
             .create(&member)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+            .map_err(GuildError::from)?;
+        forget_guild(self.guild_cache.as_ref(), guild_id).await;
 
         Ok(MemberDto::from(created))
     }
 
     async fn leave_guild(&self, guild_id: i64, user_id: i64) -> Result<(), GuildError> {
-        // Owner cannot leave
-        if self.is_owner(guild_id, user_id).await? {
-            return Err(GuildError::CannotLeaveAsOwner);
-        }
+        let server = self
+            .server_repo
+            .find_by_id(guild_id)
+            .await
+            .map_err(GuildError::from)?
+            .ok_or(GuildError::NotFound)?;
 
+        check_can_leave(&server, user_id)?;
+
+        // Member roles are removed with the membership
         self.member_repo
             .delete(guild_id, user_id)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+            .map_err(|e| match e {
+                AppError::NotFound(_) => GuildError::MemberNotFound,
+                e => GuildError::from(e),
+            })?;
+        forget_guild(self.guild_cache.as_ref(), guild_id).await;
+        forget_counts(self.counts_cache.as_ref(), guild_id).await;
 
         Ok(())
     }
@@ -390,7 +1053,9 @@ where
         self.member_repo
             .delete(guild_id, target_id)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+            .map_err(GuildError::from)?;
+        forget_guild(self.guild_cache.as_ref(), guild_id).await;
+        forget_counts(self.counts_cache.as_ref(), guild_id).await;
 
         Ok(())
     }
@@ -406,7 +1071,7 @@ where
             .member_repo
             .is_member(guild_id, new_owner_id)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+            .map_err(GuildError::from)?;
 
         if !is_member {
             return Err(GuildError::MemberNotFound);
@@ -415,15 +1080,525 @@ where
         self.server_repo
             .transfer_ownership(guild_id, new_owner_id)
             .await
-            .map_err(|e| GuildError::Internal(e.to_string()))?;
+            .map_err(GuildError::from)?;
+        forget_guild(self.guild_cache.as_ref(), guild_id).await;
 
         Ok(())
     }
+
+    async fn get_guild_channels(&self, server_id: i64, actor_id: i64) -> Result<Vec<ChannelDto>, GuildError> {
+        let server = self
+            .server_repo
+            .find_by_id(server_id)
+            .await
+            .map_err(GuildError::from)?
+            .ok_or(GuildError::NotFound)?;
+
+        let member = self
+            .member_repo
+            .find(server_id, actor_id)
+            .await
+            .map_err(GuildError::from)?
+            .ok_or(GuildError::Forbidden)?;
+
+        let channels = self
+            .channel_repo
+            .find_by_server_id(server_id)
+            .await
+            .map_err(GuildError::from)?;
+
+        let visible = self.viewable_channel_ids(&server, &member, &channels).await?;
+
+        Ok(order_channel_tree(channels, &visible)
+            .into_iter()
+            .map(ChannelDto::from)
+            .collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    // Tests would go here
+    fn channel(
+        id: i64,
+        channel_type: ChannelType,
+        position: i32,
+        parent_id: Option<i64>,
+    ) -> Channel {
+        let now = Utc::now();
+        Channel {
+            id,
+            server_id: Some(1),
+            name: format!("channel-{}", id),
+            channel_type,
+            topic: None,
+            position,
+            parent_id,
+            nsfw: false,
+            rate_limit_per_user: 0,
+            slowmode_exempt_roles: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn ids(channels: &[Channel]) -> Vec<i64> {
+        channels.iter().map(|c| c.id).collect()
+    }
+
+    #[test]
+    fn test_channel_tree_orders_by_category_then_position() {
+        let channels = vec![
+            channel(10, ChannelType::Category, 1, None),
+            channel(11, ChannelType::Text, 1, Some(10)),
+            channel(12, ChannelType::Text, 0, Some(10)),
+            channel(20, ChannelType::Category, 0, None),
+            channel(21, ChannelType::Voice, 0, Some(20)),
+            channel(30, ChannelType::Text, 5, None),
+        ];
+        let visible: HashSet<i64> = [10, 11, 12, 20, 21, 30].into_iter().collect();
+
+        let tree = order_channel_tree(channels, &visible);
+        assert_eq!(ids(&tree), vec![30, 20, 21, 10, 12, 11]);
+    }
+
+    #[test]
+    fn test_channel_tree_filters_hidden_channels() {
+        let channels = vec![
+            channel(10, ChannelType::Category, 0, None),
+            channel(11, ChannelType::Text, 0, Some(10)),
+            channel(12, ChannelType::Text, 1, Some(10)),
+            channel(30, ChannelType::Text, 0, None),
+        ];
+        let visible: HashSet<i64> = [10, 11].into_iter().collect();
+
+        let tree = order_channel_tree(channels, &visible);
+        assert_eq!(ids(&tree), vec![10, 11]);
+    }
+
+    #[test]
+    fn test_channel_tree_omits_categories_without_visible_children() {
+        let channels = vec![
+            channel(10, ChannelType::Category, 0, None),
+            channel(11, ChannelType::Text, 0, Some(10)),
+            channel(20, ChannelType::Category, 1, None),
+            channel(30, ChannelType::Text, 0, None),
+        ];
+        let visible: HashSet<i64> = [10, 20, 30].into_iter().collect();
+
+        let tree = order_channel_tree(channels, &visible);
+        assert_eq!(ids(&tree), vec![30]);
+    }
+
+    #[test]
+    fn test_partial_update_only_changes_name() {
+        let mut server = Server {
+            id: 1,
+            name: "Old".to_string(),
+            icon_url: Some("https://cdn.example.com/icon.png".to_string()),
+            description: Some("About".to_string()),
+            system_channel_id: Some(10),
+            ..Default::default()
+        };
+        let update = UpdateGuildDto {
+            name: Some(validate_guild_name("  New name ").unwrap()),
+            ..Default::default()
+        };
+
+        apply_guild_update(&mut server, update);
+
+        assert_eq!(server.name, "New name");
+        assert_eq!(server.icon_url.as_deref(), Some("https://cdn.example.com/icon.png"));
+        assert_eq!(server.description.as_deref(), Some("About"));
+        assert_eq!(server.system_channel_id, Some(10));
+    }
+
+    #[test]
+    fn test_partial_update_only_changes_notification_settings() {
+        let mut server = Server {
+            name: "Guild".to_string(),
+            explicit_content_filter: ExplicitContentFilter::All,
+            ..Default::default()
+        };
+        let update = UpdateGuildDto {
+            default_message_notifications: Some(DefaultMessageNotifications::Mentions),
+            ..Default::default()
+        };
+
+        apply_guild_update(&mut server, update);
+
+        assert_eq!(server.default_message_notifications, DefaultMessageNotifications::Mentions);
+        assert_eq!(server.explicit_content_filter, ExplicitContentFilter::All);
+        assert_eq!(server.name, "Guild");
+    }
+
+    #[test]
+    fn test_explicit_null_clears_field() {
+        let mut server = Server {
+            description: Some("About".to_string()),
+            system_channel_id: Some(10),
+            ..Default::default()
+        };
+        let update = UpdateGuildDto {
+            description: Some(None),
+            system_channel_id: Some(None),
+            ..Default::default()
+        };
+
+        apply_guild_update(&mut server, update);
+
+        assert_eq!(server.description, None);
+        assert_eq!(server.system_channel_id, None);
+    }
+
+    #[test]
+    fn test_guild_name_and_icon_validation() {
+        assert!(matches!(validate_guild_name("a"), Err(GuildError::InvalidName)));
+        assert!(matches!(validate_guild_name(&"a".repeat(101)), Err(GuildError::InvalidName)));
+        assert!(validate_guild_name("ab").is_ok());
+
+        assert!(validate_icon_url("https://cdn.example.com/icon.png").is_ok());
+        assert!(matches!(validate_icon_url("ftp://example.com/icon.png"), Err(GuildError::InvalidIconUrl)));
+        assert!(matches!(validate_icon_url("https:///icon.png"), Err(GuildError::InvalidIconUrl)));
+        assert!(matches!(validate_icon_url("https://example.com/my icon.png"), Err(GuildError::InvalidIconUrl)));
+    }
+
+    #[test]
+    fn test_invalid_channel_reference_is_rejected() {
+        let text = channel(10, ChannelType::Text, 0, None);
+        let voice = channel(11, ChannelType::Voice, 0, None);
+        let mut other_guild = channel(12, ChannelType::Text, 0, None);
+        other_guild.server_id = Some(2);
+
+        assert!(check_channel_reference(Some(&text), 1, ChannelType::Text, GuildError::InvalidSystemChannel).is_ok());
+        assert!(matches!(
+            check_channel_reference(None, 1, ChannelType::Text, GuildError::InvalidSystemChannel),
+            Err(GuildError::InvalidSystemChannel)
+        ));
+        assert!(matches!(
+            check_channel_reference(Some(&other_guild), 1, ChannelType::Text, GuildError::InvalidSystemChannel),
+            Err(GuildError::InvalidSystemChannel)
+        ));
+        assert!(matches!(
+            check_channel_reference(Some(&text), 1, ChannelType::Voice, GuildError::InvalidAfkChannel),
+            Err(GuildError::InvalidAfkChannel)
+        ));
+        assert!(check_channel_reference(Some(&voice), 1, ChannelType::Voice, GuildError::InvalidAfkChannel).is_ok());
+    }
+
+    fn role(id: i64, server_id: i64) -> Role {
+        Role {
+            id,
+            server_id,
+            name: "Member".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_joining_member_gets_auto_role() {
+        let server = Server {
+            id: 1,
+            auto_role_id: Some(5),
+            ..Default::default()
+        };
+
+        assert_eq!(initial_member_roles(&server, Some(&role(5, 1))), vec![5]);
+
+        // A role that has since gone missing is skipped
+        assert!(initial_member_roles(&server, None).is_empty());
+    }
+
+    #[test]
+    fn test_joining_member_without_auto_role_gets_no_roles() {
+        let server = Server {
+            id: 1,
+            ..Default::default()
+        };
+
+        assert!(initial_member_roles(&server, None).is_empty());
+        assert!(initial_member_roles(&server, Some(&role(5, 1))).is_empty());
+    }
+
+    #[test]
+    fn test_auto_role_must_be_guild_role_other_than_everyone() {
+        assert!(check_auto_role(Some(&role(5, 1)), 1).is_ok());
+        assert!(matches!(check_auto_role(None, 1), Err(GuildError::InvalidAutoRole)));
+        assert!(matches!(check_auto_role(Some(&role(5, 2)), 1), Err(GuildError::InvalidAutoRole)));
+        // @everyone shares the guild's ID
+        assert!(matches!(check_auto_role(Some(&role(1, 1)), 1), Err(GuildError::InvalidAutoRole)));
+    }
+
+    #[test]
+    fn test_owner_cannot_leave() {
+        let server = Server {
+            id: 1,
+            owner_id: 10,
+            ..Default::default()
+        };
+
+        assert!(matches!(check_can_leave(&server, 10), Err(GuildError::OwnerCannotLeave)));
+    }
+
+    #[test]
+    fn test_member_can_leave() {
+        let mut server = Server {
+            id: 1,
+            owner_id: 10,
+            ..Default::default()
+        };
+        assert!(check_can_leave(&server, 20).is_ok());
+
+        // Once ownership is transferred the former owner may leave
+        server.owner_id = 20;
+        assert!(check_can_leave(&server, 10).is_ok());
+        assert!(matches!(check_can_leave(&server, 20), Err(GuildError::OwnerCannotLeave)));
+    }
+    #[test]
+    fn test_only_discoverable_guilds_can_be_previewed() {
+        let mut server = Server {
+            id: 1,
+            ..Default::default()
+        };
+        assert!(matches!(check_discoverable(&server), Err(GuildError::NotDiscoverable)));
+
+        server.discoverable = true;
+        assert!(check_discoverable(&server).is_ok());
+        assert_eq!(server.features(), vec![Server::FEATURE_DISCOVERABLE.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_creates_with_one_nonce_make_one_guild() {
+        use crate::infrastructure::cache::InMemoryCache;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let lock = DistributedLock::with_cache(InMemoryCache::new());
+        let created = AtomicUsize::new(0);
+        let create = || async {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            Ok(created.fetch_add(1, Ordering::SeqCst))
+        };
+
+        let (first, second) = tokio::join!(
+            create_once(Some(&lock), 1, Some("abc"), create),
+            create_once(Some(&lock), 1, Some("abc"), create),
+        );
+
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+        assert!(first.is_ok() ^ second.is_ok());
+        assert!(matches!(first.and(second), Err(GuildError::DuplicateCreate)));
+
+        // Another nonce, or another owner's create, goes ahead
+        assert!(create_once(Some(&lock), 1, Some("def"), create).await.is_ok());
+        assert!(create_once(Some(&lock), 2, Some("abc"), create).await.is_ok());
+        assert_eq!(created.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_failed_create_frees_its_nonce() {
+        use crate::infrastructure::cache::InMemoryCache;
+
+        let lock = DistributedLock::with_cache(InMemoryCache::new());
+
+        let failed = create_once(Some(&lock), 1, Some("abc"), || async {
+            Err::<(), _>(GuildError::Internal("db down".into()))
+        })
+        .await;
+        assert!(matches!(failed, Err(GuildError::Internal(_))));
+
+        assert!(create_once(Some(&lock), 1, Some("abc"), || async { Ok(()) }).await.is_ok());
+        // Without a nonce nothing is deduplicated
+        assert!(create_once(Some(&lock), 1, None, || async { Ok(()) }).await.is_ok());
+        assert!(create_once(Some(&lock), 1, None, || async { Ok(()) }).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_guild_reads_are_cached_until_an_update() {
+        use crate::infrastructure::cache::InMemoryCache;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let cache = GuildCache::with_cache(InMemoryCache::new());
+        let name = std::sync::Mutex::new("Before".to_string());
+        let loads = AtomicUsize::new(0);
+        let load = || async {
+            loads.fetch_add(1, Ordering::SeqCst);
+            let server = Server {
+                id: 1,
+                name: name.lock().unwrap().clone(),
+                ..Default::default()
+            };
+            Ok(GuildDto::from_server(server, 3))
+        };
+
+        let first = cached_guild(Some(&cache), 1, load).await.unwrap();
+        let second = cached_guild(Some(&cache), 1, load).await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert_eq!(second.name, first.name);
+        assert_eq!(second.member_count, 3);
+
+        // An update busts the entry, so the next read sees it
+        *name.lock().unwrap() = "After".to_string();
+        forget_guild(Some(&cache), 1).await;
+        let updated = cached_guild(Some(&cache), 1, load).await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+        assert_eq!(updated.name, "After");
+
+        // Failed loads are not cached
+        let missing = cached_guild(Some(&cache), 2, || async { Err(GuildError::NotFound) }).await;
+        assert!(matches!(missing, Err(GuildError::NotFound)));
+        assert!(cache.get::<GuildDto>(2).await.unwrap().is_none());
+    }
+
+    #[cfg(feature = "db-tests")]
+    mod db {
+        use super::*;
+        use crate::infrastructure::repositories::{
+            test_db, PgChannelRepository, PgMemberRepository, PgRoleRepository, PgServerRepository,
+        };
+
+        fn service(pool: sqlx::PgPool) -> impl GuildService {
+            GuildServiceImpl::new(
+                Arc::new(PgServerRepository::new(pool.clone())),
+                Arc::new(PgChannelRepository::new(pool.clone())),
+                Arc::new(PgMemberRepository::new(pool.clone())),
+                Arc::new(PgRoleRepository::new(pool)),
+                Arc::new(SnowflakeGenerator::new(1, 1)),
+            )
+        }
+
+        fn rename(name: &str) -> UpdateGuildDto {
+            UpdateGuildDto {
+                name: Some(name.to_string()),
+                ..Default::default()
+            }
+        }
+
+        #[tokio::test]
+        async fn test_update_guild_counts_permissions_from_everyone() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let member = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            test_db::member(&pool, server, member).await;
+            let service = service(pool.clone());
+
+            let denied = service.update_guild(server, member, rename("renamed")).await;
+            assert!(matches!(denied, Err(GuildError::Forbidden)));
+
+            // @everyone is held implicitly, without a member_roles row
+            test_db::set_permissions(&pool, server, Permissions::MANAGE_GUILD).await;
+            let updated = service.update_guild(server, member, rename("renamed")).await.unwrap();
+            assert_eq!(updated.name, "renamed");
+        }
+
+        #[tokio::test]
+        async fn test_joined_members_are_listed_in_join_order() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let service = service(pool.clone());
+            // The higher ID joins first, so join order differs from ID order
+            let (a, b) = (test_db::user(&pool).await, test_db::user(&pool).await);
+            let (first, second) = (a.max(b), a.min(b));
+
+            service.join_guild(server, first).await.unwrap();
+            service.join_guild(server, second).await.unwrap();
+
+            let joined = service.get_members(server, MemberOrder::JoinedAt, None, 10).await.unwrap();
+            let order: Vec<String> = joined.into_iter().map(|m| m.user_id).collect();
+            assert_eq!(order, [owner, first, second].map(|id| id.to_string()));
+            let after_owner = service.get_members(server, MemberOrder::JoinedAt, Some(owner), 1).await.unwrap();
+            assert_eq!(after_owner[0].user_id, first.to_string());
+        }
+
+        #[tokio::test]
+        async fn test_leaving_removes_the_member_and_recounts() {
+            use crate::infrastructure::cache::InMemoryCache;
+
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let member = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            test_db::member(&pool, server, member).await;
+            sqlx::query("UPDATE servers SET discoverable = TRUE WHERE id = $1")
+                .bind(server)
+                .execute(&pool)
+                .await
+                .unwrap();
+            let service = GuildServiceImpl::new(
+                Arc::new(PgServerRepository::new(pool.clone())),
+                Arc::new(PgChannelRepository::new(pool.clone())),
+                Arc::new(PgMemberRepository::new(pool.clone())),
+                Arc::new(PgRoleRepository::new(pool.clone())),
+                Arc::new(SnowflakeGenerator::new(1, 1)),
+            )
+            .with_counts_cache(GuildCountsCache::with_cache(InMemoryCache::new()));
+
+            // Cache the counts before anyone leaves
+            assert_eq!(service.get_guild_preview(server).await.unwrap().approximate_member_count, 2);
+
+            service.leave_guild(server, member).await.unwrap();
+
+            let members = PgMemberRepository::new(pool);
+            assert!(!members.is_member(server, member).await.unwrap());
+            assert_eq!(service.get_guild_preview(server).await.unwrap().approximate_member_count, 1);
+
+            // The owner has to transfer the guild first
+            let result = service.leave_guild(server, owner).await;
+            assert!(matches!(result, Err(GuildError::OwnerCannotLeave)));
+            assert!(members.is_member(server, owner).await.unwrap());
+        }
+
+        #[tokio::test]
+        async fn test_preview_counts_members_and_online_presences() {
+            use crate::domain::{PresenceVisibility, UserStatus};
+            use crate::infrastructure::cache::{InMemoryCache, SessionPresence};
+
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let mut users = vec![owner];
+            for _ in 0..3 {
+                let user = test_db::user(&pool).await;
+                test_db::member(&pool, server, user).await;
+                users.push(user);
+            }
+            let presence = SessionCacheService::with_cache(InMemoryCache::new());
+            let service = GuildServiceImpl::new(
+                Arc::new(PgServerRepository::new(pool.clone())),
+                Arc::new(PgChannelRepository::new(pool.clone())),
+                Arc::new(PgMemberRepository::new(pool.clone())),
+                Arc::new(PgRoleRepository::new(pool.clone())),
+                Arc::new(SnowflakeGenerator::new(1, 1)),
+            )
+            .with_presence(presence.clone());
+
+            let result = service.get_guild_preview(server).await;
+            assert!(matches!(result, Err(GuildError::NotDiscoverable)));
+
+            sqlx::query("UPDATE servers SET discoverable = TRUE WHERE id = $1")
+                .bind(server)
+                .execute(&pool)
+                .await
+                .unwrap();
+            let statuses = [UserStatus::Online, UserStatus::Idle, UserStatus::Offline];
+            for (user_id, status) in users.iter().zip(statuses) {
+                let session = SessionPresence {
+                    status,
+                    custom_status: None,
+                    activities: vec![],
+                    updated_at: 0,
+                };
+                presence
+                    .set_presence(*user_id, "session", &session, vec![server], PresenceVisibility::Everyone)
+                    .await
+                    .unwrap();
+            }
+
+            let preview = service.get_guild_preview(server).await.unwrap();
+            assert_eq!(preview.approximate_member_count, 4);
+            assert_eq!(preview.approximate_presence_count, 2);
+        }
+    }
 }
@@ -5,11 +5,16 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use chrono::{Duration, Utc};
+use chrono::Utc;
 
-use crate::domain::{Invite, InviteRepository, MemberRepository};
+use crate::domain::{
+    Channel, ChannelRepository, Invite, InviteRepository, MemberRepository, RoleRepository, User,
+    UserRepository,
+};
+use crate::domain::value_objects::Permissions;
 use crate::infrastructure::repositories::PgInviteRepository;
-use crate::application::services::{GuildService, GuildError};
+use crate::application::services::{GuildDto, GuildService, GuildError, GuildPermissions, MemberDto};
+use crate::shared::error::AppError;
 
 /// Invite service trait defining invite operations.
 #[async_trait]
@@ -27,8 +32,15 @@ pub trait InviteService: Send + Sync {
     /// Get invite preview (server info without full details).
     async fn get_invite_preview(&self, code: &str) -> Result<InvitePreviewDto, InviteError>;
 
-    /// Get all invites for a server.
-    async fn get_server_invites(&self, server_id: i64) -> Result<Vec<InviteDto>, InviteError>;
+    /// Get a page of invites for a server, newest first.
+    ///
+    /// Requires MANAGE_GUILD.
+    async fn get_server_invites(
+        &self,
+        server_id: i64,
+        actor_id: i64,
+        query: InviteQueryDto,
+    ) -> Result<Vec<InviteDto>, InviteError>;
 
     /// Use an invite to join a server.
     async fn use_invite(&self, code: &str, user_id: i64) -> Result<UseInviteResultDto, InviteError>;
@@ -62,6 +74,15 @@ pub struct CreateInviteDto {
     pub temporary: Option<bool>,
 }
 
+/// Query parameters for listing server invites.
+#[derive(Debug, Clone, Default)]
+pub struct InviteQueryDto {
+    /// Only return invites created before this invite.
+    pub before_code: Option<String>,
+    /// Page size (default 50, max 100).
+    pub limit: Option<i32>,
+}
+
 /// Invite data transfer object.
 #[derive(Debug, Clone)]
 pub struct InviteDto {
@@ -87,12 +108,19 @@ pub struct InviteDto {
     pub created_at: String,
     /// Whether invite is currently valid.
     pub is_valid: bool,
+    /// Remaining uses (None if unlimited).
+    pub remaining_uses: Option<i32>,
+    /// Time until expiration in seconds (None if never).
+    pub expires_in: Option<i64>,
 }
 
 impl InviteDto {
     /// Create DTO from domain Invite entity.
     pub fn from_invite(invite: Invite) -> Self {
         let is_valid = invite.is_valid();
+        let remaining_uses = invite.remaining_uses();
+        let expires_in = invite.expires_in();
+        let expires_at = invite.expiry().map(|dt| dt.to_rfc3339());
         Self {
             code: invite.code,
             server_id: invite.server_id.to_string(),
@@ -102,9 +130,11 @@ impl InviteDto {
             uses: invite.uses,
             max_age: invite.max_age,
             temporary: invite.temporary,
-            expires_at: invite.expires_at.map(|dt| dt.to_rfc3339()),
+            expires_at,
             created_at: invite.created_at.to_rfc3339(),
             is_valid,
+            remaining_uses,
+            expires_in,
         }
     }
 }
@@ -122,11 +152,11 @@ pub struct InvitePreviewDto {
     pub server_icon: Option<String>,
     /// Channel ID the invite leads to.
     pub channel_id: String,
-    /// Channel name.
-    pub channel_name: String,
+    /// Channel name (None if the channel was deleted).
+    pub channel_name: Option<String>,
     /// Inviter user ID.
     pub inviter_id: Option<String>,
-    /// Inviter username.
+    /// Inviter username (None if the account was deleted).
     pub inviter_name: Option<String>,
     /// Current member count.
     pub member_count: i64,
@@ -141,6 +171,8 @@ pub struct UseInviteResultDto {
     pub server_id: String,
     /// Whether user was already a member.
     pub already_member: bool,
+    /// The new membership, including any auto role (None if already a member).
+    pub member: Option<MemberDto>,
 }
 
 /// Invite validation result.
@@ -185,8 +217,25 @@ pub enum InviteError {
     #[error("Already a member of this server")]
     AlreadyMember,
 
+    #[error("Unknown pagination cursor")]
+    InvalidCursor,
+
     #[error("Internal error: {0}")]
     Internal(String),
+
+    /// No database connection was free in time; the request may succeed on retry
+    #[error("Service unavailable")]
+    Unavailable,
+}
+
+impl From<AppError> for InviteError {
+    fn from(e: AppError) -> Self {
+        if e.is_unavailable() {
+            InviteError::Unavailable
+        } else {
+            InviteError::Internal(e.to_string())
+        }
+    }
 }
 
 impl From<GuildError> for InviteError {
@@ -196,68 +245,142 @@ impl From<GuildError> for InviteError {
             GuildError::Forbidden => InviteError::Forbidden,
             GuildError::AlreadyMember => InviteError::AlreadyMember,
             GuildError::Internal(msg) => InviteError::Internal(msg),
+            GuildError::Unavailable => InviteError::Unavailable,
             _ => InviteError::Internal(err.to_string()),
         }
     }
 }
 
+/// Assemble an invite preview from the looked-up server, channel and inviter.
+///
+/// A channel or inviter that no longer exists is left out of the preview.
+fn build_preview(
+    invite: Invite,
+    guild: GuildDto,
+    channel: Option<Channel>,
+    inviter: Option<User>,
+) -> InvitePreviewDto {
+    let is_valid = invite.is_valid();
+
+    InvitePreviewDto {
+        code: invite.code,
+        server_id: invite.server_id.to_string(),
+        server_name: guild.name,
+        server_icon: guild.icon_url,
+        channel_id: invite.channel_id.to_string(),
+        channel_name: channel.map(|channel| channel.name),
+        inviter_id: invite.inviter_id.map(|id| id.to_string()),
+        inviter_name: inviter.map(|user| user.username),
+        member_count: guild.member_count,
+        is_valid,
+    }
+}
+
 /// Invite service implementation.
-pub struct InviteServiceImpl<I, G, M>
+pub struct InviteServiceImpl<I, G, M, R, C, U>
 where
     I: InviteRepository,
     G: GuildService,
     M: MemberRepository,
+    R: RoleRepository,
+    C: ChannelRepository,
+    U: UserRepository,
 {
     invite_repo: Arc<I>,
     guild_service: Arc<G>,
     member_repo: Arc<M>,
+    role_repo: Arc<R>,
+    channel_repo: Arc<C>,
+    user_repo: Arc<U>,
 }
 
-impl<I, G, M> InviteServiceImpl<I, G, M>
+impl<I, G, M, R, C, U> InviteServiceImpl<I, G, M, R, C, U>
 where
     I: InviteRepository,
     G: GuildService,
     M: MemberRepository,
+    R: RoleRepository,
+    C: ChannelRepository,
+    U: UserRepository,
 {
+    /// Default number of invites returned per page.
+    const DEFAULT_PAGE_SIZE: i32 = 50;
+
+    /// Maximum number of invites returned per page.
+    const MAX_PAGE_SIZE: i32 = 100;
+
     /// Create a new InviteServiceImpl.
     pub fn new(
         invite_repo: Arc<I>,
         guild_service: Arc<G>,
         member_repo: Arc<M>,
+        role_repo: Arc<R>,
+        channel_repo: Arc<C>,
+        user_repo: Arc<U>,
     ) -> Self {
         Self {
             invite_repo,
             guild_service,
             member_repo,
+            role_repo,
+            channel_repo,
+            user_repo,
         }
     }
 
-    /// Generate a unique invite code (8 alphanumeric characters).
-    fn generate_unique_code() -> String {
-        Invite::generate_code()
-    }
+    /// Check if the actor has a server-level permission.
+    ///
+    /// The server owner and administrators hold every permission; other
+    /// members need `required` from @everyone or one of their roles.
+    async fn has_server_permission(
+        &self,
+        server_id: i64,
+        actor_id: i64,
+        required: i64,
+    ) -> Result<bool, InviteError> {
+        let guild = self.guild_service.get_guild(server_id).await?;
+        let owner_id: i64 = guild
+            .owner_id
+            .parse()
+            .map_err(|_| InviteError::Internal("Invalid guild owner ID".to_string()))?;
+        if owner_id == actor_id {
+            return Ok(true);
+        }
 
-    /// Check if actor has permission to manage invites for a server.
-    async fn can_manage_invites(&self, server_id: i64, actor_id: i64) -> Result<bool, InviteError> {
-        // Check if actor is a member
-        let is_member = self
+        let Some(member) = self
             .member_repo
-            .is_member(server_id, actor_id)
+            .find(server_id, actor_id)
+            .await
+            .map_err(InviteError::from)?
+        else {
+            return Ok(false);
+        };
+
+        let roles = self
+            .role_repo
+            .find_by_server_id(server_id)
             .await
-            .map_err(|e| InviteError::Internal(e.to_string()))?;
+            .map_err(InviteError::from)?;
+        let permissions = GuildPermissions::new(server_id, owner_id, roles).base(&member);
 
-        // For now, any member can create/manage invites
-        // A more complete implementation would check permissions
-        Ok(is_member)
+        Ok(Permissions::new(permissions).has(required))
+    }
+
+    /// Generate a unique invite code (8 alphanumeric characters).
+    fn generate_unique_code() -> String {
+        Invite::generate_code()
     }
 }
 
 #[async_trait]
-impl<I, G, M> InviteService for InviteServiceImpl<I, G, M>
+impl<I, G, M, R, C, U> InviteService for InviteServiceImpl<I, G, M, R, C, U>
 where
     I: InviteRepository + 'static,
     G: GuildService + 'static,
     M: MemberRepository + 'static,
+    R: RoleRepository + 'static,
+    C: ChannelRepository + 'static,
+    U: UserRepository + 'static,
 {
     async fn create_invite(
         &self,
@@ -271,7 +394,10 @@ where
             .map_err(|_| InviteError::ServerNotFound)?;
 
         // Verify actor can create invites
-        if !self.can_manage_invites(request.server_id, inviter_id).await? {
+        if !self
+            .has_server_permission(request.server_id, inviter_id, Permissions::CREATE_INSTANT_INVITE)
+            .await?
+        {
             return Err(InviteError::Forbidden);
         }
 
@@ -280,7 +406,7 @@ where
         let mut attempts = 0;
         const MAX_ATTEMPTS: i32 = 5;
 
-        while self.invite_repo.code_exists(&code).await.map_err(|e| InviteError::Internal(e.to_string()))? {
+        while self.invite_repo.code_exists(&code).await.map_err(InviteError::from)? {
             if attempts >= MAX_ATTEMPTS {
                 return Err(InviteError::Internal("Failed to generate unique invite code".to_string()));
             }
@@ -294,11 +420,7 @@ where
         let temporary = request.temporary.unwrap_or(false);
 
         let now = Utc::now();
-        let expires_at = if max_age > 0 {
-            Some(now + Duration::seconds(max_age as i64))
-        } else {
-            None
-        };
+        let expires_at = Invite::expiry_for(now, max_age);
 
         let invite = Invite {
             code,
@@ -317,7 +439,7 @@ where
             .invite_repo
             .create(&invite)
             .await
-            .map_err(|e| InviteError::Internal(e.to_string()))?;
+            .map_err(InviteError::from)?;
 
         Ok(InviteDto::from_invite(created))
     }
@@ -327,7 +449,7 @@ where
             .invite_repo
             .find_by_code(code)
             .await
-            .map_err(|e| InviteError::Internal(e.to_string()))?
+            .map_err(InviteError::from)?
             .ok_or(InviteError::NotFound)?;
 
         Ok(InviteDto::from_invite(invite))
@@ -339,40 +461,64 @@ where
             .invite_repo
             .find_by_code(code)
             .await
-            .map_err(|e| InviteError::Internal(e.to_string()))?
+            .map_err(InviteError::from)?
             .ok_or(InviteError::NotFound)?;
 
-        let is_valid = invite.is_valid();
+        // Server, channel and inviter are independent; look them up together
+        let inviter_lookup = async {
+            match invite.inviter_id {
+                Some(inviter_id) => self.user_repo.find_by_id(inviter_id).await,
+                None => Ok(None),
+            }
+        };
+        let (guild, channel, inviter) = tokio::join!(
+            self.guild_service.get_guild(invite.server_id),
+            self.channel_repo.find_by_id(invite.channel_id),
+            inviter_lookup,
+        );
 
-        // Get server info
-        let guild = self
-            .guild_service
-            .get_guild(invite.server_id)
-            .await
-            .map_err(|_| InviteError::ServerNotFound)?;
+        let guild = guild.map_err(|_| InviteError::ServerNotFound)?;
+        let channel = channel.map_err(InviteError::from)?;
+        let inviter = inviter.map_err(InviteError::from)?;
 
-        // For now, we don't have channel name lookup, so use a placeholder
-        // A complete implementation would fetch channel details
-        Ok(InvitePreviewDto {
-            code: invite.code,
-            server_id: invite.server_id.to_string(),
-            server_name: guild.name,
-            server_icon: guild.icon_url,
-            channel_id: invite.channel_id.to_string(),
-            channel_name: "general".to_string(), // Placeholder
-            inviter_id: invite.inviter_id.map(|id| id.to_string()),
-            inviter_name: None, // Would need user lookup
-            member_count: guild.member_count,
-            is_valid,
-        })
+        Ok(build_preview(invite, guild, channel, inviter))
     }
 
-    async fn get_server_invites(&self, server_id: i64) -> Result<Vec<InviteDto>, InviteError> {
+    async fn get_server_invites(
+        &self,
+        server_id: i64,
+        actor_id: i64,
+        query: InviteQueryDto,
+    ) -> Result<Vec<InviteDto>, InviteError> {
+        if !self
+            .has_server_permission(server_id, actor_id, Permissions::MANAGE_GUILD)
+            .await?
+        {
+            return Err(InviteError::Forbidden);
+        }
+
+        let limit = query
+            .limit
+            .unwrap_or(Self::DEFAULT_PAGE_SIZE)
+            .clamp(1, Self::MAX_PAGE_SIZE);
+
+        // The cursor has to be one of this server's invites
+        if let Some(code) = query.before_code.as_deref() {
+            let cursor = self
+                .invite_repo
+                .find_by_code(code)
+                .await
+                .map_err(InviteError::from)?;
+            if !cursor.is_some_and(|invite| invite.server_id == server_id) {
+                return Err(InviteError::InvalidCursor);
+            }
+        }
+
         let invites = self
             .invite_repo
-            .find_by_server_id(server_id)
+            .find_by_server_id_paginated(server_id, query.before_code.as_deref(), limit)
             .await
-            .map_err(|e| InviteError::Internal(e.to_string()))?;
+            .map_err(InviteError::from)?;
 
         Ok(invites.into_iter().map(InviteDto::from_invite).collect())
     }
@@ -383,7 +529,7 @@ where
             .invite_repo
             .find_by_code(code)
             .await
-            .map_err(|e| InviteError::Internal(e.to_string()))?
+            .map_err(InviteError::from)?
             .ok_or(InviteError::NotFound)?;
 
         // Check if expired
@@ -401,12 +547,13 @@ where
             .member_repo
             .is_member(invite.server_id, user_id)
             .await
-            .map_err(|e| InviteError::Internal(e.to_string()))?;
+            .map_err(InviteError::from)?;
 
         if is_member {
             return Ok(UseInviteResultDto {
                 server_id: invite.server_id.to_string(),
                 already_member: true,
+                member: None,
             });
         }
 
@@ -414,16 +561,18 @@ where
         self.invite_repo
             .increment_uses(code)
             .await
-            .map_err(|e| InviteError::Internal(e.to_string()))?;
+            .map_err(InviteError::from)?;
 
-        // Join the guild
-        self.guild_service
+        // Join the guild, with the guild's auto role if one is set
+        let member = self
+            .guild_service
             .join_guild(invite.server_id, user_id)
             .await?;
 
         Ok(UseInviteResultDto {
             server_id: invite.server_id.to_string(),
             already_member: false,
+            member: Some(member),
         })
     }
 
@@ -433,21 +582,24 @@ where
             .invite_repo
             .find_by_code(code)
             .await
-            .map_err(|e| InviteError::Internal(e.to_string()))?
+            .map_err(InviteError::from)?
             .ok_or(InviteError::NotFound)?;
 
-        // Check permission: must be inviter or have manage_invites permission
+        // Check permission: must be inviter or have MANAGE_GUILD
         let is_inviter = invite.inviter_id == Some(actor_id);
-        let can_manage = self.can_manage_invites(invite.server_id, actor_id).await?;
 
-        if !is_inviter && !can_manage {
+        if !is_inviter
+            && !self
+                .has_server_permission(invite.server_id, actor_id, Permissions::MANAGE_GUILD)
+                .await?
+        {
             return Err(InviteError::Forbidden);
         }
 
         self.invite_repo
             .delete(code)
             .await
-            .map_err(|e| InviteError::Internal(e.to_string()))?;
+            .map_err(InviteError::from)?;
 
         Ok(())
     }
@@ -457,11 +609,10 @@ where
             .invite_repo
             .find_by_code(code)
             .await
-            .map_err(|e| InviteError::Internal(e.to_string()))?;
+            .map_err(InviteError::from)?;
 
         match invite {
             Some(inv) => {
-                let now = Utc::now();
                 let is_expired = inv.is_expired();
                 let is_maxed = inv.is_maxed_out();
                 let is_valid = !is_expired && !is_maxed;
@@ -475,11 +626,7 @@ where
                 };
 
                 let remaining_uses = inv.remaining_uses();
-
-                let expires_in = inv.expires_at.map(|exp| {
-                    let duration = exp - now;
-                    duration.num_seconds().max(0)
-                });
+                let expires_in = inv.expires_in();
 
                 Ok(InviteValidationDto {
                     code: inv.code,
@@ -504,7 +651,7 @@ where
             .invite_repo
             .find_by_inviter_id(user_id)
             .await
-            .map_err(|e| InviteError::Internal(e.to_string()))?;
+            .map_err(InviteError::from)?;
 
         Ok(invites.into_iter().map(InviteDto::from_invite).collect())
     }
@@ -514,18 +661,19 @@ where
             .invite_repo
             .delete_expired()
             .await
-            .map_err(|e| InviteError::Internal(e.to_string()))?;
+            .map_err(InviteError::from)?;
 
         Ok(deleted as u64)
     }
 }
 
 /// Concrete implementation using PostgreSQL repository.
-pub type PgInviteService<G, M> = InviteServiceImpl<PgInviteRepository, G, M>;
+pub type PgInviteService<G, M, R, C, U> = InviteServiceImpl<PgInviteRepository, G, M, R, C, U>;
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Duration;
 
     #[test]
     fn test_create_invite_dto() {
@@ -569,6 +717,47 @@ mod tests {
         assert!(dto.is_valid);
     }
 
+    #[test]
+    fn test_invite_dto_remaining_uses_for_limited_invite() {
+        let invite = Invite {
+            code: "limited1".to_string(),
+            server_id: 123,
+            channel_id: 456,
+            inviter_id: Some(789),
+            max_uses: 10,
+            uses: 3,
+            max_age: 3600,
+            temporary: false,
+            expires_at: Some(Utc::now() + Duration::seconds(3600)),
+            created_at: Utc::now(),
+        };
+
+        let dto = InviteDto::from_invite(invite);
+        assert_eq!(dto.remaining_uses, Some(7));
+        let expires_in = dto.expires_in.unwrap();
+        assert!(expires_in > 3590 && expires_in <= 3600);
+    }
+
+    #[test]
+    fn test_invite_dto_unlimited_invite_has_no_stats() {
+        let invite = Invite {
+            code: "forever1".to_string(),
+            server_id: 123,
+            channel_id: 456,
+            inviter_id: None,
+            max_uses: 0,
+            uses: 42,
+            max_age: 0,
+            temporary: false,
+            expires_at: None,
+            created_at: Utc::now(),
+        };
+
+        let dto = InviteDto::from_invite(invite);
+        assert_eq!(dto.remaining_uses, None);
+        assert_eq!(dto.expires_in, None);
+    }
+
     #[test]
     fn test_invite_validation_dto() {
         let validation = InviteValidationDto {
@@ -583,4 +772,160 @@ mod tests {
         assert!(validation.invalid_reason.is_none());
         assert_eq!(validation.remaining_uses, Some(5));
     }
+
+    fn preview_invite() -> Invite {
+        Invite {
+            code: "preview1".to_string(),
+            server_id: 123,
+            channel_id: 456,
+            inviter_id: Some(789),
+            max_uses: 0,
+            uses: 0,
+            max_age: 0,
+            temporary: false,
+            expires_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn preview_guild() -> GuildDto {
+        let server = crate::domain::Server {
+            id: 123,
+            name: "Rustaceans".to_string(),
+            ..Default::default()
+        };
+        GuildDto::from_server(server, 42)
+    }
+
+    #[test]
+    fn test_preview_shows_channel_and_inviter_names() {
+        let channel = Channel {
+            id: 456,
+            name: "announcements".to_string(),
+            ..Default::default()
+        };
+        let inviter = User {
+            id: 789,
+            username: "ferris".to_string(),
+            ..Default::default()
+        };
+
+        let preview = build_preview(preview_invite(), preview_guild(), Some(channel), Some(inviter));
+
+        assert_eq!(preview.server_name, "Rustaceans");
+        assert_eq!(preview.channel_id, "456");
+        assert_eq!(preview.channel_name.as_deref(), Some("announcements"));
+        assert_eq!(preview.inviter_id.as_deref(), Some("789"));
+        assert_eq!(preview.inviter_name.as_deref(), Some("ferris"));
+        assert_eq!(preview.member_count, 42);
+        assert!(preview.is_valid);
+    }
+
+    #[test]
+    fn test_preview_degrades_when_channel_and_inviter_are_gone() {
+        let preview = build_preview(preview_invite(), preview_guild(), None, None);
+
+        assert_eq!(preview.channel_id, "456");
+        assert_eq!(preview.channel_name, None);
+        assert_eq!(preview.inviter_id.as_deref(), Some("789"));
+        assert_eq!(preview.inviter_name, None);
+    }
+
+    #[cfg(feature = "db-tests")]
+    mod db {
+        use super::*;
+        use crate::application::services::GuildServiceImpl;
+        use crate::infrastructure::repositories::{
+            test_db, PgChannelRepository, PgMemberRepository, PgRoleRepository, PgServerRepository,
+            PgUserRepository,
+        };
+        use crate::shared::snowflake::SnowflakeGenerator;
+
+        fn service(pool: sqlx::PgPool) -> impl InviteService {
+            let server_repo = Arc::new(PgServerRepository::new(pool.clone()));
+            let channel_repo = Arc::new(PgChannelRepository::new(pool.clone()));
+            let member_repo = Arc::new(PgMemberRepository::new(pool.clone()));
+            let role_repo = Arc::new(PgRoleRepository::new(pool.clone()));
+            let guild_service = Arc::new(GuildServiceImpl::new(
+                server_repo,
+                channel_repo.clone(),
+                member_repo.clone(),
+                role_repo.clone(),
+                Arc::new(SnowflakeGenerator::new(1, 1)),
+            ));
+
+            InviteServiceImpl::new(
+                Arc::new(PgInviteRepository::new(pool.clone())),
+                guild_service,
+                member_repo,
+                role_repo,
+                channel_repo,
+                Arc::new(PgUserRepository::new(pool)),
+            )
+        }
+
+        fn invite_request(server_id: i64, channel_id: i64) -> CreateInviteDto {
+            CreateInviteDto {
+                server_id,
+                channel_id,
+                max_uses: None,
+                max_age: None,
+                temporary: None,
+            }
+        }
+
+        #[tokio::test]
+        async fn test_member_without_create_instant_invite_is_rejected() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let member = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            test_db::member(&pool, server, member).await;
+            let channel = test_db::channel(&pool, server).await;
+            test_db::set_permissions(&pool, server, Permissions::DEFAULT & !Permissions::CREATE_INSTANT_INVITE)
+                .await;
+            let service = service(pool.clone());
+
+            let denied = service.create_invite(invite_request(server, channel), member).await;
+            assert!(matches!(denied, Err(InviteError::Forbidden)));
+
+            // The owner needs no role at all
+            assert!(service.create_invite(invite_request(server, channel), owner).await.is_ok());
+
+            let inviter = test_db::role(&pool, server, 1).await;
+            test_db::set_permissions(&pool, inviter, Permissions::CREATE_INSTANT_INVITE).await;
+            test_db::assign_role(&pool, server, member, inviter).await;
+            assert!(service.create_invite(invite_request(server, channel), member).await.is_ok());
+        }
+
+        #[tokio::test]
+        async fn test_listing_invites_requires_manage_guild() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let manager = test_db::user(&pool).await;
+            let admin = test_db::user(&pool).await;
+            let member = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            test_db::set_permissions(&pool, server, Permissions::DEFAULT).await;
+            for user in [manager, admin, member] {
+                test_db::member(&pool, server, user).await;
+            }
+            for (user, permissions) in [(manager, Permissions::MANAGE_GUILD), (admin, Permissions::ADMINISTRATOR)] {
+                let role = test_db::role(&pool, server, 1).await;
+                test_db::set_permissions(&pool, role, permissions).await;
+                test_db::assign_role(&pool, server, user, role).await;
+            }
+            let service = service(pool);
+            let query = || InviteQueryDto {
+                before_code: None,
+                limit: None,
+            };
+
+            let denied = service.get_server_invites(server, member, query()).await;
+            assert!(matches!(denied, Err(InviteError::Forbidden)));
+            for user in [owner, manager, admin] {
+                assert!(service.get_server_invites(server, user, query()).await.is_ok());
+            }
+        }
+    }
 }
@@ -0,0 +1,292 @@
+//! Member Service
+//!
+//! Handles reading guild members as a single view combining the user's
+//! profile with their membership, roles and guild-level permissions.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::guild_service::GuildError;
+use super::permission_resolver::GuildPermissions;
+use super::user_service::UserDto;
+use crate::domain::{
+    Member, MemberRepository, Role, RoleRepository, Server, ServerRepository, User,
+    UserRepository,
+};
+
+/// Member service trait
+#[async_trait]
+pub trait MemberService: Send + Sync {
+    /// Get a member of a guild with their profile, roles and permissions.
+    ///
+    /// The actor must be a member of the guild.
+    async fn get_member(
+        &self,
+        server_id: i64,
+        user_id: i64,
+        actor_id: i64,
+    ) -> Result<MemberDetailDto, GuildError>;
+}
+
+/// Full member view
+#[derive(Debug, Clone)]
+pub struct MemberDetailDto {
+    pub user: UserDto,
+    pub server_id: String,
+    pub nickname: Option<String>,
+    pub roles: Vec<String>,
+    pub joined_at: String,
+    /// Guild-level permission bitfield as string
+    pub permissions: String,
+    /// End of the member's timeout, if one was ever set
+    pub communication_disabled_until: Option<String>,
+    pub timed_out: bool,
+}
+
+/// Build the member view from the loaded member, user and guild roles.
+///
+/// A target that is not a member, or whose user no longer exists, is
+/// reported as `MemberNotFound`.
+fn build_member_detail(
+    server: &Server,
+    member: Option<Member>,
+    user: Option<User>,
+    roles: &[Role],
+    now: DateTime<Utc>,
+) -> Result<MemberDetailDto, GuildError> {
+    let (member, user) = match (member, user) {
+        (Some(member), Some(user)) => (member, user),
+        _ => return Err(GuildError::MemberNotFound),
+    };
+
+    let permissions = GuildPermissions::new(server.id, server.owner_id, roles.to_vec()).base(&member);
+
+    Ok(MemberDetailDto {
+        user: UserDto::from(user),
+        server_id: member.server_id.to_string(),
+        nickname: member.nickname.clone(),
+        roles: member.roles.iter().map(|r| r.to_string()).collect(),
+        joined_at: member.joined_at.to_rfc3339(),
+        permissions: permissions.to_string(),
+        communication_disabled_until: member.communication_disabled_until.map(|t| t.to_rfc3339()),
+        timed_out: member.is_timed_out(now),
+    })
+}
+
+/// MemberService implementation
+pub struct MemberServiceImpl<S, M, R, U>
+where
+    S: ServerRepository,
+    M: MemberRepository,
+    R: RoleRepository,
+    U: UserRepository,
+{
+    server_repo: Arc<S>,
+    member_repo: Arc<M>,
+    role_repo: Arc<R>,
+    user_repo: Arc<U>,
+}
+
+impl<S, M, R, U> MemberServiceImpl<S, M, R, U>
+where
+    S: ServerRepository,
+    M: MemberRepository,
+    R: RoleRepository,
+    U: UserRepository,
+{
+    pub fn new(server_repo: Arc<S>, member_repo: Arc<M>, role_repo: Arc<R>, user_repo: Arc<U>) -> Self {
+        Self {
+            server_repo,
+            member_repo,
+            role_repo,
+            user_repo,
+        }
+    }
+}
+
+#[async_trait]
+impl<S, M, R, U> MemberService for MemberServiceImpl<S, M, R, U>
+where
+    S: ServerRepository + 'static,
+    M: MemberRepository + 'static,
+    R: RoleRepository + 'static,
+    U: UserRepository + 'static,
+{
+    async fn get_member(
+        &self,
+        server_id: i64,
+        user_id: i64,
+        actor_id: i64,
+    ) -> Result<MemberDetailDto, GuildError> {
+        let server = self
+            .server_repo
+            .find_by_id(server_id)
+            .await
+            .map_err(GuildError::from)?
+            .ok_or(GuildError::NotFound)?;
+
+        let is_member = self
+            .member_repo
+            .is_member(server_id, actor_id)
+            .await
+            .map_err(GuildError::from)?;
+
+        if !is_member {
+            return Err(GuildError::Forbidden);
+        }
+
+        let member = self
+            .member_repo
+            .find(server_id, user_id)
+            .await
+            .map_err(GuildError::from)?;
+
+        if member.is_none() {
+            return Err(GuildError::MemberNotFound);
+        }
+
+        // All guild roles in one query; the member's role IDs select from them
+        let roles = self
+            .role_repo
+            .find_by_server_id(server_id)
+            .await
+            .map_err(GuildError::from)?;
+
+        let user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await
+            .map_err(GuildError::from)?;
+
+        build_member_detail(&server, member, user, &roles, Utc::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::Permissions;
+    use chrono::Duration;
+
+    const SERVER_ID: i64 = 1;
+
+    fn server() -> Server {
+        Server {
+            id: SERVER_ID,
+            owner_id: 99,
+            ..Default::default()
+        }
+    }
+
+    fn role(id: i64, permissions: i64) -> Role {
+        Role {
+            id,
+            server_id: SERVER_ID,
+            permissions,
+            ..Default::default()
+        }
+    }
+
+    fn user(id: i64) -> User {
+        User {
+            id,
+            username: format!("user{}", id),
+            ..Default::default()
+        }
+    }
+
+    fn member(user_id: i64, roles: Vec<i64>) -> Member {
+        Member {
+            server_id: SERVER_ID,
+            user_id,
+            roles,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_permissions_combine_all_member_roles() {
+        let roles = vec![
+            // @everyone applies to every member
+            role(SERVER_ID, Permissions::VIEW_CHANNEL),
+            role(10, Permissions::SEND_MESSAGES),
+            role(11, Permissions::MANAGE_MESSAGES),
+            role(12, Permissions::BAN_MEMBERS),
+        ];
+
+        let detail = build_member_detail(
+            &server(),
+            Some(member(5, vec![10, 11])),
+            Some(user(5)),
+            &roles,
+            Utc::now(),
+        )
+        .unwrap();
+
+        let expected =
+            Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::MANAGE_MESSAGES;
+        assert_eq!(detail.permissions, expected.to_string());
+        assert_eq!(detail.roles, vec!["10", "11"]);
+        assert_eq!(detail.user.username, "user5");
+        assert!(!detail.timed_out);
+    }
+
+    #[test]
+    fn test_permissions_include_an_everyone_role_not_sharing_the_guild_id() {
+        // @everyone is the lowest role when no role shares the guild's ID
+        let roles = vec![
+            Role {
+                position: 0,
+                ..role(20, Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES)
+            },
+            Role {
+                position: 1,
+                ..role(21, Permissions::BAN_MEMBERS)
+            },
+        ];
+
+        let detail =
+            build_member_detail(&server(), Some(member(5, vec![])), Some(user(5)), &roles, Utc::now()).unwrap();
+
+        let expected = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+        assert_eq!(detail.permissions, expected.to_string());
+        assert!(detail.roles.is_empty());
+    }
+
+    #[test]
+    fn test_owner_has_all_permissions() {
+        let detail =
+            build_member_detail(&server(), Some(member(99, vec![])), Some(user(99)), &[], Utc::now())
+                .unwrap();
+
+        assert_eq!(detail.permissions, Permissions::ALL.to_string());
+    }
+
+    #[test]
+    fn test_non_member_target_is_not_found() {
+        let result = build_member_detail(&server(), None, Some(user(5)), &[], Utc::now());
+        assert!(matches!(result, Err(GuildError::MemberNotFound)));
+
+        // A membership whose user was deleted is not shown either
+        let result = build_member_detail(&server(), Some(member(5, vec![])), None, &[], Utc::now());
+        assert!(matches!(result, Err(GuildError::MemberNotFound)));
+    }
+
+    #[test]
+    fn test_timeout_status() {
+        let now = Utc::now();
+        let mut timed_out = member(5, vec![]);
+        timed_out.communication_disabled_until = Some(now + Duration::minutes(10));
+
+        let detail = build_member_detail(&server(), Some(timed_out.clone()), Some(user(5)), &[], now).unwrap();
+        assert!(detail.timed_out);
+        assert!(detail.communication_disabled_until.is_some());
+
+        // An elapsed timeout no longer applies
+        let later = now + Duration::minutes(11);
+        let detail = build_member_detail(&server(), Some(timed_out), Some(user(5)), &[], later).unwrap();
+        assert!(!detail.timed_out);
+    }
+}
@@ -0,0 +1,338 @@
+//! Message Write Batcher
+//!
+//! Background task smoothing message writes during spikes.
+//!
+//! Instead of one INSERT per message, created messages are queued for a
+//! few milliseconds and written together with
+//! [`MessageRepository::create_batch`]. Each caller still gets back its own
+//! message. Messages are written in snowflake order, and a failed write is
+//! reported to every caller in the batch.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{timeout_at, Instant};
+
+use crate::config::MessageBatchSettings;
+use crate::domain::{Message, MessageRepository};
+use crate::shared::error::AppError;
+
+/// Upper bound on the batch size; each message binds 10 parameters in
+/// `MessageRepository::create_batch` and Postgres allows 65535 per
+/// statement
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+/// Messages waiting for the batcher before callers are pushed back
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// Batching limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageBatchConfig {
+    /// Maximum messages written per INSERT
+    pub max_batch_size: usize,
+    /// Longest the first message of a batch waits for others
+    pub max_delay: Duration,
+}
+
+impl Default for MessageBatchConfig {
+    fn default() -> Self {
+        Self::from_settings(&MessageBatchSettings::default())
+    }
+}
+
+impl MessageBatchConfig {
+    /// Build the config from settings, clamping the batch size to
+    /// `1..=MAX_BATCH_SIZE`
+    pub fn from_settings(settings: &MessageBatchSettings) -> Self {
+        Self {
+            max_batch_size: settings.max_batch_size.clamp(1, MAX_BATCH_SIZE),
+            max_delay: Duration::from_millis(settings.max_delay_ms),
+        }
+    }
+}
+
+/// A queued message and where to send its result
+struct PendingWrite {
+    message: Message,
+    reply: oneshot::Sender<Result<Message, AppError>>,
+}
+
+/// Handle for queueing message writes.
+///
+/// Cloning is cheap and clones feed the same background task.
+#[derive(Clone)]
+pub struct MessageWriteBatcher {
+    tx: mpsc::Sender<PendingWrite>,
+}
+
+impl std::fmt::Debug for MessageWriteBatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageWriteBatcher").finish_non_exhaustive()
+    }
+}
+
+impl MessageWriteBatcher {
+    /// Start the background task writing batches to `repo`.
+    pub fn spawn<M: MessageRepository + 'static>(repo: Arc<M>, config: MessageBatchConfig) -> Self {
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        tokio::spawn(run(repo, config, rx));
+        Self { tx }
+    }
+
+    /// Queue a message and wait for its batch to be written.
+    ///
+    /// Returns the stored message, or the error that failed its batch.
+    pub async fn create(&self, message: Message) -> Result<Message, AppError> {
+        let (reply, result) = oneshot::channel();
+
+        self.tx
+            .send(PendingWrite { message, reply })
+            .await
+            .map_err(|_| AppError::Internal("Message batcher stopped".to_string()))?;
+
+        result
+            .await
+            .map_err(|_| AppError::Internal("Message batcher dropped the write".to_string()))?
+    }
+}
+
+/// Collect batches until every handle is dropped.
+async fn run<M: MessageRepository>(
+    repo: Arc<M>,
+    config: MessageBatchConfig,
+    mut rx: mpsc::Receiver<PendingWrite>,
+) {
+    while let Some(first) = rx.recv().await {
+        let mut batch = vec![first];
+        let deadline = Instant::now() + config.max_delay;
+
+        while batch.len() < config.max_batch_size {
+            match timeout_at(deadline, rx.recv()).await {
+                Ok(Some(write)) => batch.push(write),
+                // Deadline reached, or every handle dropped
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        flush(repo.as_ref(), batch).await;
+    }
+}
+
+/// Write one batch and answer every caller in it.
+async fn flush<M: MessageRepository + ?Sized>(repo: &M, mut batch: Vec<PendingWrite>) {
+    // Snowflakes order messages; write them in that order
+    batch.sort_by_key(|write| write.message.id);
+
+    let messages: Vec<Message> = batch.iter().map(|write| write.message.clone()).collect();
+
+    match repo.create_batch(&messages).await {
+        Ok(created) => {
+            let mut by_id: HashMap<i64, Message> = created.into_iter().map(|m| (m.id, m)).collect();
+            for write in batch {
+                let result = by_id.remove(&write.message.id).ok_or_else(|| {
+                    AppError::Internal("Batched message was not stored".to_string())
+                });
+                let _ = write.reply.send(result);
+            }
+        }
+        Err(e) => {
+            tracing::warn!(count = batch.len(), error = %e, "Batched message write failed");
+            let message = format!("Batched message write failed: {}", e);
+            for write in batch {
+                let _ = write.reply.send(Err(AppError::Internal(message.clone())));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+    use std::sync::Mutex;
+
+    use crate::domain::Attachment;
+
+    /// Repository recording each batch; only batch creates are supported
+    #[derive(Default)]
+    struct RecordingRepository {
+        batches: Mutex<Vec<Vec<i64>>>,
+        fail: AtomicBool,
+        /// ID of a message left out of the result
+        omit: AtomicI64,
+    }
+
+    impl RecordingRepository {
+        fn batches(&self) -> Vec<Vec<i64>> {
+            self.batches.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl MessageRepository for RecordingRepository {
+        async fn create_batch(&self, messages: &[Message]) -> Result<Vec<Message>, AppError> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(AppError::Internal("database unavailable".into()));
+            }
+            self.batches
+                .lock()
+                .unwrap()
+                .push(messages.iter().map(|m| m.id).collect());
+            let omit = self.omit.load(Ordering::SeqCst);
+            // Returned out of order, as Postgres is free to
+            Ok(messages.iter().rev().filter(|m| m.id != omit).cloned().collect())
+        }
+        async fn find_by_id(&self, _: i64) -> Result<Option<Message>, AppError> {
+            unimplemented!()
+        }
+        async fn find_by_channel(
+            &self,
+            _: i64,
+            _: Option<i64>,
+            _: Option<i64>,
+            _: i32,
+        ) -> Result<Vec<Message>, AppError> {
+            unimplemented!()
+        }
+        async fn find_by_ids(&self, _: &[i64]) -> Result<Vec<Message>, AppError> {
+            unimplemented!()
+        }
+        async fn find_pinned(&self, _: i64) -> Result<Vec<Message>, AppError> {
+            unimplemented!()
+        }
+        async fn find_by_author(&self, _: i64, _: i64, _: i32) -> Result<Vec<Message>, AppError> {
+            unimplemented!()
+        }
+        async fn create(&self, _: &Message) -> Result<Message, AppError> {
+            unimplemented!()
+        }
+        async fn create_with_attachments(&self, _: &Message, _: &[i64]) -> Result<Message, AppError> {
+            unimplemented!()
+        }
+        async fn find_attachments_by_ids(&self, _: &[i64]) -> Result<Vec<Attachment>, AppError> {
+            unimplemented!()
+        }
+        async fn find_attachments_by_message_ids(&self, _: &[i64]) -> Result<Vec<Attachment>, AppError> {
+            unimplemented!()
+        }
+        async fn update(&self, _: &Message) -> Result<Message, AppError> {
+            unimplemented!()
+        }
+        async fn delete(&self, _: i64) -> Result<(), AppError> {
+            unimplemented!()
+        }
+        async fn bulk_delete(&self, _: i64, _: Vec<i64>) -> Result<Vec<i64>, AppError> {
+            unimplemented!()
+        }
+        async fn pin(&self, _: i64, _: &Message) -> Result<Option<Message>, AppError> {
+            unimplemented!()
+        }
+        async fn unpin(&self, _: i64) -> Result<(), AppError> {
+            unimplemented!()
+        }
+        async fn count_by_channel(&self, _: i64) -> Result<i64, AppError> {
+            unimplemented!()
+        }
+    }
+
+    fn message(id: i64) -> Message {
+        Message {
+            id,
+            channel_id: 10,
+            author_id: 20,
+            content: format!("message {}", id),
+            ..Default::default()
+        }
+    }
+
+    fn config(max_batch_size: usize) -> MessageBatchConfig {
+        MessageBatchConfig {
+            max_batch_size,
+            max_delay: Duration::from_millis(50),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writes_share_one_batch() {
+        let repo = Arc::new(RecordingRepository::default());
+        let batcher = MessageWriteBatcher::spawn(repo.clone(), config(10));
+
+        let (a, b, c) = tokio::join!(
+            batcher.create(message(3)),
+            batcher.create(message(1)),
+            batcher.create(message(2)),
+        );
+
+        // Each caller gets its own message back
+        assert_eq!(a.unwrap().content, "message 3");
+        assert_eq!(b.unwrap().content, "message 1");
+        assert_eq!(c.unwrap().content, "message 2");
+
+        // Written together, in snowflake order
+        assert_eq!(repo.batches(), vec![vec![1, 2, 3]]);
+    }
+
+    #[tokio::test]
+    async fn test_full_batch_is_flushed_without_waiting() {
+        let repo = Arc::new(RecordingRepository::default());
+        let batcher = MessageWriteBatcher::spawn(repo.clone(), config(2));
+
+        let (a, b, c) = tokio::join!(
+            batcher.create(message(1)),
+            batcher.create(message(2)),
+            batcher.create(message(3)),
+        );
+        assert!(a.is_ok() && b.is_ok() && c.is_ok());
+
+        assert_eq!(repo.batches(), vec![vec![1, 2], vec![3]]);
+    }
+
+    #[tokio::test]
+    async fn test_flush_failure_reaches_every_caller() {
+        let repo = Arc::new(RecordingRepository::default());
+        repo.fail.store(true, Ordering::SeqCst);
+        let batcher = MessageWriteBatcher::spawn(repo.clone(), config(10));
+
+        let (a, b) = tokio::join!(batcher.create(message(1)), batcher.create(message(2)));
+
+        for result in [a, b] {
+            match result {
+                Err(AppError::Internal(msg)) => assert!(msg.contains("database unavailable")),
+                other => panic!("expected batch failure, got {:?}", other.map(|m| m.id)),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_message_missing_from_result_is_an_error() {
+        let repo = Arc::new(RecordingRepository::default());
+        repo.omit.store(2, Ordering::SeqCst);
+        let batcher = MessageWriteBatcher::spawn(repo.clone(), config(10));
+
+        let (a, b) = tokio::join!(batcher.create(message(1)), batcher.create(message(2)));
+
+        assert_eq!(a.unwrap().id, 1);
+        assert!(matches!(b, Err(AppError::Internal(_))));
+    }
+
+    #[test]
+    fn test_config_clamps_batch_size() {
+        let settings = MessageBatchSettings {
+            enabled: true,
+            max_batch_size: 0,
+            max_delay_ms: 5,
+        };
+        assert_eq!(MessageBatchConfig::from_settings(&settings).max_batch_size, 1);
+
+        let settings = MessageBatchSettings {
+            max_batch_size: 1_000_000,
+            ..settings
+        };
+        let config = MessageBatchConfig::from_settings(&settings);
+        assert_eq!(config.max_batch_size, MAX_BATCH_SIZE);
+        assert_eq!(config.max_delay, Duration::from_millis(5));
+    }
+}
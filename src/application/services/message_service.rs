@@ -2,14 +2,30 @@
 //!
 //! Handles message operations including send, edit, delete.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use chrono::Utc;
-
+use chrono::{DateTime, Utc};
+use url::Url;
+
+use super::content_filter::{ContentFilter, FilterDecision, MessageContext, NoopContentFilter};
+use super::message_batcher::MessageWriteBatcher;
+use super::permission_resolver;
+use super::user_service::UserDto;
+use crate::domain::services::PermissionService;
+use crate::domain::value_objects::Permissions;
 use crate::domain::{
-    ChannelRepository, MemberRepository, Message, MessageRepository, MessageType,
+    message_flags, Attachment, Channel, ChannelRepository, Embed, MemberRepository, Message,
+    MessageCountStore, MessageRepository, MessageType, RoleRepository, ServerRepository, UserRepository,
+    MAX_EMBEDS,
+};
+use crate::infrastructure::cache::{
+    Cache, CachedChannelPermissions, CachedUserProfile, MentionCooldown,
+    PermissionCacheService, RedisCache, Slowmode, UserProfileCache,
 };
+use crate::infrastructure::metrics::record_permission_cache_fallback;
+use crate::shared::error::AppError;
 use crate::shared::snowflake::SnowflakeGenerator;
 
 /// Message service trait
@@ -19,25 +35,47 @@ pub trait MessageService: Send + Sync {
     async fn send_message(&self, channel_id: i64, author_id: i64, request: CreateMessageDto) -> Result<MessageDto, MessageError>;
 
     /// Get messages from a channel (requires user_id for authorization check)
+    ///
+    /// Pages are newest-first; `before` and `after` are mutually exclusive.
     async fn get_messages(&self, channel_id: i64, user_id: i64, query: MessageQueryDto) -> Result<Vec<MessageDto>, MessageError>;
 
+    /// Get specific messages of a channel by ID, newest first, with the
+    /// same access checks as `get_messages`.
+    ///
+    /// At most `MAX_MESSAGE_LIMIT` distinct IDs may be given; IDs of
+    /// messages outside the channel are skipped.
+    async fn get_messages_by_ids(&self, channel_id: i64, ids: Vec<i64>, actor_id: i64) -> Result<Vec<MessageDto>, MessageError>;
+
     /// Get a single message
     async fn get_message(&self, channel_id: i64, message_id: i64) -> Result<MessageDto, MessageError>;
 
-    /// Edit a message
-    async fn edit_message(&self, message_id: i64, author_id: i64, content: &str) -> Result<MessageDto, MessageError>;
+    /// Edit a message's content (author only) or flags (author or
+    /// MANAGE_MESSAGES); system messages can't be edited
+    async fn edit_message(&self, message_id: i64, actor_id: i64, edit: EditMessageDto) -> Result<MessageDto, MessageError>;
 
     /// Delete a message
     async fn delete_message(&self, message_id: i64, actor_id: i64) -> Result<(), MessageError>;
 
-    /// Pin a message
-    async fn pin_message(&self, channel_id: i64, message_id: i64, actor_id: i64) -> Result<(), MessageError>;
+    /// Delete up to `MAX_MESSAGE_LIMIT` messages of a channel at once
+    /// (requires MANAGE_MESSAGES), returning the IDs actually deleted
+    async fn bulk_delete_messages(
+        &self,
+        channel_id: i64,
+        actor_id: i64,
+        message_ids: Vec<i64>,
+    ) -> Result<Vec<i64>, MessageError>;
+
+    /// Pin a message (requires MANAGE_MESSAGES) and post a pin system message
+    async fn pin_message(&self, channel_id: i64, message_id: i64, actor_id: i64) -> Result<PinsUpdateDto, MessageError>;
 
-    /// Unpin a message
-    async fn unpin_message(&self, channel_id: i64, message_id: i64, actor_id: i64) -> Result<(), MessageError>;
+    /// Unpin a message (requires MANAGE_MESSAGES)
+    async fn unpin_message(&self, channel_id: i64, message_id: i64, actor_id: i64) -> Result<PinsUpdateDto, MessageError>;
 
     /// Get pinned messages
     async fn get_pinned_messages(&self, channel_id: i64) -> Result<Vec<MessageDto>, MessageError>;
+
+    /// Number of messages in a channel (requires user_id for authorization check)
+    async fn channel_message_count(&self, channel_id: i64, user_id: i64) -> Result<i64, MessageError>;
 }
 
 /// Create message request
@@ -45,6 +83,50 @@ pub trait MessageService: Send + Sync {
 pub struct CreateMessageDto {
     pub content: String,
     pub reply_to: Option<i64>,
+    /// Previously uploaded attachments to associate with the message
+    pub attachment_ids: Vec<i64>,
+    /// Rich embeds; requires `EMBED_LINKS`
+    pub embeds: Vec<Embed>,
+    /// Read the message aloud; requires `SEND_TTS_MESSAGES`
+    pub tts: bool,
+    /// Message flags; only `SUPPRESS_EMBEDS` may be set
+    pub flags: i64,
+}
+
+/// Edit message request; only fields present change
+#[derive(Debug, Clone, Default)]
+pub struct EditMessageDto {
+    pub content: Option<String>,
+    /// New message flags; only `SUPPRESS_EMBEDS` may be changed
+    pub flags: Option<i64>,
+}
+
+/// Attachment data transfer object
+#[derive(Debug, Clone)]
+pub struct AttachmentDto {
+    pub id: String,
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub size: i32,
+    pub url: String,
+    pub proxy_url: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
+impl From<Attachment> for AttachmentDto {
+    fn from(attachment: Attachment) -> Self {
+        Self {
+            id: attachment.id.to_string(),
+            filename: attachment.filename,
+            content_type: attachment.content_type,
+            size: attachment.size,
+            url: attachment.url,
+            proxy_url: attachment.proxy_url,
+            width: attachment.width,
+            height: attachment.height,
+        }
+    }
 }
 
 /// Message data transfer object
@@ -57,8 +139,28 @@ pub struct MessageDto {
     pub message_type: String,
     pub reply_to_id: Option<String>,
     pub pinned: bool,
+    pub tts: bool,
+    pub flags: i64,
     pub edited_at: Option<String>,
     pub created_at: String,
+    pub attachments: Vec<AttachmentDto>,
+    pub embeds: Vec<Embed>,
+    /// Author profile, when resolved by the service
+    pub author: Option<UserDto>,
+    /// Author's guild nickname and roles, for messages in a guild
+    pub member: Option<MessageMemberDto>,
+    /// The message this one replies to, without its own reference, when
+    /// resolved by the service
+    pub referenced_message: Option<Box<MessageDto>>,
+    /// Set on a reply whose referenced message has been deleted
+    pub referenced_message_deleted: bool,
+}
+
+/// Guild member details of a message author
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageMemberDto {
+    pub nickname: Option<String>,
+    pub roles: Vec<String>,
 }
 
 impl From<Message> for MessageDto {
@@ -71,13 +173,42 @@ impl From<Message> for MessageDto {
             message_type: message.message_type.as_str().to_string(),
             reply_to_id: message.reply_to_id.map(|id| id.to_string()),
             pinned: message.pinned,
+            tts: message.tts,
+            flags: message.flags,
             edited_at: message.edited_at.map(|t| t.to_rfc3339()),
             created_at: message.created_at.to_rfc3339(),
+            attachments: Vec::new(),
+            embeds: message.embeds,
+            author: None,
+            member: None,
+            referenced_message: None,
+            referenced_message_deleted: false,
         }
     }
 }
 
+/// Result of pinning or unpinning a message
+#[derive(Debug, Clone)]
+pub struct PinsUpdateDto {
+    pub channel_id: String,
+    pub guild_id: Option<String>,
+    /// When a message was last pinned, if known (set when pinning)
+    pub last_pin_timestamp: Option<String>,
+    /// `CHANNEL_PINNED_MESSAGE` system message created by a pin; its
+    /// `MESSAGE_CREATE` is queued together with the pin
+    pub system_message: Option<MessageDto>,
+}
+
+/// Messages returned when no limit is given
+pub const DEFAULT_MESSAGE_LIMIT: i32 = 50;
+
+/// Maximum messages returned per page
+pub const MAX_MESSAGE_LIMIT: i32 = 100;
+
 /// Message query parameters
+///
+/// `before` and `after` are mutually exclusive. Pages are always returned
+/// newest-first, whichever cursor is used.
 #[derive(Debug, Clone, Default)]
 pub struct MessageQueryDto {
     pub before: Option<i64>,
@@ -95,276 +226,2598 @@ pub enum MessageError {
     #[error("Channel not found")]
     ChannelNotFound,
 
+    #[error("Cannot send messages to this channel type")]
+    InvalidChannelType,
+
     #[error("Permission denied")]
     Forbidden,
 
+    #[error("This channel is age-restricted")]
+    NsfwRestricted,
+
+    #[error("Only the author can edit this message")]
+    NotAuthor,
+
+    #[error("System messages cannot be edited")]
+    SystemMessage,
+
     #[error("Rate limited")]
     RateLimited,
 
+    #[error("@everyone and @here can be used again in {retry_after} seconds")]
+    MentionCooldown { retry_after: u64 },
+
+    #[error("Slowmode is on; you can send another message in {retry_after} seconds")]
+    Slowmode { retry_after: u64 },
+
+    #[error("Only the suppress embeds flag can be set")]
+    InvalidFlags,
+
     #[error("Message too long")]
     ContentTooLong,
 
+    #[error("Message must have content, attachments or embeds")]
+    EmptyMessage,
+
+    #[error("TTS messages must have content")]
+    EmptyTtsMessage,
+
+    #[error("Too many embeds")]
+    TooManyEmbeds,
+
+    #[error("Invalid embed: {0}")]
+    InvalidEmbed(String),
+
+    #[error("Message blocked: {0}")]
+    ContentBlocked(String),
+
+    #[error("Attachment not found")]
+    AttachmentNotFound,
+
+    #[error("Attachment is already attached to a message")]
+    AttachmentAlreadyAttached,
+
+    #[error("Only one of before and after may be given")]
+    InvalidCursor,
+
+    #[error("Limit must not be negative")]
+    InvalidLimit,
+
+    #[error("At most {MAX_MESSAGE_LIMIT} messages can be fetched by ID at once")]
+    TooManyIds,
+
+    #[error("Between 1 and {MAX_MESSAGE_LIMIT} messages can be deleted at once")]
+    InvalidBulkDelete,
+
     #[error("Internal error: {0}")]
     Internal(String),
-}
 
-/// MessageService implementation
-pub struct MessageServiceImpl<M, C, Mem>
-where
-    M: MessageRepository,
-    C: ChannelRepository,
-    Mem: MemberRepository,
-{
-    message_repo: Arc<M>,
-    channel_repo: Arc<C>,
-    member_repo: Arc<Mem>,
-    id_generator: Arc<SnowflakeGenerator>,
+    /// No database connection was free in time; the request may succeed on retry
+    #[error("Service unavailable")]
+    Unavailable,
 }
 
-impl<M, C, Mem> MessageServiceImpl<M, C, Mem>
-where
-    M: MessageRepository,
-    C: ChannelRepository,
-    Mem: MemberRepository,
-{
-    pub fn new(
-        message_repo: Arc<M>,
-        channel_repo: Arc<C>,
-        member_repo: Arc<Mem>,
-        id_generator: Arc<SnowflakeGenerator>,
-    ) -> Self {
-        Self {
-            message_repo,
-            channel_repo,
-            member_repo,
-            id_generator,
+impl From<AppError> for MessageError {
+    fn from(e: AppError) -> Self {
+        if e.is_unavailable() {
+            MessageError::Unavailable
+        } else {
+            MessageError::Internal(e.to_string())
         }
     }
+}
 
-    async fn check_channel_access(&self, channel_id: i64, user_id: i64) -> Result<bool, MessageError> {
-        let channel = self
-            .channel_repo
-            .find_by_id(channel_id)
-            .await
-            .map_err(|e| MessageError::Internal(e.to_string()))?
-            .ok_or(MessageError::ChannelNotFound)?;
+/// Validate a message page query, returning the page size.
+///
+/// Negative limits are rejected; others are clamped to
+/// `1..=MAX_MESSAGE_LIMIT`.
+fn validate_message_query(query: &MessageQueryDto) -> Result<i32, MessageError> {
+    if query.before.is_some() && query.after.is_some() {
+        return Err(MessageError::InvalidCursor);
+    }
 
-        // For guild channels, check membership
-        if let Some(guild_id) = channel.server_id {
-            let is_member = self
-                .member_repo
-                .is_member(guild_id, user_id)
-                .await
-                .map_err(|e| MessageError::Internal(e.to_string()))?;
+    match query.limit {
+        Some(limit) if limit < 0 => Err(MessageError::InvalidLimit),
+        limit => Ok(limit.unwrap_or(DEFAULT_MESSAGE_LIMIT).clamp(1, MAX_MESSAGE_LIMIT)),
+    }
+}
 
-            return Ok(is_member);
-        }
+/// Check a user must pass to see a channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChannelAccess {
+    /// Guild channels: membership in the guild
+    Member(i64),
+    /// DM channels: being one of the channel's recipients
+    Recipient,
+}
 
-        // DM channels - simplified check
-        Ok(true)
+fn required_access(channel: &Channel) -> ChannelAccess {
+    match channel.server_id {
+        Some(guild_id) if !channel.is_dm() => ChannelAccess::Member(guild_id),
+        _ => ChannelAccess::Recipient,
     }
 }
 
-#[async_trait]
-impl<M, C, Mem> MessageService for MessageServiceImpl<M, C, Mem>
-where
-    M: MessageRepository + 'static,
-    C: ChannelRepository + 'static,
-    Mem: MemberRepository + 'static,
-{
-    async fn send_message(&self, channel_id: i64, author_id: i64, request: CreateMessageDto) -> Result<MessageDto, MessageError> {
-        // Check access
-        if !self.check_channel_access(channel_id, author_id).await? {
-            return Err(MessageError::Forbidden);
-        }
+/// Reject channels that cannot hold messages (voice channels, categories).
+fn check_text_based(channel: &Channel) -> Result<(), MessageError> {
+    if !channel.is_text_based() {
+        return Err(MessageError::InvalidChannelType);
+    }
+    Ok(())
+}
 
-        // Validate content length
-        if request.content.len() > 2000 {
-            return Err(MessageError::ContentTooLong);
+/// Run a message through the content filter.
+///
+/// Returns `Ok` when the message may be stored; flagged messages are logged
+/// and still stored.
+async fn screen_message(filter: &dyn ContentFilter, context: &MessageContext) -> Result<(), MessageError> {
+    let decision = filter
+        .check(context)
+        .await
+        .map_err(MessageError::from)?;
+
+    match decision {
+        FilterDecision::Allow => Ok(()),
+        FilterDecision::Flag => {
+            tracing::warn!(
+                channel_id = context.channel_id,
+                author_id = context.author_id,
+                "Message flagged by content filter"
+            );
+            Ok(())
         }
+        FilterDecision::Block { reason } => Err(MessageError::ContentBlocked(reason)),
+    }
+}
 
-        let now = Utc::now();
-        let message_type = if request.reply_to.is_some() {
-            MessageType::Reply
-        } else {
-            MessageType::Default
-        };
+/// Validate message content, allowing empty content when attachments or
+/// embeds are present.
+fn validate_content(content: &str, other_parts: usize) -> Result<(), MessageError> {
+    if content.len() > 2000 {
+        return Err(MessageError::ContentTooLong);
+    }
 
-        let message = Message {
-            id: self.id_generator.generate(),
-            channel_id,
-            author_id,
-            content: request.content,
-            message_type,
-            reply_to_id: request.reply_to,
-            pinned: false,
-            edited_at: None,
-            created_at: now,
-        };
+    if content.trim().is_empty() && other_parts == 0 {
+        return Err(MessageError::EmptyMessage);
+    }
 
-        let created = self
-            .message_repo
-            .create(&message)
-            .await
-            .map_err(|e| MessageError::Internal(e.to_string()))?;
+    Ok(())
+}
 
-        Ok(MessageDto::from(created))
+/// Check that `actor_id` may change the content of `message`.
+///
+/// Content belongs to the author alone; moderators manage messages through
+/// pins and deletes instead. System messages have no author to edit them.
+fn check_editable(message: &Message, actor_id: i64) -> Result<(), MessageError> {
+    if message.is_system() {
+        return Err(MessageError::SystemMessage);
+    }
+    if message.author_id != actor_id {
+        return Err(MessageError::NotAuthor);
     }
 
-    async fn get_messages(&self, channel_id: i64, user_id: i64, query: MessageQueryDto) -> Result<Vec<MessageDto>, MessageError> {
-        // Check channel access authorization
-        if !self.check_channel_access(channel_id, user_id).await? {
-            return Err(MessageError::Forbidden);
-        }
+    Ok(())
+}
+
+/// Reject flags users may not set themselves.
+fn validate_flags(flags: i64) -> Result<(), MessageError> {
+    if flags & !message_flags::USER_SETTABLE != 0 {
+        return Err(MessageError::InvalidFlags);
+    }
+    Ok(())
+}
 
-        let limit = query.limit.unwrap_or(50).min(100);
+/// Check that `actor_id` may change the flags of `message`: its author, or
+/// in a guild channel anyone with `MANAGE_MESSAGES`.
+fn check_flags_editable(
+    message: &Message,
+    actor_id: i64,
+    channel_permissions: Option<i64>,
+) -> Result<(), MessageError> {
+    if message.is_system() {
+        return Err(MessageError::SystemMessage);
+    }
+    if message.author_id == actor_id {
+        return Ok(());
+    }
 
-        let messages = self
-            .message_repo
-            .find_by_channel(channel_id, query.before, query.after, limit)
-            .await
-            .map_err(|e| MessageError::Internal(e.to_string()))?;
+    match channel_permissions {
+        Some(_) => require_channel_permission(channel_permissions, Permissions::MANAGE_MESSAGES),
+        // Nobody moderates another user's messages in a DM
+        None => Err(MessageError::NotAuthor),
+    }
+}
+
+/// Flags after a validated edit to `requested`; flags users can't set are
+/// kept as they were.
+fn edited_flags(current: i64, requested: i64) -> i64 {
+    (current & !message_flags::USER_SETTABLE) | (requested & message_flags::USER_SETTABLE)
+}
+
+/// Whether an embed URL is an absolute `http(s)` URL with a host.
+fn is_valid_embed_url(value: &str) -> bool {
+    value.len() <= Embed::MAX_URL_LENGTH
+        && Url::parse(value).is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some())
+}
 
-        Ok(messages.into_iter().map(MessageDto::from).collect())
+/// Validate embed count, per-field limits and URLs.
+fn validate_embeds(embeds: &[Embed]) -> Result<(), MessageError> {
+    if embeds.len() > MAX_EMBEDS {
+        return Err(MessageError::TooManyEmbeds);
     }
 
-    async fn get_message(&self, channel_id: i64, message_id: i64) -> Result<MessageDto, MessageError> {
-        let message = self
-            .message_repo
-            .find_by_id(message_id)
-            .await
-            .map_err(|e| MessageError::Internal(e.to_string()))?
-            .ok_or(MessageError::NotFound)?;
+    let too_long = |value: Option<&str>, max: usize| value.is_some_and(|v| v.chars().count() > max);
 
-        // Verify channel matches
-        if message.channel_id != channel_id {
-            return Err(MessageError::NotFound);
+    for embed in embeds {
+        if too_long(embed.title.as_deref(), Embed::MAX_TITLE_LENGTH) {
+            return Err(MessageError::InvalidEmbed(format!(
+                "title must be at most {} characters",
+                Embed::MAX_TITLE_LENGTH
+            )));
+        }
+        if too_long(embed.description.as_deref(), Embed::MAX_DESCRIPTION_LENGTH) {
+            return Err(MessageError::InvalidEmbed(format!(
+                "description must be at most {} characters",
+                Embed::MAX_DESCRIPTION_LENGTH
+            )));
+        }
+        if embed.color.is_some_and(|c| c > Embed::MAX_COLOR) {
+            return Err(MessageError::InvalidEmbed("color must be a 24-bit RGB value".into()));
         }
+        if embed.fields.len() > Embed::MAX_FIELDS {
+            return Err(MessageError::InvalidEmbed(format!(
+                "at most {} fields are allowed",
+                Embed::MAX_FIELDS
+            )));
+        }
+        for field in &embed.fields {
+            if field.name.trim().is_empty() || field.value.trim().is_empty() {
+                return Err(MessageError::InvalidEmbed("field name and value are required".into()));
+            }
+            if too_long(Some(&field.name), Embed::MAX_FIELD_NAME_LENGTH) {
+                return Err(MessageError::InvalidEmbed(format!(
+                    "field name must be at most {} characters",
+                    Embed::MAX_FIELD_NAME_LENGTH
+                )));
+            }
+            if too_long(Some(&field.value), Embed::MAX_FIELD_VALUE_LENGTH) {
+                return Err(MessageError::InvalidEmbed(format!(
+                    "field value must be at most {} characters",
+                    Embed::MAX_FIELD_VALUE_LENGTH
+                )));
+            }
+        }
+        if too_long(embed.footer.as_ref().map(|f| f.text.as_str()), Embed::MAX_FOOTER_TEXT_LENGTH) {
+            return Err(MessageError::InvalidEmbed(format!(
+                "footer text must be at most {} characters",
+                Embed::MAX_FOOTER_TEXT_LENGTH
+            )));
+        }
+        if !embed.urls().all(is_valid_embed_url) {
+            return Err(MessageError::InvalidEmbed("URLs must be absolute http or https URLs".into()));
+        }
+    }
 
-        Ok(MessageDto::from(message))
+    let total: usize = embeds.iter().map(Embed::text_length).sum();
+    if total > Embed::MAX_TOTAL_LENGTH {
+        return Err(MessageError::InvalidEmbed(format!(
+            "embeds must contain at most {} characters in total",
+            Embed::MAX_TOTAL_LENGTH
+        )));
     }
 
-    async fn edit_message(&self, message_id: i64, author_id: i64, content: &str) -> Result<MessageDto, MessageError> {
-        // Validate content length
-        if content.len() > 2000 {
-            return Err(MessageError::ContentTooLong);
-        }
+    Ok(())
+}
 
-        let mut message = self
-            .message_repo
-            .find_by_id(message_id)
-            .await
-            .map_err(|e| MessageError::Internal(e.to_string()))?
-            .ok_or(MessageError::NotFound)?;
+/// Require a permission in a channel.
+///
+/// `channel_permissions` is `None` for DM channels, where no permission
+/// is needed.
+fn require_channel_permission(
+    channel_permissions: Option<i64>,
+    permission: i64,
+) -> Result<(), MessageError> {
+    match channel_permissions {
+        Some(perms) if !Permissions::new(perms).has(permission) => Err(MessageError::Forbidden),
+        _ => Ok(()),
+    }
+}
 
-        // Only author can edit
-        if message.author_id != author_id {
-            return Err(MessageError::Forbidden);
+/// Reading a guild channel's history requires `VIEW_CHANNEL` and
+/// `READ_MESSAGE_HISTORY`.
+///
+/// A channel the user can't view is reported as missing rather than
+/// forbidden, so its existence isn't revealed.
+fn check_history_access(channel_permissions: Option<i64>) -> Result<(), MessageError> {
+    if let Some(perms) = channel_permissions {
+        if !Permissions::new(perms).has(Permissions::VIEW_CHANNEL) {
+            return Err(MessageError::ChannelNotFound);
         }
+    }
 
-        message.content = content.to_string();
-        message.edited_at = Some(Utc::now());
-
-        let updated = self
-            .message_repo
-            .update(&message)
-            .await
-            .map_err(|e| MessageError::Internal(e.to_string()))?;
+    require_channel_permission(channel_permissions, Permissions::READ_MESSAGE_HISTORY)
+}
 
-        Ok(MessageDto::from(updated))
+/// NSFW channels need the user to have allowed NSFW content or to
+/// moderate the channel.
+///
+/// Channels the user can't view are left to [`check_history_access`], so
+/// hidden NSFW channels still look missing.
+fn check_nsfw_access(channel: &Channel, channel_permissions: Option<i64>, nsfw_allowed: bool) -> Result<(), MessageError> {
+    match channel_permissions {
+        Some(perms)
+            if Permissions::new(perms).has(Permissions::VIEW_CHANNEL)
+                && !PermissionService::can_view_nsfw(channel, perms, nsfw_allowed) =>
+        {
+            Err(MessageError::NsfwRestricted)
+        }
+        _ => Ok(()),
     }
+}
 
-    async fn delete_message(&self, message_id: i64, actor_id: i64) -> Result<(), MessageError> {
-        let message = self
-            .message_repo
-            .find_by_id(message_id)
-            .await
-            .map_err(|e| MessageError::Internal(e.to_string()))?
-            .ok_or(MessageError::NotFound)?;
+/// Load a page of a channel's history, checking permissions before any
+/// message is queried.
+async fn load_history<M: MessageRepository>(
+    message_repo: &M,
+    channel_id: i64,
+    channel_permissions: Option<i64>,
+    query: &MessageQueryDto,
+) -> Result<Vec<Message>, MessageError> {
+    check_history_access(channel_permissions)?;
+    let limit = validate_message_query(query)?;
+
+    message_repo
+        .find_by_channel(channel_id, query.before, query.after, limit)
+        .await
+        .map_err(MessageError::from)
+}
 
-        // Only author can delete (simplified - full implementation would check MANAGE_MESSAGES permission)
-        if message.author_id != actor_id {
-            return Err(MessageError::Forbidden);
-        }
+/// Load specific messages of a channel, checking permissions before any
+/// message is queried.
+///
+/// IDs are deduplicated and at most `MAX_MESSAGE_LIMIT` may be asked for.
+/// Messages in other channels and ephemeral messages are left out; the
+/// rest are returned newest-first, like history pages.
+async fn load_by_ids<M: MessageRepository>(
+    message_repo: &M,
+    channel_id: i64,
+    channel_permissions: Option<i64>,
+    ids: &[i64],
+) -> Result<Vec<Message>, MessageError> {
+    check_history_access(channel_permissions)?;
+
+    let mut ids = ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.len() > MAX_MESSAGE_LIMIT as usize {
+        return Err(MessageError::TooManyIds);
+    }
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
 
-        self.message_repo
-            .delete(message_id)
-            .await
-            .map_err(|e| MessageError::Internal(e.to_string()))?;
+    let mut messages: Vec<Message> = message_repo
+        .find_by_ids(&ids)
+        .await
+        .map_err(MessageError::from)?
+        .into_iter()
+        .filter(|m| m.channel_id == channel_id && !m.has_flag(message_flags::EPHEMERAL))
+        .collect();
+    messages.sort_unstable_by_key(|m| std::cmp::Reverse(m.id));
 
-        Ok(())
+    Ok(messages)
+}
+
+/// Load the messages a page of replies refers to, in one query, keyed by ID.
+///
+/// Only messages in the same channel as the reply are resolved.
+async fn load_referenced<M: MessageRepository + ?Sized>(
+    message_repo: &M,
+    messages: &[Message],
+) -> Result<HashMap<i64, Message>, AppError> {
+    let mut ids: Vec<i64> = messages.iter().filter_map(|m| m.reply_to_id).collect();
+    if ids.is_empty() {
+        return Ok(HashMap::new());
     }
+    ids.sort_unstable();
+    ids.dedup();
+
+    Ok(message_repo
+        .find_by_ids(&ids)
+        .await?
+        .into_iter()
+        .filter(|referenced| {
+            messages
+                .iter()
+                .any(|m| m.reply_to_id == Some(referenced.id) && m.channel_id == referenced.channel_id)
+        })
+        .map(|referenced| (referenced.id, referenced))
+        .collect())
+}
 
-    async fn pin_message(&self, channel_id: i64, message_id: i64, actor_id: i64) -> Result<(), MessageError> {
-        let mut message = self
-            .message_repo
-            .find_by_id(message_id)
-            .await
-            .map_err(|e| MessageError::Internal(e.to_string()))?
-            .ok_or(MessageError::NotFound)?;
+/// Attach a reply's referenced message, or mark it deleted when it wasn't
+/// found. Messages that aren't replies are left alone.
+fn attach_reference(dto: &mut MessageDto, reply_to_id: Option<i64>, referenced: &HashMap<i64, MessageDto>) {
+    let Some(reply_to_id) = reply_to_id else {
+        return;
+    };
 
-        // Verify channel matches
-        if message.channel_id != channel_id {
-            return Err(MessageError::NotFound);
-        }
+    match referenced.get(&reply_to_id) {
+        Some(message) => dto.referenced_message = Some(Box::new(message.clone())),
+        None => dto.referenced_message_deleted = true,
+    }
+}
 
-        // Check permission (simplified)
-        if !self.check_channel_access(channel_id, actor_id).await? {
-            return Err(MessageError::Forbidden);
-        }
+/// Sending embeds in a guild channel requires `EMBED_LINKS`.
+fn check_embed_permission(
+    embeds: &[Embed],
+    channel_permissions: Option<i64>,
+) -> Result<(), MessageError> {
+    if embeds.is_empty() {
+        return Ok(());
+    }
 
-        message.pinned = true;
+    require_channel_permission(channel_permissions, Permissions::EMBED_LINKS)
+}
 
-        self.message_repo
-            .update(&message)
-            .await
-            .map_err(|e| MessageError::Internal(e.to_string()))?;
+/// Text-to-speech messages need text to speak.
+fn validate_tts(tts: bool, content: &str) -> Result<(), MessageError> {
+    if tts && content.trim().is_empty() {
+        return Err(MessageError::EmptyTtsMessage);
+    }
+    Ok(())
+}
 
-        Ok(())
+/// Sending TTS messages in a guild channel requires `SEND_TTS_MESSAGES`.
+fn check_tts_permission(tts: bool, channel_permissions: Option<i64>) -> Result<(), MessageError> {
+    if !tts {
+        return Ok(());
     }
 
-    async fn unpin_message(&self, channel_id: i64, message_id: i64, actor_id: i64) -> Result<(), MessageError> {
-        let mut message = self
-            .message_repo
-            .find_by_id(message_id)
-            .await
-            .map_err(|e| MessageError::Internal(e.to_string()))?
-            .ok_or(MessageError::NotFound)?;
+    require_channel_permission(channel_permissions, Permissions::SEND_TTS_MESSAGES)
+}
 
-        // Verify channel matches
-        if message.channel_id != channel_id {
-            return Err(MessageError::NotFound);
+/// A member's permissions in a guild channel, served from the permission
+/// cache and computed by `compute` on a miss.
+///
+/// A failed read is logged and counted rather than failing the check, so a
+/// Redis outage costs a recomputation instead of an error. Only misses are
+/// written back; a cache that couldn't be read is left alone.
+async fn cached_or_computed<C, F, Fut>(
+    cache: &PermissionCacheService<C>,
+    channel_id: i64,
+    guild_id: i64,
+    user_id: i64,
+    compute: F,
+) -> Result<Option<i64>, MessageError>
+where
+    C: Cache,
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<Option<i64>, MessageError>>,
+{
+    let missed = match cache.get_channel_permissions(guild_id, channel_id, user_id).await {
+        Ok(Some(cached)) => return Ok(Some(cached.permissions as i64)),
+        Ok(None) => true,
+        Err(e) => {
+            record_permission_cache_fallback();
+            tracing::warn!(channel_id, error = %e, "Failed to read cached channel permissions, recomputing");
+            false
         }
+    };
+
+    let permissions = compute().await?;
+    let (Some(computed), true) = (permissions, missed) else {
+        return Ok(permissions);
+    };
+
+    let granted = Permissions::new(computed);
+    let cached = CachedChannelPermissions {
+        user_id,
+        channel_id,
+        guild_id,
+        permissions: computed as u64,
+        can_view: granted.has(Permissions::VIEW_CHANNEL),
+        can_send: granted.has(Permissions::SEND_MESSAGES),
+        can_manage: granted.has(Permissions::MANAGE_CHANNELS),
+    };
+    if let Err(e) = cache.set_channel_permissions(guild_id, channel_id, user_id, &cached).await {
+        tracing::warn!(channel_id, error = %e, "Failed to cache channel permissions");
+    }
 
-        // Check permission (simplified)
-        if !self.check_channel_access(channel_id, actor_id).await? {
-            return Err(MessageError::Forbidden);
-        }
+    Ok(permissions)
+}
 
-        message.pinned = false;
+/// Whether content pings everyone in the channel
+fn mentions_everyone(content: &str) -> bool {
+    content.contains("@everyone") || content.contains("@here")
+}
 
-        self.message_repo
-            .update(&message)
-            .await
-            .map_err(|e| MessageError::Internal(e.to_string()))?;
+/// Refuse a message that pings everyone while the channel's
+/// `@everyone`/`@here` cooldown runs; other messages pass untouched.
+///
+/// A cache failure lets the message through rather than blocking pings
+/// while Redis is unavailable.
+async fn check_mention_cooldown<C: Cache>(
+    cooldown: Option<&MentionCooldown<C>>,
+    channel_id: i64,
+    pings_everyone: bool,
+) -> Result<(), MessageError> {
+    let Some(cooldown) = cooldown.filter(|_| pings_everyone) else {
+        return Ok(());
+    };
+
+    match cooldown.remaining(channel_id).await {
+        Ok(None) => Ok(()),
+        Ok(Some(retry_after)) => Err(MessageError::MentionCooldown { retry_after }),
+        Err(e) => {
+            tracing::warn!(channel_id, error = %e, "Failed to check mention cooldown");
+            Ok(())
+        }
+    }
+}
 
-        Ok(())
+/// Start the channel's `@everyone`/`@here` cooldown once a message that
+/// pings everyone is stored, so a failed send never locks the channel.
+async fn start_mention_cooldown<C: Cache>(
+    cooldown: Option<&MentionCooldown<C>>,
+    channel_id: i64,
+    pings_everyone: bool,
+) {
+    let Some(cooldown) = cooldown.filter(|_| pings_everyone) else {
+        return;
+    };
+
+    if let Err(e) = cooldown.start(channel_id).await {
+        tracing::warn!(channel_id, error = %e, "Failed to start mention cooldown");
     }
+}
 
-    async fn get_pinned_messages(&self, channel_id: i64) -> Result<Vec<MessageDto>, MessageError> {
-        let messages = self
-            .message_repo
-            .find_pinned(channel_id)
-            .await
-            .map_err(|e| MessageError::Internal(e.to_string()))?;
+/// Whether a member skips a guild channel's slowmode: those with
+/// `MANAGE_MESSAGES` there, or holding one of the channel's exempt roles.
+fn slowmode_exempt(channel_permissions: i64, member_roles: &[i64], exempt_roles: &[i64]) -> bool {
+    Permissions::new(channel_permissions).has(Permissions::MANAGE_MESSAGES)
+        || member_roles.iter().any(|role| exempt_roles.contains(role))
+}
+
+/// The slowmode of a channel in seconds, or `None` if the author is exempt
+/// or the channel has none.
+fn slowmode_seconds(channel: &Channel, exempt: bool) -> Option<u64> {
+    u64::try_from(channel.rate_limit_per_user).ok().filter(|&seconds| seconds > 0 && !exempt)
+}
 
-        Ok(messages.into_iter().map(MessageDto::from).collect())
+/// Refuse a message while the author's slowmode cooldown in the channel
+/// runs; exempt authors and channels without slowmode pass untouched.
+///
+/// A cache failure lets the message through rather than blocking the
+/// channel while Redis is unavailable.
+async fn check_slowmode<C: Cache>(
+    slowmode: Option<&Slowmode<C>>,
+    channel: &Channel,
+    author_id: i64,
+    exempt: bool,
+) -> Result<(), MessageError> {
+    let (Some(slowmode), Some(_)) = (slowmode, slowmode_seconds(channel, exempt)) else {
+        return Ok(());
+    };
+
+    match slowmode.remaining(channel.id, author_id).await {
+        Ok(None) => Ok(()),
+        Ok(Some(retry_after)) => Err(MessageError::Slowmode { retry_after }),
+        Err(e) => {
+            tracing::warn!(channel_id = channel.id, error = %e, "Failed to check slowmode");
+            Ok(())
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Start the author's slowmode cooldown once their message is stored, so a
+/// failed send never holds them back.
+async fn start_slowmode<C: Cache>(slowmode: Option<&Slowmode<C>>, channel: &Channel, author_id: i64, exempt: bool) {
+    let (Some(slowmode), Some(seconds)) = (slowmode, slowmode_seconds(channel, exempt)) else {
+        return;
+    };
+
+    if let Err(e) = slowmode.start(channel.id, author_id, seconds).await {
+        tracing::warn!(channel_id = channel.id, error = %e, "Failed to start slowmode");
+    }
+}
 
-    // Tests would go here
+/// Build the system message announcing that `pinned_message_id` was pinned.
+fn pin_system_message(
+    id: i64,
+    channel_id: i64,
+    actor_id: i64,
+    pinned_message_id: i64,
+    now: DateTime<Utc>,
+) -> Message {
+    Message {
+        id,
+        channel_id,
+        author_id: actor_id,
+        content: String::new(),
+        message_type: MessageType::ChannelPinnedMessage,
+        reply_to_id: Some(pinned_message_id),
+        created_at: now,
+        ..Default::default()
+    }
+}
+
+/// Validate that every requested attachment exists, was uploaded by the
+/// author and has not been attached to another message yet.
+fn validate_attachments(
+    author_id: i64,
+    requested: &[i64],
+    found: &[Attachment],
+) -> Result<(), MessageError> {
+    for id in requested {
+        let attachment = found
+            .iter()
+            .find(|a| a.id == *id)
+            .ok_or(MessageError::AttachmentNotFound)?;
+
+        if attachment.uploader_id != Some(author_id) {
+            return Err(MessageError::Forbidden);
+        }
+
+        if attachment.is_attached() {
+            return Err(MessageError::AttachmentAlreadyAttached);
+        }
+    }
+
+    Ok(())
+}
+
+/// Load the profiles of message authors.
+///
+/// Cached profiles are used where available; every other author is loaded
+/// in a single query and written back to the cache. Cache failures are
+/// logged and fall back to the database.
+async fn load_authors<U, C>(
+    user_repo: &U,
+    cache: Option<&UserProfileCache<C>>,
+    author_ids: &[i64],
+) -> Result<HashMap<i64, UserDto>, AppError>
+where
+    U: UserRepository + ?Sized,
+    C: Cache,
+{
+    let mut ids = author_ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let mut profiles = match cache {
+        Some(cache) => cache.get_many(&ids).await.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Failed to read cached author profiles");
+            HashMap::new()
+        }),
+        None => HashMap::new(),
+    };
+
+    let missing: Vec<i64> = ids.into_iter().filter(|id| !profiles.contains_key(id)).collect();
+    if !missing.is_empty() {
+        let loaded: Vec<CachedUserProfile> = user_repo
+            .find_by_ids(&missing)
+            .await?
+            .into_iter()
+            .map(CachedUserProfile::from)
+            .collect();
+
+        if let Some(cache) = cache {
+            if let Err(e) = cache.set_many(&loaded).await {
+                tracing::warn!(error = %e, "Failed to cache author profiles");
+            }
+        }
+
+        profiles.extend(loaded.into_iter().map(|profile| (profile.id, profile)));
+    }
+
+    Ok(profiles
+        .into_iter()
+        .map(|(id, profile)| (id, UserDto::from(profile)))
+        .collect())
+}
+
+/// Resolved authors of a set of messages
+#[derive(Default)]
+struct MessageAuthors {
+    users: HashMap<i64, UserDto>,
+    members: HashMap<i64, MessageMemberDto>,
+}
+
+impl MessageAuthors {
+    fn apply(&self, dto: &mut MessageDto, author_id: i64) {
+        dto.author = self.users.get(&author_id).cloned();
+        dto.member = self.members.get(&author_id).cloned();
+    }
+}
+
+/// MessageService implementation
+///
+/// `K` is the cache backing the `@everyone` mention cooldown, `P` the one
+/// backing the permission cache and `W` the one backing slowmode.
+pub struct MessageServiceImpl<M, C, Mem, S, R, U, K = RedisCache, P = RedisCache, W = RedisCache>
+where
+    M: MessageRepository,
+    C: ChannelRepository,
+    Mem: MemberRepository,
+    S: ServerRepository,
+    R: RoleRepository,
+    U: UserRepository,
+    K: Cache,
+    P: Cache,
+    W: Cache,
+{
+    message_repo: Arc<M>,
+    channel_repo: Arc<C>,
+    member_repo: Arc<Mem>,
+    server_repo: Arc<S>,
+    role_repo: Arc<R>,
+    user_repo: Arc<U>,
+    id_generator: Arc<SnowflakeGenerator>,
+    content_filter: Arc<dyn ContentFilter>,
+    message_counts: Option<Arc<dyn MessageCountStore>>,
+    write_batcher: Option<MessageWriteBatcher>,
+    author_cache: Option<UserProfileCache>,
+    mention_cooldown: Option<MentionCooldown<K>>,
+    permission_cache: Option<PermissionCacheService<P>>,
+    slowmode: Option<Slowmode<W>>,
+}
+
+impl<M, C, Mem, S, R, U> MessageServiceImpl<M, C, Mem, S, R, U>
+where
+    M: MessageRepository,
+    C: ChannelRepository,
+    Mem: MemberRepository,
+    S: ServerRepository,
+    R: RoleRepository,
+    U: UserRepository,
+{
+    pub fn new(
+        message_repo: Arc<M>,
+        channel_repo: Arc<C>,
+        member_repo: Arc<Mem>,
+        server_repo: Arc<S>,
+        role_repo: Arc<R>,
+        user_repo: Arc<U>,
+        id_generator: Arc<SnowflakeGenerator>,
+    ) -> Self {
+        Self {
+            message_repo,
+            channel_repo,
+            member_repo,
+            server_repo,
+            role_repo,
+            user_repo,
+            id_generator,
+            content_filter: Arc::new(NoopContentFilter),
+            message_counts: None,
+            write_batcher: None,
+            author_cache: None,
+            mention_cooldown: None,
+            permission_cache: None,
+            slowmode: None,
+        }
+    }
+}
+
+impl<M, C, Mem, S, R, U, K, P, W> MessageServiceImpl<M, C, Mem, S, R, U, K, P, W>
+where
+    M: MessageRepository,
+    C: ChannelRepository,
+    Mem: MemberRepository,
+    S: ServerRepository,
+    R: RoleRepository,
+    U: UserRepository,
+    K: Cache,
+    P: Cache,
+    W: Cache,
+{
+    /// Run new messages through the given content filter before storing them
+    pub fn with_content_filter(mut self, content_filter: Arc<dyn ContentFilter>) -> Self {
+        self.content_filter = content_filter;
+        self
+    }
+
+    /// Cache channel message counts, keeping them up to date as messages
+    /// are created and deleted
+    pub fn with_message_counts(mut self, counts: Arc<dyn MessageCountStore>) -> Self {
+        self.message_counts = Some(counts);
+        self
+    }
+
+    /// Write messages without attachments through the given batcher
+    /// instead of one INSERT each
+    pub fn with_write_batcher(mut self, batcher: MessageWriteBatcher) -> Self {
+        self.write_batcher = Some(batcher);
+        self
+    }
+
+    /// Cache author profiles resolved for message responses
+    pub fn with_author_cache(mut self, cache: UserProfileCache) -> Self {
+        self.author_cache = Some(cache);
+        self
+    }
+
+    /// Limit how often members allowed to mention everyone can ping a channel
+    pub fn with_mention_cooldown<T: Cache>(
+        self,
+        cooldown: MentionCooldown<T>,
+    ) -> MessageServiceImpl<M, C, Mem, S, R, U, T, P, W> {
+        MessageServiceImpl {
+            message_repo: self.message_repo,
+            channel_repo: self.channel_repo,
+            member_repo: self.member_repo,
+            server_repo: self.server_repo,
+            role_repo: self.role_repo,
+            user_repo: self.user_repo,
+            id_generator: self.id_generator,
+            content_filter: self.content_filter,
+            message_counts: self.message_counts,
+            write_batcher: self.write_batcher,
+            author_cache: self.author_cache,
+            mention_cooldown: Some(cooldown),
+            permission_cache: self.permission_cache,
+            slowmode: self.slowmode,
+        }
+    }
+
+    /// Enforce channels' slowmode on new messages
+    pub fn with_slowmode<T: Cache>(self, slowmode: Slowmode<T>) -> MessageServiceImpl<M, C, Mem, S, R, U, K, P, T> {
+        MessageServiceImpl {
+            message_repo: self.message_repo,
+            channel_repo: self.channel_repo,
+            member_repo: self.member_repo,
+            server_repo: self.server_repo,
+            role_repo: self.role_repo,
+            user_repo: self.user_repo,
+            id_generator: self.id_generator,
+            content_filter: self.content_filter,
+            message_counts: self.message_counts,
+            write_batcher: self.write_batcher,
+            author_cache: self.author_cache,
+            mention_cooldown: self.mention_cooldown,
+            permission_cache: self.permission_cache,
+            slowmode: Some(slowmode),
+        }
+    }
+
+    /// Cache members' computed channel permissions.
+    ///
+    /// Entries are dropped when overwrites or roles change and otherwise
+    /// expire with the cache's TTL.
+    pub fn with_permission_cache<T: Cache>(
+        self,
+        cache: PermissionCacheService<T>,
+    ) -> MessageServiceImpl<M, C, Mem, S, R, U, K, T, W> {
+        MessageServiceImpl {
+            message_repo: self.message_repo,
+            channel_repo: self.channel_repo,
+            member_repo: self.member_repo,
+            server_repo: self.server_repo,
+            role_repo: self.role_repo,
+            user_repo: self.user_repo,
+            id_generator: self.id_generator,
+            content_filter: self.content_filter,
+            message_counts: self.message_counts,
+            write_batcher: self.write_batcher,
+            author_cache: self.author_cache,
+            mention_cooldown: self.mention_cooldown,
+            permission_cache: Some(cache),
+            slowmode: self.slowmode,
+        }
+    }
+
+    /// Apply a change to the cached message count of a channel.
+    ///
+    /// The message is already stored at this point, so failures are logged
+    /// rather than surfaced; the count is corrected on reconciliation.
+    async fn adjust_message_count(&self, channel_id: i64, delta: i64) {
+        let Some(counts) = &self.message_counts else {
+            return;
+        };
+
+        let result = if delta >= 0 {
+            counts.increment(channel_id).await
+        } else {
+            counts.decrement_by(channel_id, delta.unsigned_abs()).await
+        };
+
+        if let Err(e) = result {
+            tracing::warn!(channel_id, error = %e, "Failed to update channel message count");
+        }
+    }
+
+    async fn check_channel_access(&self, channel_id: i64, user_id: i64) -> Result<bool, MessageError> {
+        let channel = self.find_channel(channel_id).await?;
+        self.can_access(&channel, user_id).await
+    }
+
+    /// Check whether a user can see a loaded channel.
+    async fn can_access(&self, channel: &Channel, user_id: i64) -> Result<bool, MessageError> {
+        match required_access(channel) {
+            ChannelAccess::Member(guild_id) => self
+                .member_repo
+                .is_member(guild_id, user_id)
+                .await
+                .map_err(MessageError::from),
+            ChannelAccess::Recipient => self
+                .channel_repo
+                .is_recipient(channel.id, user_id)
+                .await
+                .map_err(MessageError::from),
+        }
+    }
+
+    /// [`check_nsfw_access`] for a user, whose NSFW setting is only looked
+    /// up in NSFW channels.
+    async fn require_nsfw_access(&self, channel: &Channel, permissions: Option<i64>, user_id: i64) -> Result<(), MessageError> {
+        let nsfw_allowed = permission_resolver::nsfw_allowed(self.user_repo.as_ref(), channel, user_id)
+            .await
+            .map_err(MessageError::from)?;
+        check_nsfw_access(channel, permissions, nsfw_allowed)
+    }
+
+    async fn find_channel(&self, channel_id: i64) -> Result<Channel, MessageError> {
+        self.channel_repo
+            .find_by_id(channel_id)
+            .await
+            .map_err(MessageError::from)?
+            .ok_or(MessageError::ChannelNotFound)
+    }
+
+    /// Compute a user's effective permissions in a channel.
+    ///
+    /// Returns `None` for DM channels, which have no permission model.
+    async fn channel_permissions(&self, channel: &Channel, user_id: i64) -> Result<Option<i64>, MessageError> {
+        if channel.server_id.is_none() {
+            return Ok(None);
+        }
+
+        permission_resolver::channel_permissions(
+            self.server_repo.as_ref(),
+            self.member_repo.as_ref(),
+            self.role_repo.as_ref(),
+            self.channel_repo.as_ref(),
+            channel,
+            user_id,
+        )
+        .await
+        .map_err(MessageError::from)?
+        .map(Some)
+        .ok_or(MessageError::Forbidden)
+    }
+
+    /// [`Self::channel_permissions`], served from the permission cache when
+    /// one is attached.
+    ///
+    /// Cache failures fall back to computing the permissions.
+    async fn cached_channel_permissions(&self, channel: &Channel, user_id: i64) -> Result<Option<i64>, MessageError> {
+        let (Some(cache), Some(guild_id)) = (&self.permission_cache, channel.server_id) else {
+            return self.channel_permissions(channel, user_id).await;
+        };
+
+        cached_or_computed(cache, channel.id, guild_id, user_id, || {
+            self.channel_permissions(channel, user_id)
+        })
+        .await
+    }
+
+    /// Whether a member skips a guild channel's slowmode.
+    ///
+    /// Their roles are only looked up when the channel has exempt roles.
+    async fn is_slowmode_exempt(&self, channel: &Channel, user_id: i64, permissions: i64) -> Result<bool, MessageError> {
+        let roles = match channel.server_id {
+            Some(guild_id) if !channel.slowmode_exempt_roles.is_empty() => self
+                .member_repo
+                .find(guild_id, user_id)
+                .await
+                .map_err(MessageError::from)?
+                .map(|member| member.roles)
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+
+        Ok(slowmode_exempt(permissions, &roles, &channel.slowmode_exempt_roles))
+    }
+
+    /// Load a channel and one of its messages for pinning or unpinning,
+    /// requiring `MANAGE_MESSAGES`.
+    async fn load_for_pin_change(
+        &self,
+        channel_id: i64,
+        message_id: i64,
+        actor_id: i64,
+    ) -> Result<(Channel, Message), MessageError> {
+        let channel = self.find_channel(channel_id).await?;
+
+        let message = self
+            .message_repo
+            .find_by_id(message_id)
+            .await
+            .map_err(MessageError::from)?
+            .ok_or(MessageError::NotFound)?;
+
+        // The message must belong to the target channel
+        if message.channel_id != channel_id {
+            return Err(MessageError::NotFound);
+        }
+
+        if channel.server_id.is_none() && !self.check_channel_access(channel_id, actor_id).await? {
+            return Err(MessageError::Forbidden);
+        }
+
+        let permissions = self.channel_permissions(&channel, actor_id).await?;
+        require_channel_permission(permissions, Permissions::MANAGE_MESSAGES)?;
+
+        Ok((channel, message))
+    }
+
+    /// Resolve message authors, with their member details when the
+    /// messages are in a guild.
+    async fn resolve_authors(&self, guild_id: Option<i64>, author_ids: &[i64]) -> Result<MessageAuthors, MessageError> {
+        let users = load_authors(self.user_repo.as_ref(), self.author_cache.as_ref(), author_ids)
+            .await
+            .map_err(MessageError::from)?;
+
+        let members = match guild_id {
+            Some(guild_id) => self
+                .member_repo
+                .find_many(guild_id, author_ids)
+                .await
+                .map_err(MessageError::from)?
+                .into_iter()
+                .map(|member| {
+                    let details = MessageMemberDto {
+                        nickname: member.nickname,
+                        roles: member.roles.iter().map(|id| id.to_string()).collect(),
+                    };
+                    (member.user_id, details)
+                })
+                .collect(),
+            None => HashMap::new(),
+        };
+
+        Ok(MessageAuthors { users, members })
+    }
+
+    /// Convert messages to DTOs with their attachments, authors and
+    /// referenced messages loaded in one query each.
+    async fn to_dtos_with_attachments(&self, guild_id: Option<i64>, messages: Vec<Message>) -> Result<Vec<MessageDto>, MessageError> {
+        let message_ids: Vec<i64> = messages.iter().map(|m| m.id).collect();
+        let referenced = load_referenced(self.message_repo.as_ref(), &messages)
+            .await
+            .map_err(MessageError::from)?;
+
+        let mut author_ids: Vec<i64> = messages
+            .iter()
+            .chain(referenced.values())
+            .map(|m| m.author_id)
+            .collect();
+        author_ids.sort_unstable();
+        author_ids.dedup();
+        let authors = self.resolve_authors(guild_id, &author_ids).await?;
+
+        let attachments = self
+            .message_repo
+            .find_attachments_by_message_ids(&message_ids)
+            .await
+            .map_err(MessageError::from)?;
+
+        let mut by_message: HashMap<i64, Vec<AttachmentDto>> = HashMap::new();
+        for attachment in attachments {
+            if let Some(message_id) = attachment.message_id {
+                by_message
+                    .entry(message_id)
+                    .or_default()
+                    .push(AttachmentDto::from(attachment));
+            }
+        }
+
+        // Referenced messages are shallow: no attachments or reference of their own
+        let referenced: HashMap<i64, MessageDto> = referenced
+            .into_iter()
+            .map(|(id, message)| {
+                let author_id = message.author_id;
+                let mut dto = MessageDto::from(message);
+                authors.apply(&mut dto, author_id);
+                (id, dto)
+            })
+            .collect();
+
+        Ok(messages
+            .into_iter()
+            .map(|message| {
+                let attachments = by_message.remove(&message.id).unwrap_or_default();
+                let author_id = message.author_id;
+                let reply_to_id = message.reply_to_id;
+                let mut dto = MessageDto::from(message);
+                dto.attachments = attachments;
+                authors.apply(&mut dto, author_id);
+                attach_reference(&mut dto, reply_to_id, &referenced);
+                dto
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl<M, C, Mem, S, R, U, K, P, W> MessageService for MessageServiceImpl<M, C, Mem, S, R, U, K, P, W>
+where
+    M: MessageRepository + 'static,
+    C: ChannelRepository + 'static,
+    Mem: MemberRepository + 'static,
+    S: ServerRepository + 'static,
+    R: RoleRepository + 'static,
+    U: UserRepository + 'static,
+    K: Cache + 'static,
+    P: Cache + 'static,
+    W: Cache + 'static,
+{
+    async fn send_message(&self, channel_id: i64, author_id: i64, request: CreateMessageDto) -> Result<MessageDto, MessageError> {
+        let channel = self.find_channel(channel_id).await?;
+        check_text_based(&channel)?;
+
+        // Check access
+        if !self.can_access(&channel, author_id).await? {
+            return Err(MessageError::Forbidden);
+        }
+
+        let mut attachment_ids = request.attachment_ids;
+        attachment_ids.sort_unstable();
+        attachment_ids.dedup();
+
+        validate_content(&request.content, attachment_ids.len() + request.embeds.len())?;
+        validate_tts(request.tts, &request.content)?;
+        validate_embeds(&request.embeds)?;
+        validate_flags(request.flags)?;
+
+        // Only members allowed to mention everyone actually ping
+        let mut pings_everyone = false;
+        let mut slowmode_exempt = true;
+        let in_slowmode = channel.rate_limit_per_user > 0 && self.slowmode.is_some();
+        if channel.nsfw || in_slowmode || !request.embeds.is_empty() || request.tts || mentions_everyone(&request.content) {
+            let permissions = self.channel_permissions(&channel, author_id).await?;
+            self.require_nsfw_access(&channel, permissions, author_id).await?;
+            check_embed_permission(&request.embeds, permissions)?;
+            check_tts_permission(request.tts, permissions)?;
+            pings_everyone = mentions_everyone(&request.content)
+                && permissions.is_some_and(|p| Permissions::new(p).has(Permissions::MENTION_EVERYONE));
+            if let (true, Some(permissions)) = (in_slowmode, permissions) {
+                slowmode_exempt = self.is_slowmode_exempt(&channel, author_id, permissions).await?;
+            }
+        }
+
+        let attachments = self
+            .message_repo
+            .find_attachments_by_ids(&attachment_ids)
+            .await
+            .map_err(MessageError::from)?;
+
+        validate_attachments(author_id, &attachment_ids, &attachments)?;
+
+        let context = MessageContext {
+            channel_id,
+            guild_id: channel.server_id,
+            author_id,
+            content: request.content,
+            attachments,
+            embeds: request.embeds,
+        };
+        screen_message(self.content_filter.as_ref(), &context).await?;
+        let MessageContext { content, attachments, embeds, .. } = context;
+
+        check_slowmode(self.slowmode.as_ref(), &channel, author_id, slowmode_exempt).await?;
+        check_mention_cooldown(self.mention_cooldown.as_ref(), channel_id, pings_everyone).await?;
+
+        let now = Utc::now();
+        let message_type = if request.reply_to.is_some() {
+            MessageType::Reply
+        } else {
+            MessageType::Default
+        };
+
+        let message = Message {
+            id: self.id_generator.generate(),
+            channel_id,
+            author_id,
+            content,
+            message_type,
+            reply_to_id: request.reply_to,
+            pinned: false,
+            tts: request.tts,
+            flags: request.flags,
+            embeds,
+            edited_at: None,
+            created_at: now,
+        };
+
+        let created = match &self.write_batcher {
+            // Attachments are claimed in their own transaction
+            Some(batcher) if attachment_ids.is_empty() => batcher.create(message).await,
+            _ => {
+                self.message_repo
+                    .create_with_attachments(&message, &attachment_ids)
+                    .await
+            }
+        }
+        .map_err(|e| match e {
+            // Lost a race with another message claiming the same upload
+            AppError::Conflict(_) => MessageError::AttachmentAlreadyAttached,
+            e => MessageError::from(e),
+        })?;
+
+        start_slowmode(self.slowmode.as_ref(), &channel, author_id, slowmode_exempt).await;
+        start_mention_cooldown(self.mention_cooldown.as_ref(), channel_id, pings_everyone).await;
+        self.adjust_message_count(channel_id, 1).await;
+
+        // The message is stored; a failed lookup of its author or referenced
+        // message only leaves them out
+        match self.to_dtos_with_attachments(channel.server_id, vec![created.clone()]).await {
+            Ok(mut dtos) => Ok(dtos.remove(0)),
+            Err(e) => {
+                tracing::warn!(channel_id, error = %e, "Failed to resolve message author or reference");
+                let mut dto = MessageDto::from(created);
+                dto.attachments = attachments.into_iter().map(AttachmentDto::from).collect();
+                Ok(dto)
+            }
+        }
+    }
+
+    async fn get_messages(&self, channel_id: i64, user_id: i64, query: MessageQueryDto) -> Result<Vec<MessageDto>, MessageError> {
+        // Check channel access authorization
+        let channel = self.find_channel(channel_id).await?;
+        if !self.can_access(&channel, user_id).await? {
+            return Err(MessageError::Forbidden);
+        }
+
+        let permissions = self.cached_channel_permissions(&channel, user_id).await?;
+        self.require_nsfw_access(&channel, permissions, user_id).await?;
+        let messages = load_history(self.message_repo.as_ref(), channel_id, permissions, &query).await?;
+
+        self.to_dtos_with_attachments(channel.server_id, messages).await
+    }
+
+    async fn get_messages_by_ids(&self, channel_id: i64, ids: Vec<i64>, actor_id: i64) -> Result<Vec<MessageDto>, MessageError> {
+        let channel = self.find_channel(channel_id).await?;
+        if !self.can_access(&channel, actor_id).await? {
+            return Err(MessageError::Forbidden);
+        }
+
+        let permissions = self.cached_channel_permissions(&channel, actor_id).await?;
+        self.require_nsfw_access(&channel, permissions, actor_id).await?;
+        let messages = load_by_ids(self.message_repo.as_ref(), channel_id, permissions, &ids).await?;
+
+        self.to_dtos_with_attachments(channel.server_id, messages).await
+    }
+
+    async fn get_message(&self, channel_id: i64, message_id: i64) -> Result<MessageDto, MessageError> {
+        let message = self
+            .message_repo
+            .find_by_id(message_id)
+            .await
+            .map_err(MessageError::from)?
+            .ok_or(MessageError::NotFound)?;
+
+        // Verify channel matches
+        if message.channel_id != channel_id {
+            return Err(MessageError::NotFound);
+        }
+
+        let channel = self.find_channel(channel_id).await?;
+        let mut dtos = self.to_dtos_with_attachments(channel.server_id, vec![message]).await?;
+        Ok(dtos.remove(0))
+    }
+
+    async fn edit_message(&self, message_id: i64, actor_id: i64, edit: EditMessageDto) -> Result<MessageDto, MessageError> {
+        let mut message = self
+            .message_repo
+            .find_by_id(message_id)
+            .await
+            .map_err(MessageError::from)?
+            .ok_or(MessageError::NotFound)?;
+
+        if let Some(content) = &edit.content {
+            check_editable(&message, actor_id)?;
+
+            // Content may only be emptied if something else is left to show
+            let attachments = self
+                .message_repo
+                .find_attachments_by_message_ids(&[message.id])
+                .await
+                .map_err(MessageError::from)?;
+            validate_content(content, attachments.len() + message.embeds.len())?;
+            validate_tts(message.tts, content)?;
+        }
+
+        let channel = self.find_channel(message.channel_id).await?;
+        if let Some(flags) = edit.flags {
+            validate_flags(flags)?;
+            // Moderators may suppress embeds on others' messages
+            let permissions = if message.author_id == actor_id {
+                None
+            } else {
+                self.channel_permissions(&channel, actor_id).await?
+            };
+            check_flags_editable(&message, actor_id, permissions)?;
+            message.flags = edited_flags(message.flags, flags);
+        }
+
+        // The repository queues MESSAGE_UPDATE with the stored edit
+        // and marks the message edited only if its content changed
+        if let Some(content) = edit.content {
+            message.content = content;
+        }
+
+        let updated = self
+            .message_repo
+            .update(&message)
+            .await
+            .map_err(MessageError::from)?;
+
+        let mut dtos = self.to_dtos_with_attachments(channel.server_id, vec![updated]).await?;
+        Ok(dtos.remove(0))
+    }
+
+    async fn delete_message(&self, message_id: i64, actor_id: i64) -> Result<(), MessageError> {
+        let message = self
+            .message_repo
+            .find_by_id(message_id)
+            .await
+            .map_err(MessageError::from)?
+            .ok_or(MessageError::NotFound)?;
+
+        // Only author can delete (simplified - full implementation would check MANAGE_MESSAGES permission)
+        if message.author_id != actor_id {
+            return Err(MessageError::Forbidden);
+        }
+
+        self.message_repo
+            .delete(message_id)
+            .await
+            .map_err(MessageError::from)?;
+
+        self.adjust_message_count(message.channel_id, -1).await;
+
+        Ok(())
+    }
+
+    async fn bulk_delete_messages(
+        &self,
+        channel_id: i64,
+        actor_id: i64,
+        message_ids: Vec<i64>,
+    ) -> Result<Vec<i64>, MessageError> {
+        let mut ids = message_ids;
+        ids.sort_unstable();
+        ids.dedup();
+        if ids.is_empty() || ids.len() > MAX_MESSAGE_LIMIT as usize {
+            return Err(MessageError::InvalidBulkDelete);
+        }
+
+        let channel = self.find_channel(channel_id).await?;
+        if channel.server_id.is_none() && !self.check_channel_access(channel_id, actor_id).await? {
+            return Err(MessageError::Forbidden);
+        }
+
+        let permissions = self.channel_permissions(&channel, actor_id).await?;
+        require_channel_permission(permissions, Permissions::MANAGE_MESSAGES)?;
+
+        let deleted = self
+            .message_repo
+            .bulk_delete(channel_id, ids)
+            .await
+            .map_err(MessageError::from)?;
+
+        if !deleted.is_empty() {
+            self.adjust_message_count(channel_id, -(deleted.len() as i64)).await;
+        }
+
+        Ok(deleted)
+    }
+
+    async fn pin_message(&self, channel_id: i64, message_id: i64, actor_id: i64) -> Result<PinsUpdateDto, MessageError> {
+        let (channel, message) = self.load_for_pin_change(channel_id, message_id, actor_id).await?;
+
+        let mut update = PinsUpdateDto {
+            channel_id: channel_id.to_string(),
+            guild_id: channel.server_id.map(|id| id.to_string()),
+            last_pin_timestamp: None,
+            system_message: None,
+        };
+
+        // Pinning twice is a no-op and doesn't post another system message
+        if message.pinned {
+            return Ok(update);
+        }
+
+        let now = Utc::now();
+        let system_message = pin_system_message(
+            self.id_generator.generate(),
+            channel_id,
+            actor_id,
+            message_id,
+            now,
+        );
+
+        let created = self
+            .message_repo
+            .pin(message_id, &system_message)
+            .await
+            .map_err(|e| match e {
+                AppError::NotFound(_) => MessageError::NotFound,
+                e => MessageError::from(e),
+            })?;
+
+        // Pinned concurrently by someone else, who posted the system message
+        let Some(created) = created else {
+            return Ok(update);
+        };
+
+        self.adjust_message_count(channel_id, 1).await;
+
+        update.last_pin_timestamp = Some(now.to_rfc3339());
+        update.system_message = Some(MessageDto::from(created));
+
+        Ok(update)
+    }
+
+    async fn unpin_message(&self, channel_id: i64, message_id: i64, actor_id: i64) -> Result<PinsUpdateDto, MessageError> {
+        let (channel, message) = self.load_for_pin_change(channel_id, message_id, actor_id).await?;
+
+        if message.pinned {
+            self.message_repo
+                .unpin(message_id)
+                .await
+                .map_err(MessageError::from)?;
+        }
+
+        Ok(PinsUpdateDto {
+            channel_id: channel_id.to_string(),
+            guild_id: channel.server_id.map(|id| id.to_string()),
+            last_pin_timestamp: None,
+            system_message: None,
+        })
+    }
+
+    async fn get_pinned_messages(&self, channel_id: i64) -> Result<Vec<MessageDto>, MessageError> {
+        let messages = self
+            .message_repo
+            .find_pinned(channel_id)
+            .await
+            .map_err(MessageError::from)?;
+
+        let channel = self.find_channel(channel_id).await?;
+        self.to_dtos_with_attachments(channel.server_id, messages).await
+    }
+
+    async fn channel_message_count(&self, channel_id: i64, user_id: i64) -> Result<i64, MessageError> {
+        if !self.check_channel_access(channel_id, user_id).await? {
+            return Err(MessageError::Forbidden);
+        }
+
+        if let Some(counts) = &self.message_counts {
+            match counts.get(channel_id).await {
+                Ok(Some(count)) => return Ok(count),
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!(channel_id, error = %e, "Failed to read channel message count");
+                }
+            }
+        }
+
+        let count = self
+            .message_repo
+            .count_by_channel(channel_id)
+            .await
+            .map_err(MessageError::from)?;
+
+        if let Some(counts) = &self.message_counts {
+            match counts.reconcile(channel_id, count).await {
+                Ok(Some(previous)) if previous != count => {
+                    tracing::debug!(channel_id, previous, count, "Reconciled channel message count");
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!(channel_id, error = %e, "Failed to cache channel message count");
+                }
+            }
+        }
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    use crate::domain::{ChannelType, OwnedGuildAction, User, UserStatus};
+    use crate::infrastructure::cache::InMemoryCache;
+    use uuid::Uuid;
+
+    fn pending_attachment(id: i64, uploader_id: i64) -> Attachment {
+        Attachment {
+            id,
+            uploader_id: Some(uploader_id),
+            filename: "photo.png".to_string(),
+            size: 1024,
+            url: "https://cdn.example.com/photo.png".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn channel(channel_type: ChannelType, server_id: Option<i64>) -> Channel {
+        Channel {
+            id: 1,
+            server_id,
+            channel_type,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_posting_to_category_rejected() {
+        let category = channel(ChannelType::Category, Some(100));
+
+        assert!(matches!(check_text_based(&category), Err(MessageError::InvalidChannelType)));
+    }
+
+    #[test]
+    fn test_posting_to_voice_channel_rejected() {
+        let voice = channel(ChannelType::Voice, Some(100));
+
+        assert!(matches!(check_text_based(&voice), Err(MessageError::InvalidChannelType)));
+    }
+
+    #[test]
+    fn test_dm_requires_recipient() {
+        let dm = channel(ChannelType::Dm, None);
+        let text = channel(ChannelType::Text, Some(100));
+
+        assert!(check_text_based(&dm).is_ok());
+        assert_eq!(required_access(&dm), ChannelAccess::Recipient);
+        assert_eq!(required_access(&channel(ChannelType::GroupDm, None)), ChannelAccess::Recipient);
+        assert_eq!(required_access(&text), ChannelAccess::Member(100));
+    }
+
+    #[test]
+    fn test_empty_content_allowed_with_attachment() {
+        assert!(validate_content("", 1).is_ok());
+        assert!(validate_attachments(1, &[10], &[pending_attachment(10, 1)]).is_ok());
+    }
+
+    #[test]
+    fn test_empty_content_without_attachments_rejected() {
+        assert!(matches!(validate_content("   ", 0), Err(MessageError::EmptyMessage)));
+    }
+
+    #[test]
+    fn test_already_attached_attachment_rejected() {
+        let mut attachment = pending_attachment(10, 1);
+        attachment.message_id = Some(99);
+
+        let result = validate_attachments(1, &[10], &[attachment]);
+        assert!(matches!(result, Err(MessageError::AttachmentAlreadyAttached)));
+    }
+
+    #[test]
+    fn test_attachment_owned_by_another_user_rejected() {
+        let result = validate_attachments(1, &[10], &[pending_attachment(10, 2)]);
+        assert!(matches!(result, Err(MessageError::Forbidden)));
+    }
+
+    #[test]
+    fn test_missing_attachment_rejected() {
+        let result = validate_attachments(1, &[10, 11], &[pending_attachment(10, 1)]);
+        assert!(matches!(result, Err(MessageError::AttachmentNotFound)));
+    }
+
+    fn titled_embed(title: &str) -> Embed {
+        Embed {
+            title: Some(title.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_embed_count_capped() {
+        let embeds = vec![titled_embed("hi"); MAX_EMBEDS];
+        assert!(validate_embeds(&embeds).is_ok());
+
+        let embeds = vec![titled_embed("hi"); MAX_EMBEDS + 1];
+        assert!(matches!(validate_embeds(&embeds), Err(MessageError::TooManyEmbeds)));
+    }
+
+    #[test]
+    fn test_embed_field_limits() {
+        let long_title = titled_embed(&"a".repeat(Embed::MAX_TITLE_LENGTH + 1));
+        assert!(matches!(validate_embeds(&[long_title]), Err(MessageError::InvalidEmbed(_))));
+
+        // Each embed is within limits, but together they exceed the total
+        let big = Embed {
+            description: Some("a".repeat(Embed::MAX_DESCRIPTION_LENGTH)),
+            ..Default::default()
+        };
+        assert!(validate_embeds(std::slice::from_ref(&big)).is_ok());
+        assert!(matches!(validate_embeds(&[big.clone(), big]), Err(MessageError::InvalidEmbed(_))));
+    }
+
+    #[test]
+    fn test_embed_urls_must_be_http() {
+        let linked = |url: &str| Embed {
+            url: Some(url.to_string()),
+            ..titled_embed("hi")
+        };
+        assert!(validate_embeds(&[linked("https://example.com/page")]).is_ok());
+        assert!(validate_embeds(&[linked("http://example.com")]).is_ok());
+
+        for url in ["javascript:alert(1)", "data:text/html,hi", "/relative", "https://"] {
+            assert!(matches!(validate_embeds(&[linked(url)]), Err(MessageError::InvalidEmbed(_))), "{url}");
+        }
+
+        let bad_image = Embed {
+            image: Some(crate::domain::EmbedImage { url: "file:///etc/passwd".to_string() }),
+            ..titled_embed("hi")
+        };
+        assert!(matches!(validate_embeds(&[bad_image]), Err(MessageError::InvalidEmbed(_))));
+    }
+
+    #[test]
+    fn test_embeds_require_embed_links() {
+        let embeds = vec![titled_embed("hi")];
+        let without = Permissions::SEND_MESSAGES;
+        let with = Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS;
+
+        assert!(matches!(
+            check_embed_permission(&embeds, Some(without)),
+            Err(MessageError::Forbidden)
+        ));
+        assert!(check_embed_permission(&embeds, Some(with)).is_ok());
+        assert!(check_embed_permission(&[], Some(without)).is_ok());
+        // DM channels have no permission model
+        assert!(check_embed_permission(&embeds, None).is_ok());
+    }
+
+    #[test]
+    fn test_tts_requires_send_tts_messages() {
+        let without = Permissions::SEND_MESSAGES;
+        let with = Permissions::SEND_MESSAGES | Permissions::SEND_TTS_MESSAGES;
+
+        assert!(matches!(check_tts_permission(true, Some(without)), Err(MessageError::Forbidden)));
+        assert!(check_tts_permission(true, Some(with)).is_ok());
+        assert!(check_tts_permission(false, Some(without)).is_ok());
+        // DM channels have no permission model
+        assert!(check_tts_permission(true, None).is_ok());
+    }
+
+    #[test]
+    fn test_tts_requires_content() {
+        assert!(matches!(validate_tts(true, "  "), Err(MessageError::EmptyTtsMessage)));
+        assert!(validate_tts(true, "hello").is_ok());
+        assert!(validate_tts(false, "").is_ok());
+    }
+
+    #[test]
+    fn test_tts_flag_reaches_dto() {
+        let message = Message {
+            id: 1,
+            channel_id: 100,
+            author_id: 200,
+            content: "hello".to_string(),
+            tts: true,
+            ..Default::default()
+        };
+
+        assert!(MessageDto::from(message.clone()).tts);
+        assert!(!MessageDto::from(Message { tts: false, ..message }).tts);
+    }
+
+    #[test]
+    fn test_only_suppress_embeds_can_be_requested() {
+        assert!(validate_flags(0).is_ok());
+        assert!(validate_flags(message_flags::SUPPRESS_EMBEDS).is_ok());
+        assert!(matches!(validate_flags(message_flags::EPHEMERAL), Err(MessageError::InvalidFlags)));
+        assert!(matches!(
+            validate_flags(message_flags::SUPPRESS_EMBEDS | message_flags::CROSSPOSTED),
+            Err(MessageError::InvalidFlags)
+        ));
+    }
+
+    #[test]
+    fn test_suppress_embeds_edit_by_author_or_moderator() {
+        let message = Message {
+            id: 1,
+            channel_id: 100,
+            author_id: 200,
+            flags: message_flags::CROSSPOSTED,
+            ..Default::default()
+        };
+        let member = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+
+        assert!(check_flags_editable(&message, 200, None).is_ok());
+        assert!(check_flags_editable(&message, 300, Some(member | Permissions::MANAGE_MESSAGES)).is_ok());
+        assert!(matches!(check_flags_editable(&message, 300, Some(member)), Err(MessageError::Forbidden)));
+        // No moderators in DMs
+        assert!(matches!(check_flags_editable(&message, 300, None), Err(MessageError::NotAuthor)));
+
+        let system = Message {
+            message_type: MessageType::ChannelPinnedMessage,
+            ..message.clone()
+        };
+        assert!(matches!(check_flags_editable(&system, 200, None), Err(MessageError::SystemMessage)));
+
+        // Suppressing and restoring embeds leaves other flags alone
+        let suppressed = edited_flags(message.flags, message_flags::SUPPRESS_EMBEDS);
+        assert_eq!(suppressed, message_flags::CROSSPOSTED | message_flags::SUPPRESS_EMBEDS);
+        assert_eq!(edited_flags(suppressed, 0), message_flags::CROSSPOSTED);
+
+        let dto = MessageDto::from(Message { flags: suppressed, ..message });
+        assert_eq!(dto.flags, suppressed);
+    }
+
+    #[test]
+    fn test_pin_requires_manage_messages() {
+        let member = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+
+        assert!(matches!(
+            require_channel_permission(Some(member), Permissions::MANAGE_MESSAGES),
+            Err(MessageError::Forbidden)
+        ));
+        assert!(require_channel_permission(
+            Some(member | Permissions::MANAGE_MESSAGES),
+            Permissions::MANAGE_MESSAGES
+        )
+        .is_ok());
+        assert!(require_channel_permission(
+            Some(Permissions::ADMINISTRATOR),
+            Permissions::MANAGE_MESSAGES
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_pin_creates_pinned_message_system_message() {
+        let now = Utc::now();
+        let message = pin_system_message(3, 100, 200, 42, now);
+
+        assert_eq!(message.message_type, MessageType::ChannelPinnedMessage);
+        assert!(message.is_system());
+        assert_eq!(message.channel_id, 100);
+        assert_eq!(message.author_id, 200);
+        assert_eq!(message.reply_to_id, Some(42));
+        assert!(!message.pinned);
+        assert_eq!(message.created_at, now);
+    }
+
+    fn authored_message(author_id: i64, message_type: MessageType) -> Message {
+        Message {
+            id: 42,
+            channel_id: 100,
+            author_id,
+            message_type,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_only_author_can_edit() {
+        let message = authored_message(200, MessageType::Default);
+
+        assert!(check_editable(&message, 200).is_ok());
+        assert!(matches!(check_editable(&message, 300), Err(MessageError::NotAuthor)));
+    }
+
+    #[test]
+    fn test_system_messages_cannot_be_edited() {
+        let message = authored_message(200, MessageType::ChannelPinnedMessage);
+
+        // Not even by the user the system message names as its author
+        assert!(matches!(check_editable(&message, 200), Err(MessageError::SystemMessage)));
+    }
+
+    #[test]
+    fn test_message_limit_is_clamped() {
+        let limit = |limit| validate_message_query(&MessageQueryDto { limit, ..Default::default() });
+
+        assert_eq!(limit(None).unwrap(), DEFAULT_MESSAGE_LIMIT);
+        assert_eq!(limit(Some(0)).unwrap(), 1);
+        assert_eq!(limit(Some(25)).unwrap(), 25);
+        assert_eq!(limit(Some(i32::MAX)).unwrap(), MAX_MESSAGE_LIMIT);
+        assert!(matches!(limit(Some(-1)), Err(MessageError::InvalidLimit)));
+    }
+
+    #[test]
+    fn test_before_and_after_are_mutually_exclusive() {
+        let query = MessageQueryDto {
+            before: Some(10),
+            after: Some(5),
+            ..Default::default()
+        };
+        assert!(matches!(validate_message_query(&query), Err(MessageError::InvalidCursor)));
+
+        let query = MessageQueryDto {
+            after: Some(5),
+            ..Default::default()
+        };
+        assert!(validate_message_query(&query).is_ok());
+    }
+
+    /// User repository that records the IDs of every batched lookup
+    #[derive(Default)]
+    struct RecordingUserRepository {
+        lookups: Mutex<Vec<Vec<i64>>>,
+    }
+
+    #[async_trait]
+    impl UserRepository for RecordingUserRepository {
+        async fn find_by_id(&self, _id: i64) -> Result<Option<User>, AppError> { unimplemented!() }
+        async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<User>, AppError> {
+            self.lookups.lock().unwrap().push(ids.to_vec());
+            Ok(ids
+                .iter()
+                .map(|&id| User {
+                    id,
+                    username: format!("user{}", id),
+                    ..Default::default()
+                })
+                .collect())
+        }
+        async fn find_mutual_friends(&self, _user_id: i64, _other_user_id: i64) -> Result<Vec<User>, AppError> { unimplemented!() }
+        async fn find_by_email(&self, _email: &str) -> Result<Option<User>, AppError> { unimplemented!() }
+        async fn find_by_username(&self, _username: &str) -> Result<Option<User>, AppError> { unimplemented!() }
+        async fn create(&self, _user: &User) -> Result<User, AppError> { unimplemented!() }
+        async fn update(&self, _user: &User) -> Result<User, AppError> { unimplemented!() }
+        async fn delete(&self, _id: i64) -> Result<(), AppError> { unimplemented!() }
+        async fn email_exists(&self, _email: &str) -> Result<bool, AppError> { unimplemented!() }
+        async fn username_exists(&self, _username: &str) -> Result<bool, AppError> { unimplemented!() }
+        async fn update_status(&self, _id: i64, _status: UserStatus) -> Result<(), AppError> { unimplemented!() }
+        async fn change_password(&self, _id: i64, _password_hash: &str, _session_id: Uuid, _refresh_token_hash: &str, _refresh_expires_at: DateTime<Utc>) -> Result<Vec<Uuid>, AppError> { unimplemented!() }
+        async fn delete_account(&self, _user: &User, _owned_guilds: &[OwnedGuildAction]) -> Result<Vec<Uuid>, AppError> { unimplemented!() }
+    }
+
+    #[tokio::test]
+    async fn test_page_from_one_author_issues_single_lookup() {
+        let repo = RecordingUserRepository::default();
+        // A page of three messages by the same author
+        let author_ids = [200, 200, 200];
+
+        let authors = load_authors(&repo, None::<&UserProfileCache<InMemoryCache>>, &author_ids)
+            .await
+            .unwrap();
+
+        assert_eq!(*repo.lookups.lock().unwrap(), vec![vec![200]]);
+        assert_eq!(authors.len(), 1);
+        assert_eq!(authors[&200].username, "user200");
+    }
+
+    #[tokio::test]
+    async fn test_cached_authors_skip_the_database() {
+        let repo = RecordingUserRepository::default();
+        let cache = UserProfileCache::with_cache(InMemoryCache::new());
+
+        load_authors(&repo, Some(&cache), &[200, 300]).await.unwrap();
+        let authors = load_authors(&repo, Some(&cache), &[300, 200, 400]).await.unwrap();
+
+        // The second page only looks up the author it has not seen
+        assert_eq!(*repo.lookups.lock().unwrap(), vec![vec![200, 300], vec![400]]);
+        assert_eq!(authors.len(), 3);
+        assert_eq!(authors[&300].username, "user300");
+    }
+
+    #[test]
+    fn test_everyone_and_here_are_detected() {
+        assert!(mentions_everyone("@everyone standup in 5"));
+        assert!(mentions_everyone("ping @here"));
+        assert!(!mentions_everyone("hello everyone"));
+        assert!(!mentions_everyone("mail me at here@example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_mention_cooldown_blocks_repeated_pings() {
+        let cooldown = MentionCooldown::with_cache(InMemoryCache::new()).with_cooldown(5);
+
+        check_mention_cooldown(Some(&cooldown), 10, true).await.unwrap();
+        start_mention_cooldown(Some(&cooldown), 10, true).await;
+        let result = check_mention_cooldown(Some(&cooldown), 10, true).await;
+        assert!(matches!(result, Err(MessageError::MentionCooldown { retry_after }) if (1..=5).contains(&retry_after)));
+
+        // Other channels have their own cooldown
+        check_mention_cooldown(Some(&cooldown), 11, true).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mention_cooldown_ignores_normal_messages() {
+        let cooldown = MentionCooldown::with_cache(InMemoryCache::new()).with_cooldown(5);
+
+        start_mention_cooldown(Some(&cooldown), 10, false).await;
+        check_mention_cooldown(Some(&cooldown), 10, true).await.unwrap();
+        start_mention_cooldown(Some(&cooldown), 10, true).await;
+        check_mention_cooldown(Some(&cooldown), 10, false).await.unwrap();
+        check_mention_cooldown(None::<&MentionCooldown<InMemoryCache>>, 10, true).await.unwrap();
+    }
+
+    /// Message repository that only serves history, counting the queries,
+    /// and lookups of `messages`
+    #[derive(Default)]
+    struct HistoryRepository {
+        queries: Mutex<usize>,
+        messages: Vec<Message>,
+    }
+
+    #[async_trait]
+    impl MessageRepository for HistoryRepository {
+        async fn find_by_id(&self, _: i64) -> Result<Option<Message>, AppError> {
+            unimplemented!()
+        }
+        async fn find_by_channel(&self, _: i64, _: Option<i64>, _: Option<i64>, _: i32) -> Result<Vec<Message>, AppError> {
+            *self.queries.lock().unwrap() += 1;
+            Ok(Vec::new())
+        }
+        async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<Message>, AppError> {
+            *self.queries.lock().unwrap() += 1;
+            Ok(self.messages.iter().filter(|m| ids.contains(&m.id)).cloned().collect())
+        }
+        async fn find_pinned(&self, _: i64) -> Result<Vec<Message>, AppError> {
+            unimplemented!()
+        }
+        async fn find_by_author(&self, _: i64, _: i64, _: i32) -> Result<Vec<Message>, AppError> {
+            unimplemented!()
+        }
+        async fn create(&self, _: &Message) -> Result<Message, AppError> {
+            unimplemented!()
+        }
+        async fn create_batch(&self, _: &[Message]) -> Result<Vec<Message>, AppError> {
+            unimplemented!()
+        }
+        async fn create_with_attachments(&self, _: &Message, _: &[i64]) -> Result<Message, AppError> {
+            unimplemented!()
+        }
+        async fn find_attachments_by_ids(&self, _: &[i64]) -> Result<Vec<Attachment>, AppError> {
+            unimplemented!()
+        }
+        async fn find_attachments_by_message_ids(&self, _: &[i64]) -> Result<Vec<Attachment>, AppError> {
+            unimplemented!()
+        }
+        async fn update(&self, _: &Message) -> Result<Message, AppError> {
+            unimplemented!()
+        }
+        async fn delete(&self, _: i64) -> Result<(), AppError> {
+            unimplemented!()
+        }
+        async fn bulk_delete(&self, _: i64, _: Vec<i64>) -> Result<Vec<i64>, AppError> {
+            unimplemented!()
+        }
+        async fn pin(&self, _: i64, _: &Message) -> Result<Option<Message>, AppError> {
+            unimplemented!()
+        }
+        async fn unpin(&self, _: i64) -> Result<(), AppError> {
+            unimplemented!()
+        }
+        async fn count_by_channel(&self, _: i64) -> Result<i64, AppError> {
+            unimplemented!()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_history_without_permission_is_rejected_before_querying() {
+        let repo = HistoryRepository::default();
+        let query = MessageQueryDto::default();
+
+        let view_only = Permissions::VIEW_CHANNEL;
+        let result = load_history(&repo, 1, Some(view_only), &query).await;
+        assert!(matches!(result, Err(MessageError::Forbidden)));
+
+        // A channel the user can't see looks like one that doesn't exist
+        let result = load_history(&repo, 1, Some(Permissions::READ_MESSAGE_HISTORY), &query).await;
+        assert!(matches!(result, Err(MessageError::ChannelNotFound)));
+
+        assert_eq!(*repo.queries.lock().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_history_is_queried_for_readers_and_dm_recipients() {
+        let repo = HistoryRepository::default();
+        let query = MessageQueryDto::default();
+
+        let reader = Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY;
+        assert!(load_history(&repo, 1, Some(reader), &query).await.is_ok());
+        // DM channels have no permission model
+        assert!(load_history(&repo, 2, None, &query).await.is_ok());
+
+        assert_eq!(*repo.queries.lock().unwrap(), 2);
+    }
+
+
+    #[tokio::test]
+    async fn test_messages_by_id_are_deduplicated_and_capped() {
+        let repo = HistoryRepository::default();
+        let reader = Some(Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY);
+
+        let too_many: Vec<i64> = (1..=MAX_MESSAGE_LIMIT as i64 + 1).collect();
+        assert!(matches!(load_by_ids(&repo, 10, reader, &too_many).await, Err(MessageError::TooManyIds)));
+
+        // Duplicates count once
+        let mut repeated: Vec<i64> = (1..=MAX_MESSAGE_LIMIT as i64).collect();
+        repeated.extend(1..=10);
+        assert!(load_by_ids(&repo, 10, reader, &repeated).await.is_ok());
+
+        // History access is still required
+        let view_only = Some(Permissions::VIEW_CHANNEL);
+        assert!(matches!(load_by_ids(&repo, 10, view_only, &[1]).await, Err(MessageError::Forbidden)));
+        assert_eq!(*repo.queries.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_messages_by_id_exclude_other_channels() {
+        let message = |id: i64, channel_id: i64| Message { id, channel_id, ..Default::default() };
+        let ephemeral = Message { flags: message_flags::EPHEMERAL, ..message(4, 10) };
+        let repo = HistoryRepository {
+            messages: vec![message(1, 10), message(2, 11), message(3, 10), ephemeral],
+            ..Default::default()
+        };
+
+        let messages = load_by_ids(&repo, 10, None, &[1, 2, 3, 4, 99]).await.unwrap();
+        let ids: Vec<i64> = messages.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![3, 1]);
+    }
+
+    fn reply(id: i64, reply_to_id: i64) -> Message {
+        Message {
+            id,
+            channel_id: 10,
+            message_type: MessageType::Reply,
+            reply_to_id: Some(reply_to_id),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reply_includes_its_referenced_message() {
+        let original = Message { id: 1, channel_id: 10, content: "original".to_string(), ..Default::default() };
+        let elsewhere = Message { id: 2, channel_id: 11, ..Default::default() };
+        let repo = HistoryRepository { messages: vec![original, elsewhere], ..Default::default() };
+        let page = vec![reply(3, 1), reply(4, 2)];
+
+        let referenced = load_referenced(&repo, &page).await.unwrap();
+        // A message in another channel is not resolved
+        assert_eq!(referenced.keys().collect::<Vec<_>>(), vec![&1]);
+
+        let referenced: HashMap<i64, MessageDto> = referenced.into_iter().map(|(id, m)| (id, MessageDto::from(m))).collect();
+        let mut dto = MessageDto::from(page[0].clone());
+        attach_reference(&mut dto, Some(1), &referenced);
+
+        let quoted = dto.referenced_message.unwrap();
+        assert_eq!(quoted.id, "1");
+        assert_eq!(quoted.content, "original");
+        assert!(quoted.referenced_message.is_none());
+        assert!(!dto.referenced_message_deleted);
+    }
+
+    #[tokio::test]
+    async fn test_reply_to_deleted_message_resolves_to_null() {
+        let repo = HistoryRepository::default();
+        let page = vec![reply(3, 1), Message { id: 4, channel_id: 10, ..Default::default() }];
+
+        let referenced = load_referenced(&repo, &page).await.unwrap();
+        assert!(referenced.is_empty());
+
+        let mut deleted = MessageDto::from(page[0].clone());
+        attach_reference(&mut deleted, Some(1), &HashMap::new());
+        assert!(deleted.referenced_message.is_none());
+        assert!(deleted.referenced_message_deleted);
+
+        // Messages that aren't replies aren't flagged
+        let mut plain = MessageDto::from(page[1].clone());
+        attach_reference(&mut plain, None, &HashMap::new());
+        assert!(!plain.referenced_message_deleted);
+    }
+
+    #[test]
+    fn test_exempt_role_or_manage_messages_bypasses_slowmode() {
+        let member = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+        let exempt_roles = [20, 21];
+
+        assert!(slowmode_exempt(member, &[10, 21], &exempt_roles));
+        assert!(slowmode_exempt(member | Permissions::MANAGE_MESSAGES, &[10], &exempt_roles));
+        assert!(!slowmode_exempt(member, &[10], &exempt_roles));
+        assert!(!slowmode_exempt(member, &[10], &[]));
+    }
+
+    #[tokio::test]
+    async fn test_slowmode_holds_back_only_non_exempt_members() {
+        let slowmode = Slowmode::with_cache(InMemoryCache::new());
+        let slow = Channel {
+            rate_limit_per_user: 30,
+            slowmode_exempt_roles: vec![20],
+            ..channel(ChannelType::Text, Some(100))
+        };
+        let member = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+
+        // A normal member waits between messages
+        let normal = slowmode_exempt(member, &[10], &slow.slowmode_exempt_roles);
+        check_slowmode(Some(&slowmode), &slow, 7, normal).await.unwrap();
+        start_slowmode(Some(&slowmode), &slow, 7, normal).await;
+        let result = check_slowmode(Some(&slowmode), &slow, 7, normal).await;
+        assert!(matches!(result, Err(MessageError::Slowmode { retry_after }) if (1..=30).contains(&retry_after)));
+
+        // A member holding an exempt role doesn't
+        let exempt = slowmode_exempt(member, &[10, 20], &slow.slowmode_exempt_roles);
+        start_slowmode(Some(&slowmode), &slow, 8, exempt).await;
+        check_slowmode(Some(&slowmode), &slow, 8, exempt).await.unwrap();
+
+        // Neither does anyone in a channel without slowmode
+        let fast = Channel { id: 2, ..channel(ChannelType::Text, Some(100)) };
+        start_slowmode(Some(&slowmode), &fast, 7, normal).await;
+        check_slowmode(Some(&slowmode), &fast, 7, normal).await.unwrap();
+    }
+
+    #[test]
+    fn test_restricted_nsfw_history_is_reported_only_when_visible() {
+        let nsfw = Channel {
+            nsfw: true,
+            ..channel(ChannelType::Text, Some(100))
+        };
+        let member = Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY;
+
+        assert!(matches!(check_nsfw_access(&nsfw, Some(member), false), Err(MessageError::NsfwRestricted)));
+        // A hidden NSFW channel is left to the history check to report missing
+        assert!(check_nsfw_access(&nsfw, Some(Permissions::READ_MESSAGE_HISTORY), false).is_ok());
+        assert!(matches!(
+            check_history_access(Some(Permissions::READ_MESSAGE_HISTORY)),
+            Err(MessageError::ChannelNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_permission_cache_falls_back_to_recomputing() {
+        use crate::infrastructure::cache::failing_cache::FailingCache;
+        use crate::infrastructure::metrics::PERMISSION_CACHE_FALLBACKS_TOTAL;
+
+        let backend = FailingCache::default();
+        let cache = PermissionCacheService::with_cache(backend.clone());
+        let permissions = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+        let before = PERMISSION_CACHE_FALLBACKS_TOTAL.get();
+
+        let result = cached_or_computed(&cache, 1, 100, 7, || async { Ok(Some(permissions)) }).await;
+
+        // Same answer as a cold cache, without writing back to a cache that is down
+        assert!(matches!(result, Ok(Some(p)) if p == permissions));
+        assert_eq!(backend.calls(), 1);
+        assert!(PERMISSION_CACHE_FALLBACKS_TOTAL.get() > before);
+    }
+
+    #[tokio::test]
+    async fn test_permission_cache_miss_is_written_back_and_hit_skips_recomputing() {
+        let cache = PermissionCacheService::with_cache(InMemoryCache::new());
+
+        let miss = cached_or_computed(&cache, 1, 100, 7, || async { Ok(Some(Permissions::VIEW_CHANNEL)) }).await;
+        assert!(matches!(miss, Ok(Some(p)) if p == Permissions::VIEW_CHANNEL));
+
+        let cached = cache.get_channel_permissions(100, 1, 7).await.unwrap().unwrap();
+        assert!(cached.can_view && !cached.can_send);
+
+        let hit = cached_or_computed(&cache, 1, 100, 7, || async { panic!("recomputed on a cache hit") }).await;
+        assert!(matches!(hit, Ok(Some(p)) if p == Permissions::VIEW_CHANNEL));
+
+        // Members without access aren't cached
+        let hidden = cached_or_computed(&cache, 1, 100, 8, || async { Ok(None) }).await;
+        assert!(matches!(hidden, Ok(None)));
+        assert!(cache.get_channel_permissions(100, 1, 8).await.unwrap().is_none());
+    }
+
+    #[cfg(feature = "db-tests")]
+    mod db {
+        use super::*;
+        use crate::application::services::content_filter::KeywordFilter;
+        use crate::infrastructure::repositories::{
+            test_db, PgChannelRepository, PgMemberRepository, PgMessageRepository, PgRoleRepository,
+            PgServerRepository, PgUserRepository,
+        };
+
+        fn service(
+            pool: sqlx::PgPool,
+        ) -> MessageServiceImpl<
+            PgMessageRepository,
+            PgChannelRepository,
+            PgMemberRepository,
+            PgServerRepository,
+            PgRoleRepository,
+            PgUserRepository,
+        > {
+            MessageServiceImpl::new(
+                Arc::new(PgMessageRepository::new(pool.clone())),
+                Arc::new(PgChannelRepository::new(pool.clone())),
+                Arc::new(PgMemberRepository::new(pool.clone())),
+                Arc::new(PgServerRepository::new(pool.clone())),
+                Arc::new(PgRoleRepository::new(pool.clone())),
+                Arc::new(PgUserRepository::new(pool)),
+                Arc::new(SnowflakeGenerator::new(1, 1)),
+            )
+        }
+
+        struct FlagAllFilter;
+
+        #[async_trait]
+        impl ContentFilter for FlagAllFilter {
+            async fn check(&self, _ctx: &MessageContext) -> Result<FilterDecision, AppError> {
+                Ok(FilterDecision::Flag)
+            }
+        }
+
+        fn text(content: &str) -> CreateMessageDto {
+            CreateMessageDto {
+                content: content.to_string(),
+                ..with_embeds(Vec::new())
+            }
+        }
+
+        async fn message_count(pool: &sqlx::PgPool, channel_id: i64) -> i64 {
+            sqlx::query_scalar("SELECT COUNT(*) FROM messages WHERE channel_id = $1")
+                .bind(channel_id)
+                .fetch_one(pool)
+                .await
+                .unwrap()
+        }
+
+        fn with_embeds(embeds: Vec<Embed>) -> CreateMessageDto {
+            CreateMessageDto {
+                content: String::new(),
+                reply_to: None,
+                attachment_ids: Vec::new(),
+                embeds,
+                tts: false,
+                flags: 0,
+            }
+        }
+
+        #[tokio::test]
+        async fn test_dm_recipients_can_post_and_read_history() {
+            let pool = test_db::pool().await;
+            let alice = test_db::user(&pool).await;
+            let bob = test_db::user(&pool).await;
+            let outsider = test_db::user(&pool).await;
+            let dm = Channel {
+                id: test_db::id(),
+                server_id: None,
+                channel_type: ChannelType::Dm,
+                ..Default::default()
+            };
+            PgChannelRepository::new(pool.clone()).create_dm(&dm, &[alice, bob]).await.unwrap();
+            let service = service(pool);
+
+            service.send_message(dm.id, alice, text("hi")).await.unwrap();
+            let history = service.get_messages(dm.id, bob, MessageQueryDto::default()).await.unwrap();
+            assert_eq!(history.len(), 1);
+
+            assert!(matches!(
+                service.send_message(dm.id, outsider, text("hi")).await,
+                Err(MessageError::Forbidden)
+            ));
+            assert!(matches!(
+                service.get_messages(dm.id, outsider, MessageQueryDto::default()).await,
+                Err(MessageError::Forbidden)
+            ));
+        }
+
+        #[tokio::test]
+        async fn test_sending_embeds_requires_embed_links() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let member = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            test_db::member(&pool, server, member).await;
+            let channel = test_db::channel(&pool, server).await;
+            test_db::set_permissions(&pool, server, Permissions::DEFAULT & !Permissions::EMBED_LINKS).await;
+            let service = service(pool.clone());
+
+            let denied = service.send_message(channel, member, with_embeds(vec![titled_embed("hi")])).await;
+            assert!(matches!(denied, Err(MessageError::Forbidden)));
+
+            let linker = test_db::role(&pool, server, 1).await;
+            test_db::set_permissions(&pool, linker, Permissions::EMBED_LINKS).await;
+            test_db::assign_role(&pool, server, member, linker).await;
+
+            let sent = service
+                .send_message(channel, member, with_embeds(vec![titled_embed("hi")]))
+                .await
+                .unwrap();
+            assert_eq!(sent.embeds.len(), 1);
+        }
+
+        #[tokio::test]
+        async fn test_sending_invalid_embeds_is_rejected() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let channel = test_db::channel(&pool, server).await;
+            let service = service(pool);
+
+            let too_many = with_embeds(vec![titled_embed("hi"); MAX_EMBEDS + 1]);
+            assert!(matches!(
+                service.send_message(channel, owner, too_many).await,
+                Err(MessageError::TooManyEmbeds)
+            ));
+
+            let script = Embed {
+                url: Some("javascript:alert(1)".to_string()),
+                ..titled_embed("hi")
+            };
+            assert!(matches!(
+                service.send_message(channel, owner, with_embeds(vec![script])).await,
+                Err(MessageError::InvalidEmbed(_))
+            ));
+        }
+
+        #[tokio::test]
+        async fn test_blocking_filter_prevents_insert() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let channel = test_db::channel(&pool, server).await;
+            let service = service(pool.clone()).with_content_filter(Arc::new(KeywordFilter::new(["forbidden"])));
+
+            let blocked = service.send_message(channel, owner, text("this is Forbidden")).await;
+            assert!(matches!(blocked, Err(MessageError::ContentBlocked(_))));
+            assert_eq!(message_count(&pool, channel).await, 0);
+
+            service.send_message(channel, owner, text("hello")).await.unwrap();
+            assert_eq!(message_count(&pool, channel).await, 1);
+        }
+
+        #[tokio::test]
+        async fn test_only_a_stored_ping_starts_the_mention_cooldown() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let channel = test_db::channel(&pool, server).await;
+            let service = service(pool.clone())
+                .with_content_filter(Arc::new(KeywordFilter::new(["forbidden"])))
+                .with_mention_cooldown(MentionCooldown::with_cache(InMemoryCache::new()).with_cooldown(5));
+
+            let blocked = service.send_message(channel, owner, text("@everyone forbidden")).await;
+            assert!(matches!(blocked, Err(MessageError::ContentBlocked(_))));
+
+            // The blocked ping didn't start the cooldown
+            service.send_message(channel, owner, text("@everyone standup")).await.unwrap();
+            let again = service.send_message(channel, owner, text("@here standup")).await;
+            assert!(matches!(again, Err(MessageError::MentionCooldown { .. })));
+            assert_eq!(message_count(&pool, channel).await, 1);
+
+            // Messages without a ping are unaffected
+            service.send_message(channel, owner, text("hello")).await.unwrap();
+        }
+
+        #[tokio::test]
+        async fn test_replies_carry_their_referenced_message() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let channel = test_db::channel(&pool, server).await;
+            let service = service(pool.clone());
+            let reply_to = |id: i64| CreateMessageDto {
+                reply_to: Some(id),
+                ..text("reply")
+            };
+
+            let original = service.send_message(channel, owner, text("original")).await.unwrap();
+            let reply = service.send_message(channel, owner, reply_to(original.id.parse().unwrap())).await.unwrap();
+            let quoted = reply.referenced_message.expect("the reply carries the original");
+            assert_eq!(quoted.content, "original");
+            assert!(!reply.referenced_message_deleted);
+
+            // Ephemeral messages are never shown to other readers through a reply
+            let ephemeral = test_db::message(&pool, channel, owner).await;
+            sqlx::query("UPDATE messages SET flags = $2 WHERE id = $1")
+                .bind(ephemeral)
+                .bind(message_flags::EPHEMERAL)
+                .execute(&pool)
+                .await
+                .unwrap();
+            let hidden = service.send_message(channel, owner, reply_to(ephemeral)).await.unwrap();
+            assert!(hidden.referenced_message.is_none());
+            assert!(hidden.referenced_message_deleted);
+
+            // History resolves references the same way, including deleted ones
+            service.delete_message(original.id.parse().unwrap(), owner).await.unwrap();
+            let history = service.get_messages(channel, owner, MessageQueryDto::default()).await.unwrap();
+            let replies: Vec<_> = history.iter().filter(|m| m.reply_to_id.is_some()).collect();
+            assert_eq!(replies.len(), 2);
+            assert!(replies.iter().all(|m| m.referenced_message.is_none() && m.referenced_message_deleted));
+        }
+
+        #[tokio::test]
+        async fn test_only_a_stored_message_starts_the_slowmode() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let member = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            test_db::member(&pool, server, member).await;
+            let channel = test_db::channel(&pool, server).await;
+            sqlx::query("UPDATE channels SET rate_limit_per_user = 30 WHERE id = $1")
+                .bind(channel)
+                .execute(&pool)
+                .await
+                .unwrap();
+            let service = service(pool.clone())
+                .with_content_filter(Arc::new(KeywordFilter::new(["forbidden"])))
+                .with_slowmode(Slowmode::with_cache(InMemoryCache::new()));
+
+            let blocked = service.send_message(channel, member, text("forbidden")).await;
+            assert!(matches!(blocked, Err(MessageError::ContentBlocked(_))));
+
+            // The blocked message didn't start the cooldown
+            service.send_message(channel, member, text("hello")).await.unwrap();
+            let again = service.send_message(channel, member, text("hello again")).await;
+            assert!(matches!(again, Err(MessageError::Slowmode { retry_after }) if (1..=30).contains(&retry_after)));
+            assert_eq!(message_count(&pool, channel).await, 1);
+
+            // The owner may manage messages and skips it
+            service.send_message(channel, owner, text("one")).await.unwrap();
+            service.send_message(channel, owner, text("two")).await.unwrap();
+        }
+
+        #[tokio::test]
+        async fn test_flagging_filter_still_inserts() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let channel = test_db::channel(&pool, server).await;
+            let service = service(pool.clone()).with_content_filter(Arc::new(FlagAllFilter));
+
+            service.send_message(channel, owner, text("hello")).await.unwrap();
+            assert_eq!(message_count(&pool, channel).await, 1);
+        }
+
+        #[derive(Default)]
+        struct RecordedCounts {
+            decrements: std::sync::Mutex<Vec<(i64, u64)>>,
+        }
+
+        #[async_trait]
+        impl MessageCountStore for RecordedCounts {
+            async fn get(&self, _: i64) -> Result<Option<i64>, AppError> {
+                Ok(None)
+            }
+            async fn reconcile(&self, _: i64, _: i64) -> Result<Option<i64>, AppError> {
+                Ok(None)
+            }
+            async fn increment(&self, _: i64) -> Result<(), AppError> {
+                Ok(())
+            }
+            async fn decrement_by(&self, channel_id: i64, count: u64) -> Result<(), AppError> {
+                self.decrements.lock().unwrap().push((channel_id, count));
+                Ok(())
+            }
+        }
+
+        #[tokio::test]
+        async fn test_bulk_delete_removes_channel_messages_and_queues_one_event() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let member = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            test_db::member(&pool, server, member).await;
+            test_db::set_permissions(&pool, server, Permissions::DEFAULT & !Permissions::MANAGE_MESSAGES).await;
+            let channel = test_db::channel(&pool, server).await;
+            let other_channel = test_db::channel(&pool, server).await;
+            let counts = Arc::new(RecordedCounts::default());
+            let service = service(pool.clone()).with_message_counts(counts.clone());
+
+            let mut ids = Vec::new();
+            for content in ["one", "two", "three"] {
+                let sent = service.send_message(channel, owner, text(content)).await.unwrap();
+                ids.push(sent.id.parse::<i64>().unwrap());
+            }
+            let elsewhere: i64 = service.send_message(other_channel, owner, text("stay")).await.unwrap().id.parse().unwrap();
+            let mut requested = ids.clone();
+            requested.push(elsewhere);
+
+            assert!(matches!(
+                service.bulk_delete_messages(channel, member, requested.clone()).await,
+                Err(MessageError::Forbidden)
+            ));
+            assert!(matches!(
+                service.bulk_delete_messages(channel, owner, Vec::new()).await,
+                Err(MessageError::InvalidBulkDelete)
+            ));
+
+            let mut deleted = service.bulk_delete_messages(channel, owner, requested).await.unwrap();
+            deleted.sort_unstable();
+            ids.sort_unstable();
+            assert_eq!(deleted, ids);
+            assert_eq!(message_count(&pool, channel).await, 0);
+            assert_eq!(message_count(&pool, other_channel).await, 1);
+            assert_eq!(*counts.decrements.lock().unwrap(), vec![(channel, 3)]);
+
+            let events: Vec<serde_json::Value> = sqlx::query_scalar(
+                "SELECT payload FROM event_outbox WHERE event_type = 'MESSAGE_DELETE_BULK' AND payload->>'channel_id' = $1",
+            )
+            .bind(channel.to_string())
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+            let expected: Vec<String> = ids.iter().map(i64::to_string).collect();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0]["ids"], serde_json::json!(expected));
+        }
+
+        #[tokio::test]
+        async fn test_pin_posts_system_message_and_queues_its_event() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let channel = test_db::channel(&pool, server).await;
+            let service = service(pool.clone());
+            let message: i64 = service.send_message(channel, owner, text("pin me")).await.unwrap().id.parse().unwrap();
+
+            let update = service.pin_message(channel, message, owner).await.unwrap();
+            let system_message = update.system_message.expect("system message");
+            let queued: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM event_outbox WHERE dedup_key = $1")
+                .bind(format!("MESSAGE_CREATE:{}", system_message.id))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(queued, 1);
+
+            // Pinning again posts nothing
+            let again = service.pin_message(channel, message, owner).await.unwrap();
+            assert!(again.system_message.is_none());
+            let posted: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM messages WHERE channel_id = $1 AND reply_to_id = $2")
+                .bind(channel)
+                .bind(message)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(posted, 1);
+        }
+
+        #[tokio::test]
+        async fn test_cached_history_permissions_follow_role_changes() {
+            use crate::application::services::{RoleService, RoleServiceImpl, UpdateRoleDto};
+
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let reader = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            test_db::member(&pool, server, reader).await;
+            let channel = test_db::channel(&pool, server).await;
+            test_db::set_permissions(&pool, server, Permissions::VIEW_CHANNEL).await;
+            let history = test_db::role(&pool, server, 1).await;
+            test_db::set_permissions(&pool, history, Permissions::READ_MESSAGE_HISTORY).await;
+
+            let cache = PermissionCacheService::with_cache(InMemoryCache::new());
+            let messages = service(pool.clone()).with_permission_cache(cache.clone());
+            let roles = RoleServiceImpl::new(
+                Arc::new(PgRoleRepository::new(pool.clone())),
+                Arc::new(PgServerRepository::new(pool.clone())),
+                Arc::new(PgMemberRepository::new(pool.clone())),
+                Arc::new(SnowflakeGenerator::new(1, 1)),
+            )
+            .with_permission_cache(cache.clone());
+            let read = || messages.get_messages(channel, reader, MessageQueryDto::default());
+
+            roles.assign_role_to_member(server, reader, history, owner).await.unwrap();
+            read().await.unwrap();
+            assert!(cache.get_channel_permissions(server, channel, reader).await.unwrap().is_some());
+
+            // Losing the role takes effect at once
+            roles.remove_role_from_member(server, reader, history, owner).await.unwrap();
+            assert!(matches!(read().await, Err(MessageError::Forbidden)));
+
+            // So does the role losing the permission
+            roles.assign_role_to_member(server, reader, history, owner).await.unwrap();
+            read().await.unwrap();
+            let update = UpdateRoleDto {
+                permissions: Some(0),
+                ..Default::default()
+            };
+            roles.update_role(history, owner, update).await.unwrap();
+            assert!(matches!(read().await, Err(MessageError::Forbidden)));
+
+            // A change made behind the services' back is served from the cache
+            test_db::set_permissions(&pool, history, Permissions::READ_MESSAGE_HISTORY).await;
+            assert!(matches!(read().await, Err(MessageError::Forbidden)));
+        }
+    }
 }
@@ -7,39 +7,96 @@
 //! - **AuthService**: Authentication, JWT tokens, password management
 //! - **UserService**: User profile management
 //! - **GuildService**: Server/guild management
+//! - **MemberService**: Guild member profiles with roles and permissions
 //! - **ChannelService**: Channel operations
 //! - **MessageService**: Message CRUD operations
+//! - **MessageWriteBatcher**: Optional batching of message inserts
+//! - **AttachmentService**: Attachment uploads to the configured file storage
+//! - **ReactionService**: Message reaction queries
 //! - **RoleService**: Role management and member role assignments
 //! - **InviteService**: Server invite management
+//! - **VoiceService**: Voice channel signaling and voice state moderation
+//! - **NotificationService**: Per-user channel mutes and notification levels
+//! - **ContentFilter**: Pluggable content scanning hook for new messages
+//! - **RecipientPermissions**: Members' permissions for filtered gateway broadcasts
+//! - **PermissionResolver**: Loads roles, overwrites and DM recipients for permission checks
 
 pub mod auth_service;
 pub mod user_service;
 pub mod guild_service;
+pub mod member_service;
 pub mod channel_service;
 pub mod message_service;
+pub mod message_batcher;
+pub mod attachment_service;
+pub mod reaction_service;
 pub mod role_service;
 pub mod invite_service;
+pub mod voice_service;
+pub mod notification_service;
+pub mod content_filter;
+pub mod recipient_permissions;
+pub mod permission_resolver;
 
 // Re-export auth service types
-pub use auth_service::{AuthService, AuthServiceImpl, AuthTokens, AuthError, Claims};
+pub use auth_service::{
+    AuthService, AuthServiceImpl, AuthTokens, AuthError, Claims, ClientInfo, PasswordChangeDto,
+    SessionDto,
+};
 
 // Re-export user service types
-pub use user_service::{UserService, UserServiceImpl, UserDto, UpdateProfileDto, ServerPreviewDto, UserError};
+pub use user_service::{
+    UserService, UserServiceImpl, UserDto, UpdateProfileDto, ServerPreviewDto, DeletedAccountDto,
+    OwnedGuildPolicy, UserError,
+};
 
 // Re-export guild service types
-pub use guild_service::{GuildService, GuildServiceImpl, GuildDto, CreateGuildDto, UpdateGuildDto, MemberDto, GuildError};
+pub use guild_service::{GuildService, GuildServiceImpl, GuildDto, GuildPreviewDto, CreateGuildDto, UpdateGuildDto, MemberDto, GuildError};
+
+// Re-export member service types
+pub use member_service::{MemberService, MemberServiceImpl, MemberDetailDto};
 
 // Re-export channel service types
 pub use channel_service::{ChannelService, ChannelServiceImpl, ChannelDto, CreateChannelDto, UpdateChannelDto, PermissionOverwriteDto, ChannelError};
 
 // Re-export message service types
-pub use message_service::{MessageService, MessageServiceImpl, MessageDto, CreateMessageDto, MessageQueryDto, MessageError};
+pub use message_service::{MessageService, MessageServiceImpl, MessageDto, MessageMemberDto, AttachmentDto, CreateMessageDto, EditMessageDto, MessageQueryDto, MessageError, PinsUpdateDto};
+
+// Re-export message batcher types
+pub use message_batcher::{MessageBatchConfig, MessageWriteBatcher};
+
+// Re-export attachment service types
+pub use attachment_service::{AttachmentService, AttachmentServiceImpl, UploadAttachmentDto, AttachmentError};
+
+// Re-export reaction service types
+pub use reaction_service::{ReactionService, ReactionServiceImpl, ReactorQueryDto, ReactionError};
 
 // Re-export role service types
-pub use role_service::{RoleService, RoleServiceImpl, RoleDto, CreateRoleDto, UpdateRoleDto, RolePositionDto, RoleError};
+pub use role_service::{RoleService, RoleServiceImpl, RoleDto, CreateRoleDto, UpdateRoleDto, RolePositionDto, MemberRolesDto, RoleError};
 
 // Re-export invite service types
 pub use invite_service::{
-    InviteService, InviteServiceImpl, InviteDto, CreateInviteDto, InvitePreviewDto,
+    InviteService, InviteServiceImpl, InviteDto, InviteQueryDto, CreateInviteDto, InvitePreviewDto,
     InviteValidationDto, UseInviteResultDto, InviteError,
 };
+
+// Re-export voice service types
+pub use voice_service::{
+    VoiceService, VoiceServiceImpl, VoiceStateDto, UpdateVoiceStateDto, ModifyVoiceStateDto,
+    VoiceError,
+};
+
+// Re-export notification service types
+pub use notification_service::{
+    NotificationService, NotificationServiceImpl, ChannelOverrideDto, ChannelOverrideUpdateDto,
+    NotificationError,
+};
+
+// Re-export content filter types
+pub use content_filter::{ContentFilter, FilterDecision, KeywordFilter, MessageContext, NoopContentFilter};
+
+// Re-export recipient permission types
+pub use recipient_permissions::{GuildRecipientPermissions, RecipientPermissions};
+
+// Re-export permission resolver types
+pub use permission_resolver::GuildPermissions;
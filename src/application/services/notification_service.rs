@@ -0,0 +1,339 @@
+//! Notification Service
+//!
+//! Handles per-user channel notification settings: muting channels and
+//! overriding the guild's default notification level.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use crate::shared::error::AppError;
+
+use crate::domain::{
+    ChannelOverride, ChannelOverrideRepository, ChannelRepository, MemberRepository,
+    NotificationLevel,
+};
+
+/// Notification service trait
+#[async_trait]
+pub trait NotificationService: Send + Sync {
+    /// Get the user's channel overrides, ordered by channel ID.
+    ///
+    /// Channels without an override are unmuted and follow the guild default.
+    async fn get_overrides(&self, user_id: i64) -> Result<Vec<ChannelOverrideDto>, NotificationError>;
+
+    /// Update the user's override for a channel. Only fields present in
+    /// `update` change.
+    async fn set_channel_override(
+        &self,
+        user_id: i64,
+        channel_id: i64,
+        update: ChannelOverrideUpdateDto,
+    ) -> Result<ChannelOverrideDto, NotificationError>;
+
+    /// Mute a channel for the user
+    async fn mute_channel(&self, user_id: i64, channel_id: i64) -> Result<ChannelOverrideDto, NotificationError>;
+
+    /// Unmute a channel for the user
+    async fn unmute_channel(&self, user_id: i64, channel_id: i64) -> Result<ChannelOverrideDto, NotificationError>;
+}
+
+/// Channel override data transfer object
+#[derive(Debug, Clone)]
+pub struct ChannelOverrideDto {
+    pub channel_id: String,
+    pub muted: bool,
+    /// None inherits the guild default
+    pub notification_level: Option<NotificationLevel>,
+}
+
+impl From<ChannelOverride> for ChannelOverrideDto {
+    fn from(channel_override: ChannelOverride) -> Self {
+        Self {
+            channel_id: channel_override.channel_id.to_string(),
+            muted: channel_override.muted,
+            notification_level: channel_override.notification_level,
+        }
+    }
+}
+
+/// Channel override update
+///
+/// `None` leaves a field unchanged; `Some(None)` resets the notification
+/// level to the guild default.
+#[derive(Debug, Clone, Default)]
+pub struct ChannelOverrideUpdateDto {
+    pub muted: Option<bool>,
+    pub notification_level: Option<Option<NotificationLevel>>,
+}
+
+/// Notification service errors
+#[derive(Debug, thiserror::Error)]
+pub enum NotificationError {
+    #[error("Channel not found")]
+    ChannelNotFound,
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+
+    /// No database connection was free in time; the request may succeed on retry
+    #[error("Service unavailable")]
+    Unavailable,
+}
+
+impl From<AppError> for NotificationError {
+    fn from(e: AppError) -> Self {
+        if e.is_unavailable() {
+            NotificationError::Unavailable
+        } else {
+            NotificationError::Internal(e.to_string())
+        }
+    }
+}
+
+/// Apply an update to a user's current override for a channel.
+fn apply_override_update(
+    current: Option<ChannelOverride>,
+    user_id: i64,
+    channel_id: i64,
+    update: ChannelOverrideUpdateDto,
+) -> ChannelOverride {
+    let mut channel_override =
+        current.unwrap_or_else(|| ChannelOverride::new(user_id, channel_id));
+
+    if let Some(muted) = update.muted {
+        channel_override.muted = muted;
+    }
+    if let Some(level) = update.notification_level {
+        channel_override.notification_level = level;
+    }
+
+    channel_override
+}
+
+/// NotificationService implementation
+pub struct NotificationServiceImpl<O, C, M>
+where
+    O: ChannelOverrideRepository,
+    C: ChannelRepository,
+    M: MemberRepository,
+{
+    override_repo: Arc<O>,
+    channel_repo: Arc<C>,
+    member_repo: Arc<M>,
+}
+
+impl<O, C, M> NotificationServiceImpl<O, C, M>
+where
+    O: ChannelOverrideRepository,
+    C: ChannelRepository,
+    M: MemberRepository,
+{
+    pub fn new(override_repo: Arc<O>, channel_repo: Arc<C>, member_repo: Arc<M>) -> Self {
+        Self {
+            override_repo,
+            channel_repo,
+            member_repo,
+        }
+    }
+
+    /// Require the user to be a member of the channel's guild, or a
+    /// recipient of a DM channel.
+    ///
+    /// Channels the user can't reach are reported as not found.
+    async fn require_channel_access(&self, channel_id: i64, user_id: i64) -> Result<(), NotificationError> {
+        let channel = self
+            .channel_repo
+            .find_by_id(channel_id)
+            .await
+            .map_err(NotificationError::from)?
+            .ok_or(NotificationError::ChannelNotFound)?;
+
+        let has_access = match channel.server_id {
+            Some(server_id) if !channel.is_dm() => self
+                .member_repo
+                .find(server_id, user_id)
+                .await
+                .map_err(NotificationError::from)?
+                .is_some(),
+            _ => self
+                .channel_repo
+                .is_recipient(channel_id, user_id)
+                .await
+                .map_err(NotificationError::from)?,
+        };
+
+        if !has_access {
+            return Err(NotificationError::ChannelNotFound);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<O, C, M> NotificationService for NotificationServiceImpl<O, C, M>
+where
+    O: ChannelOverrideRepository + 'static,
+    C: ChannelRepository + 'static,
+    M: MemberRepository + 'static,
+{
+    async fn get_overrides(&self, user_id: i64) -> Result<Vec<ChannelOverrideDto>, NotificationError> {
+        let overrides = self
+            .override_repo
+            .find_by_user(user_id)
+            .await
+            .map_err(NotificationError::from)?;
+
+        Ok(overrides.into_iter().map(ChannelOverrideDto::from).collect())
+    }
+
+    async fn set_channel_override(
+        &self,
+        user_id: i64,
+        channel_id: i64,
+        update: ChannelOverrideUpdateDto,
+    ) -> Result<ChannelOverrideDto, NotificationError> {
+        self.require_channel_access(channel_id, user_id).await?;
+
+        let current = self
+            .override_repo
+            .find(user_id, channel_id)
+            .await
+            .map_err(NotificationError::from)?;
+
+        let channel_override = apply_override_update(current, user_id, channel_id, update);
+
+        // Default settings need no row
+        if channel_override.is_default() {
+            self.override_repo
+                .delete(user_id, channel_id)
+                .await
+                .map_err(NotificationError::from)?;
+            return Ok(channel_override.into());
+        }
+
+        let saved = self
+            .override_repo
+            .upsert(&channel_override)
+            .await
+            .map_err(NotificationError::from)?;
+
+        Ok(saved.into())
+    }
+
+    async fn mute_channel(&self, user_id: i64, channel_id: i64) -> Result<ChannelOverrideDto, NotificationError> {
+        let update = ChannelOverrideUpdateDto {
+            muted: Some(true),
+            ..Default::default()
+        };
+        self.set_channel_override(user_id, channel_id, update).await
+    }
+
+    async fn unmute_channel(&self, user_id: i64, channel_id: i64) -> Result<ChannelOverrideDto, NotificationError> {
+        let update = ChannelOverrideUpdateDto {
+            muted: Some(false),
+            ..Default::default()
+        };
+        self.set_channel_override(user_id, channel_id, update).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn muted(channel_id: i64) -> ChannelOverride {
+        ChannelOverride {
+            muted: true,
+            ..ChannelOverride::new(1, channel_id)
+        }
+    }
+
+    #[test]
+    fn test_mute_is_kept_across_other_updates() {
+        let mute = ChannelOverrideUpdateDto {
+            muted: Some(true),
+            ..Default::default()
+        };
+        let channel_override = apply_override_update(None, 1, 10, mute);
+        assert!(channel_override.muted);
+        assert!(!channel_override.is_default());
+
+        // Changing the notification level leaves the channel muted
+        let level = ChannelOverrideUpdateDto {
+            notification_level: Some(Some(NotificationLevel::Mentions)),
+            ..Default::default()
+        };
+        let channel_override = apply_override_update(Some(channel_override), 1, 10, level);
+        assert!(channel_override.muted);
+        assert_eq!(channel_override.notification_level, Some(NotificationLevel::Mentions));
+    }
+
+    #[test]
+    fn test_unmuting_restores_defaults() {
+        let unmute = ChannelOverrideUpdateDto {
+            muted: Some(false),
+            ..Default::default()
+        };
+        let channel_override = apply_override_update(Some(muted(10)), 1, 10, unmute);
+
+        assert!(!channel_override.muted);
+        assert!(channel_override.is_default());
+    }
+
+    #[test]
+    fn test_notification_level_parsing() {
+        assert_eq!(NotificationLevel::parse("nothing"), Some(NotificationLevel::Nothing));
+        assert_eq!(NotificationLevel::parse(NotificationLevel::All.as_str()), Some(NotificationLevel::All));
+        assert_eq!(NotificationLevel::parse("none"), None);
+    }
+
+    #[cfg(feature = "db-tests")]
+    mod db {
+        use super::*;
+        use crate::infrastructure::repositories::{
+            test_db, PgChannelOverrideRepository, PgChannelRepository, PgMemberRepository,
+        };
+
+        fn service(pool: sqlx::PgPool) -> impl NotificationService {
+            NotificationServiceImpl::new(
+                Arc::new(PgChannelOverrideRepository::new(pool.clone())),
+                Arc::new(PgChannelRepository::new(pool.clone())),
+                Arc::new(PgMemberRepository::new(pool)),
+            )
+        }
+
+        #[tokio::test]
+        async fn test_mute_persists_until_unmuted() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let channel = test_db::channel(&pool, server).await;
+            let service = service(pool);
+
+            service.mute_channel(owner, channel).await.unwrap();
+            let overrides = service.get_overrides(owner).await.unwrap();
+            assert_eq!(overrides.len(), 1);
+            assert_eq!(overrides[0].channel_id, channel.to_string());
+            assert!(overrides[0].muted);
+
+            // Unmuting restores the defaults, which aren't stored
+            service.unmute_channel(owner, channel).await.unwrap();
+            assert!(service.get_overrides(owner).await.unwrap().is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_channels_outside_the_users_guilds_cannot_be_muted() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let outsider = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let channel = test_db::channel(&pool, server).await;
+            let service = service(pool);
+
+            let result = service.mute_channel(outsider, channel).await;
+
+            assert!(matches!(result, Err(NotificationError::ChannelNotFound)));
+            assert!(service.get_overrides(outsider).await.unwrap().is_empty());
+        }
+    }
+}
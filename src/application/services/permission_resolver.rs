@@ -0,0 +1,245 @@
+//! Permission Resolver
+//!
+//! Loads what a permission check needs (the guild owner, its roles, channel
+//! overwrites, DM recipients) and computes the result with
+//! [`PermissionService`]. Services resolve permissions here rather than
+//! assembling those inputs themselves.
+
+use crate::domain::services::PermissionService;
+use crate::domain::{
+    Channel, ChannelRepository, Member, MemberRepository, PermissionOverwrite, Role, RoleRepository,
+    ServerRepository, UserRepository,
+};
+use crate::shared::error::AppError;
+
+/// A guild's owner and roles, loaded once to compute the permissions of
+/// any number of its members.
+#[derive(Debug, Clone)]
+pub struct GuildPermissions {
+    owner_id: i64,
+    roles: Vec<Role>,
+    everyone_id: Option<i64>,
+}
+
+impl GuildPermissions {
+    /// Build from a guild's owner and all of its roles.
+    ///
+    /// The @everyone role is the one sharing the guild's ID, or failing
+    /// that the lowest role at position 0.
+    pub fn new(guild_id: i64, owner_id: i64, roles: Vec<Role>) -> Self {
+        let everyone_id = roles
+            .iter()
+            .filter(|role| role.id == guild_id || role.position == 0)
+            .min_by_key(|role| (role.id != guild_id, role.position))
+            .map(|role| role.id);
+
+        Self {
+            owner_id,
+            roles,
+            everyone_id,
+        }
+    }
+
+    /// Load a guild's owner and roles, or `None` if the guild doesn't exist.
+    pub async fn load<S, R>(server_repo: &S, role_repo: &R, guild_id: i64) -> Result<Option<Self>, AppError>
+    where
+        S: ServerRepository + ?Sized,
+        R: RoleRepository + ?Sized,
+    {
+        let Some(server) = server_repo.find_by_id(guild_id).await? else {
+            return Ok(None);
+        };
+        let roles = role_repo.find_by_server_id(guild_id).await?;
+
+        Ok(Some(Self::new(guild_id, server.owner_id, roles)))
+    }
+
+    /// The guild owner's user ID.
+    pub fn owner_id(&self) -> i64 {
+        self.owner_id
+    }
+
+    /// All of the guild's roles.
+    pub fn roles(&self) -> &[Role] {
+        &self.roles
+    }
+
+    /// A member's guild-wide permissions.
+    ///
+    /// The owner and administrators get every permission.
+    pub fn base(&self, member: &Member) -> i64 {
+        PermissionService::calculate_base_permissions(&self.with_everyone(member), &self.roles, self.owner_id)
+    }
+
+    /// A member's permissions in one of the guild's channels.
+    pub fn channel(&self, member: &Member, channel: &Channel, overwrites: &[PermissionOverwrite]) -> i64 {
+        PermissionService::calculate_channel_permissions(
+            &self.with_everyone(member),
+            channel,
+            overwrites,
+            &self.roles,
+            self.owner_id,
+        )
+    }
+
+    /// The member with the @everyone role, which everyone implicitly holds.
+    fn with_everyone(&self, member: &Member) -> Member {
+        let mut member = member.clone();
+        if let Some(everyone_id) = self.everyone_id {
+            if !member.roles.contains(&everyone_id) {
+                member.roles.push(everyone_id);
+            }
+        }
+        member
+    }
+}
+
+/// A user's guild-wide permissions, or `None` if the guild doesn't exist
+/// or the user isn't a member.
+pub async fn guild_permissions<S, M, R>(
+    server_repo: &S,
+    member_repo: &M,
+    role_repo: &R,
+    guild_id: i64,
+    user_id: i64,
+) -> Result<Option<i64>, AppError>
+where
+    S: ServerRepository + ?Sized,
+    M: MemberRepository + ?Sized,
+    R: RoleRepository + ?Sized,
+{
+    let Some(guild) = GuildPermissions::load(server_repo, role_repo, guild_id).await? else {
+        return Ok(None);
+    };
+    let Some(member) = member_repo.find(guild_id, user_id).await? else {
+        return Ok(None);
+    };
+
+    Ok(Some(guild.base(&member)))
+}
+
+/// A user's permissions in a channel, or `None` if it is a guild channel
+/// and the user isn't a member of the guild.
+///
+/// DM channels grant their recipients a fixed set of permissions and
+/// everyone else none.
+pub async fn channel_permissions<S, M, R, C>(
+    server_repo: &S,
+    member_repo: &M,
+    role_repo: &R,
+    channel_repo: &C,
+    channel: &Channel,
+    user_id: i64,
+) -> Result<Option<i64>, AppError>
+where
+    S: ServerRepository + ?Sized,
+    M: MemberRepository + ?Sized,
+    R: RoleRepository + ?Sized,
+    C: ChannelRepository + ?Sized,
+{
+    let Some(guild_id) = channel.server_id else {
+        let recipients = channel_repo.find_recipient_ids(channel.id).await?;
+        return Ok(Some(PermissionService::calculate_dm_permissions(channel, &recipients, user_id)));
+    };
+
+    let Some(guild) = GuildPermissions::load(server_repo, role_repo, guild_id).await? else {
+        return Ok(None);
+    };
+    let Some(member) = member_repo.find(guild_id, user_id).await? else {
+        return Ok(None);
+    };
+    let overwrites = channel_repo.get_permission_overwrites(channel.id).await?;
+
+    Ok(Some(guild.channel(&member, channel, &overwrites)))
+}
+
+/// Whether a user opted in to NSFW content, for
+/// [`PermissionService::can_view_nsfw`].
+///
+/// The setting only matters in NSFW channels, so it is only looked up for
+/// those; for other channels this is `false` without a query.
+pub async fn nsfw_allowed<U>(user_repo: &U, channel: &Channel, user_id: i64) -> Result<bool, AppError>
+where
+    U: UserRepository + ?Sized,
+{
+    if !channel.nsfw {
+        return Ok(false);
+    }
+    let user = user_repo.find_by_id(user_id).await?;
+    Ok(user.is_some_and(|u| u.nsfw_allowed))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::domain::value_objects::Permissions;
+
+    fn role(id: i64, position: i32, permissions: i64) -> Role {
+        Role {
+            id,
+            server_id: 1,
+            name: "role".to_string(),
+            permissions,
+            position,
+            color: None,
+            hoist: false,
+            mentionable: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn member(user_id: i64, roles: Vec<i64>) -> Member {
+        Member {
+            server_id: 1,
+            user_id,
+            nickname: None,
+            joined_at: Utc::now(),
+            roles,
+            communication_disabled_until: None,
+        }
+    }
+
+    #[test]
+    fn test_base_includes_everyone_and_assigned_roles() {
+        let guild = GuildPermissions::new(
+            1,
+            99,
+            vec![
+                role(1, 0, Permissions::VIEW_CHANNEL),
+                role(2, 1, Permissions::MANAGE_GUILD),
+                role(3, 2, Permissions::KICK_MEMBERS),
+            ],
+        );
+
+        let permissions = guild.base(&member(10, vec![2]));
+
+        assert_eq!(permissions, Permissions::VIEW_CHANNEL | Permissions::MANAGE_GUILD);
+    }
+
+    #[test]
+    fn test_base_grants_everything_to_owner_and_admins() {
+        let guild = GuildPermissions::new(
+            1,
+            99,
+            vec![role(1, 0, 0), role(2, 1, Permissions::ADMINISTRATOR)],
+        );
+
+        assert_eq!(guild.base(&member(99, vec![])), Permissions::ALL);
+        assert_eq!(guild.base(&member(10, vec![2])), Permissions::ALL);
+        assert_eq!(guild.base(&member(11, vec![])), 0);
+    }
+
+    #[test]
+    fn test_everyone_falls_back_to_position_zero() {
+        let guild = GuildPermissions::new(
+            1,
+            99,
+            vec![role(5, 0, Permissions::SEND_MESSAGES), role(6, 1, Permissions::MANAGE_GUILD)],
+        );
+
+        assert_eq!(guild.base(&member(10, vec![])), Permissions::SEND_MESSAGES);
+    }
+}
@@ -0,0 +1,493 @@
+//! Reaction Service
+//!
+//! Handles message reactions: adding and removing the actor's own
+//! reactions, and listing who reacted with a given emoji.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::permission_resolver;
+use super::user_service::UserDto;
+use crate::domain::value_objects::Permissions;
+use crate::domain::{
+    Channel, ChannelRepository, MemberRepository, MessageRepository, RoleRepository,
+    ServerRepository, User, UserRepository,
+};
+use crate::infrastructure::repositories::ReactionRepository;
+use crate::shared::error::AppError;
+
+/// Reactors returned when no limit is given
+pub const DEFAULT_REACTOR_LIMIT: i32 = 25;
+
+/// Maximum reactors returned per page
+pub const MAX_REACTOR_LIMIT: i32 = 100;
+
+/// Maximum length of a stored emoji identifier (`message_reactions.emoji`)
+const MAX_EMOJI_LENGTH: usize = 100;
+
+/// Reaction service trait
+#[async_trait]
+pub trait ReactionService: Send + Sync {
+    /// List users who reacted to a message with `emoji`, ordered by user ID.
+    ///
+    /// Requires `READ_MESSAGE_HISTORY` in guild channels. `emoji` is a
+    /// unicode emoji or a custom emoji as `name:id` or `id`.
+    async fn get_reactors(
+        &self,
+        channel_id: i64,
+        message_id: i64,
+        emoji: &str,
+        actor_id: i64,
+        query: ReactorQueryDto,
+    ) -> Result<Vec<UserDto>, ReactionError>;
+
+    /// React to a message as the actor; reacting twice is a no-op.
+    ///
+    /// Requires `READ_MESSAGE_HISTORY` and `ADD_REACTIONS` in guild channels.
+    async fn add_reaction(
+        &self,
+        channel_id: i64,
+        message_id: i64,
+        emoji: &str,
+        actor_id: i64,
+    ) -> Result<(), ReactionError>;
+
+    /// Remove the actor's own reaction; removing a missing one succeeds.
+    async fn remove_reaction(
+        &self,
+        channel_id: i64,
+        message_id: i64,
+        emoji: &str,
+        actor_id: i64,
+    ) -> Result<(), ReactionError>;
+}
+
+/// Reactor pagination query
+#[derive(Debug, Clone, Default)]
+pub struct ReactorQueryDto {
+    /// Return users with an ID greater than this
+    pub after: Option<i64>,
+    pub limit: Option<i32>,
+}
+
+/// Reaction service errors
+#[derive(Debug, thiserror::Error)]
+pub enum ReactionError {
+    #[error("Message not found")]
+    MessageNotFound,
+
+    #[error("Channel not found")]
+    ChannelNotFound,
+
+    #[error("Invalid emoji")]
+    InvalidEmoji,
+
+    #[error("Permission denied")]
+    Forbidden,
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+
+    /// No database connection was free in time; the request may succeed on retry
+    #[error("Service unavailable")]
+    Unavailable,
+}
+
+impl From<AppError> for ReactionError {
+    fn from(e: AppError) -> Self {
+        if e.is_unavailable() {
+            ReactionError::Unavailable
+        } else {
+            ReactionError::Internal(e.to_string())
+        }
+    }
+}
+
+/// Normalize an emoji identifier to the form stored in `message_reactions`.
+///
+/// Custom emoji are stored by ID and may be given as `id`, `name:id` or
+/// `<:name:id>`; anything without a colon is taken as a unicode emoji.
+pub fn normalize_emoji(raw: &str) -> Result<String, ReactionError> {
+    let emoji = raw.trim();
+    let emoji = emoji
+        .strip_prefix('<')
+        .and_then(|e| e.strip_suffix('>'))
+        .unwrap_or(emoji);
+
+    if emoji.is_empty() || emoji.chars().count() > MAX_EMOJI_LENGTH {
+        return Err(ReactionError::InvalidEmoji);
+    }
+
+    match emoji.rsplit_once(':') {
+        Some((_, id)) if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) => Ok(id.to_string()),
+        Some(_) => Err(ReactionError::InvalidEmoji),
+        None => Ok(emoji.to_string()),
+    }
+}
+
+/// Clamp a requested page size to `1..=MAX_REACTOR_LIMIT`.
+fn reactor_limit(limit: Option<i32>) -> i32 {
+    limit.unwrap_or(DEFAULT_REACTOR_LIMIT).clamp(1, MAX_REACTOR_LIMIT)
+}
+
+/// Order loaded users to match the reactor page, skipping users that no
+/// longer exist.
+fn order_reactors(reactor_ids: &[i64], users: Vec<User>) -> Vec<User> {
+    let mut by_id: HashMap<i64, User> = users.into_iter().map(|u| (u.id, u)).collect();
+    reactor_ids.iter().filter_map(|id| by_id.remove(id)).collect()
+}
+
+/// ReactionService implementation
+pub struct ReactionServiceImpl<Rx, M, C, Mem, S, R, U>
+where
+    Rx: ReactionRepository,
+    M: MessageRepository,
+    C: ChannelRepository,
+    Mem: MemberRepository,
+    S: ServerRepository,
+    R: RoleRepository,
+    U: UserRepository,
+{
+    reaction_repo: Arc<Rx>,
+    message_repo: Arc<M>,
+    channel_repo: Arc<C>,
+    member_repo: Arc<Mem>,
+    server_repo: Arc<S>,
+    role_repo: Arc<R>,
+    user_repo: Arc<U>,
+}
+
+impl<Rx, M, C, Mem, S, R, U> ReactionServiceImpl<Rx, M, C, Mem, S, R, U>
+where
+    Rx: ReactionRepository,
+    M: MessageRepository,
+    C: ChannelRepository,
+    Mem: MemberRepository,
+    S: ServerRepository,
+    R: RoleRepository,
+    U: UserRepository,
+{
+    pub fn new(
+        reaction_repo: Arc<Rx>,
+        message_repo: Arc<M>,
+        channel_repo: Arc<C>,
+        member_repo: Arc<Mem>,
+        server_repo: Arc<S>,
+        role_repo: Arc<R>,
+        user_repo: Arc<U>,
+    ) -> Self {
+        Self {
+            reaction_repo,
+            message_repo,
+            channel_repo,
+            member_repo,
+            server_repo,
+            role_repo,
+            user_repo,
+        }
+    }
+
+    /// Require `permission` in a guild channel or DM.
+    async fn require_channel_permission(
+        &self,
+        channel: &Channel,
+        user_id: i64,
+        permission: i64,
+    ) -> Result<(), ReactionError> {
+        let permissions = permission_resolver::channel_permissions(
+            self.server_repo.as_ref(),
+            self.member_repo.as_ref(),
+            self.role_repo.as_ref(),
+            self.channel_repo.as_ref(),
+            channel,
+            user_id,
+        )
+        .await
+        .map_err(ReactionError::from)?
+        .ok_or(ReactionError::Forbidden)?;
+
+        if !Permissions::new(permissions).has(permission) {
+            return Err(ReactionError::Forbidden);
+        }
+        Ok(())
+    }
+
+    /// Check `permission` in a channel, then make sure the message is in it.
+    async fn require_message(
+        &self,
+        channel_id: i64,
+        message_id: i64,
+        actor_id: i64,
+        permission: i64,
+    ) -> Result<(), ReactionError> {
+        let channel = self
+            .channel_repo
+            .find_by_id(channel_id)
+            .await
+            .map_err(ReactionError::from)?
+            .ok_or(ReactionError::ChannelNotFound)?;
+
+        self.require_channel_permission(&channel, actor_id, permission).await?;
+
+        let message = self
+            .message_repo
+            .find_by_id(message_id)
+            .await
+            .map_err(ReactionError::from)?
+            .ok_or(ReactionError::MessageNotFound)?;
+
+        if message.channel_id != channel_id {
+            return Err(ReactionError::MessageNotFound);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<Rx, M, C, Mem, S, R, U> ReactionService for ReactionServiceImpl<Rx, M, C, Mem, S, R, U>
+where
+    Rx: ReactionRepository + 'static,
+    M: MessageRepository + 'static,
+    C: ChannelRepository + 'static,
+    Mem: MemberRepository + 'static,
+    S: ServerRepository + 'static,
+    R: RoleRepository + 'static,
+    U: UserRepository + 'static,
+{
+    async fn get_reactors(
+        &self,
+        channel_id: i64,
+        message_id: i64,
+        emoji: &str,
+        actor_id: i64,
+        query: ReactorQueryDto,
+    ) -> Result<Vec<UserDto>, ReactionError> {
+        let emoji = normalize_emoji(emoji)?;
+        self.require_message(
+            channel_id,
+            message_id,
+            actor_id,
+            Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY,
+        )
+        .await?;
+
+        let reactor_ids = self
+            .reaction_repo
+            .list_reactors(message_id, &emoji, query.after, reactor_limit(query.limit))
+            .await
+            .map_err(ReactionError::from)?;
+
+        if reactor_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let users = self
+            .user_repo
+            .find_by_ids(&reactor_ids)
+            .await
+            .map_err(ReactionError::from)?;
+
+        Ok(order_reactors(&reactor_ids, users)
+            .into_iter()
+            .map(UserDto::from)
+            .collect())
+    }
+    async fn add_reaction(
+        &self,
+        channel_id: i64,
+        message_id: i64,
+        emoji: &str,
+        actor_id: i64,
+    ) -> Result<(), ReactionError> {
+        let emoji = normalize_emoji(emoji)?;
+        self.require_message(
+            channel_id,
+            message_id,
+            actor_id,
+            Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY | Permissions::ADD_REACTIONS,
+        )
+        .await?;
+
+        self.reaction_repo
+            .add_reaction(message_id, actor_id, &emoji)
+            .await
+            .map_err(ReactionError::from)
+    }
+
+    async fn remove_reaction(
+        &self,
+        channel_id: i64,
+        message_id: i64,
+        emoji: &str,
+        actor_id: i64,
+    ) -> Result<(), ReactionError> {
+        let emoji = normalize_emoji(emoji)?;
+        self.require_message(channel_id, message_id, actor_id, Permissions::VIEW_CHANNEL)
+            .await?;
+
+        self.reaction_repo
+            .remove_reaction(message_id, actor_id, &emoji)
+            .await
+            .map_err(ReactionError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: i64) -> User {
+        User {
+            id,
+            username: format!("user{}", id),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_unicode_and_custom_emoji_are_normalized() {
+        assert_eq!(normalize_emoji("👍").unwrap(), "👍");
+        assert_eq!(normalize_emoji("party:123456").unwrap(), "123456");
+        assert_eq!(normalize_emoji("<a:party:123456>").unwrap(), "123456");
+        assert_eq!(normalize_emoji("123456").unwrap(), "123456");
+
+        assert!(matches!(normalize_emoji(""), Err(ReactionError::InvalidEmoji)));
+        assert!(matches!(normalize_emoji("party:"), Err(ReactionError::InvalidEmoji)));
+        assert!(matches!(normalize_emoji("party:abc"), Err(ReactionError::InvalidEmoji)));
+    }
+
+    #[test]
+    fn test_reactor_limit_is_clamped() {
+        assert_eq!(reactor_limit(None), DEFAULT_REACTOR_LIMIT);
+        assert_eq!(reactor_limit(Some(0)), 1);
+        assert_eq!(reactor_limit(Some(1000)), MAX_REACTOR_LIMIT);
+    }
+
+    #[test]
+    fn test_page_keeps_cursor_order() {
+        // Users load in arbitrary order; the page stays sorted by ID so the
+        // last entry is the cursor for the next page
+        let page = order_reactors(&[3, 5, 8], vec![user(8), user(3), user(5)]);
+
+        let ids: Vec<i64> = page.iter().map(|u| u.id).collect();
+        assert_eq!(ids, vec![3, 5, 8]);
+        assert_eq!(page.last().map(|u| u.id), Some(8));
+    }
+
+    #[test]
+    fn test_missing_users_are_skipped() {
+        let page = order_reactors(&[3, 5, 8], vec![user(8), user(3)]);
+
+        let ids: Vec<i64> = page.iter().map(|u| u.id).collect();
+        assert_eq!(ids, vec![3, 8]);
+    }
+
+    #[test]
+    fn test_unreacted_emoji_has_no_reactors() {
+        assert!(order_reactors(&[], vec![user(1)]).is_empty());
+    }
+
+    #[cfg(feature = "db-tests")]
+    mod db {
+        use super::*;
+        use crate::infrastructure::repositories::{
+            test_db, PgChannelRepository, PgMemberRepository, PgMessageRepository, PgReactionRepository,
+            PgRoleRepository, PgServerRepository, PgUserRepository,
+        };
+
+        fn service(pool: sqlx::PgPool) -> impl ReactionService {
+            ReactionServiceImpl::new(
+                Arc::new(PgReactionRepository::new(pool.clone())),
+                Arc::new(PgMessageRepository::new(pool.clone())),
+                Arc::new(PgChannelRepository::new(pool.clone())),
+                Arc::new(PgMemberRepository::new(pool.clone())),
+                Arc::new(PgServerRepository::new(pool.clone())),
+                Arc::new(PgRoleRepository::new(pool.clone())),
+                Arc::new(PgUserRepository::new(pool)),
+            )
+        }
+
+        fn ids(users: &[UserDto]) -> Vec<i64> {
+            users.iter().map(|u| u.id.parse().unwrap()).collect()
+        }
+
+        #[tokio::test]
+        async fn test_reactors_are_paged_after_the_cursor() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let channel = test_db::channel(&pool, server).await;
+            let message = test_db::message(&pool, channel, owner).await;
+            let mut reactors = Vec::new();
+            for _ in 0..3 {
+                let reactor = test_db::user(&pool).await;
+                PgReactionRepository::new(pool.clone()).add_reaction(message, reactor, "👍").await.unwrap();
+                reactors.push(reactor);
+            }
+            reactors.sort_unstable();
+            let service = service(pool);
+
+            let page = |after, limit| ReactorQueryDto { after, limit };
+            let first = service.get_reactors(channel, message, "👍", owner, page(None, Some(2))).await.unwrap();
+            assert_eq!(ids(&first), reactors[..2]);
+
+            let cursor = ids(&first).last().copied();
+            let rest = service.get_reactors(channel, message, "👍", owner, page(cursor, Some(2))).await.unwrap();
+            assert_eq!(ids(&rest), reactors[2..]);
+
+            let cursor = Some(reactors[2]);
+            let past_end = service.get_reactors(channel, message, "👍", owner, page(cursor, None)).await.unwrap();
+            assert!(past_end.is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_unreacted_emoji_returns_an_empty_page() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let outsider = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let channel = test_db::channel(&pool, server).await;
+            let message = test_db::message(&pool, channel, owner).await;
+            PgReactionRepository::new(pool.clone()).add_reaction(message, owner, "👍").await.unwrap();
+            let service = service(pool);
+
+            let reactors = service
+                .get_reactors(channel, message, "party:123", owner, ReactorQueryDto::default())
+                .await
+                .unwrap();
+            assert!(reactors.is_empty());
+
+            let denied = service
+                .get_reactors(channel, message, "👍", outsider, ReactorQueryDto::default())
+                .await;
+            assert!(matches!(denied, Err(ReactionError::Forbidden)));
+        }
+
+        #[tokio::test]
+        async fn test_members_add_and_remove_their_own_reaction() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let member = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            test_db::member(&pool, server, member).await;
+            test_db::set_permissions(&pool, server, Permissions::DEFAULT).await;
+            let channel = test_db::channel(&pool, server).await;
+            let message = test_db::message(&pool, channel, owner).await;
+            let service = service(pool.clone());
+            let reactors = || service.get_reactors(channel, message, "👍", owner, ReactorQueryDto::default());
+
+            service.add_reaction(channel, message, "👍", member).await.unwrap();
+            service.add_reaction(channel, message, "👍", member).await.unwrap();
+            assert_eq!(ids(&reactors().await.unwrap()), vec![member]);
+
+            service.remove_reaction(channel, message, "👍", member).await.unwrap();
+            assert!(reactors().await.unwrap().is_empty());
+
+            test_db::set_permissions(&pool, server, Permissions::DEFAULT & !Permissions::ADD_REACTIONS).await;
+            let denied = service.add_reaction(channel, message, "👍", member).await;
+            assert!(matches!(denied, Err(ReactionError::Forbidden)));
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Recipient Permissions
+//!
+//! Resolves the permissions of connected guild members so the gateway can
+//! limit a broadcast to members who may see it, e.g. only `VIEW_CHANNEL`
+//! holders for a channel's events, or only members allowed to see an NSFW
+//! channel's messages.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+
+use super::permission_resolver::GuildPermissions;
+use crate::domain::services::PermissionService;
+use crate::domain::{ChannelRepository, MemberRepository, RoleRepository, ServerRepository, UserRepository};
+use crate::shared::error::AppError;
+
+/// Source of members' permissions for permission-filtered broadcasts
+#[async_trait]
+pub trait RecipientPermissions: Send + Sync {
+    /// Effective permissions of each of `user_ids` in a guild, in
+    /// `channel_id` when given.
+    ///
+    /// Users who aren't members of the guild are left out of the result.
+    async fn permissions(
+        &self,
+        guild_id: i64,
+        channel_id: Option<i64>,
+        user_ids: &[i64],
+    ) -> Result<HashMap<i64, i64>, AppError>;
+
+    /// Those of `user_ids` who may see the messages of an NSFW channel,
+    /// i.e. members who allowed NSFW content or moderate the channel, or
+    /// `None` if the channel isn't NSFW.
+    ///
+    /// Nobody may see the messages of a channel that is already gone.
+    async fn nsfw_viewers(
+        &self,
+        guild_id: i64,
+        channel_id: i64,
+        user_ids: &[i64],
+    ) -> Result<Option<Vec<i64>>, AppError>;
+}
+
+/// How long a guild's owner and roles are reused across broadcasts
+const GUILD_TTL: Duration = Duration::from_secs(5);
+
+/// Computes recipients' permissions with the shared permission resolver.
+///
+/// A guild's owner and roles are loaded once and reused for [`GUILD_TTL`],
+/// so a burst of events in one guild doesn't reload them for every event.
+pub struct GuildRecipientPermissions<S, M, R, C, U>
+where
+    S: ServerRepository,
+    M: MemberRepository,
+    R: RoleRepository,
+    C: ChannelRepository,
+    U: UserRepository,
+{
+    server_repo: Arc<S>,
+    member_repo: Arc<M>,
+    role_repo: Arc<R>,
+    channel_repo: Arc<C>,
+    user_repo: Arc<U>,
+    guilds: DashMap<i64, (Instant, Arc<GuildPermissions>)>,
+    guild_ttl: Duration,
+}
+
+impl<S, M, R, C, U> GuildRecipientPermissions<S, M, R, C, U>
+where
+    S: ServerRepository,
+    M: MemberRepository,
+    R: RoleRepository,
+    C: ChannelRepository,
+    U: UserRepository,
+{
+    /// Create a new GuildRecipientPermissions
+    pub fn new(
+        server_repo: Arc<S>,
+        member_repo: Arc<M>,
+        role_repo: Arc<R>,
+        channel_repo: Arc<C>,
+        user_repo: Arc<U>,
+    ) -> Self {
+        Self {
+            server_repo,
+            member_repo,
+            role_repo,
+            channel_repo,
+            user_repo,
+            guilds: DashMap::new(),
+            guild_ttl: GUILD_TTL,
+        }
+    }
+
+    /// Reuse a guild's owner and roles for `ttl` instead of [`GUILD_TTL`]
+    pub fn with_guild_ttl(mut self, ttl: Duration) -> Self {
+        self.guild_ttl = ttl;
+        self
+    }
+
+    /// A guild's owner and roles, loaded unless a recent load is cached
+    async fn guild(&self, guild_id: i64) -> Result<Option<Arc<GuildPermissions>>, AppError> {
+        if let Some(entry) = self.guilds.get(&guild_id) {
+            let (loaded_at, guild) = entry.value();
+            if loaded_at.elapsed() < self.guild_ttl {
+                return Ok(Some(guild.clone()));
+            }
+        }
+
+        let loaded = GuildPermissions::load(self.server_repo.as_ref(), self.role_repo.as_ref(), guild_id).await?;
+        let Some(guild) = loaded else {
+            self.guilds.remove(&guild_id);
+            return Ok(None);
+        };
+        let guild = Arc::new(guild);
+        self.guilds.insert(guild_id, (Instant::now(), guild.clone()));
+
+        Ok(Some(guild))
+    }
+}
+
+#[async_trait]
+impl<S, M, R, C, U> RecipientPermissions for GuildRecipientPermissions<S, M, R, C, U>
+where
+    S: ServerRepository + 'static,
+    M: MemberRepository + 'static,
+    R: RoleRepository + 'static,
+    C: ChannelRepository + 'static,
+    U: UserRepository + 'static,
+{
+    async fn permissions(
+        &self,
+        guild_id: i64,
+        channel_id: Option<i64>,
+        user_ids: &[i64],
+    ) -> Result<HashMap<i64, i64>, AppError> {
+        let Some(guild) = self.guild(guild_id).await? else {
+            return Ok(HashMap::new());
+        };
+
+        // Nobody may see the events of a channel that is already gone
+        let channel = match channel_id {
+            Some(channel_id) => match self.channel_repo.find_by_id(channel_id).await? {
+                Some(channel) => Some(channel),
+                None => return Ok(HashMap::new()),
+            },
+            None => None,
+        };
+        let overwrites = match &channel {
+            Some(channel) => self.channel_repo.get_permission_overwrites(channel.id).await?,
+            None => Vec::new(),
+        };
+
+        let members = self.member_repo.find_many(guild_id, user_ids).await?;
+        let permissions = members
+            .iter()
+            .map(|member| {
+                let permissions = match &channel {
+                    Some(channel) => guild.channel(member, channel, &overwrites),
+                    None => guild.base(member),
+                };
+                (member.user_id, permissions)
+            })
+            .collect();
+
+        Ok(permissions)
+    }
+
+    async fn nsfw_viewers(
+        &self,
+        guild_id: i64,
+        channel_id: i64,
+        user_ids: &[i64],
+    ) -> Result<Option<Vec<i64>>, AppError> {
+        let Some(channel) = self.channel_repo.find_by_id(channel_id).await? else {
+            return Ok(Some(Vec::new()));
+        };
+        if !channel.nsfw {
+            return Ok(None);
+        }
+        let Some(guild) = self.guild(guild_id).await? else {
+            return Ok(Some(Vec::new()));
+        };
+
+        let overwrites = self.channel_repo.get_permission_overwrites(channel.id).await?;
+        let members = self.member_repo.find_many(guild_id, user_ids).await?;
+        let allowed: HashSet<i64> = self
+            .user_repo
+            .find_by_ids(user_ids)
+            .await?
+            .into_iter()
+            .filter(|user| user.nsfw_allowed)
+            .map(|user| user.id)
+            .collect();
+        let viewers = members
+            .iter()
+            .filter(|member| {
+                let permissions = guild.channel(member, &channel, &overwrites);
+                PermissionService::can_view_nsfw(&channel, permissions, allowed.contains(&member.user_id))
+            })
+            .map(|member| member.user_id)
+            .collect();
+
+        Ok(Some(viewers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "db-tests")]
+    mod db {
+        use super::super::*;
+        use crate::domain::value_objects::Permissions;
+        use crate::infrastructure::repositories::{
+            test_db, PgChannelRepository, PgMemberRepository, PgRoleRepository, PgServerRepository,
+            PgUserRepository,
+        };
+
+        type Resolver = GuildRecipientPermissions<
+            PgServerRepository,
+            PgMemberRepository,
+            PgRoleRepository,
+            PgChannelRepository,
+            PgUserRepository,
+        >;
+
+        fn resolver(pool: &sqlx::PgPool) -> Resolver {
+            GuildRecipientPermissions::new(
+                Arc::new(PgServerRepository::new(pool.clone())),
+                Arc::new(PgMemberRepository::new(pool.clone())),
+                Arc::new(PgRoleRepository::new(pool.clone())),
+                Arc::new(PgChannelRepository::new(pool.clone())),
+                Arc::new(PgUserRepository::new(pool.clone())),
+            )
+        }
+
+        #[tokio::test]
+        async fn test_deleted_channel_has_no_recipients() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let member = test_db::user(&pool).await;
+            test_db::member(&pool, server, member).await;
+            test_db::set_permissions(&pool, server, Permissions::VIEW_CHANNEL).await;
+            let resolver = resolver(&pool);
+
+            let guild_wide = resolver.permissions(server, None, &[owner, member]).await.unwrap();
+            assert_eq!(guild_wide.len(), 2);
+            assert!(guild_wide[&member] & Permissions::VIEW_CHANNEL != 0);
+
+            let deleted = resolver.permissions(server, Some(test_db::id()), &[owner, member]).await.unwrap();
+            assert!(deleted.is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_nsfw_channel_is_seen_by_opted_in_members_and_moderators() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let opted_in = test_db::user(&pool).await;
+            let member = test_db::user(&pool).await;
+            for user in [opted_in, member] {
+                test_db::member(&pool, server, user).await;
+            }
+            test_db::set_permissions(&pool, server, Permissions::VIEW_CHANNEL).await;
+            sqlx::query("UPDATE users SET nsfw_allowed = TRUE WHERE id = $1")
+                .bind(opted_in)
+                .execute(&pool)
+                .await
+                .unwrap();
+            let sfw = test_db::channel(&pool, server).await;
+            let nsfw = test_db::channel(&pool, server).await;
+            sqlx::query("UPDATE channels SET nsfw = TRUE WHERE id = $1")
+                .bind(nsfw)
+                .execute(&pool)
+                .await
+                .unwrap();
+            let resolver = resolver(&pool);
+            let users = [owner, opted_in, member];
+
+            // The owner moderates every channel
+            let mut viewers = resolver.nsfw_viewers(server, nsfw, &users).await.unwrap().unwrap();
+            viewers.sort_unstable();
+            let mut expected = vec![owner, opted_in];
+            expected.sort_unstable();
+            assert_eq!(viewers, expected);
+
+            assert_eq!(resolver.nsfw_viewers(server, sfw, &users).await.unwrap(), None);
+            let deleted = resolver.nsfw_viewers(server, test_db::id(), &users).await.unwrap();
+            assert_eq!(deleted, Some(Vec::new()));
+        }
+
+        #[tokio::test]
+        async fn test_guild_roles_are_reused_until_the_ttl_passes() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let member = test_db::user(&pool).await;
+            test_db::member(&pool, server, member).await;
+            test_db::set_permissions(&pool, server, Permissions::VIEW_CHANNEL).await;
+            let cached = resolver(&pool);
+            let uncached = resolver(&pool).with_guild_ttl(Duration::ZERO);
+
+            cached.permissions(server, None, &[member]).await.unwrap();
+            uncached.permissions(server, None, &[member]).await.unwrap();
+            test_db::set_permissions(&pool, server, 0).await;
+
+            let stale = cached.permissions(server, None, &[member]).await.unwrap();
+            assert!(stale[&member] & Permissions::VIEW_CHANNEL != 0);
+            let fresh = uncached.permissions(server, None, &[member]).await.unwrap();
+            assert_eq!(fresh[&member] & Permissions::VIEW_CHANNEL, 0);
+        }
+    }
+}
@@ -3,13 +3,18 @@
 //! Handles role management operations including CRUD, reordering,
 //! and member role assignments.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use chrono::Utc;
 
-use crate::domain::{MemberRepository, Role, RoleRepository, ServerRepository};
+use super::guild_service::MemberDto;
+use crate::domain::services::PermissionService;
+use crate::domain::{Member, MemberRepository, Role, RoleCleanup, RoleRepository, ServerRepository};
 use crate::domain::value_objects::Permissions;
+use crate::infrastructure::cache::{Cache, EveryonePermissionsCache, PermissionCacheService, RedisCache};
+use crate::shared::error::AppError;
 use crate::shared::snowflake::SnowflakeGenerator;
 
 /// Role service trait defining all role management operations.
@@ -24,7 +29,9 @@ pub trait RoleService: Send + Sync {
     ) -> Result<RoleDto, RoleError>;
 
     /// Get a role by its ID.
-    async fn get_role(&self, role_id: i64) -> Result<RoleDto, RoleError>;
+    ///
+    /// Only members of the server can read its roles.
+    async fn get_role(&self, server_id: i64, role_id: i64, actor_id: i64) -> Result<RoleDto, RoleError>;
 
     /// Get all roles for a server.
     async fn get_roles_by_server(&self, server_id: i64) -> Result<Vec<RoleDto>, RoleError>;
@@ -40,7 +47,20 @@ pub trait RoleService: Send + Sync {
     /// Delete a role.
     async fn delete_role(&self, role_id: i64, actor_id: i64) -> Result<(), RoleError>;
 
+    /// Create a copy of a role's permissions and settings, placed just below
+    /// it in the hierarchy. Member assignments are not copied.
+    async fn clone_role(
+        &self,
+        role_id: i64,
+        actor_id: i64,
+        new_name: String,
+    ) -> Result<RoleDto, RoleError>;
+
     /// Reorder roles within a server.
+    ///
+    /// The resulting positions are renumbered 1..N with @everyone kept at 0,
+    /// and every moved role must stay below the actor's highest role in
+    /// that final ordering.
     async fn reorder_roles(
         &self,
         server_id: i64,
@@ -72,8 +92,36 @@ pub trait RoleService: Send + Sync {
         server_id: i64,
         user_id: i64,
     ) -> Result<Vec<RoleDto>, RoleError>;
+
+    /// Replace a member's roles with the given set.
+    ///
+    /// Every added or removed role is validated before anything is written,
+    /// and all changes are applied in one transaction.
+    async fn set_member_roles(
+        &self,
+        server_id: i64,
+        user_id: i64,
+        role_ids: Vec<i64>,
+        actor_id: i64,
+    ) -> Result<MemberRolesDto, RoleError>;
+
+    /// List the members holding a role, ordered by user ID.
+    ///
+    /// The actor must be a member of the server. Pass the last user ID of a
+    /// page as `after` to get the next one.
+    async fn get_role_members(
+        &self,
+        server_id: i64,
+        role_id: i64,
+        actor_id: i64,
+        after: Option<i64>,
+        limit: i32,
+    ) -> Result<Vec<MemberDto>, RoleError>;
 }
 
+/// Largest page of role members
+pub const MAX_ROLE_MEMBERS_PAGE: i32 = 1000;
+
 // =============================================================================
 // Data Transfer Objects
 // =============================================================================
@@ -159,6 +207,53 @@ pub struct RolePositionDto {
     pub position: i32,
 }
 
+/// Result of replacing a member's roles.
+#[derive(Debug, Clone)]
+pub struct MemberRolesDto {
+    /// Roles the member holds after the update, highest first.
+    pub roles: Vec<RoleDto>,
+    /// Whether any role was added or removed.
+    pub changed: bool,
+}
+
+/// Roles to add and remove to turn a member's current roles into the
+/// requested set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct RoleDiff {
+    added: Vec<i64>,
+    removed: Vec<i64>,
+}
+
+impl RoleDiff {
+    fn new(current: &[i64], desired: &[i64]) -> Self {
+        let mut added: Vec<i64> = desired
+            .iter()
+            .copied()
+            .filter(|id| !current.contains(id))
+            .collect();
+        added.sort_unstable();
+        added.dedup();
+
+        let mut removed: Vec<i64> = current
+            .iter()
+            .copied()
+            .filter(|id| !desired.contains(id))
+            .collect();
+        removed.sort_unstable();
+        removed.dedup();
+
+        Self { added, removed }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    fn changed_ids(&self) -> impl Iterator<Item = i64> + '_ {
+        self.added.iter().chain(&self.removed).copied()
+    }
+}
+
 // =============================================================================
 // Error Types
 // =============================================================================
@@ -196,25 +291,137 @@ pub enum RoleError {
     #[error("Invalid permissions value")]
     InvalidPermissions,
 
+    #[error("Cannot grant permissions you don't have")]
+    CannotGrantPermissions,
+
     #[error("Internal error: {0}")]
     Internal(String),
+
+    /// No database connection was free in time; the request may succeed on retry
+    #[error("Service unavailable")]
+    Unavailable,
+}
+
+impl From<AppError> for RoleError {
+    fn from(e: AppError) -> Self {
+        if e.is_unavailable() {
+            RoleError::Unavailable
+        } else {
+            RoleError::Internal(e.to_string())
+        }
+    }
+}
+
+/// One page of a role's members, checking the role belongs to the server
+/// and the actor is a member of it.
+///
+/// Nobody has a member-roles row for @everyone, so its members are all of
+/// the server's members.
+async fn role_members_page<M: MemberRepository>(
+    member_repo: &M,
+    role: &Role,
+    everyone: bool,
+    server_id: i64,
+    actor_id: i64,
+    after: Option<i64>,
+    limit: i32,
+) -> Result<Vec<Member>, RoleError> {
+    if role.server_id != server_id {
+        return Err(RoleError::NotFound);
+    }
+
+    let is_member = member_repo
+        .is_member(server_id, actor_id)
+        .await
+        .map_err(RoleError::from)?;
+    if !is_member {
+        return Err(RoleError::Forbidden);
+    }
+
+    let limit = limit.clamp(1, MAX_ROLE_MEMBERS_PAGE);
+    let members = if everyone {
+        member_repo.find_by_server_id(server_id, after, limit).await
+    } else {
+        member_repo.find_members_with_role(server_id, role.id, after, limit).await
+    };
+
+    members.map_err(RoleError::from)
 }
 
 // =============================================================================
 // Service Implementation
 // =============================================================================
 
+/// Permissions granted by a server's @everyone role, or none if it has no
+/// such role.
+///
+/// Served from `cache` when one is attached; misses are loaded and written
+/// back. Cache failures are logged and fall back to the repository.
+async fn everyone_permissions<R, C>(
+    role_repo: &R,
+    cache: Option<&EveryonePermissionsCache<C>>,
+    server_id: i64,
+) -> Result<i64, RoleError>
+where
+    R: RoleRepository + ?Sized,
+    C: Cache,
+{
+    if let Some(cache) = cache {
+        match cache.get(server_id).await {
+            Ok(Some(permissions)) => return Ok(permissions),
+            Ok(None) => {}
+            Err(e) => tracing::warn!(server_id, error = %e, "Failed to read cached @everyone permissions"),
+        }
+    }
+
+    let permissions = role_repo
+        .find_everyone_role(server_id)
+        .await
+        .map_err(RoleError::from)?
+        .map_or(0, |everyone| everyone.permissions);
+
+    if let Some(cache) = cache {
+        if let Err(e) = cache.set(server_id, permissions).await {
+            tracing::warn!(server_id, error = %e, "Failed to cache @everyone permissions");
+        }
+    }
+
+    Ok(permissions)
+}
+
+/// Drop a server's cached @everyone permissions after one of its roles
+/// was updated.
+///
+/// The update is already stored, so failures are logged rather than
+/// surfaced; a stale entry expires with its TTL.
+async fn invalidate_everyone_permissions<C: Cache>(cache: Option<&EveryonePermissionsCache<C>>, server_id: i64) {
+    let Some(cache) = cache else {
+        return;
+    };
+
+    if let Err(e) = cache.invalidate(server_id).await {
+        tracing::warn!(server_id, error = %e, "Failed to invalidate @everyone permissions");
+    }
+}
+
 /// RoleService implementation with PostgreSQL repositories.
-pub struct RoleServiceImpl<R, S, M>
+///
+/// `P` is the cache backing the permission cache and `E` the one backing
+/// the @everyone permissions cache.
+pub struct RoleServiceImpl<R, S, M, P = RedisCache, E = RedisCache>
 where
     R: RoleRepository,
     S: ServerRepository,
     M: MemberRepository,
+    P: Cache,
+    E: Cache,
 {
     role_repo: Arc<R>,
     server_repo: Arc<S>,
     member_repo: Arc<M>,
     id_generator: Arc<SnowflakeGenerator>,
+    permission_cache: Option<PermissionCacheService<P>>,
+    everyone_cache: Option<EveryonePermissionsCache<E>>,
 }
 
 impl<R, S, M> RoleServiceImpl<R, S, M>
@@ -235,6 +442,86 @@ where
             server_repo,
             member_repo,
             id_generator,
+            permission_cache: None,
+            everyone_cache: None,
+        }
+    }
+}
+
+impl<R, S, M, P, E> RoleServiceImpl<R, S, M, P, E>
+where
+    R: RoleRepository,
+    S: ServerRepository,
+    M: MemberRepository,
+    P: Cache,
+    E: Cache,
+{
+    /// Attach a permission cache to invalidate when roles change.
+    pub fn with_permission_cache<T: Cache>(self, cache: PermissionCacheService<T>) -> RoleServiceImpl<R, S, M, T, E> {
+        RoleServiceImpl {
+            role_repo: self.role_repo,
+            server_repo: self.server_repo,
+            member_repo: self.member_repo,
+            id_generator: self.id_generator,
+            permission_cache: Some(cache),
+            everyone_cache: self.everyone_cache,
+        }
+    }
+
+    /// Cache @everyone permissions for member permission checks.
+    pub fn with_everyone_cache<T: Cache>(self, cache: EveryonePermissionsCache<T>) -> RoleServiceImpl<R, S, M, P, T> {
+        RoleServiceImpl {
+            role_repo: self.role_repo,
+            server_repo: self.server_repo,
+            member_repo: self.member_repo,
+            id_generator: self.id_generator,
+            permission_cache: self.permission_cache,
+            everyone_cache: Some(cache),
+        }
+    }
+
+    /// Drop cached permissions derived from a deleted role.
+    ///
+    /// The role is already gone at this point, so failures are logged
+    /// rather than surfaced; stale entries expire with their TTL.
+    async fn invalidate_role_permissions(&self, server_id: i64, cleanup: &RoleCleanup) {
+        self.invalidate_channel_permissions(server_id).await;
+
+        for &user_id in &cleanup.user_ids {
+            self.invalidate_member_permissions(server_id, user_id).await;
+        }
+    }
+
+    /// Drop a member's cached guild permissions after their roles changed,
+    /// along with the guild's cached channel permissions.
+    ///
+    /// The change is already stored, so failures are logged rather than
+    /// surfaced; stale entries expire with their TTL.
+    async fn invalidate_member_roles(&self, server_id: i64, user_id: i64) {
+        self.invalidate_member_permissions(server_id, user_id).await;
+        self.invalidate_channel_permissions(server_id).await;
+    }
+
+    /// Drop a member's cached guild permissions.
+    async fn invalidate_member_permissions(&self, server_id: i64, user_id: i64) {
+        let Some(cache) = &self.permission_cache else {
+            return;
+        };
+
+        if let Err(e) = cache.invalidate_user_guild_permissions(server_id, user_id).await {
+            tracing::warn!(server_id, user_id, error = %e, "Failed to invalidate member permissions");
+        }
+    }
+
+    /// Drop every cached channel permission in a guild, all of which may
+    /// derive from its roles.
+    async fn invalidate_channel_permissions(&self, server_id: i64) {
+        let Some(cache) = &self.permission_cache else {
+            return;
+        };
+
+        if let Err(e) = cache.invalidate_guild_channel_permissions(server_id).await {
+            tracing::warn!(server_id, error = %e, "Failed to invalidate channel permissions");
         }
     }
 
@@ -244,7 +531,7 @@ where
             .server_repo
             .find_by_id(server_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?
+            .map_err(RoleError::from)?
             .ok_or(RoleError::ServerNotFound)?;
 
         Ok(server.owner_id == user_id)
@@ -256,17 +543,30 @@ where
     /// - Actor is the server owner, OR
     /// - Actor has MANAGE_ROLES or ADMINISTRATOR permission
     async fn can_manage_roles(&self, server_id: i64, actor_id: i64) -> Result<bool, RoleError> {
+        let perms = self.actor_permissions(server_id, actor_id).await?;
+        Ok(perms.has(Permissions::MANAGE_ROLES) || perms.is_admin())
+    }
+
+    /// Compute the actor's guild-level permissions.
+    ///
+    /// The server owner has every permission; non-members are rejected.
+    async fn actor_permissions(&self, server_id: i64, actor_id: i64) -> Result<Permissions, RoleError> {
         // Server owner always has permission
         if self.is_owner(server_id, actor_id).await? {
-            return Ok(true);
+            return Ok(Permissions::all());
         }
 
+        self.member_permissions(server_id, actor_id).await
+    }
+
+    /// Compute a member's permissions from @everyone and their roles.
+    async fn member_permissions(&self, server_id: i64, actor_id: i64) -> Result<Permissions, RoleError> {
         // Check if actor is a member
         let is_member = self
             .member_repo
             .is_member(server_id, actor_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
+            .map_err(RoleError::from)?;
 
         if !is_member {
             return Err(RoleError::Forbidden);
@@ -277,34 +577,40 @@ where
             .member_repo
             .get_roles(server_id, actor_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
+            .map_err(RoleError::from)?;
 
-        let mut permissions = 0_i64;
+        let mut permissions =
+            everyone_permissions(self.role_repo.as_ref(), self.everyone_cache.as_ref(), server_id).await?;
+        if role_ids.is_empty() {
+            return Ok(Permissions::new(permissions));
+        }
 
-        // Get @everyone role permissions
-        if let Some(everyone) = self
+        // Add permissions from assigned roles
+        let roles = self
             .role_repo
-            .find_everyone_role(server_id)
+            .find_by_ids(&role_ids)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?
-        {
-            permissions |= everyone.permissions;
+            .map_err(RoleError::from)?;
+
+        for role in roles {
+            permissions |= role.permissions;
         }
 
-        // Add permissions from assigned roles
-        for role_id in role_ids {
-            if let Some(role) = self
-                .role_repo
-                .find_by_id(role_id)
-                .await
-                .map_err(|e| RoleError::Internal(e.to_string()))?
-            {
-                permissions |= role.permissions;
-            }
+        Ok(Permissions::new(permissions))
+    }
+
+    /// IDs of the actor's roles, or `None` for the server owner, who is
+    /// above every role.
+    async fn actor_role_ids(&self, server_id: i64, actor_id: i64) -> Result<Option<Vec<i64>>, RoleError> {
+        if self.is_owner(server_id, actor_id).await? {
+            return Ok(None);
         }
 
-        let perms = Permissions::new(permissions);
-        Ok(perms.has(Permissions::MANAGE_ROLES) || perms.is_admin())
+        self.member_repo
+            .get_roles(server_id, actor_id)
+            .await
+            .map(Some)
+            .map_err(RoleError::from)
     }
 
     /// Get the highest role position for the actor.
@@ -322,24 +628,15 @@ where
             .member_repo
             .get_roles(server_id, actor_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
-
-        let mut highest = 0;
+            .map_err(RoleError::from)?;
 
-        for role_id in role_ids {
-            if let Some(role) = self
-                .role_repo
-                .find_by_id(role_id)
-                .await
-                .map_err(|e| RoleError::Internal(e.to_string()))?
-            {
-                if role.position > highest {
-                    highest = role.position;
-                }
-            }
-        }
+        let roles = self
+            .role_repo
+            .find_by_ids(&role_ids)
+            .await
+            .map_err(RoleError::from)?;
 
-        Ok(highest)
+        Ok(roles.iter().fold(0, |highest, r| highest.max(r.position)))
     }
 
     /// Check if the target role is within the actor's hierarchy.
@@ -360,12 +657,205 @@ where
         Ok(())
     }
 
+    /// Role a member may read: only roles of the server they're in.
+    ///
+    /// Non-members are refused before role existence is revealed.
+    fn check_role_visible(role: Option<Role>, server_id: i64, is_member: bool) -> Result<Role, RoleError> {
+        if !is_member {
+            return Err(RoleError::Forbidden);
+        }
+
+        role.filter(|role| role.server_id == server_id)
+            .ok_or(RoleError::NotFound)
+    }
+
     /// Check if a role is the @everyone role.
     fn is_everyone_role(role: &Role) -> bool {
         // @everyone role has the same ID as the server, or position 0 and name "@everyone"
         role.id == role.server_id || (role.position == 0 && role.name == "@everyone")
     }
 
+    /// Check every added or removed role against the actor's hierarchy.
+    ///
+    /// Fails on the first invalid role so that no change is applied.
+    fn validate_role_changes(
+        server_id: i64,
+        diff: &RoleDiff,
+        server_roles: &HashMap<i64, Role>,
+        actor_highest: i32,
+    ) -> Result<(), RoleError> {
+        for role_id in diff.changed_ids() {
+            let role = server_roles
+                .get(&role_id)
+                .filter(|role| role.server_id == server_id)
+                .ok_or(RoleError::NotFound)?;
+
+            if Self::is_everyone_role(role) {
+                return Err(RoleError::CannotAssignEveryoneRole);
+            }
+
+            if role.position >= actor_highest {
+                return Err(RoleError::HierarchyViolation);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that the actor may grant `requested` permission flags.
+    async fn check_can_grant(&self, server_id: i64, actor_id: i64, requested: i64) -> Result<(), RoleError> {
+        if requested == 0 {
+            return Ok(());
+        }
+
+        let is_owner = self.is_owner(server_id, actor_id).await?;
+        let actor_permissions = if is_owner {
+            Permissions::ALL
+        } else {
+            self.member_permissions(server_id, actor_id).await?.bits()
+        };
+
+        if !PermissionService::can_grant(actor_permissions, requested, is_owner) {
+            return Err(RoleError::CannotGrantPermissions);
+        }
+        Ok(())
+    }
+
+    /// Check that the actor may create a copy of `source`.
+    ///
+    /// The copy lands at `clone_position`, which must be below the actor's
+    /// highest role, and non-administrators can't hand out permissions they
+    /// don't have themselves.
+    fn check_clone_allowed(
+        source: &Role,
+        clone_position: i32,
+        actor_highest: i32,
+        actor_permissions: Permissions,
+    ) -> Result<(), RoleError> {
+        if source.position >= actor_highest || clone_position >= actor_highest {
+            return Err(RoleError::HierarchyViolation);
+        }
+
+        // The owner is given every permission, so owners pass as well
+        if !PermissionService::can_grant(actor_permissions.bits(), source.permissions, false) {
+            return Err(RoleError::CannotGrantPermissions);
+        }
+
+        Ok(())
+    }
+
+    /// Position for a copy of `source` and the roles that move up to make
+    /// room for it.
+    ///
+    /// The copy takes the source's position and the source and every role
+    /// above it shift up by one, so the copy sits just below the source.
+    /// Copies of @everyone go to the bottom of the other roles.
+    fn clone_placement(source: &Role, server_roles: &[Role]) -> (i32, Vec<(i64, i32)>) {
+        let position = source.position.max(1);
+
+        let shifted = server_roles
+            .iter()
+            .filter(|role| !Self::is_everyone_role(role) && role.position >= position)
+            .map(|role| (role.id, role.position + 1))
+            .collect();
+
+        (position, shifted)
+    }
+
+    /// Final positions of a server's roles after moving some of them.
+    ///
+    /// `roles` excludes @everyone, which stays at 0. Roles are ordered by
+    /// their requested (or current) position; on a tie, a role moved down
+    /// lands below the roles already there and one moved up lands above
+    /// them. Every role is then numbered 1..N from the bottom.
+    fn normalized_positions(roles: &[Role], requested: &[(i64, i32)]) -> Vec<(i64, i32)> {
+        let requested: HashMap<i64, i32> = requested.iter().copied().collect();
+
+        let mut order: Vec<(i32, std::cmp::Ordering, i32, i64)> = roles
+            .iter()
+            .map(|role| {
+                let target = requested.get(&role.id).copied().unwrap_or(role.position);
+                (target, target.cmp(&role.position), role.position, role.id)
+            })
+            .collect();
+        order.sort_unstable();
+
+        order
+            .into_iter()
+            .zip(1..)
+            .map(|((.., id), position)| (id, position))
+            .collect()
+    }
+
+    /// Position changes that carry out a reorder of a server's roles.
+    ///
+    /// `actor_role_ids` is `None` for the owner, who isn't bound by the
+    /// hierarchy. Only roles whose position actually changes are returned.
+    fn reorder_plan(
+        server_roles: Vec<Role>,
+        requested: &[(i64, i32)],
+        actor_role_ids: Option<&[i64]>,
+    ) -> Result<Vec<(i64, i32)>, RoleError> {
+        for (role_id, _) in requested {
+            let role = server_roles
+                .iter()
+                .find(|role| role.id == *role_id)
+                .ok_or(RoleError::NotFound)?;
+
+            // Cannot reorder @everyone role
+            if Self::is_everyone_role(role) {
+                return Err(RoleError::CannotModifyEveryoneRole);
+            }
+        }
+
+        let roles: Vec<Role> = server_roles
+            .into_iter()
+            .filter(|role| !Self::is_everyone_role(role))
+            .collect();
+        let normalized = Self::normalized_positions(&roles, requested);
+
+        if let Some(actor_role_ids) = actor_role_ids {
+            let moved: Vec<i64> = requested.iter().map(|(role_id, _)| *role_id).collect();
+            Self::check_reorder_hierarchy(&roles, &normalized, &moved, actor_role_ids)?;
+        }
+
+        Ok(normalized
+            .into_iter()
+            .filter(|(role_id, position)| roles.iter().any(|role| role.id == *role_id && role.position != *position))
+            .collect())
+    }
+
+    /// Check a reorder against the actor's hierarchy.
+    ///
+    /// Each moved role must be below the actor's highest role before the
+    /// reorder and in the final, normalized positions, so collisions and
+    /// gaps can't carry a role past the actor.
+    fn check_reorder_hierarchy(
+        roles: &[Role],
+        final_positions: &[(i64, i32)],
+        moved: &[i64],
+        actor_role_ids: &[i64],
+    ) -> Result<(), RoleError> {
+        let final_positions: HashMap<i64, i32> = final_positions.iter().copied().collect();
+        let highest_before = roles
+            .iter()
+            .filter(|role| actor_role_ids.contains(&role.id))
+            .fold(0, |highest, role| highest.max(role.position));
+        let highest_after = actor_role_ids
+            .iter()
+            .filter_map(|id| final_positions.get(id))
+            .fold(0, |highest, &position| highest.max(position));
+
+        for role in roles.iter().filter(|role| moved.contains(&role.id)) {
+            let position = final_positions.get(&role.id).copied().unwrap_or(role.position);
+            if role.position >= highest_before || position >= highest_after {
+                return Err(RoleError::HierarchyViolation);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Validate role name.
     fn validate_name(name: &str) -> Result<(), RoleError> {
         if name.is_empty() {
@@ -381,11 +871,13 @@ where
 }
 
 #[async_trait]
-impl<R, S, M> RoleService for RoleServiceImpl<R, S, M>
+impl<R, S, M, P, E> RoleService for RoleServiceImpl<R, S, M, P, E>
 where
     R: RoleRepository + 'static,
     S: ServerRepository + 'static,
     M: MemberRepository + 'static,
+    P: Cache + 'static,
+    E: Cache + 'static,
 {
     async fn create_role(
         &self,
@@ -401,12 +893,15 @@ where
         // Validate name
         Self::validate_name(&request.name)?;
 
+        let permissions = request.permissions.unwrap_or(0);
+        self.check_can_grant(server_id, actor_id, permissions).await?;
+
         // Get the next position (one above the highest)
         let max_position = self
             .role_repo
             .get_max_position(server_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
+            .map_err(RoleError::from)?;
 
         let now = Utc::now();
 
@@ -414,7 +909,7 @@ where
             id: self.id_generator.generate(),
             server_id,
             name: request.name,
-            permissions: request.permissions.unwrap_or(0),
+            permissions,
             position: max_position + 1,
             color: request.color,
             hoist: request.hoist.unwrap_or(false),
@@ -427,20 +922,25 @@ where
             .role_repo
             .create(&role)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
+            .map_err(RoleError::from)?;
 
         Ok(RoleDto::from(created))
     }
 
-    async fn get_role(&self, role_id: i64) -> Result<RoleDto, RoleError> {
+    async fn get_role(&self, server_id: i64, role_id: i64, actor_id: i64) -> Result<RoleDto, RoleError> {
+        let is_member = self
+            .member_repo
+            .is_member(server_id, actor_id)
+            .await
+            .map_err(RoleError::from)?;
+
         let role = self
             .role_repo
             .find_by_id(role_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?
-            .ok_or(RoleError::NotFound)?;
+            .map_err(RoleError::from)?;
 
-        Ok(RoleDto::from(role))
+        Ok(RoleDto::from(Self::check_role_visible(role, server_id, is_member)?))
     }
 
     async fn get_roles_by_server(&self, server_id: i64) -> Result<Vec<RoleDto>, RoleError> {
@@ -448,14 +948,14 @@ where
         self.server_repo
             .find_by_id(server_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?
+            .map_err(RoleError::from)?
             .ok_or(RoleError::ServerNotFound)?;
 
         let roles = self
             .role_repo
             .find_by_server_id(server_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
+            .map_err(RoleError::from)?;
 
         Ok(roles.into_iter().map(RoleDto::from).collect())
     }
@@ -470,7 +970,7 @@ where
             .role_repo
             .find_by_id(role_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?
+            .map_err(RoleError::from)?
             .ok_or(RoleError::NotFound)?;
 
         // Check permission
@@ -498,6 +998,9 @@ where
         }
 
         if let Some(permissions) = update.permissions {
+            // Adding or removing a flag both count as granting it
+            self.check_can_grant(role.server_id, actor_id, permissions ^ role.permissions)
+                .await?;
             role.permissions = permissions;
         }
 
@@ -528,7 +1031,9 @@ where
             .role_repo
             .update(&role)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
+            .map_err(RoleError::from)?;
+        invalidate_everyone_permissions(self.everyone_cache.as_ref(), updated.server_id).await;
+        self.invalidate_channel_permissions(updated.server_id).await;
 
         Ok(RoleDto::from(updated))
     }
@@ -538,7 +1043,7 @@ where
             .role_repo
             .find_by_id(role_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?
+            .map_err(RoleError::from)?
             .ok_or(RoleError::NotFound)?;
 
         // Cannot delete @everyone role
@@ -555,68 +1060,97 @@ where
         self.check_hierarchy(role.server_id, actor_id, role.position)
             .await?;
 
-        self.role_repo
-            .delete(role_id)
+        let cleanup = self
+            .role_repo
+            .delete_with_references(role_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
+            .map_err(RoleError::from)?;
+
+        self.invalidate_role_permissions(role.server_id, &cleanup)
+            .await;
 
         Ok(())
     }
 
-    async fn reorder_roles(
+    async fn clone_role(
         &self,
-        server_id: i64,
+        role_id: i64,
         actor_id: i64,
-        positions: Vec<RolePositionDto>,
-    ) -> Result<(), RoleError> {
+        new_name: String,
+    ) -> Result<RoleDto, RoleError> {
+        let source = self
+            .role_repo
+            .find_by_id(role_id)
+            .await
+            .map_err(RoleError::from)?
+            .ok_or(RoleError::NotFound)?;
+        let server_id = source.server_id;
+
         // Check permission
-        if !self.can_manage_roles(server_id, actor_id).await? {
+        let actor_permissions = self.actor_permissions(server_id, actor_id).await?;
+        if !(actor_permissions.has(Permissions::MANAGE_ROLES) || actor_permissions.is_admin()) {
             return Err(RoleError::Forbidden);
         }
 
+        Self::validate_name(&new_name)?;
+
+        let server_roles = self
+            .role_repo
+            .find_by_server_id(server_id)
+            .await
+            .map_err(RoleError::from)?;
+        let (position, shifted) = Self::clone_placement(&source, &server_roles);
+
         let actor_highest = self
             .get_actor_highest_role_position(server_id, actor_id)
             .await?;
+        Self::check_clone_allowed(&source, position, actor_highest, actor_permissions)?;
 
-        // Validate all positions and check hierarchy
-        for pos in &positions {
-            let role = self
-                .role_repo
-                .find_by_id(pos.id)
-                .await
-                .map_err(|e| RoleError::Internal(e.to_string()))?
-                .ok_or(RoleError::NotFound)?;
+        let now = Utc::now();
 
-            // Verify role belongs to the server
-            if role.server_id != server_id {
-                return Err(RoleError::NotFound);
-            }
+        let role = Role {
+            id: self.id_generator.generate(),
+            server_id,
+            name: new_name,
+            permissions: source.permissions,
+            position,
+            color: source.color,
+            hoist: source.hoist,
+            mentionable: source.mentionable,
+            created_at: now,
+            updated_at: now,
+        };
 
-            // Cannot reorder @everyone role
-            if Self::is_everyone_role(&role) {
-                return Err(RoleError::CannotModifyEveryoneRole);
-            }
+        let created = self
+            .role_repo
+            .create_with_positions(&role, shifted)
+            .await
+            .map_err(RoleError::from)?;
 
-            // Check hierarchy - cannot move roles at or above actor's position
-            if role.position >= actor_highest {
-                return Err(RoleError::HierarchyViolation);
-            }
+        Ok(RoleDto::from(created))
+    }
 
-            // Cannot move role to position at or above actor's position
-            if pos.position >= actor_highest {
-                return Err(RoleError::HierarchyViolation);
-            }
+    async fn reorder_roles(
+        &self,
+        server_id: i64,
+        actor_id: i64,
+        positions: Vec<RolePositionDto>,
+    ) -> Result<(), RoleError> {
+        // Check permission
+        if !self.can_manage_roles(server_id, actor_id).await? {
+            return Err(RoleError::Forbidden);
         }
 
-        let position_updates: Vec<(i64, i32)> =
-            positions.into_iter().map(|p| (p.id, p.position)).collect();
+        let actor_role_ids = self.actor_role_ids(server_id, actor_id).await?;
+        let requested: Vec<(i64, i32)> = positions.into_iter().map(|p| (p.id, p.position)).collect();
 
+        // The roles are read and moved in one transaction, so a role created
+        // meanwhile can't collide with the normalized positions
         self.role_repo
-            .update_positions(server_id, position_updates)
+            .reorder(server_id, |server_roles| {
+                Self::reorder_plan(server_roles, &requested, actor_role_ids.as_deref())
+            })
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
-
-        Ok(())
     }
 
     async fn assign_role_to_member(
@@ -636,7 +1170,7 @@ where
             .role_repo
             .find_by_id(role_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?
+            .map_err(RoleError::from)?
             .ok_or(RoleError::NotFound)?;
 
         if role.server_id != server_id {
@@ -657,7 +1191,7 @@ where
             .member_repo
             .is_member(server_id, user_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
+            .map_err(RoleError::from)?;
 
         if !is_member {
             return Err(RoleError::MemberNotFound);
@@ -667,7 +1201,8 @@ where
         self.member_repo
             .add_role(server_id, user_id, role_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
+            .map_err(RoleError::from)?;
+        self.invalidate_member_roles(server_id, user_id).await;
 
         Ok(())
     }
@@ -689,7 +1224,7 @@ where
             .role_repo
             .find_by_id(role_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?
+            .map_err(RoleError::from)?
             .ok_or(RoleError::NotFound)?;
 
         if role.server_id != server_id {
@@ -710,7 +1245,7 @@ where
             .member_repo
             .is_member(server_id, user_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
+            .map_err(RoleError::from)?;
 
         if !is_member {
             return Err(RoleError::MemberNotFound);
@@ -720,7 +1255,8 @@ where
         self.member_repo
             .remove_role(server_id, user_id, role_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
+            .map_err(RoleError::from)?;
+        self.invalidate_member_roles(server_id, user_id).await;
 
         Ok(())
     }
@@ -735,7 +1271,7 @@ where
             .member_repo
             .is_member(server_id, user_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
+            .map_err(RoleError::from)?;
 
         if !is_member {
             return Err(RoleError::MemberNotFound);
@@ -746,26 +1282,117 @@ where
             .member_repo
             .get_roles(server_id, user_id)
             .await
-            .map_err(|e| RoleError::Internal(e.to_string()))?;
+            .map_err(RoleError::from)?;
 
-        // Fetch full role objects
-        let mut roles = Vec::with_capacity(role_ids.len());
+        // Fetch full role objects, already sorted by position descending
+        let roles = self
+            .role_repo
+            .find_by_ids(&role_ids)
+            .await
+            .map_err(RoleError::from)?;
 
-        for role_id in role_ids {
-            if let Some(role) = self
-                .role_repo
-                .find_by_id(role_id)
-                .await
-                .map_err(|e| RoleError::Internal(e.to_string()))?
-            {
-                roles.push(RoleDto::from(role));
-            }
+        Ok(roles.into_iter().map(RoleDto::from).collect())
+    }
+
+    async fn get_role_members(
+        &self,
+        server_id: i64,
+        role_id: i64,
+        actor_id: i64,
+        after: Option<i64>,
+        limit: i32,
+    ) -> Result<Vec<MemberDto>, RoleError> {
+        let role = self
+            .role_repo
+            .find_by_id(role_id)
+            .await
+            .map_err(RoleError::from)?
+            .ok_or(RoleError::NotFound)?;
+
+        let members = role_members_page(
+            &*self.member_repo,
+            &role,
+            Self::is_everyone_role(&role),
+            server_id,
+            actor_id,
+            after,
+            limit,
+        )
+        .await?;
+
+        Ok(members.into_iter().map(MemberDto::from).collect())
+    }
+
+    async fn set_member_roles(
+        &self,
+        server_id: i64,
+        user_id: i64,
+        role_ids: Vec<i64>,
+        actor_id: i64,
+    ) -> Result<MemberRolesDto, RoleError> {
+        // Check permission
+        if !self.can_manage_roles(server_id, actor_id).await? {
+            return Err(RoleError::Forbidden);
         }
 
-        // Sort by position descending
-        roles.sort_by(|a, b| b.position.cmp(&a.position));
+        // Verify the user is a member of the server
+        let is_member = self
+            .member_repo
+            .is_member(server_id, user_id)
+            .await
+            .map_err(RoleError::from)?;
+
+        if !is_member {
+            return Err(RoleError::MemberNotFound);
+        }
+
+        let current = self
+            .member_repo
+            .get_roles(server_id, user_id)
+            .await
+            .map_err(RoleError::from)?;
+
+        let server_roles: HashMap<i64, Role> = self
+            .role_repo
+            .find_by_server_id(server_id)
+            .await
+            .map_err(RoleError::from)?
+            .into_iter()
+            .map(|role| (role.id, role))
+            .collect();
+
+        let diff = RoleDiff::new(&current, &role_ids);
+
+        if !diff.is_empty() {
+            let actor_highest = self
+                .get_actor_highest_role_position(server_id, actor_id)
+                .await?;
+
+            Self::validate_role_changes(server_id, &diff, &server_roles, actor_highest)?;
+
+            self.member_repo
+                .update_roles(server_id, user_id, &diff.added, &diff.removed)
+                .await
+                .map_err(RoleError::from)?;
+
+            self.invalidate_member_roles(server_id, user_id).await;
+        }
 
-        Ok(roles)
+        let mut role_ids = role_ids;
+        role_ids.sort_unstable();
+        role_ids.dedup();
+
+        let mut roles: Vec<RoleDto> = role_ids
+            .iter()
+            .filter_map(|id| server_roles.get(id).cloned())
+            .map(RoleDto::from)
+            .collect();
+        roles.sort_by_key(|r| std::cmp::Reverse(r.position));
+
+        Ok(MemberRolesDto {
+            roles,
+            changed: !diff.is_empty(),
+        })
     }
 }
 
@@ -901,4 +1528,396 @@ mod tests {
             crate::infrastructure::repositories::PgMemberRepository,
         >::is_everyone_role(&role));
     }
+
+    type PgRoleService = RoleServiceImpl<
+        crate::infrastructure::repositories::PgRoleRepository,
+        crate::infrastructure::repositories::PgServerRepository,
+        crate::infrastructure::repositories::PgMemberRepository,
+    >;
+
+    fn test_role(id: i64, position: i32) -> Role {
+        Role {
+            id,
+            server_id: 100,
+            name: format!("Role {}", id),
+            permissions: 0,
+            position,
+            color: None,
+            hoist: false,
+            mentionable: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_role_diff() {
+        let diff = RoleDiff::new(&[1, 2, 3], &[3, 4, 4, 5]);
+
+        assert_eq!(diff.added, vec![4, 5]);
+        assert_eq!(diff.removed, vec![1, 2]);
+        assert!(RoleDiff::new(&[2, 1], &[1, 2]).is_empty());
+    }
+
+    #[test]
+    fn test_role_changes_are_all_or_nothing() {
+        let roles: HashMap<i64, Role> = [test_role(1, 1), test_role(2, 2), test_role(9, 9)]
+            .into_iter()
+            .map(|role| (role.id, role))
+            .collect();
+
+        // Adding 1 and 2 is allowed, but 9 is above the actor's highest role
+        let diff = RoleDiff::new(&[], &[1, 2, 9]);
+        assert!(matches!(
+            PgRoleService::validate_role_changes(100, &diff, &roles, 5),
+            Err(RoleError::HierarchyViolation)
+        ));
+
+        // Removals are checked the same way
+        let diff = RoleDiff::new(&[9], &[1]);
+        assert!(matches!(
+            PgRoleService::validate_role_changes(100, &diff, &roles, 5),
+            Err(RoleError::HierarchyViolation)
+        ));
+
+        let diff = RoleDiff::new(&[], &[1, 42]);
+        assert!(matches!(
+            PgRoleService::validate_role_changes(100, &diff, &roles, 5),
+            Err(RoleError::NotFound)
+        ));
+
+        let diff = RoleDiff::new(&[9], &[9, 1, 2]);
+        assert!(PgRoleService::validate_role_changes(100, &diff, &roles, 5).is_ok());
+    }
+
+    #[test]
+    fn test_role_changes_reject_everyone_role() {
+        let roles: HashMap<i64, Role> = [(100, test_role(100, 0))].into_iter().collect();
+        let diff = RoleDiff::new(&[], &[100]);
+
+        assert!(matches!(
+            PgRoleService::validate_role_changes(100, &diff, &roles, i32::MAX),
+            Err(RoleError::CannotAssignEveryoneRole)
+        ));
+    }
+
+    #[test]
+    fn test_clone_is_placed_just_below_source() {
+        let roles = [test_role(100, 0), test_role(1, 1), test_role(2, 2), test_role(3, 3)];
+
+        let (position, shifted) = PgRoleService::clone_placement(&roles[2], &roles);
+        assert_eq!(position, 2);
+        assert_eq!(shifted, vec![(2, 3), (3, 4)]);
+
+        // Copies of @everyone go above it, below every other role
+        let (position, shifted) = PgRoleService::clone_placement(&roles[0], &roles);
+        assert_eq!(position, 1);
+        assert_eq!(shifted, vec![(1, 2), (2, 3), (3, 4)]);
+    }
+
+    #[test]
+    fn test_cannot_clone_role_at_or_above_own_highest() {
+        let manage = Permissions::new(Permissions::MANAGE_ROLES);
+
+        assert!(PgRoleService::check_clone_allowed(&test_role(1, 4), 4, 5, manage).is_ok());
+        assert!(matches!(
+            PgRoleService::check_clone_allowed(&test_role(1, 5), 5, 5, manage),
+            Err(RoleError::HierarchyViolation)
+        ));
+        assert!(matches!(
+            PgRoleService::check_clone_allowed(&test_role(1, 7), 7, 5, manage),
+            Err(RoleError::HierarchyViolation)
+        ));
+
+        // A member without roles can't even copy @everyone above themselves
+        assert!(matches!(
+            PgRoleService::check_clone_allowed(&test_role(100, 0), 1, 0, manage),
+            Err(RoleError::HierarchyViolation)
+        ));
+    }
+
+    #[test]
+    fn test_cannot_clone_permissions_the_actor_lacks() {
+        let mut source = test_role(1, 1);
+        source.permissions = Permissions::MANAGE_ROLES | Permissions::BAN_MEMBERS;
+
+        let manage = Permissions::new(Permissions::MANAGE_ROLES);
+        assert!(matches!(
+            PgRoleService::check_clone_allowed(&source, 1, 5, manage),
+            Err(RoleError::CannotGrantPermissions)
+        ));
+
+        let manage_and_ban = Permissions::new(Permissions::MANAGE_ROLES | Permissions::BAN_MEMBERS);
+        assert!(PgRoleService::check_clone_allowed(&source, 1, 5, manage_and_ban).is_ok());
+
+        // Administrators and owners may grant anything
+        let admin = Permissions::new(Permissions::ADMINISTRATOR);
+        assert!(PgRoleService::check_clone_allowed(&source, 1, 5, admin).is_ok());
+        assert!(PgRoleService::check_clone_allowed(&source, 1, 5, Permissions::all()).is_ok());
+    }
+
+    #[test]
+    fn test_reorder_normalizes_collisions_and_gaps() {
+        let roles = [test_role(1, 1), test_role(2, 4), test_role(3, 9)];
+
+        // Gaps close without changing the order
+        assert_eq!(PgRoleService::normalized_positions(&roles, &[]), vec![(1, 1), (2, 2), (3, 3)]);
+
+        // Moving down to an occupied position lands below the role there
+        assert_eq!(
+            PgRoleService::normalized_positions(&roles, &[(3, 1)]),
+            vec![(3, 1), (1, 2), (2, 3)]
+        );
+
+        // Moving up to an occupied position lands above it
+        assert_eq!(
+            PgRoleService::normalized_positions(&roles, &[(1, 9)]),
+            vec![(2, 1), (3, 2), (1, 3)]
+        );
+
+        // Colliding requests keep a strict order
+        let positions = PgRoleService::normalized_positions(&roles, &[(1, 5), (2, 5), (3, 5)]);
+        let mut numbers: Vec<i32> = positions.iter().map(|(_, position)| *position).collect();
+        numbers.sort_unstable();
+        assert_eq!(numbers, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_reorder_hierarchy_uses_final_positions() {
+        // The actor holds role 3; roles 1 and 2 sit below it in a gappy order
+        let roles = [test_role(1, 1), test_role(2, 2), test_role(3, 10), test_role(4, 20)];
+
+        let moved_below = PgRoleService::normalized_positions(&roles, &[(1, 5)]);
+        assert!(PgRoleService::check_reorder_hierarchy(&roles, &moved_below, &[1], &[3]).is_ok());
+
+        // Position 10 is below the actor's role before normalization, but
+        // the move lands above it
+        let moved_past = PgRoleService::normalized_positions(&roles, &[(1, 10)]);
+        assert!(matches!(
+            PgRoleService::check_reorder_hierarchy(&roles, &moved_past, &[1], &[3]),
+            Err(RoleError::HierarchyViolation)
+        ));
+
+        // Roles above the actor can't be moved at all
+        let moved_down = PgRoleService::normalized_positions(&roles, &[(4, 1)]);
+        assert!(matches!(
+            PgRoleService::check_reorder_hierarchy(&roles, &moved_down, &[4], &[3]),
+            Err(RoleError::HierarchyViolation)
+        ));
+    }
+
+    #[test]
+    fn test_non_member_cannot_read_role() {
+        assert!(matches!(
+            PgRoleService::check_role_visible(Some(test_role(1, 1)), 100, false),
+            Err(RoleError::Forbidden)
+        ));
+        // Whether or not the role exists
+        assert!(matches!(
+            PgRoleService::check_role_visible(None, 100, false),
+            Err(RoleError::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn test_member_reads_roles_of_their_server_only() {
+        let role = PgRoleService::check_role_visible(Some(test_role(1, 1)), 100, true).unwrap();
+        assert_eq!(role.id, 1);
+
+        // A role of another server looks missing
+        assert!(matches!(
+            PgRoleService::check_role_visible(Some(test_role(1, 1)), 200, true),
+            Err(RoleError::NotFound)
+        ));
+        assert!(matches!(
+            PgRoleService::check_role_visible(None, 100, true),
+            Err(RoleError::NotFound)
+        ));
+    }
+
+    #[cfg(feature = "db-tests")]
+    mod db {
+        use super::*;
+        use crate::infrastructure::repositories::{test_db, PgMemberRepository, PgRoleRepository};
+
+        /// A server with a role held by three of its members, one of whom
+        /// holds a second role too
+        struct Holders {
+            server: i64,
+            owner: i64,
+            role: Role,
+            other_role: i64,
+            holders: Vec<i64>,
+            holds_both: i64,
+        }
+
+        async fn holders(pool: &sqlx::PgPool) -> Holders {
+            let owner = test_db::user(pool).await;
+            let server = test_db::server(pool, owner).await;
+            let role_id = test_db::role(pool, server, 1).await;
+            let other_role = test_db::role(pool, server, 2).await;
+            let mut holders = Vec::new();
+            for _ in 0..3 {
+                let user = test_db::user(pool).await;
+                test_db::member(pool, server, user).await;
+                test_db::assign_role(pool, server, user, role_id).await;
+                holders.push(user);
+            }
+            let holds_both = holders[1];
+            test_db::assign_role(pool, server, holds_both, other_role).await;
+            // Members with only the other role are not listed
+            let bystander = test_db::user(pool).await;
+            test_db::member(pool, server, bystander).await;
+            test_db::assign_role(pool, server, bystander, other_role).await;
+            holders.sort_unstable();
+
+            let role = PgRoleRepository::new(pool.clone()).find_by_id(role_id).await.unwrap().unwrap();
+            Holders { server, owner, role, other_role, holders, holds_both }
+        }
+
+        fn user_ids(members: &[Member]) -> Vec<i64> {
+            members.iter().map(|m| m.user_id).collect()
+        }
+
+        #[tokio::test]
+        async fn test_role_members_are_holders_with_all_their_roles() {
+            let pool = test_db::pool().await;
+            let h = holders(&pool).await;
+            let repo = PgMemberRepository::new(pool.clone());
+
+            let members = role_members_page(&repo, &h.role, false, h.server, h.owner, None, 100).await.unwrap();
+
+            assert_eq!(user_ids(&members), h.holders);
+            let both = members.iter().find(|m| m.user_id == h.holds_both).unwrap();
+            let mut roles = both.roles.clone();
+            roles.sort_unstable();
+            let mut expected = vec![h.role.id, h.other_role];
+            expected.sort_unstable();
+            assert_eq!(roles, expected);
+
+            // @everyone lists every member of the server
+            let everyone = PgRoleRepository::new(pool).find_everyone_role(h.server).await.unwrap().unwrap();
+            let members = role_members_page(&repo, &everyone, true, h.server, h.owner, None, 100).await.unwrap();
+            assert_eq!(members.len(), 5);
+        }
+
+        #[tokio::test]
+        async fn test_role_members_paginate_by_user_id() {
+            let pool = test_db::pool().await;
+            let h = holders(&pool).await;
+            let repo = PgMemberRepository::new(pool);
+
+            let first = role_members_page(&repo, &h.role, false, h.server, h.owner, None, 2).await.unwrap();
+            let after = first.last().map(|m| m.user_id);
+            let second = role_members_page(&repo, &h.role, false, h.server, h.owner, after, 2).await.unwrap();
+
+            assert_eq!(user_ids(&first), h.holders[..2]);
+            assert_eq!(user_ids(&second), h.holders[2..]);
+
+            // Non-positive limits still return a page
+            let clamped = role_members_page(&repo, &h.role, false, h.server, h.owner, None, 0).await.unwrap();
+            assert_eq!(clamped.len(), 1);
+        }
+
+        #[tokio::test]
+        async fn test_role_members_require_matching_server_and_membership() {
+            let pool = test_db::pool().await;
+            let h = holders(&pool).await;
+            let other_owner = test_db::user(&pool).await;
+            let other_server = test_db::server(&pool, other_owner).await;
+            let repo = PgMemberRepository::new(pool.clone());
+
+            let wrong_server = role_members_page(&repo, &h.role, false, other_server, other_owner, None, 100).await;
+            assert!(matches!(wrong_server, Err(RoleError::NotFound)));
+
+            let outsider = role_members_page(&repo, &h.role, false, h.server, other_owner, None, 100).await;
+            assert!(matches!(outsider, Err(RoleError::Forbidden)));
+        }
+
+        #[tokio::test]
+        async fn test_everyone_permissions_are_cached_until_a_role_update() {
+            use crate::infrastructure::cache::InMemoryCache;
+            use crate::infrastructure::repositories::PgServerRepository;
+
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let member = test_db::user(&pool).await;
+            test_db::member(&pool, server, member).await;
+            test_db::set_permissions(&pool, server, Permissions::VIEW_CHANNEL).await;
+            let service = RoleServiceImpl::new(
+                Arc::new(PgRoleRepository::new(pool.clone())),
+                Arc::new(PgServerRepository::new(pool.clone())),
+                Arc::new(PgMemberRepository::new(pool.clone())),
+                Arc::new(SnowflakeGenerator::new(1, 1)),
+            )
+            .with_everyone_cache(EveryonePermissionsCache::with_cache(InMemoryCache::new()));
+            let create = || {
+                let request = CreateRoleDto {
+                    name: "helper".to_string(),
+                    permissions: None,
+                    color: None,
+                    hoist: None,
+                    mentionable: None,
+                };
+                service.create_role(server, member, request)
+            };
+            let manage_roles = Permissions::VIEW_CHANNEL | Permissions::MANAGE_ROLES;
+
+            assert!(matches!(create().await, Err(RoleError::Forbidden)));
+
+            // A change made behind the service's back is served from the cache
+            test_db::set_permissions(&pool, server, manage_roles).await;
+            assert!(matches!(create().await, Err(RoleError::Forbidden)));
+
+            // Updating @everyone through the service takes effect at once
+            let update = UpdateRoleDto {
+                permissions: Some(manage_roles),
+                ..Default::default()
+            };
+            service.update_role(server, owner, update).await.unwrap();
+            create().await.unwrap();
+        }
+
+        #[tokio::test]
+        async fn test_reorder_normalizes_the_stored_positions() {
+            use crate::infrastructure::repositories::PgServerRepository;
+
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let (low, tied, high) = (
+                test_db::role(&pool, server, 1).await,
+                test_db::role(&pool, server, 1).await,
+                test_db::role(&pool, server, 7).await,
+            );
+            let service: PgRoleService = RoleServiceImpl::new(
+                Arc::new(PgRoleRepository::new(pool.clone())),
+                Arc::new(PgServerRepository::new(pool.clone())),
+                Arc::new(PgMemberRepository::new(pool.clone())),
+                Arc::new(SnowflakeGenerator::new(1, 1)),
+            );
+            let positions = || {
+                sqlx::query_as::<_, (i64, i32)>(
+                    "SELECT id, position FROM roles WHERE server_id = $1 AND id <> $1 ORDER BY position",
+                )
+                .bind(server)
+                .fetch_all(&pool)
+            };
+
+            // Moving the top role to the bottom renumbers every role 1..N
+            let request = vec![RolePositionDto { id: high, position: 1 }];
+            service.reorder_roles(server, owner, request).await.unwrap();
+            let (first, second) = (low.min(tied), low.max(tied));
+            assert_eq!(positions().await.unwrap(), vec![(high, 1), (first, 2), (second, 3)]);
+
+            let everyone = vec![RolePositionDto { id: server, position: 3 }];
+            let result = service.reorder_roles(server, owner, everyone).await;
+            assert!(matches!(result, Err(RoleError::CannotModifyEveryoneRole)));
+            let unknown = vec![RolePositionDto { id: test_db::id(), position: 1 }];
+            let result = service.reorder_roles(server, owner, unknown).await;
+            assert!(matches!(result, Err(RoleError::NotFound)));
+            assert_eq!(positions().await.unwrap(), vec![(high, 1), (first, 2), (second, 3)]);
+        }
+    }
 }
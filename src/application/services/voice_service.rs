@@ -0,0 +1,623 @@
+//! Voice Service
+//!
+//! Handles voice channel signaling: joining and leaving voice channels,
+//! self mute/deafen, and moderator actions (move, server mute/deafen).
+//! No media is routed through the server.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::permission_resolver;
+use crate::domain::value_objects::Permissions;
+use crate::domain::{
+    Channel, ChannelRepository, ChannelType, MemberRepository, RoleRepository, ServerRepository,
+    VoiceState,
+};
+use crate::infrastructure::cache::{Cache, RedisCache, VoiceStateCacheService};
+use crate::shared::error::AppError;
+
+/// Voice service trait defining voice state operations.
+#[async_trait]
+pub trait VoiceService: Send + Sync {
+    /// Update the caller's own voice state (join, leave, switch channel,
+    /// self mute/deafen).
+    ///
+    /// Joining or switching channel requires `CONNECT` in the target channel;
+    /// without `SPEAK` there the user is suppressed.
+    async fn update_own_state(
+        &self,
+        user_id: i64,
+        session_id: &str,
+        request: UpdateVoiceStateDto,
+    ) -> Result<VoiceStateDto, VoiceError>;
+
+    /// Moderate another member's voice state.
+    ///
+    /// Moving requires `MOVE_MEMBERS`, server mute requires `MUTE_MEMBERS`
+    /// and server deafen requires `DEAFEN_MEMBERS`. A moved member is
+    /// suppressed if they lack `SPEAK` in the new channel.
+    async fn modify_member_state(
+        &self,
+        server_id: i64,
+        actor_id: i64,
+        target_id: i64,
+        request: ModifyVoiceStateDto,
+    ) -> Result<VoiceStateDto, VoiceError>;
+
+    /// Clear voice states owned by a gateway session (e.g. on disconnect).
+    ///
+    /// Returns the cleared states, now disconnected.
+    async fn disconnect_session(
+        &self,
+        user_id: i64,
+        session_id: &str,
+        server_ids: &[i64],
+    ) -> Result<Vec<VoiceStateDto>, VoiceError>;
+}
+
+// =============================================================================
+// Data Transfer Objects
+// =============================================================================
+
+/// Update own voice state request DTO.
+#[derive(Debug, Clone)]
+pub struct UpdateVoiceStateDto {
+    /// Server the voice channel belongs to.
+    pub server_id: i64,
+    /// Voice channel to connect to (None to disconnect).
+    pub channel_id: Option<i64>,
+    /// Whether the user is muting themselves.
+    pub self_mute: bool,
+    /// Whether the user is deafening themselves.
+    pub self_deaf: bool,
+}
+
+/// Moderator voice state change DTO.
+#[derive(Debug, Clone, Default)]
+pub struct ModifyVoiceStateDto {
+    /// Voice channel to move the member to.
+    pub channel_id: Option<i64>,
+    /// New server mute setting.
+    pub mute: Option<bool>,
+    /// New server deafen setting.
+    pub deaf: Option<bool>,
+}
+
+/// Voice state data transfer object.
+#[derive(Debug, Clone)]
+pub struct VoiceStateDto {
+    /// User ID.
+    pub user_id: String,
+    /// Server ID.
+    pub server_id: String,
+    /// Connected voice channel (None when disconnected).
+    pub channel_id: Option<String>,
+    /// Gateway session owning the voice connection.
+    pub session_id: String,
+    /// Muted by the user.
+    pub self_mute: bool,
+    /// Deafened by the user.
+    pub self_deaf: bool,
+    /// Muted by a moderator.
+    pub mute: bool,
+    /// Deafened by a moderator.
+    pub deaf: bool,
+    /// Kept from speaking for lacking `SPEAK` in the channel.
+    pub suppress: bool,
+}
+
+impl From<VoiceState> for VoiceStateDto {
+    fn from(state: VoiceState) -> Self {
+        Self {
+            user_id: state.user_id.to_string(),
+            server_id: state.server_id.to_string(),
+            channel_id: state.channel_id.map(|id| id.to_string()),
+            session_id: state.session_id,
+            self_mute: state.self_mute,
+            self_deaf: state.self_deaf,
+            mute: state.muted,
+            deaf: state.deafened,
+            suppress: state.suppress,
+        }
+    }
+}
+
+// =============================================================================
+// Error Types
+// =============================================================================
+
+/// Voice service errors.
+#[derive(Debug, thiserror::Error)]
+pub enum VoiceError {
+    #[error("Server not found")]
+    ServerNotFound,
+
+    #[error("Channel not found")]
+    ChannelNotFound,
+
+    #[error("Channel is not a voice channel in this server")]
+    NotVoiceChannel,
+
+    #[error("Member not found")]
+    MemberNotFound,
+
+    #[error("Member is not connected to voice")]
+    NotConnected,
+
+    #[error("Permission denied")]
+    Forbidden,
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+
+    /// No database connection was free in time; the request may succeed on retry
+    #[error("Service unavailable")]
+    Unavailable,
+}
+
+impl From<AppError> for VoiceError {
+    fn from(e: AppError) -> Self {
+        if e.is_unavailable() {
+            VoiceError::Unavailable
+        } else {
+            VoiceError::Internal(e.to_string())
+        }
+    }
+}
+
+// =============================================================================
+// Permission Checks
+// =============================================================================
+
+/// Check that a user may join a voice channel.
+fn check_join(channel_permissions: i64) -> Result<(), VoiceError> {
+    if !Permissions::new(channel_permissions).has(Permissions::CONNECT) {
+        return Err(VoiceError::Forbidden);
+    }
+    Ok(())
+}
+
+/// Whether a user in a voice channel is kept from speaking there.
+fn is_suppressed(channel_permissions: i64) -> bool {
+    !Permissions::new(channel_permissions).has(Permissions::SPEAK)
+}
+
+/// Permissions a moderator needs in the member's current channel.
+fn required_moderation_permissions(request: &ModifyVoiceStateDto) -> i64 {
+    let mut required = 0;
+    if request.channel_id.is_some() {
+        required |= Permissions::MOVE_MEMBERS;
+    }
+    if request.mute.is_some() {
+        required |= Permissions::MUTE_MEMBERS;
+    }
+    if request.deaf.is_some() {
+        required |= Permissions::DEAFEN_MEMBERS;
+    }
+    required
+}
+
+/// Check that a moderator may apply a voice state change.
+///
+/// `target_channel_permissions` are the moderator's permissions in the
+/// destination channel when moving; moving also requires `MOVE_MEMBERS` there.
+fn check_moderation(
+    request: &ModifyVoiceStateDto,
+    current_channel_permissions: i64,
+    target_channel_permissions: Option<i64>,
+) -> Result<(), VoiceError> {
+    let required = required_moderation_permissions(request);
+    if !Permissions::new(current_channel_permissions).has(required) {
+        return Err(VoiceError::Forbidden);
+    }
+
+    if let Some(permissions) = target_channel_permissions {
+        if !Permissions::new(permissions).has(Permissions::MOVE_MEMBERS) {
+            return Err(VoiceError::Forbidden);
+        }
+    }
+
+    Ok(())
+}
+
+// =============================================================================
+// Service Implementation
+// =============================================================================
+
+/// VoiceService implementation with PostgreSQL repositories and Redis state.
+///
+/// `V` is the cache backing the voice state cache.
+pub struct VoiceServiceImpl<S, C, M, R, V = RedisCache>
+where
+    S: ServerRepository,
+    C: ChannelRepository,
+    M: MemberRepository,
+    R: RoleRepository,
+    V: Cache,
+{
+    server_repo: Arc<S>,
+    channel_repo: Arc<C>,
+    member_repo: Arc<M>,
+    role_repo: Arc<R>,
+    voice_cache: VoiceStateCacheService<V>,
+}
+
+impl<S, C, M, R, V> VoiceServiceImpl<S, C, M, R, V>
+where
+    S: ServerRepository,
+    C: ChannelRepository,
+    M: MemberRepository,
+    R: RoleRepository,
+    V: Cache,
+{
+    /// Create a new VoiceServiceImpl.
+    pub fn new(
+        server_repo: Arc<S>,
+        channel_repo: Arc<C>,
+        member_repo: Arc<M>,
+        role_repo: Arc<R>,
+        voice_cache: VoiceStateCacheService<V>,
+    ) -> Self {
+        Self {
+            server_repo,
+            channel_repo,
+            member_repo,
+            role_repo,
+            voice_cache,
+        }
+    }
+
+    /// Load a voice channel, ensuring it belongs to the server.
+    async fn find_voice_channel(
+        &self,
+        server_id: i64,
+        channel_id: i64,
+    ) -> Result<Channel, VoiceError> {
+        let channel = self
+            .channel_repo
+            .find_by_id(channel_id)
+            .await
+            .map_err(VoiceError::from)?
+            .ok_or(VoiceError::ChannelNotFound)?;
+
+        if channel.channel_type != ChannelType::Voice || channel.server_id != Some(server_id) {
+            return Err(VoiceError::NotVoiceChannel);
+        }
+
+        Ok(channel)
+    }
+
+    /// Compute a member's effective permissions in a channel.
+    async fn channel_permissions(
+        &self,
+        user_id: i64,
+        channel: &Channel,
+    ) -> Result<i64, VoiceError> {
+        permission_resolver::channel_permissions(
+            self.server_repo.as_ref(),
+            self.member_repo.as_ref(),
+            self.role_repo.as_ref(),
+            self.channel_repo.as_ref(),
+            channel,
+            user_id,
+        )
+        .await
+        .map_err(VoiceError::from)?
+        .ok_or(VoiceError::MemberNotFound)
+    }
+
+    /// Load a member's voice state in a server.
+    async fn load_state(
+        &self,
+        server_id: i64,
+        user_id: i64,
+    ) -> Result<Option<VoiceState>, VoiceError> {
+        self.voice_cache
+            .get_voice_state(server_id, user_id)
+            .await
+            .map_err(VoiceError::from)
+    }
+}
+
+#[async_trait]
+impl<S, C, M, R, V> VoiceService for VoiceServiceImpl<S, C, M, R, V>
+where
+    S: ServerRepository + 'static,
+    C: ChannelRepository + 'static,
+    M: MemberRepository + 'static,
+    R: RoleRepository + 'static,
+    V: Cache + 'static,
+{
+    async fn update_own_state(
+        &self,
+        user_id: i64,
+        session_id: &str,
+        request: UpdateVoiceStateDto,
+    ) -> Result<VoiceStateDto, VoiceError> {
+        let server_id = request.server_id;
+        let mut state = self
+            .load_state(server_id, user_id)
+            .await?
+            .unwrap_or_else(|| VoiceState::new(user_id, server_id, session_id.to_string()));
+
+        let Some(channel_id) = request.channel_id else {
+            // Leaving voice
+            self.voice_cache
+                .delete_voice_state(server_id, user_id)
+                .await
+                .map_err(VoiceError::from)?;
+
+            state.channel_id = None;
+            return Ok(VoiceStateDto::from(state));
+        };
+
+        // Only re-check CONNECT when the channel changes
+        if state.channel_id != Some(channel_id) {
+            let channel = self.find_voice_channel(server_id, channel_id).await?;
+            let permissions = self.channel_permissions(user_id, &channel).await?;
+            check_join(permissions)?;
+            state.suppress = is_suppressed(permissions);
+        }
+
+        state.channel_id = Some(channel_id);
+        state.session_id = session_id.to_string();
+        state.self_mute = request.self_mute;
+        state.self_deaf = request.self_deaf;
+
+        self.voice_cache
+            .set_voice_state(&state)
+            .await
+            .map_err(VoiceError::from)?;
+
+        Ok(VoiceStateDto::from(state))
+    }
+
+    async fn modify_member_state(
+        &self,
+        server_id: i64,
+        actor_id: i64,
+        target_id: i64,
+        request: ModifyVoiceStateDto,
+    ) -> Result<VoiceStateDto, VoiceError> {
+        let mut state = self
+            .load_state(server_id, target_id)
+            .await?
+            .ok_or(VoiceError::NotConnected)?;
+        let current_channel_id = state.channel_id.ok_or(VoiceError::NotConnected)?;
+
+        let current_channel = self.find_voice_channel(server_id, current_channel_id).await?;
+        let current_permissions = self.channel_permissions(actor_id, &current_channel).await?;
+
+        let target_channel = match request.channel_id {
+            Some(channel_id) if channel_id != current_channel_id => {
+                Some(self.find_voice_channel(server_id, channel_id).await?)
+            }
+            _ => None,
+        };
+        let target_permissions = match &target_channel {
+            Some(channel) => Some(self.channel_permissions(actor_id, channel).await?),
+            None => None,
+        };
+
+        check_moderation(&request, current_permissions, target_permissions)?;
+
+        // A moved member may speak in the new channel only with SPEAK there
+        if let Some(channel) = &target_channel {
+            state.suppress = is_suppressed(self.channel_permissions(target_id, channel).await?);
+        }
+
+        if let Some(channel_id) = request.channel_id {
+            state.channel_id = Some(channel_id);
+        }
+        if let Some(mute) = request.mute {
+            state.muted = mute;
+        }
+        if let Some(deaf) = request.deaf {
+            state.deafened = deaf;
+        }
+
+        self.voice_cache
+            .set_voice_state(&state)
+            .await
+            .map_err(VoiceError::from)?;
+
+        Ok(VoiceStateDto::from(state))
+    }
+
+    async fn disconnect_session(
+        &self,
+        user_id: i64,
+        session_id: &str,
+        server_ids: &[i64],
+    ) -> Result<Vec<VoiceStateDto>, VoiceError> {
+        let mut cleared = Vec::new();
+
+        for &server_id in server_ids {
+            let Some(mut state) = self.load_state(server_id, user_id).await? else {
+                continue;
+            };
+            // Another session of the same user may own the connection
+            if state.session_id != session_id {
+                continue;
+            }
+
+            self.voice_cache
+                .delete_voice_state(server_id, user_id)
+                .await
+                .map_err(VoiceError::from)?;
+
+            state.channel_id = None;
+            cleared.push(VoiceStateDto::from(state));
+        }
+
+        Ok(cleared)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_requires_connect() {
+        assert!(check_join(Permissions::CONNECT).is_ok());
+        assert!(check_join(Permissions::ADMINISTRATOR).is_ok());
+        assert!(matches!(
+            check_join(Permissions::SPEAK | Permissions::VIEW_CHANNEL),
+            Err(VoiceError::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn test_server_mute_requires_mute_members() {
+        let request = ModifyVoiceStateDto {
+            mute: Some(true),
+            ..Default::default()
+        };
+
+        assert!(check_moderation(&request, Permissions::MUTE_MEMBERS, None).is_ok());
+        assert!(matches!(
+            check_moderation(&request, Permissions::DEAFEN_MEMBERS, None),
+            Err(VoiceError::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn test_move_requires_move_members_in_both_channels() {
+        let request = ModifyVoiceStateDto {
+            channel_id: Some(2),
+            ..Default::default()
+        };
+
+        assert!(check_moderation(
+            &request,
+            Permissions::MOVE_MEMBERS,
+            Some(Permissions::MOVE_MEMBERS)
+        )
+        .is_ok());
+        assert!(check_moderation(&request, Permissions::MUTE_MEMBERS, None).is_err());
+        assert!(check_moderation(&request, Permissions::MOVE_MEMBERS, Some(Permissions::CONNECT))
+            .is_err());
+    }
+
+    #[test]
+    fn test_combined_moderation_requires_all_permissions() {
+        let request = ModifyVoiceStateDto {
+            channel_id: None,
+            mute: Some(true),
+            deaf: Some(true),
+        };
+
+        assert_eq!(
+            required_moderation_permissions(&request),
+            Permissions::MUTE_MEMBERS | Permissions::DEAFEN_MEMBERS
+        );
+        assert!(check_moderation(&request, Permissions::MUTE_MEMBERS, None).is_err());
+    }
+
+    #[cfg(feature = "db-tests")]
+    mod db {
+        use super::*;
+        use crate::infrastructure::cache::InMemoryCache;
+        use crate::infrastructure::repositories::{
+            test_db, PgChannelRepository, PgMemberRepository, PgRoleRepository, PgServerRepository,
+        };
+
+        type PgVoiceService = VoiceServiceImpl<
+            PgServerRepository,
+            PgChannelRepository,
+            PgMemberRepository,
+            PgRoleRepository,
+            InMemoryCache,
+        >;
+
+        fn service(pool: &sqlx::PgPool) -> PgVoiceService {
+            VoiceServiceImpl::new(
+                Arc::new(PgServerRepository::new(pool.clone())),
+                Arc::new(PgChannelRepository::new(pool.clone())),
+                Arc::new(PgMemberRepository::new(pool.clone())),
+                Arc::new(PgRoleRepository::new(pool.clone())),
+                VoiceStateCacheService::with_cache(InMemoryCache::new()),
+            )
+        }
+
+        async fn voice_channel(pool: &sqlx::PgPool, server_id: i64) -> i64 {
+            let channel = test_db::channel(pool, server_id).await;
+            sqlx::query("UPDATE channels SET type = 'voice' WHERE id = $1")
+                .bind(channel)
+                .execute(pool)
+                .await
+                .unwrap();
+            channel
+        }
+
+        fn join(server_id: i64, channel_id: i64) -> UpdateVoiceStateDto {
+            UpdateVoiceStateDto {
+                server_id,
+                channel_id: Some(channel_id),
+                self_mute: false,
+                self_deaf: false,
+            }
+        }
+
+        #[tokio::test]
+        async fn test_joining_needs_connect_and_speaking_needs_speak() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let user = test_db::user(&pool).await;
+            test_db::member(&pool, server, user).await;
+            // Granted only through @everyone
+            let everyone = Permissions::VIEW_CHANNEL | Permissions::CONNECT | Permissions::SPEAK;
+            test_db::set_permissions(&pool, server, everyone).await;
+            let (open, listen_only, locked) = (
+                voice_channel(&pool, server).await,
+                voice_channel(&pool, server).await,
+                voice_channel(&pool, server).await,
+            );
+            test_db::overwrite(&pool, listen_only, "role", server, 0, Permissions::SPEAK).await;
+            test_db::overwrite(&pool, locked, "role", server, 0, Permissions::CONNECT).await;
+            let service = service(&pool);
+
+            let state = service.update_own_state(user, "s1", join(server, open)).await.unwrap();
+            assert_eq!(state.channel_id, Some(open.to_string()));
+            assert!(!state.suppress);
+
+            let state = service.update_own_state(user, "s1", join(server, listen_only)).await.unwrap();
+            assert_eq!(state.channel_id, Some(listen_only.to_string()));
+            assert!(state.suppress);
+
+            let result = service.update_own_state(user, "s1", join(server, locked)).await;
+            assert!(matches!(result, Err(VoiceError::Forbidden)));
+        }
+
+        #[tokio::test]
+        async fn test_server_mute_needs_mute_members() {
+            let pool = test_db::pool().await;
+            let owner = test_db::user(&pool).await;
+            let server = test_db::server(&pool, owner).await;
+            let (user, moderator) = (test_db::user(&pool).await, test_db::user(&pool).await);
+            for member in [user, moderator] {
+                test_db::member(&pool, server, member).await;
+            }
+            test_db::set_permissions(&pool, server, Permissions::VIEW_CHANNEL | Permissions::CONNECT).await;
+            let channel = voice_channel(&pool, server).await;
+            let service = service(&pool);
+            service.update_own_state(user, "s1", join(server, channel)).await.unwrap();
+            let mute = || ModifyVoiceStateDto {
+                mute: Some(true),
+                ..Default::default()
+            };
+
+            let result = service.modify_member_state(server, moderator, user, mute()).await;
+            assert!(matches!(result, Err(VoiceError::Forbidden)));
+
+            let role = test_db::role(&pool, server, 1).await;
+            test_db::set_permissions(&pool, role, Permissions::MUTE_MEMBERS).await;
+            test_db::assign_role(&pool, server, moderator, role).await;
+            let state = service.modify_member_state(server, moderator, user, mute()).await.unwrap();
+            assert!(state.mute);
+        }
+    }
+}
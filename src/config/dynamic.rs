@@ -0,0 +1,144 @@
+//! Hot-reloadable subset of the application settings.
+//!
+//! Only values that can change safely while the server is running live here.
+//! Connection pools, bind addresses and secrets stay fixed until restart.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+
+use super::settings::{LogSettings, RateLimitSettings, Settings};
+
+/// Settings that may be swapped at runtime (e.g., on `SIGHUP`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicSettings {
+    /// Global rate limiting values
+    pub rate_limit: RateLimitSettings,
+
+    /// Logging configuration
+    pub log: LogSettings,
+
+    /// Feature flags keyed by name
+    pub features: HashMap<String, bool>,
+}
+
+/// Shared handle to the current dynamic settings.
+pub type SharedDynamicSettings = Arc<ArcSwap<DynamicSettings>>;
+
+impl DynamicSettings {
+    /// Extract the hot-reloadable subset from full settings.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            rate_limit: settings.rate_limit.clone(),
+            log: settings.log.clone(),
+            features: settings.features.clone(),
+        }
+    }
+
+    /// Wrap these settings in a shared, atomically swappable handle.
+    pub fn into_shared(self) -> SharedDynamicSettings {
+        Arc::new(ArcSwap::from_pointee(self))
+    }
+
+    /// Describe the values that differ between `self` and `other`.
+    ///
+    /// Each entry has the form `"key: old -> new"`.
+    pub fn changes(&self, other: &Self) -> Vec<String> {
+        let mut changes = Vec::new();
+
+        if self.rate_limit.requests_per_second != other.rate_limit.requests_per_second {
+            changes.push(format!(
+                "rate_limit.requests_per_second: {} -> {}",
+                self.rate_limit.requests_per_second, other.rate_limit.requests_per_second
+            ));
+        }
+        if self.rate_limit.burst_size != other.rate_limit.burst_size {
+            changes.push(format!(
+                "rate_limit.burst_size: {} -> {}",
+                self.rate_limit.burst_size, other.rate_limit.burst_size
+            ));
+        }
+        if self.rate_limit.mention_cooldown_secs != other.rate_limit.mention_cooldown_secs {
+            changes.push(format!(
+                "rate_limit.mention_cooldown_secs: {} -> {}",
+                self.rate_limit.mention_cooldown_secs, other.rate_limit.mention_cooldown_secs
+            ));
+        }
+        if self.log.filter != other.log.filter {
+            changes.push(format!("log.filter: {} -> {}", self.log.filter, other.log.filter));
+        }
+
+        let mut names: Vec<&String> = self.features.keys().chain(other.features.keys()).collect();
+        names.sort();
+        names.dedup();
+        // Unknown flags are off
+        let enabled = |features: &HashMap<String, bool>, name: &str| {
+            features.get(name).copied().unwrap_or(false)
+        };
+        for name in names {
+            let old = enabled(&self.features, name);
+            let new = enabled(&other.features, name);
+            if old != new {
+                changes.push(format!("features.{}: {} -> {}", name, old, new));
+            }
+        }
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dynamic(requests_per_second: f64, burst_size: u32) -> DynamicSettings {
+        DynamicSettings {
+            rate_limit: RateLimitSettings {
+                requests_per_second,
+                burst_size,
+                mention_cooldown_secs: 5,
+            },
+            log: LogSettings {
+                filter: "info".to_string(),
+                format: None,
+            },
+            features: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_changes_empty_when_equal() {
+        let settings = dynamic(10.0, 30);
+        assert!(settings.changes(&settings.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_changes_lists_rate_limit_and_features() {
+        let old = dynamic(10.0, 30);
+        let mut new = dynamic(20.0, 30);
+        new.features.insert("voice".to_string(), true);
+
+        let changes = old.changes(&new);
+
+        assert_eq!(
+            changes,
+            vec![
+                "rate_limit.requests_per_second: 10 -> 20".to_string(),
+                "features.voice: false -> true".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_swap_is_visible_to_readers() {
+        let shared = dynamic(10.0, 30).into_shared();
+        let reader = shared.clone();
+
+        shared.store(Arc::new(dynamic(5.0, 2)));
+
+        let current = reader.load();
+        assert_eq!(current.rate_limit.requests_per_second, 5.0);
+        assert_eq!(current.rate_limit.burst_size, 2);
+    }
+}
@@ -6,6 +6,12 @@
 //! - Configuration files (config/default.toml, config/{environment}.toml)
 //! - .env files (via dotenvy)
 //!
+//! [`Settings::from_env_only`] skips the files and reads environment
+//! variables alone, for containerized deployments.
+//!
+//! A subset of the settings ([`DynamicSettings`]) can be reloaded at runtime
+//! by sending `SIGHUP` to the process.
+//!
 //! ## Usage
 //!
 //! ```rust,ignore
@@ -15,6 +21,8 @@
 //! println!("Server will listen on {}:{}", settings.server.host, settings.server.port);
 //! ```
 
+mod dynamic;
 mod settings;
 
+pub use dynamic::*;
 pub use settings::*;
@@ -1,8 +1,14 @@
 //! Application settings and configuration structures.
 
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use config::builder::{ConfigBuilder, DefaultState};
 use config::{Config, ConfigError, Environment, File};
 use serde::Deserialize;
 
+use crate::shared::snowflake::MAX_WORKER_ID;
+
 /// Root configuration structure containing all application settings.
 #[derive(Debug, Clone, Deserialize)]
 pub struct Settings {
@@ -30,6 +36,25 @@ pub struct Settings {
     /// WebSocket configuration
     pub websocket: WebSocketSettings,
 
+    /// Logging configuration
+    pub log: LogSettings,
+
+    /// Administrative access configuration
+    #[serde(default)]
+    pub admin: AdminSettings,
+
+    /// Message write batching configuration
+    #[serde(default)]
+    pub message_batch: MessageBatchSettings,
+
+    /// Attachment file storage configuration
+    #[serde(default)]
+    pub storage: StorageSettings,
+
+    /// Feature flags keyed by name
+    #[serde(default)]
+    pub features: HashMap<String, bool>,
+
     /// Current environment (development, staging, production)
     pub environment: String,
 }
@@ -42,6 +67,15 @@ pub struct ServerSettings {
 
     /// Port number to listen on
     pub port: u16,
+
+    /// Largest request body accepted by JSON endpoints, in bytes.
+    /// Attachment uploads have their own, larger limit.
+    pub max_body_bytes: usize,
+
+    /// Reverse proxies whose `X-Forwarded-For` and `X-Real-IP` headers are
+    /// trusted to carry the client address. Empty means none are.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
 }
 
 /// PostgreSQL database configuration.
@@ -58,6 +92,9 @@ pub struct DatabaseSettings {
 
     /// Connection acquire timeout in seconds
     pub acquire_timeout: u64,
+
+    /// Longest a single statement may run, in seconds (0 = no limit)
+    pub statement_timeout: u64,
 }
 
 /// Redis configuration.
@@ -66,7 +103,7 @@ pub struct RedisSettings {
     /// Redis connection URL
     pub url: String,
 
-    /// Connection pool size
+    /// Number of multiplexed connections commands are spread over
     pub pool_size: u32,
 }
 
@@ -76,31 +113,102 @@ pub struct JwtSettings {
     /// Secret key for signing tokens
     pub secret: String,
 
+    /// Signing keys by key ID, for rotating the secret. Tokens name the key
+    /// that signed them in their `kid` header, and a key keeps verifying
+    /// tokens until it is removed from here.
+    #[serde(default)]
+    pub keys: HashMap<String, String>,
+
+    /// ID of the key in `keys` that signs new tokens; without one, tokens
+    /// are signed with `secret` and carry no `kid`
+    #[serde(default)]
+    pub active_key_id: Option<String>,
+
     /// Access token expiry in minutes
     pub access_token_expiry_minutes: i64,
 
-    /// Refresh token expiry in days
+    /// Refresh token expiry in days for "remember me" logins
     pub refresh_token_expiry_days: i64,
+
+    /// Refresh token expiry in hours for logins that aren't remembered
+    pub session_refresh_token_expiry_hours: i64,
+}
+
+impl JwtSettings {
+    /// Key ID and secret that new tokens are signed with.
+    pub fn signing_key(&self) -> (Option<&str>, &str) {
+        match self.active_key_id.as_deref().and_then(|kid| self.keys.get_key_value(kid)) {
+            Some((kid, secret)) => (Some(kid.as_str()), secret.as_str()),
+            None => (None, self.secret.as_str()),
+        }
+    }
+
+    /// Secret that verifies a token signed by key `kid`.
+    ///
+    /// Tokens without a `kid` were signed with `secret`. A key ID that is no
+    /// longer configured has no secret, so its tokens are rejected.
+    pub fn verification_secret(&self, kid: Option<&str>) -> Option<&str> {
+        match kid {
+            Some(kid) => self.keys.get(kid).map(String::as_str),
+            None => Some(self.secret.as_str()),
+        }
+    }
+
+    /// Access token lifetime in seconds.
+    pub fn access_token_ttl_secs(&self) -> u64 {
+        (self.access_token_expiry_minutes.max(0) * 60) as u64
+    }
+
+    /// Refresh token lifetime for a persistent or short-lived session.
+    pub fn refresh_token_ttl(&self, persistent: bool) -> chrono::Duration {
+        if persistent {
+            chrono::Duration::days(self.refresh_token_expiry_days)
+        } else {
+            chrono::Duration::hours(self.session_refresh_token_expiry_hours)
+        }
+    }
 }
 
 /// Snowflake ID generator configuration.
+///
+/// Every instance in a cluster needs its own worker ID, or two instances can
+/// generate the same ID in the same millisecond. Either configure distinct
+/// IDs per instance or enable `auto_assign_worker_id`.
 #[derive(Debug, Clone, Deserialize)]
 pub struct SnowflakeSettings {
-    /// Machine/worker ID (0-1023)
+    /// Machine/worker ID (0-1023); used when `worker_id` is unset
     pub machine_id: u16,
 
+    /// Worker ID (0-1023)
+    #[serde(default)]
+    pub worker_id: Option<u16>,
+
+    /// Take the worker ID from a shared Redis counter at startup instead
+    #[serde(default)]
+    pub auto_assign_worker_id: bool,
+
     /// Custom epoch timestamp in milliseconds
     pub epoch: u64,
 }
 
+impl SnowflakeSettings {
+    /// The configured worker ID, preferring `worker_id` over `machine_id`.
+    pub fn configured_worker_id(&self) -> u16 {
+        self.worker_id.unwrap_or(self.machine_id)
+    }
+}
+
 /// Rate limiting configuration.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct RateLimitSettings {
     /// Maximum requests per second
     pub requests_per_second: f64,
 
     /// Burst size (bucket capacity)
     pub burst_size: u32,
+
+    /// Seconds between `@everyone`/`@here` pings in a channel (0 disables)
+    pub mention_cooldown_secs: u64,
 }
 
 /// CORS configuration.
@@ -125,8 +233,185 @@ pub struct WebSocketSettings {
 
     /// Connection timeout for identify in seconds (default: 30)
     pub identify_timeout_secs: u64,
+
+    /// Outbound messages buffered per session (default: 1024)
+    pub outbound_queue_size: usize,
+
+    /// How long a session's outbound queue may stay backed up before the
+    /// client is disconnected, in seconds (default: 10)
+    pub slow_client_timeout_secs: u64,
+}
+
+/// Logging configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LogSettings {
+    /// Tracing filter directives (e.g., "info,chat_server=debug")
+    pub filter: String,
+
+    /// Output format; read at startup only. Defaults by environment, see
+    /// [`LogSettings::format_for`].
+    #[serde(default)]
+    pub format: Option<LogFormat>,
+}
+
+/// How log lines are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// One JSON object per line, for log ingestion
+    Json,
+    /// Multi-line, human-readable output
+    Pretty,
+    /// Single-line, human-readable output
+    Compact,
+}
+
+impl LogSettings {
+    /// The configured format, or the default for `environment`: pretty in
+    /// development and JSON anywhere else.
+    pub fn format_for(&self, environment: &str) -> LogFormat {
+        self.format.unwrap_or(if environment == "development" {
+            LogFormat::Pretty
+        } else {
+            LogFormat::Json
+        })
+    }
+}
+
+/// Administrative access configuration.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AdminSettings {
+    /// User IDs allowed to call admin endpoints
+    #[serde(default)]
+    pub user_ids: Vec<i64>,
+}
+
+impl AdminSettings {
+    /// Check whether a user may call admin endpoints.
+    pub fn is_admin(&self, user_id: i64) -> bool {
+        self.user_ids.contains(&user_id)
+    }
+}
+
+/// Message write batching configuration.
+///
+/// When enabled, messages without attachments are buffered for up to
+/// `max_delay_ms` and written with a single multi-row INSERT.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MessageBatchSettings {
+    /// Whether message creates are batched
+    pub enabled: bool,
+
+    /// Maximum messages written per INSERT
+    pub max_batch_size: usize,
+
+    /// Longest a message waits for others to join its batch, in milliseconds
+    pub max_delay_ms: u64,
+}
+
+impl Default for MessageBatchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_batch_size: 50,
+            max_delay_ms: 5,
+        }
+    }
+}
+
+/// Attachment file storage configuration.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct StorageSettings {
+    /// Which backend stores files
+    pub backend: StorageBackend,
+
+    /// Settings for the `local` backend
+    pub local: LocalStorageSettings,
+
+    /// Settings for the `s3` backend
+    pub s3: S3StorageSettings,
+
+    /// Hosts avatar and banner URLs may point at, besides storage keys
+    /// (comma-separated in env)
+    pub allowed_image_hosts: Vec<String>,
+}
+
+/// Attachment storage backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// Files in a directory on the local disk
+    #[default]
+    Local,
+    /// Objects in an S3-compatible bucket
+    S3,
+}
+
+/// Local disk storage configuration.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LocalStorageSettings {
+    /// Directory files are written to
+    pub root: String,
+
+    /// URL the directory is served from
+    pub base_url: String,
 }
 
+impl Default for LocalStorageSettings {
+    fn default() -> Self {
+        Self {
+            root: "uploads".to_string(),
+            base_url: "/uploads".to_string(),
+        }
+    }
+}
+
+/// S3-compatible object storage configuration.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct S3StorageSettings {
+    /// Service endpoint, e.g. "https://s3.us-east-1.amazonaws.com"
+    pub endpoint: String,
+
+    /// Region requests are signed for
+    pub region: String,
+
+    /// Bucket objects are stored in
+    pub bucket: String,
+
+    /// Access key ID
+    pub access_key_id: String,
+
+    /// Secret access key
+    pub secret_access_key: String,
+
+    /// Base URL clients download objects from (e.g. a CDN); the bucket URL if unset
+    pub public_url: Option<String>,
+
+    /// Accept a plain `http://` endpoint, e.g. a MinIO on a private network
+    pub allow_http: bool,
+}
+
+impl Default for S3StorageSettings {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://s3.us-east-1.amazonaws.com".to_string(),
+            region: "us-east-1".to_string(),
+            bucket: String::new(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            public_url: None,
+            allow_http: false,
+        }
+    }
+}
+
+/// Default tracing filter directives.
+pub const DEFAULT_LOG_FILTER: &str = "info,chat_server=debug,sqlx=warn,tower_http=debug";
+
 /// Minimum required length for JWT secret (256 bits = 32 bytes)
 pub const MIN_JWT_SECRET_LENGTH: usize = 32;
 
@@ -149,37 +434,172 @@ impl Settings {
         // Determine the running environment
         let environment = std::env::var("RUN_ENV").unwrap_or_else(|_| "development".into());
 
+        let builder = Self::defaults(&environment)?
+            // Load from config files
+            .add_source(File::with_name("config/default").required(false))
+            .add_source(File::with_name(&format!("config/{}", environment)).required(false));
+
+        Self::build(builder)
+    }
+
+    /// Load settings from environment variables and built-in defaults only.
+    ///
+    /// For containerized deployments without config files: neither the
+    /// `config/` files nor a `.env` file are read.
+    ///
+    /// Every field is set with `APP__{SECTION}__{FIELD}`, for example:
+    ///
+    /// | Variable | Field | Default |
+    /// |----------|-------|---------|
+    /// | `RUN_ENV` | `environment` | `development` |
+    /// | `APP__SERVER__HOST` (or `SERVER_HOST`) | `server.host` | `0.0.0.0` |
+    /// | `APP__SERVER__PORT` (or `SERVER_PORT`) | `server.port` | `3000` |
+    /// | `APP__SERVER__MAX_BODY_BYTES` | `server.max_body_bytes` | `8388608` |
+    /// | `APP__SERVER__TRUSTED_PROXIES` | `server.trusted_proxies` (comma-separated) | none |
+    /// | `APP__DATABASE__URL` (or `DATABASE_URL`) | `database.url` | required |
+    /// | `APP__DATABASE__MAX_CONNECTIONS` | `database.max_connections` | `50` |
+    /// | `APP__DATABASE__MIN_CONNECTIONS` | `database.min_connections` | `5` |
+    /// | `APP__DATABASE__ACQUIRE_TIMEOUT` | `database.acquire_timeout` | `10` |
+    /// | `APP__DATABASE__STATEMENT_TIMEOUT` | `database.statement_timeout` | `30` |
+    /// | `APP__REDIS__URL` (or `REDIS_URL`) | `redis.url` | required |
+    /// | `APP__REDIS__POOL_SIZE` | `redis.pool_size` | `1` |
+    /// | `APP__JWT__SECRET` (or `JWT_SECRET`) | `jwt.secret` | required |
+    /// | `APP__JWT__KEYS__{KID}` | `jwt.keys.{kid}` (key IDs are lowercased) | none |
+    /// | `APP__JWT__ACTIVE_KEY_ID` | `jwt.active_key_id` | none (sign with `jwt.secret`) |
+    /// | `APP__JWT__ACCESS_TOKEN_EXPIRY_MINUTES` | `jwt.access_token_expiry_minutes` | `15` |
+    /// | `APP__JWT__REFRESH_TOKEN_EXPIRY_DAYS` | `jwt.refresh_token_expiry_days` | `7` |
+    /// | `APP__JWT__SESSION_REFRESH_TOKEN_EXPIRY_HOURS` | `jwt.session_refresh_token_expiry_hours` | `12` |
+    /// | `APP__SNOWFLAKE__MACHINE_ID` (or `SNOWFLAKE_MACHINE_ID`) | `snowflake.machine_id` | `1` |
+    /// | `APP__SNOWFLAKE__WORKER_ID` (or `SNOWFLAKE_WORKER_ID`) | `snowflake.worker_id` | `snowflake.machine_id` |
+    /// | `APP__SNOWFLAKE__AUTO_ASSIGN_WORKER_ID` | `snowflake.auto_assign_worker_id` | `false` |
+    /// | `APP__SNOWFLAKE__EPOCH` | `snowflake.epoch` | `1420070400000` |
+    /// | `APP__RATE_LIMIT__REQUESTS_PER_SECOND` | `rate_limit.requests_per_second` | `1.0` |
+    /// | `APP__RATE_LIMIT__BURST_SIZE` | `rate_limit.burst_size` | `20` |
+    /// | `APP__RATE_LIMIT__MENTION_COOLDOWN_SECS` | `rate_limit.mention_cooldown_secs` | `5` |
+    /// | `APP__CORS__ALLOWED_ORIGINS` | `cors.allowed_origins` (comma-separated) | `http://localhost:3000` |
+    /// | `APP__WEBSOCKET__MAX_MESSAGE_SIZE` | `websocket.max_message_size` | `65536` |
+    /// | `APP__WEBSOCKET__MAX_FRAME_SIZE` | `websocket.max_frame_size` | `16384` |
+    /// | `APP__WEBSOCKET__HEARTBEAT_INTERVAL_MS` | `websocket.heartbeat_interval_ms` | `45000` |
+    /// | `APP__WEBSOCKET__IDENTIFY_TIMEOUT_SECS` | `websocket.identify_timeout_secs` | `30` |
+    /// | `APP__WEBSOCKET__OUTBOUND_QUEUE_SIZE` | `websocket.outbound_queue_size` | `1024` |
+    /// | `APP__WEBSOCKET__SLOW_CLIENT_TIMEOUT_SECS` | `websocket.slow_client_timeout_secs` | `10` |
+    /// | `APP__LOG__FILTER` | `log.filter` | [`DEFAULT_LOG_FILTER`] |
+    /// | `APP__LOG__FORMAT` | `log.format` (`json`, `pretty` or `compact`) | `pretty` in development, else `json` |
+    /// | `APP__ADMIN__USER_IDS` | `admin.user_ids` (comma-separated) | none |
+    /// | `APP__MESSAGE_BATCH__ENABLED` | `message_batch.enabled` | `false` |
+    /// | `APP__MESSAGE_BATCH__MAX_BATCH_SIZE` | `message_batch.max_batch_size` | `50` |
+    /// | `APP__MESSAGE_BATCH__MAX_DELAY_MS` | `message_batch.max_delay_ms` | `5` |
+    /// | `APP__STORAGE__BACKEND` | `storage.backend` (`local` or `s3`) | `local` |
+    /// | `APP__STORAGE__LOCAL__ROOT` | `storage.local.root` | `uploads` |
+    /// | `APP__STORAGE__LOCAL__BASE_URL` | `storage.local.base_url` | `/uploads` |
+    /// | `APP__STORAGE__S3__ENDPOINT` | `storage.s3.endpoint` | `http://s3.us-east-1.amazonaws.com` |
+    /// | `APP__STORAGE__S3__REGION` | `storage.s3.region` | `us-east-1` |
+    /// | `APP__STORAGE__S3__BUCKET` | `storage.s3.bucket` | none |
+    /// | `APP__STORAGE__S3__ACCESS_KEY_ID` | `storage.s3.access_key_id` | none |
+    /// | `APP__STORAGE__S3__SECRET_ACCESS_KEY` | `storage.s3.secret_access_key` | none |
+    /// | `APP__STORAGE__S3__PUBLIC_URL` | `storage.s3.public_url` | bucket URL |
+    /// | `APP__FEATURES__{NAME}` | `features.{name}` | none |
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError` if a required variable is missing, a value
+    /// cannot be parsed, or the settings fail [`Settings::validate`].
+    pub fn from_env_only() -> Result<Self, ConfigError> {
+        let environment = std::env::var("RUN_ENV").unwrap_or_else(|_| "development".into());
+
+        Self::build(Self::defaults(&environment)?)
+    }
+
+    /// Check settings that deserialize fine but are unsafe to run with.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError` if a JWT secret or signing key is too short, the
+    /// active JWT key isn't configured, or the snowflake worker ID doesn't
+    /// fit in its bits.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        // Validate JWT secret length for security
+        if self.jwt.secret.len() < MIN_JWT_SECRET_LENGTH {
+            return Err(ConfigError::Message(format!(
+                "JWT secret must be at least {} characters for security. Current length: {}",
+                MIN_JWT_SECRET_LENGTH,
+                self.jwt.secret.len()
+            )));
+        }
+        if let Some((kid, _)) = self.jwt.keys.iter().find(|(_, key)| key.len() < MIN_JWT_SECRET_LENGTH) {
+            return Err(ConfigError::Message(format!(
+                "JWT key '{}' must be at least {} characters for security",
+                kid, MIN_JWT_SECRET_LENGTH
+            )));
+        }
+        if let Some(kid) = &self.jwt.active_key_id {
+            if !self.jwt.keys.contains_key(kid) {
+                return Err(ConfigError::Message(format!(
+                    "Active JWT key '{}' is not in jwt.keys",
+                    kid
+                )));
+            }
+        }
+
+        // Out-of-range IDs would be truncated and collide with another worker
+        let worker_id = self.snowflake.configured_worker_id();
+        if worker_id > MAX_WORKER_ID {
+            return Err(ConfigError::Message(format!(
+                "Snowflake worker ID must be between 0 and {}, got {}",
+                MAX_WORKER_ID, worker_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Builder holding the built-in default values
+    fn defaults(environment: &str) -> Result<ConfigBuilder<DefaultState>, ConfigError> {
         Config::builder()
             // Start with default values
-            .set_default("environment", environment.clone())?
+            .set_default("environment", environment)?
             .set_default("server.host", "0.0.0.0")?
             .set_default("server.port", 3000)?
+            .set_default("server.max_body_bytes", 8_388_608_i64)? // 8MB
             .set_default("database.max_connections", 50)?
             .set_default("database.min_connections", 5)?
             .set_default("database.acquire_timeout", 10)?
-            .set_default("redis.pool_size", 10)?
+            .set_default("database.statement_timeout", 30)?
+            .set_default("redis.pool_size", 1)?
             .set_default("jwt.access_token_expiry_minutes", 15)?
             .set_default("jwt.refresh_token_expiry_days", 7)?
+            .set_default("jwt.session_refresh_token_expiry_hours", 12)?
             .set_default("snowflake.machine_id", 1)?
             .set_default("snowflake.epoch", 1420070400000_u64)?
-            .set_default("rate_limit.requests_per_second", 10.0)?
-            .set_default("rate_limit.burst_size", 30)?
+            .set_default("rate_limit.requests_per_second", 1.0)?
+            .set_default("rate_limit.burst_size", 20)?
+            .set_default("rate_limit.mention_cooldown_secs", 5)?
             .set_default("cors.allowed_origins", vec!["http://localhost:3000"])?
             // WebSocket settings - security limits to prevent DoS
             .set_default("websocket.max_message_size", 65536_i64)? // 64KB
             .set_default("websocket.max_frame_size", 16384_i64)?   // 16KB
             .set_default("websocket.heartbeat_interval_ms", 45000_i64)?
             .set_default("websocket.identify_timeout_secs", 30_i64)?
-            // Load from config files
-            .add_source(File::with_name("config/default").required(false))
-            .add_source(File::with_name(&format!("config/{}", environment)).required(false))
+            .set_default("websocket.outbound_queue_size", 1024_i64)?
+            .set_default("websocket.slow_client_timeout_secs", 10_i64)?
+            .set_default("log.filter", DEFAULT_LOG_FILTER)
+    }
+
+    /// Apply environment variables on top of `builder`, then deserialize
+    /// and validate the result
+    fn build(builder: ConfigBuilder<DefaultState>) -> Result<Self, ConfigError> {
+        let settings: Self = builder
             // Load from environment variables
             // APP__SERVER__PORT=3000 -> server.port = 3000
             .add_source(
                 Environment::default()
                     .prefix("APP")
                     .separator("__")
-                    .try_parsing(true),
+                    .try_parsing(true)
+                    .list_separator(",")
+                    .with_list_parse_key("server.trusted_proxies")
+                    .with_list_parse_key("cors.allowed_origins")
+                    .with_list_parse_key("admin.user_ids")
+                    .with_list_parse_key("storage.allowed_image_hosts"),
             )
             // Map simple environment variables
             .set_override_option(
@@ -206,19 +626,15 @@ impl Settings {
                 "snowflake.machine_id",
                 std::env::var("SNOWFLAKE_MACHINE_ID").ok(),
             )?
+            .set_override_option(
+                "snowflake.worker_id",
+                std::env::var("SNOWFLAKE_WORKER_ID").ok(),
+            )?
             .build()?
-            .try_deserialize()
-            .and_then(|settings: Self| {
-                // Validate JWT secret length for security
-                if settings.jwt.secret.len() < MIN_JWT_SECRET_LENGTH {
-                    return Err(ConfigError::Message(format!(
-                        "JWT secret must be at least {} characters for security. Current length: {}",
-                        MIN_JWT_SECRET_LENGTH,
-                        settings.jwt.secret.len()
-                    )));
-                }
-                Ok(settings)
-            })
+            .try_deserialize()?;
+
+        settings.validate()?;
+        Ok(settings)
     }
 
     /// Get the full server address as a string.
@@ -242,3 +658,160 @@ impl DatabaseSettings {
         &self.url
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_only_settings_come_from_env_vars() {
+        // Only this test touches these variables
+        let vars = [
+            ("APP__DATABASE__URL", "postgres://env-only/chat"),
+            ("APP__DATABASE__MAX_CONNECTIONS", "7"),
+            ("APP__REDIS__URL", "redis://env-only:6379"),
+            ("APP__REDIS__POOL_SIZE", "4"),
+            ("APP__JWT__SECRET", "an-env-only-secret-that-is-long-enough"),
+            ("APP__CORS__ALLOWED_ORIGINS", "https://a.example,https://b.example"),
+            ("APP__MESSAGE_BATCH__ENABLED", "true"),
+        ];
+        for (name, value) in vars {
+            std::env::set_var(name, value);
+        }
+
+        let result = Settings::from_env_only();
+
+        for (name, _) in vars {
+            std::env::remove_var(name);
+        }
+        let settings = result.unwrap();
+
+        assert_eq!(settings.database.max_connections, 7);
+        assert_eq!(settings.redis.pool_size, 4);
+        assert_eq!(
+            settings.cors.allowed_origins,
+            vec!["https://a.example".to_string(), "https://b.example".to_string()]
+        );
+        assert!(settings.message_batch.enabled);
+        // Unset fields keep their defaults
+        assert_eq!(settings.database.min_connections, 5);
+        assert_eq!(settings.websocket.heartbeat_interval_ms, 45000);
+        assert_eq!(settings.websocket.outbound_queue_size, 1024);
+        assert_eq!(settings.log.filter, DEFAULT_LOG_FILTER);
+        assert_eq!(settings.log.format, None);
+    }
+
+    /// Build settings from the defaults plus the required values and `overrides`
+    fn settings_with(overrides: &[(&str, i64)]) -> Result<Settings, ConfigError> {
+        let mut builder = Settings::defaults("test")?
+            .set_override("database.url", "postgres://localhost/chat")?
+            .set_override("redis.url", "redis://localhost:6379")?
+            .set_override("jwt.secret", "a-test-secret-that-is-long-enough-to-pass")?;
+        for (key, value) in overrides {
+            builder = builder.set_override(*key, *value)?;
+        }
+
+        Settings::build(builder)
+    }
+
+    #[test]
+    fn test_worker_id_must_fit_in_worker_bits() {
+        let settings = settings_with(&[("snowflake.worker_id", i64::from(MAX_WORKER_ID))]).unwrap();
+        assert_eq!(settings.snowflake.configured_worker_id(), MAX_WORKER_ID);
+
+        let result = settings_with(&[("snowflake.worker_id", i64::from(MAX_WORKER_ID) + 1)]);
+        assert!(matches!(result, Err(ConfigError::Message(message)) if message.contains("worker ID")));
+
+        // The legacy machine ID is held to the same range
+        assert!(settings_with(&[("snowflake.machine_id", 1024)]).is_err());
+    }
+
+    #[test]
+    fn test_worker_id_falls_back_to_machine_id() {
+        let settings = settings_with(&[("snowflake.machine_id", 7)]).unwrap();
+        assert_eq!(settings.snowflake.configured_worker_id(), 7);
+
+        let settings = settings_with(&[("snowflake.machine_id", 7), ("snowflake.worker_id", 9)]).unwrap();
+        assert_eq!(settings.snowflake.configured_worker_id(), 9);
+    }
+
+    #[test]
+    fn test_old_jwt_key_verifies_while_new_key_signs() {
+        let old_key = "an-old-signing-key-that-is-long-enough";
+        let new_key = "a-new-signing-key-that-is-long-enough!";
+        let rotated = |active: &str| {
+            Settings::build(
+                Settings::defaults("test")?
+                    .set_override("database.url", "postgres://localhost/chat")?
+                    .set_override("redis.url", "redis://localhost:6379")?
+                    .set_override("jwt.secret", "a-test-secret-that-is-long-enough-to-pass")?
+                    .set_override("jwt.keys.old", old_key)?
+                    .set_override("jwt.keys.new", new_key)?
+                    .set_override("jwt.active_key_id", active)?,
+            )
+        };
+
+        // Without a keyset, tokens are signed with the secret
+        assert_eq!(settings_with(&[]).unwrap().jwt.signing_key().0, None);
+
+        let jwt = rotated("new").unwrap().jwt;
+        assert_eq!(jwt.signing_key(), (Some("new"), new_key));
+        assert_eq!(jwt.verification_secret(Some("old")), Some(old_key));
+        assert_eq!(jwt.verification_secret(Some("new")), Some(new_key));
+        // Tokens from before the keyset was configured, and from removed keys
+        assert_eq!(jwt.verification_secret(None), Some(jwt.secret.as_str()));
+        assert_eq!(jwt.verification_secret(Some("removed")), None);
+
+        // The active key must be one of the configured keys
+        assert!(matches!(rotated("missing"), Err(ConfigError::Message(message)) if message.contains("missing")));
+    }
+
+    #[test]
+    fn test_token_signed_before_rotation_still_decodes() {
+        use crate::application::services::auth_service::{decode_token, encode_token, Claims};
+
+        let keyed = |active: &str, keys: &[(&str, &str)]| {
+            let mut builder = Settings::defaults("test")
+                .unwrap()
+                .set_override("database.url", "postgres://localhost/chat")
+                .unwrap()
+                .set_override("redis.url", "redis://localhost:6379")
+                .unwrap()
+                .set_override("jwt.secret", "a-test-secret-that-is-long-enough-to-pass")
+                .unwrap()
+                .set_override("jwt.active_key_id", active)
+                .unwrap();
+            for (kid, key) in keys {
+                builder = builder.set_override(format!("jwt.keys.{kid}"), *key).unwrap();
+            }
+            Settings::build(builder).unwrap().jwt
+        };
+        let old_key = ("old", "an-old-signing-key-that-is-long-enough");
+        let new_key = ("new", "a-new-signing-key-that-is-long-enough!");
+        let now = chrono::Utc::now().timestamp();
+        let claims = |sub: &str| Claims {
+            sub: sub.to_string(),
+            exp: now + 600,
+            iat: now,
+            jti: None,
+            sid: None,
+        };
+        let kid = |token: &str| jsonwebtoken::decode_header(token).unwrap().kid;
+
+        let before = keyed("old", &[old_key]);
+        let old_token = encode_token(&before, &claims("1")).unwrap();
+        assert_eq!(kid(&old_token).as_deref(), Some("old"));
+
+        // After rotation the old token still verifies and new ones use the new key
+        let after = keyed("new", &[old_key, new_key]);
+        assert_eq!(decode_token::<Claims>(&after, &old_token).unwrap().claims.sub, "1");
+        let new_token = encode_token(&after, &claims("2")).unwrap();
+        assert_eq!(kid(&new_token).as_deref(), Some("new"));
+        assert_eq!(decode_token::<Claims>(&after, &new_token).unwrap().claims.sub, "2");
+
+        // Once the old key is removed its tokens are rejected
+        let retired = keyed("new", &[new_key]);
+        assert!(decode_token::<Claims>(&retired, &old_token).is_err());
+        assert!(decode_token::<Claims>(&retired, &new_token).is_ok());
+    }
+}
@@ -15,7 +15,8 @@ pub const MAX_ATTACHMENT_SIZE: i32 = 26_214_400;
 ///
 /// Maps to the `attachments` table:
 /// - id: BIGINT PRIMARY KEY (Snowflake ID)
-/// - message_id: BIGINT NOT NULL REFERENCES messages(id)
+/// - message_id: BIGINT NULL REFERENCES messages(id) -- NULL until posted
+/// - uploader_id: BIGINT NULL REFERENCES users(id)
 /// - filename: VARCHAR(255) NOT NULL
 /// - content_type: VARCHAR(100) NULL (MIME type)
 /// - size: INTEGER NOT NULL (bytes, max 25MB)
@@ -29,8 +30,11 @@ pub struct Attachment {
     /// Snowflake ID (primary key)
     pub id: i64,
 
-    /// Message ID this attachment belongs to
-    pub message_id: i64,
+    /// Message ID this attachment belongs to (None until posted)
+    pub message_id: Option<i64>,
+
+    /// User ID who uploaded the attachment
+    pub uploader_id: Option<i64>,
 
     /// Original filename
     pub filename: String,
@@ -58,6 +62,11 @@ pub struct Attachment {
 }
 
 impl Attachment {
+    /// Check if this attachment is already associated with a message.
+    pub fn is_attached(&self) -> bool {
+        self.message_id.is_some()
+    }
+
     /// Check if this attachment is an image.
     pub fn is_image(&self) -> bool {
         self.content_type
@@ -117,7 +126,8 @@ impl Default for Attachment {
     fn default() -> Self {
         Self {
             id: 0,
-            message_id: 0,
+            message_id: None,
+            uploader_id: None,
             filename: String::new(),
             content_type: None,
             size: 0,
@@ -14,8 +14,11 @@ use crate::shared::error::AppError;
 /// ```sql
 /// CREATE TYPE channel_type AS ENUM ('text', 'voice', 'category', 'dm', 'group_dm');
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+///
+/// Binds and decodes directly as the Postgres enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, sqlx::Type)]
 #[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "channel_type", rename_all = "snake_case")]
 pub enum ChannelType {
     /// A text channel within a server
     #[default]
@@ -31,7 +34,7 @@ pub enum ChannelType {
 }
 
 impl ChannelType {
-    /// Convert from database string representation.
+    /// Convert from string representation.
     pub fn from_str(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "text" => Self::Text,
@@ -43,7 +46,7 @@ impl ChannelType {
         }
     }
 
-    /// Convert to database string representation.
+    /// Convert to string representation.
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Text => "text",
@@ -73,6 +76,7 @@ impl std::fmt::Display for ChannelType {
 /// - parent_id: BIGINT REFERENCES channels(id) -- Category reference
 /// - nsfw: BOOLEAN NOT NULL DEFAULT FALSE
 /// - rate_limit_per_user: INTEGER DEFAULT 0 -- Slowmode in seconds
+/// - slowmode_exempt_roles: JSONB NOT NULL DEFAULT '[]' -- Role IDs
 /// - created_at: TIMESTAMPTZ NOT NULL DEFAULT NOW()
 /// - updated_at: TIMESTAMPTZ NOT NULL DEFAULT NOW()
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,6 +109,10 @@ pub struct Channel {
     /// Slowmode rate limit (seconds between messages per user)
     pub rate_limit_per_user: i32,
 
+    /// Roles whose members aren't subject to slowmode
+    #[serde(default)]
+    pub slowmode_exempt_roles: Vec<i64>,
+
     /// Channel creation timestamp
     pub created_at: DateTime<Utc>,
 
@@ -155,6 +163,7 @@ impl Default for Channel {
             parent_id: None,
             nsfw: false,
             rate_limit_per_user: 0,
+            slowmode_exempt_roles: Vec::new(),
             created_at: now,
             updated_at: now,
         }
@@ -182,6 +191,21 @@ pub struct PermissionOverwrite {
     pub deny: i64,
 }
 
+/// Everything deleting a channel changed besides the channel itself.
+///
+/// Messages and permission overwrites of the channel are always deleted.
+/// Children of a deleted category are kept and moved to the top level of
+/// the server, and invites leading to the channel stop working.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelDeletion {
+    pub channel_id: i64,
+    pub server_id: Option<i64>,
+    /// Channels moved out of the deleted category
+    pub reparented_children: Vec<i64>,
+    /// Codes of invites to the channel, deleted with it
+    pub invalidated_invites: Vec<String>,
+}
+
 /// Repository trait for Channel data access operations.
 #[async_trait]
 pub trait ChannelRepository: Send + Sync {
@@ -194,30 +218,76 @@ pub trait ChannelRepository: Send + Sync {
     /// Find channels by parent category ID.
     async fn find_by_parent_id(&self, parent_id: i64) -> Result<Vec<Channel>, AppError>;
 
+    /// Check whether a user is a recipient of a DM channel.
+    async fn is_recipient(&self, channel_id: i64, user_id: i64) -> Result<bool, AppError>;
+
+    /// User IDs of the recipients of a DM channel.
+    async fn find_recipient_ids(&self, channel_id: i64) -> Result<Vec<i64>, AppError>;
+
     /// Find DM channel between two users.
     async fn find_dm_channel(&self, user1_id: i64, user2_id: i64) -> Result<Option<Channel>, AppError>;
 
     /// Create a new channel.
     async fn create(&self, channel: &Channel) -> Result<Channel, AppError>;
 
+    /// Create a DM channel together with its recipients.
+    async fn create_dm(&self, channel: &Channel, recipient_ids: &[i64]) -> Result<Channel, AppError>;
+
     /// Update an existing channel.
     async fn update(&self, channel: &Channel) -> Result<Channel, AppError>;
 
     /// Delete a channel.
     async fn delete(&self, id: i64) -> Result<(), AppError>;
 
+    /// Delete a channel with its messages, overwrites and invites, move
+    /// its children to the top level and queue a `CHANNEL_DELETE` event,
+    /// all in one transaction.
+    async fn delete_with_dependents(&self, channel: &Channel) -> Result<ChannelDeletion, AppError>;
+
+    /// Close or reopen a DM for one of its recipients.
+    async fn set_dm_closed(&self, channel_id: i64, user_id: i64, closed: bool) -> Result<(), AppError>;
+
     /// Update channel positions (for reordering).
     async fn update_positions(&self, server_id: i64, positions: Vec<(i64, i32)>) -> Result<(), AppError>;
 
     /// Get permission overwrites for a channel.
     async fn get_permission_overwrites(&self, channel_id: i64) -> Result<Vec<PermissionOverwrite>, AppError>;
 
+    /// Get permission overwrites for several channels in one query.
+    async fn get_permission_overwrites_for_channels(
+        &self,
+        channel_ids: &[i64],
+    ) -> Result<Vec<PermissionOverwrite>, AppError>;
+
     /// Set permission overwrites for a channel.
     async fn set_permission_overwrites(
         &self,
         channel_id: i64,
         overwrites: Vec<PermissionOverwrite>,
     ) -> Result<(), AppError>;
+
+    /// Create or replace the overwrite for one target of a channel.
+    async fn upsert_permission_overwrite(&self, overwrite: &PermissionOverwrite) -> Result<(), AppError>;
+
+    /// Delete the overwrite for one target of a channel.
+    ///
+    /// Returns whether an overwrite was removed.
+    async fn delete_permission_overwrite(
+        &self,
+        channel_id: i64,
+        target_type: &str,
+        target_id: i64,
+    ) -> Result<bool, AppError>;
+
+    /// Create a channel with a copy of the permission overwrites of the
+    /// channel `overwrites_from`, if any, applying `(channel_id, position)`
+    /// updates to existing channels first, all in one transaction.
+    async fn create_with_overwrites(
+        &self,
+        channel: &Channel,
+        overwrites_from: Option<i64>,
+        positions: &[(i64, i32)],
+    ) -> Result<Channel, AppError>;
 }
 
 #[cfg(test)]
@@ -322,6 +392,7 @@ mod tests {
             parent_id: None,
             nsfw: false,
             rate_limit_per_user: 0,
+            slowmode_exempt_roles: Vec::new(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -0,0 +1,92 @@
+//! Channel Override Entity
+//!
+//! A user's notification settings for one channel: whether it is muted and
+//! which notification level it uses. Only non-default overrides are stored;
+//! a missing override means the channel is unmuted and follows the guild's
+//! default notification level.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::shared::error::AppError;
+
+/// Channel notification level, matching the PostgreSQL ENUM
+/// `channel_notification_level`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "channel_notification_level", rename_all = "snake_case")]
+pub enum NotificationLevel {
+    /// Notify on every message
+    All,
+    /// Notify only when mentioned
+    Mentions,
+    /// Never notify
+    Nothing,
+}
+
+impl NotificationLevel {
+    /// Parse a string representation, rejecting unknown values.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "all" => Some(Self::All),
+            "mentions" => Some(Self::Mentions),
+            "nothing" => Some(Self::Nothing),
+            _ => None,
+        }
+    }
+
+    /// Convert to string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Mentions => "mentions",
+            Self::Nothing => "nothing",
+        }
+    }
+}
+
+/// A user's notification override for one channel.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ChannelOverride {
+    pub user_id: i64,
+    pub channel_id: i64,
+    pub muted: bool,
+    /// None inherits the guild default
+    pub notification_level: Option<NotificationLevel>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ChannelOverride {
+    /// Override with default settings
+    pub fn new(user_id: i64, channel_id: i64) -> Self {
+        Self {
+            user_id,
+            channel_id,
+            muted: false,
+            notification_level: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Whether the override matches the defaults and need not be stored
+    pub fn is_default(&self) -> bool {
+        !self.muted && self.notification_level.is_none()
+    }
+}
+
+/// Trait defining channel override repository operations.
+#[async_trait]
+pub trait ChannelOverrideRepository: Send + Sync {
+    /// Find a user's override for a channel.
+    async fn find(&self, user_id: i64, channel_id: i64) -> Result<Option<ChannelOverride>, AppError>;
+
+    /// Get all of a user's overrides, ordered by channel ID.
+    async fn find_by_user(&self, user_id: i64) -> Result<Vec<ChannelOverride>, AppError>;
+
+    /// Create or replace an override.
+    async fn upsert(&self, channel_override: &ChannelOverride) -> Result<ChannelOverride, AppError>;
+
+    /// Delete an override. Returns whether one existed.
+    async fn delete(&self, user_id: i64, channel_id: i64) -> Result<bool, AppError>;
+}
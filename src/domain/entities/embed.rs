@@ -0,0 +1,167 @@
+//! Message embed types.
+//!
+//! Embeds are rich content blocks attached to a message. They are stored
+//! as a JSON array in the `messages.message_embeds` column.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of embeds on a single message.
+pub const MAX_EMBEDS: usize = 10;
+
+/// Rich embed attached to a message.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Embed {
+    /// Title (up to 256 characters)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Body text (up to 4096 characters)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Link the title points to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// Accent color as a 24-bit RGB integer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<u32>,
+
+    /// Name/value fields (up to 25)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<EmbedField>,
+
+    /// Footer shown below the body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub footer: Option<EmbedFooter>,
+
+    /// Large image shown in the embed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<EmbedImage>,
+
+    /// Timestamp shown in the footer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// A name/value field in an embed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmbedField {
+    /// Field name (up to 256 characters)
+    pub name: String,
+
+    /// Field value (up to 1024 characters)
+    pub value: String,
+
+    /// Whether the field may be displayed side by side with others
+    #[serde(default)]
+    pub inline: bool,
+}
+
+/// Embed footer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmbedFooter {
+    /// Footer text (up to 2048 characters)
+    pub text: String,
+
+    /// Small icon shown next to the text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+}
+
+/// Embed image.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmbedImage {
+    /// Image source URL
+    pub url: String,
+}
+
+impl Embed {
+    /// Maximum title length in characters.
+    pub const MAX_TITLE_LENGTH: usize = 256;
+
+    /// Maximum description length in characters.
+    pub const MAX_DESCRIPTION_LENGTH: usize = 4096;
+
+    /// Maximum number of fields.
+    pub const MAX_FIELDS: usize = 25;
+
+    /// Maximum field name length in characters.
+    pub const MAX_FIELD_NAME_LENGTH: usize = 256;
+
+    /// Maximum field value length in characters.
+    pub const MAX_FIELD_VALUE_LENGTH: usize = 1024;
+
+    /// Maximum footer text length in characters.
+    pub const MAX_FOOTER_TEXT_LENGTH: usize = 2048;
+
+    /// Maximum combined text length across all embeds of a message.
+    pub const MAX_TOTAL_LENGTH: usize = 6000;
+
+    /// Highest valid color value (0xFFFFFF).
+    pub const MAX_COLOR: u32 = 0xFF_FFFF;
+
+    /// Maximum length of any URL in the embed.
+    pub const MAX_URL_LENGTH: usize = 2048;
+
+    /// Every URL in the embed (title link, footer icon, image).
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        self.url
+            .as_deref()
+            .into_iter()
+            .chain(self.footer.as_ref().and_then(|f| f.icon_url.as_deref()))
+            .chain(self.image.as_ref().map(|i| i.url.as_str()))
+    }
+
+    /// Total characters of text in the embed (title, description, field
+    /// names and values, footer text).
+    pub fn text_length(&self) -> usize {
+        let len = |s: &Option<String>| s.as_deref().map_or(0, |s| s.chars().count());
+
+        len(&self.title)
+            + len(&self.description)
+            + self
+                .fields
+                .iter()
+                .map(|f| f.name.chars().count() + f.value.chars().count())
+                .sum::<usize>()
+            + self.footer.as_ref().map_or(0, |f| f.text.chars().count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_length_counts_all_text() {
+        let embed = Embed {
+            title: Some("abc".to_string()),
+            description: Some("de".to_string()),
+            fields: vec![EmbedField {
+                name: "f".to_string(),
+                value: "gh".to_string(),
+                inline: false,
+            }],
+            footer: Some(EmbedFooter {
+                text: "안녕".to_string(),
+                icon_url: None,
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(embed.text_length(), 10);
+    }
+
+    #[test]
+    fn test_embed_serialization_omits_empty_parts() {
+        let embed = Embed {
+            title: Some("Hello".to_string()),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_value(&embed).unwrap();
+        assert_eq!(json, serde_json::json!({ "title": "Hello" }));
+    }
+}
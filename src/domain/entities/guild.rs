@@ -10,6 +10,86 @@ use serde::{Deserialize, Serialize};
 
 use crate::shared::error::AppError;
 
+/// Default notification level for members, matching the PostgreSQL ENUM
+/// `message_notification_level`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "message_notification_level", rename_all = "snake_case")]
+pub enum DefaultMessageNotifications {
+    /// Notify on every message
+    #[default]
+    All,
+    /// Notify only when mentioned
+    Mentions,
+}
+
+impl DefaultMessageNotifications {
+    /// Parse a string representation, rejecting unknown values.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "all" => Some(Self::All),
+            "mentions" => Some(Self::Mentions),
+            _ => None,
+        }
+    }
+
+    /// Convert to string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Mentions => "mentions",
+        }
+    }
+}
+
+impl std::fmt::Display for DefaultMessageNotifications {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Whose messages are scanned for explicit content, matching the
+/// PostgreSQL ENUM `explicit_content_filter_level`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "explicit_content_filter_level", rename_all = "snake_case")]
+pub enum ExplicitContentFilter {
+    /// No scanning
+    #[default]
+    Disabled,
+    /// Scan messages from members without any role
+    MembersWithoutRoles,
+    /// Scan messages from every member
+    All,
+}
+
+impl ExplicitContentFilter {
+    /// Parse a string representation, rejecting unknown values.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "disabled" => Some(Self::Disabled),
+            "members_without_roles" => Some(Self::MembersWithoutRoles),
+            "all" => Some(Self::All),
+            _ => None,
+        }
+    }
+
+    /// Convert to string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Disabled => "disabled",
+            Self::MembersWithoutRoles => "members_without_roles",
+            Self::All => "all",
+        }
+    }
+}
+
+impl std::fmt::Display for ExplicitContentFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// Represents a server (guild) in the chat system.
 ///
 /// A server is a community space containing channels, roles, and members.
@@ -20,6 +100,11 @@ use crate::shared::error::AppError;
 /// - owner_id: BIGINT NOT NULL REFERENCES users(id)
 /// - icon_url: TEXT NULL
 /// - description: TEXT NULL
+/// - system_channel_id: BIGINT NULL REFERENCES channels(id)
+/// - afk_channel_id: BIGINT NULL REFERENCES channels(id)
+/// - default_message_notifications: message_notification_level NOT NULL DEFAULT 'all'
+/// - explicit_content_filter: explicit_content_filter_level NOT NULL DEFAULT 'disabled'
+/// - discoverable: BOOLEAN NOT NULL DEFAULT FALSE
 /// - created_at: TIMESTAMPTZ NOT NULL DEFAULT NOW()
 /// - updated_at: TIMESTAMPTZ NOT NULL DEFAULT NOW()
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +124,25 @@ pub struct Server {
     /// Server description
     pub description: Option<String>,
 
+    /// Text channel receiving system messages
+    pub system_channel_id: Option<i64>,
+
+    /// Voice channel idle members are moved to
+    pub afk_channel_id: Option<i64>,
+
+    /// Notification level for members who haven't chosen their own
+    pub default_message_notifications: DefaultMessageNotifications,
+
+    /// Whose messages are scanned for explicit content
+    pub explicit_content_filter: ExplicitContentFilter,
+
+    /// Role given to members joining through an invite
+    pub auto_role_id: Option<i64>,
+
+    /// Whether anyone may preview the server without joining
+    #[serde(default)]
+    pub discoverable: bool,
+
     /// Server creation timestamp
     pub created_at: DateTime<Utc>,
 
@@ -47,10 +151,22 @@ pub struct Server {
 }
 
 impl Server {
+    /// Feature flag of servers that can be previewed without joining
+    pub const FEATURE_DISCOVERABLE: &'static str = "DISCOVERABLE";
+
     /// Check if a user is the owner of this server.
     pub fn is_owner(&self, user_id: i64) -> bool {
         self.owner_id == user_id
     }
+
+    /// Feature flags advertised to clients (e.g. `DISCOVERABLE`).
+    pub fn features(&self) -> Vec<String> {
+        let mut features = Vec::new();
+        if self.discoverable {
+            features.push(Self::FEATURE_DISCOVERABLE.to_string());
+        }
+        features
+    }
 }
 
 impl Default for Server {
@@ -62,6 +178,12 @@ impl Default for Server {
             owner_id: 0,
             icon_url: None,
             description: None,
+            system_channel_id: None,
+            afk_channel_id: None,
+            default_message_notifications: DefaultMessageNotifications::default(),
+            explicit_content_filter: ExplicitContentFilter::default(),
+            auto_role_id: None,
+            discoverable: false,
             created_at: now,
             updated_at: now,
         }
@@ -81,6 +203,9 @@ pub trait ServerRepository: Send + Sync {
     /// Find all servers a user is a member of.
     async fn find_by_user_id(&self, user_id: i64) -> Result<Vec<Server>, AppError>;
 
+    /// Find the servers both users are members of, with their member counts.
+    async fn find_mutual(&self, user_id: i64, other_user_id: i64) -> Result<Vec<(Server, i64)>, AppError>;
+
     /// Find all servers owned by a user.
     async fn find_by_owner_id(&self, owner_id: i64) -> Result<Vec<Server>, AppError>;
 
@@ -98,7 +223,37 @@ pub trait ServerRepository: Send + Sync {
 
     /// Transfer ownership to another user.
     async fn transfer_ownership(&self, server_id: i64, new_owner_id: i64) -> Result<(), AppError>;
+
+    /// Find the longest-standing member of a server other than the given user.
+    async fn find_oldest_member(&self, server_id: i64, excluding_user_id: i64) -> Result<Option<i64>, AppError>;
 }
 
 /// Type alias for API compatibility.
 pub type GuildRepository = dyn ServerRepository;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_level_round_trips() {
+        for level in [DefaultMessageNotifications::All, DefaultMessageNotifications::Mentions] {
+            assert_eq!(DefaultMessageNotifications::parse(level.as_str()), Some(level));
+        }
+        assert_eq!(DefaultMessageNotifications::parse("none"), None);
+        assert_eq!(DefaultMessageNotifications::parse("ALL"), None);
+    }
+
+    #[test]
+    fn test_content_filter_round_trips() {
+        for level in [
+            ExplicitContentFilter::Disabled,
+            ExplicitContentFilter::MembersWithoutRoles,
+            ExplicitContentFilter::All,
+        ] {
+            assert_eq!(ExplicitContentFilter::parse(level.as_str()), Some(level));
+        }
+        assert_eq!(ExplicitContentFilter::parse("members"), None);
+        assert_eq!(ExplicitContentFilter::parse(""), None);
+    }
+}
@@ -55,13 +55,23 @@ pub struct Invite {
 }
 
 impl Invite {
+    /// When an invite created at `created_at` with `max_age` expires
+    /// (None if never).
+    pub fn expiry_for(created_at: DateTime<Utc>, max_age: i32) -> Option<DateTime<Utc>> {
+        (max_age > 0).then(|| created_at + chrono::Duration::seconds(max_age as i64))
+    }
+
+    /// When the invite expires (None if never).
+    ///
+    /// An invite with a `max_age` but no `expires_at` is inconsistent; its
+    /// expiry is derived from `created_at` so it can't live forever.
+    pub fn expiry(&self) -> Option<DateTime<Utc>> {
+        self.expires_at.or_else(|| Self::expiry_for(self.created_at, self.max_age))
+    }
+
     /// Check if the invite has expired.
     pub fn is_expired(&self) -> bool {
-        if let Some(expires_at) = self.expires_at {
-            expires_at < Utc::now()
-        } else {
-            false
-        }
+        self.expiry().is_some_and(|expiry| expiry < Utc::now())
     }
 
     /// Check if the invite has reached its maximum uses.
@@ -83,6 +93,12 @@ impl Invite {
         }
     }
 
+    /// Get seconds until expiration, clamped at zero (None if never expires).
+    pub fn expires_in(&self) -> Option<i64> {
+        self.expiry()
+            .map(|expires_at| (expires_at - Utc::now()).num_seconds().max(0))
+    }
+
     /// Generate a random invite code.
     pub fn generate_code() -> String {
         use rand::Rng;
@@ -108,11 +124,7 @@ impl Invite {
         temporary: bool,
     ) -> Self {
         let now = Utc::now();
-        let expires_at = if max_age > 0 {
-            Some(now + chrono::Duration::seconds(max_age as i64))
-        } else {
-            None
-        };
+        let expires_at = Self::expiry_for(now, max_age);
 
         Self {
             code: Self::generate_code(),
@@ -155,6 +167,17 @@ pub trait InviteRepository: Send + Sync {
     /// Find all invites for a server.
     async fn find_by_server_id(&self, server_id: i64) -> Result<Vec<Invite>, AppError>;
 
+    /// Find a page of invites for a server, newest first.
+    ///
+    /// When `before_code` is set, only invites created before that invite
+    /// are returned.
+    async fn find_by_server_id_paginated(
+        &self,
+        server_id: i64,
+        before_code: Option<&str>,
+        limit: i32,
+    ) -> Result<Vec<Invite>, AppError>;
+
     /// Find all invites for a channel.
     async fn find_by_channel_id(&self, channel_id: i64) -> Result<Vec<Invite>, AppError>;
 
@@ -176,3 +199,49 @@ pub trait InviteRepository: Send + Sync {
     /// Check if an invite code exists.
     async fn code_exists(&self, code: &str) -> Result<bool, AppError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_missing_expiry_is_derived_from_max_age() {
+        let created_at = Utc::now() - Duration::hours(2);
+        let invite = Invite {
+            max_age: 3600,
+            expires_at: None,
+            created_at,
+            ..Default::default()
+        };
+
+        assert_eq!(invite.expiry(), Some(created_at + Duration::seconds(3600)));
+        assert!(invite.is_expired());
+        assert!(!invite.is_valid());
+        assert_eq!(invite.expires_in(), Some(0));
+
+        // A stored expiry wins over the derived one
+        let stored = Utc::now() + Duration::hours(1);
+        let invite = Invite {
+            expires_at: Some(stored),
+            ..invite
+        };
+        assert_eq!(invite.expiry(), Some(stored));
+        assert!(!invite.is_expired());
+    }
+
+    #[test]
+    fn test_zero_max_age_never_expires() {
+        let invite = Invite {
+            max_age: 0,
+            expires_at: None,
+            created_at: Utc::now() - Duration::days(365),
+            ..Default::default()
+        };
+
+        assert_eq!(invite.expiry(), None);
+        assert!(!invite.is_expired());
+        assert_eq!(invite.expires_in(), None);
+        assert!(invite.is_valid());
+    }
+}
@@ -15,6 +15,7 @@ use crate::shared::error::AppError;
 /// - user_id: BIGINT NOT NULL REFERENCES users(id) (composite PK)
 /// - nickname: VARCHAR(32) NULL
 /// - joined_at: TIMESTAMPTZ NOT NULL DEFAULT NOW()
+/// - communication_disabled_until: TIMESTAMPTZ NULL
 ///
 /// Role assignments are stored in the `member_roles` junction table.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +35,10 @@ pub struct Member {
     /// IDs of roles assigned to this member (loaded from member_roles table)
     #[serde(default)]
     pub roles: Vec<i64>,
+
+    /// End of the member's timeout, if they have been timed out
+    #[serde(default)]
+    pub communication_disabled_until: Option<DateTime<Utc>>,
 }
 
 impl Member {
@@ -47,6 +52,13 @@ impl Member {
         self.nickname.as_deref().unwrap_or(username)
     }
 
+    /// Check if the member is timed out at `now`.
+    pub fn is_timed_out(&self, now: DateTime<Utc>) -> bool {
+        self.communication_disabled_until
+            .map(|until| until > now)
+            .unwrap_or(false)
+    }
+
     /// Create a new member with just the required fields.
     pub fn new(server_id: i64, user_id: i64) -> Self {
         Self {
@@ -55,6 +67,7 @@ impl Member {
             nickname: None,
             joined_at: Utc::now(),
             roles: Vec::new(),
+            communication_disabled_until: None,
         }
     }
 }
@@ -67,10 +80,22 @@ impl Default for Member {
             nickname: None,
             joined_at: Utc::now(),
             roles: Vec::new(),
+            communication_disabled_until: None,
         }
     }
 }
 
+/// Order of a paginated member list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberOrder {
+    /// By user ID
+    #[default]
+    UserId,
+    /// Earliest joined first, see [`Member::join_position`]
+    JoinedAt,
+}
+
 /// Represents a role assignment for a member.
 ///
 /// Maps to the `member_roles` table:
@@ -95,6 +120,10 @@ pub trait MemberRepository: Send + Sync {
     /// Find a member by server and user ID.
     async fn find(&self, server_id: i64, user_id: i64) -> Result<Option<Member>, AppError>;
 
+    /// Find several members of a server in one query. Users who are not
+    /// members are skipped and the order of the results is unspecified.
+    async fn find_many(&self, server_id: i64, user_ids: &[i64]) -> Result<Vec<Member>, AppError>;
+
     /// Find all servers a user is a member of.
     async fn find_by_user(&self, user_id: i64) -> Result<Vec<Member>, AppError>;
 
@@ -106,6 +135,16 @@ pub trait MemberRepository: Send + Sync {
         limit: i32,
     ) -> Result<Vec<Member>, AppError>;
 
+    /// Find members in a server ordered by [`Member::join_position`], with
+    /// cursor-based pagination. `after` is the user ID of the last member of
+    /// the previous page.
+    async fn find_by_server_id_joined(
+        &self,
+        server_id: i64,
+        after: Option<i64>,
+        limit: i32,
+    ) -> Result<Vec<Member>, AppError>;
+
     /// Search members by nickname or username.
     async fn search(
         &self,
@@ -129,6 +168,15 @@ pub trait MemberRepository: Send + Sync {
     /// Remove a role from a member.
     async fn remove_role(&self, server_id: i64, user_id: i64, role_id: i64) -> Result<(), AppError>;
 
+    /// Add and remove several roles of a member in a single transaction.
+    async fn update_roles(
+        &self,
+        server_id: i64,
+        user_id: i64,
+        added: &[i64],
+        removed: &[i64],
+    ) -> Result<(), AppError>;
+
     /// Get all role IDs for a member.
     async fn get_roles(&self, server_id: i64, user_id: i64) -> Result<Vec<i64>, AppError>;
 
@@ -140,6 +188,16 @@ pub trait MemberRepository: Send + Sync {
 
     /// Find members with a specific role.
     async fn find_by_role(&self, server_id: i64, role_id: i64) -> Result<Vec<Member>, AppError>;
+
+    /// Find members holding a role, ordered by user ID, with cursor-based
+    /// pagination. Each member comes with all of their roles.
+    async fn find_members_with_role(
+        &self,
+        server_id: i64,
+        role_id: i64,
+        after: Option<i64>,
+        limit: i32,
+    ) -> Result<Vec<Member>, AppError>;
 }
 
 #[cfg(test)]
@@ -157,6 +215,7 @@ mod tests {
             nickname: None,
             joined_at: Utc::now(),
             roles: Vec::new(),
+            communication_disabled_until: None,
         }
     }
 
@@ -445,4 +504,11 @@ mod tests {
 
         assert_eq!(member.display_name("username"), "Test User");
     }
+
+    #[test]
+    fn test_member_order_from_query_value() {
+        let order: MemberOrder = serde_json::from_str("\"joined_at\"").unwrap();
+        assert_eq!(order, MemberOrder::JoinedAt);
+        assert_eq!(MemberOrder::default(), MemberOrder::UserId);
+    }
 }
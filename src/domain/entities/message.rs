@@ -6,6 +6,7 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::{Attachment, Embed};
 use crate::shared::error::AppError;
 
 /// Message types matching the PostgreSQL ENUM `message_type`.
@@ -24,8 +25,11 @@ use crate::shared::error::AppError;
 ///     'reply'              -- Reply to another message
 /// );
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+///
+/// Binds and decodes directly as the Postgres enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, sqlx::Type)]
 #[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "message_type", rename_all = "snake_case")]
 pub enum MessageType {
     /// A regular user message
     #[default]
@@ -49,7 +53,7 @@ pub enum MessageType {
 }
 
 impl MessageType {
-    /// Convert from database string representation.
+    /// Convert from string representation.
     pub fn from_str(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "default" => Self::Default,
@@ -65,7 +69,7 @@ impl MessageType {
         }
     }
 
-    /// Convert to database string representation.
+    /// Convert to string representation.
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Default => "default",
@@ -92,6 +96,19 @@ impl std::fmt::Display for MessageType {
     }
 }
 
+/// Message flag bits, Discord-compatible.
+pub mod message_flags {
+    /// Published to channels following this one
+    pub const CROSSPOSTED: i64 = 1 << 0;
+    /// Clients don't render the message's embeds
+    pub const SUPPRESS_EMBEDS: i64 = 1 << 2;
+    /// Only visible to the user it was sent for; kept out of channel history
+    pub const EPHEMERAL: i64 = 1 << 6;
+
+    /// Flags users may set on their own messages
+    pub const USER_SETTABLE: i64 = SUPPRESS_EMBEDS;
+}
+
 /// Represents a message in a channel.
 ///
 /// Maps to the `messages` table:
@@ -102,6 +119,9 @@ impl std::fmt::Display for MessageType {
 /// - message_type: message_type NOT NULL DEFAULT 'default'
 /// - reply_to_id: BIGINT REFERENCES messages(id) -- For reply messages
 /// - pinned: BOOLEAN NOT NULL DEFAULT FALSE
+/// - tts: BOOLEAN NOT NULL DEFAULT FALSE
+/// - flags: BIGINT NOT NULL DEFAULT 0 (see [`message_flags`])
+/// - message_embeds: JSONB NOT NULL DEFAULT '[]'
 /// - edited_at: TIMESTAMPTZ NULL
 /// - created_at: TIMESTAMPTZ NOT NULL DEFAULT NOW()
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -128,6 +148,18 @@ pub struct Message {
     /// Whether message is pinned
     pub pinned: bool,
 
+    /// Whether clients should read the message aloud (text-to-speech)
+    #[serde(default)]
+    pub tts: bool,
+
+    /// Bitfield of [`message_flags`]
+    #[serde(default)]
+    pub flags: i64,
+
+    /// Rich embeds (up to 10)
+    #[serde(default)]
+    pub embeds: Vec<Embed>,
+
     /// Timestamp when message was last edited (None if never edited)
     pub edited_at: Option<DateTime<Utc>>,
 
@@ -151,6 +183,20 @@ impl Message {
         self.message_type.is_system()
     }
 
+    /// Check if all of `flags` are set on the message.
+    pub fn has_flag(&self, flags: i64) -> bool {
+        self.flags & flags == flags
+    }
+
+    /// Set or clear `flags` on the message.
+    pub fn set_flag(&mut self, flags: i64, enabled: bool) {
+        if enabled {
+            self.flags |= flags;
+        } else {
+            self.flags &= !flags;
+        }
+    }
+
     /// Get the content length in characters.
     pub fn content_length(&self) -> usize {
         self.content.chars().count()
@@ -167,6 +213,9 @@ impl Default for Message {
             message_type: MessageType::default(),
             reply_to_id: None,
             pinned: false,
+            tts: false,
+            flags: 0,
+            embeds: Vec::new(),
             edited_at: None,
             created_at: Utc::now(),
         }
@@ -182,8 +231,9 @@ pub trait MessageRepository: Send + Sync {
     /// Find messages in a channel with cursor-based pagination.
     ///
     /// Uses keyset pagination for optimal performance on large datasets.
-    /// - `before`: Get messages before this message ID (descending)
-    /// - `after`: Get messages after this message ID (ascending)
+    /// Results are always newest-first, whichever cursor is given.
+    /// - `before`: Get the newest messages older than this message ID
+    /// - `after`: Get the oldest messages newer than this message ID
     /// - `limit`: Maximum number of messages to return
     async fn find_by_channel(
         &self,
@@ -193,6 +243,11 @@ pub trait MessageRepository: Send + Sync {
         limit: i32,
     ) -> Result<Vec<Message>, AppError>;
 
+    /// Find messages by their IDs, leaving out deleted and ephemeral ones.
+    ///
+    /// The returned messages are in no particular order.
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<Message>, AppError>;
+
     /// Find pinned messages in a channel.
     async fn find_pinned(&self, channel_id: i64) -> Result<Vec<Message>, AppError>;
 
@@ -207,6 +262,33 @@ pub trait MessageRepository: Send + Sync {
     /// Create a new message.
     async fn create(&self, message: &Message) -> Result<Message, AppError>;
 
+    /// Create several messages with a single multi-row INSERT.
+    ///
+    /// Runs in one transaction, so either every message is created or none
+    /// is. A `MESSAGE_CREATE` gateway event is queued for each message, in
+    /// the order given. The returned messages are in no particular order.
+    async fn create_batch(&self, messages: &[Message]) -> Result<Vec<Message>, AppError>;
+
+    /// Create a new message and associate pending attachments with it.
+    ///
+    /// Runs in a single transaction; fails with a conflict (and creates
+    /// nothing) if any attachment was attached concurrently. A
+    /// `MESSAGE_CREATE` gateway event is queued in the same transaction.
+    async fn create_with_attachments(
+        &self,
+        message: &Message,
+        attachment_ids: &[i64],
+    ) -> Result<Message, AppError>;
+
+    /// Find attachments by their IDs.
+    async fn find_attachments_by_ids(&self, ids: &[i64]) -> Result<Vec<Attachment>, AppError>;
+
+    /// Find attachments for a set of messages, oldest first.
+    async fn find_attachments_by_message_ids(
+        &self,
+        message_ids: &[i64],
+    ) -> Result<Vec<Attachment>, AppError>;
+
     /// Update a message (for editing content).
     async fn update(&self, message: &Message) -> Result<Message, AppError>;
 
@@ -214,18 +296,44 @@ pub trait MessageRepository: Send + Sync {
     async fn delete(&self, id: i64) -> Result<(), AppError>;
 
     /// Bulk delete messages (up to 100 at a time).
-    async fn bulk_delete(&self, channel_id: i64, message_ids: Vec<i64>) -> Result<(), AppError>;
+    ///
+    /// Returns the IDs of the messages deleted; IDs from other channels and
+    /// messages that were already soft deleted are left out.
+    async fn bulk_delete(&self, channel_id: i64, message_ids: Vec<i64>) -> Result<Vec<i64>, AppError>;
 
-    /// Pin a message.
-    async fn pin(&self, id: i64) -> Result<(), AppError>;
+    /// Pin a message and create the system message announcing it.
+    ///
+    /// Runs in a single transaction that also queues the system message's
+    /// `MESSAGE_CREATE` gateway event. Returns `None`, creating nothing, if
+    /// the message was already pinned.
+    async fn pin(&self, id: i64, system_message: &Message) -> Result<Option<Message>, AppError>;
 
     /// Unpin a message.
     async fn unpin(&self, id: i64) -> Result<(), AppError>;
 
-    /// Get the count of messages in a channel.
+    /// Get the count of messages in a channel, excluding deleted ones.
     async fn count_by_channel(&self, channel_id: i64) -> Result<i64, AppError>;
 }
 
+/// Cached per-channel message counts, kept in step with message writes so
+/// reads can skip [`MessageRepository::count_by_channel`].
+#[async_trait]
+pub trait MessageCountStore: Send + Sync {
+    /// Cached message count for a channel, if any.
+    async fn get(&self, channel_id: i64) -> Result<Option<i64>, AppError>;
+
+    /// Store a count taken from the database.
+    ///
+    /// Returns the previously cached count so callers can log drift.
+    async fn reconcile(&self, channel_id: i64, count: i64) -> Result<Option<i64>, AppError>;
+
+    /// Record a new message in a channel.
+    async fn increment(&self, channel_id: i64) -> Result<(), AppError>;
+
+    /// Record deleted messages in a channel.
+    async fn decrement_by(&self, channel_id: i64, count: u64) -> Result<(), AppError>;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -373,6 +481,9 @@ mod tests {
             message_type: MessageType::Default,
             reply_to_id: None,
             pinned: false,
+            tts: false,
+            flags: 0,
+            embeds: Vec::new(),
             edited_at: None,
             created_at: Utc::now(),
         }
@@ -650,4 +761,31 @@ mod tests {
 
         assert_eq!(message.content_length(), 4000);
     }
+
+    #[test]
+    fn test_message_flags_set_and_clear_independently() {
+        let mut message = create_test_message();
+        assert_eq!(message.flags, 0);
+
+        message.set_flag(message_flags::SUPPRESS_EMBEDS, true);
+        message.set_flag(message_flags::CROSSPOSTED, true);
+        assert!(message.has_flag(message_flags::SUPPRESS_EMBEDS));
+        assert!(message.has_flag(message_flags::SUPPRESS_EMBEDS | message_flags::CROSSPOSTED));
+        assert!(!message.has_flag(message_flags::EPHEMERAL));
+
+        message.set_flag(message_flags::SUPPRESS_EMBEDS, false);
+        assert!(!message.has_flag(message_flags::SUPPRESS_EMBEDS));
+        assert_eq!(message.flags, message_flags::CROSSPOSTED);
+    }
+
+    #[test]
+    fn test_message_flag_bits_are_distinct() {
+        let flags = [
+            message_flags::CROSSPOSTED,
+            message_flags::SUPPRESS_EMBEDS,
+            message_flags::EPHEMERAL,
+        ];
+        assert_eq!(flags.iter().fold(0, |all, flag| all | flag).count_ones(), 3);
+        assert_eq!(message_flags::USER_SETTABLE & message_flags::EPHEMERAL, 0);
+    }
 }
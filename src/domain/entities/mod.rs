@@ -16,8 +16,11 @@
 //!
 //! - **Invite**: Server invite links with usage tracking
 //! - **Attachment**: File attachments on messages
+//! - **Embed**: Rich content blocks on messages
 //! - **Reaction**: Emoji reactions on messages
+//! - **ChannelOverride**: Per-user channel mutes and notification levels
 //! - **Session**: User sessions for JWT refresh token management
+//! - **VoiceState**: Ephemeral voice channel connection state (held in Redis)
 //!
 //! ## Repository Traits
 //!
@@ -28,32 +31,41 @@
 mod user;
 mod guild;
 mod channel;
+mod channel_override;
 mod message;
 mod role;
 mod member;
 mod invite;
 mod attachment;
+mod embed;
 mod reaction;
 mod session;
+mod voice_state;
 
 // Re-export User entity and related types
-pub use user::{User, UserStatus, UserRepository};
+pub use user::{OwnedGuildAction, PresenceVisibility, User, UserStatus, UserRepository};
 
 // Re-export Server/Guild entity and related types
 // Note: Server is the database table name, Guild is the API terminology
-pub use guild::{Server, Guild, ServerRepository, GuildRepository};
+pub use guild::{
+    DefaultMessageNotifications, ExplicitContentFilter, Guild, GuildRepository, Server,
+    ServerRepository,
+};
 
 // Re-export Channel entity and related types
-pub use channel::{Channel, ChannelType, PermissionOverwrite, ChannelRepository};
+pub use channel::{Channel, ChannelDeletion, ChannelType, PermissionOverwrite, ChannelRepository};
+
+// Re-export channel notification overrides
+pub use channel_override::{ChannelOverride, ChannelOverrideRepository, NotificationLevel};
 
 // Re-export Message entity and related types
-pub use message::{Message, MessageType, MessageRepository};
+pub use message::{message_flags, Message, MessageCountStore, MessageType, MessageRepository};
 
 // Re-export Role entity and related types
-pub use role::{Role, RoleRepository, permissions};
+pub use role::{Role, RoleCleanup, RoleRepository, permissions};
 
 // Re-export Member entity and related types
-pub use member::{Member, MemberRole, MemberRepository};
+pub use member::{Member, MemberOrder, MemberRole, MemberRepository};
 
 // Re-export Invite entity and related types
 pub use invite::{Invite, InviteRepository};
@@ -61,8 +73,14 @@ pub use invite::{Invite, InviteRepository};
 // Re-export Attachment entity and related types
 pub use attachment::{Attachment, AttachmentRepository, MAX_ATTACHMENT_SIZE};
 
+// Re-export Embed types
+pub use embed::{Embed, EmbedField, EmbedFooter, EmbedImage, MAX_EMBEDS};
+
 // Re-export Reaction entity and related types
 pub use reaction::{Reaction, ReactionCount, ReactionRepository};
 
 // Re-export Session entity and related types
-pub use session::{Session, DeviceType, SessionRepository};
+pub use session::{os_from_user_agent, Session, DeviceType, SessionRepository};
+
+// Re-export VoiceState entity
+pub use voice_state::VoiceState;
@@ -152,6 +152,25 @@ impl Role {
     pub fn color_hex(&self) -> Option<String> {
         self.color.map(|c| format!("#{:06X}", c))
     }
+
+    /// Sort roles highest position first, breaking ties by ID so the order
+    /// is stable.
+    pub fn sort_by_hierarchy(roles: &mut [Role]) {
+        roles.sort_by(|a, b| b.position.cmp(&a.position).then(a.id.cmp(&b.id)));
+    }
+}
+
+/// References removed together with a deleted role.
+///
+/// Returned by [`RoleRepository::delete_with_references`] so callers can
+/// invalidate any cached permissions derived from the role.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoleCleanup {
+    /// Channels that had a permission overwrite targeting the role
+    pub channel_ids: Vec<i64>,
+
+    /// Users that had the role assigned
+    pub user_ids: Vec<i64>,
 }
 
 impl Default for Role {
@@ -178,6 +197,12 @@ pub trait RoleRepository: Send + Sync {
     /// Find a role by its Snowflake ID.
     async fn find_by_id(&self, id: i64) -> Result<Option<Role>, AppError>;
 
+    /// Find several roles by ID in one query.
+    ///
+    /// Missing or deleted IDs are skipped. Results are ordered as by
+    /// [`Role::sort_by_hierarchy`].
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<Role>, AppError>;
+
     /// Find all roles in a server, ordered by position.
     async fn find_by_server_id(&self, server_id: i64) -> Result<Vec<Role>, AppError>;
 
@@ -188,15 +213,36 @@ pub trait RoleRepository: Send + Sync {
     /// Create a new role.
     async fn create(&self, role: &Role) -> Result<Role, AppError>;
 
+    /// Create a role after moving other roles to make room for it, in one
+    /// transaction. A non-empty move queues one `GUILD_ROLE_UPDATE` event.
+    async fn create_with_positions(&self, role: &Role, positions: Vec<(i64, i32)>) -> Result<Role, AppError>;
+
     /// Update an existing role.
     async fn update(&self, role: &Role) -> Result<Role, AppError>;
 
     /// Delete a role.
     async fn delete(&self, id: i64) -> Result<(), AppError>;
 
-    /// Update role positions (for reordering).
+    /// Delete a role together with its channel permission overwrites and
+    /// member assignments in a single transaction.
+    async fn delete_with_references(&self, id: i64) -> Result<RoleCleanup, AppError>;
+
+    /// Update role positions (for reordering) in one transaction, queueing
+    /// a single `GUILD_ROLE_UPDATE` event with the new positions.
     async fn update_positions(&self, server_id: i64, positions: Vec<(i64, i32)>) -> Result<(), AppError>;
 
+    /// Reorder a server's roles in one transaction.
+    ///
+    /// The server's roles are read under a lock that holds off other
+    /// reorders and role creation, `plan` turns them into the
+    /// `(role_id, position)` changes to write, and those are written with a
+    /// single `GUILD_ROLE_UPDATE` event. Nothing is written if `plan` fails
+    /// or returns no changes.
+    async fn reorder<F, E>(&self, server_id: i64, plan: F) -> Result<(), E>
+    where
+        F: FnOnce(Vec<Role>) -> Result<Vec<(i64, i32)>, E> + Send,
+        E: From<AppError> + Send;
+
     /// Get the highest role position for a server (for new role creation).
     async fn get_max_position(&self, server_id: i64) -> Result<i32, AppError>;
 }
@@ -576,6 +622,21 @@ mod tests {
         assert!(serialized.contains("\"hoist\":true"));
     }
 
+    #[test]
+    fn test_sort_by_hierarchy_is_stable() {
+        let role = |id, position| Role {
+            id,
+            position,
+            ..create_test_role()
+        };
+        let mut roles = vec![role(3, 1), role(1, 5), role(4, 1), role(2, 1)];
+
+        Role::sort_by_hierarchy(&mut roles);
+
+        let ids: Vec<i64> = roles.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+    }
+
     // ==========================================================================
     // Everyone Role Tests
     // ==========================================================================
@@ -31,12 +31,45 @@ impl DeviceType {
             "desktop" => Self::Desktop,
             "mobile" => Self::Mobile,
             "tablet" => Self::Tablet,
-            "browser" => Self::Browser,
+            "browser" | "web" => Self::Browser,
             "bot" => Self::Bot,
             _ => Self::Unknown,
         }
     }
 
+    /// Classify a client from its `User-Agent` header.
+    ///
+    /// Recognizes crawlers and HTTP libraries as bots, Electron as the
+    /// desktop app, native iOS/Android clients and mobile browsers as
+    /// mobile, and any other `Mozilla/` agent as a browser.
+    pub fn from_user_agent(user_agent: &str) -> Self {
+        let ua = user_agent.to_ascii_lowercase();
+        let has = |needle: &str| ua.contains(needle);
+
+        if ["bot", "crawler", "spider", "curl/", "wget/", "python-requests", "go-http-client"]
+            .iter()
+            .any(|n| has(n))
+        {
+            Self::Bot
+        } else if has("electron/") {
+            Self::Desktop
+        } else if has("ipad") || has("tablet") || (has("android") && !has("mobile")) {
+            Self::Tablet
+        } else if has("iphone")
+            || has("android")
+            || has("mobile")
+            || has("cfnetwork")
+            || has("okhttp")
+            || has_token(&ua, "ios")
+        {
+            Self::Mobile
+        } else if ua.starts_with("mozilla/") {
+            Self::Browser
+        } else {
+            Self::Unknown
+        }
+    }
+
     /// Convert to database string representation.
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -56,6 +89,34 @@ impl std::fmt::Display for DeviceType {
     }
 }
 
+/// Whether `token` appears as a whole alphanumeric word in `ua`.
+fn has_token(ua: &str, token: &str) -> bool {
+    ua.split(|c: char| !c.is_ascii_alphanumeric()).any(|t| t == token)
+}
+
+/// Operating system named in a `User-Agent` header, if recognizable.
+pub fn os_from_user_agent(user_agent: &str) -> Option<&'static str> {
+    let ua = user_agent.to_ascii_lowercase();
+    let has = |needle: &str| ua.contains(needle);
+
+    // iOS agents also claim "like Mac OS X" and Android ones "Linux"
+    if has("iphone") || has("ipad") || has("cfnetwork") || has_token(&ua, "ios") {
+        Some("iOS")
+    } else if has("android") {
+        Some("Android")
+    } else if has("windows") {
+        Some("Windows")
+    } else if has("mac os x") || has("macintosh") {
+        Some("macOS")
+    } else if has("cros") {
+        Some("ChromeOS")
+    } else if has("linux") {
+        Some("Linux")
+    } else {
+        None
+    }
+}
+
 /// Represents a user session for JWT refresh token management.
 ///
 /// Maps to the `user_sessions` table:
@@ -71,6 +132,7 @@ impl std::fmt::Display for DeviceType {
 /// - created_at: TIMESTAMPTZ NOT NULL DEFAULT NOW()
 /// - last_used_at: TIMESTAMPTZ NOT NULL DEFAULT NOW()
 /// - revoked_at: TIMESTAMPTZ NULL
+/// - persistent: BOOLEAN NOT NULL DEFAULT TRUE
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
     /// UUID primary key
@@ -110,6 +172,10 @@ pub struct Session {
 
     /// When the session was revoked (None if active)
     pub revoked_at: Option<DateTime<Utc>>,
+
+    /// Whether the login asked to be remembered; short-lived sessions get a
+    /// shorter refresh token lifetime and shouldn't be kept by the client
+    pub persistent: bool,
 }
 
 impl Session {
@@ -162,6 +228,7 @@ impl Session {
             created_at: now,
             last_used_at: now,
             revoked_at: None,
+            persistent: true,
         }
     }
 }
@@ -182,6 +249,7 @@ impl Default for Session {
             created_at: now,
             last_used_at: now,
             revoked_at: None,
+            persistent: true,
         }
     }
 }
@@ -234,3 +302,46 @@ pub trait SessionRepository: Send + Sync {
     /// Find sessions by IP address (for security monitoring).
     async fn find_by_ip(&self, ip_address: IpAddr) -> Result<Vec<Session>, AppError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHROME_WINDOWS: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 \
+        (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    const SAFARI_IPHONE: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) \
+        AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1";
+    const IOS_APP: &str = "ChatApp/2.3.1 (iOS 17.1; iPhone15,2) CFNetwork/1474 Darwin/23.1.0";
+    const ANDROID_TABLET: &str = "Mozilla/5.0 (Linux; Android 13; SM-X700) AppleWebKit/537.36 \
+        (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    const ELECTRON_MAC: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_1) AppleWebKit/537.36 \
+        (KHTML, like Gecko) ChatApp/1.0.0 Chrome/118.0.0.0 Electron/27.0.0 Safari/537.36";
+    const GOOGLEBOT: &str = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+
+    #[test]
+    fn test_classifies_common_user_agents() {
+        assert_eq!(DeviceType::from_user_agent(CHROME_WINDOWS), DeviceType::Browser);
+        assert_eq!(DeviceType::from_user_agent(SAFARI_IPHONE), DeviceType::Mobile);
+        assert_eq!(DeviceType::from_user_agent(IOS_APP), DeviceType::Mobile);
+        assert_eq!(DeviceType::from_user_agent(ANDROID_TABLET), DeviceType::Tablet);
+        assert_eq!(DeviceType::from_user_agent(ELECTRON_MAC), DeviceType::Desktop);
+    }
+
+    #[test]
+    fn test_bots_and_http_clients_are_bots() {
+        assert_eq!(DeviceType::from_user_agent(GOOGLEBOT), DeviceType::Bot);
+        assert_eq!(DeviceType::from_user_agent("curl/8.4.0"), DeviceType::Bot);
+        assert_eq!(DeviceType::from_user_agent("DiscordBot (https://example.com, 1.0)"), DeviceType::Bot);
+        assert_eq!(DeviceType::from_user_agent("something else"), DeviceType::Unknown);
+    }
+
+    #[test]
+    fn test_os_detection() {
+        assert_eq!(os_from_user_agent(CHROME_WINDOWS), Some("Windows"));
+        assert_eq!(os_from_user_agent(SAFARI_IPHONE), Some("iOS"));
+        assert_eq!(os_from_user_agent(IOS_APP), Some("iOS"));
+        assert_eq!(os_from_user_agent(ANDROID_TABLET), Some("Android"));
+        assert_eq!(os_from_user_agent(ELECTRON_MAC), Some("macOS"));
+        assert_eq!(os_from_user_agent("curl/8.4.0"), None);
+    }
+}
@@ -5,6 +5,7 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::shared::error::AppError;
 
@@ -42,6 +43,34 @@ impl UserStatus {
             Self::Invisible => "invisible",
         }
     }
+
+    /// Whether the status shows the user as online (online, idle, or dnd).
+    pub fn is_online(&self) -> bool {
+        matches!(self, Self::Online | Self::Idle | Self::Dnd)
+    }
+
+    /// Rank used when combining statuses from several sessions (higher wins).
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Online => 3,
+            Self::Idle => 2,
+            Self::Dnd => 1,
+            Self::Offline | Self::Invisible => 0,
+        }
+    }
+
+    /// Effective status of a user connected from several sessions.
+    ///
+    /// Precedence is online > idle > dnd > offline. Invisible sessions count
+    /// as offline, and no sessions at all means offline.
+    pub fn effective<'a>(statuses: impl IntoIterator<Item = &'a UserStatus>) -> UserStatus {
+        statuses
+            .into_iter()
+            .filter(|s| **s != Self::Invisible)
+            .max_by_key(|s| s.precedence())
+            .cloned()
+            .unwrap_or_default()
+    }
 }
 
 impl std::fmt::Display for UserStatus {
@@ -50,6 +79,47 @@ impl std::fmt::Display for UserStatus {
     }
 }
 
+/// Who may see a user's presence, stored as VARCHAR in the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PresenceVisibility {
+    /// Everyone sharing a guild with the user
+    #[default]
+    Everyone,
+    /// Only the user's friends
+    Friends,
+    /// Nobody; the user always appears offline
+    None,
+}
+
+impl PresenceVisibility {
+    /// Convert from database string representation.
+    pub fn parse_db(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "friends" => Self::Friends,
+            "none" => Self::None,
+            _ => Self::Everyone,
+        }
+    }
+
+    /// Convert to database string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Everyone => "everyone",
+            Self::Friends => "friends",
+            Self::None => "none",
+        }
+    }
+
+    /// Whether other members of the user's guilds may see their presence.
+    ///
+    /// There are no friendships yet, so friends-only presence is shown to
+    /// nobody else.
+    pub fn shared_with_guilds(&self) -> bool {
+        matches!(self, Self::Everyone)
+    }
+}
+
 /// Represents a user account in the chat system.
 ///
 /// Maps to the `users` table:
@@ -59,8 +129,11 @@ impl std::fmt::Display for UserStatus {
 /// - password_hash: VARCHAR(255) NOT NULL
 /// - display_name: VARCHAR(32) NULL
 /// - avatar_url: TEXT NULL
+/// - banner_url: TEXT NULL
 /// - status: VARCHAR(20) DEFAULT 'offline'
 /// - bio: TEXT NULL
+/// - presence_visibility: VARCHAR(10) NOT NULL DEFAULT 'everyone'
+/// - nsfw_allowed: BOOLEAN NOT NULL DEFAULT FALSE
 /// - created_at: TIMESTAMPTZ NOT NULL DEFAULT NOW()
 /// - updated_at: TIMESTAMPTZ NOT NULL DEFAULT NOW()
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,6 +157,10 @@ pub struct User {
     /// URL to user's avatar image
     pub avatar_url: Option<String>,
 
+    /// URL to user's profile banner image
+    #[serde(default)]
+    pub banner_url: Option<String>,
+
     /// User's online status
     #[serde(default)]
     pub status: UserStatus,
@@ -91,6 +168,14 @@ pub struct User {
     /// User's bio/about me text
     pub bio: Option<String>,
 
+    /// Who may see the user's presence
+    #[serde(default)]
+    pub presence_visibility: PresenceVisibility,
+
+    /// Whether the user opted in to viewing NSFW channels
+    #[serde(default)]
+    pub nsfw_allowed: bool,
+
     /// Account creation timestamp
     pub created_at: DateTime<Utc>,
 
@@ -106,7 +191,7 @@ impl User {
 
     /// Check if the user is currently online (online, idle, or dnd).
     pub fn is_online(&self) -> bool {
-        matches!(self.status, UserStatus::Online | UserStatus::Idle | UserStatus::Dnd)
+        self.status.is_online()
     }
 }
 
@@ -120,14 +205,26 @@ impl Default for User {
             password_hash: String::new(),
             display_name: None,
             avatar_url: None,
+            banner_url: None,
             status: UserStatus::default(),
             bio: None,
+            presence_visibility: PresenceVisibility::default(),
+            nsfw_allowed: false,
             created_at: now,
             updated_at: now,
         }
     }
 }
 
+/// What happens to a guild owned by an account being deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OwnedGuildAction {
+    /// Hand the guild over to another member
+    Transfer { server_id: i64, new_owner_id: i64 },
+    /// Delete the guild with its channels and messages
+    Delete { server_id: i64 },
+}
+
 /// Repository trait for User data access operations.
 ///
 /// Implementations of this trait handle the actual database interactions.
@@ -137,6 +234,14 @@ pub trait UserRepository: Send + Sync {
     /// Find a user by their Snowflake ID.
     async fn find_by_id(&self, id: i64) -> Result<Option<User>, AppError>;
 
+    /// Find several users by ID in one query. Missing IDs are skipped and
+    /// the order of the results is unspecified.
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<User>, AppError>;
+
+    /// Find the users who are friends with both users, ordered by
+    /// username.
+    async fn find_mutual_friends(&self, user_id: i64, other_user_id: i64) -> Result<Vec<User>, AppError>;
+
     /// Find a user by their email address.
     async fn find_by_email(&self, email: &str) -> Result<Option<User>, AppError>;
 
@@ -146,7 +251,8 @@ pub trait UserRepository: Send + Sync {
     /// Create a new user in the database.
     async fn create(&self, user: &User) -> Result<User, AppError>;
 
-    /// Update an existing user.
+    /// Update an existing user, queueing a `USER_UPDATE` event for the
+    /// user and members of their guilds in the same transaction.
     async fn update(&self, user: &User) -> Result<User, AppError>;
 
     /// Delete a user by ID.
@@ -160,6 +266,33 @@ pub trait UserRepository: Send + Sync {
 
     /// Update user's online status.
     async fn update_status(&self, id: i64, status: UserStatus) -> Result<(), AppError>;
+
+    /// Change a user's password in a single transaction.
+    ///
+    /// Replaces the password hash, deletes every auth session but
+    /// `session_id` and rotates that session's refresh token. Nothing is
+    /// changed if the user or the kept session doesn't exist. Returns the
+    /// IDs of the deleted sessions.
+    async fn change_password(
+        &self,
+        id: i64,
+        password_hash: &str,
+        session_id: Uuid,
+        refresh_token_hash: &str,
+        refresh_expires_at: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, AppError>;
+
+    /// Delete an account in a single transaction.
+    ///
+    /// Applies the owned guild actions, leaves all guilds, deletes all auth
+    /// sessions and overwrites the user row with the anonymized `user`,
+    /// marking it deleted. The row itself is kept so messages stay attached
+    /// to it. Returns the IDs of the deleted sessions.
+    async fn delete_account(
+        &self,
+        user: &User,
+        owned_guilds: &[OwnedGuildAction],
+    ) -> Result<Vec<Uuid>, AppError>;
 }
 
 #[cfg(test)]
@@ -231,6 +364,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_effective_status_precedence() {
+        use UserStatus::*;
+
+        assert_eq!(UserStatus::effective(&[Dnd, Idle, Online]), Online);
+        assert_eq!(UserStatus::effective(&[Dnd, Idle]), Idle);
+        assert_eq!(UserStatus::effective(&[Offline, Dnd]), Dnd);
+        assert_eq!(UserStatus::effective(&[Offline]), Offline);
+    }
+
+    #[test]
+    fn test_effective_status_invisible_and_empty_are_offline() {
+        assert_eq!(
+            UserStatus::effective(&[UserStatus::Invisible]),
+            UserStatus::Offline
+        );
+        assert_eq!(
+            UserStatus::effective(&[UserStatus::Invisible, UserStatus::Dnd]),
+            UserStatus::Dnd
+        );
+        assert_eq!(UserStatus::effective(&[]), UserStatus::Offline);
+    }
+
     #[test]
     fn test_user_status_as_str_values() {
         assert_eq!(UserStatus::Offline.as_str(), "offline");
@@ -261,8 +417,11 @@ mod tests {
             password_hash: "hashed_password".to_string(),
             display_name: None,
             avatar_url: None,
+            banner_url: None,
             status: UserStatus::Offline,
             bio: None,
+            presence_visibility: PresenceVisibility::Everyone,
+            nsfw_allowed: false,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
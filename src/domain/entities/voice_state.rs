@@ -0,0 +1,62 @@
+//! Voice state entity.
+//!
+//! Tracks which voice channel a user is connected to in a server, along with
+//! their mute and deafen flags. Voice state is ephemeral and held in Redis
+//! rather than the database. The server only handles signaling; no media is
+//! routed through it.
+
+use serde::{Deserialize, Serialize};
+
+/// A user's voice connection state within a server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoiceState {
+    /// User this state belongs to
+    pub user_id: i64,
+
+    /// Server the voice channel belongs to
+    pub server_id: i64,
+
+    /// Connected voice channel (None when disconnected)
+    pub channel_id: Option<i64>,
+
+    /// Gateway session that owns the voice connection
+    pub session_id: String,
+
+    /// Muted by the user
+    pub self_mute: bool,
+
+    /// Deafened by the user
+    pub self_deaf: bool,
+
+    /// Muted by a moderator
+    pub muted: bool,
+
+    /// Deafened by a moderator
+    pub deafened: bool,
+
+    /// Kept from speaking because the user lacks `SPEAK` in the channel
+    #[serde(default)]
+    pub suppress: bool,
+}
+
+impl VoiceState {
+    /// Create a disconnected voice state for a user in a server.
+    pub fn new(user_id: i64, server_id: i64, session_id: String) -> Self {
+        Self {
+            user_id,
+            server_id,
+            channel_id: None,
+            session_id,
+            self_mute: false,
+            self_deaf: false,
+            muted: false,
+            deafened: false,
+            suppress: false,
+        }
+    }
+
+    /// Check if the user is connected to a voice channel.
+    pub fn is_connected(&self) -> bool {
+        self.channel_id.is_some()
+    }
+}
@@ -36,13 +36,19 @@ impl PermissionService {
     /// Calculate a member's permissions in a specific channel.
     ///
     /// This applies channel permission overwrites to the base permissions.
+    /// Guild roles never apply to DM channels, which get no permissions
+    /// here; use [`Self::calculate_dm_permissions`] for them.
     pub fn calculate_channel_permissions(
         member: &Member,
-        _channel: &Channel,
+        channel: &Channel,
         overwrites: &[PermissionOverwrite],
         roles: &[Role],
         owner_id: i64,
     ) -> i64 {
+        if channel.is_dm() {
+            return 0;
+        }
+
         // Start with base permissions
         let mut permissions = Self::calculate_base_permissions(member, roles, owner_id);
 
@@ -89,6 +95,32 @@ impl PermissionService {
         permissions
     }
 
+    /// Check if a user is one of the recipients of a DM channel.
+    pub fn is_dm_participant(channel: &Channel, recipients: &[i64], user_id: i64) -> bool {
+        channel.is_dm() && recipients.contains(&user_id)
+    }
+
+    /// Calculate a user's permissions in a DM channel.
+    ///
+    /// Participants get the fixed [`Permissions::DM_PARTICIPANT`] set and
+    /// everyone else gets nothing.
+    pub fn calculate_dm_permissions(channel: &Channel, recipients: &[i64], user_id: i64) -> i64 {
+        if Self::is_dm_participant(channel, recipients, user_id) {
+            Permissions::DM_PARTICIPANT
+        } else {
+            0
+        }
+    }
+
+    /// Check if a user may open a channel given its NSFW flag.
+    ///
+    /// NSFW channels are open to users who allowed NSFW content and to
+    /// moderators, i.e. those with `MANAGE_MESSAGES` there, whatever their
+    /// own setting. Other channels are open to everyone.
+    pub fn can_view_nsfw(channel: &Channel, permissions: i64, nsfw_allowed: bool) -> bool {
+        !channel.nsfw || nsfw_allowed || Permissions::new(permissions).has(Permissions::MANAGE_MESSAGES)
+    }
+
     /// Check if a member can perform an action requiring specific permissions.
     pub fn can_perform(
         member: &Member,
@@ -102,6 +134,17 @@ impl PermissionService {
         permissions & required == required
     }
 
+    /// Check if an actor may grant `requested` permissions, e.g. on a role
+    /// or a channel overwrite.
+    ///
+    /// Actors can only hand out flags they hold themselves; the server owner
+    /// and administrators may grant anything.
+    pub fn can_grant(actor_permissions: i64, requested: i64, is_owner: bool) -> bool {
+        is_owner
+            || actor_permissions & Permissions::ADMINISTRATOR != 0
+            || requested & !actor_permissions == 0
+    }
+
     /// Check if a member can manage another member.
     ///
     /// A member can only manage others with lower role hierarchy.
@@ -154,6 +197,7 @@ impl PermissionService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::entities::ChannelType;
 
     // ==========================================================================
     // Test Helpers
@@ -485,6 +529,37 @@ mod tests {
         assert!(!can);
     }
 
+    // ==========================================================================
+    // can_grant Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_can_grant_permissions_actor_holds() {
+        let actor = Permissions::MANAGE_ROLES | Permissions::SEND_MESSAGES | Permissions::VIEW_CHANNEL;
+
+        assert!(PermissionService::can_grant(actor, Permissions::SEND_MESSAGES, false));
+        assert!(PermissionService::can_grant(actor, actor, false));
+        assert!(PermissionService::can_grant(actor, 0, false));
+    }
+
+    #[test]
+    fn test_can_grant_rejects_partial_grant() {
+        let actor = Permissions::MANAGE_ROLES | Permissions::SEND_MESSAGES;
+
+        // One held flag doesn't make up for one that isn't
+        let requested = Permissions::SEND_MESSAGES | Permissions::BAN_MEMBERS;
+        assert!(!PermissionService::can_grant(actor, requested, false));
+        assert!(!PermissionService::can_grant(actor, Permissions::ADMINISTRATOR, false));
+    }
+
+    #[test]
+    fn test_can_grant_admin_and_owner_bypass() {
+        let requested = Permissions::BAN_MEMBERS | Permissions::MANAGE_GUILD;
+
+        assert!(PermissionService::can_grant(Permissions::ADMINISTRATOR, requested, false));
+        assert!(PermissionService::can_grant(0, requested, true));
+    }
+
     // ==========================================================================
     // can_manage_member Tests
     // ==========================================================================
@@ -706,4 +781,99 @@ mod tests {
         // This documents the expected behavior: caller must filter correctly
         assert!(perms & Permissions::SEND_MESSAGES == 0);
     }
+
+    #[test]
+    fn test_deleted_role_overwrites_no_longer_apply() {
+        // Deleting a role removes both the assignment and its overwrites,
+        // so the channel falls back to the remaining roles' permissions.
+        let channel = create_test_channel(200, 100);
+        let roles = vec![
+            create_test_role(100, 100, 0, Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES),
+            create_test_role(101, 100, 1, 0),
+        ];
+
+        let member = create_test_member(2, 100, vec![101]);
+        let overwrites = vec![
+            create_test_overwrite(200, 101, "role", 0, Permissions::SEND_MESSAGES),
+        ];
+        let before = PermissionService::calculate_channel_permissions(
+            &member, &channel, &overwrites, &roles, 1
+        );
+        assert!(before & Permissions::SEND_MESSAGES == 0);
+
+        let member = create_test_member(2, 100, vec![]);
+        let overwrites: Vec<PermissionOverwrite> = vec![];
+        let after = PermissionService::calculate_channel_permissions(
+            &member, &channel, &overwrites, &roles[..1], 1
+        );
+        assert!(after & Permissions::SEND_MESSAGES != 0);
+    }
+
+    // ==========================================================================
+    // DM Channel Tests
+    // ==========================================================================
+
+    fn create_test_dm(id: i64) -> Channel {
+        Channel {
+            id,
+            server_id: None,
+            channel_type: ChannelType::Dm,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_dm_participant_gets_fixed_permission_set() {
+        let dm = create_test_dm(300);
+        let recipients = [1, 2];
+
+        assert!(PermissionService::is_dm_participant(&dm, &recipients, 1));
+        let perms = Permissions::new(PermissionService::calculate_dm_permissions(&dm, &recipients, 1));
+
+        assert!(perms.has(Permissions::VIEW_CHANNEL));
+        assert!(perms.has(Permissions::SEND_MESSAGES));
+        assert!(perms.has(Permissions::ATTACH_FILES));
+        assert!(perms.has(Permissions::READ_MESSAGE_HISTORY));
+        assert!(perms.has(Permissions::ADD_REACTIONS));
+        assert!(!perms.has(Permissions::MANAGE_MESSAGES));
+        assert!(!perms.has(Permissions::ADMINISTRATOR));
+    }
+
+    #[test]
+    fn test_dm_non_participant_gets_nothing() {
+        let dm = create_test_dm(300);
+        let recipients = [1, 2];
+
+        assert!(!PermissionService::is_dm_participant(&dm, &recipients, 3));
+        assert_eq!(PermissionService::calculate_dm_permissions(&dm, &recipients, 3), 0);
+
+        // Guild channels have no DM participants
+        let guild_channel = create_test_channel(200, 100);
+        assert!(!PermissionService::is_dm_participant(&guild_channel, &recipients, 1));
+    }
+
+    #[test]
+    fn test_guild_roles_do_not_apply_to_dm_channels() {
+        let dm = create_test_dm(300);
+        let member = create_test_member(1, 100, vec![]);
+        let roles = vec![create_test_role(100, 100, 0, Permissions::ALL)];
+
+        let perms = PermissionService::calculate_channel_permissions(&member, &dm, &[], &roles, 1);
+        assert_eq!(perms, 0);
+    }
+
+    #[test]
+    fn test_nsfw_channels_need_opt_in_or_moderation() {
+        let nsfw = Channel {
+            nsfw: true,
+            ..create_test_channel(200, 100)
+        };
+        let member = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+
+        assert!(!PermissionService::can_view_nsfw(&nsfw, member, false));
+        assert!(PermissionService::can_view_nsfw(&nsfw, member, true));
+        assert!(PermissionService::can_view_nsfw(&nsfw, member | Permissions::MANAGE_MESSAGES, false));
+        assert!(PermissionService::can_view_nsfw(&nsfw, Permissions::ADMINISTRATOR, false));
+        assert!(PermissionService::can_view_nsfw(&create_test_channel(201, 100), member, false));
+    }
 }
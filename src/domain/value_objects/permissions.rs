@@ -2,15 +2,33 @@
 //!
 //! Permissions are represented as a 64-bit bitfield where each bit
 //! represents a specific permission.
+//!
+//! In JSON a bitfield is written as a decimal string, for `JavaScript`
+//! `BigInt` compatibility. It is read from that string, a plain number, or
+//! an array of flag names such as `["KICK_MEMBERS", "BAN_MEMBERS"]`.
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// 64-bit permission bitfield.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(transparent)]
+#[serde(into = "String", try_from = "PermissionsRepr")]
 pub struct Permissions(pub i64);
 
+/// A permission name that matches no flag
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown permission: {0}")]
+pub struct UnknownPermission(pub String);
+
+/// Accepted JSON forms of a permission bitfield
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PermissionsRepr {
+    Bits(i64),
+    Text(String),
+    Names(Vec<String>),
+}
+
 impl Permissions {
     // General permissions
     /// Allows creation of instant invites
@@ -113,6 +131,58 @@ impl Permissions {
         | Self::USE_VAD
         | Self::CHANGE_NICKNAME;
 
+    /// Permissions every participant of a DM channel has
+    pub const DM_PARTICIPANT: i64 = Self::VIEW_CHANNEL
+        | Self::SEND_MESSAGES
+        | Self::ATTACH_FILES
+        | Self::READ_MESSAGE_HISTORY
+        | Self::ADD_REACTIONS;
+
+    /// Every flag with its name, lowest bit first
+    pub const FLAGS: &'static [(&'static str, i64)] = &[
+        ("CREATE_INSTANT_INVITE", Self::CREATE_INSTANT_INVITE),
+        ("KICK_MEMBERS", Self::KICK_MEMBERS),
+        ("BAN_MEMBERS", Self::BAN_MEMBERS),
+        ("ADMINISTRATOR", Self::ADMINISTRATOR),
+        ("MANAGE_CHANNELS", Self::MANAGE_CHANNELS),
+        ("MANAGE_GUILD", Self::MANAGE_GUILD),
+        ("ADD_REACTIONS", Self::ADD_REACTIONS),
+        ("VIEW_AUDIT_LOG", Self::VIEW_AUDIT_LOG),
+        ("PRIORITY_SPEAKER", Self::PRIORITY_SPEAKER),
+        ("STREAM", Self::STREAM),
+        ("VIEW_CHANNEL", Self::VIEW_CHANNEL),
+        ("SEND_MESSAGES", Self::SEND_MESSAGES),
+        ("SEND_TTS_MESSAGES", Self::SEND_TTS_MESSAGES),
+        ("MANAGE_MESSAGES", Self::MANAGE_MESSAGES),
+        ("EMBED_LINKS", Self::EMBED_LINKS),
+        ("ATTACH_FILES", Self::ATTACH_FILES),
+        ("READ_MESSAGE_HISTORY", Self::READ_MESSAGE_HISTORY),
+        ("MENTION_EVERYONE", Self::MENTION_EVERYONE),
+        ("USE_EXTERNAL_EMOJIS", Self::USE_EXTERNAL_EMOJIS),
+        ("VIEW_GUILD_INSIGHTS", Self::VIEW_GUILD_INSIGHTS),
+        ("CONNECT", Self::CONNECT),
+        ("SPEAK", Self::SPEAK),
+        ("MUTE_MEMBERS", Self::MUTE_MEMBERS),
+        ("DEAFEN_MEMBERS", Self::DEAFEN_MEMBERS),
+        ("MOVE_MEMBERS", Self::MOVE_MEMBERS),
+        ("USE_VAD", Self::USE_VAD),
+        ("CHANGE_NICKNAME", Self::CHANGE_NICKNAME),
+        ("MANAGE_NICKNAMES", Self::MANAGE_NICKNAMES),
+        ("MANAGE_ROLES", Self::MANAGE_ROLES),
+        ("MANAGE_WEBHOOKS", Self::MANAGE_WEBHOOKS),
+        ("MANAGE_EMOJIS_AND_STICKERS", Self::MANAGE_EMOJIS_AND_STICKERS),
+        ("USE_APPLICATION_COMMANDS", Self::USE_APPLICATION_COMMANDS),
+        ("REQUEST_TO_SPEAK", Self::REQUEST_TO_SPEAK),
+        ("MANAGE_EVENTS", Self::MANAGE_EVENTS),
+        ("MANAGE_THREADS", Self::MANAGE_THREADS),
+        ("CREATE_PUBLIC_THREADS", Self::CREATE_PUBLIC_THREADS),
+        ("CREATE_PRIVATE_THREADS", Self::CREATE_PRIVATE_THREADS),
+        ("USE_EXTERNAL_STICKERS", Self::USE_EXTERNAL_STICKERS),
+        ("SEND_MESSAGES_IN_THREADS", Self::SEND_MESSAGES_IN_THREADS),
+        ("USE_EMBEDDED_ACTIVITIES", Self::USE_EMBEDDED_ACTIVITIES),
+        ("MODERATE_MEMBERS", Self::MODERATE_MEMBERS),
+    ];
+
     /// Create a new Permissions instance.
     pub const fn new(bits: i64) -> Self {
         Self(bits)
@@ -129,6 +199,9 @@ impl Permissions {
     }
 
     /// Check if a specific permission is set.
+    ///
+    /// When `permission` combines several flags, all of them must be set;
+    /// use [`intersects`](Self::intersects) to check for any of them.
     pub const fn has(&self, permission: i64) -> bool {
         // Administrator overrides all
         if self.0 & Self::ADMINISTRATOR != 0 {
@@ -137,6 +210,33 @@ impl Permissions {
         self.0 & permission == permission
     }
 
+    /// Check if at least one of the flags in `flags` is set.
+    ///
+    /// Unlike [`has`](Self::has), which requires every flag, this is an
+    /// "any of" check, e.g. "can moderate at all". Administrators always
+    /// pass.
+    pub const fn intersects(&self, flags: i64) -> bool {
+        // Administrator overrides all
+        if self.0 & Self::ADMINISTRATOR != 0 {
+            return true;
+        }
+        self.0 & flags != 0
+    }
+
+    /// Check if at least one of the given permissions is set.
+    ///
+    /// Equivalent to [`intersects`](Self::intersects) with the flags OR-ed
+    /// together.
+    pub const fn any_of(&self, permissions: &[i64]) -> bool {
+        let mut flags = 0;
+        let mut i = 0;
+        while i < permissions.len() {
+            flags |= permissions[i];
+            i += 1;
+        }
+        self.intersects(flags)
+    }
+
     /// Check if administrator permission is set.
     pub const fn is_admin(&self) -> bool {
         self.0 & Self::ADMINISTRATOR != 0
@@ -167,6 +267,30 @@ impl Permissions {
         Self(self.0 & other.0)
     }
 
+    /// Look up a single flag by its name, e.g. `"KICK_MEMBERS"`.
+    pub fn flag(name: &str) -> Option<i64> {
+        Self::FLAGS
+            .iter()
+            .find(|(flag_name, _)| *flag_name == name)
+            .map(|(_, flag)| *flag)
+    }
+
+    /// Combine the named flags into a bitfield.
+    ///
+    /// Fails on the first name that matches no flag.
+    pub fn from_names<I, S>(names: I) -> Result<Self, UnknownPermission>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        names.into_iter().try_fold(Self::empty(), |perms, name| {
+            let name = name.as_ref();
+            Self::flag(name)
+                .map(|flag| Self(perms.0 | flag))
+                .ok_or_else(|| UnknownPermission(name.to_string()))
+        })
+    }
+
     /// Get the raw bits.
     pub const fn bits(&self) -> i64 {
         self.0
@@ -195,6 +319,27 @@ impl From<i64> for Permissions {
     }
 }
 
+impl From<Permissions> for String {
+    fn from(perms: Permissions) -> Self {
+        perms.to_string()
+    }
+}
+
+impl TryFrom<PermissionsRepr> for Permissions {
+    type Error = String;
+
+    fn try_from(repr: PermissionsRepr) -> Result<Self, Self::Error> {
+        match repr {
+            PermissionsRepr::Bits(bits) => Ok(Self(bits)),
+            PermissionsRepr::Text(text) => text
+                .parse()
+                .map(Self)
+                .map_err(|_| format!("Invalid permissions bitfield: {}", text)),
+            PermissionsRepr::Names(names) => Self::from_names(names).map_err(|e| e.to_string()),
+        }
+    }
+}
+
 impl From<Permissions> for i64 {
     fn from(perms: Permissions) -> Self {
         perms.0
@@ -585,6 +730,41 @@ mod tests {
         assert!(!perms.has(too_many));
     }
 
+    // ==========================================================================
+    // Any-Of Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_intersects_is_any_of() {
+        let perms = Permissions::new(Permissions::KICK_MEMBERS);
+        let moderation = Permissions::KICK_MEMBERS | Permissions::BAN_MEMBERS;
+
+        // All-of fails, any-of passes
+        assert!(!perms.has(moderation));
+        assert!(perms.intersects(moderation));
+
+        assert!(!perms.intersects(Permissions::BAN_MEMBERS | Permissions::MANAGE_ROLES));
+        assert!(!perms.intersects(0));
+    }
+
+    #[test]
+    fn test_any_of_matches_intersects() {
+        let perms = Permissions::new(Permissions::BAN_MEMBERS);
+
+        assert!(perms.any_of(&[Permissions::KICK_MEMBERS, Permissions::BAN_MEMBERS]));
+        assert!(!perms.any_of(&[Permissions::KICK_MEMBERS, Permissions::MODERATE_MEMBERS]));
+        assert!(!perms.any_of(&[]));
+    }
+
+    #[test]
+    fn test_admin_intersects_everything() {
+        let perms = Permissions::new(Permissions::ADMINISTRATOR);
+
+        assert!(perms.intersects(Permissions::KICK_MEMBERS | Permissions::BAN_MEMBERS));
+        assert!(perms.any_of(&[Permissions::MODERATE_MEMBERS]));
+        assert!(perms.any_of(&[]));
+    }
+
     // ==========================================================================
     // Administrator Override Tests
     // ==========================================================================
@@ -974,6 +1154,45 @@ mod tests {
         assert_eq!(p1, p2);
     }
 
+    // ==========================================================================
+    // Names and Serialization Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_from_names_combines_flags() {
+        let perms = Permissions::from_names(["KICK_MEMBERS", "BAN_MEMBERS"]).unwrap();
+        assert_eq!(perms.bits(), Permissions::KICK_MEMBERS | Permissions::BAN_MEMBERS);
+        assert_eq!(Permissions::from_names(Vec::<String>::new()).unwrap(), Permissions::empty());
+
+        let combined = Permissions::FLAGS.iter().fold(0, |bits, (_, flag)| bits | flag);
+        assert_eq!(combined, Permissions::ALL);
+    }
+
+    #[test]
+    fn test_from_names_rejects_unknown_names() {
+        assert_eq!(
+            Permissions::from_names(["KICK_MEMBERS", "kick_members"]),
+            Err(UnknownPermission("kick_members".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_deserialize_bitfield_or_names() {
+        let expected = Permissions::new(6);
+
+        for json in [r#""6""#, "6", r#"["KICK_MEMBERS","BAN_MEMBERS"]"#] {
+            assert_eq!(serde_json::from_str::<Permissions>(json).unwrap(), expected, "{}", json);
+        }
+        assert!(serde_json::from_str::<Permissions>(r#""six""#).is_err());
+        assert!(serde_json::from_str::<Permissions>(r#"["NOT_A_PERMISSION"]"#).is_err());
+    }
+
+    #[test]
+    fn test_serialize_as_string() {
+        let json = serde_json::to_string(&Permissions::new(Permissions::ADMINISTRATOR)).unwrap();
+        assert_eq!(json, r#""8""#);
+    }
+
     // ==========================================================================
     // Edge Cases
     // ==========================================================================
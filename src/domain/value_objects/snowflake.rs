@@ -185,6 +185,18 @@ mod tests {
         assert_eq!(created.year(), 2016);
     }
 
+    #[test]
+    fn test_created_at_matches_generation_time() {
+        let generator = crate::shared::snowflake::SnowflakeGenerator::new(1, 1);
+        let before = Utc::now().timestamp_millis();
+        let id = generator.generate();
+        let after = Utc::now().timestamp_millis();
+
+        // What the database records as created_at when the row is inserted
+        let derived = Snowflake::new(id).created_at().timestamp_millis();
+        assert!((before..=after).contains(&derived));
+    }
+
     #[test]
     fn test_snowflake_timestamp_roundtrip() {
         let timestamp = 1700000000000_u64;
@@ -6,6 +6,7 @@
 //! - A `Cache` trait defining common caching operations
 //! - A `RedisCache` implementation using Redis as the backing store
 //! - JSON serialization/deserialization for complex types
+//! - Raw string access for callers that manage their own encoding
 //!
 //! # Example
 //!
@@ -22,13 +23,13 @@
 //! ```
 
 use async_trait::async_trait;
-use redis::aio::ConnectionManager;
 use redis::AsyncCommands;
 use serde::{de::DeserializeOwned, Serialize};
 use std::sync::Arc;
 use tracing::{debug, instrument, warn};
 
 use crate::shared::error::AppError;
+use super::RedisPool;
 
 /// Generic cache trait for abstracting cache operations.
 ///
@@ -36,6 +37,15 @@ use crate::shared::error::AppError;
 /// allowing for different backend implementations (Redis, in-memory, etc.).
 ///
 /// All operations are async and return `Result<T, AppError>` for proper error handling.
+///
+/// # Typed vs raw values
+///
+/// `get`/`set` and friends serialize values as JSON, so a `String` is stored
+/// quoted and escaped. Use them for anything with a Rust type. Use
+/// `get_raw`/`set_raw`/`set_raw_ex` when the caller already holds the
+/// encoded form (e.g. a JSON payload it received or built itself) to store
+/// it byte-for-byte instead of encoding it a second time. A raw value that
+/// is valid JSON can still be read back with `get`.
 #[async_trait]
 pub trait Cache: Send + Sync {
     /// Retrieves a value from the cache by key.
@@ -77,6 +87,40 @@ pub trait Cache: Send + Sync {
         seconds: u64,
     ) -> Result<(), AppError>;
 
+    /// Retrieves a value as stored, without deserializing it.
+    ///
+    /// # Arguments
+    /// * `key` - The cache key to look up
+    ///
+    /// # Returns
+    /// * `Ok(Some(String))` - The stored string if the key exists
+    /// * `Ok(None)` - If the key does not exist
+    /// * `Err(AppError)` - If a cache error occurs
+    async fn get_raw(&self, key: &str) -> Result<Option<String>, AppError>;
+
+    /// Stores a string as-is, without serializing it, and without expiration.
+    ///
+    /// # Arguments
+    /// * `key` - The cache key
+    /// * `value` - The already-encoded value to store
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the value was stored successfully
+    /// * `Err(AppError)` - If a cache error occurs
+    async fn set_raw(&self, key: &str, value: &str) -> Result<(), AppError>;
+
+    /// Stores a string as-is, without serializing it, with an expiration time.
+    ///
+    /// # Arguments
+    /// * `key` - The cache key
+    /// * `value` - The already-encoded value to store
+    /// * `seconds` - Time-to-live in seconds
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the value was stored successfully
+    /// * `Err(AppError)` - If a cache error occurs
+    async fn set_raw_ex(&self, key: &str, value: &str, seconds: u64) -> Result<(), AppError>;
+
     /// Deletes a key from the cache.
     ///
     /// # Arguments
@@ -88,6 +132,18 @@ pub trait Cache: Send + Sync {
     /// * `Err(AppError)` - If a cache error occurs
     async fn delete(&self, key: &str) -> Result<bool, AppError>;
 
+    /// Deletes a key only if it holds `value`, atomically.
+    ///
+    /// # Arguments
+    /// * `key` - The cache key to delete
+    /// * `value` - The value the key must hold to be deleted
+    ///
+    /// # Returns
+    /// * `Ok(true)` - If the key held `value` and was deleted
+    /// * `Ok(false)` - If the key is missing or holds something else
+    /// * `Err(AppError)` - If a cache error occurs
+    async fn delete_if_eq<T: Serialize + Sync + Send>(&self, key: &str, value: &T) -> Result<bool, AppError>;
+
     /// Checks if a key exists in the cache.
     ///
     /// # Arguments
@@ -217,32 +273,136 @@ pub trait Cache: Send + Sync {
         &self,
         keys: &[&str],
     ) -> Result<Vec<Option<T>>, AppError>;
+
+    /// Sets multiple values with the same expiration in one round-trip.
+    ///
+    /// All entries are written atomically: either every key is set or none.
+    ///
+    /// # Arguments
+    /// * `entries` - Slice of `(key, value)` pairs to store
+    /// * `seconds` - Time-to-live in seconds, applied to every entry
+    ///
+    /// # Returns
+    /// * `Ok(())` - If all values were stored
+    /// * `Err(AppError)` - If a cache or serialization error occurs
+    async fn mset_ex<T: Serialize + Sync + Send>(
+        &self,
+        entries: &[(&str, &T)],
+        seconds: u64,
+    ) -> Result<(), AppError>;
+
+    /// Counts keys under a prefix without loading their values.
+    ///
+    /// The configured global prefix is applied before matching. Intended
+    /// for observability (e.g. the number of active sessions), so the
+    /// result is a point-in-time estimate rather than an exact count.
+    ///
+    /// # Arguments
+    /// * `prefix` - Key prefix to match (e.g. `keys::USER_SESSION`)
+    ///
+    /// # Returns
+    /// * `Ok(count)` - Number of keys matching the prefix
+    /// * `Err(AppError)` - If a cache error occurs
+    async fn count_by_prefix(&self, prefix: &str) -> Result<u64, AppError>;
+
+    /// Deletes every key under a prefix.
+    ///
+    /// The configured global prefix is applied before matching. Keys are
+    /// removed in batches, so keys written under the prefix while this runs
+    /// may survive it.
+    ///
+    /// # Arguments
+    /// * `prefix` - Key prefix to match (e.g. `"perms:channel:123:"`)
+    ///
+    /// # Returns
+    /// * `Ok(count)` - Number of keys deleted
+    /// * `Err(AppError)` - If a cache error occurs
+    async fn delete_by_prefix(&self, prefix: &str) -> Result<u64, AppError>;
+
+    /// Sets a field of a hash and refreshes the hash's expiry, atomically.
+    ///
+    /// Fields are updated independently, so concurrent writers to different
+    /// fields of the same hash never overwrite each other.
+    ///
+    /// # Arguments
+    /// * `key` - The cache key of the hash
+    /// * `field` - The field to set
+    /// * `value` - Raw string value, stored as-is
+    /// * `seconds` - Time-to-live of the whole hash in seconds
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the field was set
+    /// * `Err(AppError)` - If a cache error occurs
+    async fn hset_raw_ex(&self, key: &str, field: &str, value: &str, seconds: u64) -> Result<(), AppError>;
+
+    /// Removes a field from a hash. The hash goes away with its last field.
+    ///
+    /// # Arguments
+    /// * `key` - The cache key of the hash
+    /// * `field` - The field to remove
+    ///
+    /// # Returns
+    /// * `Ok(true)` - If the field existed and was removed
+    /// * `Ok(false)` - If the field did not exist
+    /// * `Err(AppError)` - If a cache error occurs
+    async fn hdel(&self, key: &str, field: &str) -> Result<bool, AppError>;
+
+    /// Retrieves the raw values of all fields of a hash.
+    ///
+    /// # Arguments
+    /// * `key` - The cache key of the hash
+    ///
+    /// # Returns
+    /// * `Ok(values)` - Field values in unspecified order (empty if the hash doesn't exist)
+    /// * `Err(AppError)` - If a cache error occurs
+    async fn hvals_raw(&self, key: &str) -> Result<Vec<String>, AppError>;
+
+    /// Publishes a message on a pub/sub channel.
+    ///
+    /// The configured prefix is applied to the channel name like to keys.
+    ///
+    /// # Arguments
+    /// * `channel` - The channel to publish on
+    /// * `message` - Raw message payload
+    ///
+    /// # Returns
+    /// * `Ok(count)` - Number of subscribers that received the message
+    /// * `Err(AppError)` - If a cache error occurs
+    async fn publish(&self, channel: &str, message: &str) -> Result<u64, AppError>;
+}
+
+/// Prefix of a namespaced view: `extra` appended to the cache's own prefix.
+pub(super) fn extend_prefix(prefix: Option<&str>, extra: &str) -> Arc<str> {
+    format!("{}{}", prefix.unwrap_or_default(), extra).into()
 }
 
 /// Redis-backed cache implementation.
 ///
-/// Uses a Redis ConnectionManager for efficient connection pooling and
+/// Uses a `RedisPool`, spreading commands over its connections with
 /// automatic reconnection handling.
 #[derive(Clone)]
 pub struct RedisCache {
-    /// Redis connection manager with automatic reconnection
-    conn: ConnectionManager,
+    /// Redis connection pool
+    conn: RedisPool,
     /// Optional key prefix for namespacing
     prefix: Option<Arc<str>>,
 }
 
 impl RedisCache {
+    /// Number of keys requested per `SCAN` iteration.
+    const SCAN_BATCH_SIZE: usize = 500;
+
     /// Creates a new RedisCache instance.
     ///
     /// # Arguments
-    /// * `conn` - Redis ConnectionManager instance
+    /// * `conn` - Redis connection pool
     ///
     /// # Example
     /// ```rust,ignore
-    /// let conn = ConnectionManager::new(client).await?;
+    /// let conn = create_redis_client(&settings).await?;
     /// let cache = RedisCache::new(conn);
     /// ```
-    pub fn new(conn: ConnectionManager) -> Self {
+    pub fn new(conn: RedisPool) -> Self {
         Self { conn, prefix: None }
     }
 
@@ -252,7 +412,7 @@ impl RedisCache {
     /// scenarios or logical separation of data.
     ///
     /// # Arguments
-    /// * `conn` - Redis ConnectionManager instance
+    /// * `conn` - Redis connection pool
     /// * `prefix` - Prefix to prepend to all keys
     ///
     /// # Example
@@ -260,13 +420,31 @@ impl RedisCache {
     /// let cache = RedisCache::with_prefix(conn, "chat:v1:");
     /// // key "user:123" becomes "chat:v1:user:123"
     /// ```
-    pub fn with_prefix(conn: ConnectionManager, prefix: impl Into<Arc<str>>) -> Self {
+    pub fn with_prefix(conn: RedisPool, prefix: impl Into<Arc<str>>) -> Self {
         Self {
             conn,
             prefix: Some(prefix.into()),
         }
     }
 
+    /// Returns a view of this cache whose keys are further prefixed with
+    /// `extra_prefix`, after any existing prefix.
+    ///
+    /// The view shares this cache's connections, so it is cheap to create.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let cache = RedisCache::with_prefix(conn, "chat:v1:");
+    /// let guild = cache.namespaced("guild:123:");
+    /// // key "name" becomes "chat:v1:guild:123:name"
+    /// ```
+    pub fn namespaced(&self, extra_prefix: &str) -> Self {
+        Self {
+            conn: self.conn.clone(),
+            prefix: Some(extend_prefix(self.prefix.as_deref(), extra_prefix)),
+        }
+    }
+
     /// Formats a key with the optional prefix.
     fn format_key(&self, key: &str) -> String {
         match &self.prefix {
@@ -343,6 +521,39 @@ impl Cache for RedisCache {
         Ok(())
     }
 
+    #[instrument(skip(self), level = "debug")]
+    async fn get_raw(&self, key: &str) -> Result<Option<String>, AppError> {
+        let full_key = self.format_key(key);
+        let mut conn = self.conn.clone();
+
+        let result: Option<String> = conn.get(&full_key).await?;
+        debug!(key = %full_key, hit = result.is_some(), "Cache get raw");
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self, value), level = "debug")]
+    async fn set_raw(&self, key: &str, value: &str) -> Result<(), AppError> {
+        let full_key = self.format_key(key);
+        let mut conn = self.conn.clone();
+
+        let _: () = conn.set(&full_key, value).await?;
+        debug!(key = %full_key, "Cache set raw");
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, value), level = "debug")]
+    async fn set_raw_ex(&self, key: &str, value: &str, seconds: u64) -> Result<(), AppError> {
+        let full_key = self.format_key(key);
+        let mut conn = self.conn.clone();
+
+        let _: () = conn.set_ex(&full_key, value, seconds).await?;
+        debug!(key = %full_key, ttl = seconds, "Cache set raw with expiry");
+
+        Ok(())
+    }
+
     #[instrument(skip(self), level = "debug")]
     async fn delete(&self, key: &str) -> Result<bool, AppError> {
         let full_key = self.format_key(key);
@@ -356,6 +567,30 @@ impl Cache for RedisCache {
         Ok(existed)
     }
 
+    #[instrument(skip(self, value), level = "debug")]
+    async fn delete_if_eq<T: Serialize + Sync + Send>(&self, key: &str, value: &T) -> Result<bool, AppError> {
+        let full_key = self.format_key(key);
+        let data = Self::serialize(value)?;
+        let mut conn = self.conn.clone();
+
+        // Compare and delete in one step, so a value written between the
+        // two is never deleted
+        let script = redis::Script::new(
+            r#"
+            if redis.call('GET', KEYS[1]) == ARGV[1] then
+                return redis.call('DEL', KEYS[1])
+            end
+            return 0
+            "#,
+        );
+        let deleted: u64 = script.key(&full_key).arg(data).invoke_async(&mut conn).await?;
+        let deleted = deleted > 0;
+
+        debug!(key = %full_key, deleted = deleted, "Cache delete if equal");
+
+        Ok(deleted)
+    }
+
     #[instrument(skip(self), level = "debug")]
     async fn exists(&self, key: &str) -> Result<bool, AppError> {
         let full_key = self.format_key(key);
@@ -523,6 +758,148 @@ impl Cache for RedisCache {
 
         Ok(values)
     }
+
+    #[instrument(skip(self, entries), fields(count = entries.len()), level = "debug")]
+    async fn mset_ex<T: Serialize + Sync + Send>(
+        &self,
+        entries: &[(&str, &T)],
+        seconds: u64,
+    ) -> Result<(), AppError> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        // Serialize everything up front so a bad value writes nothing
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for (key, value) in entries {
+            pipe.set_ex(self.format_key(key), Self::serialize(*value)?, seconds)
+                .ignore();
+        }
+
+        let mut conn = self.conn.clone();
+        let _: () = pipe.query_async(&mut conn).await?;
+        debug!(count = entries.len(), ttl = seconds, "Cache set many with expiry");
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn count_by_prefix(&self, prefix: &str) -> Result<u64, AppError> {
+        let pattern = format!("{}*", self.format_key(prefix));
+        let mut conn = self.conn.clone();
+        let mut cursor: u64 = 0;
+        let mut count: u64 = 0;
+
+        // SCAN is incremental, so this never blocks Redis the way KEYS would.
+        // Keys may be reported more than once if the keyspace is rehashed
+        // mid-scan, which is acceptable for an estimate.
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(Self::SCAN_BATCH_SIZE)
+                .query_async(&mut conn)
+                .await?;
+
+            count += keys.len() as u64;
+
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        debug!(pattern = %pattern, count = count, "Cache count by prefix");
+
+        Ok(count)
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn delete_by_prefix(&self, prefix: &str) -> Result<u64, AppError> {
+        let pattern = format!("{}*", self.format_key(prefix));
+        let mut conn = self.conn.clone();
+        let mut cursor: u64 = 0;
+        let mut deleted: u64 = 0;
+
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(Self::SCAN_BATCH_SIZE)
+                .query_async(&mut conn)
+                .await?;
+
+            if !keys.is_empty() {
+                let count: u64 = conn.del(keys.as_slice()).await?;
+                deleted += count;
+            }
+
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        debug!(pattern = %pattern, count = deleted, "Cache delete by prefix");
+
+        Ok(deleted)
+    }
+
+    #[instrument(skip(self, value), level = "debug")]
+    async fn hset_raw_ex(&self, key: &str, field: &str, value: &str, seconds: u64) -> Result<(), AppError> {
+        let full_key = self.format_key(key);
+        let mut conn = self.conn.clone();
+
+        let _: () = redis::pipe()
+            .atomic()
+            .hset(&full_key, field, value)
+            .ignore()
+            .expire(&full_key, seconds as i64)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+        debug!(key = %full_key, field = field, ttl = seconds, "Cache hash set with expiry");
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn hdel(&self, key: &str, field: &str) -> Result<bool, AppError> {
+        let full_key = self.format_key(key);
+        let mut conn = self.conn.clone();
+
+        let removed: u64 = conn.hdel(&full_key, field).await?;
+        debug!(key = %full_key, field = field, removed = removed > 0, "Cache hash delete");
+
+        Ok(removed > 0)
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn hvals_raw(&self, key: &str) -> Result<Vec<String>, AppError> {
+        let full_key = self.format_key(key);
+        let mut conn = self.conn.clone();
+
+        let values: Vec<String> = conn.hvals(&full_key).await?;
+        debug!(key = %full_key, count = values.len(), "Cache hash values");
+
+        Ok(values)
+    }
+
+    #[instrument(skip(self, message), level = "debug")]
+    async fn publish(&self, channel: &str, message: &str) -> Result<u64, AppError> {
+        let full_channel = self.format_key(channel);
+        let mut conn = self.conn.clone();
+
+        let receivers: u64 = conn.publish(&full_channel, message).await?;
+        debug!(channel = %full_channel, receivers = receivers, "Cache publish");
+
+        Ok(receivers)
+    }
 }
 
 impl std::fmt::Debug for RedisCache {
@@ -580,4 +957,10 @@ mod tests {
         };
         assert_eq!(result, "chat:v1:user:123");
     }
+
+    #[test]
+    fn test_namespaced_prefix_extends_existing_prefix() {
+        assert_eq!(&*extend_prefix(Some("chat:v1:"), "guild:123:"), "chat:v1:guild:123:");
+        assert_eq!(&*extend_prefix(None, "guild:123:"), "guild:123:");
+    }
 }
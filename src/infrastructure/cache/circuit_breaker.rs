@@ -0,0 +1,79 @@
+//! Cache Circuit Breaker
+//!
+//! Tracks whether the cache backend recently failed so callers can skip it
+//! for a short cool-off instead of waiting on a dead connection for every
+//! request.
+
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Default time to bypass the cache after a failure
+pub const DEFAULT_COOL_OFF: Duration = Duration::from_secs(30);
+
+/// Open/closed flag for a cache backend.
+///
+/// Cloning is cheap and clones share the same state, so one breaker can be
+/// shared by every service instance that talks to the same backend.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    /// When the breaker closes again; `None` while closed
+    open_until: Arc<Mutex<Option<Instant>>>,
+    cool_off: Duration,
+}
+
+impl CircuitBreaker {
+    /// Create a closed breaker with the given cool-off
+    pub fn new(cool_off: Duration) -> Self {
+        Self {
+            open_until: Arc::default(),
+            cool_off,
+        }
+    }
+
+    /// Whether the backend should currently be bypassed
+    pub fn is_open(&self) -> bool {
+        let mut open_until = self.open_until.lock();
+        match *open_until {
+            Some(until) if Instant::now() < until => true,
+            Some(_) => {
+                *open_until = None;
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Record a backend failure and open the breaker for the cool-off
+    pub fn trip(&self) {
+        *self.open_until.lock() = Some(Instant::now() + self.cool_off);
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(DEFAULT_COOL_OFF)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trip_opens_breaker() {
+        let breaker = CircuitBreaker::default();
+        assert!(!breaker.is_open());
+
+        breaker.trip();
+        assert!(breaker.is_open());
+        assert!(breaker.clone().is_open());
+    }
+
+    #[test]
+    fn test_breaker_closes_after_cool_off() {
+        let breaker = CircuitBreaker::new(Duration::ZERO);
+        breaker.trip();
+        assert!(!breaker.is_open());
+    }
+}
@@ -0,0 +1,85 @@
+//! Distributed Lock
+//!
+//! Short-lived locks shared by every server instance. A lock is the key
+//! `lock:{name}`, set only if absent and with an expiry so a crashed holder
+//! can't keep it forever. Each acquisition stores a random token, and only
+//! the holder of that token can release the lock early.
+
+use uuid::Uuid;
+
+use super::{keys, Cache, RedisCache, RedisPool};
+use crate::shared::error::AppError;
+
+/// Proof of holding a lock, needed to release it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockToken(String);
+
+/// Named locks over the shared cache
+#[derive(Clone)]
+pub struct DistributedLock<C: Cache = RedisCache> {
+    cache: C,
+}
+
+impl DistributedLock {
+    /// Create a new distributed lock
+    pub fn new(redis: RedisPool) -> Self {
+        Self::with_cache(RedisCache::new(redis))
+    }
+}
+
+impl<C: Cache> DistributedLock<C> {
+    /// Create a distributed lock over any cache backend
+    pub fn with_cache(cache: C) -> Self {
+        Self { cache }
+    }
+
+    /// Take the lock `name` for `ttl_secs`, unless someone else holds it.
+    ///
+    /// Returns `None` when the lock is already held.
+    pub async fn try_acquire(&self, name: &str, ttl_secs: u64) -> Result<Option<LockToken>, AppError> {
+        let token = Uuid::new_v4().to_string();
+        let acquired = self.cache.set_nx_ex(&keys::lock(name), &token, ttl_secs).await?;
+
+        Ok(acquired.then_some(LockToken(token)))
+    }
+
+    /// Release a lock before it expires.
+    ///
+    /// Returns false if the lock already expired or was taken by someone
+    /// else since, in which case it is left alone. The token is compared and
+    /// the lock deleted atomically, so a new holder's lock is never deleted.
+    pub async fn release(&self, name: &str, token: &LockToken) -> Result<bool, AppError> {
+        self.cache.delete_if_eq(&keys::lock(name), &token.0).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::cache::InMemoryCache;
+
+    #[tokio::test]
+    async fn test_lock_is_held_until_released() {
+        let lock = DistributedLock::with_cache(InMemoryCache::new());
+
+        let token = lock.try_acquire("job", 30).await.unwrap().unwrap();
+        assert_eq!(lock.try_acquire("job", 30).await.unwrap(), None);
+        // Other names are independent
+        assert!(lock.try_acquire("other", 30).await.unwrap().is_some());
+
+        assert!(lock.release("job", &token).await.unwrap());
+        assert!(lock.try_acquire("job", 30).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_stale_token_does_not_release_a_new_holder() {
+        let lock = DistributedLock::with_cache(InMemoryCache::new());
+
+        let stale = lock.try_acquire("job", 30).await.unwrap().unwrap();
+        lock.release("job", &stale).await.unwrap();
+        let _current = lock.try_acquire("job", 30).await.unwrap().unwrap();
+
+        assert!(!lock.release("job", &stale).await.unwrap());
+        assert_eq!(lock.try_acquire("job", 30).await.unwrap(), None);
+    }
+}
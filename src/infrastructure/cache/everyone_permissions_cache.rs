@@ -0,0 +1,49 @@
+//! @everyone Permissions Cache
+//!
+//! Caches the permissions of each guild's @everyone role. Every member
+//! holds @everyone, so it is part of nearly every guild permission check;
+//! keeping its permissions here saves a role lookup per check. Entries are
+//! dropped when the guild's roles are updated.
+
+use super::{keys, Cache, RedisCache, RedisPool};
+use crate::shared::error::AppError;
+
+/// How long @everyone permissions are kept if nothing invalidates them
+pub const EVERYONE_PERMISSIONS_TTL: u64 = 300;
+
+/// @everyone permissions cache service
+#[derive(Clone)]
+pub struct EveryonePermissionsCache<C: Cache = RedisCache> {
+    cache: C,
+}
+
+impl EveryonePermissionsCache {
+    /// Create a new @everyone permissions cache
+    pub fn new(redis: RedisPool) -> Self {
+        Self::with_cache(RedisCache::new(redis))
+    }
+}
+
+impl<C: Cache> EveryonePermissionsCache<C> {
+    /// Create an @everyone permissions cache over any cache backend
+    pub fn with_cache(cache: C) -> Self {
+        Self { cache }
+    }
+
+    /// Cached @everyone permissions of a guild
+    pub async fn get(&self, guild_id: i64) -> Result<Option<i64>, AppError> {
+        self.cache.get(&keys::everyone_permissions(guild_id)).await
+    }
+
+    /// Store a guild's @everyone permissions
+    pub async fn set(&self, guild_id: i64, permissions: i64) -> Result<(), AppError> {
+        self.cache
+            .set_ex(&keys::everyone_permissions(guild_id), &permissions, EVERYONE_PERMISSIONS_TTL)
+            .await
+    }
+
+    /// Drop a guild's cached @everyone permissions
+    pub async fn invalidate(&self, guild_id: i64) -> Result<bool, AppError> {
+        self.cache.delete(&keys::everyone_permissions(guild_id)).await
+    }
+}
@@ -0,0 +1,128 @@
+//! Failing Cache
+//!
+//! A [`Cache`] that is always unavailable, so the fallback paths of cache
+//! users can be tested without taking Redis down.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::Cache;
+use crate::shared::error::AppError;
+
+/// Cache whose every operation fails, counting the attempts
+#[derive(Clone, Default)]
+pub struct FailingCache {
+    calls: Arc<AtomicUsize>,
+}
+
+impl FailingCache {
+    /// Number of cache operations attempted so far
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    fn fail<T>(&self) -> Result<T, AppError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Err(AppError::Internal("Redis error: connection refused".to_string()))
+    }
+}
+
+#[async_trait]
+impl Cache for FailingCache {
+    async fn get<T: DeserializeOwned + Send>(&self, _: &str) -> Result<Option<T>, AppError> {
+        self.fail()
+    }
+    async fn set<T: Serialize + Sync + Send>(&self, _: &str, _: &T) -> Result<(), AppError> {
+        self.fail()
+    }
+    async fn set_ex<T: Serialize + Sync + Send>(
+        &self,
+        _: &str,
+        _: &T,
+        _: u64,
+    ) -> Result<(), AppError> {
+        self.fail()
+    }
+    async fn get_raw(&self, _: &str) -> Result<Option<String>, AppError> {
+        self.fail()
+    }
+    async fn set_raw(&self, _: &str, _: &str) -> Result<(), AppError> {
+        self.fail()
+    }
+    async fn set_raw_ex(&self, _: &str, _: &str, _: u64) -> Result<(), AppError> {
+        self.fail()
+    }
+    async fn delete(&self, _: &str) -> Result<bool, AppError> {
+        self.fail()
+    }
+    async fn delete_if_eq<T: Serialize + Sync + Send>(&self, _: &str, _: &T) -> Result<bool, AppError> {
+        self.fail()
+    }
+    async fn exists(&self, _: &str) -> Result<bool, AppError> {
+        self.fail()
+    }
+    async fn incr(&self, _: &str) -> Result<i64, AppError> {
+        self.fail()
+    }
+    async fn expire(&self, _: &str, _: u64) -> Result<bool, AppError> {
+        self.fail()
+    }
+    async fn ttl(&self, _: &str) -> Result<Option<i64>, AppError> {
+        self.fail()
+    }
+    async fn incr_by(&self, _: &str, _: i64) -> Result<i64, AppError> {
+        self.fail()
+    }
+    async fn decr(&self, _: &str) -> Result<i64, AppError> {
+        self.fail()
+    }
+    async fn set_nx<T: Serialize + Sync + Send>(&self, _: &str, _: &T) -> Result<bool, AppError> {
+        self.fail()
+    }
+    async fn set_nx_ex<T: Serialize + Sync + Send>(
+        &self,
+        _: &str,
+        _: &T,
+        _: u64,
+    ) -> Result<bool, AppError> {
+        self.fail()
+    }
+    async fn delete_many(&self, _: &[&str]) -> Result<u64, AppError> {
+        self.fail()
+    }
+    async fn get_many<T: DeserializeOwned + Send>(
+        &self,
+        _: &[&str],
+    ) -> Result<Vec<Option<T>>, AppError> {
+        self.fail()
+    }
+    async fn mset_ex<T: Serialize + Sync + Send>(
+        &self,
+        _: &[(&str, &T)],
+        _: u64,
+    ) -> Result<(), AppError> {
+        self.fail()
+    }
+    async fn count_by_prefix(&self, _: &str) -> Result<u64, AppError> {
+        self.fail()
+    }
+    async fn delete_by_prefix(&self, _: &str) -> Result<u64, AppError> {
+        self.fail()
+    }
+    async fn hset_raw_ex(&self, _: &str, _: &str, _: &str, _: u64) -> Result<(), AppError> {
+        self.fail()
+    }
+    async fn hdel(&self, _: &str, _: &str) -> Result<bool, AppError> {
+        self.fail()
+    }
+    async fn hvals_raw(&self, _: &str) -> Result<Vec<String>, AppError> {
+        self.fail()
+    }
+    async fn publish(&self, _: &str, _: &str) -> Result<u64, AppError> {
+        self.fail()
+    }
+}
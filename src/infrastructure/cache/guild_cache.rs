@@ -0,0 +1,68 @@
+//! Guild Cache
+//!
+//! Read-through cache of guild data under `guild:{guild_id}`. Guild
+//! settings and member counts change rarely but are read for nearly every
+//! invite and preview. Writers invalidate the entry; changes made elsewhere
+//! show up once it expires.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{keys, Cache, RedisCache, RedisPool};
+use crate::shared::error::AppError;
+
+/// How long guild data is served from the cache
+pub const GUILD_CACHE_TTL: u64 = 300;
+
+/// Guild data cache service
+#[derive(Clone)]
+pub struct GuildCache<C: Cache = RedisCache> {
+    cache: C,
+}
+
+impl GuildCache {
+    /// Create a new guild cache
+    pub fn new(redis: RedisPool) -> Self {
+        Self::with_cache(RedisCache::new(redis))
+    }
+}
+
+impl<C: Cache> GuildCache<C> {
+    /// Create a guild cache over any cache backend
+    pub fn with_cache(cache: C) -> Self {
+        Self { cache }
+    }
+
+    /// Cached guild data, if present
+    pub async fn get<T: DeserializeOwned + Send>(&self, guild_id: i64) -> Result<Option<T>, AppError> {
+        self.cache.get(&keys::guild(guild_id)).await
+    }
+
+    /// Store guild data for [`GUILD_CACHE_TTL`]
+    pub async fn set<T: Serialize + Sync + Send>(&self, guild_id: i64, guild: &T) -> Result<(), AppError> {
+        self.cache.set_ex(&keys::guild(guild_id), guild, GUILD_CACHE_TTL).await
+    }
+
+    /// Drop a guild's cached data after it changed
+    pub async fn invalidate(&self, guild_id: i64) -> Result<(), AppError> {
+        self.cache.delete(&keys::guild(guild_id)).await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::cache::InMemoryCache;
+
+    #[tokio::test]
+    async fn test_invalidated_guild_is_gone() {
+        let cache = GuildCache::with_cache(InMemoryCache::new());
+
+        cache.set(1, &"guild one").await.unwrap();
+        cache.set(2, &"guild two").await.unwrap();
+        assert_eq!(cache.get::<String>(1).await.unwrap().as_deref(), Some("guild one"));
+
+        cache.invalidate(1).await.unwrap();
+        assert_eq!(cache.get::<String>(1).await.unwrap(), None);
+        assert_eq!(cache.get::<String>(2).await.unwrap().as_deref(), Some("guild two"));
+    }
+}
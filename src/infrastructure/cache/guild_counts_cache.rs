@@ -0,0 +1,60 @@
+//! Guild Counts Cache
+//!
+//! Caches approximate member and online counts per guild for public
+//! previews. Counting online members means reading the presence of every
+//! member, so results are kept for a short while instead of recounted on
+//! every request; the counts are approximate anyway.
+
+use serde::{Deserialize, Serialize};
+
+use super::{keys, Cache, RedisCache, RedisPool};
+use crate::shared::error::AppError;
+
+/// How long counts are reused before being recounted
+pub const GUILD_COUNTS_TTL: u64 = 60;
+
+/// Approximate member and online counts of a guild
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuildCounts {
+    pub member_count: i64,
+    /// Members whose presence is online, idle or dnd
+    pub online_count: i64,
+}
+
+/// Guild counts cache service
+#[derive(Clone)]
+pub struct GuildCountsCache<C: Cache = RedisCache> {
+    cache: C,
+}
+
+impl GuildCountsCache {
+    /// Create a new guild counts cache
+    pub fn new(redis: RedisPool) -> Self {
+        Self::with_cache(RedisCache::new(redis))
+    }
+}
+
+impl<C: Cache> GuildCountsCache<C> {
+    /// Create a guild counts cache over any cache backend
+    pub fn with_cache(cache: C) -> Self {
+        Self { cache }
+    }
+
+    /// Cached counts for a guild, if still fresh
+    pub async fn get(&self, guild_id: i64) -> Result<Option<GuildCounts>, AppError> {
+        self.cache.get(&keys::guild_counts(guild_id)).await
+    }
+
+    /// Store freshly taken counts
+    pub async fn set(&self, guild_id: i64, counts: &GuildCounts) -> Result<(), AppError> {
+        self.cache
+            .set_ex(&keys::guild_counts(guild_id), counts, GUILD_COUNTS_TTL)
+            .await
+    }
+
+    /// Drop a guild's counts so the next read recounts
+    pub async fn invalidate(&self, guild_id: i64) -> Result<(), AppError> {
+        self.cache.delete(&keys::guild_counts(guild_id)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+//! Login Throttle
+//!
+//! Counts failed logins per account and locks the account out with a
+//! progressively longer cooldown once too many attempts have failed.
+//!
+//! Attempts are keyed by the normalized email rather than the user ID, so an
+//! address without an account is throttled exactly like one with an account
+//! and lockouts don't reveal which emails are registered.
+//!
+//! Two keys are kept per email: `login:failures:{email}` counts failures
+//! since the last successful login, and `login:lockout:{email}` exists while
+//! the account is locked out, expiring when the cooldown ends.
+
+use super::{keys, Cache, RedisCache, RedisPool};
+use crate::shared::error::AppError;
+
+/// Failed attempts allowed before the first lockout
+pub const LOCKOUT_THRESHOLD: u32 = 5;
+
+/// Cooldown after reaching the threshold; doubles with each further failure
+pub const BASE_LOCKOUT_SECS: u64 = 30;
+
+/// Longest cooldown imposed
+pub const MAX_LOCKOUT_SECS: u64 = 60 * 60;
+
+/// Failures are forgotten after this long without another failure
+pub const FAILURE_WINDOW_SECS: u64 = 24 * 60 * 60;
+
+/// Cooldown imposed after `failures` consecutive failed logins, if any.
+///
+/// Reaching [`LOCKOUT_THRESHOLD`] locks the account for
+/// [`BASE_LOCKOUT_SECS`]; every failure after that doubles the cooldown, up
+/// to [`MAX_LOCKOUT_SECS`].
+pub fn lockout_secs(failures: u32) -> Option<u64> {
+    let excess = failures.checked_sub(LOCKOUT_THRESHOLD)?;
+
+    Some(
+        2u64.checked_pow(excess)
+            .and_then(|factor| BASE_LOCKOUT_SECS.checked_mul(factor))
+            .map_or(MAX_LOCKOUT_SECS, |secs| secs.min(MAX_LOCKOUT_SECS)),
+    )
+}
+
+/// Per-account failed login throttle
+#[derive(Clone)]
+pub struct LoginThrottle<C: Cache = RedisCache> {
+    cache: C,
+}
+
+impl LoginThrottle {
+    /// Create a new login throttle
+    pub fn new(redis: RedisPool) -> Self {
+        Self::with_cache(RedisCache::new(redis))
+    }
+}
+
+impl<C: Cache> LoginThrottle<C> {
+    /// Create a login throttle over any cache backend
+    pub fn with_cache(cache: C) -> Self {
+        Self { cache }
+    }
+
+    /// Seconds until the account may try again, or `None` if it isn't locked out
+    pub async fn retry_after(&self, email: &str) -> Result<Option<u64>, AppError> {
+        let ttl = self.cache.ttl(&keys::login_lockout(&normalize(email))).await?;
+        Ok(ttl.map(|secs| secs.max(1) as u64))
+    }
+
+    /// Record a failed login, returning the cooldown it triggered, if any
+    pub async fn record_failure(&self, email: &str) -> Result<Option<u64>, AppError> {
+        let email = normalize(email);
+        let failures_key = keys::login_failures(&email);
+
+        let failures = self.cache.incr(&failures_key).await?;
+        self.cache.expire(&failures_key, FAILURE_WINDOW_SECS).await?;
+
+        let lockout = lockout_secs(u32::try_from(failures).unwrap_or(u32::MAX));
+        if let Some(secs) = lockout {
+            self.cache
+                .set_raw_ex(&keys::login_lockout(&email), "1", secs)
+                .await?;
+        }
+
+        Ok(lockout)
+    }
+
+    /// Forget failed logins after a successful one
+    pub async fn reset(&self, email: &str) -> Result<(), AppError> {
+        let email = normalize(email);
+        let failures_key = keys::login_failures(&email);
+        let lockout_key = keys::login_lockout(&email);
+
+        self.cache.delete_many(&[failures_key.as_str(), lockout_key.as_str()]).await?;
+        Ok(())
+    }
+}
+
+/// Emails are matched case-insensitively
+fn normalize(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::cache::InMemoryCache;
+
+    fn throttle() -> LoginThrottle<InMemoryCache> {
+        LoginThrottle::with_cache(InMemoryCache::new())
+    }
+
+    #[test]
+    fn test_lockout_doubles_after_threshold() {
+        let cooldowns: Vec<Option<u64>> = (1..=8).map(lockout_secs).collect();
+
+        assert_eq!(
+            cooldowns,
+            vec![None, None, None, None, Some(30), Some(60), Some(120), Some(240)]
+        );
+        assert_eq!(lockout_secs(20), Some(MAX_LOCKOUT_SECS));
+        assert_eq!(lockout_secs(u32::MAX), Some(MAX_LOCKOUT_SECS));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_failures_lock_the_account() {
+        let throttle = throttle();
+
+        for _ in 1..LOCKOUT_THRESHOLD {
+            assert_eq!(throttle.record_failure("a@example.com").await.unwrap(), None);
+        }
+        assert_eq!(throttle.retry_after("a@example.com").await.unwrap(), None);
+
+        assert_eq!(throttle.record_failure("a@example.com").await.unwrap(), Some(30));
+        assert_eq!(throttle.record_failure("A@Example.com").await.unwrap(), Some(60));
+
+        let retry_after = throttle.retry_after("a@example.com").await.unwrap().unwrap();
+        assert!((59..=60).contains(&retry_after));
+        assert_eq!(throttle.retry_after("b@example.com").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_reset_clears_failures_and_lockout() {
+        let throttle = throttle();
+        for _ in 0..LOCKOUT_THRESHOLD {
+            throttle.record_failure("a@example.com").await.unwrap();
+        }
+
+        throttle.reset("a@example.com").await.unwrap();
+
+        assert_eq!(throttle.retry_after("a@example.com").await.unwrap(), None);
+        // Counting starts over
+        assert_eq!(throttle.record_failure("a@example.com").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,580 @@
+//! In-Memory Cache
+//!
+//! Process-local `Cache` implementation backed by a `HashMap`.
+//!
+//! Values are stored as JSON strings with an optional expiry, mirroring how
+//! `RedisCache` stores them; hashes are stored as a JSON object of their
+//! fields. Expired entries are dropped lazily on access. Published messages
+//! go to the receivers handed out by [`InMemoryCache::subscribe`].
+//! Intended for tests and single-node development setups; it is not shared
+//! between processes.
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use super::cache_service::{extend_prefix, Cache};
+use crate::shared::error::AppError;
+
+/// A stored value and its optional expiry.
+#[derive(Debug, Clone)]
+struct Entry {
+    value: String,
+    expires_at: Option<Instant>,
+}
+
+impl Entry {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.map(|at| at <= now).unwrap_or(false)
+    }
+}
+
+/// Sender half of a subscription to published `(channel, message)` pairs.
+type Subscriber = mpsc::UnboundedSender<(String, String)>;
+
+/// In-memory cache implementation.
+///
+/// Cloning is cheap and clones share the same underlying store.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryCache {
+    /// Stored entries keyed by full (prefixed) key
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+    /// Optional key prefix for namespacing
+    prefix: Option<Arc<str>>,
+    /// Receivers of published `(channel, message)` pairs
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+}
+
+impl InMemoryCache {
+    /// Creates an empty in-memory cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty in-memory cache with a key prefix.
+    pub fn with_prefix(prefix: impl Into<Arc<str>>) -> Self {
+        Self {
+            prefix: Some(prefix.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Returns a view of this cache whose keys are further prefixed with
+    /// `extra_prefix`, after any existing prefix. The view shares this
+    /// cache's entries and subscribers.
+    pub fn namespaced(&self, extra_prefix: &str) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            prefix: Some(extend_prefix(self.prefix.as_deref(), extra_prefix)),
+            subscribers: self.subscribers.clone(),
+        }
+    }
+
+    /// Receive every message published from now on, as `(channel, message)`
+    /// pairs with the prefix applied to the channel.
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<(String, String)> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.subscribers.lock().push(tx);
+        rx
+    }
+
+    /// Formats a key with the optional prefix.
+    fn format_key(&self, key: &str) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}{}", prefix, key),
+            None => key.to_string(),
+        }
+    }
+
+    /// Serializes a value to JSON string.
+    fn serialize<T: Serialize>(value: &T) -> Result<String, AppError> {
+        serde_json::to_string(value)
+            .map_err(|e| AppError::Internal(format!("Cache serialization failed: {}", e)))
+    }
+
+    /// Deserializes a JSON string to the target type.
+    fn deserialize<T: DeserializeOwned>(data: &str) -> Result<T, AppError> {
+        serde_json::from_str(data)
+            .map_err(|e| AppError::Internal(format!("Cache deserialization failed: {}", e)))
+    }
+
+    /// Get the live entry for a full key, dropping it if expired.
+    fn live_entry<'a>(
+        entries: &'a mut HashMap<String, Entry>,
+        full_key: &str,
+    ) -> Option<&'a mut Entry> {
+        let now = Instant::now();
+        if entries.get(full_key).map(|e| e.is_expired(now)).unwrap_or(false) {
+            entries.remove(full_key);
+        }
+        entries.get_mut(full_key)
+    }
+
+    /// Add `delta` to an integer value, creating it as 0 if missing.
+    ///
+    /// Like Redis, an existing TTL is preserved.
+    fn add(&self, key: &str, delta: i64) -> Result<i64, AppError> {
+        let full_key = self.format_key(key);
+        let mut entries = self.entries.lock();
+
+        let entry = match Self::live_entry(&mut entries, &full_key) {
+            Some(entry) => entry,
+            None => entries.entry(full_key).or_insert(Entry {
+                value: "0".to_string(),
+                expires_at: None,
+            }),
+        };
+
+        let current: i64 = entry.value.parse().map_err(|_| {
+            AppError::Internal("Cache value is not an integer".to_string())
+        })?;
+        let value = current + delta;
+        entry.value = value.to_string();
+
+        Ok(value)
+    }
+
+    /// Insert a value, replacing any existing entry and its TTL.
+    fn insert(&self, key: &str, value: String, seconds: Option<u64>) {
+        let expires_at = seconds.map(|s| Instant::now() + Duration::from_secs(s));
+        self.entries
+            .lock()
+            .insert(self.format_key(key), Entry { value, expires_at });
+    }
+
+    /// Apply `update` to the fields of a hash, creating it if missing.
+    ///
+    /// The hash's TTL is reset to `seconds` if given and kept otherwise.
+    /// The hash is removed once it has no fields left. Returns what
+    /// `update` returned.
+    fn update_hash<T>(
+        &self,
+        key: &str,
+        seconds: Option<u64>,
+        update: impl FnOnce(&mut HashMap<String, String>) -> T,
+    ) -> Result<T, AppError> {
+        let full_key = self.format_key(key);
+        let mut entries = self.entries.lock();
+
+        let (mut fields, expires_at) = match Self::live_entry(&mut entries, &full_key) {
+            Some(entry) => (Self::deserialize(&entry.value)?, entry.expires_at),
+            None => (HashMap::new(), None),
+        };
+        let result = update(&mut fields);
+        let expires_at = seconds
+            .map(|s| Instant::now() + Duration::from_secs(s))
+            .or(expires_at);
+
+        if fields.is_empty() {
+            entries.remove(&full_key);
+        } else {
+            let value = Self::serialize(&fields)?;
+            entries.insert(full_key, Entry { value, expires_at });
+        }
+
+        Ok(result)
+    }
+
+    /// Insert a value only if no live entry exists.
+    fn insert_nx(&self, key: &str, value: String, seconds: Option<u64>) -> bool {
+        let full_key = self.format_key(key);
+        let mut entries = self.entries.lock();
+
+        if Self::live_entry(&mut entries, &full_key).is_some() {
+            return false;
+        }
+
+        let expires_at = seconds.map(|s| Instant::now() + Duration::from_secs(s));
+        entries.insert(full_key, Entry { value, expires_at });
+        true
+    }
+}
+
+#[async_trait]
+impl Cache for InMemoryCache {
+    async fn get<T: DeserializeOwned + Send>(&self, key: &str) -> Result<Option<T>, AppError> {
+        let full_key = self.format_key(key);
+        let mut entries = self.entries.lock();
+
+        match Self::live_entry(&mut entries, &full_key) {
+            Some(entry) => Ok(Some(Self::deserialize(&entry.value)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn set<T: Serialize + Sync + Send>(&self, key: &str, value: &T) -> Result<(), AppError> {
+        self.insert(key, Self::serialize(value)?, None);
+        Ok(())
+    }
+
+    async fn set_ex<T: Serialize + Sync + Send>(
+        &self,
+        key: &str,
+        value: &T,
+        seconds: u64,
+    ) -> Result<(), AppError> {
+        self.insert(key, Self::serialize(value)?, Some(seconds));
+        Ok(())
+    }
+
+    async fn get_raw(&self, key: &str) -> Result<Option<String>, AppError> {
+        let full_key = self.format_key(key);
+        let mut entries = self.entries.lock();
+
+        Ok(Self::live_entry(&mut entries, &full_key).map(|entry| entry.value.clone()))
+    }
+
+    async fn set_raw(&self, key: &str, value: &str) -> Result<(), AppError> {
+        self.insert(key, value.to_string(), None);
+        Ok(())
+    }
+
+    async fn set_raw_ex(&self, key: &str, value: &str, seconds: u64) -> Result<(), AppError> {
+        self.insert(key, value.to_string(), Some(seconds));
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, AppError> {
+        let full_key = self.format_key(key);
+        let mut entries = self.entries.lock();
+
+        let existed = Self::live_entry(&mut entries, &full_key).is_some();
+        entries.remove(&full_key);
+
+        Ok(existed)
+    }
+
+    async fn delete_if_eq<T: Serialize + Sync + Send>(&self, key: &str, value: &T) -> Result<bool, AppError> {
+        let full_key = self.format_key(key);
+        let value = Self::serialize(value)?;
+        let mut entries = self.entries.lock();
+
+        let matches = Self::live_entry(&mut entries, &full_key).is_some_and(|entry| entry.value == value);
+        if matches {
+            entries.remove(&full_key);
+        }
+
+        Ok(matches)
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, AppError> {
+        let full_key = self.format_key(key);
+        let mut entries = self.entries.lock();
+
+        Ok(Self::live_entry(&mut entries, &full_key).is_some())
+    }
+
+    async fn incr(&self, key: &str) -> Result<i64, AppError> {
+        self.add(key, 1)
+    }
+
+    async fn expire(&self, key: &str, seconds: u64) -> Result<bool, AppError> {
+        let full_key = self.format_key(key);
+        let mut entries = self.entries.lock();
+
+        match Self::live_entry(&mut entries, &full_key) {
+            Some(entry) => {
+                entry.expires_at = Some(Instant::now() + Duration::from_secs(seconds));
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn ttl(&self, key: &str) -> Result<Option<i64>, AppError> {
+        let full_key = self.format_key(key);
+        let mut entries = self.entries.lock();
+
+        let now = Instant::now();
+        Ok(Self::live_entry(&mut entries, &full_key)
+            .and_then(|entry| entry.expires_at)
+            .map(|at| at.saturating_duration_since(now).as_secs() as i64))
+    }
+
+    async fn incr_by(&self, key: &str, delta: i64) -> Result<i64, AppError> {
+        self.add(key, delta)
+    }
+
+    async fn decr(&self, key: &str) -> Result<i64, AppError> {
+        self.add(key, -1)
+    }
+
+    async fn set_nx<T: Serialize + Sync + Send>(
+        &self,
+        key: &str,
+        value: &T,
+    ) -> Result<bool, AppError> {
+        Ok(self.insert_nx(key, Self::serialize(value)?, None))
+    }
+
+    async fn set_nx_ex<T: Serialize + Sync + Send>(
+        &self,
+        key: &str,
+        value: &T,
+        seconds: u64,
+    ) -> Result<bool, AppError> {
+        Ok(self.insert_nx(key, Self::serialize(value)?, Some(seconds)))
+    }
+
+    async fn delete_many(&self, keys: &[&str]) -> Result<u64, AppError> {
+        let mut deleted = 0;
+        for key in keys {
+            if self.delete(key).await? {
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    async fn get_many<T: DeserializeOwned + Send>(
+        &self,
+        keys: &[&str],
+    ) -> Result<Vec<Option<T>>, AppError> {
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            values.push(self.get(key).await?);
+        }
+        Ok(values)
+    }
+
+    async fn mset_ex<T: Serialize + Sync + Send>(
+        &self,
+        entries: &[(&str, &T)],
+        seconds: u64,
+    ) -> Result<(), AppError> {
+        // Serialize everything first so a bad value writes nothing
+        let serialized = entries
+            .iter()
+            .map(|(key, value)| Ok((self.format_key(key), Self::serialize(*value)?)))
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let expires_at = Some(Instant::now() + Duration::from_secs(seconds));
+        let mut stored = self.entries.lock();
+        for (full_key, value) in serialized {
+            stored.insert(full_key, Entry { value, expires_at });
+        }
+
+        Ok(())
+    }
+
+    async fn count_by_prefix(&self, prefix: &str) -> Result<u64, AppError> {
+        let full_prefix = self.format_key(prefix);
+        let now = Instant::now();
+        let mut entries = self.entries.lock();
+
+        entries.retain(|_, entry| !entry.is_expired(now));
+
+        Ok(entries.keys().filter(|k| k.starts_with(&full_prefix)).count() as u64)
+    }
+
+    async fn delete_by_prefix(&self, prefix: &str) -> Result<u64, AppError> {
+        let full_prefix = self.format_key(prefix);
+        let mut entries = self.entries.lock();
+        let before = entries.len();
+
+        entries.retain(|k, _| !k.starts_with(&full_prefix));
+
+        Ok((before - entries.len()) as u64)
+    }
+
+    async fn hset_raw_ex(&self, key: &str, field: &str, value: &str, seconds: u64) -> Result<(), AppError> {
+        self.update_hash(key, Some(seconds), |fields| {
+            fields.insert(field.to_string(), value.to_string());
+        })
+    }
+
+    async fn hdel(&self, key: &str, field: &str) -> Result<bool, AppError> {
+        self.update_hash(key, None, |fields| fields.remove(field).is_some())
+    }
+
+    async fn hvals_raw(&self, key: &str) -> Result<Vec<String>, AppError> {
+        let full_key = self.format_key(key);
+        let mut entries = self.entries.lock();
+
+        match Self::live_entry(&mut entries, &full_key) {
+            Some(entry) => {
+                let fields: HashMap<String, String> = Self::deserialize(&entry.value)?;
+                Ok(fields.into_values().collect())
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn publish(&self, channel: &str, message: &str) -> Result<u64, AppError> {
+        let full_channel = self.format_key(channel);
+        let mut subscribers = self.subscribers.lock();
+
+        // Receivers that were dropped are forgotten
+        subscribers.retain(|tx| tx.send((full_channel.clone(), message.to_string())).is_ok());
+
+        Ok(subscribers.len() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_set_get_roundtrip() {
+        let cache = InMemoryCache::new();
+        cache.set("user:1", &"alice").await.unwrap();
+
+        let value: Option<String> = cache.get("user:1").await.unwrap();
+        assert_eq!(value.as_deref(), Some("alice"));
+    }
+
+    #[tokio::test]
+    async fn test_raw_string_roundtrips_without_quotes() {
+        let cache = InMemoryCache::new();
+        let payload = r#"{"id":"1","content":"say \"hi\""}"#;
+        cache.set_raw("message:1", payload).await.unwrap();
+
+        assert_eq!(cache.get_raw("message:1").await.unwrap().as_deref(), Some(payload));
+
+        // A raw JSON value reads back through the typed API unchanged
+        let value: Option<serde_json::Value> = cache.get("message:1").await.unwrap();
+        assert_eq!(value.unwrap()["content"], "say \"hi\"");
+
+        // Typed strings are stored JSON-encoded
+        cache.set("greeting", &"hello").await.unwrap();
+        assert_eq!(cache.get_raw("greeting").await.unwrap().as_deref(), Some("\"hello\""));
+    }
+
+    #[tokio::test]
+    async fn test_raw_entry_expires() {
+        let cache = InMemoryCache::new();
+        cache.set_raw_ex("temp", "plain text", 0).await.unwrap();
+        cache.set_raw_ex("kept", "plain text", 60).await.unwrap();
+
+        assert_eq!(cache.get_raw("temp").await.unwrap(), None);
+        assert_eq!(cache.get_raw("kept").await.unwrap().as_deref(), Some("plain text"));
+        assert!(cache.get_raw("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_entry_is_missing() {
+        let cache = InMemoryCache::new();
+        cache.set_ex("temp", &1, 0).await.unwrap();
+
+        assert!(!cache.exists("temp").await.unwrap());
+        assert_eq!(cache.ttl("temp").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_delete_if_eq_only_deletes_a_matching_value() {
+        let cache = InMemoryCache::new();
+        cache.set("lock", &"mine").await.unwrap();
+
+        assert!(!cache.delete_if_eq("lock", &"theirs").await.unwrap());
+        assert!(cache.exists("lock").await.unwrap());
+        assert!(cache.delete_if_eq("lock", &"mine").await.unwrap());
+        assert!(!cache.exists("lock").await.unwrap());
+        assert!(!cache.delete_if_eq("lock", &"mine").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_incr_preserves_ttl() {
+        let cache = InMemoryCache::new();
+        cache.set_ex("counter", &5, 60).await.unwrap();
+
+        assert_eq!(cache.incr("counter").await.unwrap(), 6);
+        assert!(cache.ttl("counter").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_mset_ex_sets_all_entries_with_ttl() {
+        let cache = InMemoryCache::with_prefix("chat:");
+        let (a, b, c) = ("general".to_string(), "random".to_string(), "voice".to_string());
+
+        cache
+            .mset_ex(&[("channel:1", &a), ("channel:2", &b), ("channel:3", &c)], 60)
+            .await
+            .unwrap();
+
+        let values: Vec<Option<String>> = cache
+            .get_many(&["channel:1", "channel:2", "channel:3"])
+            .await
+            .unwrap();
+        assert_eq!(values, vec![Some(a), Some(b), Some(c)]);
+
+        for key in ["channel:1", "channel:2", "channel:3"] {
+            let ttl = cache.ttl(key).await.unwrap().unwrap();
+            assert!(ttl > 0 && ttl <= 60);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hash_fields_are_updated_independently() {
+        let cache = InMemoryCache::new();
+        cache.hset_raw_ex("h", "a", "1", 60).await.unwrap();
+        cache.hset_raw_ex("h", "b", "2", 60).await.unwrap();
+
+        assert!(cache.hdel("h", "a").await.unwrap());
+        assert!(!cache.hdel("h", "a").await.unwrap());
+        assert_eq!(cache.hvals_raw("h").await.unwrap(), vec!["2".to_string()]);
+        assert!(cache.ttl("h").await.unwrap().is_some());
+
+        // The hash goes away with its last field
+        cache.hdel("h", "b").await.unwrap();
+        assert!(!cache.exists("h").await.unwrap());
+        assert!(cache.hvals_raw("h").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_publish_reaches_subscribers() {
+        let cache = InMemoryCache::with_prefix("app:");
+        assert_eq!(cache.publish("events", "dropped").await.unwrap(), 0);
+
+        let mut rx = cache.subscribe();
+        assert_eq!(cache.publish("events", "hello").await.unwrap(), 1);
+
+        assert_eq!(rx.try_recv().unwrap(), ("app:events".to_string(), "hello".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_count_by_prefix() {
+        let cache = InMemoryCache::with_prefix("chat:");
+        cache.set("session:1", &"a").await.unwrap();
+        cache.set("session:2", &"b").await.unwrap();
+        cache.set_ex("session:3", &"c", 0).await.unwrap();
+        cache.set("presence:1", &"online").await.unwrap();
+
+        assert_eq!(cache.count_by_prefix("session:").await.unwrap(), 2);
+        assert_eq!(cache.count_by_prefix("presence:").await.unwrap(), 1);
+        assert_eq!(cache.count_by_prefix("").await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_delete_by_prefix() {
+        let cache = InMemoryCache::with_prefix("chat:");
+        cache.set("perms:channel:1:7", &1).await.unwrap();
+        cache.set("perms:channel:1:8", &2).await.unwrap();
+        cache.set("perms:channel:10:7", &3).await.unwrap();
+
+        assert_eq!(cache.delete_by_prefix("perms:channel:1:").await.unwrap(), 2);
+        assert!(!cache.exists("perms:channel:1:7").await.unwrap());
+        assert!(cache.exists("perms:channel:10:7").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_namespaced_view_composes_prefixes() {
+        let root = InMemoryCache::new();
+        let cache = root.namespaced("chat:");
+        let guild = cache.namespaced("guild:123:");
+
+        guild.set("name", &"Rustaceans").await.unwrap();
+
+        let name: Option<String> = cache.get("guild:123:name").await.unwrap();
+        assert_eq!(name.as_deref(), Some("Rustaceans"));
+        let name: Option<String> = root.get("chat:guild:123:name").await.unwrap();
+        assert_eq!(name.as_deref(), Some("Rustaceans"));
+        // Other namespaces don't see the key
+        assert!(!cache.namespaced("guild:456:").exists("name").await.unwrap());
+        assert_eq!(guild.count_by_prefix("").await.unwrap(), 1);
+    }
+}
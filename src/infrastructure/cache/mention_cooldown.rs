@@ -0,0 +1,91 @@
+//! Mention Cooldown
+//!
+//! Limits how often `@everyone`/`@here` may ping a channel. A sent ping
+//! sets `mention_cooldown:{channel_id}` for the cooldown; further ones are
+//! refused until the key expires. This is separate from a
+//! channel's slowmode, which limits each user's messages.
+
+use super::{keys, Cache, RedisCache, RedisPool};
+use crate::shared::error::AppError;
+
+/// Default seconds between `@everyone`/`@here` pings in a channel
+pub const DEFAULT_MENTION_COOLDOWN_SECS: u64 = 5;
+
+/// Per-channel `@everyone`/`@here` cooldown
+#[derive(Clone)]
+pub struct MentionCooldown<C: Cache = RedisCache> {
+    cache: C,
+    cooldown_secs: u64,
+}
+
+impl MentionCooldown {
+    /// Create a new mention cooldown
+    pub fn new(redis: RedisPool) -> Self {
+        Self::with_cache(RedisCache::new(redis))
+    }
+}
+
+impl<C: Cache> MentionCooldown<C> {
+    /// Create a mention cooldown over any cache backend
+    pub fn with_cache(cache: C) -> Self {
+        Self {
+            cache,
+            cooldown_secs: DEFAULT_MENTION_COOLDOWN_SECS,
+        }
+    }
+
+    /// Set the seconds between pings; zero disables the cooldown
+    pub fn with_cooldown(mut self, seconds: u64) -> Self {
+        self.cooldown_secs = seconds;
+        self
+    }
+
+    /// Seconds until the channel may be pinged again, or `None` if it is
+    /// not cooling down from an earlier ping
+    pub async fn remaining(&self, channel_id: i64) -> Result<Option<u64>, AppError> {
+        if self.cooldown_secs == 0 {
+            return Ok(None);
+        }
+
+        let ttl = self.cache.ttl(&keys::mention_cooldown(channel_id)).await?;
+        Ok(ttl.map(|secs| secs.max(1) as u64))
+    }
+
+    /// Start the channel's cooldown after a ping was sent
+    pub async fn start(&self, channel_id: i64) -> Result<(), AppError> {
+        if self.cooldown_secs == 0 {
+            return Ok(());
+        }
+
+        self.cache
+            .set_ex(&keys::mention_cooldown(channel_id), &1, self.cooldown_secs)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::cache::InMemoryCache;
+
+    #[tokio::test]
+    async fn test_ping_waits_for_the_cooldown_started_before_it() {
+        let cooldown = MentionCooldown::with_cache(InMemoryCache::new()).with_cooldown(5);
+
+        assert_eq!(cooldown.remaining(1).await.unwrap(), None);
+        cooldown.start(1).await.unwrap();
+        let retry_after = cooldown.remaining(1).await.unwrap().unwrap();
+        assert!((1..=5).contains(&retry_after));
+
+        // Other channels have their own cooldown
+        assert_eq!(cooldown.remaining(2).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_zero_cooldown_is_disabled() {
+        let cooldown = MentionCooldown::with_cache(InMemoryCache::new()).with_cooldown(0);
+
+        cooldown.start(1).await.unwrap();
+        assert_eq!(cooldown.remaining(1).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,166 @@
+//! Channel Message Count Cache
+//!
+//! Caches per-channel message counts so they don't need a `COUNT(*)` on
+//! every read. Counts are adjusted as messages are created and deleted, and
+//! expire after a reconciliation interval so any drift is corrected by
+//! recounting from the database on the next read.
+
+use async_trait::async_trait;
+
+use crate::domain::MessageCountStore;
+use crate::shared::error::AppError;
+use super::{keys, Cache, RedisCache, RedisPool};
+
+/// Default time before a cached count is recounted from the database
+pub const DEFAULT_RECONCILE_INTERVAL: u64 = 10 * 60;
+
+/// Channel message count cache service
+#[derive(Clone)]
+pub struct MessageCountCache<C: Cache = RedisCache> {
+    cache: C,
+    reconcile_interval: u64,
+}
+
+impl MessageCountCache {
+    /// Create a new message count cache
+    pub fn new(redis: RedisPool) -> Self {
+        Self {
+            cache: RedisCache::new(redis),
+            reconcile_interval: DEFAULT_RECONCILE_INTERVAL,
+        }
+    }
+}
+
+impl<C: Cache> MessageCountCache<C> {
+    /// Use a different cache backend
+    pub fn with_cache<D: Cache>(self, cache: D) -> MessageCountCache<D> {
+        MessageCountCache {
+            cache,
+            reconcile_interval: self.reconcile_interval,
+        }
+    }
+
+    /// Set how long a count is trusted before it is recounted
+    pub fn with_reconcile_interval(mut self, seconds: u64) -> Self {
+        self.reconcile_interval = seconds;
+        self
+    }
+
+    /// Drop the cached count of a deleted channel
+    pub async fn remove(&self, channel_id: i64) -> Result<(), AppError> {
+        self.cache.delete(&keys::channel_message_count(channel_id)).await?;
+        Ok(())
+    }
+
+    /// Apply a delta to a cached count.
+    ///
+    /// Channels without a cached count are left alone; the next read counts
+    /// from the database. If the key expired between the existence check and
+    /// the increment, the increment recreates it without a TTL, so such keys
+    /// (and negative counts) are dropped rather than trusted.
+    async fn adjust(&self, channel_id: i64, delta: i64) -> Result<(), AppError> {
+        let key = keys::channel_message_count(channel_id);
+
+        if !self.cache.exists(&key).await? {
+            return Ok(());
+        }
+
+        let value = self.cache.incr_by(&key, delta).await?;
+        if value < 0 || self.cache.ttl(&key).await?.is_none() {
+            self.cache.delete(&key).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<C: Cache> MessageCountStore for MessageCountCache<C> {
+    async fn get(&self, channel_id: i64) -> Result<Option<i64>, AppError> {
+        self.cache.get(&keys::channel_message_count(channel_id)).await
+    }
+
+    /// Restarts the reconciliation interval.
+    async fn reconcile(&self, channel_id: i64, count: i64) -> Result<Option<i64>, AppError> {
+        let key = keys::channel_message_count(channel_id);
+        let previous = self.cache.get(&key).await?;
+        self.cache.set_ex(&key, &count, self.reconcile_interval).await?;
+        Ok(previous)
+    }
+
+    async fn increment(&self, channel_id: i64) -> Result<(), AppError> {
+        self.adjust(channel_id, 1).await
+    }
+
+    async fn decrement_by(&self, channel_id: i64, count: u64) -> Result<(), AppError> {
+        self.adjust(channel_id, -(count as i64)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::cache::InMemoryCache;
+
+    fn counts() -> MessageCountCache<InMemoryCache> {
+        MessageCountCache {
+            cache: InMemoryCache::new(),
+            reconcile_interval: DEFAULT_RECONCILE_INTERVAL,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_increment_and_decrement() {
+        let counts = counts();
+        counts.reconcile(1, 10).await.unwrap();
+
+        counts.increment(1).await.unwrap();
+        counts.increment(1).await.unwrap();
+        counts.decrement_by(1, 5).await.unwrap();
+
+        assert_eq!(counts.get(1).await.unwrap(), Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_uncached_channel_is_not_created() {
+        let counts = counts();
+
+        counts.increment(1).await.unwrap();
+        counts.decrement_by(1, 1).await.unwrap();
+
+        assert_eq!(counts.get(1).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_negative_count_is_dropped() {
+        let counts = counts();
+        counts.reconcile(1, 1).await.unwrap();
+
+        counts.decrement_by(1, 3).await.unwrap();
+
+        assert_eq!(counts.get(1).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_replaces_drifted_count() {
+        let counts = counts();
+        counts.reconcile(1, 10).await.unwrap();
+        counts.increment(1).await.unwrap();
+
+        let previous = counts.reconcile(1, 4).await.unwrap();
+
+        assert_eq!(previous, Some(11));
+        assert_eq!(counts.get(1).await.unwrap(), Some(4));
+        assert!(counts.cache.ttl(&keys::channel_message_count(1)).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_expired_count_is_recounted() {
+        let counts = counts().with_reconcile_interval(0);
+        counts.reconcile(1, 10).await.unwrap();
+
+        // An expired count is gone, so increments don't resurrect it
+        counts.increment(1).await.unwrap();
+        assert_eq!(counts.get(1).await.unwrap(), None);
+    }
+}
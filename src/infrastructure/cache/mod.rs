@@ -4,8 +4,10 @@
 //!
 //! This module provides:
 //! - Redis connection management with automatic reconnection
+//! - A round-robin `RedisPool` of multiplexed connections
 //! - A generic `Cache` trait for abstracting cache operations
 //! - A `RedisCache` implementation with full Redis support
+//! - An `InMemoryCache` implementation for tests and single-node setups
 //! - Predefined key prefixes for consistent cache key naming
 //!
 //! # Architecture
@@ -27,7 +29,7 @@
 //!          |
 //!          v
 //! +-------------------+
-//! | ConnectionManager |  <-- Redis connection pool
+//! |    RedisPool      |  <-- Round-robin over ConnectionManagers
 //! +-------------------+
 //! ```
 //!
@@ -38,7 +40,7 @@
 //! use chat_server::config::RedisSettings;
 //!
 //! // Create connection
-//! let settings = RedisSettings { url: "redis://localhost:6379".into() };
+//! let settings = RedisSettings { url: "redis://localhost:6379".into(), pool_size: 4 };
 //! let conn = create_redis_client(&settings).await?;
 //!
 //! // Create cache instance
@@ -50,16 +52,51 @@
 //! ```
 
 mod cache_service;
+mod circuit_breaker;
+mod distributed_lock;
+mod everyone_permissions_cache;
+#[cfg(test)]
+pub(crate) mod failing_cache;
+mod guild_cache;
+mod guild_counts_cache;
+mod login_throttle;
+mod memory_cache;
+mod mention_cooldown;
+mod message_count_cache;
 mod permission_cache;
+mod redis_pool;
 mod session_cache;
+mod slowmode;
+mod token_blacklist;
 mod typing_cache;
+mod user_profile_cache;
+mod voice_state_cache;
+mod worker_id;
 
 pub use cache_service::{Cache, RedisCache};
+pub use circuit_breaker::CircuitBreaker;
+pub use distributed_lock::{DistributedLock, LockToken};
+pub use everyone_permissions_cache::{EveryonePermissionsCache, EVERYONE_PERMISSIONS_TTL};
+pub use guild_cache::{GuildCache, GUILD_CACHE_TTL};
+pub use guild_counts_cache::{GuildCounts, GuildCountsCache};
+pub use login_throttle::{lockout_secs, LoginThrottle, LOCKOUT_THRESHOLD};
+pub use memory_cache::InMemoryCache;
+pub use mention_cooldown::{MentionCooldown, DEFAULT_MENTION_COOLDOWN_SECS};
+pub use message_count_cache::MessageCountCache;
 pub use permission_cache::{
     CachedChannelPermissions, CachedGuildMember, CachedMemberPermissions, PermissionCacheService,
 };
-pub use session_cache::{CachedSession, SessionCacheService, UserPresence};
+pub use session_cache::{
+    Activity, CachedSession, SessionCacheService, SessionPresence, UserPresence,
+    MAX_CUSTOM_STATUS_LENGTH,
+};
+pub use redis_pool::{effective_pool_size, RedisPool};
+pub use slowmode::Slowmode;
+pub use token_blacklist::TokenBlacklist;
+pub use worker_id::{worker_id_from_sequence, WorkerIdAllocator};
 pub use typing_cache::TypingCacheService;
+pub use user_profile_cache::{CachedUserProfile, UserProfileCache};
+pub use voice_state_cache::VoiceStateCacheService;
 
 use redis::aio::ConnectionManager;
 use redis::Client;
@@ -67,32 +104,35 @@ use tracing::{info, instrument};
 
 use crate::config::RedisSettings;
 
-/// Creates a Redis connection manager with automatic reconnection.
+/// Creates a pool of Redis connections with automatic reconnection.
 ///
-/// The connection manager handles connection pooling and automatic
-/// reconnection when the connection is lost.
+/// Opens `settings.pool_size` multiplexed connections (at least one);
+/// commands are spread over them round-robin.
 ///
 /// # Arguments
 /// * `settings` - Redis configuration settings
 ///
 /// # Returns
-/// * `Ok(ConnectionManager)` - On successful connection
-/// * `Err(redis::RedisError)` - If connection fails
+/// * `Ok(RedisPool)` - On successful connection
+/// * `Err(redis::RedisError)` - If any connection fails
 ///
 /// # Example
 /// ```rust,ignore
-/// let settings = RedisSettings { url: "redis://localhost:6379".into() };
-/// let conn = create_redis_client(&settings).await?;
+/// let settings = RedisSettings { url: "redis://localhost:6379".into(), pool_size: 4 };
+/// let pool = create_redis_client(&settings).await?;
 /// ```
-#[instrument(skip(settings), fields(url = %settings.url))]
-pub async fn create_redis_client(
-    settings: &RedisSettings,
-) -> Result<ConnectionManager, redis::RedisError> {
+#[instrument(skip(settings), fields(url = %settings.url, pool_size = settings.pool_size))]
+pub async fn create_redis_client(settings: &RedisSettings) -> Result<RedisPool, redis::RedisError> {
     info!("Connecting to Redis...");
     let client = Client::open(settings.url.as_str())?;
-    let manager = ConnectionManager::new(client).await?;
-    info!("Redis connection established");
-    Ok(manager)
+
+    let mut connections = Vec::new();
+    for _ in 0..effective_pool_size(settings.pool_size) {
+        connections.push(ConnectionManager::new(client.clone()).await?);
+    }
+
+    info!(connections = connections.len(), "Redis connection established");
+    Ok(RedisPool::from_connections(connections))
 }
 
 /// Creates a `RedisCache` instance from configuration settings.
@@ -149,6 +189,15 @@ pub mod keys {
     /// Prefix for user presence/online status (e.g., "presence:user_id")
     pub const USER_PRESENCE: &str = "presence:";
 
+    /// Prefix for voice states (e.g., "voice:server_id:user_id")
+    pub const VOICE_STATE: &str = "voice:";
+
+    /// Prefix for per-session presence hashes (e.g., "session_presence:user_id")
+    pub const SESSION_PRESENCES: &str = "session_presence:";
+
+    /// Pub/sub channel for effective presence updates
+    pub const PRESENCE_UPDATES: &str = "presence:updates";
+
     /// Prefix for guild member lists (e.g., "guild:members:guild_id")
     pub const GUILD_MEMBERS: &str = "guild:members:";
 
@@ -176,6 +225,36 @@ pub mod keys {
     /// Prefix for distributed locks (e.g., "lock:resource_name")
     pub const LOCK: &str = "lock:";
 
+    /// Prefix for revoked auth sessions (e.g., "revoked:session:session_id")
+    pub const REVOKED_SESSION: &str = "revoked:session:";
+
+    /// Prefix for cached channel message counts (e.g., "channel:message_count:channel_id")
+    pub const CHANNEL_MESSAGE_COUNT: &str = "channel:message_count:";
+
+    /// Prefix for failed login counters (e.g., "login:failures:email")
+    pub const LOGIN_FAILURES: &str = "login:failures:";
+
+    /// Prefix for login lockouts (e.g., "login:lockout:email")
+    pub const LOGIN_LOCKOUT: &str = "login:lockout:";
+
+    /// Prefix for cached guild member/online counts (e.g., "guild:counts:guild_id")
+    pub const GUILD_COUNTS: &str = "guild:counts:";
+
+    /// Prefix for hashes of a guild's online members (e.g., "guild:online:guild_id")
+    pub const GUILD_ONLINE: &str = "guild:online:";
+
+    /// Prefix for cached @everyone permissions (e.g., "guild:everyone_perms:guild_id")
+    pub const EVERYONE_PERMISSIONS: &str = "guild:everyone_perms:";
+
+    /// Prefix for `@everyone`/`@here` cooldowns (e.g., "mention_cooldown:channel_id")
+    pub const MENTION_COOLDOWN: &str = "mention_cooldown:";
+
+    /// Prefix for per-user slowmode cooldowns (e.g., "slowmode:channel_id:user_id")
+    pub const SLOWMODE: &str = "slowmode:";
+
+    /// Counter snowflake worker IDs are assigned from
+    pub const WORKER_ID_SEQ: &str = "snowflake:worker_id_seq";
+
     /// Generates a session key for a user
     #[inline]
     pub fn session(user_id: impl std::fmt::Display) -> String {
@@ -188,12 +267,33 @@ pub mod keys {
         format!("{}{}", USER_PRESENCE, user_id)
     }
 
+    /// Generates a voice state key for a user in a server
+    #[inline]
+    pub fn voice_state(
+        server_id: impl std::fmt::Display,
+        user_id: impl std::fmt::Display,
+    ) -> String {
+        format!("{}{}:{}", VOICE_STATE, server_id, user_id)
+    }
+
+    /// Generates a per-session presence hash key for a user
+    #[inline]
+    pub fn session_presences(user_id: impl std::fmt::Display) -> String {
+        format!("{}{}", SESSION_PRESENCES, user_id)
+    }
+
     /// Generates a guild members key
     #[inline]
     pub fn guild_members(guild_id: impl std::fmt::Display) -> String {
         format!("{}{}", GUILD_MEMBERS, guild_id)
     }
 
+    /// Generates a guild online members key
+    #[inline]
+    pub fn guild_online(guild_id: impl std::fmt::Display) -> String {
+        format!("{}{}", GUILD_ONLINE, guild_id)
+    }
+
     /// Generates a typing indicator key
     #[inline]
     pub fn typing(channel_id: impl std::fmt::Display, user_id: impl std::fmt::Display) -> String {
@@ -218,6 +318,12 @@ pub mod keys {
         format!("{}{}", CHANNEL, channel_id)
     }
 
+    /// Generates a channel message count key
+    #[inline]
+    pub fn channel_message_count(channel_id: impl std::fmt::Display) -> String {
+        format!("{}{}", CHANNEL_MESSAGE_COUNT, channel_id)
+    }
+
     /// Generates a message cache key
     #[inline]
     pub fn message(message_id: impl std::fmt::Display) -> String {
@@ -241,4 +347,46 @@ pub mod keys {
     pub fn lock(resource: &str) -> String {
         format!("{}{}", LOCK, resource)
     }
+
+    /// Generates a revoked auth session key
+    #[inline]
+    pub fn revoked_session(session_id: impl std::fmt::Display) -> String {
+        format!("{}{}", REVOKED_SESSION, session_id)
+    }
+
+    /// Generates a failed login counter key
+    #[inline]
+    pub fn login_failures(email: &str) -> String {
+        format!("{}{}", LOGIN_FAILURES, email)
+    }
+
+    /// Generates a login lockout key
+    #[inline]
+    pub fn login_lockout(email: &str) -> String {
+        format!("{}{}", LOGIN_LOCKOUT, email)
+    }
+
+    /// Generates a guild counts key
+    #[inline]
+    pub fn guild_counts(guild_id: impl std::fmt::Display) -> String {
+        format!("{}{}", GUILD_COUNTS, guild_id)
+    }
+
+    /// Generates a guild's @everyone permissions key
+    #[inline]
+    pub fn everyone_permissions(guild_id: impl std::fmt::Display) -> String {
+        format!("{}{}", EVERYONE_PERMISSIONS, guild_id)
+    }
+
+    /// Generates a mention cooldown key
+    #[inline]
+    pub fn mention_cooldown(channel_id: impl std::fmt::Display) -> String {
+        format!("{}{}", MENTION_COOLDOWN, channel_id)
+    }
+
+    /// Generates a slowmode cooldown key
+    #[inline]
+    pub fn slowmode(channel_id: impl std::fmt::Display, user_id: impl std::fmt::Display) -> String {
+        format!("{}{}:{}", SLOWMODE, channel_id, user_id)
+    }
 }
@@ -1,12 +1,11 @@
 //! Permission Cache Service
 //!
-//! Redis-based caching for computed permissions.
+//! Caching for computed permissions, over any [`Cache`] backend.
 
-use redis::aio::ConnectionManager;
-use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
 
 use crate::shared::error::AppError;
+use super::{Cache, RedisCache, RedisPool};
 
 /// Cache key prefixes for permission caching
 mod keys {
@@ -48,8 +47,8 @@ pub struct CachedGuildMember {
 
 /// Permission cache service
 #[derive(Clone)]
-pub struct PermissionCacheService {
-    redis: ConnectionManager,
+pub struct PermissionCacheService<C: Cache = RedisCache> {
+    cache: C,
     member_perms_ttl: u64,
     channel_perms_ttl: u64,
     guild_members_ttl: u64,
@@ -57,27 +56,34 @@ pub struct PermissionCacheService {
 
 impl PermissionCacheService {
     /// Create a new permission cache service
-    pub fn new(redis: ConnectionManager) -> Self {
-        Self {
-            redis,
-            member_perms_ttl: 5 * 60,     // 5 minutes for member permissions
-            channel_perms_ttl: 5 * 60,    // 5 minutes for channel permissions
-            guild_members_ttl: 10 * 60,   // 10 minutes for guild members list
-        }
+    pub fn new(redis: RedisPool) -> Self {
+        Self::with_cache(RedisCache::new(redis))
     }
 
     /// Create with custom TTLs
     pub fn with_ttl(
-        redis: ConnectionManager,
+        redis: RedisPool,
         member_perms_ttl: u64,
         channel_perms_ttl: u64,
         guild_members_ttl: u64,
     ) -> Self {
         Self {
-            redis,
             member_perms_ttl,
             channel_perms_ttl,
             guild_members_ttl,
+            ..Self::new(redis)
+        }
+    }
+}
+
+impl<C: Cache> PermissionCacheService<C> {
+    /// Create a permission cache service over any cache backend
+    pub fn with_cache(cache: C) -> Self {
+        Self {
+            cache,
+            member_perms_ttl: 5 * 60,     // 5 minutes for member permissions
+            channel_perms_ttl: 5 * 60,    // 5 minutes for channel permissions
+            guild_members_ttl: 10 * 60,   // 10 minutes for guild members list
         }
     }
 
@@ -91,15 +97,7 @@ impl PermissionCacheService {
         perms: &CachedMemberPermissions,
     ) -> Result<(), AppError> {
         let key = format!("{}{}:{}", keys::MEMBER_PERMS, guild_id, user_id);
-        let value = serde_json::to_string(perms)
-            .map_err(|e| AppError::Internal(format!("Serialization error: {}", e)))?;
-
-        let mut conn = self.redis.clone();
-        conn.set_ex::<_, _, ()>(&key, value, self.member_perms_ttl)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-
-        Ok(())
+        self.cache.set_ex(&key, perms, self.member_perms_ttl).await
     }
 
     /// Get cached member permissions
@@ -109,21 +107,7 @@ impl PermissionCacheService {
         user_id: i64,
     ) -> Result<Option<CachedMemberPermissions>, AppError> {
         let key = format!("{}{}:{}", keys::MEMBER_PERMS, guild_id, user_id);
-
-        let mut conn = self.redis.clone();
-        let value: Option<String> = conn
-            .get(&key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-
-        match value {
-            Some(json) => {
-                let perms = serde_json::from_str(&json)
-                    .map_err(|e| AppError::Internal(format!("Deserialization error: {}", e)))?;
-                Ok(Some(perms))
-            }
-            None => Ok(None),
-        }
+        self.cache.get(&key).await
     }
 
     /// Invalidate member permissions for a guild
@@ -133,14 +117,7 @@ impl PermissionCacheService {
         user_id: i64,
     ) -> Result<bool, AppError> {
         let key = format!("{}{}:{}", keys::MEMBER_PERMS, guild_id, user_id);
-
-        let mut conn = self.redis.clone();
-        let deleted: i64 = conn
-            .del(&key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-
-        Ok(deleted > 0)
+        self.cache.delete(&key).await
     }
 
     /// Invalidate all permissions for a user in a guild (e.g., when roles change)
@@ -151,79 +128,65 @@ impl PermissionCacheService {
     ) -> Result<(), AppError> {
         // Delete member permissions
         let member_key = format!("{}{}:{}", keys::MEMBER_PERMS, guild_id, user_id);
+        self.cache.delete(&member_key).await?;
 
-        let mut conn = self.redis.clone();
-        let _: () = conn
-            .del(&member_key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-
-        // Note: Channel permissions would need to be invalidated separately
-        // if we track which channels the user has cached permissions for
+        // Channel permissions are dropped guild-wide, see
+        // `invalidate_guild_channel_permissions`
 
         Ok(())
     }
 
     // --- Channel Permissions ---
+    //
+    // Keys are scoped by guild so that everything derived from a guild's
+    // roles can be dropped at once.
 
     /// Cache channel permissions for a member
     pub async fn set_channel_permissions(
         &self,
+        guild_id: i64,
         channel_id: i64,
         user_id: i64,
         perms: &CachedChannelPermissions,
     ) -> Result<(), AppError> {
-        let key = format!("{}{}:{}", keys::CHANNEL_PERMS, channel_id, user_id);
-        let value = serde_json::to_string(perms)
-            .map_err(|e| AppError::Internal(format!("Serialization error: {}", e)))?;
-
-        let mut conn = self.redis.clone();
-        conn.set_ex::<_, _, ()>(&key, value, self.channel_perms_ttl)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-
-        Ok(())
+        let key = format!("{}{}:{}:{}", keys::CHANNEL_PERMS, guild_id, channel_id, user_id);
+        self.cache.set_ex(&key, perms, self.channel_perms_ttl).await
     }
 
     /// Get cached channel permissions
     pub async fn get_channel_permissions(
         &self,
+        guild_id: i64,
         channel_id: i64,
         user_id: i64,
     ) -> Result<Option<CachedChannelPermissions>, AppError> {
-        let key = format!("{}{}:{}", keys::CHANNEL_PERMS, channel_id, user_id);
-
-        let mut conn = self.redis.clone();
-        let value: Option<String> = conn
-            .get(&key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-
-        match value {
-            Some(json) => {
-                let perms = serde_json::from_str(&json)
-                    .map_err(|e| AppError::Internal(format!("Deserialization error: {}", e)))?;
-                Ok(Some(perms))
-            }
-            None => Ok(None),
-        }
+        let key = format!("{}{}:{}:{}", keys::CHANNEL_PERMS, guild_id, channel_id, user_id);
+        self.cache.get(&key).await
     }
 
     /// Invalidate channel permissions for a user
     pub async fn invalidate_channel_permissions(
         &self,
+        guild_id: i64,
         channel_id: i64,
         user_id: i64,
     ) -> Result<bool, AppError> {
-        let key = format!("{}{}:{}", keys::CHANNEL_PERMS, channel_id, user_id);
+        let key = format!("{}{}:{}:{}", keys::CHANNEL_PERMS, guild_id, channel_id, user_id);
+        self.cache.delete(&key).await
+    }
 
-        let mut conn = self.redis.clone();
-        let deleted: i64 = conn
-            .del(&key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
+    /// Invalidate cached permissions of every user for a channel
+    /// (e.g., when one of its overwrites is removed)
+    pub async fn invalidate_all_channel_permissions(&self, guild_id: i64, channel_id: i64) -> Result<u64, AppError> {
+        let prefix = format!("{}{}:{}:", keys::CHANNEL_PERMS, guild_id, channel_id);
+        self.cache.delete_by_prefix(&prefix).await
+    }
 
-        Ok(deleted > 0)
+    /// Invalidate cached permissions of every user in every channel of a
+    /// guild (e.g., when a role or a member's roles change)
+    pub async fn invalidate_guild_channel_permissions(&self, guild_id: i64) -> Result<u64, AppError> {
+        let prefix = format!("{}{}:", keys::CHANNEL_PERMS, guild_id);
+        self.cache.delete_by_prefix(&prefix).await
     }
 
     // --- Guild Members Cache ---
@@ -235,35 +198,13 @@ impl PermissionCacheService {
         member_ids: &[i64],
     ) -> Result<(), AppError> {
         let key = format!("{}{}:ids", keys::GUILD_MEMBERS, guild_id);
-        let value = serde_json::to_string(member_ids)
-            .map_err(|e| AppError::Internal(format!("Serialization error: {}", e)))?;
-
-        let mut conn = self.redis.clone();
-        conn.set_ex::<_, _, ()>(&key, value, self.guild_members_ttl)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-
-        Ok(())
+        self.cache.set_ex(&key, &member_ids, self.guild_members_ttl).await
     }
 
     /// Get cached guild member IDs
     pub async fn get_guild_member_ids(&self, guild_id: i64) -> Result<Option<Vec<i64>>, AppError> {
         let key = format!("{}{}:ids", keys::GUILD_MEMBERS, guild_id);
-
-        let mut conn = self.redis.clone();
-        let value: Option<String> = conn
-            .get(&key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-
-        match value {
-            Some(json) => {
-                let ids = serde_json::from_str(&json)
-                    .map_err(|e| AppError::Internal(format!("Deserialization error: {}", e)))?;
-                Ok(Some(ids))
-            }
-            None => Ok(None),
-        }
+        self.cache.get(&key).await
     }
 
     /// Cache a single guild member
@@ -273,15 +214,7 @@ impl PermissionCacheService {
         member: &CachedGuildMember,
     ) -> Result<(), AppError> {
         let key = format!("{}{}:{}", keys::GUILD_MEMBERS, guild_id, member.user_id);
-        let value = serde_json::to_string(member)
-            .map_err(|e| AppError::Internal(format!("Serialization error: {}", e)))?;
-
-        let mut conn = self.redis.clone();
-        conn.set_ex::<_, _, ()>(&key, value, self.guild_members_ttl)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-
-        Ok(())
+        self.cache.set_ex(&key, member, self.guild_members_ttl).await
     }
 
     /// Get a cached guild member
@@ -291,21 +224,7 @@ impl PermissionCacheService {
         user_id: i64,
     ) -> Result<Option<CachedGuildMember>, AppError> {
         let key = format!("{}{}:{}", keys::GUILD_MEMBERS, guild_id, user_id);
-
-        let mut conn = self.redis.clone();
-        let value: Option<String> = conn
-            .get(&key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-
-        match value {
-            Some(json) => {
-                let member = serde_json::from_str(&json)
-                    .map_err(|e| AppError::Internal(format!("Deserialization error: {}", e)))?;
-                Ok(Some(member))
-            }
-            None => Ok(None),
-        }
+        self.cache.get(&key).await
     }
 
     /// Invalidate guild member cache
@@ -317,19 +236,9 @@ impl PermissionCacheService {
         let member_key = format!("{}{}:{}", keys::GUILD_MEMBERS, guild_id, user_id);
         let ids_key = format!("{}{}:ids", keys::GUILD_MEMBERS, guild_id);
 
-        let mut conn = self.redis.clone();
-
-        // Delete individual member cache
-        let _: () = conn
-            .del(&member_key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-
-        // Also invalidate the member IDs list since it's now stale
-        let _: () = conn
-            .del(&ids_key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
+        // Delete the individual member cache, and the member IDs list
+        // since it's now stale
+        self.cache.delete_many(&[&member_key, &ids_key]).await?;
 
         Ok(())
     }
@@ -338,12 +247,7 @@ impl PermissionCacheService {
     pub async fn invalidate_guild(&self, guild_id: i64) -> Result<(), AppError> {
         // Delete member IDs list
         let ids_key = format!("{}{}:ids", keys::GUILD_MEMBERS, guild_id);
-
-        let mut conn = self.redis.clone();
-        let _: () = conn
-            .del(&ids_key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
+        self.cache.delete(&ids_key).await?;
 
         // Note: Individual member caches and permission caches would need
         // pattern-based deletion which requires SCAN command
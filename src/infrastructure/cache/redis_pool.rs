@@ -0,0 +1,101 @@
+//! Redis Connection Pool
+//!
+//! A fixed set of multiplexed connections handed out round-robin.
+//!
+//! A single `ConnectionManager` pipelines every request over one socket,
+//! which becomes a bottleneck under high concurrency. The pool spreads
+//! commands over `redis.pool_size` connections instead. It implements
+//! [`ConnectionLike`], so it is used exactly like a `ConnectionManager`:
+//! clones share the same connections and each command goes to the next
+//! connection in turn.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use redis::aio::{ConnectionLike, ConnectionManager};
+use redis::{Cmd, Pipeline, RedisFuture, Value};
+
+/// Round-robin pool of Redis connections
+#[derive(Clone)]
+pub struct RedisPool<C = ConnectionManager> {
+    connections: Arc<[C]>,
+    next: Arc<AtomicUsize>,
+}
+
+impl<C: Clone> RedisPool<C> {
+    /// Create a pool over already opened connections.
+    ///
+    /// # Panics
+    /// If `connections` is empty.
+    pub fn from_connections(connections: Vec<C>) -> Self {
+        assert!(!connections.is_empty(), "Redis pool needs at least one connection");
+        Self {
+            connections: connections.into(),
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Number of connections in the pool
+    pub fn size(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// The next connection in turn
+    pub fn next_connection(&self) -> C {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.connections.len();
+        self.connections[index].clone()
+    }
+}
+
+impl ConnectionLike for RedisPool {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        let mut conn = self.next_connection();
+        Box::pin(async move { conn.req_packed_command(cmd).await })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        pipeline: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        // A pipeline (and any MULTI/EXEC in it) stays on one connection
+        let mut conn = self.next_connection();
+        Box::pin(async move { conn.req_packed_commands(pipeline, offset, count).await })
+    }
+
+    fn get_db(&self) -> i64 {
+        self.connections[0].get_db()
+    }
+}
+
+/// Number of connections to open for a configured pool size; at least one.
+pub fn effective_pool_size(pool_size: u32) -> usize {
+    pool_size.max(1) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_hands_out_every_connection_in_turn() {
+        let pool = RedisPool::from_connections(vec!["a", "b", "c"]);
+        assert_eq!(pool.size(), 3);
+
+        let handed_out: Vec<&str> = (0..6).map(|_| pool.next_connection()).collect();
+        assert_eq!(handed_out, vec!["a", "b", "c", "a", "b", "c"]);
+
+        // Clones share the rotation
+        let clone = pool.clone();
+        assert_eq!(clone.next_connection(), "a");
+        assert_eq!(pool.next_connection(), "b");
+    }
+
+    #[test]
+    fn test_pool_size_is_at_least_one() {
+        assert_eq!(effective_pool_size(0), 1);
+        assert_eq!(effective_pool_size(1), 1);
+        assert_eq!(effective_pool_size(8), 8);
+    }
+}
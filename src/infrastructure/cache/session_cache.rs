@@ -2,12 +2,17 @@
 //!
 //! Redis-based caching for user sessions and presence.
 
-use redis::aio::ConnectionManager;
-use redis::AsyncCommands;
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 
+use crate::domain::{PresenceVisibility, Session, SessionRepository, UserStatus};
 use crate::shared::error::AppError;
-use super::keys;
+use super::circuit_breaker::CircuitBreaker;
+use super::{keys, Cache, RedisCache, RedisPool};
+
+/// Maximum length of custom status text in characters
+pub const MAX_CUSTOM_STATUS_LENGTH: usize = 128;
 
 /// Cached session data
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,230 +25,908 @@ pub struct CachedSession {
     pub expires_at: i64,
 }
 
-/// User presence data
+impl From<&Session> for CachedSession {
+    fn from(session: &Session) -> Self {
+        Self {
+            user_id: session.user_id,
+            session_id: session.id.to_string(),
+            device_info: session.device_info.clone(),
+            ip_address: session.ip_address.map(|ip| ip.to_string()),
+            created_at: session.created_at.timestamp(),
+            expires_at: session.expires_at.timestamp(),
+        }
+    }
+}
+
+/// Activity shown alongside a user's status (e.g. "Playing ...")
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Activity {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: u8,
+}
+
+/// Presence reported by a single gateway session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionPresence {
+    pub status: UserStatus,
+    #[serde(default)]
+    pub custom_status: Option<String>,
+    #[serde(default)]
+    pub activities: Vec<Activity>,
+    /// When this session last updated its presence (Unix timestamp)
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+impl SessionPresence {
+    /// Validate client-supplied presence fields
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let Some(text) = &self.custom_status {
+            if text.chars().count() > MAX_CUSTOM_STATUS_LENGTH {
+                return Err(AppError::Validation(format!(
+                    "Custom status must be at most {} characters",
+                    MAX_CUSTOM_STATUS_LENGTH
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// User presence data, combined across all of the user's sessions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserPresence {
     pub user_id: i64,
-    pub status: String,
+    pub status: UserStatus,
     pub custom_status: Option<String>,
+    #[serde(default)]
+    pub activities: Vec<Activity>,
     pub last_seen: i64,
     pub guild_ids: Vec<i64>,
+    /// Who besides the user may see this presence
+    #[serde(default)]
+    pub visibility: PresenceVisibility,
+}
+
+impl UserPresence {
+    /// Combine per-session presences into the user's effective presence.
+    ///
+    /// The status follows [`UserStatus::effective`]; custom status and
+    /// activities come from the most recently updated session.
+    pub fn from_sessions(
+        user_id: i64,
+        sessions: &[SessionPresence],
+        guild_ids: Vec<i64>,
+    ) -> Self {
+        let status = UserStatus::effective(sessions.iter().map(|s| &s.status));
+        let latest = sessions.iter().max_by_key(|s| s.updated_at);
+
+        Self {
+            user_id,
+            status,
+            custom_status: latest.and_then(|s| s.custom_status.clone()),
+            activities: latest.map(|s| s.activities.clone()).unwrap_or_default(),
+            last_seen: chrono::Utc::now().timestamp(),
+            guild_ids,
+            visibility: PresenceVisibility::default(),
+        }
+    }
+
+    /// The presence other users are shown.
+    ///
+    /// Users whose visibility doesn't share their presence with guild
+    /// members appear offline, without custom status or activities.
+    pub fn seen_by_others(&self) -> UserPresence {
+        if self.visibility.shared_with_guilds() {
+            return self.clone();
+        }
+
+        Self {
+            status: UserStatus::Offline,
+            custom_status: None,
+            activities: Vec::new(),
+            ..self.clone()
+        }
+    }
 }
 
 /// Session cache service for managing user sessions and presence
+///
+/// Cache failures never fail the caller. Session reads fall back to the
+/// [`SessionRepository`] when one is configured, cache writes are skipped,
+/// and a [`CircuitBreaker`] bypasses the cache entirely for a short cool-off
+/// after a failure.
 #[derive(Clone)]
-pub struct SessionCacheService {
-    redis: ConnectionManager,
+pub struct SessionCacheService<C: Cache = RedisCache> {
+    cache: C,
+    session_repo: Option<Arc<dyn SessionRepository>>,
+    breaker: CircuitBreaker,
     session_ttl: u64,
     presence_ttl: u64,
 }
 
 impl SessionCacheService {
     /// Create a new session cache service
-    pub fn new(redis: ConnectionManager) -> Self {
-        Self {
-            redis,
-            session_ttl: 7 * 24 * 60 * 60, // 7 days for sessions
-            presence_ttl: 5 * 60,           // 5 minutes for presence
-        }
+    pub fn new(redis: RedisPool) -> Self {
+        Self::with_cache(RedisCache::new(redis))
     }
 
     /// Create with custom TTLs
-    pub fn with_ttl(redis: ConnectionManager, session_ttl: u64, presence_ttl: u64) -> Self {
+    pub fn with_ttl(redis: RedisPool, session_ttl: u64, presence_ttl: u64) -> Self {
         Self {
-            redis,
             session_ttl,
             presence_ttl,
+            ..Self::new(redis)
         }
     }
+}
+
+impl<C: Cache> SessionCacheService<C> {
+    /// Create a session cache service over any cache backend
+    pub fn with_cache(cache: C) -> Self {
+        Self {
+            cache,
+            session_repo: None,
+            breaker: CircuitBreaker::default(),
+            session_ttl: 7 * 24 * 60 * 60, // 7 days for sessions
+            presence_ttl: 5 * 60,           // 5 minutes for presence
+        }
+    }
+
+    /// Fall back to the database for session reads when the cache is down
+    pub fn with_session_repository(mut self, session_repo: Arc<dyn SessionRepository>) -> Self {
+        self.session_repo = Some(session_repo);
+        self
+    }
+
+    /// Share a circuit breaker with other users of the same cache backend
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    /// Whether the cache should be used for this call
+    fn cache_available(&self) -> bool {
+        !self.breaker.is_open()
+    }
+
+    /// Log a cache failure and bypass the cache for the cool-off
+    fn record_failure(&self, operation: &str, error: &AppError) {
+        tracing::warn!(
+            operation,
+            error = %error,
+            "Session cache unavailable, bypassing cache"
+        );
+        self.breaker.trip();
+    }
+
+    fn session_key(token_hash: &str) -> String {
+        format!("{}{}", keys::USER_SESSION, token_hash)
+    }
+
+    fn presence_key(user_id: i64) -> String {
+        format!("{}{}", keys::USER_PRESENCE, user_id)
+    }
 
     // --- Session Methods ---
 
     /// Cache a user session
+    ///
+    /// Skipped while the cache is unavailable.
     pub async fn set_session(&self, token_hash: &str, session: &CachedSession) -> Result<(), AppError> {
-        let key = format!("{}{}", keys::USER_SESSION, token_hash);
-        let value = serde_json::to_string(session)
-            .map_err(|e| AppError::Internal(format!("Serialization error: {}", e)))?;
+        if !self.cache_available() {
+            return Ok(());
+        }
 
-        let mut conn = self.redis.clone();
-        conn.set_ex::<_, _, ()>(&key, value, self.session_ttl)
+        if let Err(e) = self
+            .cache
+            .set_ex(&Self::session_key(token_hash), session, self.session_ttl)
             .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
+        {
+            self.record_failure("set_session", &e);
+        }
 
         Ok(())
     }
 
     /// Get a cached session by token hash
+    ///
+    /// Falls back to the session repository if the cache is unavailable.
     pub async fn get_session(&self, token_hash: &str) -> Result<Option<CachedSession>, AppError> {
-        let key = format!("{}{}", keys::USER_SESSION, token_hash);
-
-        let mut conn = self.redis.clone();
-        let value: Option<String> = conn
-            .get(&key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-
-        match value {
-            Some(json) => {
-                let session = serde_json::from_str(&json)
-                    .map_err(|e| AppError::Internal(format!("Deserialization error: {}", e)))?;
-                Ok(Some(session))
+        if self.cache_available() {
+            match self.cache.get(&Self::session_key(token_hash)).await {
+                Ok(session) => return Ok(session),
+                Err(e) => self.record_failure("get_session", &e),
             }
-            None => Ok(None),
         }
+
+        self.load_session(token_hash).await
+    }
+
+    /// Load an active session from the repository
+    async fn load_session(&self, token_hash: &str) -> Result<Option<CachedSession>, AppError> {
+        let session_repo = self.session_repo.as_ref().ok_or_else(|| {
+            AppError::Internal("Session cache unavailable and no fallback configured".to_string())
+        })?;
+
+        let session = session_repo.find_by_token_hash(token_hash).await?;
+        Ok(session.filter(Session::is_active).map(|s| CachedSession::from(&s)))
     }
 
     /// Delete a cached session
     pub async fn delete_session(&self, token_hash: &str) -> Result<bool, AppError> {
-        let key = format!("{}{}", keys::USER_SESSION, token_hash);
-
-        let mut conn = self.redis.clone();
-        let deleted: i64 = conn
-            .del(&key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
+        if !self.cache_available() {
+            return Ok(false);
+        }
 
-        Ok(deleted > 0)
+        match self.cache.delete(&Self::session_key(token_hash)).await {
+            Ok(deleted) => Ok(deleted),
+            Err(e) => {
+                self.record_failure("delete_session", &e);
+                Ok(false)
+            }
+        }
     }
 
     /// Check if a session exists
     pub async fn session_exists(&self, token_hash: &str) -> Result<bool, AppError> {
-        let key = format!("{}{}", keys::USER_SESSION, token_hash);
-
-        let mut conn = self.redis.clone();
-        let exists: bool = conn
-            .exists(&key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
+        if self.cache_available() {
+            match self.cache.exists(&Self::session_key(token_hash)).await {
+                Ok(exists) => return Ok(exists),
+                Err(e) => self.record_failure("session_exists", &e),
+            }
+        }
 
-        Ok(exists)
+        Ok(self.load_session(token_hash).await?.is_some())
     }
 
     /// Refresh session TTL
     pub async fn refresh_session(&self, token_hash: &str) -> Result<bool, AppError> {
-        let key = format!("{}{}", keys::USER_SESSION, token_hash);
+        if !self.cache_available() {
+            return Ok(false);
+        }
 
-        let mut conn = self.redis.clone();
-        let result: bool = conn
-            .expire(&key, self.session_ttl as i64)
+        match self
+            .cache
+            .expire(&Self::session_key(token_hash), self.session_ttl)
             .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-
-        Ok(result)
+        {
+            Ok(refreshed) => Ok(refreshed),
+            Err(e) => {
+                self.record_failure("refresh_session", &e);
+                Ok(false)
+            }
+        }
     }
 
     // --- Presence Methods ---
 
-    /// Set user presence
-    pub async fn set_presence(&self, user_id: i64, presence: &UserPresence) -> Result<(), AppError> {
-        let key = format!("{}{}", keys::USER_PRESENCE, user_id);
-        let value = serde_json::to_string(presence)
-            .map_err(|e| AppError::Internal(format!("Serialization error: {}", e)))?;
+    /// Set one session's presence and recompute the user's effective presence
+    ///
+    /// Returns the effective presence, as the user sees it. While the cache
+    /// is unavailable the other sessions can't be seen, so this session's
+    /// presence is returned.
+    pub async fn set_presence(
+        &self,
+        user_id: i64,
+        session_id: &str,
+        presence: &SessionPresence,
+        guild_ids: Vec<i64>,
+        visibility: PresenceVisibility,
+    ) -> Result<UserPresence, AppError> {
+        presence.validate()?;
+
+        let mut presence = presence.clone();
+        presence.updated_at = chrono::Utc::now().timestamp();
+
+        if self.cache_available() {
+            match self
+                .update_session_presences(user_id, session_id, Some(presence.clone()), &guild_ids, visibility)
+                .await
+            {
+                Ok(effective) => return Ok(effective),
+                Err(e) => self.record_failure("set_presence", &e),
+            }
+        }
 
-        let mut conn = self.redis.clone();
-        conn.set_ex::<_, _, ()>(&key, value, self.presence_ttl)
+        let mut effective = UserPresence::from_sessions(user_id, &[presence], guild_ids);
+        effective.visibility = visibility;
+        Ok(effective)
+    }
+
+    /// Remove a session's presence (e.g. on disconnect) and recompute
+    ///
+    /// Fails if the cache is unavailable, since the remaining sessions
+    /// can't be seen and the result would wrongly report the user offline.
+    pub async fn remove_session_presence(
+        &self,
+        user_id: i64,
+        session_id: &str,
+        guild_ids: Vec<i64>,
+        visibility: PresenceVisibility,
+    ) -> Result<UserPresence, AppError> {
+        if !self.cache_available() {
+            return Err(AppError::Internal("Session cache unavailable".to_string()));
+        }
+
+        self.update_session_presences(user_id, session_id, None, &guild_ids, visibility)
             .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
+            .map_err(|e| {
+                self.record_failure("remove_session_presence", &e);
+                e
+            })
+    }
+
+    /// Insert or remove one session's presence, then store and broadcast
+    /// the effective presence
+    ///
+    /// Each session is a field of the user's presence hash, so concurrent
+    /// updates from different sessions never overwrite each other.
+    async fn update_session_presences(
+        &self,
+        user_id: i64,
+        session_id: &str,
+        presence: Option<SessionPresence>,
+        guild_ids: &[i64],
+        visibility: PresenceVisibility,
+    ) -> Result<UserPresence, AppError> {
+        let sessions_key = keys::session_presences(user_id);
+
+        match presence {
+            Some(presence) => {
+                let value = serde_json::to_string(&presence)
+                    .map_err(|e| AppError::Internal(format!("Serialization error: {}", e)))?;
+                self.cache
+                    .hset_raw_ex(&sessions_key, session_id, &value, self.presence_ttl)
+                    .await?;
+            }
+            None => {
+                self.cache.hdel(&sessions_key, session_id).await?;
+            }
+        }
+
+        let sessions: Vec<SessionPresence> = self
+            .cache
+            .hvals_raw(&sessions_key)
+            .await?
+            .iter()
+            .filter_map(|json| serde_json::from_str(json).ok())
+            .collect();
+
+        let mut effective = UserPresence::from_sessions(user_id, &sessions, guild_ids.to_vec());
+        effective.visibility = visibility;
+        self.store_presence(&effective).await?;
 
+        let payload = serde_json::to_string(&effective)
+            .map_err(|e| AppError::Internal(format!("Serialization error: {}", e)))?;
+        self.cache.publish(keys::PRESENCE_UPDATES, &payload).await?;
+
+        Ok(effective)
+    }
+
+    /// Record in each of the user's guilds whether others see them online.
+    ///
+    /// Entries hold their expiry, so a user whose presence lapses without a
+    /// disconnect stops being counted with it.
+    async fn track_guild_online(&self, presence: &UserPresence) -> Result<(), AppError> {
+        let online = presence.seen_by_others().status.is_online();
+        let field = presence.user_id.to_string();
+        let expires_at = (chrono::Utc::now().timestamp() + self.presence_ttl as i64).to_string();
+
+        for guild_id in &presence.guild_ids {
+            let key = keys::guild_online(guild_id);
+            if online {
+                self.cache.hset_raw_ex(&key, &field, &expires_at, self.presence_ttl).await?;
+            } else {
+                self.cache.hdel(&key, &field).await?;
+            }
+        }
         Ok(())
     }
 
-    /// Get user presence
-    pub async fn get_presence(&self, user_id: i64) -> Result<Option<UserPresence>, AppError> {
-        let key = format!("{}{}", keys::USER_PRESENCE, user_id);
+    /// Store a user's effective presence and their place in guild online counts
+    async fn store_presence(&self, presence: &UserPresence) -> Result<(), AppError> {
+        self.cache
+            .set_ex(&Self::presence_key(presence.user_id), presence, self.presence_ttl)
+            .await?;
+        self.track_guild_online(presence).await
+    }
 
-        let mut conn = self.redis.clone();
-        let value: Option<String> = conn
-            .get(&key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
+    /// Get user presence, as the user sees it
+    ///
+    /// Returns `None` while the cache is unavailable.
+    pub async fn get_presence(&self, user_id: i64) -> Result<Option<UserPresence>, AppError> {
+        if !self.cache_available() {
+            return Ok(None);
+        }
 
-        match value {
-            Some(json) => {
-                let presence = serde_json::from_str(&json)
-                    .map_err(|e| AppError::Internal(format!("Deserialization error: {}", e)))?;
-                Ok(Some(presence))
+        match self.cache.get(&Self::presence_key(user_id)).await {
+            Ok(presence) => Ok(presence),
+            Err(e) => {
+                self.record_failure("get_presence", &e);
+                Ok(None)
             }
-            None => Ok(None),
         }
     }
 
-    /// Get multiple user presences
+    /// Get multiple user presences, as other users see them
+    ///
+    /// Returns no presences while the cache is unavailable.
     pub async fn get_presences(&self, user_ids: &[i64]) -> Result<Vec<(i64, UserPresence)>, AppError> {
-        if user_ids.is_empty() {
+        if user_ids.is_empty() || !self.cache_available() {
             return Ok(Vec::new());
         }
 
-        let keys: Vec<String> = user_ids
+        let keys: Vec<String> = user_ids.iter().map(|id| Self::presence_key(*id)).collect();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+
+        let values: Vec<Option<UserPresence>> = match self.cache.get_many(&keys).await {
+            Ok(values) => values,
+            Err(e) => {
+                self.record_failure("get_presences", &e);
+                return Ok(Vec::new());
+            }
+        };
+
+        Ok(user_ids
             .iter()
-            .map(|id| format!("{}{}", keys::USER_PRESENCE, id))
-            .collect();
+            .zip(values)
+            .filter_map(|(user_id, presence)| presence.map(|p| (*user_id, p.seen_by_others())))
+            .collect())
+    }
 
-        let mut conn = self.redis.clone();
-        let values: Vec<Option<String>> = redis::cmd("MGET")
-            .arg(&keys)
-            .query_async(&mut conn)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
+    /// Count a guild's members others currently see online
+    ///
+    /// Counts nobody while the cache is unavailable.
+    pub async fn count_guild_online(&self, guild_id: i64) -> Result<usize, AppError> {
+        if !self.cache_available() {
+            return Ok(0);
+        }
 
-        let mut results = Vec::new();
-        for (user_id, value) in user_ids.iter().zip(values.into_iter()) {
-            if let Some(json) = value {
-                if let Ok(presence) = serde_json::from_str(&json) {
-                    results.push((*user_id, presence));
-                }
+        let now = chrono::Utc::now().timestamp();
+        match self.cache.hvals_raw(&keys::guild_online(guild_id)).await {
+            Ok(expiries) => Ok(expiries
+                .iter()
+                .filter(|expires_at| expires_at.parse::<i64>().is_ok_and(|at| at > now))
+                .count()),
+            Err(e) => {
+                self.record_failure("count_guild_online", &e);
+                Ok(0)
             }
         }
-
-        Ok(results)
     }
 
     /// Delete user presence
     pub async fn delete_presence(&self, user_id: i64) -> Result<bool, AppError> {
-        let key = format!("{}{}", keys::USER_PRESENCE, user_id);
-
-        let mut conn = self.redis.clone();
-        let deleted: i64 = conn
-            .del(&key)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
+        if !self.cache_available() {
+            return Ok(false);
+        }
 
-        Ok(deleted > 0)
+        match self.cache.delete(&Self::presence_key(user_id)).await {
+            Ok(deleted) => Ok(deleted),
+            Err(e) => {
+                self.record_failure("delete_presence", &e);
+                Ok(false)
+            }
+        }
     }
 
     /// Update presence status only (optimized)
-    pub async fn update_status(&self, user_id: i64, status: &str) -> Result<bool, AppError> {
+    pub async fn update_status(&self, user_id: i64, status: UserStatus) -> Result<bool, AppError> {
         // Get existing presence and update status
-        if let Some(mut presence) = self.get_presence(user_id).await? {
-            presence.status = status.to_string();
-            presence.last_seen = chrono::Utc::now().timestamp();
-            self.set_presence(user_id, &presence).await?;
-            Ok(true)
-        } else {
-            Ok(false)
+        let Some(mut presence) = self.get_presence(user_id).await? else {
+            return Ok(false);
+        };
+
+        presence.status = status;
+        presence.last_seen = chrono::Utc::now().timestamp();
+
+        match self.store_presence(&presence).await {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                self.record_failure("update_status", &e);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Change who may see a user's stored presence
+    ///
+    /// Returns the updated presence so it can be broadcast again, or `None`
+    /// if the user has no presence stored.
+    pub async fn update_visibility(
+        &self,
+        user_id: i64,
+        visibility: PresenceVisibility,
+    ) -> Result<Option<UserPresence>, AppError> {
+        let Some(mut presence) = self.get_presence(user_id).await? else {
+            return Ok(None);
+        };
+
+        presence.visibility = visibility;
+
+        match self.store_presence(&presence).await {
+            Ok(()) => Ok(Some(presence)),
+            Err(e) => {
+                self.record_failure("update_visibility", &e);
+                Ok(None)
+            }
         }
     }
 
     /// Heartbeat - update last_seen timestamp
     pub async fn heartbeat(&self, user_id: i64) -> Result<bool, AppError> {
-        let key = format!("{}{}", keys::USER_PRESENCE, user_id);
+        if !self.cache_available() {
+            return Ok(false);
+        }
 
-        // First check if presence exists
-        let mut conn = self.redis.clone();
-        let exists: bool = conn
-            .exists(&key)
+        // Refresh TTL; returns `false` if the presence doesn't exist
+        match self
+            .cache
+            .expire(&Self::presence_key(user_id), self.presence_ttl)
             .await
-            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
+        {
+            Ok(false) => Ok(false),
+            Ok(true) => {
+                // Keep the user counted online for as long as the presence lives
+                if let Some(presence) = self.get_presence(user_id).await? {
+                    if let Err(e) = self.track_guild_online(&presence).await {
+                        self.record_failure("heartbeat", &e);
+                    }
+                }
+                Ok(true)
+            }
+            Err(e) => {
+                self.record_failure("heartbeat", &e);
+                Ok(false)
+            }
+        }
+    }
+}
 
-        if exists {
-            // Refresh TTL
-            let _: bool = conn
-                .expire(&key, self.presence_ttl as i64)
-                .await
-                .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
-            Ok(true)
-        } else {
-            Ok(false)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use parking_lot::Mutex;
+    use std::collections::HashMap;
+    use chrono::{DateTime, Utc};
+    use std::net::IpAddr;
+    use uuid::Uuid;
+
+    use crate::infrastructure::cache::failing_cache::FailingCache;
+    use crate::infrastructure::cache::InMemoryCache;
+
+    /// Session repository kept in memory
+    #[derive(Default)]
+    struct InMemorySessionRepository {
+        sessions: Mutex<Vec<Session>>,
+    }
+
+    impl InMemorySessionRepository {
+        fn with_sessions(sessions: Vec<Session>) -> Self {
+            Self {
+                sessions: Mutex::new(sessions),
+            }
+        }
+
+        fn update(&self, id: Uuid, update: impl FnOnce(&mut Session)) {
+            if let Some(session) = self.sessions.lock().iter_mut().find(|s| s.id == id) {
+                update(session);
+            }
+        }
+    }
+
+    #[async_trait]
+    impl SessionRepository for InMemorySessionRepository {
+        async fn find_by_id(&self, id: Uuid) -> Result<Option<Session>, AppError> {
+            Ok(self.sessions.lock().iter().find(|s| s.id == id).cloned())
+        }
+        async fn find_by_token_hash(&self, token_hash: &str) -> Result<Option<Session>, AppError> {
+            Ok(self
+                .sessions
+                .lock()
+                .iter()
+                .find(|s| s.refresh_token_hash == token_hash)
+                .cloned())
+        }
+        async fn find_by_user_id(&self, user_id: i64) -> Result<Vec<Session>, AppError> {
+            Ok(self
+                .sessions
+                .lock()
+                .iter()
+                .filter(|s| s.user_id == user_id && s.is_active())
+                .cloned()
+                .collect())
+        }
+        async fn create(&self, session: &Session) -> Result<Session, AppError> {
+            self.sessions.lock().push(session.clone());
+            Ok(session.clone())
+        }
+        async fn touch(&self, id: Uuid) -> Result<(), AppError> {
+            self.update(id, |s| s.last_used_at = Utc::now());
+            Ok(())
+        }
+        async fn update_token_hash(&self, id: Uuid, hash: &str, expires_at: DateTime<Utc>) -> Result<(), AppError> {
+            self.update(id, |s| {
+                s.refresh_token_hash = hash.to_string();
+                s.expires_at = expires_at;
+            });
+            Ok(())
+        }
+        async fn revoke(&self, id: Uuid) -> Result<(), AppError> {
+            self.update(id, |s| s.revoked_at = Some(Utc::now()));
+            Ok(())
+        }
+        async fn revoke_all_for_user(&self, user_id: i64, except: Option<Uuid>) -> Result<i64, AppError> {
+            let mut revoked = 0;
+            for session in self.sessions.lock().iter_mut() {
+                if session.user_id == user_id && Some(session.id) != except && session.revoked_at.is_none() {
+                    session.revoked_at = Some(Utc::now());
+                    revoked += 1;
+                }
+            }
+            Ok(revoked)
+        }
+        async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+            self.sessions.lock().retain(|s| s.id != id);
+            Ok(())
+        }
+        async fn cleanup_expired(&self) -> Result<i64, AppError> {
+            let mut sessions = self.sessions.lock();
+            let before = sessions.len();
+            sessions.retain(Session::is_active);
+            Ok((before - sessions.len()) as i64)
+        }
+        async fn count_active(&self, user_id: i64) -> Result<i64, AppError> {
+            Ok(self.find_by_user_id(user_id).await?.len() as i64)
+        }
+        async fn find_by_ip(&self, ip_address: IpAddr) -> Result<Vec<Session>, AppError> {
+            Ok(self
+                .sessions
+                .lock()
+                .iter()
+                .filter(|s| s.ip_address == Some(ip_address))
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn stored_session(token_hash: &str) -> Session {
+        Session::new(1, token_hash.to_string(), Utc::now() + chrono::Duration::days(1))
+    }
+
+    fn failing_service(sessions: Vec<Session>) -> (SessionCacheService<FailingCache>, FailingCache) {
+        let cache = FailingCache::default();
+        let service = SessionCacheService::with_cache(cache.clone())
+            .with_session_repository(Arc::new(InMemorySessionRepository::with_sessions(sessions)));
+        (service, cache)
+    }
+
+    fn session(
+        status: UserStatus,
+        custom_status: Option<&str>,
+        updated_at: i64,
+    ) -> SessionPresence {
+        SessionPresence {
+            status,
+            custom_status: custom_status.map(String::from),
+            activities: Vec::new(),
+            updated_at,
+        }
+    }
+
+    #[test]
+    fn test_effective_presence_uses_status_precedence() {
+        let sessions = vec![
+            session(UserStatus::Dnd, None, 1),
+            session(UserStatus::Online, None, 2),
+            session(UserStatus::Idle, None, 3),
+        ];
+
+        let presence = UserPresence::from_sessions(1, &sessions, vec![]);
+        assert_eq!(presence.status, UserStatus::Online);
+    }
+
+    #[test]
+    fn test_effective_presence_takes_latest_custom_status() {
+        let sessions = vec![
+            session(UserStatus::Online, Some("old"), 1),
+            session(UserStatus::Idle, Some("new"), 5),
+        ];
+
+        let presence = UserPresence::from_sessions(1, &sessions, vec![]);
+        assert_eq!(presence.custom_status.as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn test_no_sessions_is_offline() {
+        let presence = UserPresence::from_sessions(1, &[], vec![]);
+        assert_eq!(presence.status, UserStatus::Offline);
+        assert!(presence.custom_status.is_none());
+    }
+
+    #[test]
+    fn test_custom_status_length_validation() {
+        let ok = "a".repeat(MAX_CUSTOM_STATUS_LENGTH);
+        let too_long = "a".repeat(MAX_CUSTOM_STATUS_LENGTH + 1);
+
+        assert!(session(UserStatus::Online, Some(&ok), 0).validate().is_ok());
+        assert!(session(UserStatus::Online, Some(&too_long), 0).validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_session_falls_back_to_repository() {
+        let stored = stored_session("hash");
+        let (service, _) = failing_service(vec![stored.clone()]);
+
+        let session = service.get_session("hash").await.unwrap().unwrap();
+        assert_eq!(session.session_id, stored.id.to_string());
+        assert!(service.get_session("other").await.unwrap().is_none());
+        assert!(service.session_exists("hash").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_fallback_ignores_revoked_sessions() {
+        let mut stored = stored_session("hash");
+        stored.revoked_at = Some(Utc::now());
+        let (service, _) = failing_service(vec![stored]);
+
+        assert!(service.get_session("hash").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failure_opens_circuit_breaker() {
+        let (service, cache) = failing_service(vec![stored_session("hash")]);
+
+        service.get_session("hash").await.unwrap();
+        service.get_session("hash").await.unwrap();
+        service.set_session("hash", &CachedSession::from(&stored_session("hash"))).await.unwrap();
+
+        assert_eq!(cache.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_set_presence_degrades_to_session_presence() {
+        let (service, _) = failing_service(Vec::new());
+        let presence = session(UserStatus::Idle, Some("away"), 0);
+
+        let effective = service.set_presence(1, "s1", &presence, vec![], PresenceVisibility::Everyone).await.unwrap();
+        assert_eq!(effective.status, UserStatus::Idle);
+        assert_eq!(effective.custom_status.as_deref(), Some("away"));
+        assert!(service.get_presence(1).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_presence_combines_sessions_through_cache() {
+        let service = SessionCacheService::with_cache(InMemoryCache::new());
+
+        service
+            .set_presence(1, "s1", &session(UserStatus::Online, None, 0), vec![], PresenceVisibility::Everyone)
+            .await
+            .unwrap();
+        service
+            .set_presence(1, "s2", &session(UserStatus::Dnd, None, 0), vec![], PresenceVisibility::Everyone)
+            .await
+            .unwrap();
+        let effective = service
+            .remove_session_presence(1, "s1", vec![], PresenceVisibility::Everyone)
+            .await
+            .unwrap();
+
+        assert_eq!(effective.status, UserStatus::Dnd);
+        let stored = service.get_presence(1).await.unwrap().unwrap();
+        assert_eq!(stored.status, UserStatus::Dnd);
+    }
+
+    #[tokio::test]
+    async fn test_hidden_presence_appears_offline_to_others() {
+        let service = SessionCacheService::with_cache(InMemoryCache::new());
+        let presence = session(UserStatus::Online, Some("busy"), 0);
+
+        let effective = service
+            .set_presence(1, "s1", &presence, vec![10], PresenceVisibility::None)
+            .await
+            .unwrap();
+        service
+            .set_presence(2, "s2", &presence, vec![10], PresenceVisibility::Everyone)
+            .await
+            .unwrap();
+
+        // The user still sees their real status
+        assert_eq!(effective.status, UserStatus::Online);
+        assert_eq!(service.get_presence(1).await.unwrap().unwrap().status, UserStatus::Online);
+
+        let seen = effective.seen_by_others();
+        assert_eq!(seen.status, UserStatus::Offline);
+        assert!(seen.custom_status.is_none());
+
+        let others: HashMap<i64, UserPresence> =
+            service.get_presences(&[1, 2]).await.unwrap().into_iter().collect();
+        assert_eq!(others[&1].status, UserStatus::Offline);
+        assert_eq!(others[&2].status, UserStatus::Online);
+        assert_eq!(service.count_guild_online(10).await.unwrap(), 1);
+
+        // Friends-only presence is hidden too while there are no friendships
+        let shown = service.update_visibility(1, PresenceVisibility::Friends).await.unwrap().unwrap();
+        assert_eq!(shown.seen_by_others().status, UserStatus::Offline);
+        let shown = service.update_visibility(1, PresenceVisibility::Everyone).await.unwrap().unwrap();
+        assert_eq!(shown.seen_by_others().status, UserStatus::Online);
+        assert_eq!(service.count_guild_online(10).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_guild_online_count_follows_presence() {
+        let service = SessionCacheService::with_cache(InMemoryCache::new());
+        let online = session(UserStatus::Online, None, 0);
+
+        service
+            .set_presence(1, "s1", &online, vec![10, 20], PresenceVisibility::Everyone)
+            .await
+            .unwrap();
+        service
+            .set_presence(2, "s2", &session(UserStatus::Idle, None, 0), vec![10], PresenceVisibility::Everyone)
+            .await
+            .unwrap();
+        assert_eq!(service.count_guild_online(10).await.unwrap(), 2);
+        assert_eq!(service.count_guild_online(20).await.unwrap(), 1);
+        assert_eq!(service.count_guild_online(30).await.unwrap(), 0);
+
+        // Going offline and disconnecting both leave the count
+        service.update_status(1, UserStatus::Offline).await.unwrap();
+        assert_eq!(service.count_guild_online(20).await.unwrap(), 0);
+        service
+            .remove_session_presence(2, "s2", vec![10], PresenceVisibility::Everyone)
+            .await
+            .unwrap();
+        assert_eq!(service.count_guild_online(10).await.unwrap(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_session_updates_keep_every_session() {
+        let cache = InMemoryCache::new();
+        let service = SessionCacheService::with_cache(cache.clone());
+
+        let updates = (0..8).map(|i| {
+            let service = service.clone();
+            tokio::spawn(async move {
+                let presence = session(UserStatus::Idle, None, 0);
+                service
+                    .set_presence(1, &format!("s{i}"), &presence, vec![], PresenceVisibility::Everyone)
+                    .await
+                    .unwrap();
+            })
+        });
+        for update in updates.collect::<Vec<_>>() {
+            update.await.unwrap();
+        }
+
+        let stored = cache.hvals_raw(&keys::session_presences(1)).await.unwrap();
+        assert_eq!(stored.len(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_presence_changes_are_published() {
+        let cache = InMemoryCache::new();
+        let service = SessionCacheService::with_cache(cache.clone());
+        let mut updates = cache.subscribe();
+
+        service
+            .set_presence(1, "s1", &session(UserStatus::Dnd, None, 0), vec![10], PresenceVisibility::Everyone)
+            .await
+            .unwrap();
+        service
+            .remove_session_presence(1, "s1", vec![10], PresenceVisibility::Everyone)
+            .await
+            .unwrap();
+
+        for expected in [UserStatus::Dnd, UserStatus::Offline] {
+            let (channel, payload) = updates.try_recv().unwrap();
+            assert_eq!(channel, keys::PRESENCE_UPDATES);
+            let published: UserPresence = serde_json::from_str(&payload).unwrap();
+            assert_eq!(published.status, expected);
+            assert_eq!(published.guild_ids, vec![10]);
         }
     }
 }
@@ -0,0 +1,68 @@
+//! Slowmode
+//!
+//! Enforces a channel's `rate_limit_per_user`. A sent message sets
+//! `slowmode:{channel_id}:{user_id}` for the channel's slowmode; the user's
+//! next message is refused until the key expires.
+
+use super::{keys, Cache, RedisCache, RedisPool};
+use crate::shared::error::AppError;
+
+/// Per-user message cooldowns in slowmode channels
+#[derive(Clone)]
+pub struct Slowmode<C: Cache = RedisCache> {
+    cache: C,
+}
+
+impl Slowmode {
+    /// Create a new slowmode tracker
+    pub fn new(redis: RedisPool) -> Self {
+        Self::with_cache(RedisCache::new(redis))
+    }
+}
+
+impl<C: Cache> Slowmode<C> {
+    /// Create a slowmode tracker over any cache backend
+    pub fn with_cache(cache: C) -> Self {
+        Self { cache }
+    }
+
+    /// Seconds until a user may send again in a channel, or `None` if they
+    /// are not cooling down from an earlier message
+    pub async fn remaining(&self, channel_id: i64, user_id: i64) -> Result<Option<u64>, AppError> {
+        let ttl = self.cache.ttl(&keys::slowmode(channel_id, user_id)).await?;
+        Ok(ttl.map(|secs| secs.max(1) as u64))
+    }
+
+    /// Start a user's cooldown of `seconds` in a channel after they sent a
+    /// message; zero starts none
+    pub async fn start(&self, channel_id: i64, user_id: i64, seconds: u64) -> Result<(), AppError> {
+        if seconds == 0 {
+            return Ok(());
+        }
+
+        self.cache.set_ex(&keys::slowmode(channel_id, user_id), &1, seconds).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::cache::InMemoryCache;
+
+    #[tokio::test]
+    async fn test_second_message_waits_for_the_slowmode() {
+        let slowmode = Slowmode::with_cache(InMemoryCache::new());
+
+        assert_eq!(slowmode.remaining(1, 10).await.unwrap(), None);
+        slowmode.start(1, 10, 30).await.unwrap();
+        let retry_after = slowmode.remaining(1, 10).await.unwrap().unwrap();
+        assert!((1..=30).contains(&retry_after));
+
+        // Each user and channel has its own cooldown
+        assert_eq!(slowmode.remaining(1, 11).await.unwrap(), None);
+        assert_eq!(slowmode.remaining(2, 10).await.unwrap(), None);
+        // No slowmode, no cooldown
+        slowmode.start(3, 10, 0).await.unwrap();
+        assert_eq!(slowmode.remaining(3, 10).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,56 @@
+//! Token Blacklist
+//!
+//! Redis-based record of revoked auth sessions.
+//!
+//! Access tokens are stateless JWTs, so deleting a session row does not
+//! invalidate access tokens already issued for it. Revoked session IDs (the
+//! `sid` claim) are kept here until those tokens would have expired anyway.
+
+use redis::AsyncCommands;
+
+use crate::shared::error::AppError;
+use super::{keys, RedisPool};
+
+/// Revoked auth session blacklist
+#[derive(Clone)]
+pub struct TokenBlacklist {
+    redis: RedisPool,
+    revocation_ttl: u64,
+}
+
+impl TokenBlacklist {
+    /// Create a new blacklist
+    ///
+    /// `revocation_ttl` should be at least the access token lifetime in seconds.
+    pub fn new(redis: RedisPool, revocation_ttl: u64) -> Self {
+        Self {
+            redis,
+            revocation_ttl,
+        }
+    }
+
+    /// Mark an auth session as revoked
+    pub async fn revoke_session(&self, session_id: &str) -> Result<(), AppError> {
+        let key = keys::revoked_session(session_id);
+
+        let mut conn = self.redis.clone();
+        conn.set_ex::<_, _, ()>(&key, 1, self.revocation_ttl)
+            .await
+            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Check whether an auth session has been revoked
+    pub async fn is_session_revoked(&self, session_id: &str) -> Result<bool, AppError> {
+        let key = keys::revoked_session(session_id);
+
+        let mut conn = self.redis.clone();
+        let exists: bool = conn
+            .exists(&key)
+            .await
+            .map_err(|e| AppError::Internal(format!("Redis error: {}", e)))?;
+
+        Ok(exists)
+    }
+}
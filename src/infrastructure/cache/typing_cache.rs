@@ -2,22 +2,21 @@
 //!
 //! Redis-based caching for typing indicators in channels.
 
-use redis::aio::ConnectionManager;
 use redis::AsyncCommands;
 
 use crate::shared::error::AppError;
-use super::keys;
+use super::{keys, RedisPool};
 
 /// Typing indicator cache service
 #[derive(Clone)]
 pub struct TypingCacheService {
-    redis: ConnectionManager,
+    redis: RedisPool,
     typing_ttl: u64,
 }
 
 impl TypingCacheService {
     /// Create a new typing cache service
-    pub fn new(redis: ConnectionManager) -> Self {
+    pub fn new(redis: RedisPool) -> Self {
         Self {
             redis,
             typing_ttl: 10, // 10 seconds (Discord standard)
@@ -25,7 +24,7 @@ impl TypingCacheService {
     }
 
     /// Create with custom TTL
-    pub fn with_ttl(redis: ConnectionManager, typing_ttl: u64) -> Self {
+    pub fn with_ttl(redis: RedisPool, typing_ttl: u64) -> Self {
         Self { redis, typing_ttl }
     }
 
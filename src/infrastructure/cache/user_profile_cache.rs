@@ -0,0 +1,221 @@
+//! User Profile Cache
+//!
+//! Caches user profiles under `user:{id}` keys so message pages can resolve
+//! their authors without a database round-trip. Profiles are written after
+//! a database lookup, expire after a short TTL, and are invalidated when a
+//! user updates their profile.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{PresenceVisibility, User, UserRepository, UserStatus};
+use crate::shared::error::AppError;
+use super::{keys, Cache, RedisCache, RedisPool};
+
+/// Default time a cached profile is trusted
+pub const DEFAULT_PROFILE_TTL: u64 = 5 * 60;
+
+/// Cached user profile; everything but credentials
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedUserProfile {
+    pub id: i64,
+    pub username: String,
+    pub email: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    #[serde(default)]
+    pub banner_url: Option<String>,
+    pub status: UserStatus,
+    pub bio: Option<String>,
+    #[serde(default)]
+    pub presence_visibility: PresenceVisibility,
+    #[serde(default)]
+    pub nsfw_allowed: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<User> for CachedUserProfile {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            display_name: user.display_name,
+            avatar_url: user.avatar_url,
+            banner_url: user.banner_url,
+            status: user.status,
+            bio: user.bio,
+            presence_visibility: user.presence_visibility,
+            nsfw_allowed: user.nsfw_allowed,
+            created_at: user.created_at,
+        }
+    }
+}
+
+/// User profile cache service
+#[derive(Clone)]
+pub struct UserProfileCache<C: Cache = RedisCache> {
+    cache: C,
+    ttl: u64,
+}
+
+impl UserProfileCache {
+    /// Create a new user profile cache
+    pub fn new(redis: RedisPool) -> Self {
+        Self::with_cache(RedisCache::new(redis))
+    }
+}
+
+impl<C: Cache> UserProfileCache<C> {
+    /// Create a user profile cache over any cache backend
+    pub fn with_cache(cache: C) -> Self {
+        Self {
+            cache,
+            ttl: DEFAULT_PROFILE_TTL,
+        }
+    }
+
+    /// Set how long a profile is cached
+    pub fn with_ttl(mut self, seconds: u64) -> Self {
+        self.ttl = seconds;
+        self
+    }
+
+    /// Cached profiles for the given users, keyed by user ID.
+    ///
+    /// Users without a cached profile are left out.
+    pub async fn get_many(&self, user_ids: &[i64]) -> Result<HashMap<i64, CachedUserProfile>, AppError> {
+        let keys: Vec<String> = user_ids.iter().map(keys::user).collect();
+        let key_refs: Vec<&str> = keys.iter().map(String::as_str).collect();
+
+        let profiles: Vec<Option<CachedUserProfile>> = self.cache.get_many(&key_refs).await?;
+
+        Ok(profiles
+            .into_iter()
+            .flatten()
+            .map(|profile| (profile.id, profile))
+            .collect())
+    }
+
+    /// Cache profiles loaded from the database
+    pub async fn set_many(&self, profiles: &[CachedUserProfile]) -> Result<(), AppError> {
+        let keys: Vec<String> = profiles.iter().map(|p| keys::user(p.id)).collect();
+        let entries: Vec<(&str, &CachedUserProfile)> =
+            keys.iter().map(String::as_str).zip(profiles).collect();
+
+        self.cache.mset_ex(&entries, self.ttl).await
+    }
+
+    /// A user's profile, from the cache if present and otherwise loaded from
+    /// `user_repo` and cached; `None` if the user doesn't exist.
+    ///
+    /// Cache failures are logged and fall back to the database.
+    pub async fn get_or_load<U>(&self, user_repo: &U, user_id: i64) -> Result<Option<CachedUserProfile>, AppError>
+    where
+        U: UserRepository + ?Sized,
+    {
+        match self.get_many(&[user_id]).await {
+            Ok(mut cached) => {
+                if let Some(profile) = cached.remove(&user_id) {
+                    return Ok(Some(profile));
+                }
+            }
+            Err(e) => tracing::warn!(user_id, error = %e, "Failed to read cached profile"),
+        }
+
+        let Some(profile) = user_repo.find_by_id(user_id).await?.map(CachedUserProfile::from) else {
+            return Ok(None);
+        };
+        if let Err(e) = self.set_many(std::slice::from_ref(&profile)).await {
+            tracing::warn!(user_id, error = %e, "Failed to cache profile");
+        }
+        Ok(Some(profile))
+    }
+
+    /// Drop a user's cached profile after it changed
+    pub async fn invalidate(&self, user_id: i64) -> Result<(), AppError> {
+        self.cache.delete(&keys::user(user_id)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::cache::InMemoryCache;
+
+    fn profile(id: i64) -> CachedUserProfile {
+        CachedUserProfile {
+            id,
+            username: format!("user{}", id),
+            email: format!("user{}@example.com", id),
+            display_name: None,
+            avatar_url: None,
+            banner_url: None,
+            status: UserStatus::Online,
+            bio: None,
+            presence_visibility: PresenceVisibility::Everyone,
+            nsfw_allowed: false,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn profiles() -> UserProfileCache<InMemoryCache> {
+        UserProfileCache::with_cache(InMemoryCache::new())
+    }
+
+    #[tokio::test]
+    async fn test_cached_profiles_are_returned_by_id() {
+        let cache = profiles();
+        cache.set_many(&[profile(1), profile(2)]).await.unwrap();
+
+        let cached = cache.get_many(&[1, 2, 3]).await.unwrap();
+
+        assert_eq!(cached.len(), 2);
+        assert_eq!(cached[&1].username, "user1");
+        assert_eq!(cached[&2].email, "user2@example.com");
+        assert!(!cached.contains_key(&3));
+    }
+
+    #[tokio::test]
+    async fn test_invalidated_profile_is_gone() {
+        let cache = profiles();
+        cache.set_many(&[profile(1)]).await.unwrap();
+
+        cache.invalidate(1).await.unwrap();
+
+        assert!(cache.get_many(&[1]).await.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "db-tests")]
+    #[tokio::test]
+    async fn test_loaded_profile_is_served_from_the_cache_until_invalidated() {
+        use crate::infrastructure::repositories::{test_db, PgUserRepository};
+
+        let pool = test_db::pool().await;
+        let user = test_db::user(&pool).await;
+        let repo = PgUserRepository::new(pool.clone());
+        let cache = profiles();
+        let set_visibility = |visibility: &'static str| {
+            sqlx::query("UPDATE users SET presence_visibility = $2 WHERE id = $1")
+                .bind(user)
+                .bind(visibility)
+                .execute(&pool)
+        };
+
+        let loaded = cache.get_or_load(&repo, user).await.unwrap().unwrap();
+        assert_eq!(loaded.presence_visibility, PresenceVisibility::Everyone);
+
+        // Later reads don't see the database until the profile is dropped
+        set_visibility("none").await.unwrap();
+        let cached = cache.get_or_load(&repo, user).await.unwrap().unwrap();
+        assert_eq!(cached.presence_visibility, PresenceVisibility::Everyone);
+        cache.invalidate(user).await.unwrap();
+        let reloaded = cache.get_or_load(&repo, user).await.unwrap().unwrap();
+        assert_eq!(reloaded.presence_visibility, PresenceVisibility::None);
+
+        assert!(cache.get_or_load(&repo, test_db::id()).await.unwrap().is_none());
+    }
+}
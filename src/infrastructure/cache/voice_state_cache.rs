@@ -0,0 +1,51 @@
+//! Voice State Cache
+//!
+//! Redis-based storage for users' voice channel connection state.
+
+use crate::domain::VoiceState;
+use crate::shared::error::AppError;
+use super::{keys, Cache, RedisCache, RedisPool};
+
+/// Voice state cache service
+#[derive(Clone)]
+pub struct VoiceStateCacheService<C: Cache = RedisCache> {
+    cache: C,
+    voice_state_ttl: u64,
+}
+
+impl VoiceStateCacheService {
+    /// Create a new voice state cache service
+    pub fn new(redis: RedisPool) -> Self {
+        Self::with_cache(RedisCache::new(redis))
+    }
+}
+
+impl<C: Cache> VoiceStateCacheService<C> {
+    /// Create a voice state cache over any cache backend
+    pub fn with_cache(cache: C) -> Self {
+        Self {
+            cache,
+            voice_state_ttl: 24 * 60 * 60, // 24 hours, guards against orphaned states
+        }
+    }
+
+    /// Store a user's voice state
+    pub async fn set_voice_state(&self, state: &VoiceState) -> Result<(), AppError> {
+        let key = keys::voice_state(state.server_id, state.user_id);
+        self.cache.set_ex(&key, state, self.voice_state_ttl).await
+    }
+
+    /// Get a user's voice state in a server
+    pub async fn get_voice_state(
+        &self,
+        server_id: i64,
+        user_id: i64,
+    ) -> Result<Option<VoiceState>, AppError> {
+        self.cache.get(&keys::voice_state(server_id, user_id)).await
+    }
+
+    /// Remove a user's voice state in a server
+    pub async fn delete_voice_state(&self, server_id: i64, user_id: i64) -> Result<bool, AppError> {
+        self.cache.delete(&keys::voice_state(server_id, user_id)).await
+    }
+}
@@ -0,0 +1,70 @@
+//! Snowflake Worker ID Assignment
+//!
+//! Hands out snowflake worker IDs from a shared Redis counter so instances
+//! in a cluster get distinct IDs without per-instance configuration.
+//!
+//! Each instance increments `snowflake:worker_id_seq` once at startup and
+//! takes the result modulo the worker ID space, so an ID is only handed out
+//! again after [`MAX_WORKER_ID`] + 1 further starts.
+
+use super::{keys, Cache, RedisCache, RedisPool};
+use crate::shared::error::AppError;
+use crate::shared::snowflake::MAX_WORKER_ID;
+
+/// Worker ID for the `sequence`-th startup
+pub fn worker_id_from_sequence(sequence: i64) -> u16 {
+    sequence.rem_euclid(i64::from(MAX_WORKER_ID) + 1) as u16
+}
+
+/// Assigns worker IDs from the shared counter
+#[derive(Clone)]
+pub struct WorkerIdAllocator<C: Cache = RedisCache> {
+    cache: C,
+}
+
+impl WorkerIdAllocator {
+    /// Create a new worker ID allocator
+    pub fn new(redis: RedisPool) -> Self {
+        Self::with_cache(RedisCache::new(redis))
+    }
+}
+
+impl<C: Cache> WorkerIdAllocator<C> {
+    /// Create a worker ID allocator over any cache backend
+    pub fn with_cache(cache: C) -> Self {
+        Self { cache }
+    }
+
+    /// Take the next worker ID
+    pub async fn assign(&self) -> Result<u16, AppError> {
+        let sequence = self.cache.incr(keys::WORKER_ID_SEQ).await?;
+        Ok(worker_id_from_sequence(sequence))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::cache::InMemoryCache;
+
+    #[test]
+    fn test_sequence_wraps_around_worker_space() {
+        assert_eq!(worker_id_from_sequence(1), 1);
+        assert_eq!(worker_id_from_sequence(i64::from(MAX_WORKER_ID)), MAX_WORKER_ID);
+        assert_eq!(worker_id_from_sequence(i64::from(MAX_WORKER_ID) + 1), 0);
+        assert_eq!(worker_id_from_sequence(i64::from(MAX_WORKER_ID) + 2), 1);
+        assert!(worker_id_from_sequence(i64::MAX) <= MAX_WORKER_ID);
+        assert!(worker_id_from_sequence(-1) <= MAX_WORKER_ID);
+    }
+
+    #[tokio::test]
+    async fn test_instances_get_distinct_ids() {
+        let cache = InMemoryCache::new();
+        let first = WorkerIdAllocator::with_cache(cache.clone());
+        let second = WorkerIdAllocator::with_cache(cache);
+
+        let a = first.assign().await.unwrap();
+        let b = second.assign().await.unwrap();
+        assert_ne!(a, b);
+    }
+}
@@ -5,7 +5,7 @@
 pub mod unit_of_work;
 
 use sqlx::postgres::PgPoolOptions;
-use sqlx::PgPool;
+use sqlx::{Executor, PgPool};
 use std::time::Duration;
 
 use crate::config::DatabaseSettings;
@@ -15,16 +15,50 @@ pub use unit_of_work::{
 };
 
 /// Create a PostgreSQL connection pool
+///
+/// Every connection gets the configured `statement_timeout`, so a runaway
+/// query is cancelled instead of holding its connection indefinitely.
 pub async fn create_pool(settings: &DatabaseSettings) -> Result<PgPool, sqlx::Error> {
+    let session_setup = statement_timeout_command(settings.statement_timeout);
+
     PgPoolOptions::new()
         .max_connections(settings.max_connections)
         .min_connections(settings.min_connections)
         .acquire_timeout(Duration::from_secs(settings.acquire_timeout))
+        .after_connect(move |conn, _meta| {
+            let session_setup = session_setup.clone();
+            Box::pin(async move {
+                if let Some(command) = session_setup {
+                    conn.execute(command.as_str()).await?;
+                }
+                Ok(())
+            })
+        })
         .connect(&settings.url)
         .await
 }
 
+/// `SET` command applying a statement timeout of `seconds` to a session,
+/// or None if statements may run without limit
+fn statement_timeout_command(seconds: u64) -> Option<String> {
+    (seconds > 0).then(|| format!("SET statement_timeout = {}", seconds.saturating_mul(1000)))
+}
+
 /// Run database migrations
 pub async fn run_migrations(pool: &PgPool) -> Result<(), sqlx::migrate::MigrateError> {
     sqlx::migrate!("./migrations").run(pool).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statement_timeout_is_set_in_milliseconds() {
+        assert_eq!(
+            statement_timeout_command(30).as_deref(),
+            Some("SET statement_timeout = 30000")
+        );
+        assert_eq!(statement_timeout_command(0), None);
+    }
+}
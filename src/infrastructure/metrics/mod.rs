@@ -6,11 +6,13 @@
 //! - HTTP request counts by method, path, and status
 //! - HTTP request latency histograms
 //! - Active WebSocket connection gauges
+//! - WebSocket slow-client disconnect counts
 //! - Database query duration histograms
 
 use once_cell::sync::Lazy;
 use prometheus::{
-    Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+    Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry,
+    TextEncoder,
 };
 
 /// Global metrics registry
@@ -58,6 +60,39 @@ pub static WEBSOCKET_CONNECTIONS_ACTIVE: Lazy<GaugeVec> = Lazy::new(|| {
     .expect("Failed to create WEBSOCKET_CONNECTIONS_ACTIVE metric")
 });
 
+/// WebSocket sessions closed because the client couldn't keep up with its events
+pub static WEBSOCKET_SLOW_CLIENT_DISCONNECTS_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    IntCounter::with_opts(
+        Opts::new(
+            "websocket_slow_client_disconnects_total",
+            "Number of WebSocket sessions closed for falling behind on outbound events",
+        )
+        .namespace("chat_server"),
+    )
+    .expect("Failed to create WEBSOCKET_SLOW_CLIENT_DISCONNECTS_TOTAL metric")
+});
+
+/// WebSocket sessions closed, by why they ended
+pub static WEBSOCKET_CLOSES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new("websocket_closes_total", "Number of WebSocket sessions closed").namespace("chat_server"),
+        &["reason"],
+    )
+    .expect("Failed to create WEBSOCKET_CLOSES_TOTAL metric")
+});
+
+/// Permission checks recomputed because the permission cache couldn't be read
+pub static PERMISSION_CACHE_FALLBACKS_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    IntCounter::with_opts(
+        Opts::new(
+            "permission_cache_fallbacks_total",
+            "Number of permission checks recomputed after a permission cache read failed",
+        )
+        .namespace("chat_server"),
+    )
+    .expect("Failed to create PERMISSION_CACHE_FALLBACKS_TOTAL metric")
+});
+
 /// Database query duration histogram
 pub static DB_QUERY_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
     let buckets = vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5];
@@ -93,6 +128,15 @@ fn register_metrics(registry: &Registry) {
     registry
         .register(Box::new(WEBSOCKET_CONNECTIONS_ACTIVE.clone()))
         .expect("Failed to register WEBSOCKET_CONNECTIONS_ACTIVE");
+    registry
+        .register(Box::new(WEBSOCKET_SLOW_CLIENT_DISCONNECTS_TOTAL.clone()))
+        .expect("Failed to register WEBSOCKET_SLOW_CLIENT_DISCONNECTS_TOTAL");
+    registry
+        .register(Box::new(WEBSOCKET_CLOSES_TOTAL.clone()))
+        .expect("Failed to register WEBSOCKET_CLOSES_TOTAL");
+    registry
+        .register(Box::new(PERMISSION_CACHE_FALLBACKS_TOTAL.clone()))
+        .expect("Failed to register PERMISSION_CACHE_FALLBACKS_TOTAL");
     registry
         .register(Box::new(DB_QUERY_DURATION_SECONDS.clone()))
         .expect("Failed to register DB_QUERY_DURATION_SECONDS");
@@ -139,6 +183,21 @@ pub fn set_websocket_connections(connected: i64, authenticated: i64) {
         .set(authenticated as f64);
 }
 
+/// Helper to count a WebSocket session closed for being too slow
+pub fn record_slow_client_disconnect() {
+    WEBSOCKET_SLOW_CLIENT_DISCONNECTS_TOTAL.inc();
+}
+
+/// Helper to count a closed WebSocket session
+pub fn record_websocket_close(reason: &str) {
+    WEBSOCKET_CLOSES_TOTAL.with_label_values(&[reason]).inc();
+}
+
+/// Helper to count a permission check that fell back to recomputation
+pub fn record_permission_cache_fallback() {
+    PERMISSION_CACHE_FALLBACKS_TOTAL.inc();
+}
+
 /// Helper to update database pool stats
 pub fn update_db_pool_stats(idle: u32, active: u32, max: u32) {
     DB_POOL_CONNECTIONS
@@ -163,6 +222,8 @@ mod tests {
         let _ = &*HTTP_REQUESTS_TOTAL;
         let _ = &*HTTP_REQUEST_DURATION_SECONDS;
         let _ = &*WEBSOCKET_CONNECTIONS_ACTIVE;
+        let _ = &*WEBSOCKET_SLOW_CLIENT_DISCONNECTS_TOTAL;
+        let _ = &*WEBSOCKET_CLOSES_TOTAL;
         let _ = &*DB_QUERY_DURATION_SECONDS;
     }
 
@@ -4,9 +4,11 @@
 //! - Database repositories (PostgreSQL)
 //! - Cache implementations (Redis)
 //! - Metrics and observability (Prometheus)
+//! - Attachment file storage (local disk, S3)
 //! - External API clients
 
 pub mod cache;
 pub mod database;
 pub mod metrics;
 pub mod repositories;
+pub mod storage;
@@ -16,8 +16,10 @@ use crate::shared::error::AppError;
 pub struct AttachmentEntity {
     /// Snowflake ID for the attachment
     pub id: i64,
-    /// ID of the message this attachment belongs to
-    pub message_id: i64,
+    /// ID of the message this attachment belongs to (None until posted)
+    pub message_id: Option<i64>,
+    /// ID of the user who uploaded the attachment
+    pub uploader_id: Option<i64>,
     /// Original filename
     pub filename: String,
     /// MIME content type (e.g., "image/png", "application/pdf")
@@ -40,7 +42,8 @@ pub struct AttachmentEntity {
 #[derive(Debug, Clone)]
 pub struct CreateAttachment {
     pub id: i64,
-    pub message_id: i64,
+    pub message_id: Option<i64>,
+    pub uploader_id: Option<i64>,
     pub filename: String,
     pub content_type: Option<String>,
     pub size: i32,
@@ -92,7 +95,7 @@ impl AttachmentRepository for PgAttachmentRepository {
     async fn find_by_id(&self, id: i64) -> Result<Option<AttachmentEntity>, AppError> {
         let attachment = sqlx::query_as::<_, AttachmentEntity>(
             r#"
-            SELECT id, message_id, filename, content_type, size, url,
+            SELECT id, message_id, uploader_id, filename, content_type, size, url,
                    proxy_url, width, height, created_at
             FROM attachments
             WHERE id = $1
@@ -111,7 +114,7 @@ impl AttachmentRepository for PgAttachmentRepository {
     async fn find_by_message_id(&self, message_id: i64) -> Result<Vec<AttachmentEntity>, AppError> {
         let attachments = sqlx::query_as::<_, AttachmentEntity>(
             r#"
-            SELECT id, message_id, filename, content_type, size, url,
+            SELECT id, message_id, uploader_id, filename, content_type, size, url,
                    proxy_url, width, height, created_at
             FROM attachments
             WHERE message_id = $1
@@ -131,14 +134,15 @@ impl AttachmentRepository for PgAttachmentRepository {
     async fn create(&self, attachment: &CreateAttachment) -> Result<AttachmentEntity, AppError> {
         let created = sqlx::query_as::<_, AttachmentEntity>(
             r#"
-            INSERT INTO attachments (id, message_id, filename, content_type, size, url, proxy_url, width, height)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            RETURNING id, message_id, filename, content_type, size, url,
+            INSERT INTO attachments (id, message_id, uploader_id, filename, content_type, size, url, proxy_url, width, height)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id, message_id, uploader_id, filename, content_type, size, url,
                       proxy_url, width, height, created_at
             "#,
         )
         .bind(attachment.id)
         .bind(attachment.message_id)
+        .bind(attachment.uploader_id)
         .bind(&attachment.filename)
         .bind(&attachment.content_type)
         .bind(attachment.size)
@@ -215,14 +219,15 @@ impl PgAttachmentRepository {
         for attachment in attachments {
             let row = sqlx::query_as::<_, AttachmentEntity>(
                 r#"
-                INSERT INTO attachments (id, message_id, filename, content_type, size, url, proxy_url, width, height)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-                RETURNING id, message_id, filename, content_type, size, url,
+                INSERT INTO attachments (id, message_id, uploader_id, filename, content_type, size, url, proxy_url, width, height)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                RETURNING id, message_id, uploader_id, filename, content_type, size, url,
                           proxy_url, width, height, created_at
                 "#,
             )
             .bind(attachment.id)
             .bind(attachment.message_id)
+            .bind(attachment.uploader_id)
             .bind(&attachment.filename)
             .bind(&attachment.content_type)
             .bind(attachment.size)
@@ -255,7 +260,7 @@ impl PgAttachmentRepository {
 
         let attachments = sqlx::query_as::<_, AttachmentEntity>(
             r#"
-            SELECT a.id, a.message_id, a.filename, a.content_type, a.size, a.url,
+            SELECT a.id, a.message_id, a.uploader_id, a.filename, a.content_type, a.size, a.url,
                    a.proxy_url, a.width, a.height, a.created_at
             FROM attachments a
             INNER JOIN messages m ON a.message_id = m.id
@@ -320,7 +325,7 @@ impl PgAttachmentRepository {
             Some(before_id) => {
                 sqlx::query_as::<_, AttachmentEntity>(
                     r#"
-                    SELECT a.id, a.message_id, a.filename, a.content_type, a.size, a.url,
+                    SELECT a.id, a.message_id, a.uploader_id, a.filename, a.content_type, a.size, a.url,
                            a.proxy_url, a.width, a.height, a.created_at
                     FROM attachments a
                     INNER JOIN messages m ON a.message_id = m.id
@@ -340,7 +345,7 @@ impl PgAttachmentRepository {
             None => {
                 sqlx::query_as::<_, AttachmentEntity>(
                     r#"
-                    SELECT a.id, a.message_id, a.filename, a.content_type, a.size, a.url,
+                    SELECT a.id, a.message_id, a.uploader_id, a.filename, a.content_type, a.size, a.url,
                            a.proxy_url, a.width, a.height, a.created_at
                     FROM attachments a
                     INNER JOIN messages m ON a.message_id = m.id
@@ -369,7 +374,8 @@ mod tests {
     fn test_create_attachment_struct() {
         let attachment = CreateAttachment {
             id: 123456789,
-            message_id: 987654321,
+            message_id: Some(987654321),
+            uploader_id: Some(111111111),
             filename: "test.png".to_string(),
             content_type: Some("image/png".to_string()),
             size: 1024,
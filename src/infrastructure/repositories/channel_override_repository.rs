@@ -0,0 +1,92 @@
+//! Channel Override Repository Implementation
+//!
+//! PostgreSQL implementation of per-user channel notification overrides.
+//! Only non-default overrides are stored; a missing row means the channel
+//! is unmuted and follows the guild's default notification level.
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::domain::{ChannelOverride, ChannelOverrideRepository};
+use crate::shared::error::AppError;
+
+/// PostgreSQL implementation of the ChannelOverrideRepository.
+pub struct PgChannelOverrideRepository {
+    pool: PgPool,
+}
+
+impl PgChannelOverrideRepository {
+    /// Creates a new PgChannelOverrideRepository with the given connection pool.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ChannelOverrideRepository for PgChannelOverrideRepository {
+    async fn find(&self, user_id: i64, channel_id: i64) -> Result<Option<ChannelOverride>, AppError> {
+        let row = sqlx::query_as::<_, ChannelOverride>(
+            r#"
+            SELECT user_id, channel_id, muted, notification_level, updated_at
+            FROM channel_overrides
+            WHERE user_id = $1 AND channel_id = $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(channel_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn find_by_user(&self, user_id: i64) -> Result<Vec<ChannelOverride>, AppError> {
+        let rows = sqlx::query_as::<_, ChannelOverride>(
+            r#"
+            SELECT user_id, channel_id, muted, notification_level, updated_at
+            FROM channel_overrides
+            WHERE user_id = $1
+            ORDER BY channel_id ASC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn upsert(&self, channel_override: &ChannelOverride) -> Result<ChannelOverride, AppError> {
+        let row = sqlx::query_as::<_, ChannelOverride>(
+            r#"
+            INSERT INTO channel_overrides (user_id, channel_id, muted, notification_level)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, channel_id) DO UPDATE
+            SET muted = EXCLUDED.muted,
+                notification_level = EXCLUDED.notification_level,
+                updated_at = NOW()
+            RETURNING user_id, channel_id, muted, notification_level, updated_at
+            "#,
+        )
+        .bind(channel_override.user_id)
+        .bind(channel_override.channel_id)
+        .bind(channel_override.muted)
+        .bind(channel_override.notification_level)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn delete(&self, user_id: i64, channel_id: i64) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            "DELETE FROM channel_overrides WHERE user_id = $1 AND channel_id = $2",
+        )
+        .bind(user_id)
+        .bind(channel_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -5,9 +5,11 @@
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use sqlx::PgPool;
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgPool};
 
-use crate::domain::{Channel, ChannelRepository, ChannelType, PermissionOverwrite};
+use super::outbox_repository;
+use crate::domain::{Channel, ChannelDeletion, ChannelRepository, ChannelType, PermissionOverwrite};
 use crate::shared::error::AppError;
 
 /// Database row representation matching the actual channels table schema.
@@ -17,12 +19,13 @@ struct ChannelRow {
     server_id: Option<i64>,
     name: String,
     #[sqlx(rename = "type")]
-    channel_type: String, // PostgreSQL ENUM comes as string
+    channel_type: ChannelType,
     topic: Option<String>,
     position: i32,
     parent_id: Option<i64>,
     nsfw: bool,
     rate_limit_per_user: Option<i32>,
+    slowmode_exempt_roles: Json<Vec<i64>>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
@@ -34,12 +37,13 @@ impl ChannelRow {
             id: self.id,
             server_id: self.server_id,
             name: self.name,
-            channel_type: ChannelType::from_str(&self.channel_type),
+            channel_type: self.channel_type,
             topic: self.topic,
             position: self.position,
             parent_id: self.parent_id,
             nsfw: self.nsfw,
             rate_limit_per_user: self.rate_limit_per_user.unwrap_or(0),
+            slowmode_exempt_roles: self.slowmode_exempt_roles.0,
             created_at: self.created_at,
             updated_at: self.updated_at,
         }
@@ -68,6 +72,39 @@ impl PermissionOverwriteRow {
     }
 }
 
+/// Insert one channel on the given connection.
+async fn insert_channel(conn: &mut PgConnection, channel: &Channel) -> Result<Channel, AppError> {
+    let row = sqlx::query_as::<_, ChannelRow>(
+        r#"
+        INSERT INTO channels (id, server_id, name, type, topic, position, parent_id, nsfw, rate_limit_per_user,
+                              slowmode_exempt_roles)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING id, server_id, name, type, topic, position, parent_id, nsfw, rate_limit_per_user,
+                  slowmode_exempt_roles, created_at, updated_at
+        "#,
+    )
+    .bind(channel.id)
+    .bind(channel.server_id)
+    .bind(&channel.name)
+    .bind(channel.channel_type)
+    .bind(&channel.topic)
+    .bind(channel.position)
+    .bind(channel.parent_id)
+    .bind(channel.nsfw)
+    .bind(channel.rate_limit_per_user)
+    .bind(Json(&channel.slowmode_exempt_roles))
+    .fetch_one(conn)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            AppError::Conflict("Channel with this ID already exists".to_string())
+        }
+        _ => AppError::Database(e),
+    })?;
+
+    Ok(row.into_channel())
+}
+
 /// PostgreSQL channel repository implementation.
 ///
 /// Provides CRUD operations for channels against a PostgreSQL database.
@@ -117,7 +154,7 @@ impl ChannelRepository for PgChannelRepository {
         let row = sqlx::query_as::<_, ChannelRow>(
             r#"
             SELECT id, server_id, name, type, topic, position, parent_id, nsfw, rate_limit_per_user,
-                   created_at, updated_at
+                   slowmode_exempt_roles, created_at, updated_at
             FROM channels
             WHERE id = $1 AND deleted_at IS NULL
             "#,
@@ -134,7 +171,7 @@ impl ChannelRepository for PgChannelRepository {
         let rows = sqlx::query_as::<_, ChannelRow>(
             r#"
             SELECT id, server_id, name, type, topic, position, parent_id, nsfw, rate_limit_per_user,
-                   created_at, updated_at
+                   slowmode_exempt_roles, created_at, updated_at
             FROM channels
             WHERE server_id = $1 AND deleted_at IS NULL
             ORDER BY position ASC
@@ -152,7 +189,7 @@ impl ChannelRepository for PgChannelRepository {
         let rows = sqlx::query_as::<_, ChannelRow>(
             r#"
             SELECT id, server_id, name, type, topic, position, parent_id, nsfw, rate_limit_per_user,
-                   created_at, updated_at
+                   slowmode_exempt_roles, created_at, updated_at
             FROM channels
             WHERE parent_id = $1 AND deleted_at IS NULL
             ORDER BY position ASC
@@ -165,45 +202,83 @@ impl ChannelRepository for PgChannelRepository {
         Ok(rows.into_iter().map(|r| r.into_channel()).collect())
     }
 
-    /// Find DM channel between two users.
-    /// Note: Current schema doesn't fully support DM channels with recipients table.
-    async fn find_dm_channel(&self, _user1_id: i64, _user2_id: i64) -> Result<Option<Channel>, AppError> {
-        // DM channel lookup would require a separate dm_participants table
-        // For now, return None as the schema doesn't fully support this
-        Ok(None)
+    /// Find the DM channel between two users.
+    async fn find_dm_channel(&self, user1_id: i64, user2_id: i64) -> Result<Option<Channel>, AppError> {
+        let row = sqlx::query_as::<_, ChannelRow>(
+            r#"
+            SELECT c.id, c.server_id, c.name, c.type, c.topic, c.position, c.parent_id, c.nsfw,
+                   c.rate_limit_per_user, c.slowmode_exempt_roles, c.created_at, c.updated_at
+            FROM channels c
+            JOIN dm_recipients a ON a.channel_id = c.id AND a.user_id = $1
+            JOIN dm_recipients b ON b.channel_id = c.id AND b.user_id = $2
+            WHERE c.type = 'dm' AND c.deleted_at IS NULL
+            ORDER BY c.id
+            LIMIT 1
+            "#,
+        )
+        .bind(user1_id)
+        .bind(user2_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| r.into_channel()))
+    }
+
+    /// Check whether a user is a recipient of a DM channel.
+    async fn is_recipient(&self, channel_id: i64, user_id: i64) -> Result<bool, AppError> {
+        let exists = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM dm_recipients
+                WHERE channel_id = $1 AND user_id = $2
+            )
+            "#,
+        )
+        .bind(channel_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists)
+    }
+
+    /// User IDs of the recipients of a DM channel.
+    async fn find_recipient_ids(&self, channel_id: i64) -> Result<Vec<i64>, AppError> {
+        let ids = sqlx::query_scalar::<_, i64>(
+            "SELECT user_id FROM dm_recipients WHERE channel_id = $1",
+        )
+        .bind(channel_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
     }
 
     /// Create a new channel.
     async fn create(&self, channel: &Channel) -> Result<Channel, AppError> {
-        let channel_type_str = channel.channel_type.as_str();
+        let mut conn = self.pool.acquire().await?;
+        insert_channel(&mut conn, channel).await
+    }
 
-        let row = sqlx::query_as::<_, ChannelRow>(
+    /// Create a DM channel and add its recipients in one transaction.
+    async fn create_dm(&self, channel: &Channel, recipient_ids: &[i64]) -> Result<Channel, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let created = insert_channel(&mut tx, channel).await?;
+        sqlx::query(
             r#"
-            INSERT INTO channels (id, server_id, name, type, topic, position, parent_id, nsfw, rate_limit_per_user)
-            VALUES ($1, $2, $3, $4::channel_type, $5, $6, $7, $8, $9)
-            RETURNING id, server_id, name, type, topic, position, parent_id, nsfw, rate_limit_per_user,
-                      created_at, updated_at
+            INSERT INTO dm_recipients (channel_id, user_id)
+            SELECT $1, user_id FROM UNNEST($2::BIGINT[]) AS t(user_id)
+            ON CONFLICT DO NOTHING
             "#,
         )
-        .bind(channel.id)
-        .bind(channel.server_id)
-        .bind(&channel.name)
-        .bind(channel_type_str)
-        .bind(&channel.topic)
-        .bind(channel.position)
-        .bind(channel.parent_id)
-        .bind(channel.nsfw)
-        .bind(channel.rate_limit_per_user)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|e| match &e {
-            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
-                AppError::Conflict("Channel with this ID already exists".to_string())
-            }
-            _ => AppError::Database(e),
-        })?;
+        .bind(created.id)
+        .bind(recipient_ids)
+        .execute(&mut *tx)
+        .await?;
 
-        Ok(row.into_channel())
+        tx.commit().await?;
+        Ok(created)
     }
 
     /// Update an existing channel.
@@ -217,10 +292,11 @@ impl ChannelRepository for PgChannelRepository {
                 parent_id = $5,
                 nsfw = $6,
                 rate_limit_per_user = $7,
+                slowmode_exempt_roles = $8,
                 updated_at = NOW()
             WHERE id = $1
             RETURNING id, server_id, name, type, topic, position, parent_id, nsfw, rate_limit_per_user,
-                      created_at, updated_at
+                      slowmode_exempt_roles, created_at, updated_at
             "#,
         )
         .bind(channel.id)
@@ -230,6 +306,7 @@ impl ChannelRepository for PgChannelRepository {
         .bind(channel.parent_id)
         .bind(channel.nsfw)
         .bind(channel.rate_limit_per_user)
+        .bind(Json(&channel.slowmode_exempt_roles))
         .fetch_optional(&self.pool)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Channel with id {} not found", channel.id)))?;
@@ -251,6 +328,97 @@ impl ChannelRepository for PgChannelRepository {
         Ok(())
     }
 
+    /// Delete a channel and everything attached to it in one transaction.
+    async fn delete_with_dependents(&self, channel: &Channel) -> Result<ChannelDeletion, AppError> {
+        let channel_id = channel.id;
+        let mut tx = self.pool.begin().await?;
+
+        // Children are matched here rather than up front so a channel moved
+        // into the category meanwhile isn't left pointing at it
+        let reparented_children: Vec<i64> = sqlx::query_scalar(
+            r#"
+            UPDATE channels
+            SET parent_id = NULL, updated_at = NOW()
+            WHERE parent_id = $1
+            RETURNING id
+            "#,
+        )
+        .bind(channel_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let invalidated_invites: Vec<String> =
+            sqlx::query_scalar("DELETE FROM invites WHERE channel_id = $1 RETURNING code")
+                .bind(channel_id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        sqlx::query("DELETE FROM channel_permission_overwrites WHERE channel_id = $1")
+            .bind(channel_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM messages WHERE channel_id = $1")
+            .bind(channel_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let result = sqlx::query("DELETE FROM channels WHERE id = $1")
+            .bind(channel_id)
+            .execute(&mut *tx)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Channel with id {} not found", channel_id)));
+        }
+
+        let payload = serde_json::json!({
+            "id": channel_id.to_string(),
+            "guild_id": channel.server_id,
+        });
+        outbox_repository::enqueue(
+            &mut tx,
+            &format!("CHANNEL_DELETE:{}", channel_id),
+            "CHANNEL_DELETE",
+            &payload,
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(ChannelDeletion {
+            channel_id,
+            server_id: channel.server_id,
+            reparented_children,
+            invalidated_invites,
+        })
+    }
+
+    /// Close or reopen a DM for one of its recipients.
+    async fn set_dm_closed(&self, channel_id: i64, user_id: i64, closed: bool) -> Result<(), AppError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE dm_recipients
+            SET closed_at = CASE WHEN $3 THEN NOW() END
+            WHERE channel_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(channel_id)
+        .bind(user_id)
+        .bind(closed)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!(
+                "User {} is not a recipient of channel {}",
+                user_id, channel_id
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Update channel positions (for reordering).
     async fn update_positions(
         &self,
@@ -276,6 +444,29 @@ impl ChannelRepository for PgChannelRepository {
         Ok(rows.into_iter().map(|r| r.into_permission_overwrite()).collect())
     }
 
+    /// Get permission overwrites for several channels in one query.
+    async fn get_permission_overwrites_for_channels(
+        &self,
+        channel_ids: &[i64],
+    ) -> Result<Vec<PermissionOverwrite>, AppError> {
+        if channel_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query_as::<_, PermissionOverwriteRow>(
+            r#"
+            SELECT channel_id, target_type, target_id, allow, deny
+            FROM channel_permission_overwrites
+            WHERE channel_id = ANY($1)
+            "#,
+        )
+        .bind(channel_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into_permission_overwrite()).collect())
+    }
+
     /// Set permission overwrites for a channel.
     /// Replaces all existing overwrites.
     async fn set_permission_overwrites(
@@ -311,11 +502,160 @@ impl ChannelRepository for PgChannelRepository {
         tx.commit().await?;
         Ok(())
     }
+
+    /// Insert or update a single overwrite, keyed by channel and target.
+    async fn upsert_permission_overwrite(&self, overwrite: &PermissionOverwrite) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO channel_permission_overwrites (channel_id, target_type, target_id, allow, deny)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (channel_id, target_type, target_id)
+            DO UPDATE SET allow = EXCLUDED.allow, deny = EXCLUDED.deny, updated_at = NOW()
+            "#,
+        )
+        .bind(overwrite.channel_id)
+        .bind(&overwrite.target_type)
+        .bind(overwrite.target_id)
+        .bind(overwrite.allow)
+        .bind(overwrite.deny)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Delete a single overwrite.
+    async fn delete_permission_overwrite(
+        &self,
+        channel_id: i64,
+        target_type: &str,
+        target_id: i64,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM channel_permission_overwrites
+            WHERE channel_id = $1 AND target_type = $2 AND target_id = $3
+            "#,
+        )
+        .bind(channel_id)
+        .bind(target_type)
+        .bind(target_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Create a channel with copied overwrites, shifting existing positions first.
+    ///
+    /// The overwrites are copied inside the transaction, so ones changed
+    /// while the channel is created aren't missed.
+    async fn create_with_overwrites(
+        &self,
+        channel: &Channel,
+        overwrites_from: Option<i64>,
+        positions: &[(i64, i32)],
+    ) -> Result<Channel, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        for (channel_id, position) in positions {
+            sqlx::query(
+                r#"
+                UPDATE channels
+                SET position = $3, updated_at = NOW()
+                WHERE id = $1 AND server_id = $2
+                "#,
+            )
+            .bind(channel_id)
+            .bind(channel.server_id)
+            .bind(position)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let row = sqlx::query_as::<_, ChannelRow>(
+            r#"
+            INSERT INTO channels (id, server_id, name, type, topic, position, parent_id, nsfw, rate_limit_per_user,
+                                  slowmode_exempt_roles)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id, server_id, name, type, topic, position, parent_id, nsfw, rate_limit_per_user,
+                      slowmode_exempt_roles, created_at, updated_at
+            "#,
+        )
+        .bind(channel.id)
+        .bind(channel.server_id)
+        .bind(&channel.name)
+        .bind(channel.channel_type)
+        .bind(&channel.topic)
+        .bind(channel.position)
+        .bind(channel.parent_id)
+        .bind(channel.nsfw)
+        .bind(channel.rate_limit_per_user)
+        .bind(Json(&channel.slowmode_exempt_roles))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if let Some(source_id) = overwrites_from {
+            sqlx::query(
+                r#"
+                INSERT INTO channel_permission_overwrites (channel_id, target_type, target_id, allow, deny)
+                SELECT $1, target_type, target_id, allow, deny
+                FROM channel_permission_overwrites
+                WHERE channel_id = $2
+                "#,
+            )
+            .bind(channel.id)
+            .bind(source_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(row.into_channel())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-
-    // Integration tests would go here
+    #[cfg(feature = "db-tests")]
+    #[tokio::test]
+    async fn test_channel_type_round_trips_through_column() {
+        use sqlx::{Connection, PgConnection};
+
+        use super::*;
+
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let mut conn = PgConnection::connect(&url).await.unwrap();
+        let variants = [
+            ChannelType::Text,
+            ChannelType::Voice,
+            ChannelType::Category,
+            ChannelType::Dm,
+            ChannelType::GroupDm,
+        ];
+
+        // Every database label decodes, in declaration order
+        let labels: Vec<ChannelType> =
+            sqlx::query_scalar("SELECT unnest(enum_range(NULL::channel_type))")
+                .fetch_all(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(labels, variants);
+
+        sqlx::query("CREATE TEMP TABLE channel_type_round_trip (value channel_type NOT NULL)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        for channel_type in variants {
+            let decoded: ChannelType = sqlx::query_scalar(
+                "INSERT INTO channel_type_round_trip (value) VALUES ($1) RETURNING value",
+            )
+            .bind(channel_type)
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+            assert_eq!(decoded, channel_type);
+        }
+    }
 }
@@ -39,7 +39,22 @@ pub struct InviteEntity {
 
 impl InviteEntity {
     /// Convert to domain Invite entity.
+    ///
+    /// A row with a `max_age` but no `expires_at` is reported here, once per
+    /// load, and gets the expiry derived from its creation time.
     pub fn into_invite(self) -> Invite {
+        let expires_at = self.expires_at.or_else(|| {
+            let derived = Invite::expiry_for(self.created_at, self.max_age);
+            if derived.is_some() {
+                tracing::warn!(
+                    code = %self.code,
+                    max_age = self.max_age,
+                    "Invite has a max age but no expiry; deriving it from its creation time"
+                );
+            }
+            derived
+        });
+
         Invite {
             code: self.code,
             server_id: self.server_id,
@@ -49,7 +64,7 @@ impl InviteEntity {
             uses: self.uses,
             max_age: self.max_age,
             temporary: self.temporary,
-            expires_at: self.expires_at,
+            expires_at,
             created_at: self.created_at,
         }
     }
@@ -294,6 +309,40 @@ impl PgInviteRepository {
         Ok(invites)
     }
 
+    /// Find a page of invites for a server, newest first.
+    ///
+    /// `before_code` acts as a cursor: only invites created before it are
+    /// returned. Ties on `created_at` are broken by code. A cursor that is
+    /// not one of the server's invites matches nothing.
+    pub async fn find_by_server_id_paginated(
+        &self,
+        server_id: i64,
+        before_code: Option<&str>,
+        limit: i32,
+    ) -> Result<Vec<InviteEntity>, AppError> {
+        let invites = sqlx::query_as::<_, InviteEntity>(
+            r#"
+            SELECT code, server_id, channel_id, inviter_id, max_uses, uses,
+                   max_age, temporary, expires_at, created_at
+            FROM invites
+            WHERE server_id = $1
+              AND ($2::VARCHAR IS NULL OR (created_at, code) < (
+                  SELECT created_at, code FROM invites
+                  WHERE server_id = $1 AND code = $2
+              ))
+            ORDER BY created_at DESC, code DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(server_id)
+        .bind(before_code)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(invites)
+    }
+
     /// Find only valid (non-expired, not maxed out) invites for a server.
     pub async fn find_valid_by_server_id(
         &self,
@@ -319,12 +368,17 @@ impl PgInviteRepository {
 
     /// Delete all expired invites (cleanup job).
     ///
-    /// Returns the number of invites deleted.
+    /// Invites missing `expires_at` despite a `max_age` expire from their
+    /// creation time, as in [`Invite::expiry`]. Returns the number of invites
+    /// deleted.
     pub async fn delete_expired(&self) -> Result<u64, AppError> {
         let result = sqlx::query(
             r#"
             DELETE FROM invites
-            WHERE expires_at IS NOT NULL AND expires_at <= NOW()
+            WHERE COALESCE(
+                expires_at,
+                CASE WHEN max_age > 0 THEN created_at + make_interval(secs => max_age) END
+            ) <= NOW()
             "#,
         )
         .execute(&self.pool)
@@ -486,6 +540,18 @@ impl DomainInviteRepository for PgInviteRepository {
         Ok(entities.into_iter().map(|e| e.into_invite()).collect())
     }
 
+    async fn find_by_server_id_paginated(
+        &self,
+        server_id: i64,
+        before_code: Option<&str>,
+        limit: i32,
+    ) -> Result<Vec<Invite>, AppError> {
+        let entities = self
+            .find_by_server_id_paginated(server_id, before_code, limit)
+            .await?;
+        Ok(entities.into_iter().map(|e| e.into_invite()).collect())
+    }
+
     async fn find_by_channel_id(&self, channel_id: i64) -> Result<Vec<Invite>, AppError> {
         let entities = self.find_by_channel_id(channel_id).await?;
         Ok(entities.into_iter().map(|e| e.into_invite()).collect())
@@ -566,4 +632,85 @@ mod tests {
             created_at: Utc::now(),
         };
     }
+
+    #[test]
+    fn test_loaded_invite_without_expiry_gets_one_from_max_age() {
+        let created_at = Utc::now();
+        let entity = |max_age, expires_at| InviteEntity {
+            code: "test1234".to_string(),
+            server_id: 1,
+            channel_id: 2,
+            inviter_id: None,
+            max_uses: 0,
+            uses: 0,
+            max_age,
+            temporary: false,
+            expires_at,
+            created_at,
+        };
+
+        let derived = entity(3600, None).into_invite();
+        assert_eq!(derived.expires_at, Some(created_at + chrono::Duration::seconds(3600)));
+        assert_eq!(entity(0, None).into_invite().expires_at, None);
+        let stored = created_at + chrono::Duration::seconds(60);
+        assert_eq!(entity(3600, Some(stored)).into_invite().expires_at, Some(stored));
+    }
+
+    #[cfg(feature = "db-tests")]
+    #[tokio::test]
+    async fn test_paginated_cursor_is_scoped_to_server() {
+        use crate::infrastructure::repositories::test_db;
+
+        let pool = test_db::pool().await;
+        let owner = test_db::user(&pool).await;
+        let server = test_db::server(&pool, owner).await;
+        let other_server = test_db::server(&pool, owner).await;
+        let channel = test_db::channel(&pool, server).await;
+        let other_channel = test_db::channel(&pool, other_server).await;
+
+        let invite = |server_id: i64, channel_id: i64, age_secs: i64| {
+            let pool = pool.clone();
+            async move {
+                let code = format!("{:010}", test_db::id() % 10_000_000_000);
+                sqlx::query(
+                    "INSERT INTO invites (code, server_id, channel_id, created_at) \
+                     VALUES ($1, $2, $3, NOW() - make_interval(secs => $4))",
+                )
+                .bind(&code)
+                .bind(server_id)
+                .bind(channel_id)
+                .bind(age_secs as f64)
+                .execute(&pool)
+                .await
+                .unwrap();
+                code
+            }
+        };
+        let oldest = invite(server, channel, 30).await;
+        let middle = invite(server, channel, 20).await;
+        let newest = invite(server, channel, 10).await;
+        let foreign = invite(other_server, other_channel, 15).await;
+
+        let repo = PgInviteRepository::new(pool.clone());
+        let codes = |invites: Vec<InviteEntity>| {
+            invites.into_iter().map(|invite| invite.code).collect::<Vec<_>>()
+        };
+
+        let page = repo.find_by_server_id_paginated(server, None, 2).await.unwrap();
+        assert_eq!(codes(page), vec![newest.clone(), middle.clone()]);
+
+        let page = repo
+            .find_by_server_id_paginated(server, Some(&newest), 10)
+            .await
+            .unwrap();
+        assert_eq!(codes(page), vec![middle, oldest]);
+
+        // Another server's invite is not a cursor here, even though it sits
+        // between this server's invites in time
+        let page = repo
+            .find_by_server_id_paginated(server, Some(&foreign), 10)
+            .await
+            .unwrap();
+        assert!(page.is_empty());
+    }
 }
@@ -17,6 +17,7 @@ struct MemberRow {
     user_id: i64,
     nickname: Option<String>,
     joined_at: DateTime<Utc>,
+    communication_disabled_until: Option<DateTime<Utc>>,
 }
 
 /// Database row with aggregated roles (for JOIN queries to avoid N+1).
@@ -26,6 +27,7 @@ struct MemberWithRolesRow {
     user_id: i64,
     nickname: Option<String>,
     joined_at: DateTime<Utc>,
+    communication_disabled_until: Option<DateTime<Utc>>,
     /// Aggregated role IDs from LEFT JOIN with member_roles
     role_ids: Option<Vec<i64>>,
 }
@@ -39,6 +41,7 @@ impl MemberRow {
             user_id: self.user_id,
             nickname: self.nickname,
             joined_at: self.joined_at,
+            communication_disabled_until: self.communication_disabled_until,
             roles,
         }
     }
@@ -52,6 +55,7 @@ impl MemberWithRolesRow {
             user_id: self.user_id,
             nickname: self.nickname,
             joined_at: self.joined_at,
+            communication_disabled_until: self.communication_disabled_until,
             // Handle NULL from array_agg when no roles exist
             roles: self.role_ids.unwrap_or_default(),
         }
@@ -134,7 +138,7 @@ impl MemberRepository for PgMemberRepository {
     async fn find(&self, server_id: i64, user_id: i64) -> Result<Option<Member>, AppError> {
         let row = sqlx::query_as::<_, MemberRow>(
             r#"
-            SELECT server_id, user_id, nickname, joined_at
+            SELECT server_id, user_id, nickname, joined_at, communication_disabled_until
             FROM server_members
             WHERE server_id = $1 AND user_id = $2
             "#,
@@ -153,17 +157,46 @@ impl MemberRepository for PgMemberRepository {
         }
     }
 
+    /// Find several members of a server.
+    /// Uses a single query with array_agg to avoid N+1 pattern.
+    async fn find_many(&self, server_id: i64, user_ids: &[i64]) -> Result<Vec<Member>, AppError> {
+        if user_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query_as::<_, MemberWithRolesRow>(
+            r#"
+            SELECT sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                   sm.communication_disabled_until,
+                   ARRAY_REMOVE(ARRAY_AGG(mr.role_id), NULL) as role_ids
+            FROM server_members sm
+            LEFT JOIN member_roles mr ON sm.server_id = mr.server_id AND sm.user_id = mr.user_id
+            WHERE sm.server_id = $1 AND sm.user_id = ANY($2)
+            GROUP BY sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                     sm.communication_disabled_until
+            "#,
+        )
+        .bind(server_id)
+        .bind(user_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into_member()).collect())
+    }
+
     /// Find all servers a user is a member of.
     /// Uses a single query with array_agg to avoid N+1 pattern.
     async fn find_by_user(&self, user_id: i64) -> Result<Vec<Member>, AppError> {
         let rows = sqlx::query_as::<_, MemberWithRolesRow>(
             r#"
             SELECT sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                   sm.communication_disabled_until,
                    ARRAY_REMOVE(ARRAY_AGG(mr.role_id), NULL) as role_ids
             FROM server_members sm
             LEFT JOIN member_roles mr ON sm.server_id = mr.server_id AND sm.user_id = mr.user_id
             WHERE sm.user_id = $1
-            GROUP BY sm.server_id, sm.user_id, sm.nickname, sm.joined_at
+            GROUP BY sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                     sm.communication_disabled_until
             ORDER BY sm.joined_at DESC
             "#,
         )
@@ -187,11 +220,13 @@ impl MemberRepository for PgMemberRepository {
             sqlx::query_as::<_, MemberWithRolesRow>(
                 r#"
                 SELECT sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                       sm.communication_disabled_until,
                        ARRAY_REMOVE(ARRAY_AGG(mr.role_id), NULL) as role_ids
                 FROM server_members sm
                 LEFT JOIN member_roles mr ON sm.server_id = mr.server_id AND sm.user_id = mr.user_id
                 WHERE sm.server_id = $1 AND sm.user_id > $2
-                GROUP BY sm.server_id, sm.user_id, sm.nickname, sm.joined_at
+                GROUP BY sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                         sm.communication_disabled_until
                 ORDER BY sm.user_id ASC
                 LIMIT $3
                 "#,
@@ -205,11 +240,13 @@ impl MemberRepository for PgMemberRepository {
             sqlx::query_as::<_, MemberWithRolesRow>(
                 r#"
                 SELECT sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                       sm.communication_disabled_until,
                        ARRAY_REMOVE(ARRAY_AGG(mr.role_id), NULL) as role_ids
                 FROM server_members sm
                 LEFT JOIN member_roles mr ON sm.server_id = mr.server_id AND sm.user_id = mr.user_id
                 WHERE sm.server_id = $1
-                GROUP BY sm.server_id, sm.user_id, sm.nickname, sm.joined_at
+                GROUP BY sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                         sm.communication_disabled_until
                 ORDER BY sm.user_id ASC
                 LIMIT $2
                 "#,
@@ -223,6 +260,42 @@ impl MemberRepository for PgMemberRepository {
         Ok(rows.into_iter().map(|r| r.into_member()).collect())
     }
 
+    /// Find members in a server in join order with cursor-based pagination.
+    /// The cursor member's join time is looked up, so a cursor whose member
+    /// has since left ends the listing.
+    async fn find_by_server_id_joined(
+        &self,
+        server_id: i64,
+        after: Option<i64>,
+        limit: i32,
+    ) -> Result<Vec<Member>, AppError> {
+        let rows = sqlx::query_as::<_, MemberWithRolesRow>(
+            r#"
+            SELECT sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                   sm.communication_disabled_until,
+                   ARRAY_REMOVE(ARRAY_AGG(mr.role_id), NULL) as role_ids
+            FROM server_members sm
+            LEFT JOIN member_roles mr ON sm.server_id = mr.server_id AND sm.user_id = mr.user_id
+            WHERE sm.server_id = $1
+              AND ($2::BIGINT IS NULL OR (sm.joined_at, sm.user_id) > (
+                  SELECT c.joined_at, c.user_id FROM server_members c
+                  WHERE c.server_id = $1 AND c.user_id = $2
+              ))
+            GROUP BY sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                     sm.communication_disabled_until
+            ORDER BY sm.joined_at ASC, sm.user_id ASC
+            LIMIT $3
+            "#,
+        )
+        .bind(server_id)
+        .bind(after)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into_member()).collect())
+    }
+
     /// Search members by nickname or username.
     /// Joins with users table to search by username as well.
     /// Uses a single query with array_agg to avoid N+1 pattern.
@@ -237,13 +310,15 @@ impl MemberRepository for PgMemberRepository {
         let rows = sqlx::query_as::<_, MemberWithRolesRow>(
             r#"
             SELECT sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                   sm.communication_disabled_until,
                    ARRAY_REMOVE(ARRAY_AGG(mr.role_id), NULL) as role_ids
             FROM server_members sm
             INNER JOIN users u ON sm.user_id = u.id
             LEFT JOIN member_roles mr ON sm.server_id = mr.server_id AND sm.user_id = mr.user_id
             WHERE sm.server_id = $1
               AND (sm.nickname ILIKE $2 OR u.username ILIKE $2)
-            GROUP BY sm.server_id, sm.user_id, sm.nickname, sm.joined_at
+            GROUP BY sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                     sm.communication_disabled_until
             ORDER BY sm.joined_at DESC
             LIMIT $3
             "#,
@@ -266,7 +341,7 @@ impl MemberRepository for PgMemberRepository {
             r#"
             INSERT INTO server_members (server_id, user_id, nickname, joined_at)
             VALUES ($1, $2, $3, $4)
-            RETURNING server_id, user_id, nickname, joined_at
+            RETURNING server_id, user_id, nickname, joined_at, communication_disabled_until
             "#,
         )
         .bind(member.server_id)
@@ -392,6 +467,53 @@ impl MemberRepository for PgMemberRepository {
         Ok(())
     }
 
+    /// Add and remove several roles of a member in a single transaction.
+    async fn update_roles(
+        &self,
+        server_id: i64,
+        user_id: i64,
+        added: &[i64],
+        removed: &[i64],
+    ) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        if !removed.is_empty() {
+            sqlx::query(
+                "DELETE FROM member_roles WHERE server_id = $1 AND user_id = $2 AND role_id = ANY($3)",
+            )
+            .bind(server_id)
+            .bind(user_id)
+            .bind(removed)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if !added.is_empty() {
+            sqlx::query(
+                r#"
+                INSERT INTO member_roles (server_id, user_id, role_id)
+                SELECT $1, $2, UNNEST($3::BIGINT[])
+                ON CONFLICT (server_id, user_id, role_id) DO NOTHING
+                "#,
+            )
+            .bind(server_id)
+            .bind(user_id)
+            .bind(added)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| match &e {
+                sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                    AppError::NotFound("Member or role not found".to_string())
+                }
+                _ => AppError::Database(e),
+            })?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
     /// Get all role IDs for a member.
     async fn get_roles(&self, server_id: i64, user_id: i64) -> Result<Vec<i64>, AppError> {
         self.load_member_roles(server_id, user_id).await
@@ -428,6 +550,7 @@ impl MemberRepository for PgMemberRepository {
         let rows = sqlx::query_as::<_, MemberWithRolesRow>(
             r#"
             SELECT sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                   sm.communication_disabled_until,
                    ARRAY_REMOVE(ARRAY_AGG(all_mr.role_id), NULL) as role_ids
             FROM server_members sm
             INNER JOIN member_roles filter_mr ON sm.server_id = filter_mr.server_id
@@ -435,7 +558,8 @@ impl MemberRepository for PgMemberRepository {
             LEFT JOIN member_roles all_mr ON sm.server_id = all_mr.server_id
                 AND sm.user_id = all_mr.user_id
             WHERE sm.server_id = $1
-            GROUP BY sm.server_id, sm.user_id, sm.nickname, sm.joined_at
+            GROUP BY sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                     sm.communication_disabled_until
             ORDER BY sm.joined_at DESC
             "#,
         )
@@ -446,11 +570,91 @@ impl MemberRepository for PgMemberRepository {
 
         Ok(rows.into_iter().map(|r| r.into_member()).collect())
     }
+
+    async fn find_members_with_role(
+        &self,
+        server_id: i64,
+        role_id: i64,
+        after: Option<i64>,
+        limit: i32,
+    ) -> Result<Vec<Member>, AppError> {
+        // The filter join selects holders; the second join collects every
+        // role they have. Without a cursor, start below the smallest ID.
+        let rows = sqlx::query_as::<_, MemberWithRolesRow>(
+            r#"
+            SELECT sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                   sm.communication_disabled_until,
+                   ARRAY_REMOVE(ARRAY_AGG(all_mr.role_id), NULL) as role_ids
+            FROM server_members sm
+            INNER JOIN member_roles filter_mr ON sm.server_id = filter_mr.server_id
+                AND sm.user_id = filter_mr.user_id AND filter_mr.role_id = $2
+            LEFT JOIN member_roles all_mr ON sm.server_id = all_mr.server_id
+                AND sm.user_id = all_mr.user_id
+            WHERE sm.server_id = $1 AND sm.user_id > $3
+            GROUP BY sm.server_id, sm.user_id, sm.nickname, sm.joined_at,
+                     sm.communication_disabled_until
+            ORDER BY sm.user_id ASC
+            LIMIT $4
+            "#,
+        )
+        .bind(server_id)
+        .bind(role_id)
+        .bind(after.unwrap_or(i64::MIN))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into_member()).collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    // Integration tests would go here
+    #[cfg(feature = "db-tests")]
+    #[tokio::test]
+    async fn test_find_by_server_id_joined_pages_in_join_order() {
+        use crate::infrastructure::repositories::test_db;
+
+        let pool = test_db::pool().await;
+        let owner = test_db::user(&pool).await;
+        let server = test_db::server(&pool, owner).await;
+        let early = test_db::user(&pool).await;
+        let tied_a = test_db::user(&pool).await;
+        let tied_b = test_db::user(&pool).await;
+        for (user_id, minute) in [(owner, 30), (early, 0), (tied_a, 10), (tied_b, 10)] {
+            if user_id != owner {
+                test_db::member(&pool, server, user_id).await;
+            }
+            sqlx::query(
+                "UPDATE server_members SET joined_at = TIMESTAMPTZ '2024-01-01 00:00:00Z' + make_interval(mins => $3) \
+                 WHERE server_id = $1 AND user_id = $2",
+            )
+            .bind(server)
+            .bind(user_id)
+            .bind(minute)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let repo = PgMemberRepository::new(pool.clone());
+        let page = |after, limit| {
+            let repo = &repo;
+            async move {
+                let members = repo.find_by_server_id_joined(server, after, limit).await.unwrap();
+                members.into_iter().map(|m| m.user_id).collect::<Vec<_>>()
+            }
+        };
+
+        // Members who joined at the same time are ordered by user ID
+        let tied = (tied_a.min(tied_b), tied_a.max(tied_b));
+        assert_eq!(page(None, 2).await, vec![early, tied.0]);
+        assert_eq!(page(Some(tied.0), 2).await, vec![tied.1, owner]);
+        assert!(page(Some(owner), 2).await.is_empty());
+
+        // A cursor whose member has left ends the listing
+        repo.delete(server, tied.0).await.unwrap();
+        assert!(page(Some(tied.0), 2).await.is_empty());
+    }
 }
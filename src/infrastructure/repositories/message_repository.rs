@@ -5,9 +5,12 @@
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use sqlx::PgPool;
+use sha2::{Digest, Sha256};
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
 
-use crate::domain::{Attachment, Message, MessageRepository, MessageType};
+use super::outbox_repository;
+use crate::domain::{Attachment, Embed, Message, MessageRepository, MessageType};
 use crate::shared::error::AppError;
 
 /// PostgreSQL message repository implementation.
@@ -27,6 +30,158 @@ impl PgMessageRepository {
     }
 }
 
+/// Author fields included in `MESSAGE_CREATE` events.
+#[derive(Debug, sqlx::FromRow)]
+struct EventAuthorRow {
+    username: String,
+    display_name: Option<String>,
+    avatar_url: Option<String>,
+    server_id: Option<i64>,
+}
+
+/// Queue a `MESSAGE_CREATE` gateway event for a newly stored message.
+///
+/// Runs on the creating transaction so the event is committed together
+/// with the message; the outbox relay publishes it.
+async fn enqueue_message_create(conn: &mut PgConnection, message: &Message) -> Result<(), AppError> {
+    let author = sqlx::query_as::<_, EventAuthorRow>(
+        r#"
+        SELECT u.username, u.display_name, u.avatar_url, c.server_id
+        FROM users u, channels c
+        WHERE u.id = $1 AND c.id = $2
+        "#,
+    )
+    .bind(message.author_id)
+    .bind(message.channel_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let payload = serde_json::json!({
+        "id": message.id.to_string(),
+        "channel_id": message.channel_id.to_string(),
+        "guild_id": author.server_id,
+        "author": {
+            "id": message.author_id.to_string(),
+            "username": author.username,
+            "display_name": author.display_name,
+            "avatar_url": author.avatar_url,
+        },
+        "content": message.content,
+        "tts": message.tts,
+        "flags": message.flags,
+        "timestamp": message.created_at.to_rfc3339(),
+        "reply_to": message.reply_to_id.map(|id| id.to_string()),
+    });
+
+    outbox_repository::enqueue(
+        conn,
+        &format!("MESSAGE_CREATE:{}", message.id),
+        "MESSAGE_CREATE",
+        &payload,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Insert one message on the given connection.
+async fn insert_message(conn: &mut PgConnection, message: &Message) -> Result<Message, AppError> {
+    let row = sqlx::query_as::<_, MessageRow>(
+        r#"
+        INSERT INTO messages (id, channel_id, author_id, content, message_type, reply_to_id, pinned,
+                              tts, flags, message_embeds)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING id, channel_id, author_id, content,
+                  message_type, reply_to_id,
+                  pinned, tts, flags, message_embeds, edited_at, created_at
+        "#,
+    )
+    .bind(message.id)
+    .bind(message.channel_id)
+    .bind(message.author_id)
+    .bind(&message.content)
+    .bind(message.message_type)
+    .bind(message.reply_to_id)
+    .bind(message.pinned)
+    .bind(message.tts)
+    .bind(message.flags)
+    .bind(Json(&message.embeds))
+    .fetch_one(conn)
+    .await?;
+
+    Ok(row.into_message())
+}
+
+/// Dedup key and payload of the `MESSAGE_UPDATE` event for a message
+/// updated at `at`.
+///
+/// The key includes the update time rather than `edited_at`, so a flags-only
+/// update that leaves `edited_at` alone is still published, while a retried
+/// enqueue of the same update is not.
+fn message_update_event(message: &Message, guild_id: Option<i64>, at: DateTime<Utc>) -> (String, serde_json::Value) {
+    let payload = serde_json::json!({
+        "id": message.id.to_string(),
+        "channel_id": message.channel_id.to_string(),
+        "guild_id": guild_id,
+        "content": message.content,
+        "flags": message.flags,
+        "edited_timestamp": message.edited_at.map(|t| t.to_rfc3339()),
+    });
+
+    (
+        format!("MESSAGE_UPDATE:{}:{}", message.id, at.timestamp_micros()),
+        payload,
+    )
+}
+
+/// Dedup key and payload of the `MESSAGE_DELETE` event for one message.
+fn message_delete_event(
+    message_id: i64,
+    channel_id: i64,
+    guild_id: Option<i64>,
+) -> (String, serde_json::Value) {
+    let payload = serde_json::json!({
+        "id": message_id.to_string(),
+        "channel_id": channel_id.to_string(),
+        "guild_id": guild_id,
+    });
+
+    (format!("MESSAGE_DELETE:{}", message_id), payload)
+}
+
+/// Dedup key and payload of the `MESSAGE_DELETE_BULK` event for a bulk delete.
+///
+/// The dedup key hashes the IDs to stay within the column limit.
+fn message_delete_bulk_event(
+    channel_id: i64,
+    guild_id: Option<i64>,
+    message_ids: &[i64],
+) -> (String, serde_json::Value) {
+    let mut ids = message_ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let ids: Vec<String> = ids.iter().map(i64::to_string).collect();
+    let digest = Sha256::digest(ids.join(",").as_bytes());
+    let payload = serde_json::json!({
+        "ids": ids,
+        "channel_id": channel_id.to_string(),
+        "guild_id": guild_id,
+    });
+
+    (format!("MESSAGE_DELETE_BULK:{}:{:x}", channel_id, digest), payload)
+}
+
+/// Guild of a channel, for routing message events; `None` for DMs.
+async fn channel_guild_id(conn: &mut PgConnection, channel_id: i64) -> Result<Option<i64>, AppError> {
+    let guild_id = sqlx::query_scalar::<_, Option<i64>>("SELECT server_id FROM channels WHERE id = $1")
+        .bind(channel_id)
+        .fetch_optional(conn)
+        .await?;
+
+    Ok(guild_id.flatten())
+}
+
 /// Internal row type for message queries.
 /// Maps to the messages table schema defined in the migration.
 #[derive(Debug, sqlx::FromRow)]
@@ -35,9 +190,12 @@ struct MessageRow {
     channel_id: i64,
     author_id: i64,
     content: String,
-    message_type: String, // PostgreSQL enum maps to string
+    message_type: MessageType,
     reply_to_id: Option<i64>,
     pinned: bool,
+    tts: bool,
+    flags: i64,
+    message_embeds: Json<Vec<Embed>>,
     edited_at: Option<DateTime<Utc>>,
     created_at: DateTime<Utc>,
 }
@@ -50,9 +208,12 @@ impl MessageRow {
             channel_id: self.channel_id,
             author_id: self.author_id,
             content: self.content,
-            message_type: MessageType::from_str(&self.message_type),
+            message_type: self.message_type,
             reply_to_id: self.reply_to_id,
             pinned: self.pinned,
+            tts: self.tts,
+            flags: self.flags,
+            embeds: self.message_embeds.0,
             edited_at: self.edited_at,
             created_at: self.created_at,
         }
@@ -63,7 +224,8 @@ impl MessageRow {
 #[derive(Debug, sqlx::FromRow)]
 struct AttachmentRow {
     id: i64,
-    message_id: i64,
+    message_id: Option<i64>,
+    uploader_id: Option<i64>,
     filename: String,
     content_type: Option<String>,
     size: i32,
@@ -79,6 +241,7 @@ impl AttachmentRow {
         Attachment {
             id: self.id,
             message_id: self.message_id,
+            uploader_id: self.uploader_id,
             filename: self.filename,
             content_type: self.content_type,
             size: self.size,
@@ -100,8 +263,8 @@ impl MessageRepository for PgMessageRepository {
         let row = sqlx::query_as::<_, MessageRow>(
             r#"
             SELECT id, channel_id, author_id, content,
-                   message_type::text as message_type, reply_to_id,
-                   pinned, edited_at, created_at
+                   message_type, reply_to_id,
+                   pinned, tts, flags, message_embeds, edited_at, created_at
             FROM messages
             WHERE id = $1 AND deleted_at IS NULL
             "#,
@@ -113,10 +276,34 @@ impl MessageRepository for PgMessageRepository {
         Ok(row.map(|r| r.into_message()))
     }
 
+    /// Find messages by their IDs in one query, skipping deleted and
+    /// ephemeral ones.
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<Message>, AppError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query_as::<_, MessageRow>(
+            r#"
+            SELECT id, channel_id, author_id, content,
+                   message_type, reply_to_id,
+                   pinned, tts, flags, message_embeds, edited_at, created_at
+            FROM messages
+            WHERE id = ANY($1) AND deleted_at IS NULL AND flags & 64 = 0
+            "#,
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into_message()).collect())
+    }
+
     /// Find messages in a channel with cursor-based pagination.
     ///
     /// Uses keyset pagination for efficient scrolling through large message histories.
-    /// Messages are returned in descending order (newest first).
+    /// Messages are returned in descending order (newest first), including
+    /// pages fetched with `after`. Ephemeral messages (flag 64) are left out.
     ///
     /// # Arguments
     /// * `channel_id` - The channel to fetch messages from
@@ -139,10 +326,10 @@ impl MessageRepository for PgMessageRepository {
                 sqlx::query_as::<_, MessageRow>(
                     r#"
                     SELECT id, channel_id, author_id, content,
-                           message_type::text as message_type, reply_to_id,
-                           pinned, edited_at, created_at
+                           message_type, reply_to_id,
+                           pinned, tts, flags, message_embeds, edited_at, created_at
                     FROM messages
-                    WHERE channel_id = $1 AND id < $2 AND deleted_at IS NULL
+                    WHERE channel_id = $1 AND id < $2 AND deleted_at IS NULL AND flags & 64 = 0
                     ORDER BY id DESC
                     LIMIT $3
                     "#,
@@ -154,14 +341,15 @@ impl MessageRepository for PgMessageRepository {
                 .await?
             }
             (None, Some(after_id)) => {
-                // Get messages after cursor (newer messages)
+                // Get the oldest messages after the cursor, then flip them
+                // to newest-first like the other pages
                 sqlx::query_as::<_, MessageRow>(
                     r#"
                     SELECT id, channel_id, author_id, content,
-                           message_type::text as message_type, reply_to_id,
-                           pinned, edited_at, created_at
+                           message_type, reply_to_id,
+                           pinned, tts, flags, message_embeds, edited_at, created_at
                     FROM messages
-                    WHERE channel_id = $1 AND id > $2 AND deleted_at IS NULL
+                    WHERE channel_id = $1 AND id > $2 AND deleted_at IS NULL AND flags & 64 = 0
                     ORDER BY id ASC
                     LIMIT $3
                     "#,
@@ -171,16 +359,19 @@ impl MessageRepository for PgMessageRepository {
                 .bind(limit)
                 .fetch_all(&self.pool)
                 .await?
+                .into_iter()
+                .rev()
+                .collect()
             }
             _ => {
                 // No cursor: get most recent messages
                 sqlx::query_as::<_, MessageRow>(
                     r#"
                     SELECT id, channel_id, author_id, content,
-                           message_type::text as message_type, reply_to_id,
-                           pinned, edited_at, created_at
+                           message_type, reply_to_id,
+                           pinned, tts, flags, message_embeds, edited_at, created_at
                     FROM messages
-                    WHERE channel_id = $1 AND deleted_at IS NULL
+                    WHERE channel_id = $1 AND deleted_at IS NULL AND flags & 64 = 0
                     ORDER BY id DESC
                     LIMIT $2
                     "#,
@@ -203,8 +394,8 @@ impl MessageRepository for PgMessageRepository {
         let rows = sqlx::query_as::<_, MessageRow>(
             r#"
             SELECT id, channel_id, author_id, content,
-                   message_type::text as message_type, reply_to_id,
-                   pinned, edited_at, created_at
+                   message_type, reply_to_id,
+                   pinned, tts, flags, message_embeds, edited_at, created_at
             FROM messages
             WHERE channel_id = $1 AND pinned = TRUE AND deleted_at IS NULL
             ORDER BY created_at DESC
@@ -222,109 +413,279 @@ impl MessageRepository for PgMessageRepository {
     ///
     /// The message ID should be a pre-generated Snowflake ID from the application layer.
     async fn create(&self, message: &Message) -> Result<Message, AppError> {
-        let message_type_str = message.message_type.as_str();
+        let mut conn = self.pool.acquire().await?;
+        insert_message(&mut conn, message).await
+    }
 
-        let row = sqlx::query_as::<_, MessageRow>(
+    /// Create several messages in one INSERT and queue their events.
+    async fn create_batch(&self, messages: &[Message]) -> Result<Vec<Message>, AppError> {
+        if messages.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let mut query = QueryBuilder::<Postgres>::new(
+            "INSERT INTO messages (id, channel_id, author_id, content, message_type, reply_to_id, pinned, \
+             tts, flags, message_embeds) ",
+        );
+        query.push_values(messages, |mut row, message| {
+            row.push_bind(message.id)
+                .push_bind(message.channel_id)
+                .push_bind(message.author_id)
+                .push_bind(&message.content)
+                .push_bind(message.message_type)
+                .push_bind(message.reply_to_id)
+                .push_bind(message.pinned)
+                .push_bind(message.tts)
+                .push_bind(message.flags)
+                .push_bind(Json(&message.embeds));
+        });
+        query.push(
+            " RETURNING id, channel_id, author_id, content, message_type, reply_to_id, \
+             pinned, tts, flags, message_embeds, edited_at, created_at",
+        );
+
+        let rows = query
+            .build_query_as::<MessageRow>()
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let created: Vec<Message> = rows.into_iter().map(|r| r.into_message()).collect();
+
+        for message in messages {
+            if let Some(message) = created.iter().find(|m| m.id == message.id) {
+                enqueue_message_create(&mut tx, message).await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(created)
+    }
+
+    /// Create a new message and claim its pending attachments.
+    ///
+    /// Attachments are only claimed while still unattached and owned by the
+    /// author, so a concurrent send of the same upload rolls back here.
+    async fn create_with_attachments(
+        &self,
+        message: &Message,
+        attachment_ids: &[i64],
+    ) -> Result<Message, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let message = insert_message(&mut tx, message).await?;
+
+        if !attachment_ids.is_empty() {
+            let result = sqlx::query(
+                r#"
+                UPDATE attachments
+                SET message_id = $1
+                WHERE id = ANY($2) AND message_id IS NULL AND uploader_id = $3
+                "#,
+            )
+            .bind(message.id)
+            .bind(attachment_ids)
+            .bind(message.author_id)
+            .execute(&mut *tx)
+            .await?;
+
+            if result.rows_affected() != attachment_ids.len() as u64 {
+                return Err(AppError::Conflict(
+                    "Attachment is already attached to a message".to_string(),
+                ));
+            }
+        }
+
+        enqueue_message_create(&mut tx, &message).await?;
+
+        tx.commit().await?;
+
+        Ok(message)
+    }
+
+    /// Find attachments by their IDs.
+    async fn find_attachments_by_ids(&self, ids: &[i64]) -> Result<Vec<Attachment>, AppError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query_as::<_, AttachmentRow>(
             r#"
-            INSERT INTO messages (id, channel_id, author_id, content, message_type, reply_to_id, pinned)
-            VALUES ($1, $2, $3, $4, $5::message_type, $6, $7)
-            RETURNING id, channel_id, author_id, content,
-                      message_type::text as message_type, reply_to_id,
-                      pinned, edited_at, created_at
+            SELECT id, message_id, uploader_id, filename, content_type, size, url,
+                   proxy_url, width, height, created_at
+            FROM attachments
+            WHERE id = ANY($1)
             "#,
         )
-        .bind(message.id)
-        .bind(message.channel_id)
-        .bind(message.author_id)
-        .bind(&message.content)
-        .bind(message_type_str)
-        .bind(message.reply_to_id)
-        .bind(message.pinned)
-        .fetch_one(&self.pool)
+        .bind(ids)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(row.into_message())
+        Ok(rows.into_iter().map(|r| r.into_attachment()).collect())
+    }
+
+    /// Find attachments for a set of messages.
+    ///
+    /// Returns attachments ordered by creation time (oldest first).
+    async fn find_attachments_by_message_ids(
+        &self,
+        message_ids: &[i64],
+    ) -> Result<Vec<Attachment>, AppError> {
+        if message_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query_as::<_, AttachmentRow>(
+            r#"
+            SELECT id, message_id, uploader_id, filename, content_type, size, url,
+                   proxy_url, width, height, created_at
+            FROM attachments
+            WHERE message_id = ANY($1)
+            ORDER BY created_at ASC, id ASC
+            "#,
+        )
+        .bind(message_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into_attachment()).collect())
     }
 
     /// Update a message (for editing content).
     ///
-    /// Only content can be edited. The edited_at timestamp is automatically updated.
+    /// Only content, embeds and flags can be edited. The edited_at timestamp is automatically
+    /// updated when the content changes, and a `MESSAGE_UPDATE` event is queued in the same
+    /// transaction.
     async fn update(&self, message: &Message) -> Result<Message, AppError> {
+        let mut tx = self.pool.begin().await?;
+
         let row = sqlx::query_as::<_, MessageRow>(
             r#"
             UPDATE messages
-            SET content = $2, edited_at = NOW()
+            SET content = $2, message_embeds = $3, flags = $4,
+                edited_at = CASE WHEN content IS DISTINCT FROM $2 THEN NOW() ELSE edited_at END
             WHERE id = $1 AND deleted_at IS NULL
             RETURNING id, channel_id, author_id, content,
-                      message_type::text as message_type, reply_to_id,
-                      pinned, edited_at, created_at
+                      message_type, reply_to_id,
+                      pinned, tts, flags, message_embeds, edited_at, created_at
             "#,
         )
         .bind(message.id)
         .bind(&message.content)
-        .fetch_one(&self.pool)
+        .bind(Json(&message.embeds))
+        .bind(message.flags)
+        .fetch_one(&mut *tx)
         .await?;
+        let updated = row.into_message();
+
+        let guild_id = channel_guild_id(&mut tx, updated.channel_id).await?;
+        let (dedup_key, payload) = message_update_event(&updated, guild_id, Utc::now());
+        outbox_repository::enqueue(&mut tx, &dedup_key, "MESSAGE_UPDATE", &payload).await?;
 
-        Ok(row.into_message())
+        tx.commit().await?;
+        Ok(updated)
     }
 
     /// Soft delete a message.
     ///
-    /// Sets deleted_at timestamp instead of removing the row.
+    /// Sets deleted_at timestamp instead of removing the row, and queues a
+    /// `MESSAGE_DELETE` event in the same transaction.
     async fn delete(&self, id: i64) -> Result<(), AppError> {
-        let result = sqlx::query(
-            "UPDATE messages SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL"
+        let mut tx = self.pool.begin().await?;
+
+        let channel_id = sqlx::query_scalar::<_, i64>(
+            "UPDATE messages SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL RETURNING channel_id"
         )
             .bind(id)
-            .execute(&self.pool)
-            .await?;
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Message {} not found", id)))?;
 
-        if result.rows_affected() == 0 {
-            return Err(AppError::NotFound(format!("Message {} not found", id)));
-        }
+        let guild_id = channel_guild_id(&mut tx, channel_id).await?;
+        let (dedup_key, payload) = message_delete_event(id, channel_id, guild_id);
+        outbox_repository::enqueue(&mut tx, &dedup_key, "MESSAGE_DELETE", &payload).await?;
 
+        tx.commit().await?;
         Ok(())
     }
 
     /// Bulk delete multiple messages in a channel.
     ///
     /// This is more efficient than deleting messages one by one.
-    /// Only deletes messages that belong to the specified channel.
-    async fn bulk_delete(&self, channel_id: i64, message_ids: Vec<i64>) -> Result<(), AppError> {
+    /// Only deletes messages that belong to the specified channel, and
+    /// returns the IDs of those that were not already soft deleted. A single
+    /// `MESSAGE_DELETE_BULK` event listing them is queued in the same
+    /// transaction.
+    async fn bulk_delete(&self, channel_id: i64, message_ids: Vec<i64>) -> Result<Vec<i64>, AppError> {
         if message_ids.is_empty() {
-            return Ok(());
+            return Ok(Vec::new());
         }
 
-        sqlx::query(
+        let mut tx = self.pool.begin().await?;
+
+        // Soft deleted messages already had their MESSAGE_DELETE
+        let deleted = sqlx::query_scalar::<_, i64>(
             r#"
-            DELETE FROM messages
-            WHERE channel_id = $1 AND id = ANY($2)
+            WITH deleted AS (
+                DELETE FROM messages
+                WHERE channel_id = $1 AND id = ANY($2)
+                RETURNING id, deleted_at
+            )
+            SELECT id FROM deleted WHERE deleted_at IS NULL
             "#,
         )
         .bind(channel_id)
         .bind(&message_ids)
-        .execute(&self.pool)
+        .fetch_all(&mut *tx)
         .await?;
 
-        Ok(())
+        if !deleted.is_empty() {
+            let guild_id = channel_guild_id(&mut tx, channel_id).await?;
+            let (dedup_key, payload) = message_delete_bulk_event(channel_id, guild_id, &deleted);
+            outbox_repository::enqueue(&mut tx, &dedup_key, "MESSAGE_DELETE_BULK", &payload).await?;
+        }
+
+        tx.commit().await?;
+        Ok(deleted)
     }
 
-    /// Pin a message.
-    async fn pin(&self, id: i64) -> Result<(), AppError> {
-        let result = sqlx::query(
+    /// Pin a message and post its system message in one transaction.
+    async fn pin(&self, id: i64, system_message: &Message) -> Result<Option<Message>, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        // Locks the row so concurrent pins post one system message
+        let pinned: Option<bool> = sqlx::query_scalar(
             r#"
-            UPDATE messages SET pinned = TRUE WHERE id = $1
+            SELECT pinned FROM messages WHERE id = $1 FOR UPDATE
             "#,
         )
         .bind(id)
-        .execute(&self.pool)
+        .fetch_optional(&mut *tx)
         .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(AppError::NotFound(format!("Message {} not found", id)));
+        match pinned {
+            None => return Err(AppError::NotFound(format!("Message {} not found", id))),
+            Some(true) => return Ok(None),
+            Some(false) => {}
         }
 
-        Ok(())
+        sqlx::query(
+            r#"
+            UPDATE messages SET pinned = TRUE WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        let created = insert_message(&mut tx, system_message).await?;
+        enqueue_message_create(&mut tx, &created).await?;
+
+        tx.commit().await?;
+
+        Ok(Some(created))
     }
 
     /// Unpin a message.
@@ -359,8 +720,8 @@ impl MessageRepository for PgMessageRepository {
         let rows = sqlx::query_as::<_, MessageRow>(
             r#"
             SELECT id, channel_id, author_id, content,
-                   message_type::text as message_type, reply_to_id,
-                   pinned, edited_at, created_at
+                   message_type, reply_to_id,
+                   pinned, tts, flags, message_embeds, edited_at, created_at
             FROM messages
             WHERE channel_id = $1 AND author_id = $2
             ORDER BY id DESC
@@ -377,10 +738,10 @@ impl MessageRepository for PgMessageRepository {
         Ok(messages)
     }
 
-    /// Get the count of messages in a channel.
+    /// Get the count of messages in a channel, excluding deleted ones.
     async fn count_by_channel(&self, channel_id: i64) -> Result<i64, AppError> {
         let count = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM messages WHERE channel_id = $1",
+            "SELECT COUNT(*) FROM messages WHERE channel_id = $1 AND deleted_at IS NULL",
         )
         .bind(channel_id)
         .fetch_one(&self.pool)
@@ -396,7 +757,7 @@ impl PgMessageRepository {
     async fn load_attachments(&self, message_id: i64) -> Result<Vec<Attachment>, AppError> {
         let rows = sqlx::query_as::<_, AttachmentRow>(
             r#"
-            SELECT id, message_id, filename, content_type, size, url,
+            SELECT id, message_id, uploader_id, filename, content_type, size, url,
                    proxy_url, width, height, created_at
             FROM attachments
             WHERE message_id = $1
@@ -431,6 +792,8 @@ impl PgMessageRepository {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::presentation::websocket::GatewayEvent;
+    use crate::domain::message_flags;
 
     #[test]
     fn test_message_type_conversion() {
@@ -440,10 +803,204 @@ mod tests {
         assert!(matches!(MessageType::from_str("unknown"), MessageType::Default));
     }
 
+    fn decode(event_type: &str, payload: serde_json::Value) -> GatewayEvent {
+        serde_json::from_value(serde_json::json!({"t": event_type, "d": payload})).unwrap()
+    }
+
+    #[test]
+    fn test_bulk_delete_emits_one_event_with_every_id() {
+        let (dedup_key, payload) = message_delete_bulk_event(10, Some(20), &[3, 1, 2]);
+
+        match decode("MESSAGE_DELETE_BULK", payload) {
+            GatewayEvent::MessageDeleteBulk(event) => {
+                assert_eq!(event.ids, vec!["1", "2", "3"]);
+                assert_eq!(event.channel_id, "10");
+                assert_eq!(event.guild_id, Some(20));
+            }
+            other => panic!("expected MESSAGE_DELETE_BULK, got {}", other.event_name()),
+        }
+        assert!(dedup_key.len() <= 128);
+
+        // The same set of IDs in any order is the same event
+        assert_eq!(message_delete_bulk_event(10, Some(20), &[2, 3, 1, 1]).0, dedup_key);
+    }
+
+    #[test]
+    fn test_single_delete_emits_message_delete() {
+        let (dedup_key, payload) = message_delete_event(5, 10, None);
+
+        assert_eq!(dedup_key, "MESSAGE_DELETE:5");
+        match decode("MESSAGE_DELETE", payload) {
+            GatewayEvent::MessageDelete(event) => {
+                assert_eq!(event.id, "5");
+                assert_eq!(event.channel_id, "10");
+                assert_eq!(event.guild_id, None);
+            }
+            other => panic!("expected MESSAGE_DELETE, got {}", other.event_name()),
+        }
+    }
+
+    #[test]
+    fn test_edit_emits_message_update() {
+        let edited_at = Utc::now();
+        let message = Message {
+            id: 5,
+            channel_id: 10,
+            content: "edited".to_string(),
+            flags: message_flags::SUPPRESS_EMBEDS,
+            edited_at: Some(edited_at),
+            ..Default::default()
+        };
+
+        let (dedup_key, payload) = message_update_event(&message, Some(20), edited_at);
+        match decode("MESSAGE_UPDATE", payload) {
+            GatewayEvent::MessageUpdate(event) => {
+                assert_eq!(event.id, "5");
+                assert_eq!(event.channel_id, "10");
+                assert_eq!(event.guild_id, Some(20));
+                assert_eq!(event.content.as_deref(), Some("edited"));
+                assert_eq!(event.flags, Some(message_flags::SUPPRESS_EMBEDS));
+                assert_eq!(event.edited_timestamp, Some(edited_at.to_rfc3339()));
+            }
+            other => panic!("expected MESSAGE_UPDATE, got {}", other.event_name()),
+        }
+
+        // A later update of the same message is a new event, even if its
+        // content is unchanged
+        let later = edited_at + chrono::Duration::seconds(1);
+        assert_ne!(message_update_event(&message, Some(20), later).0, dedup_key);
+    }
+
     #[test]
     fn test_message_type_to_str() {
         assert_eq!(MessageType::Default.as_str(), "default");
         assert_eq!(MessageType::Reply.as_str(), "reply");
         assert_eq!(MessageType::GuildMemberJoin.as_str(), "guild_member_join");
     }
+
+    #[cfg(feature = "db-tests")]
+    #[tokio::test]
+    async fn test_message_type_round_trips_through_column() {
+        use sqlx::{Connection, PgConnection};
+
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let mut conn = PgConnection::connect(&url).await.unwrap();
+        let variants = [
+            MessageType::Default,
+            MessageType::RecipientAdd,
+            MessageType::RecipientRemove,
+            MessageType::Call,
+            MessageType::ChannelNameChange,
+            MessageType::ChannelIconChange,
+            MessageType::ChannelPinnedMessage,
+            MessageType::GuildMemberJoin,
+            MessageType::Reply,
+        ];
+
+        // Every database label decodes, in declaration order
+        let labels: Vec<MessageType> =
+            sqlx::query_scalar("SELECT unnest(enum_range(NULL::message_type))")
+                .fetch_all(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(labels, variants);
+
+        sqlx::query("CREATE TEMP TABLE message_type_round_trip (value message_type NOT NULL)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        for message_type in variants {
+            let decoded: MessageType = sqlx::query_scalar(
+                "INSERT INTO message_type_round_trip (value) VALUES ($1) RETURNING value",
+            )
+            .bind(message_type)
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+            assert_eq!(decoded, message_type);
+        }
+    }
+
+    #[cfg(feature = "db-tests")]
+    #[tokio::test]
+    async fn test_only_a_content_change_marks_a_message_edited() {
+        use crate::infrastructure::repositories::test_db;
+
+        let pool = test_db::pool().await;
+        let owner = test_db::user(&pool).await;
+        let server = test_db::server(&pool, owner).await;
+        let channel = test_db::channel(&pool, server).await;
+        let repo = PgMessageRepository::new(pool.clone());
+        let message = Message {
+            id: test_db::id(),
+            channel_id: channel,
+            author_id: owner,
+            content: "hi".to_string(),
+            ..Default::default()
+        };
+        let created = repo.create(&message).await.unwrap();
+
+        // Suppressing embeds or re-saving the same content isn't an edit
+        let suppressed = Message {
+            flags: message_flags::SUPPRESS_EMBEDS,
+            ..created
+        };
+        let updated = repo.update(&suppressed).await.unwrap();
+        assert_eq!(updated.flags, message_flags::SUPPRESS_EMBEDS);
+        assert_eq!(updated.edited_at, None);
+
+        let edited = repo.update(&Message { content: "hello".to_string(), ..updated }).await.unwrap();
+        let edited_at = edited.edited_at.expect("a content change is an edit");
+        let resaved = repo.update(&Message { flags: 0, ..edited }).await.unwrap();
+        assert_eq!(resaved.edited_at, Some(edited_at));
+
+        // Every update is still published
+        let updates: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM event_outbox WHERE event_type = 'MESSAGE_UPDATE' AND payload->>'id' = $1",
+        )
+        .bind(message.id.to_string())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(updates, 3);
+    }
+
+    #[cfg(feature = "db-tests")]
+    #[tokio::test]
+    async fn test_bulk_delete_removes_rows_and_returns_live_messages() {
+        use crate::infrastructure::repositories::test_db;
+
+        let pool = test_db::pool().await;
+        let owner = test_db::user(&pool).await;
+        let server = test_db::server(&pool, owner).await;
+        let channel = test_db::channel(&pool, server).await;
+        let repo = PgMessageRepository::new(pool.clone());
+
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let message = Message {
+                id: test_db::id(),
+                channel_id: channel,
+                author_id: owner,
+                content: "hi".to_string(),
+                ..Default::default()
+            };
+            ids.push(repo.create(&message).await.unwrap().id);
+        }
+        repo.delete(ids[0]).await.unwrap();
+
+        // The soft deleted message goes too, but was already announced
+        let mut deleted = repo.bulk_delete(channel, ids.clone()).await.unwrap();
+        deleted.sort_unstable();
+        let mut live = ids[1..].to_vec();
+        live.sort_unstable();
+        assert_eq!(deleted, live);
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM messages WHERE id = ANY($1)")
+            .bind(&ids)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
 }
@@ -17,6 +17,8 @@
 //! - **ReactionRepository** - Message reactions management
 //! - **AttachmentRepository** - File attachment handling
 //! - **InviteRepository** - Server invite links with expiration
+//! - **OutboxRepository** - Gateway event outbox for reliable publishing
+//! - **ChannelOverrideRepository** - Per-user channel mute and notification settings
 //!
 //! ## Usage Example
 //!
@@ -50,6 +52,11 @@ pub mod reaction_repository;
 pub mod attachment_repository;
 pub mod invite_repository;
 pub mod session_repository;
+pub mod outbox_repository;
+pub mod channel_override_repository;
+
+#[cfg(all(test, feature = "db-tests"))]
+pub(crate) mod test_db;
 
 // Keep guild_repository for backward compatibility during transition
 #[deprecated(note = "Use server_repository instead - 'servers' is the actual table name")]
@@ -74,6 +81,8 @@ pub use invite_repository::{
     CreateInvite, InviteEntity, InvitePreview, InviteRepository, PgInviteRepository,
 };
 pub use session_repository::PgSessionRepository;
+pub use outbox_repository::{OutboxEvent, OutboxRepository, PgOutboxRepository};
+pub use channel_override_repository::PgChannelOverrideRepository;
 
 // Backward compatibility - re-export old guild repository with deprecation warning
 #[allow(deprecated)]
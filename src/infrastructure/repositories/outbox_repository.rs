@@ -0,0 +1,186 @@
+//! Event Outbox Repository Implementation
+//!
+//! PostgreSQL implementation of the gateway event outbox. Events are
+//! enqueued on the same connection (and transaction) as the change they
+//! describe, then published by a background relay.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+
+use crate::shared::error::AppError;
+
+/// Outbox row awaiting (or past) publication.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OutboxEvent {
+    pub id: i64,
+    /// Unique key for the event; enqueueing the same key twice is a no-op
+    pub dedup_key: String,
+    /// Gateway event name, e.g. `MESSAGE_CREATE`
+    pub event_type: String,
+    /// Gateway event data (`d`)
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+}
+
+/// Trait defining event outbox operations used by the relay.
+#[async_trait]
+pub trait OutboxRepository: Send + Sync {
+    /// Claim up to `limit` unsent events for `lease`, oldest first.
+    ///
+    /// Events claimed by another relay are skipped until their lease runs
+    /// out, so concurrent relays never publish the same event at once.
+    async fn claim_unsent(&self, limit: i64, lease: Duration) -> Result<Vec<OutboxEvent>, AppError>;
+
+    /// Mark events as sent.
+    ///
+    /// Already-sent events are left alone. Returns the number of events
+    /// newly marked.
+    async fn mark_sent(&self, ids: &[i64]) -> Result<u64, AppError>;
+
+    /// Delete events sent before `cutoff`, returning how many were removed.
+    ///
+    /// Their dedup keys are freed, so the retention period must outlast any
+    /// retry that could enqueue the same event again.
+    async fn delete_sent_before(&self, cutoff: DateTime<Utc>) -> Result<u64, AppError>;
+}
+
+/// Enqueue an event on an open connection, typically inside the
+/// transaction that makes the change the event describes.
+///
+/// Returns `false` if an event with the same `dedup_key` already exists.
+pub async fn enqueue(
+    conn: &mut PgConnection,
+    dedup_key: &str,
+    event_type: &str,
+    payload: &serde_json::Value,
+) -> Result<bool, AppError> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO event_outbox (dedup_key, event_type, payload)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (dedup_key) DO NOTHING
+        "#,
+    )
+    .bind(dedup_key)
+    .bind(event_type)
+    .bind(payload)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// PostgreSQL implementation of the OutboxRepository.
+pub struct PgOutboxRepository {
+    pool: PgPool,
+}
+
+impl PgOutboxRepository {
+    /// Creates a new PgOutboxRepository with the given connection pool.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for PgOutboxRepository {
+    async fn claim_unsent(&self, limit: i64, lease: Duration) -> Result<Vec<OutboxEvent>, AppError> {
+        let mut rows = sqlx::query_as::<_, OutboxEvent>(
+            r#"
+            UPDATE event_outbox
+            SET claimed_until = NOW() + make_interval(secs => $2)
+            WHERE id IN (
+                SELECT id FROM event_outbox
+                WHERE sent_at IS NULL AND (claimed_until IS NULL OR claimed_until < NOW())
+                ORDER BY id ASC
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, dedup_key, event_type, payload, created_at, sent_at
+            "#,
+        )
+        .bind(limit)
+        .bind(lease.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await?;
+
+        // RETURNING gives no order guarantee
+        rows.sort_unstable_by_key(|row| row.id);
+        Ok(rows)
+    }
+
+    async fn mark_sent(&self, ids: &[i64]) -> Result<u64, AppError> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let result = sqlx::query(
+            r#"
+            UPDATE event_outbox
+            SET sent_at = NOW()
+            WHERE id = ANY($1) AND sent_at IS NULL
+            "#,
+        )
+        .bind(ids)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_sent_before(&self, cutoff: DateTime<Utc>) -> Result<u64, AppError> {
+        let result = sqlx::query("DELETE FROM event_outbox WHERE sent_at < $1")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(all(test, feature = "db-tests"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_claimed_events_are_skipped_until_the_lease_lapses() {
+        use crate::infrastructure::repositories::test_db;
+
+        let pool = test_db::pool().await;
+        let key = format!("TEST:{}", test_db::id());
+        let mut conn = pool.acquire().await.unwrap();
+        enqueue(&mut conn, &key, "TEST", &serde_json::json!({})).await.unwrap();
+        let outbox = PgOutboxRepository::new(pool.clone());
+        let claimed = |events: Vec<OutboxEvent>| events.iter().any(|e| e.dedup_key == key);
+
+        // Other tests share the table, so claim everything that is unsent
+        assert!(claimed(outbox.claim_unsent(i64::MAX, Duration::from_secs(60)).await.unwrap()));
+        assert!(!claimed(outbox.claim_unsent(i64::MAX, Duration::from_secs(60)).await.unwrap()));
+
+        sqlx::query("UPDATE event_outbox SET claimed_until = NOW() - INTERVAL '1 second' WHERE dedup_key = $1")
+            .bind(&key)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let events = outbox.claim_unsent(i64::MAX, Duration::from_secs(60)).await.unwrap();
+        let id = events.iter().find(|e| e.dedup_key == key).unwrap().id;
+
+        outbox.mark_sent(&[id]).await.unwrap();
+        sqlx::query("UPDATE event_outbox SET sent_at = NOW() - INTERVAL '2 days' WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(outbox.delete_sent_before(Utc::now() - chrono::Duration::days(1)).await.unwrap() >= 1);
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM event_outbox WHERE id = $1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+}
@@ -66,6 +66,18 @@ pub trait ReactionRepository: Send + Sync {
         emoji: &str,
     ) -> Result<Vec<i64>, AppError>;
 
+    /// List user IDs who reacted with a specific emoji, one page at a time.
+    ///
+    /// Keyset-paginated by user ID (ascending): pass the last ID of the
+    /// previous page as `after_user_id` to get the next page.
+    async fn list_reactors(
+        &self,
+        message_id: i64,
+        emoji: &str,
+        after_user_id: Option<i64>,
+        limit: i32,
+    ) -> Result<Vec<i64>, AppError>;
+
     /// Check if a user has reacted with a specific emoji.
     async fn has_user_reacted(
         &self,
@@ -224,6 +236,36 @@ impl ReactionRepository for PgReactionRepository {
         Ok(rows.into_iter().map(|(id,)| id).collect())
     }
 
+    /// List user IDs who reacted with a specific emoji, one page at a time.
+    ///
+    /// Served by the (message_id, user_id, emoji) primary key.
+    async fn list_reactors(
+        &self,
+        message_id: i64,
+        emoji: &str,
+        after_user_id: Option<i64>,
+        limit: i32,
+    ) -> Result<Vec<i64>, AppError> {
+        let rows: Vec<(i64,)> = sqlx::query_as(
+            r#"
+            SELECT user_id
+            FROM message_reactions
+            WHERE message_id = $1 AND emoji = $2
+              AND ($3::BIGINT IS NULL OR user_id > $3)
+            ORDER BY user_id ASC
+            LIMIT $4
+            "#,
+        )
+        .bind(message_id)
+        .bind(emoji)
+        .bind(after_user_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+
     /// Check if a user has reacted with a specific emoji.
     ///
     /// Efficient single-row check using EXISTS.
@@ -7,9 +7,34 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 
-use crate::domain::{Role, RoleRepository};
+use super::outbox_repository;
+use crate::domain::{Role, RoleCleanup, RoleRepository};
 use crate::shared::error::AppError;
 
+/// Dedup key and payload of the `GUILD_ROLE_UPDATE` event for a reorder.
+///
+/// One event carries every moved role. The key includes the reorder time
+/// so each reorder is published, while a retried enqueue of it is not.
+fn role_positions_event(
+    server_id: i64,
+    positions: &[(i64, i32)],
+    at: DateTime<Utc>,
+) -> (String, serde_json::Value) {
+    let roles: Vec<serde_json::Value> = positions
+        .iter()
+        .map(|(id, position)| serde_json::json!({ "id": id.to_string(), "position": position }))
+        .collect();
+    let payload = serde_json::json!({
+        "guild_id": server_id,
+        "roles": roles,
+    });
+
+    (
+        format!("GUILD_ROLE_UPDATE:{}:{}", server_id, at.timestamp_micros()),
+        payload,
+    )
+}
+
 /// Database row representation matching the actual roles table schema.
 #[derive(Debug, sqlx::FromRow)]
 struct RoleRow {
@@ -88,27 +113,81 @@ impl PgRoleRepository {
         positions: Vec<(i64, i32)>,
     ) -> Result<(), AppError> {
         let mut tx = self.pool.begin().await?;
-
-        for (role_id, position) in positions {
-            sqlx::query(
-                r#"
-                UPDATE roles
-                SET position = $3, updated_at = NOW()
-                WHERE id = $1 AND server_id = $2
-                "#,
-            )
-            .bind(role_id)
-            .bind(server_id)
-            .bind(position)
-            .execute(&mut *tx)
-            .await?;
-        }
-
+        move_roles(&mut tx, server_id, &positions).await?;
         tx.commit().await?;
         Ok(())
     }
 }
 
+/// Move roles to new positions and queue one `GUILD_ROLE_UPDATE` for them.
+async fn move_roles(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    server_id: i64,
+    positions: &[(i64, i32)],
+) -> Result<(), AppError> {
+    for &(role_id, position) in positions {
+        sqlx::query(
+            r#"
+            UPDATE roles
+            SET position = $3, updated_at = NOW()
+            WHERE id = $1 AND server_id = $2
+            "#,
+        )
+        .bind(role_id)
+        .bind(server_id)
+        .bind(position)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    let (dedup_key, payload) = role_positions_event(server_id, positions, Utc::now());
+    outbox_repository::enqueue(tx, &dedup_key, "GUILD_ROLE_UPDATE", &payload).await?;
+    Ok(())
+}
+
+/// Lock a server's roles for the rest of the transaction, so reorders and
+/// role creation in the server run one at a time.
+async fn lock_server_roles(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, server_id: i64) -> Result<(), AppError> {
+    sqlx::query("SELECT 1 FROM servers WHERE id = $1 FOR UPDATE")
+        .bind(server_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Insert a role row.
+async fn insert_role<'e, E>(executor: E, role: &Role) -> Result<Role, AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let row = sqlx::query_as::<_, RoleRow>(
+        r#"
+        INSERT INTO roles (id, server_id, name, permissions, position, color, hoist, mentionable)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, server_id, name, permissions, position, color, hoist, mentionable,
+                  created_at, updated_at
+        "#,
+    )
+    .bind(role.id)
+    .bind(role.server_id)
+    .bind(&role.name)
+    .bind(role.permissions)
+    .bind(role.position)
+    .bind(role.color)
+    .bind(role.hoist)
+    .bind(role.mentionable)
+    .fetch_one(executor)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            AppError::Conflict("Role with this ID already exists".to_string())
+        }
+        _ => AppError::Database(e),
+    })?;
+
+    Ok(row.into_role())
+}
+
 #[async_trait]
 impl RoleRepository for PgRoleRepository {
     /// Find a role by its ID.
@@ -128,6 +207,29 @@ impl RoleRepository for PgRoleRepository {
         Ok(row.map(|r| r.into_role()))
     }
 
+    /// Find several roles by ID in one query.
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<Role>, AppError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query_as::<_, RoleRow>(
+            r#"
+            SELECT id, server_id, name, permissions, position, color, hoist, mentionable,
+                   created_at, updated_at
+            FROM roles
+            WHERE id = ANY($1) AND deleted_at IS NULL
+            "#,
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut roles: Vec<Role> = rows.into_iter().map(|r| r.into_role()).collect();
+        Role::sort_by_hierarchy(&mut roles);
+        Ok(roles)
+    }
+
     /// Find all roles in a server, ordered by position descending.
     async fn find_by_server_id(&self, server_id: i64) -> Result<Vec<Role>, AppError> {
         let rows = sqlx::query_as::<_, RoleRow>(
@@ -169,32 +271,23 @@ impl RoleRepository for PgRoleRepository {
 
     /// Create a new role.
     async fn create(&self, role: &Role) -> Result<Role, AppError> {
-        let row = sqlx::query_as::<_, RoleRow>(
-            r#"
-            INSERT INTO roles (id, server_id, name, permissions, position, color, hoist, mentionable)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, server_id, name, permissions, position, color, hoist, mentionable,
-                      created_at, updated_at
-            "#,
-        )
-        .bind(role.id)
-        .bind(role.server_id)
-        .bind(&role.name)
-        .bind(role.permissions)
-        .bind(role.position)
-        .bind(role.color)
-        .bind(role.hoist)
-        .bind(role.mentionable)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|e| match &e {
-            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
-                AppError::Conflict("Role with this ID already exists".to_string())
-            }
-            _ => AppError::Database(e),
-        })?;
+        insert_role(&self.pool, role).await
+    }
 
-        Ok(row.into_role())
+    /// Move roles to make room, then create a role, in one transaction.
+    async fn create_with_positions(
+        &self,
+        role: &Role,
+        positions: Vec<(i64, i32)>,
+    ) -> Result<Role, AppError> {
+        let mut tx = self.pool.begin().await?;
+        lock_server_roles(&mut tx, role.server_id).await?;
+        if !positions.is_empty() {
+            move_roles(&mut tx, role.server_id, &positions).await?;
+        }
+        let created = insert_role(&mut *tx, role).await?;
+        tx.commit().await?;
+        Ok(created)
     }
 
     /// Update an existing role.
@@ -243,6 +336,48 @@ impl RoleRepository for PgRoleRepository {
         Ok(())
     }
 
+    /// Delete a role and everything that references it.
+    ///
+    /// Channel overwrites targeting the role have no foreign key to `roles`,
+    /// so they are removed explicitly alongside the member assignments.
+    async fn delete_with_references(&self, id: i64) -> Result<RoleCleanup, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let channel_ids = sqlx::query_scalar::<_, i64>(
+            r#"
+            DELETE FROM channel_permission_overwrites
+            WHERE target_type = 'role' AND target_id = $1
+            RETURNING channel_id
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let user_ids = sqlx::query_scalar::<_, i64>(
+            "DELETE FROM member_roles WHERE role_id = $1 RETURNING user_id",
+        )
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let result = sqlx::query("DELETE FROM roles WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Role with id {} not found", id)));
+        }
+
+        tx.commit().await?;
+
+        Ok(RoleCleanup {
+            channel_ids,
+            user_ids,
+        })
+    }
+
     /// Update role positions (for reordering).
     async fn update_positions(
         &self,
@@ -252,6 +387,40 @@ impl RoleRepository for PgRoleRepository {
         self.reorder_positions(server_id, positions).await
     }
 
+    /// Reorder roles from a plan made over the server's current roles, in
+    /// one transaction.
+    async fn reorder<F, E>(&self, server_id: i64, plan: F) -> Result<(), E>
+    where
+        F: FnOnce(Vec<Role>) -> Result<Vec<(i64, i32)>, E> + Send,
+        E: From<AppError> + Send,
+    {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        lock_server_roles(&mut tx, server_id).await?;
+
+        let rows = sqlx::query_as::<_, RoleRow>(
+            r#"
+            SELECT id, server_id, name, permissions, position, color, hoist, mentionable,
+                   created_at, updated_at
+            FROM roles
+            WHERE server_id = $1 AND deleted_at IS NULL
+            ORDER BY position DESC
+            "#,
+        )
+        .bind(server_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::from)?;
+
+        let positions = plan(rows.into_iter().map(|r| r.into_role()).collect())?;
+        if positions.is_empty() {
+            return Ok(());
+        }
+
+        move_roles(&mut tx, server_id, &positions).await?;
+        tx.commit().await.map_err(AppError::from)?;
+        Ok(())
+    }
+
     /// Get the highest role position for a server.
     async fn get_max_position(&self, server_id: i64) -> Result<i32, AppError> {
         let max_pos = sqlx::query_scalar::<_, Option<i32>>(
@@ -268,6 +437,153 @@ impl RoleRepository for PgRoleRepository {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::presentation::websocket::GatewayEvent;
 
-    // Integration tests would go here
+    #[test]
+    fn test_reorder_emits_one_event_with_every_position() {
+        let at = Utc::now();
+        let (dedup_key, payload) = role_positions_event(10, &[(1, 2), (2, 1)], at);
+
+        let event: GatewayEvent =
+            serde_json::from_value(serde_json::json!({"t": "GUILD_ROLE_UPDATE", "d": payload})).unwrap();
+        match event {
+            GatewayEvent::GuildRoleUpdate(event) => {
+                assert_eq!(event.guild_id, 10);
+                let roles: Vec<(&str, i32)> = event.roles.iter().map(|r| (r.id.as_str(), r.position)).collect();
+                assert_eq!(roles, vec![("1", 2), ("2", 1)]);
+            }
+            other => panic!("expected GUILD_ROLE_UPDATE, got {}", other.event_name()),
+        }
+
+        // Later reorders are separate events
+        let later = at + chrono::Duration::seconds(1);
+        assert_ne!(role_positions_event(10, &[(1, 2)], later).0, dedup_key);
+    }
+
+    #[cfg(feature = "db-tests")]
+    #[tokio::test]
+    async fn test_delete_with_references_removes_overwrites_and_assignments() {
+        use crate::infrastructure::repositories::test_db;
+
+        let pool = test_db::pool().await;
+        let owner = test_db::user(&pool).await;
+        let holder = test_db::user(&pool).await;
+        let server = test_db::server(&pool, owner).await;
+        test_db::member(&pool, server, holder).await;
+        let channel = test_db::channel(&pool, server).await;
+        let role = test_db::role(&pool, server, 1).await;
+        let kept = test_db::role(&pool, server, 2).await;
+        test_db::assign_role(&pool, server, holder, role).await;
+        test_db::assign_role(&pool, server, holder, kept).await;
+        for target in [role, kept] {
+            sqlx::query(
+                "INSERT INTO channel_permission_overwrites (channel_id, target_type, target_id, allow) \
+                 VALUES ($1, 'role', $2, 1024)",
+            )
+            .bind(channel)
+            .bind(target)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let repo = PgRoleRepository::new(pool.clone());
+
+        let cleanup = repo.delete_with_references(role).await.unwrap();
+
+        assert_eq!(cleanup.channel_ids, vec![channel]);
+        assert_eq!(cleanup.user_ids, vec![holder]);
+        assert!(repo.find_by_id(role).await.unwrap().is_none());
+        // Other roles keep their overwrites and holders
+        let overwrite_targets: Vec<i64> =
+            sqlx::query_scalar("SELECT target_id FROM channel_permission_overwrites WHERE channel_id = $1")
+                .bind(channel)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(overwrite_targets, vec![kept]);
+        let holder_roles: Vec<i64> =
+            sqlx::query_scalar("SELECT role_id FROM member_roles WHERE server_id = $1 AND user_id = $2")
+                .bind(server)
+                .bind(holder)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(holder_roles, vec![kept]);
+
+        // A role that is already gone is reported missing
+        assert!(matches!(repo.delete_with_references(role).await, Err(AppError::NotFound(_))));
+    }
+
+    #[cfg(feature = "db-tests")]
+    #[tokio::test]
+    async fn test_find_by_ids_orders_by_hierarchy_and_skips_missing() {
+        use crate::infrastructure::repositories::test_db;
+
+        let pool = test_db::pool().await;
+        let owner = test_db::user(&pool).await;
+        let server = test_db::server(&pool, owner).await;
+        let low = test_db::role(&pool, server, 1).await;
+        let high = test_db::role(&pool, server, 3).await;
+        let tied = test_db::role(&pool, server, 3).await;
+        let deleted = test_db::role(&pool, server, 2).await;
+        sqlx::query("UPDATE roles SET deleted_at = NOW() WHERE id = $1")
+            .bind(deleted)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let repo = PgRoleRepository::new(pool);
+
+        let found = repo
+            .find_by_ids(&[low, deleted, tied, test_db::id(), high])
+            .await
+            .unwrap();
+
+        // Highest position first, ties broken by ID
+        let (first, second) = if high < tied { (high, tied) } else { (tied, high) };
+        let ids: Vec<i64> = found.iter().map(|role| role.id).collect();
+        assert_eq!(ids, vec![first, second, low]);
+        assert!(repo.find_by_ids(&[]).await.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "db-tests")]
+    #[tokio::test]
+    async fn test_create_with_positions_moves_roles_only_if_created() {
+        use crate::infrastructure::repositories::test_db;
+
+        let pool = test_db::pool().await;
+        let owner = test_db::user(&pool).await;
+        let server = test_db::server(&pool, owner).await;
+        let source = test_db::role(&pool, server, 2).await;
+        let above = test_db::role(&pool, server, 3).await;
+        let repo = PgRoleRepository::new(pool);
+        let position = |id: i64| {
+            let repo = &repo;
+            async move { repo.find_by_id(id).await.unwrap().unwrap().position }
+        };
+        let now = Utc::now();
+        let copy = Role {
+            id: test_db::id(),
+            server_id: server,
+            name: "copy".to_string(),
+            permissions: 0,
+            position: 2,
+            color: None,
+            hoist: false,
+            mentionable: false,
+            created_at: now,
+            updated_at: now,
+        };
+
+        // A failed create leaves the other roles where they were
+        let duplicate = Role { id: source, ..copy.clone() };
+        let result = repo.create_with_positions(&duplicate, vec![(source, 3), (above, 4)]).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        assert_eq!(position(source).await, 2);
+        assert_eq!(position(above).await, 3);
+
+        let created = repo.create_with_positions(&copy, vec![(source, 3), (above, 4)]).await.unwrap();
+        assert_eq!(created.position, 2);
+        assert_eq!(position(source).await, 3);
+        assert_eq!(position(above).await, 4);
+    }
 }
@@ -7,7 +7,9 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 
-use crate::domain::{Server, ServerRepository};
+use crate::domain::{
+    DefaultMessageNotifications, ExplicitContentFilter, Server, ServerRepository,
+};
 use crate::shared::error::AppError;
 
 /// Database row representation matching the actual servers table schema.
@@ -18,10 +20,24 @@ struct ServerRow {
     owner_id: i64,
     icon_url: Option<String>,
     description: Option<String>,
+    system_channel_id: Option<i64>,
+    afk_channel_id: Option<i64>,
+    default_message_notifications: DefaultMessageNotifications,
+    explicit_content_filter: ExplicitContentFilter,
+    auto_role_id: Option<i64>,
+    discoverable: bool,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
 
+/// A server row with its member count.
+#[derive(Debug, sqlx::FromRow)]
+struct ServerWithCountRow {
+    #[sqlx(flatten)]
+    server: ServerRow,
+    member_count: i64,
+}
+
 impl ServerRow {
     /// Convert database row to domain Server entity.
     /// Populates additional domain fields with sensible defaults.
@@ -32,6 +48,12 @@ impl ServerRow {
             owner_id: self.owner_id,
             icon_url: self.icon_url,
             description: self.description,
+            system_channel_id: self.system_channel_id,
+            afk_channel_id: self.afk_channel_id,
+            default_message_notifications: self.default_message_notifications,
+            explicit_content_filter: self.explicit_content_filter,
+            auto_role_id: self.auto_role_id,
+            discoverable: self.discoverable,
             created_at: self.created_at,
             updated_at: self.updated_at,
         }
@@ -61,7 +83,9 @@ impl ServerRepository for PgServerRepository {
     async fn find_by_id(&self, id: i64) -> Result<Option<Server>, AppError> {
         let row = sqlx::query_as::<_, ServerRow>(
             r#"
-            SELECT id, name, owner_id, icon_url, description, created_at, updated_at
+            SELECT id, name, owner_id, icon_url, description, system_channel_id, afk_channel_id,
+                   default_message_notifications, explicit_content_filter, auto_role_id,
+                   discoverable, created_at, updated_at
             FROM servers
             WHERE id = $1 AND deleted_at IS NULL
             "#,
@@ -77,7 +101,9 @@ impl ServerRepository for PgServerRepository {
     async fn find_by_user_id(&self, user_id: i64) -> Result<Vec<Server>, AppError> {
         let rows = sqlx::query_as::<_, ServerRow>(
             r#"
-            SELECT s.id, s.name, s.owner_id, s.icon_url, s.description, s.created_at, s.updated_at
+            SELECT s.id, s.name, s.owner_id, s.icon_url, s.description, s.system_channel_id,
+                   s.afk_channel_id, s.default_message_notifications, s.explicit_content_filter,
+                   s.auto_role_id, s.discoverable, s.created_at, s.updated_at
             FROM servers s
             INNER JOIN server_members sm ON s.id = sm.server_id
             WHERE sm.user_id = $1 AND s.deleted_at IS NULL
@@ -91,11 +117,39 @@ impl ServerRepository for PgServerRepository {
         Ok(rows.into_iter().map(|r| r.into_server()).collect())
     }
 
+    /// Find the servers both users are members of, with their member counts.
+    async fn find_mutual(&self, user_id: i64, other_user_id: i64) -> Result<Vec<(Server, i64)>, AppError> {
+        let rows = sqlx::query_as::<_, ServerWithCountRow>(
+            r#"
+            SELECT s.id, s.name, s.owner_id, s.icon_url, s.description, s.system_channel_id,
+                   s.afk_channel_id, s.default_message_notifications, s.explicit_content_filter,
+                   s.auto_role_id, s.discoverable, s.created_at, s.updated_at,
+                   (SELECT COUNT(*) FROM server_members c WHERE c.server_id = s.id) AS member_count
+            FROM servers s
+            INNER JOIN server_members a ON a.server_id = s.id AND a.user_id = $1
+            INNER JOIN server_members b ON b.server_id = s.id AND b.user_id = $2
+            WHERE s.deleted_at IS NULL
+            ORDER BY s.name, s.id
+            "#,
+        )
+        .bind(user_id)
+        .bind(other_user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.server.into_server(), r.member_count))
+            .collect())
+    }
+
     /// Find all servers owned by a user.
     async fn find_by_owner_id(&self, owner_id: i64) -> Result<Vec<Server>, AppError> {
         let rows = sqlx::query_as::<_, ServerRow>(
             r#"
-            SELECT id, name, owner_id, icon_url, description, created_at, updated_at
+            SELECT id, name, owner_id, icon_url, description, system_channel_id, afk_channel_id,
+                   default_message_notifications, explicit_content_filter, auto_role_id,
+                   discoverable, created_at, updated_at
             FROM servers
             WHERE owner_id = $1 AND deleted_at IS NULL
             ORDER BY created_at DESC
@@ -116,7 +170,9 @@ impl ServerRepository for PgServerRepository {
             r#"
             INSERT INTO servers (id, name, owner_id, icon_url, description)
             VALUES ($1, $2, $3, $4, $5)
-            RETURNING id, name, owner_id, icon_url, description, created_at, updated_at
+            RETURNING id, name, owner_id, icon_url, description, system_channel_id, afk_channel_id,
+                      default_message_notifications, explicit_content_filter, auto_role_id,
+                      discoverable, created_at, updated_at
             "#,
         )
         .bind(server.id)
@@ -160,9 +216,17 @@ impl ServerRepository for PgServerRepository {
                 icon_url = $3,
                 description = $4,
                 owner_id = $5,
+                system_channel_id = $6,
+                afk_channel_id = $7,
+                default_message_notifications = $8,
+                explicit_content_filter = $9,
+                auto_role_id = $10,
+                discoverable = $11,
                 updated_at = NOW()
             WHERE id = $1
-            RETURNING id, name, owner_id, icon_url, description, created_at, updated_at
+            RETURNING id, name, owner_id, icon_url, description, system_channel_id, afk_channel_id,
+                      default_message_notifications, explicit_content_filter, auto_role_id,
+                      discoverable, created_at, updated_at
             "#,
         )
         .bind(server.id)
@@ -170,6 +234,12 @@ impl ServerRepository for PgServerRepository {
         .bind(&server.icon_url)
         .bind(&server.description)
         .bind(server.owner_id)
+        .bind(server.system_channel_id)
+        .bind(server.afk_channel_id)
+        .bind(server.default_message_notifications)
+        .bind(server.explicit_content_filter)
+        .bind(server.auto_role_id)
+        .bind(server.discoverable)
         .fetch_optional(&self.pool)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Server with id {} not found", server.id)))?;
@@ -223,6 +293,24 @@ impl ServerRepository for PgServerRepository {
 
         Ok(())
     }
+
+    /// Find the longest-standing member of a server other than the given user.
+    async fn find_oldest_member(&self, server_id: i64, excluding_user_id: i64) -> Result<Option<i64>, AppError> {
+        let user_id = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT user_id FROM server_members
+            WHERE server_id = $1 AND user_id != $2
+            ORDER BY joined_at, user_id
+            LIMIT 1
+            "#,
+        )
+        .bind(server_id)
+        .bind(excluding_user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user_id)
+    }
 }
 
 #[cfg(test)]
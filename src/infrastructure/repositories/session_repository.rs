@@ -13,8 +13,8 @@ use crate::domain::{DeviceType, Session, SessionRepository};
 use crate::shared::error::AppError;
 
 /// Database row representation matching the user_sessions table schema.
-/// Note: ip_address is stored as String because sqlx doesn't support std::net::IpAddr
-/// for PostgreSQL INET type directly.
+/// Note: ip_address is read as String because sqlx doesn't support std::net::IpAddr
+/// for PostgreSQL INET type directly; queries convert it with `host()` and `::inet`.
 #[derive(Debug, sqlx::FromRow)]
 struct SessionRow {
     id: Uuid,
@@ -29,6 +29,7 @@ struct SessionRow {
     created_at: DateTime<Utc>,
     last_used_at: DateTime<Utc>,
     revoked_at: Option<DateTime<Utc>>,
+    persistent: bool,
 }
 
 impl SessionRow {
@@ -47,6 +48,7 @@ impl SessionRow {
             created_at: self.created_at,
             last_used_at: self.last_used_at,
             revoked_at: self.revoked_at,
+            persistent: self.persistent,
         }
     }
 }
@@ -71,7 +73,8 @@ impl SessionRepository for PgSessionRepository {
         let row = sqlx::query_as::<_, SessionRow>(
             r#"
             SELECT id, user_id, refresh_token_hash, device_info, device_type, os_info,
-                   ip_address, location_info, expires_at, created_at, last_used_at, revoked_at
+                   host(ip_address) AS ip_address, location_info, expires_at, created_at,
+                   last_used_at, revoked_at, persistent
             FROM user_sessions
             WHERE id = $1
             "#,
@@ -88,7 +91,8 @@ impl SessionRepository for PgSessionRepository {
         let row = sqlx::query_as::<_, SessionRow>(
             r#"
             SELECT id, user_id, refresh_token_hash, device_info, device_type, os_info,
-                   ip_address, location_info, expires_at, created_at, last_used_at, revoked_at
+                   host(ip_address) AS ip_address, location_info, expires_at, created_at,
+                   last_used_at, revoked_at, persistent
             FROM user_sessions
             WHERE refresh_token_hash = $1 AND revoked_at IS NULL
             "#,
@@ -105,7 +109,8 @@ impl SessionRepository for PgSessionRepository {
         let rows = sqlx::query_as::<_, SessionRow>(
             r#"
             SELECT id, user_id, refresh_token_hash, device_info, device_type, os_info,
-                   ip_address, location_info, expires_at, created_at, last_used_at, revoked_at
+                   host(ip_address) AS ip_address, location_info, expires_at, created_at,
+                   last_used_at, revoked_at, persistent
             FROM user_sessions
             WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
             ORDER BY last_used_at DESC
@@ -124,11 +129,13 @@ impl SessionRepository for PgSessionRepository {
             r#"
             INSERT INTO user_sessions (
                 id, user_id, refresh_token_hash, device_info, device_type, os_info,
-                ip_address, location_info, expires_at, created_at, last_used_at
+                ip_address, location_info, expires_at, created_at, last_used_at, persistent
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            VALUES ($1, $2, $3, $4, $5, $6, $7::inet, $8, $9, $10, $11, $12)
             RETURNING id, user_id, refresh_token_hash, device_info, device_type, os_info,
-                      ip_address, location_info, expires_at, created_at, last_used_at, revoked_at
+                      host(ip_address) AS ip_address, location_info, expires_at, created_at,
+                   last_used_at, revoked_at,
+                      persistent
             "#,
         )
         .bind(session.id)
@@ -142,6 +149,7 @@ impl SessionRepository for PgSessionRepository {
         .bind(session.expires_at)
         .bind(session.created_at)
         .bind(session.last_used_at)
+        .bind(session.persistent)
         .fetch_one(&self.pool)
         .await?;
 
@@ -273,9 +281,10 @@ impl SessionRepository for PgSessionRepository {
         let rows = sqlx::query_as::<_, SessionRow>(
             r#"
             SELECT id, user_id, refresh_token_hash, device_info, device_type, os_info,
-                   ip_address, location_info, expires_at, created_at, last_used_at, revoked_at
+                   host(ip_address) AS ip_address, location_info, expires_at, created_at,
+                   last_used_at, revoked_at, persistent
             FROM user_sessions
-            WHERE ip_address = $1
+            WHERE ip_address = $1::inet
             ORDER BY created_at DESC
             LIMIT 100
             "#,
@@ -0,0 +1,155 @@
+//! Database Test Fixtures
+//!
+//! Helpers for repository tests that run against a migrated PostgreSQL
+//! database at `DATABASE_URL` (the `db-tests` feature). Every fixture gets
+//! a fresh random ID, so tests can share one database and run in parallel.
+
+use std::time::Duration;
+
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgPool, Postgres};
+use uuid::Uuid;
+
+/// Connect to the test database
+pub async fn pool() -> PgPool {
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    PgPool::connect(&url).await.unwrap()
+}
+
+/// Connect a one-connection pool and hold that connection, so every query
+/// through the pool times out waiting for one
+pub async fn exhausted_pool() -> (PgPool, PoolConnection<Postgres>) {
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(Duration::from_millis(100))
+        .connect(&url)
+        .await
+        .unwrap();
+    let held = pool.acquire().await.unwrap();
+    (pool, held)
+}
+
+/// A random positive ID no other fixture uses
+pub fn id() -> i64 {
+    (Uuid::new_v4().as_u128() as i64) & i64::MAX
+}
+
+/// Insert a user
+pub async fn user(pool: &PgPool) -> i64 {
+    let id = id();
+    sqlx::query("INSERT INTO users (id, username, email, password_hash) VALUES ($1, $2, $3, 'x')")
+        .bind(id)
+        .bind(format!("user{id}"))
+        .bind(format!("user{id}@example.com"))
+        .execute(pool)
+        .await
+        .unwrap();
+    id
+}
+
+/// Insert a server owned by `owner_id`, with its @everyone role and the
+/// owner as a member
+pub async fn server(pool: &PgPool, owner_id: i64) -> i64 {
+    let id = id();
+    sqlx::query("INSERT INTO servers (id, name, owner_id) VALUES ($1, 'server', $2)")
+        .bind(id)
+        .bind(owner_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO roles (id, server_id, name) VALUES ($1, $1, '@everyone')")
+        .bind(id)
+        .execute(pool)
+        .await
+        .unwrap();
+    member(pool, id, owner_id).await;
+    id
+}
+
+/// Add a user to a server
+pub async fn member(pool: &PgPool, server_id: i64, user_id: i64) {
+    sqlx::query("INSERT INTO server_members (server_id, user_id) VALUES ($1, $2)")
+        .bind(server_id)
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+/// Insert a text channel in a server
+pub async fn channel(pool: &PgPool, server_id: i64) -> i64 {
+    let id = id();
+    sqlx::query("INSERT INTO channels (id, server_id, name) VALUES ($1, $2, 'general')")
+        .bind(id)
+        .bind(server_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    id
+}
+
+/// Insert a message in a channel
+pub async fn message(pool: &PgPool, channel_id: i64, author_id: i64) -> i64 {
+    let id = id();
+    sqlx::query("INSERT INTO messages (id, channel_id, author_id, content) VALUES ($1, $2, $3, 'hi')")
+        .bind(id)
+        .bind(channel_id)
+        .bind(author_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    id
+}
+
+/// Insert a role at `position`
+pub async fn role(pool: &PgPool, server_id: i64, position: i32) -> i64 {
+    let id = id();
+    sqlx::query("INSERT INTO roles (id, server_id, name, position) VALUES ($1, $2, 'role', $3)")
+        .bind(id)
+        .bind(server_id)
+        .bind(position)
+        .execute(pool)
+        .await
+        .unwrap();
+    id
+}
+
+/// Set a role's permissions
+pub async fn set_permissions(pool: &PgPool, role_id: i64, permissions: i64) {
+    sqlx::query("UPDATE roles SET permissions = $2 WHERE id = $1")
+        .bind(role_id)
+        .bind(permissions)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+/// Give a member a role
+pub async fn assign_role(pool: &PgPool, server_id: i64, user_id: i64, role_id: i64) {
+    sqlx::query("INSERT INTO member_roles (server_id, user_id, role_id) VALUES ($1, $2, $3)")
+        .bind(server_id)
+        .bind(user_id)
+        .bind(role_id)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+/// Add a permission overwrite to a channel
+pub async fn overwrite(pool: &PgPool, channel_id: i64, target_type: &str, target_id: i64, allow: i64, deny: i64) {
+    sqlx::query(
+        "INSERT INTO channel_permission_overwrites (id, channel_id, target_type, target_id, allow, deny) \
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(id())
+    .bind(channel_id)
+    .bind(target_type)
+    .bind(target_id)
+    .bind(allow)
+    .bind(deny)
+    .execute(pool)
+    .await
+    .unwrap();
+}
@@ -6,9 +6,46 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use sqlx::PgPool;
+use uuid::Uuid;
 
-use crate::domain::{User, UserRepository, UserStatus};
-use crate::shared::error::AppError;
+use super::outbox_repository;
+use crate::domain::{OwnedGuildAction, PresenceVisibility, User, UserRepository, UserStatus};
+use crate::shared::error::{AppError, UniqueField};
+
+/// Dedup key and payload of the `GUILD_DELETE` event for a deleted guild.
+fn guild_delete_event(server_id: i64) -> (String, serde_json::Value) {
+    (format!("GUILD_DELETE:{}", server_id), serde_json::json!({ "id": server_id }))
+}
+
+/// Dedup key and payload of the `USER_UPDATE` event for a profile change.
+///
+/// The key includes the update time so each update is published, while a
+/// retried enqueue of it is not.
+fn user_update_event(user: &User, guild_ids: &[i64], at: DateTime<Utc>) -> (String, serde_json::Value) {
+    let payload = serde_json::json!({
+        "id": user.id.to_string(),
+        "username": user.username,
+        "display_name": user.display_name,
+        "avatar_url": user.avatar_url,
+        "banner_url": user.banner_url,
+        "guild_ids": guild_ids,
+    });
+
+    (
+        format!("USER_UPDATE:{}:{}", user.id, at.timestamp_micros()),
+        payload,
+    )
+}
+
+/// Conflict for an insert violating the users table's unique `constraint`,
+/// naming the taken field when it is known
+fn unique_violation(constraint: Option<&str>) -> AppError {
+    match constraint {
+        Some("users_email_unique") => AppError::AlreadyExists(UniqueField::Email),
+        Some("users_username_unique") => AppError::AlreadyExists(UniqueField::Username),
+        _ => AppError::Conflict("User with this email or username already exists".to_string()),
+    }
+}
 
 /// Database row representation matching the actual users table schema.
 /// This is used internally for sqlx queries since the domain User has more fields.
@@ -20,8 +57,11 @@ struct UserRow {
     password_hash: String,
     display_name: Option<String>,
     avatar_url: Option<String>,
+    banner_url: Option<String>,
     status: Option<String>,
     bio: Option<String>,
+    presence_visibility: String,
+    nsfw_allowed: bool,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
@@ -36,8 +76,11 @@ impl UserRow {
             password_hash: self.password_hash,
             display_name: self.display_name,
             avatar_url: self.avatar_url,
+            banner_url: self.banner_url,
             status: self.status.map(|s| UserStatus::from_str(&s)).unwrap_or_default(),
             bio: self.bio,
+            presence_visibility: PresenceVisibility::parse_db(&self.presence_visibility),
+            nsfw_allowed: self.nsfw_allowed,
             created_at: self.created_at,
             updated_at: self.updated_at,
         }
@@ -66,8 +109,8 @@ impl UserRepository for PgUserRepository {
     async fn find_by_id(&self, id: i64) -> Result<Option<User>, AppError> {
         let row = sqlx::query_as::<_, UserRow>(
             r#"
-            SELECT id, username, email, password_hash, display_name, avatar_url,
-                   status, bio, created_at, updated_at
+            SELECT id, username, email, password_hash, display_name, avatar_url, banner_url,
+                   status, bio, presence_visibility, nsfw_allowed, created_at, updated_at
             FROM users
             WHERE id = $1 AND deleted_at IS NULL
             "#,
@@ -79,12 +122,55 @@ impl UserRepository for PgUserRepository {
         Ok(row.map(|r| r.into_user()))
     }
 
+    /// Find several users by ID in one query.
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<User>, AppError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query_as::<_, UserRow>(
+            r#"
+            SELECT id, username, email, password_hash, display_name, avatar_url, banner_url,
+                   status, bio, presence_visibility, nsfw_allowed, created_at, updated_at
+            FROM users
+            WHERE id = ANY($1) AND deleted_at IS NULL
+            "#,
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into_user()).collect())
+    }
+
+    /// Find the users who are friends with both users.
+    async fn find_mutual_friends(&self, user_id: i64, other_user_id: i64) -> Result<Vec<User>, AppError> {
+        let rows = sqlx::query_as::<_, UserRow>(
+            r#"
+            SELECT u.id, u.username, u.email, u.password_hash, u.display_name, u.avatar_url,
+                   u.banner_url, u.status, u.bio, u.presence_visibility, u.nsfw_allowed,
+                   u.created_at, u.updated_at
+            FROM users u
+            INNER JOIN friendships a ON a.friend_id = u.id AND a.user_id = $1
+            INNER JOIN friendships b ON b.friend_id = u.id AND b.user_id = $2
+            WHERE u.deleted_at IS NULL
+            ORDER BY u.username, u.id
+            "#,
+        )
+        .bind(user_id)
+        .bind(other_user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into_user()).collect())
+    }
+
     /// Find a user by their email address.
     async fn find_by_email(&self, email: &str) -> Result<Option<User>, AppError> {
         let row = sqlx::query_as::<_, UserRow>(
             r#"
-            SELECT id, username, email, password_hash, display_name, avatar_url,
-                   status, bio, created_at, updated_at
+            SELECT id, username, email, password_hash, display_name, avatar_url, banner_url,
+                   status, bio, presence_visibility, nsfw_allowed, created_at, updated_at
             FROM users
             WHERE email = $1 AND deleted_at IS NULL
             "#,
@@ -100,8 +186,8 @@ impl UserRepository for PgUserRepository {
     async fn find_by_username(&self, username: &str) -> Result<Option<User>, AppError> {
         let row = sqlx::query_as::<_, UserRow>(
             r#"
-            SELECT id, username, email, password_hash, display_name, avatar_url,
-                   status, bio, created_at, updated_at
+            SELECT id, username, email, password_hash, display_name, avatar_url, banner_url,
+                   status, bio, presence_visibility, nsfw_allowed, created_at, updated_at
             FROM users
             WHERE username = $1 AND deleted_at IS NULL
             "#,
@@ -117,10 +203,11 @@ impl UserRepository for PgUserRepository {
     async fn create(&self, user: &User) -> Result<User, AppError> {
         let row = sqlx::query_as::<_, UserRow>(
             r#"
-            INSERT INTO users (id, username, email, password_hash, display_name, avatar_url, status, bio)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, username, email, password_hash, display_name, avatar_url,
-                      status, bio, created_at, updated_at
+            INSERT INTO users (id, username, email, password_hash, display_name, avatar_url, banner_url,
+                               status, bio)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, username, email, password_hash, display_name, avatar_url, banner_url,
+                      status, bio, presence_visibility, nsfw_allowed, created_at, updated_at
             "#,
         )
         .bind(user.id)
@@ -129,13 +216,14 @@ impl UserRepository for PgUserRepository {
         .bind(&user.password_hash)
         .bind(&user.display_name)
         .bind(&user.avatar_url)
+        .bind(&user.banner_url)
         .bind(user.status.as_str())
         .bind(&user.bio)
         .fetch_one(&self.pool)
         .await
         .map_err(|e| match &e {
             sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
-                AppError::Conflict("User with this email or username already exists".to_string())
+                unique_violation(db_err.constraint())
             }
             _ => AppError::Database(e),
         })?;
@@ -145,29 +233,46 @@ impl UserRepository for PgUserRepository {
 
     /// Update an existing user.
     async fn update(&self, user: &User) -> Result<User, AppError> {
+        let mut tx = self.pool.begin().await?;
+
         let row = sqlx::query_as::<_, UserRow>(
             r#"
             UPDATE users
             SET username = $2,
                 display_name = $3,
                 avatar_url = $4,
-                bio = $5,
+                banner_url = $5,
+                bio = $6,
+                presence_visibility = $7,
+                nsfw_allowed = $8,
                 updated_at = NOW()
             WHERE id = $1
-            RETURNING id, username, email, password_hash, display_name, avatar_url,
-                      status, bio, created_at, updated_at
+            RETURNING id, username, email, password_hash, display_name, avatar_url, banner_url,
+                      status, bio, presence_visibility, nsfw_allowed, created_at, updated_at
             "#,
         )
         .bind(user.id)
         .bind(&user.username)
         .bind(&user.display_name)
         .bind(&user.avatar_url)
+        .bind(&user.banner_url)
         .bind(&user.bio)
-        .fetch_optional(&self.pool)
+        .bind(user.presence_visibility.as_str())
+        .bind(user.nsfw_allowed)
+        .fetch_optional(&mut *tx)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("User with id {} not found", user.id)))?;
+        let updated = row.into_user();
 
-        Ok(row.into_user())
+        let guild_ids = sqlx::query_scalar::<_, i64>("SELECT server_id FROM server_members WHERE user_id = $1")
+            .bind(user.id)
+            .fetch_all(&mut *tx)
+            .await?;
+        let (dedup_key, payload) = user_update_event(&updated, &guild_ids, updated.updated_at);
+        outbox_repository::enqueue(&mut tx, &dedup_key, "USER_UPDATE", &payload).await?;
+
+        tx.commit().await?;
+        Ok(updated)
     }
 
     /// Delete a user (hard delete).
@@ -223,11 +328,202 @@ impl UserRepository for PgUserRepository {
 
         Ok(())
     }
+
+    /// Change a user's password, keeping only one of their sessions.
+    async fn change_password(
+        &self,
+        id: i64,
+        password_hash: &str,
+        session_id: Uuid,
+        refresh_token_hash: &str,
+        refresh_expires_at: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            "UPDATE users SET password_hash = $2, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(id)
+        .bind(password_hash)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("User with id {} not found", id)));
+        }
+
+        let result = sqlx::query(
+            r#"
+            UPDATE user_sessions
+            SET refresh_token_hash = $3,
+                expires_at = $4,
+                last_used_at = NOW()
+            WHERE id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(session_id)
+        .bind(id)
+        .bind(refresh_token_hash)
+        .bind(refresh_expires_at)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Session {} not found", session_id)));
+        }
+
+        let session_ids = sqlx::query_scalar::<_, Uuid>(
+            "DELETE FROM user_sessions WHERE user_id = $1 AND id <> $2 RETURNING id",
+        )
+        .bind(id)
+        .bind(session_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(session_ids)
+    }
+
+    /// Delete an account, anonymizing the user row in place.
+    ///
+    /// A `GUILD_DELETE` gateway event is queued for every deleted guild in
+    /// the same transaction.
+    async fn delete_account(
+        &self,
+        user: &User,
+        owned_guilds: &[OwnedGuildAction],
+    ) -> Result<Vec<Uuid>, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        for action in owned_guilds {
+            match *action {
+                OwnedGuildAction::Transfer { server_id, new_owner_id } => {
+                    sqlx::query("UPDATE servers SET owner_id = $2, updated_at = NOW() WHERE id = $1")
+                        .bind(server_id)
+                        .bind(new_owner_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                OwnedGuildAction::Delete { server_id } => {
+                    sqlx::query("DELETE FROM servers WHERE id = $1")
+                        .bind(server_id)
+                        .execute(&mut *tx)
+                        .await?;
+
+                    let (dedup_key, payload) = guild_delete_event(server_id);
+                    outbox_repository::enqueue(&mut tx, &dedup_key, "GUILD_DELETE", &payload).await?;
+                }
+            }
+        }
+
+        sqlx::query("DELETE FROM server_members WHERE user_id = $1")
+            .bind(user.id)
+            .execute(&mut *tx)
+            .await?;
+
+        let session_ids = sqlx::query_scalar::<_, Uuid>(
+            "DELETE FROM user_sessions WHERE user_id = $1 RETURNING id",
+        )
+        .bind(user.id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET username = $2, email = $3, password_hash = $4, display_name = $5,
+                avatar_url = $6, banner_url = $7, bio = $8, status = $9, deleted_at = NOW()
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(user.id)
+        .bind(&user.username)
+        .bind(&user.email)
+        .bind(&user.password_hash)
+        .bind(&user.display_name)
+        .bind(&user.avatar_url)
+        .bind(&user.banner_url)
+        .bind(&user.bio)
+        .bind(user.status.as_str())
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("User with id {} not found", user.id)));
+        }
+
+        tx.commit().await?;
+
+        Ok(session_ids)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::presentation::websocket::GatewayEvent;
 
     // Integration tests would go here, requiring a test database
+
+    #[test]
+    fn test_unique_violations_name_the_taken_field() {
+        assert!(matches!(
+            unique_violation(Some("users_email_unique")),
+            AppError::AlreadyExists(UniqueField::Email)
+        ));
+        assert!(matches!(
+            unique_violation(Some("users_username_unique")),
+            AppError::AlreadyExists(UniqueField::Username)
+        ));
+        match unique_violation(None) {
+            AppError::Conflict(message) => assert_eq!(message, "User with this email or username already exists"),
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_deleted_guild_emits_guild_delete() {
+        let (dedup_key, payload) = guild_delete_event(7);
+        let event: GatewayEvent =
+            serde_json::from_value(serde_json::json!({"t": "GUILD_DELETE", "d": payload})).unwrap();
+
+        assert_eq!(dedup_key, "GUILD_DELETE:7");
+        match event {
+            GatewayEvent::GuildDelete(event) => assert_eq!(event.id, 7),
+            other => panic!("expected GUILD_DELETE, got {}", other.event_name()),
+        }
+    }
+
+    #[cfg(feature = "db-tests")]
+    #[tokio::test]
+    async fn test_update_queues_user_update_for_their_guilds() {
+        use crate::infrastructure::repositories::test_db;
+
+        let pool = test_db::pool().await;
+        let user_id = test_db::user(&pool).await;
+        let owner = test_db::user(&pool).await;
+        let server = test_db::server(&pool, owner).await;
+        test_db::member(&pool, server, user_id).await;
+        let repo = PgUserRepository::new(pool.clone());
+
+        let mut user = repo.find_by_id(user_id).await.unwrap().unwrap();
+        user.display_name = Some("Renamed".to_string());
+        let updated = repo.update(&user).await.unwrap();
+
+        let (dedup_key, _) = user_update_event(&updated, &[], updated.updated_at);
+        let payload: serde_json::Value =
+            sqlx::query_scalar("SELECT payload FROM event_outbox WHERE dedup_key = $1 AND event_type = 'USER_UPDATE'")
+                .bind(&dedup_key)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        match serde_json::from_value(serde_json::json!({"t": "USER_UPDATE", "d": payload})).unwrap() {
+            GatewayEvent::UserUpdate(event) => {
+                assert_eq!(event.display_name.as_deref(), Some("Renamed"));
+                assert_eq!(event.guild_ids, vec![server]);
+            }
+            other => panic!("expected USER_UPDATE, got {}", other.event_name()),
+        }
+    }
 }
@@ -0,0 +1,121 @@
+//! Local File Storage
+//!
+//! Stores objects as files under a root directory; keys map to relative
+//! paths. Serving the directory at `base_url` is left to the deployment.
+
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use super::FileStorage;
+use crate::shared::error::AppError;
+
+/// Files on the local disk
+#[derive(Debug, Clone)]
+pub struct LocalFileStorage {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl LocalFileStorage {
+    /// Create a local storage rooted at `root`, with objects served from `base_url`
+    pub fn new(root: impl Into<PathBuf>, base_url: &str) -> Self {
+        Self {
+            root: root.into(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// File path for a key; keys may not leave the root directory
+    fn path(&self, key: &str) -> Result<PathBuf, AppError> {
+        let relative = Path::new(key);
+        let is_plain = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+
+        if key.is_empty() || !is_plain {
+            return Err(AppError::BadRequest(format!("Invalid storage key: {}", key)));
+        }
+
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl FileStorage for LocalFileStorage {
+    async fn put(&self, key: &str, data: Bytes, _content_type: Option<&str>) -> Result<(), AppError> {
+        let path = self.path(key)?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| AppError::Internal(format!("Storage error: {}", e)))?;
+        }
+
+        tokio::fs::write(&path, &data)
+            .await
+            .map_err(|e| AppError::Internal(format!("Storage error: {}", e)))
+    }
+
+    fn get_url(&self, key: &str) -> String {
+        format!("{}/{}", self.base_url, key)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        let path = self.path(key)?;
+
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::Internal(format!("Storage error: {}", e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Storage in a fresh directory under the system temp dir
+    fn storage() -> (LocalFileStorage, PathBuf) {
+        let root = std::env::temp_dir().join(format!("chat-storage-{}", uuid::Uuid::new_v4()));
+        (LocalFileStorage::new(&root, "https://cdn.example.com/"), root)
+    }
+
+    #[tokio::test]
+    async fn test_put_get_url_and_delete() {
+        let (storage, root) = storage();
+        let key = "attachments/42/cat.png";
+
+        storage
+            .put(key, Bytes::from_static(b"meow"), Some("image/png"))
+            .await
+            .unwrap();
+
+        let stored = tokio::fs::read(root.join(key)).await.unwrap();
+        assert_eq!(stored, b"meow");
+        assert_eq!(storage.get_url(key), "https://cdn.example.com/attachments/42/cat.png");
+
+        storage.delete(key).await.unwrap();
+        assert!(!root.join(key).exists());
+
+        // Deleting again is not an error
+        storage.delete(key).await.unwrap();
+
+        let _ = tokio::fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn test_keys_cannot_escape_the_root() {
+        let (storage, _root) = storage();
+
+        for key in ["../outside", "/etc/passwd", "a/../../b", ""] {
+            assert!(matches!(
+                storage.put(key, Bytes::new(), None).await,
+                Err(AppError::BadRequest(_))
+            ));
+        }
+    }
+}
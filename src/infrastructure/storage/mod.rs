@@ -0,0 +1,137 @@
+//! File Storage
+//!
+//! Where uploaded attachment files live. The backend is chosen by the
+//! `[storage]` settings:
+//!
+//! - `local`: files under a directory on disk, served from `base_url` by
+//!   whatever fronts the server (e.g. a reverse proxy)
+//! - `s3`: objects in an S3-compatible bucket
+//!
+//! Objects are addressed by keys generated with [`attachment_key`], so two
+//! uploads never share a key even when their filenames match.
+
+mod local;
+mod s3;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::config::{StorageBackend, StorageSettings};
+use crate::shared::error::AppError;
+
+pub use local::LocalFileStorage;
+pub use s3::S3FileStorage;
+
+/// Longest filename kept in a storage key
+const MAX_KEY_FILENAME_LENGTH: usize = 100;
+
+/// Backend storing uploaded files
+#[async_trait]
+pub trait FileStorage: Send + Sync {
+    /// Store `data` under `key`, replacing any existing object.
+    async fn put(&self, key: &str, data: Bytes, content_type: Option<&str>) -> Result<(), AppError>;
+
+    /// URL clients download the object at `key` from.
+    fn get_url(&self, key: &str) -> String;
+
+    /// Remove the object at `key`. Removing a missing object succeeds.
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+}
+
+/// Storage key for an attachment.
+///
+/// The attachment's snowflake makes the key unique; the filename is kept
+/// (reduced to safe characters) so download URLs end in a meaningful name.
+pub fn attachment_key(attachment_id: i64, filename: &str) -> String {
+    let mut name: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_KEY_FILENAME_LENGTH)
+        .collect();
+
+    if name.chars().all(|c| c == '.') {
+        name = "file".to_string();
+    }
+
+    format!("attachments/{}/{}", attachment_id, name)
+}
+
+/// Whether `key` has the shape of a key made by [`attachment_key`]
+pub fn is_attachment_key(key: &str) -> bool {
+    let mut parts = key.split('/');
+    let (Some("attachments"), Some(id), Some(name), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+
+    id.parse::<i64>().is_ok_and(|id| id > 0)
+        && !name.is_empty()
+        && name.len() <= MAX_KEY_FILENAME_LENGTH
+        && !name.chars().all(|c| c == '.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+}
+
+/// Create the storage backend selected in the settings
+pub fn create_file_storage(settings: &StorageSettings) -> Result<Arc<dyn FileStorage>, AppError> {
+    Ok(match settings.backend {
+        StorageBackend::Local => Arc::new(LocalFileStorage::new(
+            &settings.local.root,
+            &settings.local.base_url,
+        )),
+        StorageBackend::S3 => Arc::new(S3FileStorage::new(&settings.s3)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_keys_are_unique_per_snowflake() {
+        assert_eq!(attachment_key(42, "cat.png"), "attachments/42/cat.png");
+        assert_ne!(attachment_key(42, "cat.png"), attachment_key(43, "cat.png"));
+    }
+
+    #[test]
+    fn test_attachment_key_sanitizes_filename() {
+        assert_eq!(
+            attachment_key(1, "../../etc/pass wd"),
+            "attachments/1/.._.._etc_pass_wd"
+        );
+        assert_eq!(attachment_key(1, "résumé.pdf"), "attachments/1/r_sum_.pdf");
+        assert_eq!(attachment_key(1, ".."), "attachments/1/file");
+        assert_eq!(attachment_key(1, ""), "attachments/1/file");
+
+        let long = "a".repeat(300);
+        assert_eq!(attachment_key(1, &long).len(), "attachments/1/".len() + MAX_KEY_FILENAME_LENGTH);
+    }
+
+    #[test]
+    fn test_generated_keys_are_recognized() {
+        for filename in ["cat.png", "../../etc/pass wd", "", &"a".repeat(300)] {
+            assert!(is_attachment_key(&attachment_key(42, filename)), "{}", filename);
+        }
+
+        for key in [
+            "attachments/42",
+            "attachments/x/cat.png",
+            "attachments/42/../cat.png",
+            "attachments/42/a b.png",
+            "/attachments/42/cat.png",
+            "avatars/42/cat.png",
+        ] {
+            assert!(!is_attachment_key(key), "{}", key);
+        }
+    }
+}
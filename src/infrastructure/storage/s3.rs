@@ -0,0 +1,145 @@
+//! S3 File Storage
+//!
+//! Stores objects in an S3-compatible bucket using path-style requests
+//! (`{endpoint}/{bucket}/{key}`) through `object_store`.
+//!
+//! Endpoints must be `https://` unless `allow_http` is set, which is meant
+//! for a MinIO on a private network.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use object_store::aws::{AmazonS3, AmazonS3Builder};
+use object_store::path::Path;
+use object_store::{ObjectStore, PutOptions, PutPayload};
+
+use super::FileStorage;
+use crate::config::S3StorageSettings;
+use crate::shared::error::AppError;
+
+/// Objects in an S3-compatible bucket
+pub struct S3FileStorage {
+    store: AmazonS3,
+    /// Base of download URLs; the bucket URL unless configured
+    public_url: String,
+}
+
+impl S3FileStorage {
+    /// Create an S3 storage from its settings
+    pub fn new(settings: &S3StorageSettings) -> Result<Self, AppError> {
+        let endpoint = settings.endpoint.trim_end_matches('/');
+        let secure = endpoint.starts_with("https://");
+        if !secure && !(settings.allow_http && endpoint.starts_with("http://")) {
+            return Err(AppError::Internal(format!(
+                "S3 endpoint must be an https:// URL, got {}",
+                settings.endpoint
+            )));
+        }
+
+        if settings.bucket.is_empty() {
+            return Err(AppError::Internal("S3 bucket is not configured".into()));
+        }
+
+        let store = AmazonS3Builder::new()
+            .with_endpoint(endpoint)
+            .with_region(&settings.region)
+            .with_bucket_name(&settings.bucket)
+            .with_access_key_id(&settings.access_key_id)
+            .with_secret_access_key(&settings.secret_access_key)
+            .with_allow_http(!secure)
+            .build()
+            .map_err(|e| AppError::Internal(format!("Invalid S3 settings: {}", e)))?;
+
+        let public_url = settings
+            .public_url
+            .as_deref()
+            .map(|url| url.trim_end_matches('/').to_string())
+            .unwrap_or_else(|| format!("{}/{}", endpoint, settings.bucket));
+
+        Ok(Self { store, public_url })
+    }
+}
+
+#[async_trait]
+impl FileStorage for S3FileStorage {
+    async fn put(&self, key: &str, data: Bytes, content_type: Option<&str>) -> Result<(), AppError> {
+        let mut options = PutOptions::default();
+        if let Some(content_type) = content_type {
+            options
+                .attributes
+                .insert(object_store::Attribute::ContentType, content_type.to_string().into());
+        }
+
+        self.store
+            .put_opts(&Path::from(key), PutPayload::from(data), options)
+            .await
+            .map_err(|e| AppError::Internal(format!("S3 upload failed: {}", e)))?;
+        Ok(())
+    }
+
+    fn get_url(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url, key)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        match self.store.delete(&Path::from(key)).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(e) => Err(AppError::Internal(format!("S3 delete failed: {}", e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> S3StorageSettings {
+        S3StorageSettings {
+            endpoint: "https://minio:9000/".to_string(),
+            region: "us-east-1".to_string(),
+            bucket: "attachments".to_string(),
+            access_key_id: "key".to_string(),
+            secret_access_key: "secret".to_string(),
+            public_url: None,
+            allow_http: false,
+        }
+    }
+
+    #[test]
+    fn test_object_urls_are_path_style() {
+        let storage = S3FileStorage::new(&settings()).unwrap();
+
+        assert_eq!(
+            storage.get_url("attachments/1/a.png"),
+            "https://minio:9000/attachments/attachments/1/a.png"
+        );
+
+        let storage = S3FileStorage::new(&S3StorageSettings {
+            public_url: Some("https://cdn.example.com/".to_string()),
+            ..settings()
+        })
+        .unwrap();
+        assert_eq!(storage.get_url("attachments/1/a.png"), "https://cdn.example.com/attachments/1/a.png");
+    }
+
+    #[test]
+    fn test_plain_http_endpoints_need_opting_in() {
+        let http = S3StorageSettings {
+            endpoint: "http://minio:9000".to_string(),
+            ..settings()
+        };
+        assert!(S3FileStorage::new(&http).is_err());
+
+        assert!(S3FileStorage::new(&S3StorageSettings { allow_http: true, ..http }).is_ok());
+    }
+
+    #[test]
+    fn test_default_endpoint_uses_tls() {
+        let storage = S3FileStorage::new(&S3StorageSettings {
+            bucket: "attachments".to_string(),
+            ..S3StorageSettings::default()
+        })
+        .unwrap();
+
+        assert!(storage.get_url("a").starts_with("https://"));
+    }
+}
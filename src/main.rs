@@ -17,13 +17,13 @@ use chat_server::startup::Application;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Load configuration from environment and config files
+    let settings = Settings::load()?;
+
     // Initialize tracing subscriber for structured logging
-    chat_server::telemetry::init_tracing();
+    chat_server::telemetry::init_tracing(&settings.log, &settings.environment);
 
     info!("Starting Chat Server...");
-
-    // Load configuration from environment and config files
-    let settings = Settings::load()?;
     info!(
         host = %settings.server.host,
         port = %settings.server.port,
@@ -34,7 +34,7 @@ async fn main() -> Result<()> {
     // Build and run the application
     let application = Application::build(settings).await?;
 
-    info!("Server ready to accept connections");
+    info!("Server started; waiting for migrations before accepting traffic");
     application.run_until_stopped().await?;
 
     Ok(())
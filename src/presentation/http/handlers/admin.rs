@@ -0,0 +1,162 @@
+//! Admin Handlers
+//!
+//! Operational endpoints restricted to configured admin users.
+
+use axum::{
+    extract::{Extension, State},
+    Json,
+};
+use validator::Validate;
+
+use crate::application::dto::request::{LogLevelRequest, RateLimitResetRequest};
+use crate::application::dto::response::{LogLevelResponse, RateLimitResetResponse};
+use crate::presentation::middleware::{is_valid_identifier, AuthUser, EndpointType, RateLimitStore, RateLimiter};
+use crate::shared::error::AppError;
+use crate::shared::validation::validation_error;
+use crate::startup::AppState;
+use crate::telemetry::{self, LogFilterError};
+
+/// Replace the runtime log filter
+pub async fn set_log_level(
+    Json(body): Json<LogLevelRequest>,
+) -> Result<Json<LogLevelResponse>, AppError> {
+    body.validate()
+        .map_err(validation_error)?;
+
+    let previous = apply_log_filter(&body.filter)?;
+
+    Ok(Json(LogLevelResponse {
+        previous,
+        filter: body.filter,
+    }))
+}
+
+/// Clear a client's rate limit counters
+///
+/// Returns 404 when the client had no counters for the endpoint type.
+pub async fn reset_rate_limit(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Json(body): Json<RateLimitResetRequest>,
+) -> Result<Json<RateLimitResetResponse>, AppError> {
+    body.validate()
+        .map_err(validation_error)?;
+
+    Ok(Json(reset_limits(state.redis.clone(), auth.user_id, body).await?))
+}
+
+/// Reset a client's counters in `store`, logging the admin who did it.
+async fn reset_limits<S: RateLimitStore>(
+    store: S,
+    admin_id: i64,
+    body: RateLimitResetRequest,
+) -> Result<RateLimitResetResponse, AppError> {
+    let endpoint_type = parse_reset_target(&body)?;
+
+    let keys_removed = RateLimiter::new(store, endpoint_type)
+        .reset(&body.identifier)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    tracing::info!(
+        admin_id,
+        identifier = %body.identifier,
+        endpoint_type = %body.endpoint_type,
+        keys_removed,
+        "Admin reset rate limit"
+    );
+
+    if keys_removed == 0 {
+        return Err(AppError::NotFound("No rate limit found for identifier".into()));
+    }
+
+    Ok(RateLimitResetResponse {
+        identifier: body.identifier,
+        endpoint_type: body.endpoint_type,
+        keys_removed,
+    })
+}
+
+/// Validate the identifier and endpoint type of a reset request.
+fn parse_reset_target(body: &RateLimitResetRequest) -> Result<EndpointType, AppError> {
+    if !is_valid_identifier(&body.identifier) {
+        return Err(AppError::BadRequest(
+            "Identifier must be user:{id} or ip:{address}".into(),
+        ));
+    }
+
+    EndpointType::from_name(&body.endpoint_type)
+        .ok_or_else(|| AppError::BadRequest(format!("Unknown endpoint type: {}", body.endpoint_type)))
+}
+
+/// Apply a filter string, mapping telemetry errors to API errors.
+fn apply_log_filter(filter: &str) -> Result<String, AppError> {
+    telemetry::set_log_filter(filter).map_err(|e| match e {
+        LogFilterError::Invalid(msg) => AppError::Unprocessable(msg),
+        LogFilterError::Unavailable => AppError::Internal(e.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presentation::middleware::rate_limit::test_store::MemoryStore;
+    use axum::{http::StatusCode, response::IntoResponse};
+
+    #[test]
+    fn test_invalid_filter_is_unprocessable() {
+        let err = apply_log_filter("chat_server=verbose").unwrap_err();
+
+        assert!(matches!(err, AppError::Unprocessable(_)));
+        assert_eq!(err.into_response().status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    fn reset_request(identifier: &str, endpoint_type: &str) -> RateLimitResetRequest {
+        RateLimitResetRequest {
+            identifier: identifier.to_string(),
+            endpoint_type: endpoint_type.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_reset_target_is_parsed() {
+        let endpoint = parse_reset_target(&reset_request("user:42", "auth")).unwrap();
+        assert_eq!(endpoint, EndpointType::Auth);
+    }
+
+    #[test]
+    fn test_invalid_reset_target_is_rejected() {
+        assert!(matches!(
+            parse_reset_target(&reset_request("user:*", "api")),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            parse_reset_target(&reset_request("ip:10.0.0.1", "chat")),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reset_clears_a_limited_client() {
+        let store = MemoryStore::default();
+        let limiter = RateLimiter::new(store.clone(), EndpointType::Auth);
+        while limiter.check("user:42").await.is_ok() {}
+
+        let reset = reset_limits(store, 1, reset_request("user:42", "auth")).await.unwrap();
+
+        assert_eq!(reset.keys_removed, 1);
+        assert!(limiter.check("user:42").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reset_of_unknown_client_is_not_found() {
+        let store = MemoryStore::default();
+        RateLimiter::new(store.clone(), EndpointType::Api).check("user:42").await.unwrap();
+
+        // Counters for another endpoint type or client are left alone
+        let result = reset_limits(store.clone(), 1, reset_request("user:42", "auth")).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+        let result = reset_limits(store, 1, reset_request("user:7", "api")).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+}
@@ -0,0 +1,112 @@
+//! Attachment Handlers
+//!
+//! Files are uploaded first and referenced by ID when a message is sent.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Extension, Multipart, Path, State},
+    http::StatusCode,
+    Json,
+};
+use bytes::Bytes;
+
+use crate::application::dto::response::AttachmentResponse;
+use crate::application::services::{
+    AttachmentError, AttachmentService, AttachmentServiceImpl, UploadAttachmentDto,
+};
+use crate::infrastructure::repositories::PgAttachmentRepository;
+use crate::presentation::middleware::AuthUser;
+use crate::shared::error::AppError;
+use crate::startup::AppState;
+
+/// Multipart field holding the uploaded file
+const FILE_FIELD: &str = "file";
+
+/// Build an attachment service for a request
+fn attachment_service(state: &AppState) -> AttachmentServiceImpl<PgAttachmentRepository> {
+    AttachmentServiceImpl::new(
+        Arc::new(PgAttachmentRepository::new(state.db.clone())),
+        state.storage.clone(),
+        state.snowflake.clone(),
+    )
+}
+
+fn map_attachment_error(e: AttachmentError) -> AppError {
+    match e {
+        AttachmentError::NotFound => AppError::NotFound("Attachment not found".into()),
+        AttachmentError::Forbidden => {
+            AppError::Forbidden("Only the uploader can delete this attachment".into())
+        }
+        AttachmentError::AlreadyAttached => {
+            AppError::Conflict("Attachment is already attached to a message".into())
+        }
+        e @ (AttachmentError::Empty | AttachmentError::TooLarge | AttachmentError::InvalidFilename) => {
+            AppError::BadRequest(e.to_string())
+        }
+        AttachmentError::Unavailable => AppError::Unavailable(e.to_string()),
+        e => AppError::Internal(e.to_string()),
+    }
+}
+
+/// Read the `file` field of a multipart upload
+async fn read_upload(mut multipart: Multipart) -> Result<UploadAttachmentDto, AppError> {
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(e.body_text()))?
+    {
+        if field.name() != Some(FILE_FIELD) {
+            continue;
+        }
+
+        let filename = field.file_name().unwrap_or_default().to_string();
+        let content_type = field.content_type().map(str::to_string);
+        let data: Bytes = field
+            .bytes()
+            .await
+            .map_err(|e| AppError::BadRequest(e.body_text()))?;
+
+        return Ok(UploadAttachmentDto {
+            filename,
+            content_type,
+            data,
+        });
+    }
+
+    Err(AppError::BadRequest(format!("Missing \"{}\" field", FILE_FIELD)))
+}
+
+/// Upload a file to attach to a later message
+pub async fn upload_attachment(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    multipart: Multipart,
+) -> Result<(StatusCode, Json<AttachmentResponse>), AppError> {
+    let upload = read_upload(multipart).await?;
+
+    let attachment = attachment_service(&state)
+        .upload(auth.user_id, upload)
+        .await
+        .map_err(map_attachment_error)?;
+
+    Ok((StatusCode::CREATED, Json(AttachmentResponse::from(attachment))))
+}
+
+/// Delete an uploaded attachment that no message uses yet
+pub async fn delete_attachment(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path(attachment_id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let attachment_id: i64 = attachment_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid attachment ID".into()))?;
+
+    attachment_service(&state)
+        .delete(attachment_id, auth.user_id)
+        .await
+        .map_err(map_attachment_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,35 +1,61 @@
 //! Authentication Handlers
 
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    extract::{ConnectInfo, Extension, Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use uuid::Uuid;
 use validator::Validate;
 
-use crate::application::dto::request::{LoginRequest, RefreshTokenRequest, RegisterRequest};
-use crate::application::dto::response::{RegisterResponse, TokenResponse, UserResponse};
-use crate::application::services::{AuthService, AuthServiceImpl};
-use crate::config::JwtSettings;
+use crate::application::dto::request::{
+    ChangePasswordRequest, LoginRequest, RefreshTokenRequest, RegisterRequest,
+};
+use crate::application::dto::response::{
+    RegisterResponse, SessionResponse, TokenResponse, UserResponse,
+};
+use crate::application::services::{AuthError, AuthService, AuthServiceImpl, ClientInfo};
+use crate::infrastructure::cache::{LoginThrottle, TokenBlacklist};
 use crate::infrastructure::repositories::{PgSessionRepository, PgUserRepository};
+use crate::presentation::middleware::{client_ip, AuthUser};
 use crate::shared::error::AppError;
+use crate::shared::validation::validation_error;
 use crate::startup::AppState;
 
+/// Client details for the session created at login or registration
+fn client_info(headers: &HeaderMap, connection_ip: Option<IpAddr>, trusted_proxies: &[IpAddr]) -> ClientInfo {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|h| h.to_str().ok())
+            .map(str::to_string)
+    };
+
+    ClientInfo {
+        user_agent: header("user-agent"),
+        device_type: header("x-device-type"),
+        ip_address: client_ip(headers, connection_ip, trusted_proxies),
+    }
+}
+
 /// Register a new user
 pub async fn register(
     State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(body): Json<RegisterRequest>,
 ) -> Result<(StatusCode, Json<RegisterResponse>), AppError> {
     // Validate request
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(validation_error)?;
 
     // Create service
     let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
     let session_repo = Arc::new(PgSessionRepository::new(state.db.clone()));
-    let jwt_settings = JwtSettings {
-        secret: state.settings.jwt.secret.clone(),
-        access_token_expiry_minutes: state.settings.jwt.access_token_expiry_minutes,
-        refresh_token_expiry_days: state.settings.jwt.refresh_token_expiry_days,
-    };
+    let jwt_settings = state.settings.jwt.clone();
     let auth_service = AuthServiceImpl::new(
         user_repo,
         session_repo,
@@ -39,15 +65,14 @@ pub async fn register(
 
     // Register user
     let (user, tokens) = auth_service
-        .register(&body.username, &body.email, &body.password)
+        .register(&body.username, &body.email, &body.password, &client_info(&headers, Some(peer.ip()), &state.settings.server.trusted_proxies))
         .await
         .map_err(|e| match e {
-            crate::application::services::AuthError::EmailExists => {
-                AppError::Conflict("Email already exists".into())
-            }
-            crate::application::services::AuthError::UsernameExists => {
-                AppError::Conflict("Username already exists".into())
+            e @ (crate::application::services::AuthError::EmailExists
+            | crate::application::services::AuthError::UsernameExists) => {
+                AppError::Conflict(e.to_string())
             }
+            AuthError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
@@ -65,35 +90,36 @@ pub async fn register(
 /// Login with credentials
 pub async fn login(
     State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(body): Json<LoginRequest>,
 ) -> Result<Json<TokenResponse>, AppError> {
     // Validate request
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(validation_error)?;
 
     // Create service
     let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
     let session_repo = Arc::new(PgSessionRepository::new(state.db.clone()));
-    let jwt_settings = JwtSettings {
-        secret: state.settings.jwt.secret.clone(),
-        access_token_expiry_minutes: state.settings.jwt.access_token_expiry_minutes,
-        refresh_token_expiry_days: state.settings.jwt.refresh_token_expiry_days,
-    };
+    let jwt_settings = state.settings.jwt.clone();
     let auth_service = AuthServiceImpl::new(
         user_repo,
         session_repo,
         state.snowflake.clone(),
         jwt_settings,
-    );
+    )
+    .with_login_throttle(LoginThrottle::new(state.redis.clone()));
 
     // Authenticate
     let tokens = auth_service
-        .authenticate(&body.email, &body.password)
+        .authenticate(&body.email, &body.password, body.remember_me, &client_info(&headers, Some(peer.ip()), &state.settings.server.trusted_proxies))
         .await
         .map_err(|e| match e {
-            crate::application::services::AuthError::InvalidCredentials => {
-                AppError::Unauthorized("Invalid email or password".into())
-            }
+            AuthError::InvalidCredentials => AppError::Unauthorized("Invalid email or password".into()),
+            AuthError::AccountLocked { retry_after } => AppError::RateLimited {
+                retry_after: Some(retry_after),
+            },
+            AuthError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
@@ -108,11 +134,7 @@ pub async fn refresh_token(
     // Create service
     let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
     let session_repo = Arc::new(PgSessionRepository::new(state.db.clone()));
-    let jwt_settings = JwtSettings {
-        secret: state.settings.jwt.secret.clone(),
-        access_token_expiry_minutes: state.settings.jwt.access_token_expiry_minutes,
-        refresh_token_expiry_days: state.settings.jwt.refresh_token_expiry_days,
-    };
+    let jwt_settings = state.settings.jwt.clone();
     let auth_service = AuthServiceImpl::new(
         user_repo,
         session_repo,
@@ -131,6 +153,7 @@ pub async fn refresh_token(
             crate::application::services::AuthError::TokenExpired => {
                 AppError::Unauthorized("Refresh token expired".into())
             }
+            AuthError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
@@ -145,11 +168,7 @@ pub async fn logout(
     // Create service
     let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
     let session_repo = Arc::new(PgSessionRepository::new(state.db.clone()));
-    let jwt_settings = JwtSettings {
-        secret: state.settings.jwt.secret.clone(),
-        access_token_expiry_minutes: state.settings.jwt.access_token_expiry_minutes,
-        refresh_token_expiry_days: state.settings.jwt.refresh_token_expiry_days,
-    };
+    let jwt_settings = state.settings.jwt.clone();
     let auth_service = AuthServiceImpl::new(
         user_repo,
         session_repo,
@@ -162,3 +181,137 @@ pub async fn logout(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Build an auth service wired to the token blacklist
+fn session_auth_service(
+    state: &AppState,
+) -> AuthServiceImpl<PgUserRepository, PgSessionRepository> {
+    let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
+    let session_repo = Arc::new(PgSessionRepository::new(state.db.clone()));
+    let blacklist = TokenBlacklist::new(
+        state.redis.clone(),
+        state.settings.jwt.access_token_ttl_secs(),
+    );
+
+    AuthServiceImpl::new(
+        user_repo,
+        session_repo,
+        state.snowflake.clone(),
+        state.settings.jwt.clone(),
+    )
+    .with_token_blacklist(blacklist)
+}
+
+/// Parse the caller's auth session ID from the token claims
+fn current_session_id(auth: &AuthUser) -> Option<Uuid> {
+    auth.session_id.as_deref().and_then(|s| Uuid::parse_str(s).ok())
+}
+
+/// List the current user's active sessions
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+) -> Result<Json<Vec<SessionResponse>>, AppError> {
+    let auth_service = session_auth_service(&state);
+
+    let sessions = auth_service
+        .list_sessions(auth.user_id, current_session_id(&auth))
+        .await
+        .map_err(|e| match e {
+            AuthError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    Ok(Json(sessions.into_iter().map(SessionResponse::from).collect()))
+}
+
+/// Revoke one of the current user's sessions
+///
+/// Deletes the session, blacklists its access tokens and closes any gateway
+/// connection identified with it.
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let session_id = Uuid::parse_str(&session_id)
+        .map_err(|_| AppError::BadRequest("Invalid session ID".into()))?;
+
+    let auth_service = session_auth_service(&state);
+
+    auth_service
+        .revoke_session(auth.user_id, session_id)
+        .await
+        .map_err(|e| match e {
+            AuthError::SessionNotFound => AppError::NotFound("Session not found".into()),
+            AuthError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    state.gateway.close_auth_session(&session_id.to_string());
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Log out everywhere except the current session
+pub async fn revoke_other_sessions(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+) -> Result<StatusCode, AppError> {
+    let auth_service = session_auth_service(&state);
+
+    let revoked = auth_service
+        .revoke_other_sessions(auth.user_id, current_session_id(&auth))
+        .await
+        .map_err(|e| match e {
+            AuthError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    for session_id in revoked {
+        state.gateway.close_auth_session(&session_id.to_string());
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Change the current user's password
+///
+/// Requires the current password. Every other session is revoked and its
+/// gateway connections closed; the current session gets fresh tokens.
+pub async fn change_password(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Json(body): Json<ChangePasswordRequest>,
+) -> Result<Json<TokenResponse>, AppError> {
+    body.validate()
+        .map_err(validation_error)?;
+
+    let auth_service = session_auth_service(&state);
+
+    let changed = auth_service
+        .change_password(
+            auth.user_id,
+            current_session_id(&auth),
+            &body.current_password,
+            &body.new_password,
+        )
+        .await
+        .map_err(|e| match e {
+            AuthError::InvalidCredentials => AppError::Unauthorized("Invalid password".into()),
+            AuthError::SessionNotFound => {
+                AppError::Unauthorized("Session not found or expired".into())
+            }
+            AuthError::WeakPassword(errors) => AppError::InvalidFields(errors),
+            AuthError::PasswordUnchanged => AppError::BadRequest(e.to_string()),
+            AuthError::UserNotFound => AppError::NotFound("User not found".into()),
+            AuthError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    for session_id in changed.revoked_sessions {
+        state.gateway.close_auth_session(&session_id.to_string());
+    }
+
+    Ok(Json(TokenResponse::from(changed.tokens)))
+}
@@ -9,16 +9,22 @@ use axum::{
 };
 use validator::Validate;
 
-use crate::application::dto::request::{CreateChannelRequest, UpdateChannelRequest};
+use crate::application::dto::request::{
+    CloneChannelRequest, CreateChannelRequest, CreateDmRequest, SetOverwriteRequest, UpdateChannelRequest,
+};
 use crate::application::dto::response::ChannelResponse;
 use crate::application::services::{
-    ChannelError, ChannelService, ChannelServiceImpl, CreateChannelDto, UpdateChannelDto,
+    ChannelError, ChannelService, ChannelServiceImpl, CreateChannelDto, PermissionOverwriteDto,
+    UpdateChannelDto,
 };
+use crate::infrastructure::cache::{MessageCountCache, PermissionCacheService};
 use crate::infrastructure::repositories::{
-    PgChannelRepository, PgMemberRepository, PgServerRepository,
+    PgChannelRepository, PgMemberRepository, PgRoleRepository, PgServerRepository,
+    PgUserRepository,
 };
 use crate::presentation::middleware::AuthUser;
 use crate::shared::error::AppError;
+use crate::shared::validation::validation_error;
 use crate::startup::AppState;
 
 /// Create a new channel
@@ -34,16 +40,20 @@ pub async fn create_channel(
 
     // Validate request
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(validation_error)?;
 
     let channel_repo = Arc::new(PgChannelRepository::new(state.db.clone()));
     let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
     let member_repo = Arc::new(PgMemberRepository::new(state.db.clone()));
+    let role_repo = Arc::new(PgRoleRepository::new(state.db.clone()));
+    let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
 
     let channel_service = ChannelServiceImpl::new(
         channel_repo,
         server_repo,
         member_repo,
+        role_repo,
+        user_repo,
         state.snowflake.clone(),
     );
 
@@ -54,6 +64,7 @@ pub async fn create_channel(
         parent_id: body.parent_id.and_then(|s| s.parse().ok()),
         position: body.position,
         nsfw: body.nsfw,
+        sync_permissions: body.sync_permissions.unwrap_or(true),
     };
 
     let channel = channel_service
@@ -62,6 +73,8 @@ pub async fn create_channel(
         .map_err(|e| match e {
             ChannelError::GuildNotFound => AppError::NotFound("Guild not found".into()),
             ChannelError::Forbidden => AppError::Forbidden("Permission denied".into()),
+            ChannelError::InvalidParent(_) => AppError::BadRequest(e.to_string()),
+            ChannelError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
@@ -69,8 +82,11 @@ pub async fn create_channel(
 }
 
 /// Get channel by ID
+///
+/// Channels the user can't view are reported as not found.
 pub async fn get_channel(
     State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
     Path(channel_id): Path<String>,
 ) -> Result<Json<ChannelResponse>, AppError> {
     let channel_id: i64 = channel_id
@@ -80,19 +96,66 @@ pub async fn get_channel(
     let channel_repo = Arc::new(PgChannelRepository::new(state.db.clone()));
     let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
     let member_repo = Arc::new(PgMemberRepository::new(state.db.clone()));
+    let role_repo = Arc::new(PgRoleRepository::new(state.db.clone()));
+    let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
 
     let channel_service = ChannelServiceImpl::new(
         channel_repo,
         server_repo,
         member_repo,
+        role_repo,
+        user_repo,
         state.snowflake.clone(),
     );
 
     let channel = channel_service
-        .get_channel(channel_id)
+        .get_channel(channel_id, auth.user_id)
         .await
         .map_err(|e| match e {
             ChannelError::NotFound => AppError::NotFound("Channel not found".into()),
+            e @ ChannelError::NsfwRestricted => AppError::Forbidden(e.to_string()),
+            ChannelError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    Ok(Json(ChannelResponse::from(channel)))
+}
+
+/// Open the DM channel with another user
+///
+/// Returns the existing channel if the two users already have one.
+pub async fn create_dm(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Json(body): Json<CreateDmRequest>,
+) -> Result<Json<ChannelResponse>, AppError> {
+    let recipient_id: i64 = body
+        .recipient_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid recipient ID".into()))?;
+
+    let channel_repo = Arc::new(PgChannelRepository::new(state.db.clone()));
+    let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
+    let member_repo = Arc::new(PgMemberRepository::new(state.db.clone()));
+    let role_repo = Arc::new(PgRoleRepository::new(state.db.clone()));
+    let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
+
+    let channel_service = ChannelServiceImpl::new(
+        channel_repo,
+        server_repo,
+        member_repo,
+        role_repo,
+        user_repo,
+        state.snowflake.clone(),
+    );
+
+    let channel = channel_service
+        .open_dm(auth.user_id, recipient_id)
+        .await
+        .map_err(|e| match e {
+            ChannelError::RecipientNotFound => AppError::NotFound(e.to_string()),
+            ChannelError::InvalidRecipient => AppError::BadRequest(e.to_string()),
+            ChannelError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
@@ -112,19 +175,33 @@ pub async fn update_channel(
 
     // Validate request
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(validation_error)?;
 
     let channel_repo = Arc::new(PgChannelRepository::new(state.db.clone()));
     let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
     let member_repo = Arc::new(PgMemberRepository::new(state.db.clone()));
+    let role_repo = Arc::new(PgRoleRepository::new(state.db.clone()));
+    let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
 
     let channel_service = ChannelServiceImpl::new(
         channel_repo,
         server_repo,
         member_repo,
+        role_repo,
+        user_repo,
         state.snowflake.clone(),
     );
 
+    let slowmode_exempt_roles = body
+        .slowmode_exempt_roles
+        .map(|ids| {
+            ids.iter()
+                .map(|id| id.parse::<i64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| AppError::BadRequest("Invalid role ID".into()))
+        })
+        .transpose()?;
+
     let update = UpdateChannelDto {
         name: body.name,
         topic: body.topic,
@@ -132,6 +209,7 @@ pub async fn update_channel(
         parent_id: body.parent_id.map(|opt| opt.and_then(|s| s.parse().ok())),
         nsfw: body.nsfw,
         rate_limit_per_user: body.rate_limit_per_user,
+        slowmode_exempt_roles,
     };
 
     let channel = channel_service
@@ -140,6 +218,10 @@ pub async fn update_channel(
         .map_err(|e| match e {
             ChannelError::NotFound => AppError::NotFound("Channel not found".into()),
             ChannelError::Forbidden => AppError::Forbidden("Permission denied".into()),
+            ChannelError::InvalidParent(_) | ChannelError::InvalidExemptRole => {
+                AppError::BadRequest(e.to_string())
+            }
+            ChannelError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
@@ -159,22 +241,190 @@ pub async fn delete_channel(
     let channel_repo = Arc::new(PgChannelRepository::new(state.db.clone()));
     let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
     let member_repo = Arc::new(PgMemberRepository::new(state.db.clone()));
+    let role_repo = Arc::new(PgRoleRepository::new(state.db.clone()));
+    let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
 
     let channel_service = ChannelServiceImpl::new(
         channel_repo,
         server_repo,
         member_repo,
+        role_repo,
+        user_repo,
         state.snowflake.clone(),
-    );
+    )
+    .with_permission_cache(PermissionCacheService::new(state.redis.clone()));
 
+    // CHANNEL_DELETE is queued in the outbox by the deletion itself
     channel_service
         .delete_channel(channel_id, auth.user_id)
         .await
         .map_err(|e| match e {
             ChannelError::NotFound => AppError::NotFound("Channel not found".into()),
             ChannelError::Forbidden => AppError::Forbidden("Permission denied".into()),
+            ChannelError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
+    if let Err(e) = MessageCountCache::new(state.redis.clone()).remove(channel_id).await {
+        tracing::warn!(channel_id, error = %e, "Failed to drop cached message count");
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Clone a channel's settings and permission overwrites
+pub async fn clone_channel(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path(channel_id): Path<String>,
+    Json(body): Json<CloneChannelRequest>,
+) -> Result<(StatusCode, Json<ChannelResponse>), AppError> {
+    let channel_id: i64 = channel_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid channel ID".into()))?;
+
+    // Validate request
+    body.validate()
+        .map_err(validation_error)?;
+
+    let channel_repo = Arc::new(PgChannelRepository::new(state.db.clone()));
+    let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
+    let member_repo = Arc::new(PgMemberRepository::new(state.db.clone()));
+    let role_repo = Arc::new(PgRoleRepository::new(state.db.clone()));
+    let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
+
+    let channel_service = ChannelServiceImpl::new(
+        channel_repo,
+        server_repo,
+        member_repo,
+        role_repo,
+        user_repo,
+        state.snowflake.clone(),
+    );
+
+    let channel = channel_service
+        .clone_channel(channel_id, auth.user_id, body.name)
+        .await
+        .map_err(|e| match e {
+            ChannelError::NotFound => AppError::NotFound("Channel not found".into()),
+            ChannelError::GuildNotFound => AppError::NotFound("Guild not found".into()),
+            ChannelError::Forbidden => AppError::Forbidden("Permission denied".into()),
+            ChannelError::InvalidChannelType => AppError::BadRequest("Only guild channels can be cloned".into()),
+            ChannelError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    Ok((StatusCode::CREATED, Json(ChannelResponse::from(channel))))
+}
+
+/// Replace a channel's permission overwrites with its category's
+///
+/// Requires MANAGE_ROLES in the channel.
+pub async fn sync_channel(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path(channel_id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let channel_id: i64 = channel_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid channel ID".into()))?;
+
+    overwrite_service(&state)
+        .sync_to_category(channel_id, auth.user_id)
+        .await
+        .map_err(map_overwrite_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Parse channel and target IDs and build a channel service for overwrite edits
+fn overwrite_request(
+    state: &AppState,
+    channel_id: &str,
+    target_id: &str,
+) -> Result<(impl ChannelService, i64, i64), AppError> {
+    let channel_id: i64 = channel_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid channel ID".into()))?;
+    let target_id: i64 = target_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid target ID".into()))?;
+
+    Ok((overwrite_service(state), channel_id, target_id))
+}
+
+/// Channel service for changing overwrites, dropping the cached permissions
+/// they affect
+fn overwrite_service(state: &AppState) -> impl ChannelService {
+    ChannelServiceImpl::new(
+        Arc::new(PgChannelRepository::new(state.db.clone())),
+        Arc::new(PgServerRepository::new(state.db.clone())),
+        Arc::new(PgMemberRepository::new(state.db.clone())),
+        Arc::new(PgRoleRepository::new(state.db.clone())),
+        Arc::new(PgUserRepository::new(state.db.clone())),
+        state.snowflake.clone(),
+    )
+    .with_permission_cache(PermissionCacheService::new(state.redis.clone()))
+}
+
+fn map_overwrite_error(e: ChannelError) -> AppError {
+    match e {
+        ChannelError::NotFound => AppError::NotFound("Channel not found".into()),
+        ChannelError::GuildNotFound => AppError::NotFound("Guild not found".into()),
+        ChannelError::Forbidden => AppError::Forbidden("Permission denied".into()),
+        ChannelError::InvalidChannelType => {
+            AppError::BadRequest("DM channels do not have permission overwrites".into())
+        }
+        ChannelError::InvalidOverwrite(_) => AppError::BadRequest(e.to_string()),
+        ChannelError::OverwriteTargetNotFound => AppError::NotFound("Overwrite target not found".into()),
+        ChannelError::NotInCategory => AppError::BadRequest(e.to_string()),
+        ChannelError::Unavailable => AppError::Unavailable(e.to_string()),
+        e => AppError::Internal(e.to_string()),
+    }
+}
+
+/// Create or replace the permission overwrite for one role or member
+///
+/// Requires MANAGE_ROLES in the channel.
+pub async fn set_overwrite(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((channel_id, target_id)): Path<(String, String)>,
+    Json(body): Json<SetOverwriteRequest>,
+) -> Result<StatusCode, AppError> {
+    let (channel_service, channel_id, target_id) =
+        overwrite_request(&state, &channel_id, &target_id)?;
+
+    let overwrite = PermissionOverwriteDto {
+        target_id,
+        target_type: body.target_type,
+        allow: body.allow.unwrap_or_default().bits(),
+        deny: body.deny.unwrap_or_default().bits(),
+    };
+
+    channel_service
+        .set_overwrite(channel_id, auth.user_id, overwrite)
+        .await
+        .map_err(map_overwrite_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delete the permission overwrite for one role or member
+///
+/// Requires MANAGE_ROLES in the channel.
+pub async fn delete_overwrite(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((channel_id, target_id)): Path<(String, String)>,
+) -> Result<StatusCode, AppError> {
+    let (channel_service, channel_id, target_id) =
+        overwrite_request(&state, &channel_id, &target_id)?;
+
+    channel_service
+        .delete_overwrite(channel_id, auth.user_id, target_id)
+        .await
+        .map_err(map_overwrite_error)?;
+
     Ok(StatusCode::NO_CONTENT)
 }
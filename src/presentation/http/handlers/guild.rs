@@ -9,17 +9,32 @@ use axum::{
 };
 use validator::Validate;
 
-use crate::application::dto::request::{CreateGuildRequest, MembersQueryParams, UpdateGuildRequest};
-use crate::application::dto::response::{ChannelResponse, GuildResponse, MemberResponse};
+use crate::application::dto::request::{
+    CreateGuildRequest, MembersQueryParams, SetMemberRolesRequest, UpdateGuildRequest,
+};
+use crate::application::dto::response::{
+    ChannelResponse, GuildPreviewResponse, GuildResponse, MemberDetailResponse, MemberResponse, RoleResponse,
+};
 use crate::application::services::{
-    ChannelService, ChannelServiceImpl, CreateGuildDto, GuildError, GuildService,
-    GuildServiceImpl, UpdateGuildDto,
+    CreateGuildDto, GuildError, GuildService, GuildServiceImpl, MemberService, MemberServiceImpl,
+    RoleError, RoleService, RoleServiceImpl, UpdateGuildDto,
+};
+use crate::domain::{
+    DefaultMessageNotifications, ExplicitContentFilter, MemberRepository, UserRepository,
+};
+use crate::infrastructure::cache::{
+    DistributedLock, EveryonePermissionsCache, GuildCache, GuildCountsCache, PermissionCacheService,
 };
 use crate::infrastructure::repositories::{
     PgChannelRepository, PgMemberRepository, PgRoleRepository, PgServerRepository,
+    PgUserRepository,
 };
 use crate::presentation::middleware::AuthUser;
+use crate::presentation::websocket::{
+    GatewayEvent, GuildMemberRemoveEvent, GuildMemberUpdateEvent, GuildUpdateEvent, UserObject,
+};
 use crate::shared::error::AppError;
+use crate::shared::validation::validation_error;
 use crate::startup::AppState;
 
 /// Create a new guild
@@ -30,7 +45,7 @@ pub async fn create_guild(
 ) -> Result<(StatusCode, Json<GuildResponse>), AppError> {
     // Validate request
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(validation_error)?;
 
     let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
     let channel_repo = Arc::new(PgChannelRepository::new(state.db.clone()));
@@ -43,18 +58,24 @@ pub async fn create_guild(
         member_repo,
         role_repo,
         state.snowflake.clone(),
-    );
+    )
+    .with_create_lock(DistributedLock::new(state.redis.clone()));
 
     let request = CreateGuildDto {
         name: body.name,
         icon_url: body.icon_url,
         description: body.description,
+        nonce: body.nonce,
     };
 
     let guild = guild_service
         .create_guild(auth.user_id, request)
         .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+        .map_err(|e| match e {
+            e @ GuildError::DuplicateCreate => AppError::Conflict(e.to_string()),
+            GuildError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
 
     Ok((StatusCode::CREATED, Json(GuildResponse::from(guild))))
 }
@@ -79,19 +100,57 @@ pub async fn get_guild(
         member_repo,
         role_repo,
         state.snowflake.clone(),
-    );
+    )
+    .with_guild_cache(GuildCache::new(state.redis.clone()));
 
     let guild = guild_service
         .get_guild(guild_id)
         .await
         .map_err(|e| match e {
             GuildError::NotFound => AppError::NotFound("Guild not found".into()),
+            GuildError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
     Ok(Json(GuildResponse::from(guild)))
 }
 
+/// Get the public preview of a discoverable guild
+///
+/// Needs no authentication; guilds that aren't discoverable are reported
+/// as not found so their existence isn't revealed.
+pub async fn get_guild_preview(
+    State(state): State<AppState>,
+    Path(guild_id): Path<String>,
+) -> Result<Json<GuildPreviewResponse>, AppError> {
+    let guild_id: i64 = guild_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid guild ID".into()))?;
+
+    let guild_service = GuildServiceImpl::new(
+        Arc::new(PgServerRepository::new(state.db.clone())),
+        Arc::new(PgChannelRepository::new(state.db.clone())),
+        Arc::new(PgMemberRepository::new(state.db.clone())),
+        Arc::new(PgRoleRepository::new(state.db.clone())),
+        state.snowflake.clone(),
+    )
+    .with_presence(state.session_cache.clone())
+    .with_counts_cache(GuildCountsCache::new(state.redis.clone()));
+
+    let preview = guild_service
+        .get_guild_preview(guild_id)
+        .await
+        .map_err(|e| match e {
+            GuildError::NotFound | GuildError::NotDiscoverable => {
+                AppError::NotFound("Guild not found".into())
+            }
+            GuildError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    Ok(Json(GuildPreviewResponse::from(preview)))
+}
+
 /// Update guild
 pub async fn update_guild(
     State(state): State<AppState>,
@@ -105,7 +164,7 @@ pub async fn update_guild(
 
     // Validate request
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(validation_error)?;
 
     let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
     let channel_repo = Arc::new(PgChannelRepository::new(state.db.clone()));
@@ -118,12 +177,27 @@ pub async fn update_guild(
         member_repo,
         role_repo,
         state.snowflake.clone(),
-    );
+    )
+    .with_guild_cache(GuildCache::new(state.redis.clone()));
 
     let update = UpdateGuildDto {
         name: body.name,
         icon_url: body.icon_url,
         description: body.description,
+        system_channel_id: parse_id_reference(body.system_channel_id, "system channel")?,
+        afk_channel_id: parse_id_reference(body.afk_channel_id, "AFK channel")?,
+        default_message_notifications: parse_setting(
+            body.default_message_notifications,
+            DefaultMessageNotifications::parse,
+            "default message notification level",
+        )?,
+        explicit_content_filter: parse_setting(
+            body.explicit_content_filter,
+            ExplicitContentFilter::parse,
+            "explicit content filter level",
+        )?,
+        auto_role_id: parse_id_reference(body.auto_role_id, "auto role")?,
+        discoverable: body.discoverable,
     };
 
     let guild = guild_service
@@ -132,12 +206,50 @@ pub async fn update_guild(
         .map_err(|e| match e {
             GuildError::NotFound => AppError::NotFound("Guild not found".into()),
             GuildError::Forbidden => AppError::Forbidden("Permission denied".into()),
+            e @ (GuildError::InvalidName
+            | GuildError::InvalidIconUrl
+            | GuildError::InvalidSystemChannel
+            | GuildError::InvalidAfkChannel
+            | GuildError::InvalidAutoRole) => AppError::BadRequest(e.to_string()),
+            GuildError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
+    state.gateway.dispatch(GatewayEvent::GuildUpdate(GuildUpdateEvent {
+        id: guild_id,
+        name: Some(guild.name.clone()),
+        icon_url: guild.icon_url.clone(),
+    }));
+
     Ok(Json(GuildResponse::from(guild)))
 }
 
+/// Parse a nullable channel or role ID from an update body, keeping the
+/// absent/null distinction.
+fn parse_id_reference(
+    value: Option<Option<String>>,
+    field: &str,
+) -> Result<Option<Option<i64>>, AppError> {
+    value
+        .map(|id| {
+            id.map(|id| id.parse::<i64>())
+                .transpose()
+                .map_err(|_| AppError::BadRequest(format!("Invalid {} ID", field)))
+        })
+        .transpose()
+}
+
+/// Parse an optional enum setting from an update body.
+fn parse_setting<T>(
+    value: Option<String>,
+    parse: fn(&str) -> Option<T>,
+    field: &str,
+) -> Result<Option<T>, AppError> {
+    value
+        .map(|v| parse(&v).ok_or_else(|| AppError::BadRequest(format!("Invalid {}", field))))
+        .transpose()
+}
+
 /// Delete guild
 pub async fn delete_guild(
     State(state): State<AppState>,
@@ -159,7 +271,8 @@ pub async fn delete_guild(
         member_repo,
         role_repo,
         state.snowflake.clone(),
-    );
+    )
+    .with_guild_cache(GuildCache::new(state.redis.clone()));
 
     guild_service
         .delete_guild(guild_id, auth.user_id)
@@ -167,43 +280,105 @@ pub async fn delete_guild(
         .map_err(|e| match e {
             GuildError::NotFound => AppError::NotFound("Guild not found".into()),
             GuildError::Forbidden => AppError::Forbidden("Permission denied".into()),
+            GuildError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Get guild channels
+/// Leave a guild as the current user
+///
+/// The owner has to transfer ownership first. The rest of the guild is
+/// told with GUILD_MEMBER_REMOVE.
+pub async fn leave_guild(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path(guild_id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let guild_id: i64 = guild_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid guild ID".into()))?;
+
+    let guild_service = GuildServiceImpl::new(
+        Arc::new(PgServerRepository::new(state.db.clone())),
+        Arc::new(PgChannelRepository::new(state.db.clone())),
+        Arc::new(PgMemberRepository::new(state.db.clone())),
+        Arc::new(PgRoleRepository::new(state.db.clone())),
+        state.snowflake.clone(),
+    )
+    .with_guild_cache(GuildCache::new(state.redis.clone()));
+
+    let user = PgUserRepository::new(state.db.clone())
+        .find_by_id(auth.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+
+    guild_service
+        .leave_guild(guild_id, auth.user_id)
+        .await
+        .map_err(|e| match e {
+            GuildError::NotFound => AppError::NotFound("Guild not found".into()),
+            GuildError::MemberNotFound => AppError::NotFound("Member not found".into()),
+            e @ GuildError::OwnerCannotLeave => AppError::BadRequest(e.to_string()),
+            GuildError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    state
+        .gateway
+        .dispatch(GatewayEvent::GuildMemberRemove(GuildMemberRemoveEvent {
+            guild_id,
+            user: UserObject {
+                id: user.id.to_string(),
+                username: user.username,
+                display_name: user.display_name,
+                avatar_url: user.avatar_url,
+            },
+        }));
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get guild channels visible to the current user, ordered as a category tree
 pub async fn get_guild_channels(
     State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
     Path(guild_id): Path<String>,
 ) -> Result<Json<Vec<ChannelResponse>>, AppError> {
     let guild_id: i64 = guild_id
         .parse()
         .map_err(|_| AppError::BadRequest("Invalid guild ID".into()))?;
 
-    let channel_repo = Arc::new(PgChannelRepository::new(state.db.clone()));
     let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
+    let channel_repo = Arc::new(PgChannelRepository::new(state.db.clone()));
     let member_repo = Arc::new(PgMemberRepository::new(state.db.clone()));
+    let role_repo = Arc::new(PgRoleRepository::new(state.db.clone()));
 
-    let channel_service = ChannelServiceImpl::new(
-        channel_repo,
+    let guild_service = GuildServiceImpl::new(
         server_repo,
+        channel_repo,
         member_repo,
+        role_repo,
         state.snowflake.clone(),
     );
 
-    let channels = channel_service
-        .get_guild_channels(guild_id)
+    let channels = guild_service
+        .get_guild_channels(guild_id, auth.user_id)
         .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+        .map_err(|e| match e {
+            GuildError::NotFound => AppError::NotFound("Guild not found".into()),
+            GuildError::Forbidden => AppError::Forbidden("Permission denied".into()),
+            GuildError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
 
     let responses: Vec<ChannelResponse> = channels.into_iter().map(ChannelResponse::from).collect();
 
     Ok(Json(responses))
 }
 
-/// Get guild members
+/// Get guild members, by user ID or with `?sort=joined_at` in join order
 pub async fn get_guild_members(
     State(state): State<AppState>,
     Path(guild_id): Path<String>,
@@ -230,11 +405,160 @@ pub async fn get_guild_members(
     );
 
     let members = guild_service
-        .get_members(guild_id, after, limit)
+        .get_members(guild_id, params.sort, after, limit)
+        .await
+        .map_err(|e| match e {
+            GuildError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    let responses: Vec<MemberResponse> = members.into_iter().map(MemberResponse::from).collect();
+
+    Ok(Json(responses))
+}
+
+/// Role service with the caches role changes must keep current: member
+/// permissions and each guild's @everyone permissions
+fn role_service(state: &AppState) -> impl RoleService {
+    RoleServiceImpl::new(
+        Arc::new(PgRoleRepository::new(state.db.clone())),
+        Arc::new(PgServerRepository::new(state.db.clone())),
+        Arc::new(PgMemberRepository::new(state.db.clone())),
+        state.snowflake.clone(),
+    )
+    .with_permission_cache(PermissionCacheService::new(state.redis.clone()))
+    .with_everyone_cache(EveryonePermissionsCache::new(state.redis.clone()))
+}
+
+/// List the members holding a role, paginated by user ID.
+/// The caller must be a member of the guild.
+pub async fn get_role_members(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((guild_id, role_id)): Path<(String, String)>,
+    Query(params): Query<MembersQueryParams>,
+) -> Result<Json<Vec<MemberResponse>>, AppError> {
+    let guild_id: i64 = guild_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid guild ID".into()))?;
+    let role_id: i64 = role_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid role ID".into()))?;
+
+    let after = params.after.and_then(|s| s.parse::<i64>().ok());
+    let limit = params.limit.unwrap_or(100);
+
+    let members = role_service(&state)
+        .get_role_members(guild_id, role_id, auth.user_id, after, limit)
         .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+        .map_err(|e| match e {
+            RoleError::NotFound => AppError::NotFound("Role not found".into()),
+            RoleError::Forbidden => AppError::Forbidden("Not a member of this guild".into()),
+            RoleError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
 
     let responses: Vec<MemberResponse> = members.into_iter().map(MemberResponse::from).collect();
 
     Ok(Json(responses))
 }
+
+/// Get a guild member with their profile, roles and guild-level
+/// permissions. The caller must be a member of the guild.
+pub async fn get_guild_member(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((guild_id, user_id)): Path<(String, String)>,
+) -> Result<Json<MemberDetailResponse>, AppError> {
+    let guild_id: i64 = guild_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid guild ID".into()))?;
+    let user_id: i64 = user_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".into()))?;
+
+    let member_service = MemberServiceImpl::new(
+        Arc::new(PgServerRepository::new(state.db.clone())),
+        Arc::new(PgMemberRepository::new(state.db.clone())),
+        Arc::new(PgRoleRepository::new(state.db.clone())),
+        Arc::new(PgUserRepository::new(state.db.clone())),
+    );
+
+    let member = member_service
+        .get_member(guild_id, user_id, auth.user_id)
+        .await
+        .map_err(|e| match e {
+            GuildError::NotFound => AppError::NotFound("Guild not found".into()),
+            GuildError::MemberNotFound => AppError::NotFound("Member not found".into()),
+            GuildError::Forbidden => AppError::Forbidden("Permission denied".into()),
+            GuildError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    Ok(Json(MemberDetailResponse::from(member)))
+}
+
+/// Replace a member's roles
+///
+/// Requires MANAGE_ROLES and every added or removed role must be below the
+/// actor's highest role. All changes are applied together and fanned out
+/// to the guild as a single GUILD_MEMBER_UPDATE event.
+pub async fn set_member_roles(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((guild_id, user_id)): Path<(String, String)>,
+    Json(body): Json<SetMemberRolesRequest>,
+) -> Result<Json<Vec<RoleResponse>>, AppError> {
+    let guild_id: i64 = guild_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid guild ID".into()))?;
+    let user_id: i64 = user_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".into()))?;
+    let role_ids = body
+        .roles
+        .iter()
+        .map(|id| id.parse::<i64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| AppError::BadRequest("Invalid role ID".into()))?;
+
+    let update = role_service(&state)
+        .set_member_roles(guild_id, user_id, role_ids, auth.user_id)
+        .await
+        .map_err(|e| match e {
+            RoleError::NotFound => AppError::NotFound("Role not found".into()),
+            RoleError::ServerNotFound => AppError::NotFound("Guild not found".into()),
+            RoleError::MemberNotFound => AppError::NotFound("Member not found".into()),
+            RoleError::Forbidden => AppError::Forbidden("Permission denied".into()),
+            RoleError::HierarchyViolation => AppError::Forbidden(e.to_string()),
+            RoleError::CannotAssignEveryoneRole => AppError::BadRequest(e.to_string()),
+            RoleError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    if update.changed {
+        let user = PgUserRepository::new(state.db.clone())
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+        let member = PgMemberRepository::new(state.db.clone()).find(guild_id, user_id).await?;
+
+        state
+            .gateway
+            .dispatch(GatewayEvent::GuildMemberUpdate(GuildMemberUpdateEvent {
+                guild_id,
+                user: UserObject {
+                    id: user.id.to_string(),
+                    username: user.username,
+                    display_name: user.display_name,
+                    avatar_url: user.avatar_url,
+                },
+                nickname: member.and_then(|m| m.nickname),
+                roles: update.roles.iter().map(|r| r.id.clone()).collect(),
+            }));
+    }
+
+    let responses: Vec<RoleResponse> = update.roles.into_iter().map(RoleResponse::from).collect();
+
+    Ok(Json(responses))
+}
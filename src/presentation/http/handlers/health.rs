@@ -1,58 +1,75 @@
 //! Health Check Handlers
 //!
-//! Provides health check endpoints for Kubernetes-style liveness and readiness probes.
+//! Provides a detailed health report for monitoring, plus minimal endpoints
+//! for Kubernetes-style liveness and readiness probes.
 //!
 //! # Endpoints
-//! - `GET /health` - Basic health check (backward compatible)
+//! - `GET /health` - Health report: version, uptime, dependency status
 //! - `GET /health/live` - Liveness probe (is the server running?)
 //! - `GET /health/ready` - Readiness probe (can the server accept traffic?)
 
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
-use chrono::{DateTime, Utc};
-use once_cell::sync::Lazy;
+use chrono::Utc;
 use serde::Serialize;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crate::startup::AppState;
 
-/// Server start time for uptime calculation
-static SERVER_START: Lazy<Instant> = Lazy::new(Instant::now);
-static SERVER_START_TIME: Lazy<DateTime<Utc>> = Lazy::new(Utc::now);
+/// Commit the binary was built from, if `GIT_SHA` was set at build time
+pub const GIT_SHA: Option<&str> = option_env!("GIT_SHA");
 
-/// Initialize the server start time (call during startup)
-pub fn init_server_start() {
-    Lazy::force(&SERVER_START);
-    Lazy::force(&SERVER_START_TIME);
-}
-
-/// Basic health response (backward compatible)
-#[derive(Debug, Serialize)]
-pub struct HealthResponse {
-    pub status: &'static str,
-    pub version: &'static str,
-}
-
-/// Detailed health check response
+/// Health report for monitoring
 #[derive(Debug, Serialize)]
-pub struct DetailedHealthResponse {
+pub struct HealthReport {
     pub status: HealthStatus,
+    /// False until migrations have run and dependencies are verified
+    pub ready: bool,
     pub version: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<&'static str>,
     pub uptime_seconds: u64,
     pub started_at: String,
-    pub checks: HealthChecks,
+    /// Open gateway connections
+    pub active_connections: usize,
+    pub dependencies: Dependencies,
 }
 
-/// Individual service health checks
+impl HealthReport {
+    /// Build a report from the dependency checks
+    pub fn new(
+        ready: bool,
+        uptime: Duration,
+        active_connections: usize,
+        database: ServiceHealth,
+        redis: ServiceHealth,
+    ) -> Self {
+        let started_at = Utc::now() - chrono::Duration::from_std(uptime).unwrap_or_default();
+
+        Self {
+            status: readiness_status(ready, &database, &redis),
+            ready,
+            version: env!("CARGO_PKG_VERSION"),
+            git_sha: GIT_SHA,
+            uptime_seconds: uptime.as_secs(),
+            started_at: started_at.to_rfc3339(),
+            active_connections,
+            dependencies: Dependencies { database, redis },
+        }
+    }
+}
+
+/// Status of each external dependency
 #[derive(Debug, Serialize)]
-pub struct HealthChecks {
+pub struct Dependencies {
     pub database: ServiceHealth,
     pub redis: ServiceHealth,
-    pub websocket: WebSocketHealth,
 }
 
 /// Health status for individual services
 #[derive(Debug, Serialize)]
 pub struct ServiceHealth {
+    /// Whether the service answered
+    pub up: bool,
     pub status: HealthStatus,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub latency_ms: Option<u64>,
@@ -60,11 +77,30 @@ pub struct ServiceHealth {
     pub message: Option<String>,
 }
 
-/// WebSocket gateway health
-#[derive(Debug, Serialize)]
-pub struct WebSocketHealth {
-    pub status: HealthStatus,
-    pub active_connections: usize,
+impl ServiceHealth {
+    /// A service that answered; degraded when slower than `degraded_after_ms`
+    fn up(latency_ms: u64, degraded_after_ms: u64) -> Self {
+        Self {
+            up: true,
+            status: if latency_ms < degraded_after_ms {
+                HealthStatus::Healthy
+            } else {
+                HealthStatus::Degraded
+            },
+            latency_ms: Some(latency_ms),
+            message: None,
+        }
+    }
+
+    /// A service that could not be reached
+    fn down(message: String) -> Self {
+        Self {
+            up: false,
+            status: HealthStatus::Unhealthy,
+            latency_ms: None,
+            message: Some(message),
+        }
+    }
 }
 
 /// Overall health status
@@ -76,89 +112,64 @@ pub enum HealthStatus {
     Unhealthy,
 }
 
-/// Simple liveness response
+/// Minimal probe response
 #[derive(Debug, Serialize)]
-pub struct LivenessResponse {
+pub struct ProbeResponse {
     pub status: &'static str,
 }
 
-/// Basic health check endpoint (backward compatible)
-pub async fn health_check() -> Json<HealthResponse> {
-    Json(HealthResponse {
-        status: "healthy",
-        version: env!("CARGO_PKG_VERSION"),
-    })
+/// Health report endpoint
+/// Returns 503 if a critical dependency is down or startup hasn't finished
+pub async fn health_check(State(state): State<AppState>) -> impl IntoResponse {
+    let (database, redis) = tokio::join!(check_database(&state), check_redis(&state));
+
+    let report = HealthReport::new(
+        state.readiness.is_ready(),
+        state.started_at.elapsed(),
+        state.gateway.session_count(),
+        database,
+        redis,
+    );
+
+    (status_code(report.status), Json(report))
 }
 
 /// Liveness probe - checks if the server is running
 /// Returns 200 if alive, used by Kubernetes to restart dead pods
-pub async fn liveness() -> Json<LivenessResponse> {
-    Json(LivenessResponse { status: "alive" })
+pub async fn liveness() -> Json<ProbeResponse> {
+    Json(ProbeResponse { status: "alive" })
 }
 
 /// Readiness probe - checks if the server can accept traffic
-/// Returns 200 if ready, 503 if dependencies are unavailable
+/// Returns 200 if ready, 503 while starting up or if dependencies are unavailable
 pub async fn readiness(State(state): State<AppState>) -> impl IntoResponse {
-    let uptime = SERVER_START.elapsed().as_secs();
-    let started_at = SERVER_START_TIME.to_rfc3339();
-
-    // Check database health
-    let db_health = check_database(&state).await;
-
-    // Check Redis health
-    let redis_health = check_redis(&state).await;
-
-    // Check WebSocket gateway
-    let ws_health = WebSocketHealth {
-        status: HealthStatus::Healthy,
-        active_connections: state.gateway.session_count(),
-    };
-
-    // Determine overall status
-    let overall_status = determine_overall_status(&db_health, &redis_health);
-
-    let response = DetailedHealthResponse {
-        status: overall_status,
-        version: env!("CARGO_PKG_VERSION"),
-        uptime_seconds: uptime,
-        started_at,
-        checks: HealthChecks {
-            database: db_health,
-            redis: redis_health,
-            websocket: ws_health,
-        },
-    };
-
-    // Return 503 if unhealthy
-    let status_code = match overall_status {
+    let (database, redis) = tokio::join!(check_database(&state), check_redis(&state));
+
+    match readiness_status(state.readiness.is_ready(), &database, &redis) {
+        HealthStatus::Unhealthy => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ProbeResponse { status: "not_ready" }),
+        ),
+        HealthStatus::Healthy | HealthStatus::Degraded => {
+            (StatusCode::OK, Json(ProbeResponse { status: "ready" }))
+        }
+    }
+}
+
+/// Response status for an overall health status
+fn status_code(status: HealthStatus) -> StatusCode {
+    match status {
         HealthStatus::Healthy | HealthStatus::Degraded => StatusCode::OK,
         HealthStatus::Unhealthy => StatusCode::SERVICE_UNAVAILABLE,
-    };
-
-    (status_code, Json(response))
+    }
 }
 
 /// Check database connectivity and latency
 async fn check_database(state: &AppState) -> ServiceHealth {
     let start = Instant::now();
     match sqlx::query("SELECT 1").execute(&state.db).await {
-        Ok(_) => {
-            let latency = start.elapsed().as_millis() as u64;
-            ServiceHealth {
-                status: if latency < 100 {
-                    HealthStatus::Healthy
-                } else {
-                    HealthStatus::Degraded
-                },
-                latency_ms: Some(latency),
-                message: None,
-            }
-        }
-        Err(e) => ServiceHealth {
-            status: HealthStatus::Unhealthy,
-            latency_ms: None,
-            message: Some(format!("Database connection failed: {}", e)),
-        },
+        Ok(_) => ServiceHealth::up(start.elapsed().as_millis() as u64, 100),
+        Err(e) => ServiceHealth::down(format!("Database connection failed: {}", e)),
     }
 }
 
@@ -170,24 +181,18 @@ async fn check_redis(state: &AppState) -> ServiceHealth {
         .query_async::<String>(&mut conn)
         .await
     {
-        Ok(_) => {
-            let latency = start.elapsed().as_millis() as u64;
-            ServiceHealth {
-                status: if latency < 50 {
-                    HealthStatus::Healthy
-                } else {
-                    HealthStatus::Degraded
-                },
-                latency_ms: Some(latency),
-                message: None,
-            }
-        }
-        Err(e) => ServiceHealth {
-            status: HealthStatus::Unhealthy,
-            latency_ms: None,
-            message: Some(format!("Redis connection failed: {}", e)),
-        },
+        Ok(_) => ServiceHealth::up(start.elapsed().as_millis() as u64, 50),
+        Err(e) => ServiceHealth::down(format!("Redis connection failed: {}", e)),
+    }
+}
+
+/// Determine readiness: unhealthy until startup work has finished,
+/// otherwise based on the dependency checks
+fn readiness_status(ready: bool, db: &ServiceHealth, redis: &ServiceHealth) -> HealthStatus {
+    if !ready {
+        return HealthStatus::Unhealthy;
     }
+    determine_overall_status(db, redis)
 }
 
 /// Determine overall health based on individual checks
@@ -222,16 +227,19 @@ mod tests {
     #[test]
     fn test_determine_overall_status() {
         let healthy = ServiceHealth {
+            up: true,
             status: HealthStatus::Healthy,
             latency_ms: Some(10),
             message: None,
         };
         let degraded = ServiceHealth {
+            up: true,
             status: HealthStatus::Degraded,
             latency_ms: Some(200),
             message: None,
         };
         let unhealthy = ServiceHealth {
+            up: false,
             status: HealthStatus::Unhealthy,
             latency_ms: None,
             message: Some("Connection failed".to_string()),
@@ -250,4 +258,36 @@ mod tests {
             HealthStatus::Unhealthy
         );
     }
+
+    #[test]
+    fn test_not_ready_until_startup_finishes() {
+        let healthy = ServiceHealth {
+            up: true,
+            status: HealthStatus::Healthy,
+            latency_ms: Some(10),
+            message: None,
+        };
+
+        assert_eq!(readiness_status(false, &healthy, &healthy), HealthStatus::Unhealthy);
+        assert_eq!(readiness_status(true, &healthy, &healthy), HealthStatus::Healthy);
+    }
+
+    #[test]
+    fn test_report_includes_version_and_dependency_flags() {
+        let report = HealthReport::new(
+            true,
+            Duration::from_secs(90),
+            3,
+            ServiceHealth::up(5, 100),
+            ServiceHealth::down("Redis connection failed".to_string()),
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["uptime_seconds"], 90);
+        assert_eq!(json["active_connections"], 3);
+        assert_eq!(json["dependencies"]["database"]["up"], true);
+        assert_eq!(json["dependencies"]["redis"]["up"], false);
+        assert_eq!(report.status, HealthStatus::Degraded);
+    }
 }
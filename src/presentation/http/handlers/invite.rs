@@ -2,32 +2,40 @@
 //!
 //! HTTP handlers for invite-related endpoints.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::{
-    extract::{Extension, Path, State},
+    extract::{Extension, Path, Query, State},
     http::StatusCode,
     Json,
 };
 use validator::Validate;
 
-use crate::application::dto::request::CreateInviteRequest;
+use crate::application::dto::request::{CreateInviteRequest, InviteQueryParams};
 use crate::application::dto::response::{
     GuildResponse, InviteAcceptResponse, InviteChannelInfo, InviteGuildInfo, InvitePreviewResponse,
     InviteResponse, InviteUserInfo,
 };
 use crate::application::services::{
-    CreateInviteDto, GuildService, GuildServiceImpl, InviteError, InviteService, InviteServiceImpl,
+    CreateInviteDto, GuildError, GuildService, GuildServiceImpl, InviteError, InviteQueryDto,
+    InviteService, InviteServiceImpl,
 };
-use crate::domain::{ChannelRepository, MemberRepository, ServerRepository};
+use crate::domain::{Channel, ChannelRepository, ServerRepository, UserRepository};
+use crate::infrastructure::cache::GuildCache;
 use crate::infrastructure::repositories::{
     PgChannelRepository, PgInviteRepository, PgMemberRepository, PgRoleRepository,
-    PgServerRepository, InviteRepository,
+    PgServerRepository, PgUserRepository,
 };
 use crate::presentation::middleware::AuthUser;
+use crate::presentation::websocket::{GatewayEvent, GuildMemberAddEvent, UserObject};
 use crate::shared::error::AppError;
+use crate::shared::validation::validation_error;
 use crate::startup::AppState;
 
+/// Channel name shown in previews of invites whose channel was deleted
+const DELETED_CHANNEL_NAME: &str = "deleted-channel";
+
 /// Helper to convert InviteError to AppError
 fn map_invite_error(e: InviteError) -> AppError {
     match e {
@@ -39,7 +47,9 @@ fn map_invite_error(e: InviteError) -> AppError {
         InviteError::ServerNotFound => AppError::NotFound("Guild not found".into()),
         InviteError::ChannelNotFound => AppError::NotFound("Channel not found".into()),
         InviteError::AlreadyMember => AppError::Conflict("Already a member of this guild".into()),
+        InviteError::InvalidCursor => AppError::BadRequest("Unknown before_code cursor".into()),
         InviteError::Internal(msg) => AppError::Internal(msg),
+        InviteError::Unavailable => AppError::Unavailable(e.to_string()),
     }
 }
 
@@ -57,7 +67,7 @@ fn map_invite_error(e: InviteError) -> AppError {
 /// - `temporary` (optional): Whether members are kicked when they go offline. Default: false.
 ///
 /// ## Permissions Required
-/// - Must be a member of the guild (CREATE_INSTANT_INVITE permission by default)
+/// - CREATE_INSTANT_INVITE permission (granted to @everyone by default)
 pub async fn create_invite(
     State(state): State<AppState>,
     Extension(auth): Extension<AuthUser>,
@@ -70,7 +80,7 @@ pub async fn create_invite(
 
     // Validate request
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(validation_error)?;
 
     // Parse optional channel_id
     let channel_id = body
@@ -91,15 +101,25 @@ pub async fn create_invite(
 
     let guild_service: Arc<
         GuildServiceImpl<PgServerRepository, PgChannelRepository, PgMemberRepository, PgRoleRepository>,
-    > = Arc::new(GuildServiceImpl::new(
-        server_repo.clone(),
-        channel_repo.clone(),
-        member_repo.clone(),
+    > = Arc::new(
+        GuildServiceImpl::new(
+            server_repo.clone(),
+            channel_repo.clone(),
+            member_repo.clone(),
+            role_repo.clone(),
+            state.snowflake.clone(),
+        )
+        .with_guild_cache(GuildCache::new(state.redis.clone())),
+    );
+
+    let invite_service = InviteServiceImpl::new(
+        invite_repo,
+        guild_service,
+        member_repo,
         role_repo,
-        state.snowflake.clone(),
-    ));
-
-    let invite_service = InviteServiceImpl::new(invite_repo, guild_service, member_repo);
+        channel_repo.clone(),
+        Arc::new(PgUserRepository::new(state.db.clone())),
+    );
 
     // Get first channel if not specified
     let final_channel_id = match channel_id {
@@ -108,8 +128,7 @@ pub async fn create_invite(
             // Get channels for the guild and use the first text channel
             let channels = channel_repo
                 .find_by_server_id(guild_id)
-                .await
-                .map_err(|e| AppError::Internal(e.to_string()))?;
+                .await?;
 
             channels
                 .into_iter()
@@ -135,14 +154,12 @@ pub async fn create_invite(
     // Get additional info for response
     let server = server_repo
         .find_by_id(guild_id)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?
+        .await?
         .ok_or_else(|| AppError::NotFound("Guild not found".into()))?;
 
     let channel = channel_repo
         .find_by_id(final_channel_id)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?
+        .await?
         .ok_or_else(|| AppError::NotFound("Channel not found".into()))?;
 
     let response = InviteResponse {
@@ -168,6 +185,8 @@ pub async fn create_invite(
         temporary: invite_dto.temporary,
         expires_at: invite_dto.expires_at,
         created_at: invite_dto.created_at,
+        remaining_uses: invite_dto.remaining_uses,
+        expires_in: invite_dto.expires_in,
     };
 
     Ok((StatusCode::CREATED, Json(response)))
@@ -194,15 +213,25 @@ pub async fn get_invite(
 
     let guild_service: Arc<
         GuildServiceImpl<PgServerRepository, PgChannelRepository, PgMemberRepository, PgRoleRepository>,
-    > = Arc::new(GuildServiceImpl::new(
-        server_repo.clone(),
-        channel_repo.clone(),
-        member_repo.clone(),
+    > = Arc::new(
+        GuildServiceImpl::new(
+            server_repo.clone(),
+            channel_repo.clone(),
+            member_repo.clone(),
+            role_repo.clone(),
+            state.snowflake.clone(),
+        )
+        .with_guild_cache(GuildCache::new(state.redis.clone())),
+    );
+
+    let invite_service = InviteServiceImpl::new(
+        invite_repo,
+        guild_service,
+        member_repo,
         role_repo,
-        state.snowflake.clone(),
-    ));
-
-    let invite_service = InviteServiceImpl::new(invite_repo, guild_service, member_repo);
+        channel_repo,
+        Arc::new(PgUserRepository::new(state.db.clone())),
+    );
 
     let preview = invite_service
         .get_invite_preview(&code)
@@ -218,14 +247,18 @@ pub async fn get_invite(
         },
         channel: InviteChannelInfo {
             id: preview.channel_id,
-            name: preview.channel_name,
+            name: preview.channel_name.unwrap_or_else(|| DELETED_CHANNEL_NAME.to_string()),
             channel_type: "text".to_string(), // Default for now
         },
-        inviter: preview.inviter_id.map(|id| InviteUserInfo {
-            id,
-            username: preview.inviter_name.unwrap_or_default(),
-            avatar_url: None,
-        }),
+        // A deleted inviter has no username to show
+        inviter: preview
+            .inviter_id
+            .zip(preview.inviter_name)
+            .map(|(id, username)| InviteUserInfo {
+                id,
+                username,
+                avatar_url: None,
+            }),
         approximate_member_count: preview.member_count,
         expires_at: None, // Preview doesn't include expiry info currently
     };
@@ -258,17 +291,29 @@ pub async fn accept_invite(
     let member_repo = Arc::new(PgMemberRepository::new(state.db.clone()));
     let role_repo = Arc::new(PgRoleRepository::new(state.db.clone()));
 
+    let guild_cache = GuildCache::new(state.redis.clone());
+
     let guild_service: Arc<
         GuildServiceImpl<PgServerRepository, PgChannelRepository, PgMemberRepository, PgRoleRepository>,
-    > = Arc::new(GuildServiceImpl::new(
-        server_repo.clone(),
-        channel_repo.clone(),
+    > = Arc::new(
+        GuildServiceImpl::new(
+            server_repo.clone(),
+            channel_repo.clone(),
+            member_repo.clone(),
+            role_repo.clone(),
+            state.snowflake.clone(),
+        )
+        .with_guild_cache(guild_cache.clone()),
+    );
+
+    let invite_service = InviteServiceImpl::new(
+        invite_repo,
+        guild_service.clone(),
         member_repo.clone(),
         role_repo,
-        state.snowflake.clone(),
-    ));
-
-    let invite_service = InviteServiceImpl::new(invite_repo, guild_service.clone(), member_repo.clone());
+        channel_repo,
+        Arc::new(PgUserRepository::new(state.db.clone())),
+    );
 
     let result = invite_service
         .use_invite(&code, auth.user_id)
@@ -278,10 +323,39 @@ pub async fn accept_invite(
     // If already a member, return the guild info anyway
     let guild_id: i64 = result.server_id.parse().map_err(|_| AppError::Internal("Invalid server ID".into()))?;
 
+    if let Some(member) = result.member {
+        // The new member changes the cached member count
+        if let Err(e) = guild_cache.invalidate(guild_id).await {
+            tracing::warn!(guild_id, error = %e, "Failed to invalidate cached guild");
+        }
+
+        let user = PgUserRepository::new(state.db.clone())
+            .find_by_id(auth.user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+
+        state
+            .gateway
+            .dispatch(GatewayEvent::GuildMemberAdd(GuildMemberAddEvent {
+                guild_id,
+                user: UserObject {
+                    id: user.id.to_string(),
+                    username: user.username,
+                    display_name: user.display_name,
+                    avatar_url: user.avatar_url,
+                },
+                joined_at: member.joined_at,
+                roles: member.roles,
+            }));
+    }
+
     let guild_dto = guild_service
         .get_guild(guild_id)
         .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+        .map_err(|e| match e {
+            GuildError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
 
     let response = InviteAcceptResponse {
         guild: GuildResponse {
@@ -290,6 +364,12 @@ pub async fn accept_invite(
             owner_id: guild_dto.owner_id,
             icon_url: guild_dto.icon_url,
             description: guild_dto.description,
+            system_channel_id: guild_dto.system_channel_id,
+            afk_channel_id: guild_dto.afk_channel_id,
+            default_message_notifications: guild_dto.default_message_notifications,
+            explicit_content_filter: guild_dto.explicit_content_filter,
+            auto_role_id: guild_dto.auto_role_id,
+            features: guild_dto.features,
             member_count: guild_dto.member_count,
             created_at: guild_dto.created_at,
         },
@@ -324,15 +404,25 @@ pub async fn delete_invite(
 
     let guild_service: Arc<
         GuildServiceImpl<PgServerRepository, PgChannelRepository, PgMemberRepository, PgRoleRepository>,
-    > = Arc::new(GuildServiceImpl::new(
-        server_repo,
-        channel_repo,
-        member_repo.clone(),
+    > = Arc::new(
+        GuildServiceImpl::new(
+            server_repo,
+            channel_repo.clone(),
+            member_repo.clone(),
+            role_repo.clone(),
+            state.snowflake.clone(),
+        )
+        .with_guild_cache(GuildCache::new(state.redis.clone())),
+    );
+
+    let invite_service = InviteServiceImpl::new(
+        invite_repo,
+        guild_service,
+        member_repo,
         role_repo,
-        state.snowflake.clone(),
-    ));
-
-    let invite_service = InviteServiceImpl::new(invite_repo, guild_service, member_repo);
+        channel_repo,
+        Arc::new(PgUserRepository::new(state.db.clone())),
+    );
 
     invite_service
         .delete_invite(&code, auth.user_id)
@@ -342,21 +432,27 @@ pub async fn delete_invite(
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// List all invites for a guild
+/// List invites for a guild
 ///
 /// GET /api/v1/guilds/:guild_id/invites
 ///
-/// Returns all invites for the specified guild. Requires MANAGE_GUILD permission.
+/// Returns a page of invites for the specified guild, newest first.
+/// Requires MANAGE_GUILD permission.
 ///
 /// ## Path Parameters
 /// - `guild_id`: The guild ID
 ///
+/// ## Query Parameters
+/// - `before_code` (optional): Only return invites created before this invite
+/// - `limit` (optional): Page size (default 50, max 100)
+///
 /// ## Permissions Required
 /// - MANAGE_GUILD permission
 pub async fn list_guild_invites(
     State(state): State<AppState>,
     Extension(auth): Extension<AuthUser>,
     Path(guild_id): Path<String>,
+    Query(query): Query<InviteQueryParams>,
 ) -> Result<Json<Vec<InviteResponse>>, AppError> {
     let guild_id: i64 = guild_id
         .parse()
@@ -366,38 +462,59 @@ pub async fn list_guild_invites(
     let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
     let channel_repo = Arc::new(PgChannelRepository::new(state.db.clone()));
     let member_repo = Arc::new(PgMemberRepository::new(state.db.clone()));
+    let role_repo = Arc::new(PgRoleRepository::new(state.db.clone()));
 
-    // Verify user has permission (must be a member for now)
-    let is_member = member_repo
-        .is_member(guild_id, auth.user_id)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let guild_service: Arc<
+        GuildServiceImpl<PgServerRepository, PgChannelRepository, PgMemberRepository, PgRoleRepository>,
+    > = Arc::new(
+        GuildServiceImpl::new(
+            server_repo.clone(),
+            channel_repo.clone(),
+            member_repo.clone(),
+            role_repo.clone(),
+            state.snowflake.clone(),
+        )
+        .with_guild_cache(GuildCache::new(state.redis.clone())),
+    );
+
+    let invite_service = InviteServiceImpl::new(
+        invite_repo,
+        guild_service,
+        member_repo,
+        role_repo,
+        channel_repo.clone(),
+        Arc::new(PgUserRepository::new(state.db.clone())),
+    );
 
-    if !is_member {
-        return Err(AppError::Forbidden("Permission denied".into()));
-    }
+    let query_dto = InviteQueryDto {
+        before_code: query.before_code,
+        limit: query.limit,
+    };
+
+    let invites = invite_service
+        .get_server_invites(guild_id, auth.user_id, query_dto)
+        .await
+        .map_err(map_invite_error)?;
 
     // Get guild info
     let server = server_repo
         .find_by_id(guild_id)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?
+        .await?
         .ok_or_else(|| AppError::NotFound("Guild not found".into()))?;
 
-    // Get all invites
-    let invites = invite_repo
+    // Invites always point at one of the guild's channels, so one lookup
+    // covers the whole page
+    let channels: HashMap<String, Channel> = channel_repo
         .find_by_server_id(guild_id)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+        .await?
+        .into_iter()
+        .map(|channel| (channel.id.to_string(), channel))
+        .collect();
 
     let mut responses = Vec::with_capacity(invites.len());
 
     for invite in invites {
-        // Get channel info
-        let channel = channel_repo
-            .find_by_id(invite.channel_id)
-            .await
-            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let channel = channels.get(&invite.channel_id);
 
         responses.push(InviteResponse {
             code: invite.code,
@@ -407,15 +524,14 @@ pub async fn list_guild_invites(
                 icon_url: server.icon_url.clone(),
             },
             channel: InviteChannelInfo {
-                id: channel.as_ref().map(|c| c.id.to_string()).unwrap_or_default(),
-                name: channel.as_ref().map(|c| c.name.clone()).unwrap_or_default(),
+                id: invite.channel_id,
+                name: channel.map(|c| c.name.clone()).unwrap_or_default(),
                 channel_type: channel
-                    .as_ref()
                     .map(|c| c.channel_type.as_str().to_string())
                     .unwrap_or_else(|| "text".to_string()),
             },
             inviter: invite.inviter_id.map(|id| InviteUserInfo {
-                id: id.to_string(),
+                id,
                 username: String::new(), // Would need user lookup
                 avatar_url: None,
             }),
@@ -423,10 +539,49 @@ pub async fn list_guild_invites(
             uses: invite.uses,
             max_age: invite.max_age,
             temporary: invite.temporary,
-            expires_at: invite.expires_at.map(|e| e.to_rfc3339()),
-            created_at: invite.created_at.to_rfc3339(),
+            expires_at: invite.expires_at,
+            created_at: invite.created_at,
+            remaining_uses: invite.remaining_uses,
+            expires_in: invite.expires_in,
         });
     }
 
     Ok(Json(responses))
 }
+
+#[cfg(all(test, feature = "db-tests"))]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+    use crate::infrastructure::repositories::test_db;
+    use crate::shared::snowflake::SnowflakeGenerator;
+
+    #[tokio::test]
+    async fn test_exhausted_pool_is_service_unavailable() {
+        let (pool, _held) = test_db::exhausted_pool().await;
+        let channel_repo = Arc::new(PgChannelRepository::new(pool.clone()));
+        let member_repo = Arc::new(PgMemberRepository::new(pool.clone()));
+        let role_repo = Arc::new(PgRoleRepository::new(pool.clone()));
+        let guild_service = Arc::new(GuildServiceImpl::new(
+            Arc::new(PgServerRepository::new(pool.clone())),
+            channel_repo.clone(),
+            member_repo.clone(),
+            role_repo.clone(),
+            Arc::new(SnowflakeGenerator::new(1, 1)),
+        ));
+        let invite_service = InviteServiceImpl::new(
+            Arc::new(PgInviteRepository::new(pool.clone())),
+            guild_service,
+            member_repo,
+            role_repo,
+            channel_repo,
+            Arc::new(PgUserRepository::new(pool)),
+        );
+
+        let error = invite_service.get_invite("abcdefgh").await.unwrap_err();
+
+        assert!(matches!(error, InviteError::Unavailable));
+        let response = map_invite_error(error).into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
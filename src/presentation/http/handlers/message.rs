@@ -10,16 +10,24 @@ use axum::{
 use serde::Deserialize;
 use validator::Validate;
 
-use crate::application::dto::request::SendMessageRequest;
+use crate::application::dto::request::{BulkDeleteMessagesRequest, SendMessageRequest};
 use crate::application::dto::response::MessageResponse;
 use crate::application::services::{
     CreateMessageDto, MessageError, MessageQueryDto, MessageService, MessageServiceImpl,
+    PinsUpdateDto,
+};
+use crate::domain::value_objects::Permissions;
+use crate::infrastructure::cache::{
+    MentionCooldown, MessageCountCache, PermissionCacheService, Slowmode, UserProfileCache,
 };
 use crate::infrastructure::repositories::{
-    PgChannelRepository, PgMemberRepository, PgMessageRepository,
+    PgChannelRepository, PgMemberRepository, PgMessageRepository, PgRoleRepository,
+    PgServerRepository, PgUserRepository,
 };
 use crate::presentation::middleware::AuthUser;
+use crate::presentation::websocket::{ChannelPinsUpdateEvent, GatewayEvent};
 use crate::shared::error::AppError;
+use crate::shared::validation::validation_error;
 use crate::startup::AppState;
 
 /// Message query parameters
@@ -29,6 +37,8 @@ pub struct MessageQuery {
     pub after: Option<String>,
     pub around: Option<String>,
     pub limit: Option<i32>,
+    /// Comma-separated message IDs to fetch instead of a page
+    pub ids: Option<String>,
 }
 
 /// Get messages from channel
@@ -45,27 +55,54 @@ pub async fn get_messages(
     let message_repo = Arc::new(PgMessageRepository::new(state.db.clone()));
     let channel_repo = Arc::new(PgChannelRepository::new(state.db.clone()));
     let member_repo = Arc::new(PgMemberRepository::new(state.db.clone()));
+    let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
+    let role_repo = Arc::new(PgRoleRepository::new(state.db.clone()));
 
     let message_service = MessageServiceImpl::new(
         message_repo,
         channel_repo,
         member_repo,
+        server_repo,
+        role_repo,
+        Arc::new(PgUserRepository::new(state.db.clone())),
         state.snowflake.clone(),
-    );
+    )
+    .with_author_cache(UserProfileCache::new(state.redis.clone()))
+    .with_permission_cache(PermissionCacheService::new(state.redis.clone()));
 
-    let query_dto = MessageQueryDto {
-        before: query.before.and_then(|s| s.parse().ok()),
-        after: query.after.and_then(|s| s.parse().ok()),
-        around: query.around.and_then(|s| s.parse().ok()),
-        limit: query.limit,
+    let messages = match query.ids {
+        Some(ids) => {
+            let ids = ids
+                .split(',')
+                .map(|id| id.trim().parse())
+                .collect::<Result<Vec<i64>, _>>()
+                .map_err(|_| AppError::BadRequest("Invalid message ID".into()))?;
+            message_service
+                .get_messages_by_ids(channel_id, ids, auth.user_id)
+                .await
+        }
+        None => {
+            let query_dto = MessageQueryDto {
+                before: query.before.and_then(|s| s.parse().ok()),
+                after: query.after.and_then(|s| s.parse().ok()),
+                around: query.around.and_then(|s| s.parse().ok()),
+                limit: query.limit,
+            };
+            message_service
+                .get_messages(channel_id, auth.user_id, query_dto)
+                .await
+        }
     };
 
-    let messages = message_service
-        .get_messages(channel_id, auth.user_id, query_dto)
-        .await
+    let messages = messages
         .map_err(|e| match e {
             MessageError::ChannelNotFound => AppError::NotFound("Channel not found".into()),
             MessageError::Forbidden => AppError::Forbidden("Permission denied".into()),
+            e @ MessageError::NsfwRestricted => AppError::Forbidden(e.to_string()),
+            e @ (MessageError::InvalidCursor | MessageError::InvalidLimit | MessageError::TooManyIds) => {
+                AppError::BadRequest(e.to_string())
+            }
+            MessageError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
@@ -87,22 +124,51 @@ pub async fn send_message(
 
     // Validate request
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(validation_error)?;
 
     let message_repo = Arc::new(PgMessageRepository::new(state.db.clone()));
     let channel_repo = Arc::new(PgChannelRepository::new(state.db.clone()));
     let member_repo = Arc::new(PgMemberRepository::new(state.db.clone()));
+    let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
+    let role_repo = Arc::new(PgRoleRepository::new(state.db.clone()));
 
-    let message_service = MessageServiceImpl::new(
+    let mut message_service = MessageServiceImpl::new(
         message_repo,
         channel_repo,
         member_repo,
+        server_repo,
+        role_repo,
+        Arc::new(PgUserRepository::new(state.db.clone())),
         state.snowflake.clone(),
-    );
+    )
+    .with_message_counts(Arc::new(MessageCountCache::new(state.redis.clone())))
+    .with_author_cache(UserProfileCache::new(state.redis.clone()))
+    .with_mention_cooldown(
+        MentionCooldown::new(state.redis.clone())
+            .with_cooldown(state.dynamic.load().rate_limit.mention_cooldown_secs),
+    )
+    .with_slowmode(Slowmode::new(state.redis.clone()));
+
+    if let Some(batcher) = &state.message_batcher {
+        message_service = message_service.with_write_batcher(batcher.clone());
+    }
+
+    let attachment_ids = body
+        .attachments
+        .iter()
+        .map(|id| {
+            id.parse::<i64>()
+                .map_err(|_| AppError::BadRequest("Invalid attachment ID".into()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
     let request = CreateMessageDto {
         content: body.content,
         reply_to: body.reply_to.and_then(|s| s.parse().ok()),
+        attachment_ids,
+        embeds: body.embeds,
+        tts: body.tts,
+        flags: body.flags,
     };
 
     let message = message_service
@@ -111,11 +177,166 @@ pub async fn send_message(
         .map_err(|e| match e {
             MessageError::ChannelNotFound => AppError::NotFound("Channel not found".into()),
             MessageError::Forbidden => AppError::Forbidden("Permission denied".into()),
+            e @ MessageError::NsfwRestricted => AppError::Forbidden(e.to_string()),
+            MessageError::InvalidChannelType => {
+                AppError::BadRequest("Cannot send messages to this channel type".into())
+            }
             MessageError::ContentTooLong => {
                 AppError::BadRequest("Message content too long (max 2000 characters)".into())
             }
+            MessageError::EmptyMessage => {
+                AppError::BadRequest("Message must have content, attachments or embeds".into())
+            }
+            MessageError::EmptyTtsMessage => {
+                AppError::BadRequest("TTS messages must have content".into())
+            }
+            e @ MessageError::InvalidFlags => AppError::BadRequest(e.to_string()),
+            MessageError::TooManyEmbeds => {
+                AppError::BadRequest("Messages can have at most 10 embeds".into())
+            }
+            MessageError::InvalidEmbed(reason) => {
+                AppError::BadRequest(format!("Invalid embed: {}", reason))
+            }
+            MessageError::ContentBlocked(reason) => AppError::BadRequest(reason),
+            MessageError::AttachmentNotFound => AppError::NotFound("Attachment not found".into()),
+            MessageError::AttachmentAlreadyAttached => {
+                AppError::Conflict("Attachment is already attached to a message".into())
+            }
+            MessageError::MentionCooldown { retry_after } | MessageError::Slowmode { retry_after } => {
+                AppError::RateLimited { retry_after: Some(retry_after) }
+            }
+            MessageError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
     Ok((StatusCode::CREATED, Json(MessageResponse::from(message))))
 }
+
+/// Message service for moderation actions such as pins and bulk deletes
+fn moderation_service(state: &AppState) -> impl MessageService {
+    MessageServiceImpl::new(
+        Arc::new(PgMessageRepository::new(state.db.clone())),
+        Arc::new(PgChannelRepository::new(state.db.clone())),
+        Arc::new(PgMemberRepository::new(state.db.clone())),
+        Arc::new(PgServerRepository::new(state.db.clone())),
+        Arc::new(PgRoleRepository::new(state.db.clone())),
+        Arc::new(PgUserRepository::new(state.db.clone())),
+        state.snowflake.clone(),
+    )
+    .with_message_counts(Arc::new(MessageCountCache::new(state.redis.clone())))
+    .with_author_cache(UserProfileCache::new(state.redis.clone()))
+}
+
+/// Parse channel and message IDs and build a message service
+fn pin_request(
+    state: &AppState,
+    channel_id: &str,
+    message_id: &str,
+) -> Result<(impl MessageService, i64, i64), AppError> {
+    let channel_id: i64 = channel_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid channel ID".into()))?;
+    let message_id: i64 = message_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid message ID".into()))?;
+
+    Ok((moderation_service(state), channel_id, message_id))
+}
+
+fn map_moderation_error(e: MessageError) -> AppError {
+    match e {
+        MessageError::ChannelNotFound => AppError::NotFound("Channel not found".into()),
+        MessageError::NotFound => AppError::NotFound("Message not found".into()),
+        MessageError::Forbidden => AppError::Forbidden("Permission denied".into()),
+        e @ MessageError::InvalidBulkDelete => AppError::BadRequest(e.to_string()),
+        MessageError::Unavailable => AppError::Unavailable(e.to_string()),
+        e => AppError::Internal(e.to_string()),
+    }
+}
+
+/// Fan out a CHANNEL_PINS_UPDATE to those who can view the channel
+async fn dispatch_pins_update(state: &AppState, update: PinsUpdateDto) {
+    let event = ChannelPinsUpdateEvent::from(update);
+    let Some(guild_id) = event.guild_id else {
+        state.gateway.dispatch(GatewayEvent::ChannelPinsUpdate(event));
+        return;
+    };
+
+    if let Err(e) = state
+        .gateway
+        .broadcast_to_guild(guild_id, GatewayEvent::ChannelPinsUpdate(event), Some(Permissions::VIEW_CHANNEL))
+        .await
+    {
+        tracing::warn!(guild_id, error = %e, "Failed to broadcast pins update");
+    }
+}
+
+/// Pin a message in a channel
+///
+/// Requires MANAGE_MESSAGES. Fans out a CHANNEL_PINS_UPDATE event.
+pub async fn pin_message(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((channel_id, message_id)): Path<(String, String)>,
+) -> Result<StatusCode, AppError> {
+    let (message_service, channel_id, message_id) =
+        pin_request(&state, &channel_id, &message_id)?;
+
+    let update = message_service
+        .pin_message(channel_id, message_id, auth.user_id)
+        .await
+        .map_err(map_moderation_error)?;
+
+    dispatch_pins_update(&state, update).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Unpin a message in a channel
+///
+/// Requires MANAGE_MESSAGES. Fans out a CHANNEL_PINS_UPDATE event.
+pub async fn unpin_message(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((channel_id, message_id)): Path<(String, String)>,
+) -> Result<StatusCode, AppError> {
+    let (message_service, channel_id, message_id) =
+        pin_request(&state, &channel_id, &message_id)?;
+
+    let update = message_service
+        .unpin_message(channel_id, message_id, auth.user_id)
+        .await
+        .map_err(map_moderation_error)?;
+
+    dispatch_pins_update(&state, update).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delete several messages of a channel at once
+///
+/// Requires MANAGE_MESSAGES. A single MESSAGE_DELETE_BULK event listing the
+/// deleted messages is queued with the deletion.
+pub async fn bulk_delete_messages(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path(channel_id): Path<String>,
+    Json(body): Json<BulkDeleteMessagesRequest>,
+) -> Result<StatusCode, AppError> {
+    let channel_id: i64 = channel_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid channel ID".into()))?;
+    let message_ids = body
+        .messages
+        .iter()
+        .map(|id| id.parse())
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|_| AppError::BadRequest("Invalid message ID".into()))?;
+
+    moderation_service(&state)
+        .bulk_delete_messages(channel_id, auth.user_id, message_ids)
+        .await
+        .map_err(map_moderation_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -8,4 +8,9 @@ pub mod user;
 pub mod guild;
 pub mod channel;
 pub mod message;
+pub mod attachment;
+pub mod reaction;
 pub mod invite;
+pub mod voice;
+pub mod notification;
+pub mod admin;
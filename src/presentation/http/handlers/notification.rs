@@ -0,0 +1,149 @@
+//! Notification Settings Handlers
+//!
+//! Per-user channel mutes and notification levels. Every change is sent to
+//! the user's other gateway sessions as `CHANNEL_OVERRIDE_UPDATE`.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Extension, Path, State},
+    Json,
+};
+
+use crate::application::dto::request::ChannelOverrideRequest;
+use crate::application::dto::response::ChannelOverrideResponse;
+use crate::application::services::{
+    ChannelOverrideDto, ChannelOverrideUpdateDto, NotificationError, NotificationService,
+    NotificationServiceImpl,
+};
+use crate::domain::NotificationLevel;
+use crate::infrastructure::repositories::{
+    PgChannelOverrideRepository, PgChannelRepository, PgMemberRepository,
+};
+use crate::presentation::middleware::AuthUser;
+use crate::presentation::websocket::{ChannelOverrideUpdateEvent, GatewayEvent};
+use crate::shared::error::AppError;
+use crate::startup::AppState;
+
+/// Build a notification service for a request
+fn notification_service(
+    state: &AppState,
+) -> NotificationServiceImpl<PgChannelOverrideRepository, PgChannelRepository, PgMemberRepository> {
+    NotificationServiceImpl::new(
+        Arc::new(PgChannelOverrideRepository::new(state.db.clone())),
+        Arc::new(PgChannelRepository::new(state.db.clone())),
+        Arc::new(PgMemberRepository::new(state.db.clone())),
+    )
+}
+
+fn map_notification_error(e: NotificationError) -> AppError {
+    match e {
+        NotificationError::ChannelNotFound => AppError::NotFound("Channel not found".into()),
+        NotificationError::Unavailable => AppError::Unavailable(e.to_string()),
+        e => AppError::Internal(e.to_string()),
+    }
+}
+
+fn parse_channel_id(channel_id: &str) -> Result<i64, AppError> {
+    channel_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid channel ID".into()))
+}
+
+/// Sync a changed override to the user's sessions
+fn dispatch_override(state: &AppState, user_id: i64, channel_override: &ChannelOverrideDto) {
+    state.gateway.dispatch_to_users(
+        GatewayEvent::ChannelOverrideUpdate(ChannelOverrideUpdateEvent::from(
+            channel_override.clone(),
+        )),
+        vec![user_id],
+    );
+}
+
+/// List the current user's channel overrides
+pub async fn get_channel_overrides(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+) -> Result<Json<Vec<ChannelOverrideResponse>>, AppError> {
+    let overrides = notification_service(&state)
+        .get_overrides(auth.user_id)
+        .await
+        .map_err(map_notification_error)?;
+
+    Ok(Json(
+        overrides.into_iter().map(ChannelOverrideResponse::from).collect(),
+    ))
+}
+
+/// Update the current user's override for a channel
+pub async fn update_channel_override(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path(channel_id): Path<String>,
+    Json(body): Json<ChannelOverrideRequest>,
+) -> Result<Json<ChannelOverrideResponse>, AppError> {
+    let channel_id = parse_channel_id(&channel_id)?;
+
+    let notification_level = body
+        .notification_level
+        .map(|level| {
+            level
+                .map(|l| {
+                    NotificationLevel::parse(&l).ok_or_else(|| {
+                        AppError::BadRequest("Invalid notification level".into())
+                    })
+                })
+                .transpose()
+        })
+        .transpose()?;
+
+    let update = ChannelOverrideUpdateDto {
+        muted: body.muted,
+        notification_level,
+    };
+
+    let channel_override = notification_service(&state)
+        .set_channel_override(auth.user_id, channel_id, update)
+        .await
+        .map_err(map_notification_error)?;
+
+    dispatch_override(&state, auth.user_id, &channel_override);
+
+    Ok(Json(ChannelOverrideResponse::from(channel_override)))
+}
+
+/// Mute a channel for the current user
+pub async fn mute_channel(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path(channel_id): Path<String>,
+) -> Result<Json<ChannelOverrideResponse>, AppError> {
+    let channel_id = parse_channel_id(&channel_id)?;
+
+    let channel_override = notification_service(&state)
+        .mute_channel(auth.user_id, channel_id)
+        .await
+        .map_err(map_notification_error)?;
+
+    dispatch_override(&state, auth.user_id, &channel_override);
+
+    Ok(Json(ChannelOverrideResponse::from(channel_override)))
+}
+
+/// Unmute a channel for the current user
+pub async fn unmute_channel(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path(channel_id): Path<String>,
+) -> Result<Json<ChannelOverrideResponse>, AppError> {
+    let channel_id = parse_channel_id(&channel_id)?;
+
+    let channel_override = notification_service(&state)
+        .unmute_channel(auth.user_id, channel_id)
+        .await
+        .map_err(map_notification_error)?;
+
+    dispatch_override(&state, auth.user_id, &channel_override);
+
+    Ok(Json(ChannelOverrideResponse::from(channel_override)))
+}
@@ -0,0 +1,123 @@
+//! Reaction Handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::application::dto::request::ReactorsQueryParams;
+use crate::application::dto::response::UserResponse;
+use crate::application::services::{
+    ReactionError, ReactionService, ReactionServiceImpl, ReactorQueryDto,
+};
+use crate::infrastructure::repositories::{
+    PgChannelRepository, PgMemberRepository, PgMessageRepository, PgReactionRepository,
+    PgRoleRepository, PgServerRepository, PgUserRepository,
+};
+use crate::presentation::middleware::AuthUser;
+use crate::shared::error::AppError;
+use crate::startup::AppState;
+
+/// Reaction service backed by the database
+fn reaction_service(state: &AppState) -> impl ReactionService {
+    ReactionServiceImpl::new(
+        Arc::new(PgReactionRepository::new(state.db.clone())),
+        Arc::new(PgMessageRepository::new(state.db.clone())),
+        Arc::new(PgChannelRepository::new(state.db.clone())),
+        Arc::new(PgMemberRepository::new(state.db.clone())),
+        Arc::new(PgServerRepository::new(state.db.clone())),
+        Arc::new(PgRoleRepository::new(state.db.clone())),
+        Arc::new(PgUserRepository::new(state.db.clone())),
+    )
+}
+
+fn map_reaction_error(e: ReactionError) -> AppError {
+    match e {
+        ReactionError::ChannelNotFound => AppError::NotFound("Channel not found".into()),
+        ReactionError::MessageNotFound => AppError::NotFound("Message not found".into()),
+        ReactionError::InvalidEmoji => AppError::BadRequest("Invalid emoji".into()),
+        ReactionError::Forbidden => AppError::Forbidden("Permission denied".into()),
+        ReactionError::Unavailable => AppError::Unavailable(e.to_string()),
+        e => AppError::Internal(e.to_string()),
+    }
+}
+
+/// Parse the channel and message IDs of a reaction route
+fn parse_message_path(channel_id: &str, message_id: &str) -> Result<(i64, i64), AppError> {
+    let channel_id: i64 = channel_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid channel ID".into()))?;
+    let message_id: i64 = message_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid message ID".into()))?;
+    Ok((channel_id, message_id))
+}
+
+/// List users who reacted to a message with an emoji
+///
+/// Paginated by user ID with `after` and `limit` (default 25, max 100).
+pub async fn get_reactors(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((channel_id, message_id, emoji)): Path<(String, String, String)>,
+    Query(params): Query<ReactorsQueryParams>,
+) -> Result<Json<Vec<UserResponse>>, AppError> {
+    let (channel_id, message_id) = parse_message_path(&channel_id, &message_id)?;
+    let after = params
+        .after
+        .map(|s| s.parse::<i64>())
+        .transpose()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".into()))?;
+
+    let query = ReactorQueryDto {
+        after,
+        limit: params.limit,
+    };
+
+    let users = reaction_service(&state)
+        .get_reactors(channel_id, message_id, &emoji, auth.user_id, query)
+        .await
+        .map_err(map_reaction_error)?;
+
+    Ok(Json(
+        users
+            .into_iter()
+            .map(|dto| UserResponse::from_dto(dto, false))
+            .collect(),
+    ))
+}
+
+/// React to a message as the current user
+pub async fn add_reaction(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((channel_id, message_id, emoji)): Path<(String, String, String)>,
+) -> Result<StatusCode, AppError> {
+    let (channel_id, message_id) = parse_message_path(&channel_id, &message_id)?;
+
+    reaction_service(&state)
+        .add_reaction(channel_id, message_id, &emoji, auth.user_id)
+        .await
+        .map_err(map_reaction_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Remove the current user's reaction from a message
+pub async fn remove_reaction(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((channel_id, message_id, emoji)): Path<(String, String, String)>,
+) -> Result<StatusCode, AppError> {
+    let (channel_id, message_id) = parse_message_path(&channel_id, &message_id)?;
+
+    reaction_service(&state)
+        .remove_reaction(channel_id, message_id, &emoji, auth.user_id)
+        .await
+        .map_err(map_reaction_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
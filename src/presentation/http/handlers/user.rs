@@ -4,16 +4,22 @@ use std::sync::Arc;
 
 use axum::{
     extract::{Extension, Path, State},
+    http::StatusCode,
     Json,
 };
 use validator::Validate;
 
-use crate::application::dto::request::UpdateUserRequest;
-use crate::application::dto::response::UserResponse;
-use crate::application::services::{ServerPreviewDto, UpdateProfileDto, UserService, UserServiceImpl};
+use crate::application::dto::request::{DeleteAccountRequest, UpdateUserRequest};
+use crate::application::dto::response::{GuildResponse, UserResponse};
+use crate::application::services::{
+    ServerPreviewDto, UpdateProfileDto, UserError, UserService, UserServiceImpl,
+};
+use crate::domain::UserStatus;
+use crate::infrastructure::cache::{Activity, TokenBlacklist, UserPresence, UserProfileCache};
 use crate::infrastructure::repositories::{PgServerRepository, PgUserRepository};
 use crate::presentation::middleware::AuthUser;
 use crate::shared::error::AppError;
+use crate::shared::validation::validation_error;
 use crate::startup::AppState;
 
 /// Get current authenticated user
@@ -32,6 +38,7 @@ pub async fn get_current_user(
             crate::application::services::UserError::NotFound => {
                 AppError::NotFound("User not found".into())
             }
+            UserError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
@@ -46,32 +53,59 @@ pub async fn update_current_user(
 ) -> Result<Json<UserResponse>, AppError> {
     // Validate request
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(validation_error)?;
 
     let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
     let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
-    let user_service = UserServiceImpl::new(user_repo, server_repo);
+    let user_service = UserServiceImpl::new(user_repo, server_repo)
+        .with_image_hosts(state.settings.storage.allowed_image_hosts.clone())
+        .with_storage(state.storage.clone());
 
+    let visibility = body.presence_visibility;
     let update = UpdateProfileDto {
         username: body.username,
         display_name: body.display_name,
         avatar_url: body.avatar_url,
+        banner_url: body.banner_url,
         bio: body.bio,
+        presence_visibility: visibility,
+        nsfw_allowed: body.nsfw_allowed,
     };
 
     let user = user_service
         .update_profile(auth.user_id, update)
         .await
         .map_err(|e| match e {
-            crate::application::services::UserError::NotFound => {
-                AppError::NotFound("User not found".into())
-            }
+            UserError::NotFound => AppError::NotFound("User not found".into()),
+            UserError::InvalidImageUrl(_) => AppError::BadRequest(e.to_string()),
+            UserError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
+    // USER_UPDATE is queued in the outbox by the update itself
+    forget_profile(&state, auth.user_id).await;
+
+    // Guild members are shown the presence the new visibility allows
+    if let Some(visibility) = visibility {
+        match state.session_cache.update_visibility(auth.user_id, visibility).await {
+            Ok(Some(presence)) => state.gateway.dispatch_presence(&presence, &presence.guild_ids),
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!(user_id = auth.user_id, error = %e, "Failed to update presence visibility");
+            }
+        }
+    }
+
     Ok(Json(UserResponse::from_dto(user, true)))
 }
 
+/// Drop a user's cached profile, which message authors are served from
+async fn forget_profile(state: &AppState, user_id: i64) {
+    if let Err(e) = UserProfileCache::new(state.redis.clone()).invalidate(user_id).await {
+        tracing::warn!(user_id, error = %e, "Failed to invalidate cached profile");
+    }
+}
+
 /// Server preview for user's guild list
 #[derive(Debug, serde::Serialize)]
 pub struct ServerPreviewResponse {
@@ -92,6 +126,46 @@ impl From<ServerPreviewDto> for ServerPreviewResponse {
     }
 }
 
+/// Delete the current user's account
+///
+/// Requires the account password. Owned guilds are transferred or deleted,
+/// messages are kept under an anonymized "Deleted User", and every session
+/// is revoked and disconnected from the gateway.
+pub async fn delete_current_user(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Json(body): Json<DeleteAccountRequest>,
+) -> Result<StatusCode, AppError> {
+    body.validate()
+        .map_err(validation_error)?;
+
+    let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
+    let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
+    let user_service = UserServiceImpl::new(user_repo, server_repo).with_token_blacklist(
+        TokenBlacklist::new(state.redis.clone(), state.settings.jwt.access_token_ttl_secs()),
+    );
+
+    let deleted = user_service
+        .delete_account(auth.user_id, &body.password)
+        .await
+        .map_err(|e| match e {
+            UserError::NotFound => AppError::NotFound("User not found".into()),
+            UserError::Unauthorized => AppError::Unauthorized("Invalid password".into()),
+            UserError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    // The row is anonymized in place, so its messages must not keep
+    // showing the old profile
+    forget_profile(&state, auth.user_id).await;
+
+    for session_id in &deleted.session_ids {
+        state.gateway.close_auth_session(session_id);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Get user's guilds
 pub async fn get_user_guilds(
     State(state): State<AppState>,
@@ -104,13 +178,130 @@ pub async fn get_user_guilds(
     let guilds = user_service
         .get_user_servers(auth.user_id)
         .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+        .map_err(|e| match e {
+            UserError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
 
     let responses: Vec<ServerPreviewResponse> = guilds.into_iter().map(ServerPreviewResponse::from).collect();
 
     Ok(Json(responses))
 }
 
+/// Get the guilds the current user shares with another user
+pub async fn get_mutual_guilds(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Vec<GuildResponse>>, AppError> {
+    let user_id: i64 = user_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".into()))?;
+
+    let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
+    let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
+    let user_service = UserServiceImpl::new(user_repo, server_repo);
+
+    let guilds = user_service
+        .get_mutual_guilds(auth.user_id, user_id)
+        .await
+        .map_err(|e| match e {
+            UserError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    Ok(Json(guilds.into_iter().map(GuildResponse::from).collect()))
+}
+
+/// Get the users who are friends with both the current user and another
+pub async fn get_mutual_friends(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Vec<UserResponse>>, AppError> {
+    let user_id: i64 = user_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".into()))?;
+
+    let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
+    let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
+    let user_service = UserServiceImpl::new(user_repo, server_repo);
+
+    let friends = user_service
+        .get_mutual_friends(auth.user_id, user_id)
+        .await
+        .map_err(|e| match e {
+            UserError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    Ok(Json(friends.into_iter().map(|f| UserResponse::from_dto(f, false)).collect()))
+}
+
+/// Get a user's presence as the current user may see it.
+///
+/// Users who share no guild with the current user appear offline.
+pub async fn get_user_presence(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path(user_id): Path<String>,
+) -> Result<Json<PresenceResponse>, AppError> {
+    let user_id: i64 = user_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".into()))?;
+
+    let user_repo = Arc::new(PgUserRepository::new(state.db.clone()));
+    let server_repo = Arc::new(PgServerRepository::new(state.db.clone()));
+    let user_service = UserServiceImpl::new(user_repo, server_repo).with_presence(state.session_cache.clone());
+
+    let presence = user_service
+        .get_presence(auth.user_id, user_id)
+        .await
+        .map_err(|e| match e {
+            UserError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    Ok(Json(match presence {
+        Some(presence) => PresenceResponse::from(presence),
+        None => PresenceResponse::offline(user_id),
+    }))
+}
+
+/// A user's presence
+#[derive(Debug, serde::Serialize)]
+pub struct PresenceResponse {
+    pub user_id: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_status: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub activities: Vec<Activity>,
+}
+
+impl PresenceResponse {
+    /// Presence of a user who isn't connected or can't be seen
+    fn offline(user_id: i64) -> Self {
+        Self {
+            user_id: user_id.to_string(),
+            status: UserStatus::Offline.to_string(),
+            custom_status: None,
+            activities: Vec::new(),
+        }
+    }
+}
+
+impl From<UserPresence> for PresenceResponse {
+    fn from(presence: UserPresence) -> Self {
+        Self {
+            user_id: presence.user_id.to_string(),
+            status: presence.status.to_string(),
+            custom_status: presence.custom_status,
+            activities: presence.activities,
+        }
+    }
+}
+
 /// Get user by ID
 pub async fn get_user(
     State(state): State<AppState>,
@@ -131,6 +322,7 @@ pub async fn get_user(
             crate::application::services::UserError::NotFound => {
                 AppError::NotFound("User not found".into())
             }
+            UserError::Unavailable => AppError::Unavailable(e.to_string()),
             e => AppError::Internal(e.to_string()),
         })?;
 
@@ -0,0 +1,84 @@
+//! Voice Handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Extension, Path, State},
+    Json,
+};
+
+use crate::application::dto::request::ModifyVoiceStateRequest;
+use crate::application::dto::response::VoiceStateResponse;
+use crate::application::services::{
+    ModifyVoiceStateDto, VoiceError, VoiceService, VoiceServiceImpl,
+};
+use crate::infrastructure::cache::VoiceStateCacheService;
+use crate::infrastructure::repositories::{
+    PgChannelRepository, PgMemberRepository, PgRoleRepository, PgServerRepository,
+};
+use crate::presentation::middleware::AuthUser;
+use crate::presentation::websocket::{GatewayEvent, VoiceStateUpdateEvent};
+use crate::shared::error::AppError;
+use crate::startup::AppState;
+
+/// Move, server mute or server deafen a member connected to voice
+///
+/// Moving requires MOVE_MEMBERS, muting MUTE_MEMBERS and deafening
+/// DEAFEN_MEMBERS. The change is fanned out to the guild as a
+/// VOICE_STATE_UPDATE event.
+pub async fn modify_member_voice_state(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((guild_id, user_id)): Path<(String, String)>,
+    Json(body): Json<ModifyVoiceStateRequest>,
+) -> Result<Json<VoiceStateResponse>, AppError> {
+    let guild_id: i64 = guild_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid guild ID".into()))?;
+    let user_id: i64 = user_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".into()))?;
+    let channel_id = match body.channel_id {
+        Some(id) => Some(
+            id.parse::<i64>()
+                .map_err(|_| AppError::BadRequest("Invalid channel ID".into()))?,
+        ),
+        None => None,
+    };
+
+    let voice_service = VoiceServiceImpl::new(
+        Arc::new(PgServerRepository::new(state.db.clone())),
+        Arc::new(PgChannelRepository::new(state.db.clone())),
+        Arc::new(PgMemberRepository::new(state.db.clone())),
+        Arc::new(PgRoleRepository::new(state.db.clone())),
+        VoiceStateCacheService::new(state.redis.clone()),
+    );
+
+    let request = ModifyVoiceStateDto {
+        channel_id,
+        mute: body.mute,
+        deaf: body.deaf,
+    };
+
+    let voice_state = voice_service
+        .modify_member_state(guild_id, auth.user_id, user_id, request)
+        .await
+        .map_err(|e| match e {
+            VoiceError::ServerNotFound => AppError::NotFound("Guild not found".into()),
+            VoiceError::ChannelNotFound => AppError::NotFound("Channel not found".into()),
+            VoiceError::MemberNotFound => AppError::NotFound("Member not found".into()),
+            VoiceError::NotVoiceChannel => AppError::BadRequest(e.to_string()),
+            VoiceError::NotConnected => AppError::BadRequest(e.to_string()),
+            VoiceError::Forbidden => AppError::Forbidden("Permission denied".into()),
+            VoiceError::Unavailable => AppError::Unavailable(e.to_string()),
+            e => AppError::Internal(e.to_string()),
+        })?;
+
+    state
+        .gateway
+        .dispatch(GatewayEvent::VoiceStateUpdate(VoiceStateUpdateEvent::from(
+            voice_state.clone(),
+        )));
+
+    Ok(Json(VoiceStateResponse::from(voice_state)))
+}
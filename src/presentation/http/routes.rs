@@ -3,17 +3,19 @@
 //! Configures all HTTP routes for the API.
 
 use axum::{
+    extract::DefaultBodyLimit,
     middleware,
     response::IntoResponse,
-    routing::{delete, get, patch, post},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 
 use super::handlers;
+use crate::domain::MAX_ATTACHMENT_SIZE;
 use crate::infrastructure::metrics;
 use crate::presentation::middleware::{
-    auth_middleware, create_security_headers_layer, rate_limit_api, rate_limit_auth,
-    rate_limit_websocket,
+    admin_middleware, auth_middleware, create_security_headers_layer, limit_json_bodies, rate_limit_api,
+    rate_limit_auth, rate_limit_guild, rate_limit_websocket, readiness_gate, track_http_metrics,
 };
 use crate::presentation::websocket::ws_handler;
 use crate::startup::AppState;
@@ -28,12 +30,18 @@ pub fn create_router(state: AppState) -> Router {
             state.clone(),
             rate_limit_websocket,
         ))
+        // API and gateway traffic waits for migrations; health probes don't
+        .route_layer(middleware::from_fn_with_state(state.clone(), readiness_gate))
         // Health check endpoints
         .route("/health", get(handlers::health::health_check))
         .route("/health/live", get(handlers::health::liveness))
         .route("/health/ready", get(handlers::health::readiness))
         // Prometheus metrics endpoint
         .route("/metrics", get(metrics_handler))
+        // Operational endpoints (admin only)
+        .nest("/admin", admin_routes(state.clone()))
+        // Time every request, labelled by the route it matched
+        .layer(middleware::from_fn(track_http_metrics))
         // Apply security headers globally to all responses
         // This layer runs last (outermost) so headers are added to all responses
         .layer(create_security_headers_layer())
@@ -54,7 +62,7 @@ async fn metrics_handler() -> impl IntoResponse {
 
 /// API v1 routes
 fn api_routes(state: AppState) -> Router<AppState> {
-    Router::new()
+    let json_routes = Router::new()
         // Public routes (auth has its own stricter rate limiting)
         .nest("/auth", auth_routes(state.clone()))
         // Public invite preview (no auth required)
@@ -63,7 +71,11 @@ fn api_routes(state: AppState) -> Router<AppState> {
         .nest("/users", user_routes(state.clone()))
         .nest("/guilds", guild_routes(state.clone()))
         .nest("/channels", channel_routes(state.clone()))
-        .nest("/invites", invite_routes(state.clone()))
+        .nest("/invites", invite_routes(state.clone()));
+
+    limit_json_bodies(json_routes, state.settings.server.max_body_bytes)
+        // Uploads are multipart and limited to the attachment size instead
+        .nest("/attachments", attachment_routes(state.clone()))
         // Apply API rate limiting to all API routes
         .route_layer(middleware::from_fn_with_state(state, rate_limit_api))
 }
@@ -84,8 +96,31 @@ fn user_routes(state: AppState) -> Router<AppState> {
     Router::new()
         .route("/@me", get(handlers::user::get_current_user))
         .route("/@me", patch(handlers::user::update_current_user))
+        .route("/@me", delete(handlers::user::delete_current_user))
         .route("/@me/guilds", get(handlers::user::get_user_guilds))
+        .route("/@me/guilds/:guild_id", delete(handlers::guild::leave_guild))
+        .route("/@me/channels", post(handlers::channel::create_dm))
+        .route("/@me/password", post(handlers::auth::change_password))
+        .route("/@me/sessions", get(handlers::auth::list_sessions))
+        .route("/@me/sessions", delete(handlers::auth::revoke_other_sessions))
+        .route("/@me/sessions/:session_id", delete(handlers::auth::revoke_session))
+        .route("/@me/channel-overrides", get(handlers::notification::get_channel_overrides))
+        .route(
+            "/@me/channel-overrides/:channel_id",
+            patch(handlers::notification::update_channel_override),
+        )
+        .route(
+            "/@me/channel-overrides/:channel_id/mute",
+            put(handlers::notification::mute_channel),
+        )
+        .route(
+            "/@me/channel-overrides/:channel_id/mute",
+            delete(handlers::notification::unmute_channel),
+        )
         .route("/:user_id", get(handlers::user::get_user))
+        .route("/:user_id/mutual-guilds", get(handlers::user::get_mutual_guilds))
+        .route("/:user_id/mutual-friends", get(handlers::user::get_mutual_friends))
+        .route("/:user_id/presence", get(handlers::user::get_user_presence))
         .route_layer(middleware::from_fn_with_state(state, auth_middleware))
 }
 
@@ -99,10 +134,42 @@ fn guild_routes(state: AppState) -> Router<AppState> {
         .route("/:guild_id/channels", get(handlers::guild::get_guild_channels))
         .route("/:guild_id/channels", post(handlers::channel::create_channel))
         .route("/:guild_id/members", get(handlers::guild::get_guild_members))
+        .route(
+            "/:guild_id/members/:user_id",
+            get(handlers::guild::get_guild_member),
+        )
+        .route(
+            "/:guild_id/members/:user_id/roles",
+            put(handlers::guild::set_member_roles),
+        )
+        .route(
+            "/:guild_id/roles/:role_id/members",
+            get(handlers::guild::get_role_members),
+        )
+        .route(
+            "/:guild_id/members/:user_id/voice",
+            patch(handlers::voice::modify_member_voice_state),
+        )
         // Invite routes nested under guilds
         .route("/:guild_id/invites", post(handlers::invite::create_invite))
         .route("/:guild_id/invites", get(handlers::invite::list_guild_invites))
         .route_layer(middleware::from_fn_with_state(state, auth_middleware))
+        // Public preview of discoverable guilds; added after the auth layer
+        // so it doesn't apply
+        .route("/:guild_id/preview", get(handlers::guild::get_guild_preview))
+}
+
+/// Attachment upload routes (protected)
+fn attachment_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            post(handlers::attachment::upload_attachment)
+                // Room for the largest file plus the multipart framing
+                .layer(DefaultBodyLimit::max(MAX_ATTACHMENT_SIZE as usize + 64 * 1024)),
+        )
+        .route("/:attachment_id", delete(handlers::attachment::delete_attachment))
+        .route_layer(middleware::from_fn_with_state(state, auth_middleware))
 }
 
 /// Channel routes (protected)
@@ -111,8 +178,31 @@ fn channel_routes(state: AppState) -> Router<AppState> {
         .route("/:channel_id", get(handlers::channel::get_channel))
         .route("/:channel_id", patch(handlers::channel::update_channel))
         .route("/:channel_id", delete(handlers::channel::delete_channel))
+        .route("/:channel_id/clone", post(handlers::channel::clone_channel))
+        .route("/:channel_id/sync", post(handlers::channel::sync_channel))
+        .route("/:channel_id/permissions/:target_id", put(handlers::channel::set_overwrite))
+        .route("/:channel_id/permissions/:target_id", delete(handlers::channel::delete_overwrite))
         .route("/:channel_id/messages", get(handlers::message::get_messages))
-        .route("/:channel_id/messages", post(handlers::message::send_message))
+        // Guild-local actions are also limited per guild; these layers run
+        // inside authentication so the limit is per user
+        .route(
+            "/:channel_id/messages",
+            post(handlers::message::send_message)
+                .layer(middleware::from_fn_with_state(state.clone(), rate_limit_guild)),
+        )
+        .route(
+            "/:channel_id/messages/bulk-delete",
+            post(handlers::message::bulk_delete_messages),
+        )
+        .route("/:channel_id/messages/:message_id/reactions/:emoji", get(handlers::reaction::get_reactors))
+        .route(
+            "/:channel_id/messages/:message_id/reactions/:emoji/@me",
+            put(handlers::reaction::add_reaction)
+                .delete(handlers::reaction::remove_reaction)
+                .layer(middleware::from_fn_with_state(state.clone(), rate_limit_guild)),
+        )
+        .route("/:channel_id/pins/:message_id", put(handlers::message::pin_message))
+        .route("/:channel_id/pins/:message_id", delete(handlers::message::unpin_message))
         .route_layer(middleware::from_fn_with_state(state, auth_middleware))
 }
 
@@ -125,3 +215,15 @@ fn invite_routes(state: AppState) -> Router<AppState> {
         .route("/:code", delete(handlers::invite::delete_invite))
         .route_layer(middleware::from_fn_with_state(state, auth_middleware))
 }
+
+/// Admin routes (protected, admin users only)
+fn admin_routes(state: AppState) -> Router<AppState> {
+    let routes = Router::new()
+        .route("/log-level", post(handlers::admin::set_log_level))
+        .route("/ratelimit/reset", post(handlers::admin::reset_rate_limit));
+
+    limit_json_bodies(routes, state.settings.server.max_body_bytes)
+        // Layers run bottom-up: authenticate first, then check admin access
+        .route_layer(middleware::from_fn_with_state(state.clone(), admin_middleware))
+        .route_layer(middleware::from_fn_with_state(state, auth_middleware))
+}
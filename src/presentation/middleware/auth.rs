@@ -8,9 +8,11 @@ use axum::{
     middleware::Next,
     response::Response,
 };
-use jsonwebtoken::{decode, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
 
+use crate::application::services::auth_service::decode_token;
+use crate::config::AdminSettings;
+use crate::infrastructure::cache::TokenBlacklist;
 use crate::shared::error::AppError;
 use crate::startup::AppState;
 
@@ -23,12 +25,38 @@ pub struct Claims {
     pub exp: i64,
     /// Issued at time (Unix timestamp)
     pub iat: i64,
+    /// Auth session ID the token was issued for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
 }
 
 /// Authenticated user extension
 #[derive(Debug, Clone)]
 pub struct AuthUser {
     pub user_id: i64,
+    /// Auth session ID (`sid` claim), absent for tokens issued without one
+    pub session_id: Option<String>,
+}
+
+/// Check whether the token's auth session has been revoked.
+///
+/// Fails open on Redis errors so a cache outage does not lock every user out.
+pub(crate) async fn is_session_revoked(state: &AppState, session_id: Option<&str>) -> bool {
+    let Some(session_id) = session_id else {
+        return false;
+    };
+
+    let blacklist = TokenBlacklist::new(
+        state.redis.clone(),
+        state.settings.jwt.access_token_ttl_secs(),
+    );
+    match blacklist.is_session_revoked(session_id).await {
+        Ok(revoked) => revoked,
+        Err(e) => {
+            tracing::warn!(session_id = %session_id, error = %e, "Failed to check token blacklist");
+            false
+        }
+    }
 }
 
 /// Authentication middleware that validates JWT tokens
@@ -50,12 +78,7 @@ pub async fn auth_middleware(
         .ok_or_else(|| AppError::Unauthorized("Invalid authorization header format".into()))?;
 
     // Decode and validate JWT
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(state.settings.jwt.secret.as_bytes()),
-        &Validation::default(),
-    )
-    .map_err(|e| match e.kind() {
+    let token_data = decode_token::<Claims>(&state.settings.jwt, token).map_err(|e| match e.kind() {
         jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
             AppError::Unauthorized("Token expired".into())
         }
@@ -69,8 +92,14 @@ pub async fn auth_middleware(
         .parse()
         .map_err(|_| AppError::Unauthorized("Invalid token claims".into()))?;
 
+    // Reject tokens whose session was revoked
+    let session_id = token_data.claims.sid;
+    if is_session_revoked(&state, session_id.as_deref()).await {
+        return Err(AppError::Unauthorized("Session revoked".into()));
+    }
+
     // Insert authenticated user into request extensions
-    request.extensions_mut().insert(AuthUser { user_id });
+    request.extensions_mut().insert(AuthUser { user_id, session_id });
 
     // Continue to the next handler
     Ok(next.run(request).await)
@@ -89,13 +118,14 @@ pub async fn optional_auth_middleware(
         .and_then(|h| h.to_str().ok())
     {
         if let Some(token) = auth_header.strip_prefix("Bearer ") {
-            if let Ok(token_data) = decode::<Claims>(
-                token,
-                &DecodingKey::from_secret(state.settings.jwt.secret.as_bytes()),
-                &Validation::default(),
-            ) {
+            if let Ok(token_data) = decode_token::<Claims>(&state.settings.jwt, token) {
+                let session_id = token_data.claims.sid;
                 if let Ok(user_id) = token_data.claims.sub.parse::<i64>() {
-                    request.extensions_mut().insert(AuthUser { user_id });
+                    if !is_session_revoked(&state, session_id.as_deref()).await {
+                        request
+                            .extensions_mut()
+                            .insert(AuthUser { user_id, session_id });
+                    }
                 }
             }
         }
@@ -103,3 +133,60 @@ pub async fn optional_auth_middleware(
 
     next.run(request).await
 }
+
+/// Admin authorization middleware.
+///
+/// Must run after [`auth_middleware`]; rejects users that are not listed in
+/// the `admin.user_ids` setting.
+pub async fn admin_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    require_admin(&state.settings.admin, request.extensions().get::<AuthUser>())?;
+
+    Ok(next.run(request).await)
+}
+
+/// Check that the authenticated user is a configured admin.
+fn require_admin(admin: &AdminSettings, auth: Option<&AuthUser>) -> Result<(), AppError> {
+    let auth = auth.ok_or_else(|| AppError::Unauthorized("Authentication required".into()))?;
+
+    if !admin.is_admin(auth.user_id) {
+        tracing::warn!(user_id = auth.user_id, "Non-admin user attempted admin access");
+        return Err(AppError::Forbidden("Admin access required".into()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, response::IntoResponse};
+
+    fn user(user_id: i64) -> AuthUser {
+        AuthUser {
+            user_id,
+            session_id: None,
+        }
+    }
+
+    #[test]
+    fn test_non_admin_is_forbidden() {
+        let admin = AdminSettings { user_ids: vec![1] };
+
+        let err = require_admin(&admin, Some(&user(2))).unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::FORBIDDEN);
+
+        let err = require_admin(&admin, None).unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_admin_is_allowed() {
+        let admin = AdminSettings { user_ids: vec![1] };
+
+        assert!(require_admin(&admin, Some(&user(1))).is_ok());
+    }
+}
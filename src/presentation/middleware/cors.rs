@@ -5,7 +5,7 @@
 //! to a restrictive policy (no cross-origin requests allowed) unless explicitly
 //! configured.
 
-use axum::http::{header, Method};
+use axum::http::{header, HeaderName, Method};
 use tower_http::cors::CorsLayer;
 
 use crate::config::CorsSettings;
@@ -51,6 +51,7 @@ pub fn create_cors_layer(settings: &CorsSettings) -> CorsLayer {
                 header::AUTHORIZATION,
                 header::ACCEPT,
                 header::ORIGIN,
+                HeaderName::from_static("x-device-type"),
             ])
             .allow_credentials(true)
             .max_age(std::time::Duration::from_secs(3600)) // 1 hour cache
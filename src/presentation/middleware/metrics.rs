@@ -0,0 +1,83 @@
+//! Request Metrics Middleware
+//!
+//! Times every request and records it in `http_requests_total` and
+//! `http_request_duration_seconds`. Requests are labelled with the route
+//! template they matched (`/channels/:id`), not their concrete path, so each
+//! route has one series however many IDs it is called with. Requests no
+//! route matched share a single `<unmatched>` label.
+
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::infrastructure::metrics::record_http_request;
+
+/// Request metrics middleware
+pub async fn track_http_metrics(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = request.method().clone();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_PATH, |matched| matched.as_str())
+        .to_owned();
+
+    let response = next.run(request).await;
+
+    record_http_request(
+        method.as_str(),
+        &path,
+        response.status().as_u16(),
+        start.elapsed().as_secs_f64(),
+    );
+    response
+}
+
+/// Label for every request no route matched, so scanners probing random
+/// paths can't create new series
+const UNMATCHED_PATH: &str = "<unmatched>";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    use crate::infrastructure::metrics::HTTP_REQUESTS_TOTAL;
+
+    #[tokio::test]
+    async fn test_requests_are_labelled_by_route_template() {
+        let app = Router::new()
+            .route("/channels/{id}", get(|| async { "ok" }))
+            .layer(middleware::from_fn(track_http_metrics));
+        let counter = HTTP_REQUESTS_TOTAL.with_label_values(&["GET", "/channels/{id}", "200"]);
+        let before = counter.get();
+
+        for uri in ["/channels/1", "/channels/2"] {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            app.clone().oneshot(request).await.unwrap();
+        }
+
+        assert_eq!(counter.get() - before, 2);
+    }
+
+    #[tokio::test]
+    async fn test_unmatched_requests_share_one_label() {
+        let app = Router::new()
+            .route("/channels/{id}", get(|| async { "ok" }))
+            .layer(middleware::from_fn(track_http_metrics));
+        let counter = HTTP_REQUESTS_TOTAL.with_label_values(&["GET", UNMATCHED_PATH, "404"]);
+        let before = counter.get();
+
+        for uri in ["/wp-admin/setup.php", "/xk2j9q", "/channels/1/unknown"] {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            app.clone().oneshot(request).await.unwrap();
+        }
+
+        assert_eq!(counter.get() - before, 3);
+    }
+}
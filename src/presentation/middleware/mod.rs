@@ -5,20 +5,30 @@
 pub mod auth;
 pub mod cors;
 pub mod logging;
+pub mod metrics;
 pub mod rate_limit;
+pub mod readiness;
+pub mod request_body;
 pub mod security;
 
-pub use auth::{auth_middleware, optional_auth_middleware, AuthUser};
+pub use auth::{admin_middleware, auth_middleware, optional_auth_middleware, AuthUser};
+pub use metrics::track_http_metrics;
+pub use readiness::readiness_gate;
+pub use request_body::{limit_json_bodies, require_json_body};
 pub use rate_limit::{
     rate_limit_api,
     rate_limit_auth,
     rate_limit_global,
+    rate_limit_guild,
     rate_limit_high_frequency,
     rate_limit_websocket,
+    client_ip,
+    is_valid_identifier,
     ConfigurableRateLimiter,
     EndpointType,
     RateLimitConfig,
     RateLimiter,
+    RateLimitStore,
     RateLimitInfo,
 };
 pub use security::{
@@ -6,17 +6,22 @@
 
 use std::net::IpAddr;
 
+use async_trait::async_trait;
 use axum::{
-    extract::{ConnectInfo, Request, State},
-    http::{header, StatusCode},
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{header, HeaderMap, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
-use redis::aio::ConnectionManager;
 use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
+use crate::config::DynamicSettings;
+use crate::domain::ChannelRepository;
+use crate::infrastructure::cache::RedisPool;
+use crate::infrastructure::repositories::PgChannelRepository;
 use crate::presentation::middleware::auth::AuthUser;
 use crate::shared::error::ErrorResponse;
 use crate::startup::AppState;
@@ -39,6 +44,37 @@ pub struct RateLimitConfig {
     pub burst_allowance: u32,
 }
 
+impl RateLimitConfig {
+    /// Build a one-minute window configuration from the global settings.
+    pub fn from_settings(settings: &crate::config::RateLimitSettings) -> Self {
+        // Convert requests_per_second to requests_per_window
+        let window_seconds = 60u64;
+        let requests_per_window = (settings.requests_per_second * window_seconds as f64) as u32;
+
+        Self {
+            requests_per_window,
+            window_seconds,
+            burst_allowance: settings.burst_size,
+        }
+    }
+
+    /// Requests accepted per window, including the burst allowance.
+    ///
+    /// Only used to decide rejection; clients are told the steady-state
+    /// `requests_per_window` as the limit.
+    pub fn max_requests(&self) -> u32 {
+        self.requests_per_window + self.burst_allowance
+    }
+
+    /// Requests left before reaching the steady-state limit.
+    ///
+    /// Stays at 0 while the burst allowance is being used, so clients pace
+    /// themselves to `requests_per_window`.
+    pub fn remaining(&self, count: u32) -> u32 {
+        self.requests_per_window.saturating_sub(count)
+    }
+}
+
 impl Default for RateLimitConfig {
     fn default() -> Self {
         Self {
@@ -62,6 +98,9 @@ pub enum EndpointType {
     WebSocket,
     /// High-frequency endpoints (typing indicators, presence)
     HighFrequency,
+    /// Guild-local actions (message send, reactions)
+    /// Counted per user and guild so activity in one guild can't starve another
+    Guild,
 }
 
 impl EndpointType {
@@ -72,6 +111,7 @@ impl EndpointType {
     /// - API: Balanced limits for normal usage
     /// - WebSocket: Per-connection limits prevent resource exhaustion
     /// - HighFrequency: Relaxed limits for real-time features
+    /// - Guild: Per-guild limits for actions local to a guild
     pub fn config(&self) -> RateLimitConfig {
         match self {
             EndpointType::Auth => RateLimitConfig {
@@ -94,6 +134,24 @@ impl EndpointType {
                 window_seconds: 60,
                 burst_allowance: 30,
             },
+            EndpointType::Guild => RateLimitConfig {
+                requests_per_window: 30,   // 30 actions per minute in each guild
+                window_seconds: 60,
+                burst_allowance: 10,
+            },
+        }
+    }
+
+    /// Look up an endpoint type by its API name (`auth`, `api`, `websocket`,
+    /// `high_frequency`, `guild`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "auth" => Some(EndpointType::Auth),
+            "api" => Some(EndpointType::Api),
+            "websocket" => Some(EndpointType::WebSocket),
+            "high_frequency" => Some(EndpointType::HighFrequency),
+            "guild" => Some(EndpointType::Guild),
+            _ => None,
         }
     }
 
@@ -104,10 +162,153 @@ impl EndpointType {
             EndpointType::Api => "rl:api",
             EndpointType::WebSocket => "rl:ws",
             EndpointType::HighFrequency => "rl:hf",
+            EndpointType::Guild => "rl:guild",
         }
     }
 }
 
+// ============================================================================
+// Rate Limit Buckets
+// ============================================================================
+
+/// Route parameters that get a separate bucket per value, Discord-style.
+///
+/// Two channels never share a limit on the same route, while other
+/// parameters (e.g. message IDs) do.
+const MAJOR_PARAMETERS: &[&str] = &["channel_id", "guild_id"];
+
+/// Rate limit bucket a request is counted against.
+///
+/// Identified by a stable hash of the HTTP method, the matched route
+/// template (`/channels/:channel_id/messages`, not the concrete URL) and
+/// the value of the route's major parameter, if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitBucket(String);
+
+impl RateLimitBucket {
+    /// Build the bucket for a request matched by `route` with concrete `path`.
+    pub fn new(method: &str, route: &str, path: &str) -> Self {
+        Self::hash(method, route, major_parameter(route, path))
+    }
+
+    /// Build the bucket for a route regardless of its major parameter.
+    ///
+    /// Used by guild-scoped limits, where the guild already separates the
+    /// counters and every channel in a guild shares one budget.
+    pub fn for_route(method: &str, route: &str) -> Self {
+        Self::hash(method, route, None)
+    }
+
+    fn hash(method: &str, route: &str, major: Option<&str>) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(method.as_bytes());
+        hasher.update(b" ");
+        hasher.update(route.as_bytes());
+        if let Some(major) = major {
+            hasher.update(b"#");
+            hasher.update(major.as_bytes());
+        }
+
+        let digest = hasher.finalize();
+        Self(digest[..8].iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    /// Build the bucket for a request, using its matched route when available.
+    fn from_request(request: &Request) -> Self {
+        let path = request.uri().path();
+        Self::new(request.method().as_str(), matched_route(request), path)
+    }
+
+    /// The bucket identifier sent in `X-RateLimit-Bucket`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The route template a request was matched by, or its path if unmatched.
+fn matched_route(request: &Request) -> &str {
+    request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str)
+        .unwrap_or(request.uri().path())
+}
+
+/// Find the value of the first major parameter in a concrete path.
+fn major_parameter<'a>(route: &str, path: &'a str) -> Option<&'a str> {
+    named_major_parameter(route, path).map(|(_, value)| value)
+}
+
+/// Find the name and value of the first major parameter in a concrete path.
+///
+/// Accepts both `:name` and `{name}` parameter syntax in the route.
+fn named_major_parameter<'r, 'p>(route: &'r str, path: &'p str) -> Option<(&'r str, &'p str)> {
+    route
+        .split('/')
+        .zip(path.split('/'))
+        .find_map(|(segment, value)| {
+            let name = segment
+                .strip_prefix(':')
+                .or_else(|| segment.strip_prefix('{')?.strip_suffix('}'))?;
+            MAJOR_PARAMETERS.contains(&name).then_some((name, value))
+        })
+}
+
+/// What a guild-scoped request's major parameter points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GuildTarget {
+    /// The route names the guild directly
+    Guild(i64),
+    /// The route names a channel, whose guild has to be looked up
+    Channel(i64),
+}
+
+/// Find the guild or channel a request acts in from its major parameter.
+fn guild_target(route: &str, path: &str) -> Option<GuildTarget> {
+    let (name, value) = named_major_parameter(route, path)?;
+    let id = value.parse::<i64>().ok()?;
+
+    match name {
+        "guild_id" => Some(GuildTarget::Guild(id)),
+        "channel_id" => Some(GuildTarget::Channel(id)),
+        _ => None,
+    }
+}
+
+/// Counter key for a guild-scoped limit.
+///
+/// The guild goes before the client identifier so an admin reset of
+/// `user:{id}` (a `{prefix}:*:user:{id}` scan) clears every guild at once.
+/// Requests outside any guild (DM channels) are keyed by bucket alone.
+fn guild_scoped_key(bucket: &RateLimitBucket, guild_id: Option<i64>, identifier: &str) -> String {
+    match guild_id {
+        Some(guild_id) => format!("{}:guild:{}:{}", bucket.as_str(), guild_id, identifier),
+        None => format!("{}:{}", bucket.as_str(), identifier),
+    }
+}
+
+/// Check that a limiter identifier is well-formed (`user:{id}` or `ip:{addr}`).
+///
+/// Identifiers end up in a `SCAN MATCH` pattern on reset, so anything else
+/// (including glob characters) is rejected.
+pub fn is_valid_identifier(identifier: &str) -> bool {
+    if let Some(id) = identifier.strip_prefix("user:") {
+        return id.parse::<i64>().is_ok();
+    }
+    identifier
+        .strip_prefix("ip:")
+        .is_some_and(|ip| ip == "unknown" || ip.parse::<IpAddr>().is_ok())
+}
+
+/// Scope reported in `X-RateLimit-Scope` for a limiter identifier.
+fn identifier_scope(identifier: &str) -> &'static str {
+    if identifier.starts_with("user:") {
+        "user"
+    } else {
+        "ip"
+    }
+}
+
 // ============================================================================
 // Rate Limit Response
 // ============================================================================
@@ -115,9 +316,9 @@ impl EndpointType {
 /// Information about rate limit status returned to clients.
 #[derive(Debug, Serialize)]
 pub struct RateLimitInfo {
-    /// Maximum requests allowed in the current window
+    /// Steady-state requests allowed per window, excluding burst allowance
     pub limit: u32,
-    /// Remaining requests in the current window
+    /// Remaining requests before the steady-state limit is reached
     pub remaining: u32,
     /// Unix timestamp when the rate limit resets
     pub reset_at: i64,
@@ -160,46 +361,55 @@ struct RateLimitExceededResponse {
 /// - Atomic: Uses Redis transactions to prevent race conditions
 /// - Tamper-resistant: Keys are server-side only
 #[derive(Clone)]
-pub struct RateLimiter {
-    redis: ConnectionManager,
+pub struct RateLimiter<S: RateLimitStore = RedisPool> {
+    redis: S,
     config: RateLimitConfig,
     endpoint_type: EndpointType,
 }
 
-impl RateLimiter {
-    /// Create a new rate limiter instance.
-    pub fn new(redis: ConnectionManager, endpoint_type: EndpointType) -> Self {
-        Self {
-            redis,
-            config: endpoint_type.config(),
-            endpoint_type,
-        }
-    }
-
-    /// Create a rate limiter with custom configuration.
-    pub fn with_config(
-        redis: ConnectionManager,
-        endpoint_type: EndpointType,
-        config: RateLimitConfig,
-    ) -> Self {
-        Self {
-            redis,
-            config,
-            endpoint_type,
-        }
-    }
+/// Outcome of recording a request in a sliding window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowHit {
+    /// Whether the request fit in the window
+    pub allowed: bool,
+    /// Requests in the window, including this one if allowed
+    pub count: u32,
+    /// Milliseconds until the oldest request leaves the window (rejections only)
+    pub retry_after_ms: i64,
+}
 
-    /// Check if a request should be allowed.
-    ///
-    /// Returns `Ok(RateLimitInfo)` if allowed, `Err(RateLimitInfo)` if rate limited.
-    pub async fn check(&self, identifier: &str) -> Result<RateLimitInfo, RateLimitInfo> {
-        let key = format!("{}:{}", self.endpoint_type.key_prefix(), identifier);
-        let now_ms = chrono::Utc::now().timestamp_millis();
-        let window_ms = (self.config.window_seconds * 1000) as i64;
-        let window_start = now_ms - window_ms;
-        let max_requests = self.config.requests_per_window + self.config.burst_allowance;
+/// Storage for sliding-window request counters.
+///
+/// Implemented by the Redis connection for production use; the trait lets
+/// the limiting logic run against an in-process store in tests.
+#[async_trait]
+pub trait RateLimitStore: Clone + Send + Sync {
+    /// Record a request at `now_ms` under `key`, unless `max_requests`
+    /// already fall within the last `window_seconds`.
+    async fn hit(
+        &self,
+        key: &str,
+        now_ms: i64,
+        window_seconds: u64,
+        max_requests: u32,
+    ) -> Result<WindowHit, redis::RedisError>;
+
+    /// Delete `{prefix}:{identifier}` and every bucketed
+    /// `{prefix}:*:{identifier}` key, returning how many existed.
+    async fn clear(&self, prefix: &str, identifier: &str) -> Result<u64, redis::RedisError>;
+}
 
-        let mut conn = self.redis.clone();
+#[async_trait]
+impl RateLimitStore for RedisPool {
+    async fn hit(
+        &self,
+        key: &str,
+        now_ms: i64,
+        window_seconds: u64,
+        max_requests: u32,
+    ) -> Result<WindowHit, redis::RedisError> {
+        let window_start = now_ms - (window_seconds * 1000) as i64;
+        let mut conn = self.clone();
 
         // Execute rate limiting logic atomically using a Lua script
         // This ensures consistency even under high concurrency
@@ -237,58 +447,137 @@ impl RateLimiter {
         );
 
         let result: Vec<i64> = script
-            .key(&key)
+            .key(key)
             .arg(now_ms)
             .arg(window_start)
             .arg(max_requests as i64)
-            .arg(self.config.window_seconds as i64)
+            .arg(window_seconds as i64)
             .invoke_async(&mut conn)
+            .await?;
+
+        Ok(WindowHit {
+            allowed: result[0] == 1,
+            count: result[1] as u32,
+            retry_after_ms: result.get(3).copied().unwrap_or(0),
+        })
+    }
+
+    async fn clear(&self, prefix: &str, identifier: &str) -> Result<u64, redis::RedisError> {
+        let pattern = format!("{}:*:{}", prefix, identifier);
+        let mut conn = self.clone();
+
+        let mut keys = vec![format!("{}:{}", prefix, identifier)];
+        let mut cursor: u64 = 0;
+        loop {
+            let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(500)
+                .query_async(&mut conn)
+                .await?;
+
+            keys.extend(batch);
+
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        keys.sort_unstable();
+        keys.dedup();
+        conn.del(&keys).await
+    }
+}
+
+impl<S: RateLimitStore> RateLimiter<S> {
+    /// Create a new rate limiter instance.
+    pub fn new(redis: S, endpoint_type: EndpointType) -> Self {
+        Self {
+            redis,
+            config: endpoint_type.config(),
+            endpoint_type,
+        }
+    }
+
+    /// Create a rate limiter with custom configuration.
+    pub fn with_config(
+        redis: S,
+        endpoint_type: EndpointType,
+        config: RateLimitConfig,
+    ) -> Self {
+        Self {
+            redis,
+            config,
+            endpoint_type,
+        }
+    }
+
+    /// Check if a request should be allowed.
+    ///
+    /// Returns `Ok(RateLimitInfo)` if allowed, `Err(RateLimitInfo)` if rate limited.
+    pub async fn check(&self, identifier: &str) -> Result<RateLimitInfo, RateLimitInfo> {
+        let key = format!("{}:{}", self.endpoint_type.key_prefix(), identifier);
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let reset_at = (now_ms / 1000) + self.config.window_seconds as i64;
+
+        let hit = self
+            .redis
+            .hit(&key, now_ms, self.config.window_seconds, self.config.max_requests())
             .await
             .map_err(|e| {
                 tracing::error!("Rate limiter Redis error: {}", e);
                 // On Redis error, allow the request but log it
                 // This prevents Redis issues from causing complete service denial
                 RateLimitInfo {
-                    limit: max_requests,
+                    limit: self.config.requests_per_window,
                     remaining: 1,
-                    reset_at: (now_ms / 1000) + self.config.window_seconds as i64,
+                    reset_at,
                     retry_after: 0,
                 }
             })?;
 
-        let allowed = result[0] == 1;
-        let current_count = result[1] as u32;
-        let remaining = max_requests.saturating_sub(current_count);
-        let reset_at = (now_ms / 1000) + self.config.window_seconds as i64;
-
         let info = RateLimitInfo {
-            limit: max_requests,
-            remaining,
+            limit: self.config.requests_per_window,
+            remaining: self.config.remaining(hit.count),
             reset_at,
-            retry_after: if allowed {
+            retry_after: if hit.allowed {
                 0
             } else {
                 // Calculate retry_after in seconds
-                let retry_ms = result.get(3).copied().unwrap_or(0);
-                ((retry_ms as f64) / 1000.0).ceil() as u64
+                ((hit.retry_after_ms as f64) / 1000.0).ceil() as u64
             },
         };
 
-        if allowed {
+        if hit.allowed {
             Ok(info)
         } else {
             Err(info)
         }
     }
 
+    /// Reset the rate limit for an identifier (admin use only).
+    ///
+    /// Clears the identifier's counters in every bucket and returns how many
+    /// keys were removed. Callers must check the identifier with
+    /// [`is_valid_identifier`] first, as it is used in a `SCAN` pattern.
+    ///
+    /// # Security Warning
+    /// This should only be exposed to admin endpoints with proper authorization.
+    pub async fn reset(&self, identifier: &str) -> Result<u64, redis::RedisError> {
+        self.redis.clear(self.endpoint_type.key_prefix(), identifier).await
+    }
+}
+
+impl RateLimiter {
     /// Get the current rate limit status without consuming a request.
     pub async fn status(&self, identifier: &str) -> Result<RateLimitInfo, redis::RedisError> {
         let key = format!("{}:{}", self.endpoint_type.key_prefix(), identifier);
         let now_ms = chrono::Utc::now().timestamp_millis();
         let window_ms = (self.config.window_seconds * 1000) as i64;
         let window_start = now_ms - window_ms;
-        let max_requests = self.config.requests_per_window + self.config.burst_allowance;
-
         let mut conn = self.redis.clone();
 
         // Remove old entries and count
@@ -296,23 +585,13 @@ impl RateLimiter {
         let count: u32 = conn.zcard(&key).await?;
 
         Ok(RateLimitInfo {
-            limit: max_requests,
-            remaining: max_requests.saturating_sub(count),
+            limit: self.config.requests_per_window,
+            remaining: self.config.remaining(count),
             reset_at: (now_ms / 1000) + self.config.window_seconds as i64,
             retry_after: 0,
         })
     }
 
-    /// Reset the rate limit for an identifier (admin use only).
-    ///
-    /// # Security Warning
-    /// This should only be exposed to admin endpoints with proper authorization.
-    pub async fn reset(&self, identifier: &str) -> Result<(), redis::RedisError> {
-        let key = format!("{}:{}", self.endpoint_type.key_prefix(), identifier);
-        let mut conn = self.redis.clone();
-        let _: () = conn.del(&key).await?;
-        Ok(())
-    }
 }
 
 // ============================================================================
@@ -323,49 +602,20 @@ impl RateLimiter {
 ///
 /// Priority:
 /// 1. Authenticated user ID (most accurate, prevents account sharing abuse)
-/// 2. X-Forwarded-For header (for reverse proxy setups)
-/// 3. Client IP address (fallback)
+/// 2. Client IP address, see [`client_ip`]
 ///
 /// # Security Considerations
 ///
 /// - User ID is preferred as it cannot be spoofed
-/// - X-Forwarded-For should only be trusted from known proxies
+/// - Forwarding headers are only trusted from configured proxies
 /// - IP-based limiting can be bypassed with VPNs but provides baseline protection
-fn extract_identifier(request: &Request, client_ip: Option<IpAddr>) -> String {
+fn extract_identifier(request: &Request, connection_ip: Option<IpAddr>, trusted_proxies: &[IpAddr]) -> String {
     // Check for authenticated user first
     if let Some(auth_user) = request.extensions().get::<AuthUser>() {
         return format!("user:{}", auth_user.user_id);
     }
 
-    // Try X-Forwarded-For header (first IP in chain is original client)
-    // Note: This header can be spoofed if not behind a trusted proxy
-    if let Some(forwarded_for) = request
-        .headers()
-        .get("x-forwarded-for")
-        .and_then(|h| h.to_str().ok())
-    {
-        if let Some(first_ip) = forwarded_for.split(',').next() {
-            let ip = first_ip.trim();
-            // Basic validation - ensure it looks like an IP
-            if ip.parse::<IpAddr>().is_ok() {
-                return format!("ip:{}", ip);
-            }
-        }
-    }
-
-    // Try X-Real-IP header (common with nginx)
-    if let Some(real_ip) = request
-        .headers()
-        .get("x-real-ip")
-        .and_then(|h| h.to_str().ok())
-    {
-        if real_ip.parse::<IpAddr>().is_ok() {
-            return format!("ip:{}", real_ip);
-        }
-    }
-
-    // Fall back to connection IP
-    match client_ip {
+    match client_ip(request.headers(), connection_ip, trusted_proxies) {
         Some(ip) => format!("ip:{}", ip),
         None => {
             // Last resort - use a hash of headers to create some uniqueness
@@ -376,6 +626,43 @@ fn extract_identifier(request: &Request, client_ip: Option<IpAddr>) -> String {
     }
 }
 
+/// Determine the client IP address of a request.
+///
+/// Forwarding headers are only honoured when the connection comes from one
+/// of `trusted_proxies`; otherwise anyone could claim any address. Then
+/// `X-Forwarded-For` is read from the right, skipping trusted proxies, so
+/// an address the client prepended itself is never used, with `X-Real-IP`
+/// as the fallback. Without a trusted proxy the connection IP is used.
+pub fn client_ip(headers: &HeaderMap, connection_ip: Option<IpAddr>, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+    let peer = connection_ip?;
+    if !trusted_proxies.contains(&peer) {
+        return Some(peer);
+    }
+
+    let hops: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(','))
+        .map(str::trim)
+        .collect();
+    for hop in hops.iter().rev() {
+        match hop.parse::<IpAddr>() {
+            Ok(ip) if trusted_proxies.contains(&ip) => continue,
+            Ok(ip) => return Some(ip),
+            // A malformed hop ends the part of the chain that can be trusted
+            Err(_) => break,
+        }
+    }
+
+    let real_ip = headers
+        .get("x-real-ip")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|ip| ip.trim().parse().ok());
+
+    Some(real_ip.unwrap_or(peer))
+}
+
 // ============================================================================
 // Middleware Functions
 // ============================================================================
@@ -388,7 +675,7 @@ fn extract_identifier(request: &Request, client_ip: Option<IpAddr>) -> String {
 /// - Account enumeration
 pub async fn rate_limit_auth(
     State(state): State<AppState>,
-    connect_info: Option<ConnectInfo<std::net::SocketAddr>>,
+    connect_info: ConnectInfo<std::net::SocketAddr>,
     request: Request,
     next: Next,
 ) -> Response {
@@ -396,9 +683,12 @@ pub async fn rate_limit_auth(
 }
 
 /// Rate limiting middleware for standard API endpoints.
+///
+/// Limits come from the hot-reloadable `rate_limit` settings, so a
+/// configuration reload takes effect on the next request.
 pub async fn rate_limit_api(
     State(state): State<AppState>,
-    connect_info: Option<ConnectInfo<std::net::SocketAddr>>,
+    connect_info: ConnectInfo<std::net::SocketAddr>,
     request: Request,
     next: Next,
 ) -> Response {
@@ -408,7 +698,7 @@ pub async fn rate_limit_api(
 /// Rate limiting middleware for WebSocket connections.
 pub async fn rate_limit_websocket(
     State(state): State<AppState>,
-    connect_info: Option<ConnectInfo<std::net::SocketAddr>>,
+    connect_info: ConnectInfo<std::net::SocketAddr>,
     request: Request,
     next: Next,
 ) -> Response {
@@ -418,50 +708,136 @@ pub async fn rate_limit_websocket(
 /// Rate limiting middleware for high-frequency endpoints.
 pub async fn rate_limit_high_frequency(
     State(state): State<AppState>,
-    connect_info: Option<ConnectInfo<std::net::SocketAddr>>,
+    connect_info: ConnectInfo<std::net::SocketAddr>,
     request: Request,
     next: Next,
 ) -> Response {
     rate_limit_inner(state, connect_info, request, next, EndpointType::HighFrequency).await
 }
 
+/// Rate limiting middleware for guild-local actions (message send, reactions).
+///
+/// Counts per `(user, guild)`, so a user hammering one guild keeps their full
+/// budget everywhere else. The guild comes from the route's major parameter;
+/// for channel routes it is the channel's guild. Must run after
+/// authentication so requests are counted per user rather than per IP.
+pub async fn rate_limit_guild(
+    State(state): State<AppState>,
+    connect_info: ConnectInfo<std::net::SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let client_ip = Some(connect_info.0.ip());
+    let identifier = extract_identifier(&request, client_ip, &state.settings.server.trusted_proxies);
+    let route = matched_route(&request);
+    let scope = identifier_scope(&identifier);
+
+    let guild_id = match guild_target(route, request.uri().path()) {
+        Some(GuildTarget::Guild(guild_id)) => Some(guild_id),
+        Some(GuildTarget::Channel(channel_id)) => channel_guild_id(&state, channel_id).await,
+        None => None,
+    };
+
+    // Within a guild every channel shares the budget; outside one (DMs) the
+    // usual per-channel bucket applies
+    let bucket = match guild_id {
+        Some(_) => RateLimitBucket::for_route(request.method().as_str(), route),
+        None => RateLimitBucket::from_request(&request),
+    };
+
+    let limiter = RateLimiter::new(state.redis.clone(), EndpointType::Guild);
+
+    match limiter.check(&guild_scoped_key(&bucket, guild_id, &identifier)).await {
+        Ok(info) => {
+            let mut response = next.run(request).await;
+            add_rate_limit_headers(response.headers_mut(), &info, scope, Some(&bucket));
+            response
+        }
+        Err(info) => {
+            tracing::warn!(
+                identifier = %identifier,
+                bucket = %bucket.as_str(),
+                guild_id = ?guild_id,
+                "Guild rate limit exceeded"
+            );
+            create_rate_limit_response(info, scope, Some(&bucket))
+        }
+    }
+}
+
+/// Guild a channel belongs to, or `None` for DM channels and unknown channels.
+///
+/// Lookup failures are logged and treated as "no guild": the request is then
+/// limited per channel route, and the handler reports the actual error.
+async fn channel_guild_id(state: &AppState, channel_id: i64) -> Option<i64> {
+    match PgChannelRepository::new(state.db.clone()).find_by_id(channel_id).await {
+        Ok(channel) => channel.and_then(|channel| channel.server_id),
+        Err(e) => {
+            tracing::warn!(channel_id, error = %e, "Failed to resolve channel guild for rate limiting");
+            None
+        }
+    }
+}
+
 /// Internal rate limiting implementation.
 async fn rate_limit_inner(
     state: AppState,
-    connect_info: Option<ConnectInfo<std::net::SocketAddr>>,
+    connect_info: ConnectInfo<std::net::SocketAddr>,
     request: Request,
     next: Next,
     endpoint_type: EndpointType,
 ) -> Response {
-    let client_ip = connect_info.map(|ci| ci.0.ip());
-    let identifier = extract_identifier(&request, client_ip);
+    let client_ip = Some(connect_info.0.ip());
+    let identifier = extract_identifier(&request, client_ip, &state.settings.server.trusted_proxies);
+    let bucket = RateLimitBucket::from_request(&request);
+    let scope = identifier_scope(&identifier);
 
-    let limiter = RateLimiter::new(state.redis.clone(), endpoint_type);
+    let config = endpoint_config(endpoint_type, &state.dynamic.load());
+    let limiter = RateLimiter::with_config(state.redis.clone(), endpoint_type, config);
 
-    match limiter.check(&identifier).await {
+    // Count per bucket so each route (and channel/guild) has its own limit
+    match limiter.check(&format!("{}:{}", bucket.as_str(), identifier)).await {
         Ok(info) => {
             // Request allowed - add rate limit headers and continue
             let mut response = next.run(request).await;
-            add_rate_limit_headers(response.headers_mut(), &info);
+            add_rate_limit_headers(response.headers_mut(), &info, scope, Some(&bucket));
             response
         }
         Err(info) => {
             // Rate limited - return 429 response
             tracing::warn!(
                 identifier = %identifier,
+                bucket = %bucket.as_str(),
                 endpoint_type = ?endpoint_type,
                 "Rate limit exceeded"
             );
-            create_rate_limit_response(info)
+            create_rate_limit_response(info, scope, Some(&bucket))
         }
     }
 }
 
+/// Limits for an endpoint type under the current dynamic settings.
+///
+/// General API traffic follows the configured `rate_limit` values; the
+/// other endpoint types keep their fixed limits.
+fn endpoint_config(endpoint_type: EndpointType, dynamic: &DynamicSettings) -> RateLimitConfig {
+    match endpoint_type {
+        EndpointType::Api => RateLimitConfig::from_settings(&dynamic.rate_limit),
+        _ => endpoint_type.config(),
+    }
+}
+
 /// Add rate limit headers to a response.
 ///
 /// Headers follow the IETF draft standard for rate limiting:
 /// https://datatracker.ietf.org/doc/draft-ietf-httpapi-ratelimit-headers/
-fn add_rate_limit_headers(headers: &mut header::HeaderMap, info: &RateLimitInfo) {
+/// plus Discord's `X-RateLimit-Bucket` and `X-RateLimit-Scope`.
+fn add_rate_limit_headers(
+    headers: &mut header::HeaderMap,
+    info: &RateLimitInfo,
+    scope: &'static str,
+    bucket: Option<&RateLimitBucket>,
+) {
     // Standard rate limit headers
     if let Ok(v) = header::HeaderValue::from_str(&info.limit.to_string()) {
         headers.insert("X-RateLimit-Limit", v);
@@ -472,10 +848,20 @@ fn add_rate_limit_headers(headers: &mut header::HeaderMap, info: &RateLimitInfo)
     if let Ok(v) = header::HeaderValue::from_str(&info.reset_at.to_string()) {
         headers.insert("X-RateLimit-Reset", v);
     }
+    if let Some(bucket) = bucket {
+        if let Ok(v) = header::HeaderValue::from_str(bucket.as_str()) {
+            headers.insert("X-RateLimit-Bucket", v);
+        }
+    }
+    headers.insert("X-RateLimit-Scope", header::HeaderValue::from_static(scope));
 }
 
 /// Create a 429 Too Many Requests response.
-fn create_rate_limit_response(info: RateLimitInfo) -> Response {
+fn create_rate_limit_response(
+    info: RateLimitInfo,
+    scope: &'static str,
+    bucket: Option<&RateLimitBucket>,
+) -> Response {
     let body = RateLimitExceededResponse {
         error: ErrorResponse {
             code: 10006,
@@ -506,6 +892,8 @@ fn create_rate_limit_response(info: RateLimitInfo) -> Response {
             reset_at: info.reset_at,
             retry_after: info.retry_after,
         },
+        scope,
+        bucket,
     );
 
     response
@@ -521,14 +909,14 @@ fn create_rate_limit_response(info: RateLimitInfo) -> Response {
 /// configuration rather than using the predefined endpoint type defaults.
 #[derive(Clone)]
 pub struct ConfigurableRateLimiter {
-    redis: ConnectionManager,
+    redis: RedisPool,
     key_prefix: String,
     config: RateLimitConfig,
 }
 
 impl ConfigurableRateLimiter {
     /// Create a new configurable rate limiter.
-    pub fn new(redis: ConnectionManager, key_prefix: impl Into<String>, config: RateLimitConfig) -> Self {
+    pub fn new(redis: RedisPool, key_prefix: impl Into<String>, config: RateLimitConfig) -> Self {
         Self {
             redis,
             key_prefix: key_prefix.into(),
@@ -540,21 +928,13 @@ impl ConfigurableRateLimiter {
     ///
     /// Uses the global rate limit settings from configuration.
     pub fn from_settings(
-        redis: ConnectionManager,
+        redis: RedisPool,
         settings: &crate::config::RateLimitSettings,
     ) -> Self {
-        // Convert requests_per_second to requests_per_window
-        let window_seconds = 60u64;
-        let requests_per_window = (settings.requests_per_second * window_seconds as f64) as u32;
-
         Self {
             redis,
             key_prefix: "rl:global".to_string(),
-            config: RateLimitConfig {
-                requests_per_window,
-                window_seconds,
-                burst_allowance: settings.burst_size,
-            },
+            config: RateLimitConfig::from_settings(settings),
         }
     }
 
@@ -564,7 +944,7 @@ impl ConfigurableRateLimiter {
         let now_ms = chrono::Utc::now().timestamp_millis();
         let window_ms = (self.config.window_seconds * 1000) as i64;
         let window_start = now_ms - window_ms;
-        let max_requests = self.config.requests_per_window + self.config.burst_allowance;
+        let max_requests = self.config.max_requests();
 
         let mut conn = self.redis.clone();
 
@@ -606,7 +986,7 @@ impl ConfigurableRateLimiter {
             .map_err(|e| {
                 tracing::error!("Rate limiter Redis error: {}", e);
                 RateLimitInfo {
-                    limit: max_requests,
+                    limit: self.config.requests_per_window,
                     remaining: 1,
                     reset_at: (now_ms / 1000) + self.config.window_seconds as i64,
                     retry_after: 0,
@@ -615,12 +995,11 @@ impl ConfigurableRateLimiter {
 
         let allowed = result[0] == 1;
         let current_count = result[1] as u32;
-        let remaining = max_requests.saturating_sub(current_count);
         let reset_at = (now_ms / 1000) + self.config.window_seconds as i64;
 
         let info = RateLimitInfo {
-            limit: max_requests,
-            remaining,
+            limit: self.config.requests_per_window,
+            remaining: self.config.remaining(current_count),
             reset_at,
             retry_after: if allowed {
                 0
@@ -639,24 +1018,27 @@ impl ConfigurableRateLimiter {
 }
 
 /// Global rate limiting middleware using application settings.
+///
+/// Limits are read from the hot-reloadable settings on every request, so a
+/// configuration reload takes effect without a restart.
 pub async fn rate_limit_global(
     State(state): State<AppState>,
-    connect_info: Option<ConnectInfo<std::net::SocketAddr>>,
+    connect_info: ConnectInfo<std::net::SocketAddr>,
     request: Request,
     next: Next,
 ) -> Response {
-    let client_ip = connect_info.map(|ci| ci.0.ip());
-    let identifier = extract_identifier(&request, client_ip);
+    let client_ip = Some(connect_info.0.ip());
+    let identifier = extract_identifier(&request, client_ip, &state.settings.server.trusted_proxies);
 
     let limiter = ConfigurableRateLimiter::from_settings(
         state.redis.clone(),
-        &state.settings.rate_limit,
+        &state.dynamic.load().rate_limit,
     );
 
     match limiter.check(&identifier).await {
         Ok(info) => {
             let mut response = next.run(request).await;
-            add_rate_limit_headers(response.headers_mut(), &info);
+            add_rate_limit_headers(response.headers_mut(), &info, "global", None);
             response
         }
         Err(info) => {
@@ -664,7 +1046,7 @@ pub async fn rate_limit_global(
                 identifier = %identifier,
                 "Global rate limit exceeded"
             );
-            create_rate_limit_response(info)
+            create_rate_limit_response(info, "global", None)
         }
     }
 }
@@ -673,8 +1055,15 @@ pub async fn rate_limit_global(
 // Tests
 // ============================================================================
 
+#[cfg(test)]
+pub(crate) mod test_store;
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use super::test_store::MemoryStore;
     use super::*;
 
     #[test]
@@ -694,6 +1083,148 @@ mod tests {
         assert_eq!(config.burst_allowance, 10);
     }
 
+    #[test]
+    fn test_remaining_counts_down_to_steady_state_limit() {
+        let config = RateLimitConfig {
+            requests_per_window: 5,
+            window_seconds: 60,
+            burst_allowance: 2,
+        };
+
+        assert_eq!(config.max_requests(), 7);
+        assert_eq!(config.remaining(0), 5);
+        assert_eq!(config.remaining(1), 4);
+        assert_eq!(config.remaining(5), 0);
+    }
+
+    #[tokio::test]
+    async fn test_remaining_stays_zero_while_burst_is_used() {
+        let config = RateLimitConfig {
+            requests_per_window: 5,
+            window_seconds: 60,
+            burst_allowance: 2,
+        };
+        let limiter = RateLimiter::with_config(MemoryStore::default(), EndpointType::Api, config);
+
+        let mut reported = Vec::new();
+        for _ in 0..5 {
+            let info = limiter.check("user:1").await.unwrap();
+            assert_eq!(info.limit, 5);
+            reported.push(info.remaining);
+        }
+        assert_eq!(reported, vec![4, 3, 2, 1, 0]);
+
+        // Requests 6 and 7 are still accepted, but report no headroom
+        for _ in 0..2 {
+            let info = limiter.check("user:1").await.unwrap();
+            assert_eq!((info.limit, info.remaining), (5, 0));
+        }
+
+        let rejected = limiter.check("user:1").await.unwrap_err();
+        assert_eq!((rejected.limit, rejected.remaining), (5, 0));
+        assert!(rejected.retry_after > 0);
+    }
+
+    fn dynamic_settings(requests_per_second: f64, burst_size: u32) -> DynamicSettings {
+        DynamicSettings {
+            rate_limit: crate::config::RateLimitSettings {
+                requests_per_second,
+                burst_size,
+                mention_cooldown_secs: 5,
+            },
+            log: crate::config::LogSettings {
+                filter: "info".to_string(),
+                format: None,
+            },
+            features: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reload_changes_enforced_api_limit() {
+        let store = MemoryStore::default();
+        let dynamic = dynamic_settings(0.05, 0).into_shared();
+        // Built per request, as `rate_limit_inner` does
+        let limiter = |dynamic: &crate::config::SharedDynamicSettings| {
+            let config = endpoint_config(EndpointType::Api, &dynamic.load());
+            RateLimiter::with_config(store.clone(), EndpointType::Api, config)
+        };
+
+        // Three requests per minute: the first two fit
+        for _ in 0..2 {
+            assert!(limiter(&dynamic).check("user:1").await.is_ok());
+        }
+
+        // Reload down to a single request per minute
+        dynamic.store(Arc::new(dynamic_settings(0.0, 1)));
+
+        let rejected = limiter(&dynamic).check("user:1").await.unwrap_err();
+        assert_eq!(rejected.limit, 0);
+        assert!(rejected.retry_after > 0);
+    }
+
+    #[test]
+    fn test_only_api_follows_dynamic_settings() {
+        let dynamic = dynamic_settings(0.5, 3);
+
+        let api = endpoint_config(EndpointType::Api, &dynamic);
+        assert_eq!(api.requests_per_window, 30);
+        assert_eq!(api.burst_allowance, 3);
+
+        let auth = endpoint_config(EndpointType::Auth, &dynamic);
+        assert_eq!(auth.requests_per_window, EndpointType::Auth.config().requests_per_window);
+    }
+
+    #[test]
+    fn test_endpoint_type_from_name() {
+        assert_eq!(EndpointType::from_name("api"), Some(EndpointType::Api));
+        assert_eq!(EndpointType::from_name("high_frequency"), Some(EndpointType::HighFrequency));
+        assert_eq!(EndpointType::from_name("guild"), Some(EndpointType::Guild));
+        assert_eq!(EndpointType::from_name("API"), None);
+    }
+
+    fn forwarded(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", value.parse().unwrap());
+        headers.insert("x-real-ip", "198.51.100.9".parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_forwarding_headers_are_ignored_from_untrusted_peers() {
+        let peer: IpAddr = "203.0.113.5".parse().unwrap();
+        let proxies: Vec<IpAddr> = vec!["10.0.0.1".parse().unwrap()];
+
+        assert_eq!(client_ip(&forwarded("1.2.3.4"), Some(peer), &proxies), Some(peer));
+        assert_eq!(client_ip(&forwarded("1.2.3.4"), Some(peer), &[]), Some(peer));
+        assert_eq!(client_ip(&forwarded("1.2.3.4"), None, &proxies), None);
+    }
+
+    #[test]
+    fn test_forwarded_client_is_the_last_untrusted_hop() {
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        let proxies = vec![proxy, "10.0.0.2".parse().unwrap()];
+
+        // The client prepended a forged address; the proxies appended theirs
+        let ip = client_ip(&forwarded("6.6.6.6, 1.2.3.4, 10.0.0.2"), Some(proxy), &proxies);
+        assert_eq!(ip, Some("1.2.3.4".parse().unwrap()));
+
+        // Without a usable X-Forwarded-For, X-Real-IP from the proxy is used
+        let ip = client_ip(&forwarded("garbage"), Some(proxy), &proxies);
+        assert_eq!(ip, Some("198.51.100.9".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_valid_identifiers() {
+        assert!(is_valid_identifier("user:12345"));
+        assert!(is_valid_identifier("ip:192.168.1.1"));
+        assert!(is_valid_identifier("ip:::1"));
+
+        assert!(!is_valid_identifier("user:*"));
+        assert!(!is_valid_identifier("ip:10.0.0.*"));
+        assert!(!is_valid_identifier("12345"));
+    }
+
     #[test]
     fn test_identifier_format() {
         // User identifiers should be prefixed
@@ -704,4 +1235,102 @@ mod tests {
         let ip_id = "ip:192.168.1.1";
         assert!(ip_id.starts_with("ip:"));
     }
+
+    #[test]
+    fn test_bucket_stable_across_non_major_ids() {
+        let route = "/api/v1/channels/:channel_id/pins/:message_id";
+
+        let first = RateLimitBucket::new("PUT", route, "/api/v1/channels/10/pins/111");
+        let second = RateLimitBucket::new("PUT", route, "/api/v1/channels/10/pins/222");
+        assert_eq!(first, second);
+        assert_eq!(first.as_str().len(), 16);
+    }
+
+    #[test]
+    fn test_bucket_split_by_major_parameter() {
+        let route = "/api/v1/channels/{channel_id}/messages";
+
+        let first = RateLimitBucket::new("POST", route, "/api/v1/channels/10/messages");
+        let second = RateLimitBucket::new("POST", route, "/api/v1/channels/20/messages");
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_bucket_split_by_route_and_method() {
+        let path = "/api/v1/channels/10/messages";
+        let get = RateLimitBucket::new("GET", "/api/v1/channels/:channel_id/messages", path);
+        let post = RateLimitBucket::new("POST", "/api/v1/channels/:channel_id/messages", path);
+        let channel = RateLimitBucket::new("GET", "/api/v1/channels/:channel_id", "/api/v1/channels/10");
+
+        assert_ne!(get, post);
+        assert_ne!(get, channel);
+    }
+
+    #[test]
+    fn test_major_parameter_extraction() {
+        assert_eq!(
+            major_parameter("/guilds/:guild_id/members/:user_id", "/guilds/5/members/7"),
+            Some("5")
+        );
+        assert_eq!(major_parameter("/users/{user_id}", "/users/7"), None);
+    }
+
+    #[test]
+    fn test_guild_target_from_major_parameter() {
+        assert_eq!(
+            guild_target("/guilds/:guild_id/invites", "/guilds/5/invites"),
+            Some(GuildTarget::Guild(5))
+        );
+        assert_eq!(
+            guild_target("/channels/:channel_id/messages", "/channels/10/messages"),
+            Some(GuildTarget::Channel(10))
+        );
+        assert_eq!(guild_target("/channels/:channel_id/messages", "/channels/abc/messages"), None);
+        assert_eq!(guild_target("/users/:user_id", "/users/7"), None);
+    }
+
+    #[test]
+    fn test_guild_scoped_keys_separate_guilds() {
+        let bucket = RateLimitBucket::for_route("POST", "/channels/:channel_id/messages");
+
+        let guild_a = guild_scoped_key(&bucket, Some(1), "user:42");
+        let guild_b = guild_scoped_key(&bucket, Some(2), "user:42");
+        assert_ne!(guild_a, guild_b);
+        assert_eq!(guild_a, format!("{}:guild:1:user:42", bucket.as_str()));
+
+        // An admin reset of the user scans `{prefix}:*:user:42`
+        assert!(guild_a.ends_with(":user:42"));
+        assert_eq!(guild_scoped_key(&bucket, None, "user:42"), format!("{}:user:42", bucket.as_str()));
+    }
+
+    #[test]
+    fn test_guild_route_bucket_ignores_channel() {
+        let route = "/channels/:channel_id/messages";
+
+        assert_eq!(
+            RateLimitBucket::for_route("POST", route),
+            RateLimitBucket::for_route("POST", route)
+        );
+        assert_ne!(
+            RateLimitBucket::for_route("POST", route),
+            RateLimitBucket::new("POST", route, "/channels/10/messages")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exhausting_guild_a_leaves_guild_b_budget() {
+        let config = EndpointType::Guild.config();
+        let limiter = RateLimiter::with_config(MemoryStore::default(), EndpointType::Guild, config.clone());
+        let route = "/channels/:channel_id/messages/:message_id/reactions/:emoji/@me";
+        let bucket = RateLimitBucket::for_route("PUT", route);
+        let key = |guild_id| guild_scoped_key(&bucket, Some(guild_id), "user:42");
+
+        for _ in 0..config.max_requests() {
+            assert!(limiter.check(&key(1)).await.is_ok());
+        }
+        assert!(limiter.check(&key(1)).await.is_err());
+
+        let info = limiter.check(&key(2)).await.unwrap();
+        assert_eq!(info.remaining, config.requests_per_window - 1);
+    }
 }
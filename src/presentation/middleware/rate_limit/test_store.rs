@@ -0,0 +1,59 @@
+//! In-Process Rate Limit Store
+//!
+//! A [`RateLimitStore`] kept in memory, so the limiting logic can be tested
+//! without Redis.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+use super::{RateLimitStore, WindowHit};
+
+/// Sliding-window store kept in process, mirroring the Redis script.
+#[derive(Clone, Default)]
+pub struct MemoryStore(Arc<Mutex<HashMap<String, Vec<i64>>>>);
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn hit(
+        &self,
+        key: &str,
+        now_ms: i64,
+        window_seconds: u64,
+        max_requests: u32,
+    ) -> Result<WindowHit, redis::RedisError> {
+        let window_ms = (window_seconds * 1000) as i64;
+        let mut windows = self.0.lock();
+        let hits = windows.entry(key.to_string()).or_default();
+        hits.retain(|&at| at > now_ms - window_ms);
+
+        if (hits.len() as u32) < max_requests {
+            hits.push(now_ms);
+            return Ok(WindowHit {
+                allowed: true,
+                count: hits.len() as u32,
+                retry_after_ms: 0,
+            });
+        }
+        Ok(WindowHit {
+            allowed: false,
+            count: hits.len() as u32,
+            retry_after_ms: hits[0] + window_ms - now_ms,
+        })
+    }
+
+    async fn clear(&self, prefix: &str, identifier: &str) -> Result<u64, redis::RedisError> {
+        let exact = format!("{}:{}", prefix, identifier);
+        let bucket_prefix = format!("{}:", prefix);
+        let bucket_suffix = format!(":{}", identifier);
+
+        let mut windows = self.0.lock();
+        let before = windows.len();
+        windows.retain(|key, _| {
+            key != &exact && !(key.starts_with(&bucket_prefix) && key.ends_with(&bucket_suffix))
+        });
+        Ok((before - windows.len()) as u64)
+    }
+}
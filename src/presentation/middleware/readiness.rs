@@ -0,0 +1,50 @@
+//! Readiness Gate
+//!
+//! Rejects traffic until startup work (migrations, dependency checks) has
+//! finished, so early requests get a retryable 503 instead of failing
+//! against an unmigrated database. Health probes are not gated.
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::shared::error::AppError;
+use crate::startup::{AppState, Readiness};
+
+/// Readiness gate middleware
+pub async fn readiness_gate(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    require_ready(&state.readiness)?;
+
+    Ok(next.run(request).await)
+}
+
+/// Check that the server has finished starting up.
+fn require_ready(readiness: &Readiness) -> Result<(), AppError> {
+    if !readiness.is_ready() {
+        return Err(AppError::Unavailable("Server is starting up".into()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, response::IntoResponse};
+
+    #[test]
+    fn test_requests_are_rejected_until_ready() {
+        let readiness = Readiness::new();
+
+        let err = require_ready(&readiness).unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        readiness.mark_ready();
+        assert!(require_ready(&readiness).is_ok());
+    }
+}
@@ -0,0 +1,156 @@
+//! Request Body Limits
+//!
+//! Bounds and type-checks request bodies before handlers read them, so a
+//! huge or mistyped body is rejected without being buffered:
+//!
+//! - bodies over the limit get `413 Payload Too Large`
+//! - bodies that aren't JSON get `415 Unsupported Media Type`
+//!
+//! Requests without a body (e.g. `PUT .../pins/:id`) pass through.
+
+use axum::{
+    extract::{DefaultBodyLimit, Request},
+    http::{header, HeaderMap},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use tower_http::limit::RequestBodyLimitLayer;
+
+use crate::shared::error::AppError;
+
+/// Limit the bodies of a JSON router's routes to `max_body_bytes` and
+/// require them to be JSON.
+///
+/// Applies to the routes already added to `router`; routes added afterwards
+/// (such as attachment uploads) can set their own limits.
+pub fn limit_json_bodies<S>(router: Router<S>, max_body_bytes: usize) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router
+        .layer(middleware::from_fn(require_json_body))
+        // The layer below enforces the limit; extractors shouldn't apply
+        // axum's smaller default on top of it
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(max_body_bytes))
+}
+
+/// Reject requests whose body isn't declared as JSON
+pub async fn require_json_body(request: Request, next: Next) -> Result<Response, AppError> {
+    if has_body(request.headers()) && !is_json(request.headers()) {
+        return Err(AppError::UnsupportedMediaType(
+            "Request body must be application/json".into(),
+        ));
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Whether the request declares a non-empty body
+fn has_body(headers: &HeaderMap) -> bool {
+    if headers.contains_key(header::TRANSFER_ENCODING) {
+        return true;
+    }
+
+    headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .is_some_and(|length| length > 0)
+}
+
+/// Whether the content type is `application/json` or a `+json` type
+fn is_json(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+
+    // Parameters such as `charset` don't matter
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    match essence.split_once('/') {
+        Some(("application", subtype)) => subtype == "json" || subtype.ends_with("+json"),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, routing::post, Json};
+    use tower::ServiceExt;
+
+    async fn echo(Json(body): Json<serde_json::Value>) -> Json<serde_json::Value> {
+        Json(body)
+    }
+
+    fn app() -> Router {
+        limit_json_bodies(Router::new().route("/", post(echo)), 64)
+    }
+
+    fn request(content_type: &str, body: String) -> Request {
+        Request::builder()
+            .method("POST")
+            .uri("/")
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_LENGTH, body.len())
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_json_body_within_limit_is_accepted() {
+        for content_type in ["application/json", "application/json; charset=utf-8", "application/merge-patch+json"] {
+            let response = app()
+                .oneshot(request(content_type, r#"{"name":"general"}"#.to_string()))
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK, "{}", content_type);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body_is_rejected() {
+        let body = format!(r#"{{"content":"{}"}}"#, "a".repeat(100));
+
+        let response = app().oneshot(request("application/json", body)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_content_type_is_rejected() {
+        for content_type in ["text/plain", "application/x-www-form-urlencoded", "not a mime type"] {
+            let response = app()
+                .oneshot(request(content_type, r#"{"name":"general"}"#.to_string()))
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE, "{}", content_type);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_body_pass() {
+        let app = limit_json_bodies(Router::new().route("/", post(|| async { "ok" })), 64);
+        let request = Request::builder()
+            .method("POST")
+            .uri("/")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,235 @@
+//! Outbound Backpressure
+//!
+//! Each session's outbound messages go through a bounded queue drained by
+//! the socket writer. A client that reads too slowly fills its queue; once
+//! the queue has stayed backed up for longer than the slow-client timeout,
+//! the session is closed instead of buffering without bound.
+//!
+//! Everything sent to a session, from the socket handler and the gateway
+//! alike, goes through its [`OutboundQueue`], so no path can drop messages
+//! without the backlog being noticed.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc::{self, error::SendTimeoutError, error::TrySendError};
+use tokio::sync::{watch, Mutex};
+
+/// Share of the queue (in tenths) that counts as backed up
+const BACKLOG_THRESHOLD_TENTHS: usize = 9;
+
+/// Why a message could not be queued for a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendFailure {
+    /// The socket writer is gone; the connection is already closing
+    Closed,
+    /// The queue stayed backed up for longer than the timeout
+    SlowClient,
+}
+
+/// Tracks how long a session's outbound queue has been backed up
+#[derive(Debug)]
+pub struct OutboundBackpressure {
+    /// Queued messages at which the queue counts as backed up
+    threshold: usize,
+    timeout: Duration,
+    backlogged_since: Option<Instant>,
+}
+
+impl OutboundBackpressure {
+    /// Monitor a queue of `capacity` messages, allowing it to stay backed
+    /// up for at most `timeout`
+    pub fn new(capacity: usize, timeout: Duration) -> Self {
+        Self {
+            threshold: (capacity * BACKLOG_THRESHOLD_TENTHS).div_ceil(10).max(1),
+            timeout,
+            backlogged_since: None,
+        }
+    }
+
+    /// Whether a queue holding `queued` messages is backed up
+    pub fn is_backlogged(&self, queued: usize) -> bool {
+        queued >= self.threshold
+    }
+
+    /// Record the queue depth at `now`.
+    ///
+    /// Returns true once the queue has been backed up for longer than the
+    /// timeout, meaning the client should be disconnected.
+    pub fn observe(&mut self, queued: usize, now: Instant) -> bool {
+        if !self.is_backlogged(queued) {
+            self.backlogged_since = None;
+            return false;
+        }
+
+        let since = *self.backlogged_since.get_or_insert(now);
+        now.duration_since(since) > self.timeout
+    }
+
+    /// How much longer the queue may stay backed up, as of `now`
+    pub fn remaining(&self, now: Instant) -> Duration {
+        match self.backlogged_since {
+            Some(since) => self.timeout.saturating_sub(now.duration_since(since)),
+            None => self.timeout,
+        }
+    }
+
+    /// Queue a message, waiting out the rest of the timeout if the queue is full
+    pub async fn send<T>(&mut self, tx: &mpsc::Sender<T>, message: T) -> Result<(), SendFailure> {
+        let now = Instant::now();
+        if self.observe(queued(tx), now) {
+            return Err(SendFailure::SlowClient);
+        }
+
+        match tx.try_send(message) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Closed(_)) => Err(SendFailure::Closed),
+            Err(TrySendError::Full(message)) => {
+                match tx.send_timeout(message, self.remaining(now)).await {
+                    Ok(()) => Ok(()),
+                    Err(SendTimeoutError::Closed(_)) => Err(SendFailure::Closed),
+                    Err(SendTimeoutError::Timeout(_)) => Err(SendFailure::SlowClient),
+                }
+            }
+        }
+    }
+}
+
+/// A session's bounded outbound queue, shared by every sender.
+///
+/// All sends share one [`OutboundBackpressure`]. The first send that finds
+/// the client too slow flags it, and the session watching
+/// [`OutboundQueue::slow_client`] closes the connection.
+pub struct OutboundQueue<T> {
+    tx: mpsc::Sender<T>,
+    backpressure: Arc<Mutex<OutboundBackpressure>>,
+    slow: Arc<watch::Sender<bool>>,
+}
+
+impl<T> Clone for OutboundQueue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            backpressure: self.backpressure.clone(),
+            slow: self.slow.clone(),
+        }
+    }
+}
+
+impl<T> OutboundQueue<T> {
+    /// Create a queue of `capacity` messages that may stay backed up for
+    /// at most `timeout`, with the receiver the socket writer drains
+    pub fn new(capacity: usize, timeout: Duration) -> (Self, mpsc::Receiver<T>) {
+        let capacity = capacity.max(1);
+        let (tx, rx) = mpsc::channel(capacity);
+        let queue = Self {
+            tx,
+            backpressure: Arc::new(Mutex::new(OutboundBackpressure::new(capacity, timeout))),
+            slow: Arc::new(watch::channel(false).0),
+        };
+        (queue, rx)
+    }
+
+    /// Queue a message, waiting out the rest of the timeout if the queue is
+    /// full, and flag the client as slow if it never drains
+    pub async fn send(&self, message: T) -> Result<(), SendFailure> {
+        let result = self.backpressure.lock().await.send(&self.tx, message).await;
+        if result == Err(SendFailure::SlowClient) {
+            self.slow.send_replace(true);
+        }
+        result
+    }
+
+    /// Changes to true once a send gave up on the client
+    pub fn slow_client(&self) -> watch::Receiver<bool> {
+        self.slow.subscribe()
+    }
+}
+
+/// Messages waiting in a queue
+fn queued<T>(tx: &mpsc::Sender<T>) -> usize {
+    tx.max_capacity() - tx.capacity()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backlog_is_detected_near_capacity() {
+        let backpressure = OutboundBackpressure::new(100, Duration::from_secs(10));
+
+        assert!(!backpressure.is_backlogged(0));
+        assert!(!backpressure.is_backlogged(89));
+        assert!(backpressure.is_backlogged(90));
+        assert!(backpressure.is_backlogged(100));
+
+        // Tiny queues are backed up only when full
+        assert!(!OutboundBackpressure::new(1, Duration::ZERO).is_backlogged(0));
+        assert!(OutboundBackpressure::new(1, Duration::ZERO).is_backlogged(1));
+    }
+
+    #[tokio::test]
+    async fn test_full_channel_counts_as_backlogged() {
+        let (tx, _rx) = mpsc::channel(4);
+        let backpressure = OutboundBackpressure::new(4, Duration::from_secs(10));
+
+        for i in 0..4 {
+            assert!(!backpressure.is_backlogged(queued(&tx)));
+            tx.try_send(i).unwrap();
+        }
+
+        assert_eq!(queued(&tx), 4);
+        assert!(backpressure.is_backlogged(queued(&tx)));
+    }
+
+    #[test]
+    fn test_disconnect_only_after_sustained_backlog() {
+        let mut backpressure = OutboundBackpressure::new(10, Duration::from_secs(5));
+        let start = Instant::now();
+
+        assert!(!backpressure.observe(10, start));
+        assert!(!backpressure.observe(10, start + Duration::from_secs(5)));
+        assert_eq!(backpressure.remaining(start + Duration::from_secs(2)), Duration::from_secs(3));
+
+        // Draining resets the clock
+        assert!(!backpressure.observe(2, start + Duration::from_secs(5)));
+        assert!(!backpressure.observe(10, start + Duration::from_secs(6)));
+        assert!(!backpressure.observe(10, start + Duration::from_secs(11)));
+        assert!(backpressure.observe(10, start + Duration::from_secs(12)));
+    }
+
+    #[tokio::test]
+    async fn test_send_gives_up_on_a_client_that_never_reads() {
+        let (tx, mut rx) = mpsc::channel(2);
+        let mut backpressure = OutboundBackpressure::new(2, Duration::from_millis(20));
+
+        assert_eq!(backpressure.send(&tx, 1).await, Ok(()));
+        assert_eq!(backpressure.send(&tx, 2).await, Ok(()));
+        assert_eq!(backpressure.send(&tx, 3).await, Err(SendFailure::SlowClient));
+
+        // A reader catching up clears the backlog
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, Some(2));
+        assert_eq!(backpressure.send(&tx, 4).await, Ok(()));
+
+        drop(rx);
+        assert_eq!(backpressure.send(&tx, 5).await, Err(SendFailure::Closed));
+    }
+
+    #[tokio::test]
+    async fn test_queue_flags_a_slow_client_for_every_sender() {
+        let (queue, mut rx) = OutboundQueue::new(1, Duration::from_millis(20));
+        let other_sender = queue.clone();
+        let mut slow = queue.slow_client();
+
+        assert_eq!(queue.send(1).await, Ok(()));
+        assert!(!*slow.borrow());
+
+        // A second sender shares the backlog and trips the flag
+        assert_eq!(other_sender.send(2).await, Err(SendFailure::SlowClient));
+        assert!(slow.has_changed().unwrap());
+        assert!(*slow.borrow_and_update());
+        assert_eq!(rx.recv().await, Some(1));
+    }
+}
@@ -0,0 +1,146 @@
+//! Disconnect Reasons
+//!
+//! Every gateway session ends with a [`DisconnectReason`], logged in the
+//! session's span and counted in `websocket_closes_total{reason}`.
+//! [`ConnectionGauge`] keeps the active-connection gauge balanced however
+//! the session ends.
+
+use prometheus::Gauge;
+
+use super::messages::GatewayClose;
+use crate::infrastructure::metrics::{record_websocket_close, WEBSOCKET_CONNECTIONS_ACTIVE};
+
+/// Why a gateway session ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisconnectReason {
+    /// The client sent a close frame or dropped the connection
+    ClientClosed,
+    /// Reading from the socket failed
+    SocketError,
+    /// The client didn't identify in time
+    IdentifyTimeout,
+    /// The client tried to resume; no session can be resumed
+    ResumeFailed,
+    /// The identify token was invalid or its session revoked
+    AuthFailed,
+    /// No heartbeat arrived within the interval and grace period
+    HeartbeatTimeout,
+    /// The client fell too far behind reading its events
+    SlowClient,
+    /// The auth session was revoked while connected
+    SessionRevoked,
+    /// The server asked the client to reconnect, e.g. before shutdown
+    ServerShutdown,
+    /// The server failed to set up or run the session
+    ServerError,
+}
+
+impl DisconnectReason {
+    /// Label used in logs and the `reason` metric label
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ClientClosed => "client_closed",
+            Self::SocketError => "socket_error",
+            Self::IdentifyTimeout => "identify_timeout",
+            Self::ResumeFailed => "resume_failed",
+            Self::AuthFailed => "auth_failed",
+            Self::HeartbeatTimeout => "heartbeat_timeout",
+            Self::SlowClient => "slow_client",
+            Self::SessionRevoked => "session_revoked",
+            Self::ServerShutdown => "server_shutdown",
+            Self::ServerError => "server_error",
+        }
+    }
+
+    /// Count a session closed for this reason
+    pub fn record(self) {
+        record_websocket_close(self.as_str());
+    }
+}
+
+impl From<GatewayClose> for DisconnectReason {
+    /// Reason for a close the server requested on a running session
+    fn from(close: GatewayClose) -> Self {
+        match close {
+            GatewayClose::Reconnect => Self::ServerShutdown,
+            GatewayClose::InvalidSession { .. } => Self::SessionRevoked,
+        }
+    }
+}
+
+/// One connection counted in a connection gauge, uncounted when dropped
+#[derive(Debug)]
+pub struct ConnectionGauge {
+    gauge: Gauge,
+}
+
+impl ConnectionGauge {
+    /// Count a connection in `gauge` for as long as the guard lives
+    pub fn new(gauge: Gauge) -> Self {
+        gauge.inc();
+        Self { gauge }
+    }
+
+    /// Count an open connection in `websocket_connections_active{state}`
+    pub fn active(state: &str) -> Self {
+        Self::new(WEBSOCKET_CONNECTIONS_ACTIVE.with_label_values(&[state]))
+    }
+}
+
+impl Drop for ConnectionGauge {
+    fn drop(&mut self) {
+        self.gauge.dec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_requested_closes_map_to_reasons() {
+        assert_eq!(DisconnectReason::from(GatewayClose::Reconnect), DisconnectReason::ServerShutdown);
+        assert_eq!(
+            DisconnectReason::from(GatewayClose::InvalidSession { resumable: false }),
+            DisconnectReason::SessionRevoked
+        );
+    }
+
+    #[test]
+    fn test_reason_labels_are_distinct() {
+        let reasons = [
+            DisconnectReason::ClientClosed,
+            DisconnectReason::SocketError,
+            DisconnectReason::IdentifyTimeout,
+            DisconnectReason::ResumeFailed,
+            DisconnectReason::AuthFailed,
+            DisconnectReason::HeartbeatTimeout,
+            DisconnectReason::SlowClient,
+            DisconnectReason::SessionRevoked,
+            DisconnectReason::ServerShutdown,
+            DisconnectReason::ServerError,
+        ];
+        let mut labels: Vec<&str> = reasons.iter().map(|r| r.as_str()).collect();
+        labels.sort_unstable();
+        labels.dedup();
+
+        assert_eq!(labels.len(), reasons.len());
+    }
+
+    #[test]
+    fn test_gauge_returns_to_zero_however_connections_end() {
+        let gauge = Gauge::new("test_connections", "test").unwrap();
+
+        let first = ConnectionGauge::new(gauge.clone());
+        let second = ConnectionGauge::new(gauge.clone());
+        assert_eq!(gauge.get(), 2.0);
+
+        // A session that ends early and one that runs to completion
+        drop(second);
+        assert_eq!(gauge.get(), 1.0);
+        let task = std::thread::spawn(move || drop(first));
+        task.join().unwrap();
+
+        assert_eq!(gauge.get(), 0.0);
+    }
+}
@@ -4,10 +4,19 @@
 
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, watch};
 
-use super::messages::GatewaySend;
+use super::backpressure::OutboundQueue;
+use super::messages::{GatewayClose, GatewaySend};
+use crate::application::services::{
+    ChannelOverrideDto, PinsUpdateDto, RecipientPermissions, VoiceStateDto,
+};
+use crate::domain::value_objects::Permissions;
+use crate::infrastructure::cache::{Activity, UserPresence};
+use crate::domain::NotificationLevel;
+use crate::shared::error::AppError;
 
 /// Gateway event types for internal communication
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +29,8 @@ pub enum GatewayEvent {
     MessageUpdate(MessageUpdateEvent),
     #[serde(rename = "MESSAGE_DELETE")]
     MessageDelete(MessageDeleteEvent),
+    #[serde(rename = "MESSAGE_DELETE_BULK")]
+    MessageDeleteBulk(MessageDeleteBulkEvent),
 
     // Guild events
     #[serde(rename = "GUILD_CREATE")]
@@ -36,6 +47,8 @@ pub enum GatewayEvent {
     ChannelUpdate(ChannelUpdateEvent),
     #[serde(rename = "CHANNEL_DELETE")]
     ChannelDelete(ChannelDeleteEvent),
+    #[serde(rename = "CHANNEL_PINS_UPDATE")]
+    ChannelPinsUpdate(ChannelPinsUpdateEvent),
 
     // Member events
     #[serde(rename = "GUILD_MEMBER_ADD")]
@@ -45,11 +58,27 @@ pub enum GatewayEvent {
     #[serde(rename = "GUILD_MEMBER_REMOVE")]
     GuildMemberRemove(GuildMemberRemoveEvent),
 
+    // Role events
+    #[serde(rename = "GUILD_ROLE_UPDATE")]
+    GuildRoleUpdate(GuildRoleUpdateEvent),
+
     // Presence events
     #[serde(rename = "PRESENCE_UPDATE")]
     PresenceUpdate(PresenceUpdateEvent),
     #[serde(rename = "TYPING_START")]
     TypingStart(TypingStartEvent),
+
+    // Voice events
+    #[serde(rename = "VOICE_STATE_UPDATE")]
+    VoiceStateUpdate(VoiceStateUpdateEvent),
+
+    // User events
+    #[serde(rename = "USER_UPDATE")]
+    UserUpdate(UserUpdateEvent),
+
+    // User settings events
+    #[serde(rename = "CHANNEL_OVERRIDE_UPDATE")]
+    ChannelOverrideUpdate(ChannelOverrideUpdateEvent),
 }
 
 impl GatewayEvent {
@@ -59,17 +88,23 @@ impl GatewayEvent {
             GatewayEvent::MessageCreate(_) => "MESSAGE_CREATE",
             GatewayEvent::MessageUpdate(_) => "MESSAGE_UPDATE",
             GatewayEvent::MessageDelete(_) => "MESSAGE_DELETE",
+            GatewayEvent::MessageDeleteBulk(_) => "MESSAGE_DELETE_BULK",
             GatewayEvent::GuildCreate(_) => "GUILD_CREATE",
             GatewayEvent::GuildUpdate(_) => "GUILD_UPDATE",
             GatewayEvent::GuildDelete(_) => "GUILD_DELETE",
             GatewayEvent::ChannelCreate(_) => "CHANNEL_CREATE",
             GatewayEvent::ChannelUpdate(_) => "CHANNEL_UPDATE",
             GatewayEvent::ChannelDelete(_) => "CHANNEL_DELETE",
+            GatewayEvent::ChannelPinsUpdate(_) => "CHANNEL_PINS_UPDATE",
             GatewayEvent::GuildMemberAdd(_) => "GUILD_MEMBER_ADD",
             GatewayEvent::GuildMemberUpdate(_) => "GUILD_MEMBER_UPDATE",
             GatewayEvent::GuildMemberRemove(_) => "GUILD_MEMBER_REMOVE",
+            GatewayEvent::GuildRoleUpdate(_) => "GUILD_ROLE_UPDATE",
             GatewayEvent::PresenceUpdate(_) => "PRESENCE_UPDATE",
             GatewayEvent::TypingStart(_) => "TYPING_START",
+            GatewayEvent::VoiceStateUpdate(_) => "VOICE_STATE_UPDATE",
+            GatewayEvent::UserUpdate(_) => "USER_UPDATE",
+            GatewayEvent::ChannelOverrideUpdate(_) => "CHANNEL_OVERRIDE_UPDATE",
         }
     }
 
@@ -79,37 +114,80 @@ impl GatewayEvent {
             GatewayEvent::MessageCreate(e) => e.guild_id,
             GatewayEvent::MessageUpdate(e) => e.guild_id,
             GatewayEvent::MessageDelete(e) => e.guild_id,
+            GatewayEvent::MessageDeleteBulk(e) => e.guild_id,
             GatewayEvent::GuildCreate(e) => Some(e.id),
             GatewayEvent::GuildUpdate(e) => Some(e.id),
             GatewayEvent::GuildDelete(e) => Some(e.id),
             GatewayEvent::ChannelCreate(e) => e.guild_id,
             GatewayEvent::ChannelUpdate(e) => e.guild_id,
             GatewayEvent::ChannelDelete(e) => e.guild_id,
+            GatewayEvent::ChannelPinsUpdate(e) => e.guild_id,
             GatewayEvent::GuildMemberAdd(e) => Some(e.guild_id),
             GatewayEvent::GuildMemberUpdate(e) => Some(e.guild_id),
             GatewayEvent::GuildMemberRemove(e) => Some(e.guild_id),
+            GatewayEvent::GuildRoleUpdate(e) => Some(e.guild_id),
             GatewayEvent::PresenceUpdate(e) => e.guild_id,
             GatewayEvent::TypingStart(e) => e.guild_id,
+            GatewayEvent::VoiceStateUpdate(e) => e.guild_id,
+            GatewayEvent::UserUpdate(_) => None,
+            GatewayEvent::ChannelOverrideUpdate(_) => None,
         }
     }
 
+    /// Get the channel this event concerns, if any (for permission checks)
+    pub fn channel_id(&self) -> Option<i64> {
+        let channel_id = match self {
+            GatewayEvent::MessageCreate(e) => &e.channel_id,
+            GatewayEvent::MessageUpdate(e) => &e.channel_id,
+            GatewayEvent::MessageDelete(e) => &e.channel_id,
+            GatewayEvent::MessageDeleteBulk(e) => &e.channel_id,
+            GatewayEvent::ChannelCreate(e) => &e.id,
+            GatewayEvent::ChannelUpdate(e) => &e.id,
+            GatewayEvent::ChannelDelete(e) => &e.id,
+            GatewayEvent::ChannelPinsUpdate(e) => &e.channel_id,
+            GatewayEvent::TypingStart(e) => &e.channel_id,
+            GatewayEvent::VoiceStateUpdate(e) => e.channel_id.as_ref()?,
+            _ => return None,
+        };
+        channel_id.parse().ok()
+    }
+
+    /// Whether the event carries message content, which an NSFW channel
+    /// restricts to some of its members
+    pub fn has_message_content(&self) -> bool {
+        matches!(self, GatewayEvent::MessageCreate(_) | GatewayEvent::MessageUpdate(_))
+    }
+
     /// Convert to JSON value for sending
     pub fn to_json(&self) -> serde_json::Value {
         match self {
             GatewayEvent::MessageCreate(e) => serde_json::to_value(e).unwrap_or_default(),
             GatewayEvent::MessageUpdate(e) => serde_json::to_value(e).unwrap_or_default(),
             GatewayEvent::MessageDelete(e) => serde_json::to_value(e).unwrap_or_default(),
+            GatewayEvent::MessageDeleteBulk(e) => serde_json::to_value(e).unwrap_or_default(),
             GatewayEvent::GuildCreate(e) => serde_json::to_value(e).unwrap_or_default(),
             GatewayEvent::GuildUpdate(e) => serde_json::to_value(e).unwrap_or_default(),
             GatewayEvent::GuildDelete(e) => serde_json::to_value(e).unwrap_or_default(),
             GatewayEvent::ChannelCreate(e) => serde_json::to_value(e).unwrap_or_default(),
             GatewayEvent::ChannelUpdate(e) => serde_json::to_value(e).unwrap_or_default(),
             GatewayEvent::ChannelDelete(e) => serde_json::to_value(e).unwrap_or_default(),
+            GatewayEvent::ChannelPinsUpdate(e) => serde_json::to_value(e).unwrap_or_default(),
             GatewayEvent::GuildMemberAdd(e) => serde_json::to_value(e).unwrap_or_default(),
             GatewayEvent::GuildMemberUpdate(e) => serde_json::to_value(e).unwrap_or_default(),
             GatewayEvent::GuildMemberRemove(e) => serde_json::to_value(e).unwrap_or_default(),
+            GatewayEvent::GuildRoleUpdate(e) => serde_json::to_value(e).unwrap_or_default(),
             GatewayEvent::PresenceUpdate(e) => serde_json::to_value(e).unwrap_or_default(),
             GatewayEvent::TypingStart(e) => serde_json::to_value(e).unwrap_or_default(),
+            GatewayEvent::VoiceStateUpdate(e) => serde_json::to_value(e).unwrap_or_default(),
+            GatewayEvent::UserUpdate(e) => {
+                // Members shouldn't learn which other guilds the user is in
+                let mut value = serde_json::to_value(e).unwrap_or_default();
+                if let Some(fields) = value.as_object_mut() {
+                    fields.remove("guild_ids");
+                }
+                value
+            }
+            GatewayEvent::ChannelOverrideUpdate(e) => serde_json::to_value(e).unwrap_or_default(),
         }
     }
 }
@@ -122,6 +200,12 @@ pub struct MessageCreateEvent {
     pub guild_id: Option<i64>,
     pub author: UserObject,
     pub content: String,
+    /// Whether clients should read the message aloud
+    #[serde(default)]
+    pub tts: bool,
+    /// Bitfield of message flags, e.g. suppressed embeds
+    #[serde(default)]
+    pub flags: i64,
     pub timestamp: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub edited_timestamp: Option<String>,
@@ -137,6 +221,8 @@ pub struct MessageUpdateEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub flags: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub edited_timestamp: Option<String>,
 }
 
@@ -147,6 +233,14 @@ pub struct MessageDeleteEvent {
     pub guild_id: Option<i64>,
 }
 
+/// Several messages in one channel deleted at once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageDeleteBulkEvent {
+    pub ids: Vec<String>,
+    pub channel_id: String,
+    pub guild_id: Option<i64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuildCreateEvent {
     pub id: i64,
@@ -197,11 +291,31 @@ pub struct ChannelDeleteEvent {
     pub guild_id: Option<i64>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelPinsUpdateEvent {
+    pub channel_id: String,
+    pub guild_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_pin_timestamp: Option<String>,
+}
+
+impl From<PinsUpdateDto> for ChannelPinsUpdateEvent {
+    fn from(update: PinsUpdateDto) -> Self {
+        Self {
+            channel_id: update.channel_id,
+            guild_id: update.guild_id.and_then(|id| id.parse().ok()),
+            last_pin_timestamp: update.last_pin_timestamp,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuildMemberAddEvent {
     pub guild_id: i64,
     pub user: UserObject,
     pub joined_at: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -219,6 +333,19 @@ pub struct GuildMemberRemoveEvent {
     pub user: UserObject,
 }
 
+/// New positions of a guild's roles after a reorder, in one event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuildRoleUpdateEvent {
+    pub guild_id: i64,
+    pub roles: Vec<RolePositionObject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolePositionObject {
+    pub id: String,
+    pub position: i32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PresenceUpdateEvent {
     pub user_id: String,
@@ -226,6 +353,21 @@ pub struct PresenceUpdateEvent {
     pub status: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub custom_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub activities: Vec<Activity>,
+}
+
+impl PresenceUpdateEvent {
+    /// Event announcing `presence` in a guild
+    pub fn new(presence: &UserPresence, guild_id: i64) -> Self {
+        Self {
+            user_id: presence.user_id.to_string(),
+            guild_id: Some(guild_id),
+            status: presence.status.to_string(),
+            custom_status: presence.custom_status.clone(),
+            activities: presence.activities.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -236,6 +378,68 @@ pub struct TypingStartEvent {
     pub timestamp: i64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceStateUpdateEvent {
+    pub guild_id: Option<i64>,
+    pub channel_id: Option<String>,
+    pub user_id: String,
+    pub session_id: String,
+    pub self_mute: bool,
+    pub self_deaf: bool,
+    pub mute: bool,
+    pub deaf: bool,
+    #[serde(default)]
+    pub suppress: bool,
+}
+
+impl From<VoiceStateDto> for VoiceStateUpdateEvent {
+    fn from(state: VoiceStateDto) -> Self {
+        Self {
+            guild_id: state.server_id.parse().ok(),
+            channel_id: state.channel_id,
+            user_id: state.user_id,
+            session_id: state.session_id,
+            self_mute: state.self_mute,
+            self_deaf: state.self_deaf,
+            mute: state.mute,
+            deaf: state.deaf,
+            suppress: state.suppress,
+        }
+    }
+}
+
+/// A user's profile changed; sent to the user and members of their guilds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserUpdateEvent {
+    pub id: String,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub banner_url: Option<String>,
+    /// Guilds whose members receive the event; used for routing and not
+    /// sent to clients
+    #[serde(default)]
+    pub guild_ids: Vec<i64>,
+}
+
+/// A user's channel override changed; sent only to that user's sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelOverrideUpdateEvent {
+    pub channel_id: String,
+    pub muted: bool,
+    pub notification_level: Option<NotificationLevel>,
+}
+
+impl From<ChannelOverrideDto> for ChannelOverrideUpdateEvent {
+    fn from(dto: ChannelOverrideDto) -> Self {
+        Self {
+            channel_id: dto.channel_id,
+            muted: dto.muted,
+            notification_level: dto.notification_level,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserObject {
     pub id: String,
@@ -259,7 +463,13 @@ pub struct ConnectedSession {
     pub user_id: i64,
     pub session_id: String,
     pub guilds: Vec<i64>,
-    pub sender: mpsc::UnboundedSender<GatewaySend>,
+    /// Bounded outbound queue; a client that stays too far behind on it is
+    /// closed as slow
+    pub sender: OutboundQueue<GatewaySend>,
+    /// Auth session (`sid` token claim) this connection was identified with
+    pub auth_session_id: Option<String>,
+    /// Set when the server wants the connection closed, with the reason
+    pub close: watch::Sender<Option<GatewayClose>>,
 }
 
 /// WebSocket gateway managing all connections
@@ -274,6 +484,8 @@ pub struct Gateway {
     event_tx: broadcast::Sender<RoutedEvent>,
     /// Heartbeat interval in milliseconds
     heartbeat_interval_ms: u64,
+    /// Resolves members' permissions for permission-filtered broadcasts
+    recipient_permissions: Option<Arc<dyn RecipientPermissions>>,
 }
 
 impl Gateway {
@@ -285,9 +497,16 @@ impl Gateway {
             guild_sessions: DashMap::new(),
             event_tx,
             heartbeat_interval_ms: 41250, // Discord uses 41.25 seconds
+            recipient_permissions: None,
         }
     }
 
+    /// Resolve permissions for [`Gateway::broadcast_to_guild`] with `resolver`
+    pub fn with_recipient_permissions(mut self, resolver: Arc<dyn RecipientPermissions>) -> Self {
+        self.recipient_permissions = Some(resolver);
+        self
+    }
+
     /// Get the heartbeat interval
     pub fn heartbeat_interval(&self) -> u64 {
         self.heartbeat_interval_ms
@@ -304,13 +523,17 @@ impl Gateway {
         session_id: String,
         user_id: i64,
         guilds: Vec<i64>,
-        sender: mpsc::UnboundedSender<GatewaySend>,
+        sender: OutboundQueue<GatewaySend>,
+        auth_session_id: Option<String>,
+        close: watch::Sender<Option<GatewayClose>>,
     ) {
         let session = Arc::new(ConnectedSession {
             user_id,
             session_id: session_id.clone(),
             guilds: guilds.clone(),
             sender,
+            auth_session_id,
+            close,
         });
 
         // Store session
@@ -377,13 +600,52 @@ impl Gateway {
 
     /// Broadcast event to all relevant sessions
     pub fn dispatch(&self, event: GatewayEvent) {
+        // USER_UPDATE has no guild; it goes to the user and members of theirs
+        let target_users = match &event {
+            GatewayEvent::UserUpdate(e) => {
+                let mut user_ids = self.guild_user_ids(&e.guild_ids);
+                if let Ok(user_id) = e.id.parse() {
+                    if !user_ids.contains(&user_id) {
+                        user_ids.push(user_id);
+                    }
+                }
+                Some(user_ids)
+            }
+            _ => None,
+        };
         let routed = RoutedEvent {
             event,
-            target_users: None,
+            target_users,
         };
         let _ = self.event_tx.send(routed);
     }
 
+    /// Dispatch an event, delivering message content in an NSFW channel only
+    /// to the connected members allowed to see it.
+    ///
+    /// Other events, and every event when the gateway has no permission
+    /// resolver, are dispatched as with [`Gateway::dispatch`].
+    pub async fn dispatch_to_viewers(&self, event: GatewayEvent) -> Result<(), AppError> {
+        let (Some(guild_id), Some(channel_id), Some(resolver)) =
+            (event.guild_id(), event.channel_id(), self.recipient_permissions.as_ref())
+        else {
+            self.dispatch(event);
+            return Ok(());
+        };
+        if !event.has_message_content() {
+            self.dispatch(event);
+            return Ok(());
+        }
+
+        let members = self.guild_user_ids(&[guild_id]);
+        match resolver.nsfw_viewers(guild_id, channel_id, &members).await? {
+            None => self.dispatch(event),
+            Some(viewers) if !viewers.is_empty() => self.dispatch_to_users(event, viewers),
+            Some(_) => {}
+        }
+        Ok(())
+    }
+
     /// Send event to specific users
     pub fn dispatch_to_users(&self, event: GatewayEvent, user_ids: Vec<i64>) {
         let routed = RoutedEvent {
@@ -393,35 +655,130 @@ impl Gateway {
         let _ = self.event_tx.send(routed);
     }
 
-    /// Send event directly to a session (bypassing broadcast)
-    pub fn send_to_session(&self, session_id: &str, message: GatewaySend) -> bool {
-        if let Some(session) = self.sessions.get(session_id) {
-            session.sender.send(message).is_ok()
-        } else {
-            false
+    /// Dispatch a user's PRESENCE_UPDATE to each of `guild_ids`.
+    ///
+    /// Other members get the presence the user's visibility allows them to
+    /// see; the user's own sessions always get the real one.
+    pub fn dispatch_presence(&self, presence: &UserPresence, guild_ids: &[i64]) {
+        let shown = presence.seen_by_others();
+        for &guild_id in guild_ids {
+            if presence.visibility.shared_with_guilds() {
+                self.dispatch(GatewayEvent::PresenceUpdate(PresenceUpdateEvent::new(presence, guild_id)));
+                continue;
+            }
+
+            let others: Vec<i64> = self
+                .guild_user_ids(&[guild_id])
+                .into_iter()
+                .filter(|&user_id| user_id != presence.user_id)
+                .collect();
+            if !others.is_empty() {
+                self.dispatch_to_users(
+                    GatewayEvent::PresenceUpdate(PresenceUpdateEvent::new(&shown, guild_id)),
+                    others,
+                );
+            }
+            self.dispatch_to_users(
+                GatewayEvent::PresenceUpdate(PresenceUpdateEvent::new(presence, guild_id)),
+                vec![presence.user_id],
+            );
         }
     }
 
-    /// Send event to all sessions of a user
-    pub fn send_to_user(&self, user_id: i64, message: GatewaySend) {
-        if let Some(session_ids) = self.user_sessions.get(&user_id) {
-            for session_id in session_ids.value() {
-                if let Some(session) = self.sessions.get(session_id) {
-                    let _ = session.sender.send(message.clone());
-                }
-            }
+    /// Deliver an event to the connected members of a guild.
+    ///
+    /// With `required_permission`, only members holding it (in the event's
+    /// channel, for channel events) receive the event. Returns the number
+    /// of users the event was sent to.
+    pub async fn broadcast_to_guild(
+        &self,
+        guild_id: i64,
+        event: GatewayEvent,
+        required_permission: Option<i64>,
+    ) -> Result<usize, AppError> {
+        let mut recipients = self.guild_user_ids(&[guild_id]);
+
+        if let Some(required) = required_permission {
+            let resolver = self.recipient_permissions.as_ref().ok_or_else(|| {
+                AppError::Internal("Gateway has no recipient permission resolver".into())
+            })?;
+            let permissions = resolver
+                .permissions(guild_id, event.channel_id(), &recipients)
+                .await?;
+            recipients = permitted_recipients(recipients, &permissions, required);
         }
+
+        if recipients.is_empty() {
+            return Ok(0);
+        }
+
+        let count = recipients.len();
+        self.dispatch_to_users(event, recipients);
+        Ok(count)
+    }
+
+    /// Send event directly to a session (bypassing broadcast)
+    pub async fn send_to_session(&self, session_id: &str, message: GatewaySend) -> bool {
+        let Some(sender) = self.sessions.get(session_id).map(|s| s.sender.clone()) else {
+            return false;
+        };
+        sender.send(message).await.is_ok()
+    }
+
+    /// Send event to all sessions of a user
+    pub async fn send_to_user(&self, user_id: i64, message: GatewaySend) {
+        let session_ids = self
+            .user_sessions
+            .get(&user_id)
+            .map(|ids| ids.value().clone())
+            .unwrap_or_default();
+        self.send_to_sessions(&session_ids, message).await;
     }
 
     /// Send event to all sessions in a guild
-    pub fn send_to_guild(&self, guild_id: i64, message: GatewaySend) {
-        if let Some(session_ids) = self.guild_sessions.get(&guild_id) {
-            for session_id in session_ids.value() {
-                if let Some(session) = self.sessions.get(session_id) {
-                    let _ = session.sender.send(message.clone());
-                }
+    pub async fn send_to_guild(&self, guild_id: i64, message: GatewaySend) {
+        let session_ids = self
+            .guild_sessions
+            .get(&guild_id)
+            .map(|ids| ids.value().clone())
+            .unwrap_or_default();
+        self.send_to_sessions(&session_ids, message).await;
+    }
+
+    /// Queue a message for each session, concurrently so one slow client
+    /// doesn't hold up the rest
+    async fn send_to_sessions(&self, session_ids: &[String], message: GatewaySend) {
+        // Collect the queues first; no map guard is held across a send
+        let senders: Vec<OutboundQueue<GatewaySend>> = session_ids
+            .iter()
+            .filter_map(|session_id| self.sessions.get(session_id).map(|s| s.sender.clone()))
+            .collect();
+        futures::future::join_all(senders.iter().map(|sender| sender.send(message.clone()))).await;
+    }
+
+    /// Close all connections identified with the given auth session
+    ///
+    /// The session is invalid and cannot be resumed. Returns the number of
+    /// connections signalled.
+    pub fn close_auth_session(&self, auth_session_id: &str) -> usize {
+        let mut closed = 0;
+        for session in self.sessions.iter() {
+            if session.auth_session_id.as_deref() == Some(auth_session_id) {
+                session.close.send_replace(Some(GatewayClose::InvalidSession { resumable: false }));
+                closed += 1;
             }
         }
+        closed
+    }
+
+    /// Ask every connection to reconnect and resume, e.g. before shutdown
+    ///
+    /// Returns the number of connections signalled.
+    pub fn reconnect_all(&self) -> usize {
+        for session in self.sessions.iter() {
+            session.close.send_replace(Some(GatewayClose::Reconnect));
+        }
+        self.sessions.len()
     }
 
     /// Get session count
@@ -442,6 +799,24 @@ impl Gateway {
             .unwrap_or(false)
     }
 
+    /// Users with a session subscribed to any of the given guilds
+    pub fn guild_user_ids(&self, guild_ids: &[i64]) -> Vec<i64> {
+        let mut user_ids: Vec<i64> = guild_ids
+            .iter()
+            .filter_map(|guild_id| self.guild_sessions.get(guild_id))
+            .flat_map(|session_ids| {
+                session_ids
+                    .iter()
+                    .filter_map(|session_id| self.sessions.get(session_id).map(|s| s.user_id))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        user_ids.sort_unstable();
+        user_ids.dedup();
+        user_ids
+    }
+
     /// Check if user is online (has at least one session)
     pub fn is_user_online(&self, user_id: i64) -> bool {
         self.user_sessions
@@ -451,8 +826,287 @@ impl Gateway {
     }
 }
 
+/// Users granted `required`; users without known permissions are dropped
+fn permitted_recipients(user_ids: Vec<i64>, permissions: &HashMap<i64, i64>, required: i64) -> Vec<i64> {
+    user_ids
+        .into_iter()
+        .filter(|user_id| {
+            permissions
+                .get(user_id)
+                .is_some_and(|&granted| Permissions::new(granted).has(required))
+        })
+        .collect()
+}
+
 impl Default for Gateway {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presentation::websocket::CloseCode;
+    use async_trait::async_trait;
+
+    /// Fixed permissions per user, whatever the guild or channel
+    struct FixedPermissions(HashMap<i64, i64>);
+
+    #[async_trait]
+    impl RecipientPermissions for FixedPermissions {
+        async fn permissions(
+            &self,
+            _guild_id: i64,
+            _channel_id: Option<i64>,
+            user_ids: &[i64],
+        ) -> Result<HashMap<i64, i64>, AppError> {
+            Ok(user_ids
+                .iter()
+                .filter_map(|id| self.0.get(id).map(|&permissions| (*id, permissions)))
+                .collect())
+        }
+
+        /// Channel 50 is NSFW and only user 1 allowed NSFW content
+        async fn nsfw_viewers(
+            &self,
+            _guild_id: i64,
+            channel_id: i64,
+            user_ids: &[i64],
+        ) -> Result<Option<Vec<i64>>, AppError> {
+            Ok((channel_id == 50).then(|| user_ids.iter().copied().filter(|&id| id == 1).collect()))
+        }
+    }
+
+    fn pins_update(guild_id: i64) -> GatewayEvent {
+        GatewayEvent::ChannelPinsUpdate(ChannelPinsUpdateEvent {
+            channel_id: "50".to_string(),
+            guild_id: Some(guild_id),
+            last_pin_timestamp: None,
+        })
+    }
+
+    fn register_in_guilds(gateway: &Gateway, session_id: &str, user_id: i64, guilds: Vec<i64>) {
+        let (sender, _) = OutboundQueue::new(1, std::time::Duration::from_secs(1));
+        let (close, _) = watch::channel(None);
+        gateway.register_session(session_id.to_string(), user_id, guilds, sender, None, close);
+    }
+
+    fn register(gateway: &Gateway, session_id: &str, auth_session_id: &str) -> watch::Receiver<Option<GatewayClose>> {
+        let (sender, _) = OutboundQueue::new(1, std::time::Duration::from_secs(1));
+        let (close, close_rx) = watch::channel(None);
+        gateway.register_session(
+            session_id.to_string(),
+            1,
+            Vec::new(),
+            sender,
+            Some(auth_session_id.to_string()),
+            close,
+        );
+        close_rx
+    }
+
+    #[test]
+    fn test_shutdown_asks_every_session_to_reconnect() {
+        let gateway = Gateway::new();
+        let first = register(&gateway, "a", "auth-1");
+        let second = register(&gateway, "b", "auth-2");
+
+        assert_eq!(gateway.reconnect_all(), 2);
+
+        for rx in [first, second] {
+            let close = rx.borrow().unwrap();
+            assert_eq!(close, GatewayClose::Reconnect);
+            assert_eq!(close.close_code() as u16, 4000);
+        }
+    }
+
+    #[test]
+    fn test_revoked_auth_session_is_not_resumable() {
+        let gateway = Gateway::new();
+        let revoked = register(&gateway, "a", "auth-1");
+        let other = register(&gateway, "b", "auth-2");
+
+        assert_eq!(gateway.close_auth_session("auth-1"), 1);
+
+        let close = revoked.borrow().unwrap();
+        assert_eq!(close, GatewayClose::InvalidSession { resumable: false });
+        assert_eq!(close.close_code(), CloseCode::InvalidSession);
+        assert!(other.borrow().is_none());
+    }
+
+    #[test]
+    fn test_guild_user_ids_are_deduplicated() {
+        let gateway = Gateway::new();
+        register_in_guilds(&gateway, "a", 1, vec![10, 20]);
+        register_in_guilds(&gateway, "b", 1, vec![10]);
+        register_in_guilds(&gateway, "c", 2, vec![20]);
+        register_in_guilds(&gateway, "d", 3, vec![30]);
+
+        assert_eq!(gateway.guild_user_ids(&[10, 20]), vec![1, 2]);
+        assert!(gateway.guild_user_ids(&[40]).is_empty());
+    }
+
+    #[test]
+    fn test_members_without_permission_are_not_recipients() {
+        let permissions = HashMap::from([
+            (1, Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES),
+            (2, Permissions::SEND_MESSAGES),
+            (3, Permissions::ADMINISTRATOR),
+        ]);
+
+        let recipients = permitted_recipients(vec![1, 2, 3, 4], &permissions, Permissions::VIEW_CHANNEL);
+
+        // 4 isn't a member, so has no permissions at all
+        assert_eq!(recipients, vec![1, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_guild_broadcast_skips_members_lacking_permission() {
+        let gateway = Gateway::new().with_recipient_permissions(Arc::new(FixedPermissions(HashMap::from([
+            (1, Permissions::VIEW_CHANNEL),
+            (2, 0),
+        ]))));
+        register_in_guilds(&gateway, "a", 1, vec![10]);
+        register_in_guilds(&gateway, "b", 2, vec![10]);
+        register_in_guilds(&gateway, "c", 3, vec![20]);
+        let mut events = gateway.subscribe();
+
+        let sent = gateway
+            .broadcast_to_guild(10, pins_update(10), Some(Permissions::VIEW_CHANNEL))
+            .await
+            .unwrap();
+
+        assert_eq!(sent, 1);
+        assert_eq!(events.recv().await.unwrap().target_users, Some(vec![1]));
+
+        // Without a required permission every connected member gets it
+        gateway.broadcast_to_guild(10, pins_update(10), None).await.unwrap();
+        assert_eq!(events.recv().await.unwrap().target_users, Some(vec![1, 2]));
+    }
+
+    #[tokio::test]
+    async fn test_nsfw_channel_messages_only_reach_their_viewers() {
+        let gateway = Gateway::new().with_recipient_permissions(Arc::new(FixedPermissions(HashMap::new())));
+        register_in_guilds(&gateway, "a", 1, vec![10]);
+        register_in_guilds(&gateway, "b", 2, vec![10]);
+        let mut events = gateway.subscribe();
+        let message = |channel_id: &str| {
+            GatewayEvent::MessageCreate(MessageCreateEvent {
+                id: "1".to_string(),
+                channel_id: channel_id.to_string(),
+                guild_id: Some(10),
+                author: UserObject {
+                    id: "2".to_string(),
+                    username: "bob".to_string(),
+                    display_name: None,
+                    avatar_url: None,
+                },
+                content: "hello".to_string(),
+                tts: false,
+                flags: 0,
+                timestamp: "2024-12-17T00:00:00+00:00".to_string(),
+                edited_timestamp: None,
+                reply_to: None,
+            })
+        };
+
+        gateway.dispatch_to_viewers(message("50")).await.unwrap();
+        assert_eq!(events.recv().await.unwrap().target_users, Some(vec![1]));
+
+        // Other channels' messages, and events without content, go to the guild
+        gateway.dispatch_to_viewers(message("60")).await.unwrap();
+        assert_eq!(events.recv().await.unwrap().target_users, None);
+        gateway.dispatch_to_viewers(pins_update(10)).await.unwrap();
+        assert_eq!(events.recv().await.unwrap().target_users, None);
+    }
+
+    #[tokio::test]
+    async fn test_direct_sends_flag_a_client_that_stops_reading() {
+        let gateway = Gateway::new();
+        let (sender, mut rx) = OutboundQueue::new(1, std::time::Duration::from_millis(20));
+        let mut slow = sender.slow_client();
+        let (close, _) = watch::channel(None);
+        gateway.register_session("a".to_string(), 1, vec![10], sender, None, close);
+        let hello = GatewaySend::hello(1000);
+
+        gateway.send_to_guild(10, hello.clone()).await;
+        assert!(!*slow.borrow());
+
+        // The queue stays full past the timeout, so the session is flagged
+        gateway.send_to_user(1, hello.clone()).await;
+        assert!(*slow.borrow_and_update());
+        assert!(!gateway.send_to_session("a", hello).await);
+        assert!(rx.recv().await.is_some());
+    }
+
+    #[test]
+    fn test_user_update_goes_to_the_user_and_their_guilds() {
+        let gateway = Gateway::new();
+        register_in_guilds(&gateway, "a", 2, vec![10]);
+        register_in_guilds(&gateway, "b", 3, vec![20]);
+        let mut events = gateway.subscribe();
+
+        let event: GatewayEvent = serde_json::from_value(serde_json::json!({
+            "t": "USER_UPDATE",
+            "d": {
+                "id": "1",
+                "username": "renamed",
+                "display_name": null,
+                "avatar_url": null,
+                "banner_url": null,
+                "guild_ids": [10],
+            },
+        }))
+        .unwrap();
+        gateway.dispatch(event);
+
+        let routed = events.try_recv().unwrap();
+        assert_eq!(routed.target_users, Some(vec![2, 1]));
+        let sent = routed.event.to_json();
+        assert_eq!(sent["username"], "renamed");
+        assert!(sent.get("guild_ids").is_none());
+    }
+
+    #[test]
+    fn test_hidden_presence_is_broadcast_as_offline() {
+        use crate::domain::{PresenceVisibility, UserStatus};
+
+        let gateway = Gateway::new();
+        register_in_guilds(&gateway, "a", 1, vec![10]);
+        register_in_guilds(&gateway, "b", 2, vec![10]);
+        register_in_guilds(&gateway, "c", 3, vec![10]);
+        let mut events = gateway.subscribe();
+
+        let presence = UserPresence {
+            user_id: 1,
+            status: UserStatus::Online,
+            custom_status: Some("here".to_string()),
+            activities: Vec::new(),
+            last_seen: 0,
+            guild_ids: vec![10],
+            visibility: PresenceVisibility::None,
+        };
+        gateway.dispatch_presence(&presence, &[10]);
+
+        let status_of = |routed: RoutedEvent| match routed.event {
+            GatewayEvent::PresenceUpdate(e) => (routed.target_users, e.status, e.custom_status),
+            other => panic!("unexpected event {}", other.event_name()),
+        };
+        // Mutual guild members see the user offline...
+        assert_eq!(
+            status_of(events.try_recv().unwrap()),
+            (Some(vec![2, 3]), "offline".to_string(), None)
+        );
+        // ...while the user's own sessions see the real status
+        assert_eq!(
+            status_of(events.try_recv().unwrap()),
+            (Some(vec![1]), "online".to_string(), Some("here".to_string()))
+        );
+        assert!(events.try_recv().is_err());
+
+        // Shared presence goes to the whole guild as is
+        gateway.dispatch_presence(&UserPresence { visibility: PresenceVisibility::Everyone, ..presence }, &[10]);
+        assert_eq!(status_of(events.try_recv().unwrap()), (None, "online".to_string(), Some("here".to_string())));
+    }
+}
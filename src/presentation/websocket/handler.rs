@@ -5,29 +5,44 @@
 //! - Message size limits to prevent DoS
 //! - Connection timeout for identify
 //! - Heartbeat monitoring
+//! - Bounded outbound queues, closing clients that can't keep up
 
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use axum::{
     extract::{
-        ws::{Message, WebSocket, WebSocketUpgrade},
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade},
         State,
     },
     response::Response,
 };
-use futures::{SinkExt, StreamExt};
-use jsonwebtoken::{decode, DecodingKey, Validation};
+use futures::{Sink, SinkExt, StreamExt};
 use serde_json::json;
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, oneshot, watch};
+use tokio::task::JoinHandle;
 use tokio::time::{interval, timeout};
+use tracing::Instrument;
 use uuid::Uuid;
 
-use super::gateway::Gateway;
-use super::messages::{GatewaySend, HelloPayload, IdentifyPayload, OpCode, ReadyPayload};
+use super::backpressure::{OutboundQueue, SendFailure};
+use super::disconnect::{ConnectionGauge, DisconnectReason};
+use super::gateway::GatewayEvent;
+use super::messages::{
+    CloseCode, GatewayClose, GatewayDecodeError, GatewayReceive, GatewaySend, IdentifyPayload, OpCode,
+    ReadyPayload, VoiceStateUpdatePayload,
+};
 use super::session::SessionState;
-use crate::domain::{MemberRepository, UserRepository};
-use crate::infrastructure::repositories::{PgMemberRepository, PgUserRepository};
+use crate::domain::{MemberRepository, PresenceVisibility, UserRepository};
+use crate::application::services::auth_service::decode_token;
+use crate::application::services::{UpdateVoiceStateDto, VoiceService, VoiceServiceImpl};
+use crate::infrastructure::cache::{SessionPresence, UserProfileCache, VoiceStateCacheService};
+use crate::infrastructure::metrics::record_slow_client_disconnect;
+use crate::infrastructure::repositories::{
+    PgChannelRepository, PgMemberRepository, PgRoleRepository, PgServerRepository,
+    PgUserRepository,
+};
+use crate::presentation::middleware::auth::is_session_revoked;
 use crate::startup::AppState;
 
 /// JWT claims for token validation
@@ -36,6 +51,9 @@ struct Claims {
     sub: String,
     #[allow(dead_code)]
     exp: usize,
+    /// Auth session ID the token was issued for
+    #[serde(default)]
+    sid: Option<String>,
 }
 
 /// WebSocket upgrade handler with message size limits
@@ -49,13 +67,41 @@ pub async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) ->
 }
 
 /// Handle individual WebSocket connection
+///
+/// Everything logged for the connection is recorded in its
+/// `gateway_session` span.
 async fn handle_socket(socket: WebSocket, state: AppState) {
-    let session_id = Uuid::new_v4().to_string();
-    let mut session_state = SessionState::new(session_id.clone());
+    let session_state = SessionState::new(Uuid::new_v4().to_string());
+    let connected_at = session_state.connected_at;
+    let span = session_state.span();
+
+    async move {
+        // Uncounted when dropped, however the session ends
+        let _connected = ConnectionGauge::active("connected");
+
+        tracing::info!("Gateway session connected");
+        let reason = run_session(socket, state, session_state).await;
+        reason.record();
+        tracing::info!(
+            reason = reason.as_str(),
+            duration_ms = connected_at.elapsed().as_millis() as u64,
+            "Gateway session disconnected"
+        );
+    }
+    .instrument(span)
+    .await
+}
+
+/// Run a gateway session until the connection closes, returning why it ended
+async fn run_session(socket: WebSocket, state: AppState, mut session_state: SessionState) -> DisconnectReason {
+    let session_id = session_state.session_id.clone();
 
     // Get configured timeouts
     let identify_timeout_secs = state.settings.websocket.identify_timeout_secs;
-    let heartbeat_interval_ms = state.settings.websocket.heartbeat_interval_ms;
+    let heartbeat_interval_ms = jittered_heartbeat_interval(
+        state.settings.websocket.heartbeat_interval_ms,
+        rand::random(),
+    );
 
     tracing::debug!(
         session_id = %session_id,
@@ -66,55 +112,61 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
     // Split socket for concurrent read/write
     let (mut sender, mut receiver) = socket.split();
 
-    // Create channel for outgoing messages
-    let (tx, mut rx) = mpsc::unbounded_channel::<GatewaySend>();
-
-    // Send Hello message immediately with configured heartbeat interval
-    let hello_payload = match serde_json::to_value(HelloPayload {
-        heartbeat_interval: heartbeat_interval_ms,
-    }) {
-        Ok(v) => v,
-        Err(e) => {
-            tracing::error!("Failed to serialize HelloPayload: {}", e);
-            return;
-        }
-    };
+    // Create bounded channel for outgoing messages
+    let (tx, mut rx) = OutboundQueue::<GatewaySend>::new(
+        state.settings.websocket.outbound_queue_size,
+        Duration::from_secs(state.settings.websocket.slow_client_timeout_secs),
+    );
+    let mut slow_client = tx.slow_client();
 
-    let hello = GatewaySend {
-        op: OpCode::Hello as u8,
-        d: Some(hello_payload),
-        s: None,
-        t: None,
-    };
+    // Close frame sent ahead of anything still queued
+    let (close_now, mut close_now_rx) = oneshot::channel::<CloseFrame>();
+    let mut close_now = Some(close_now);
 
-    let hello_json = match serde_json::to_string(&hello) {
-        Ok(j) => j,
-        Err(e) => {
-            tracing::error!("Failed to serialize Hello message: {}", e);
-            return;
-        }
-    };
-
-    if let Err(e) = sender.send(Message::Text(hello_json.into())).await {
+    // Send Hello immediately, before the client identifies
+    if let Err(e) = send_hello(&mut sender, heartbeat_interval_ms).await {
         tracing::error!("Failed to send Hello: {}", e);
-        return;
+        return DisconnectReason::SocketError;
     }
 
     // Spawn task to forward messages from channel to WebSocket
-    let sender_task = tokio::spawn(async move {
-        while let Some(msg) = rx.recv().await {
-            let text = match serde_json::to_string(&msg) {
-                Ok(t) => t,
-                Err(e) => {
-                    tracing::error!("Failed to serialize message: {}", e);
-                    continue;
+    let sender_task = tokio::spawn(
+        async move {
+            loop {
+                let msg = tokio::select! {
+                    biased;
+                    frame = &mut close_now_rx => {
+                        if let Ok(frame) = frame {
+                            let _ = timeout(CLOSE_FLUSH_DELAY, sender.send(Message::Close(Some(frame)))).await;
+                        }
+                        break;
+                    }
+                    msg = rx.recv() => match msg {
+                        Some(msg) => msg,
+                        None => break,
+                    },
+                };
+
+                let text = match serde_json::to_string(&msg) {
+                    Ok(t) => t,
+                    Err(e) => {
+                        tracing::error!("Failed to serialize message: {}", e);
+                        continue;
+                    }
+                };
+                if sender.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+
+                // Reconnect and Invalid Session end the session
+                if let Some(frame) = close_frame(&msg) {
+                    let _ = sender.send(Message::Close(Some(frame))).await;
+                    break;
                 }
-            };
-            if sender.send(Message::Text(text.into())).await.is_err() {
-                break;
             }
         }
-    });
+        .in_current_span(),
+    );
 
     // Wait for Identify (with configured timeout)
     let identify_timeout = Duration::from_secs(identify_timeout_secs);
@@ -122,64 +174,56 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
         while let Some(msg) = receiver.next().await {
             match msg {
                 Ok(Message::Text(text)) => {
-                    if let Ok(payload) = serde_json::from_str::<serde_json::Value>(&text) {
-                        if payload.get("op").and_then(|v| v.as_u64())
-                            == Some(OpCode::Identify as u64)
-                        {
-                            if let Some(d) = payload.get("d") {
-                                if let Ok(identify) =
-                                    serde_json::from_value::<IdentifyPayload>(d.clone())
-                                {
-                                    return Some(identify);
-                                }
-                            }
+                    let handshake = GatewayReceive::decode(&text).and_then(|frame| {
+                        match frame.op {
+                            OpCode::Identify => frame.payload().map(|p| Some(Handshake::Identify(p))),
+                            OpCode::Resume => Ok(Some(Handshake::Resume)),
+                            _ => Ok(None),
                         }
+                    });
+
+                    match handshake {
+                        Ok(Some(handshake)) => return Ok(handshake),
+                        Ok(None) => {}
+                        Err(e) => report_decode_error(&tx, &session_id, &e).await,
                     }
                 }
-                Ok(Message::Close(_)) => return None,
-                Err(_) => return None,
+                Ok(Message::Close(_)) => return Err(DisconnectReason::ClientClosed),
+                Err(_) => return Err(DisconnectReason::SocketError),
                 _ => continue,
             }
         }
-        None
+        Err(DisconnectReason::ClientClosed)
     })
     .await;
 
     let identify = match identify_result {
-        Ok(Some(id)) => id,
-        Ok(None) => {
+        Ok(Ok(Handshake::Identify(identify))) => identify,
+        Ok(Ok(Handshake::Resume)) => {
+            // No event history is kept to replay, so every resume fails
+            tracing::debug!(session_id = %session_id, "Resume failed; client must identify");
+            end_session(&tx, sender_task, GatewayClose::InvalidSession { resumable: false }).await;
+            return DisconnectReason::ResumeFailed;
+        }
+        Ok(Err(reason)) => {
             tracing::debug!(session_id = %session_id, "Connection closed before Identify");
             sender_task.abort();
-            return;
+            return reason;
         }
         Err(_) => {
             tracing::debug!(session_id = %session_id, "Identify timeout");
-            let _ = tx.send(GatewaySend {
-                op: OpCode::InvalidSession as u8,
-                d: Some(json!(false)),
-                s: None,
-                t: None,
-            });
-            tokio::time::sleep(Duration::from_millis(100)).await;
-            sender_task.abort();
-            return;
+            end_session(&tx, sender_task, GatewayClose::InvalidSession { resumable: false }).await;
+            return DisconnectReason::IdentifyTimeout;
         }
     };
 
     // Validate token and get user
-    let user_id = match validate_token(&identify.token, &state).await {
-        Ok(id) => id,
+    let (user_id, auth_session_id) = match validate_token(&identify.token, &state).await {
+        Ok(validated) => validated,
         Err(e) => {
             tracing::debug!(session_id = %session_id, error = %e, "Invalid token");
-            let _ = tx.send(GatewaySend {
-                op: OpCode::InvalidSession as u8,
-                d: Some(json!(false)),
-                s: None,
-                t: None,
-            });
-            tokio::time::sleep(Duration::from_millis(100)).await;
-            sender_task.abort();
-            return;
+            end_session(&tx, sender_task, GatewayClose::InvalidSession { resumable: false }).await;
+            return DisconnectReason::AuthFailed;
         }
     };
 
@@ -188,21 +232,17 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
         Ok(data) => data,
         Err(e) => {
             tracing::error!(session_id = %session_id, error = %e, "Failed to get user data");
-            let _ = tx.send(GatewaySend {
-                op: OpCode::InvalidSession as u8,
-                d: Some(json!(false)),
-                s: None,
-                t: None,
-            });
-            tokio::time::sleep(Duration::from_millis(100)).await;
-            sender_task.abort();
-            return;
+            end_session(&tx, sender_task, GatewayClose::InvalidSession { resumable: false }).await;
+            return DisconnectReason::ServerError;
         }
     };
 
+    let _authenticated = ConnectionGauge::active("authenticated");
+
     // Update session state
     session_state.user_id = user_id;
     session_state.identified = true;
+    tracing::Span::current().record("user_id", user_id);
 
     // Extract guild IDs for session registration
     let guild_ids: Vec<i64> = guilds
@@ -215,11 +255,14 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
         .collect();
 
     // Register session with gateway
+    let (close, mut close_rx) = watch::channel(None);
     state.gateway.register_session(
         session_id.clone(),
         user_id,
         guild_ids,
         tx.clone(),
+        auth_session_id,
+        close,
     );
 
     // Send READY event
@@ -235,7 +278,7 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
             tracing::error!("Failed to serialize ReadyPayload: {}", e);
             state.gateway.unregister_session(&session_id);
             sender_task.abort();
-            return;
+            return DisconnectReason::ServerError;
         }
     };
 
@@ -246,10 +289,10 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
         t: Some("READY".to_string()),
     };
 
-    if tx.send(ready).is_err() {
+    if tx.send(ready).await.is_err() {
         state.gateway.unregister_session(&session_id);
         sender_task.abort();
-        return;
+        return DisconnectReason::SocketError;
     }
 
     tracing::info!(
@@ -261,41 +304,47 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
     // Subscribe to gateway events
     let mut event_rx = state.gateway.subscribe();
 
-    // Heartbeat check interval (heartbeat + grace period)
+    // Heartbeat check interval (the interval sent in Hello + grace period)
     let grace_period_ms = 10000_u64; // 10 second grace period
     let mut heartbeat_check = interval(Duration::from_millis(heartbeat_interval_ms + grace_period_ms));
     heartbeat_check.tick().await; // Skip first immediate tick
 
     // Main message loop
-    loop {
+    let reason = loop {
         tokio::select! {
             // Handle incoming messages
             msg = receiver.next() => {
                 match msg {
                     Some(Ok(Message::Text(text))) => {
-                        if let Err(e) = handle_message(
+                        match handle_message(
                             &text,
                             &mut session_state,
                             &tx,
-                            &state.gateway,
+                            &state,
                         ).await {
-                            tracing::debug!(
-                                session_id = %session_id,
-                                error = %e,
-                                "Error handling message"
-                            );
+                            Ok(()) => {}
+                            Err(FrameError::Decode(e)) => {
+                                report_decode_error(&tx, &session_id, &e).await;
+                            }
+                            Err(e) => {
+                                tracing::debug!(
+                                    session_id = %session_id,
+                                    error = %e,
+                                    "Error handling message"
+                                );
+                            }
                         }
                     }
                     Some(Ok(Message::Close(_))) | None => {
                         tracing::debug!(session_id = %session_id, "Connection closed");
-                        break;
+                        break DisconnectReason::ClientClosed;
                     }
                     Some(Ok(Message::Ping(_))) => {
                         // Pong is handled automatically by axum
                     }
                     Some(Err(e)) => {
                         tracing::debug!(session_id = %session_id, error = %e, "WebSocket error");
-                        break;
+                        break DisconnectReason::SocketError;
                     }
                     _ => {}
                 }
@@ -329,8 +378,12 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
                                 s: Some(sequence),
                                 t: Some(routed_event.event.event_name().to_string()),
                             };
-                            if tx.send(dispatch).is_err() {
-                                break;
+                            match tx.send(dispatch).await {
+                                Ok(()) => {}
+                                Err(SendFailure::Closed) => break DisconnectReason::SocketError,
+                                Err(SendFailure::SlowClient) => {
+                                    break close_slow_client(&session_id, &mut close_now).await;
+                                }
                             }
                         }
                     }
@@ -343,11 +396,31 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
                     }
                     Err(broadcast::error::RecvError::Closed) => {
                         tracing::error!("Gateway event channel closed");
-                        break;
+                        break DisconnectReason::ServerError;
                     }
                 }
             }
 
+            // Auth session revoked elsewhere, or the server is shutting down
+            Ok(()) = close_rx.changed() => {
+                let Some(reason) = *close_rx.borrow_and_update() else {
+                    continue;
+                };
+                tracing::info!(
+                    session_id = %session_id,
+                    reason = ?reason,
+                    "Server closing connection"
+                );
+                let _ = tx.send(reason.to_gateway_send()).await;
+                tokio::time::sleep(CLOSE_FLUSH_DELAY).await;
+                break DisconnectReason::from(reason);
+            }
+
+            // A send anywhere gave up on a client that stopped reading
+            Ok(()) = slow_client.changed() => {
+                break close_slow_client(&session_id, &mut close_now).await;
+            }
+
             // Check heartbeat timeout
             _ = heartbeat_check.tick() => {
                 let timeout_ms = heartbeat_interval_ms + grace_period_ms;
@@ -356,68 +429,207 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
                         session_id = %session_id,
                         "Heartbeat timeout, closing connection"
                     );
-                    break;
+                    break DisconnectReason::HeartbeatTimeout;
                 }
             }
         }
-    }
+    };
 
     // Cleanup
+    let guild_ids = state.gateway.get_session_guilds(&session_id).unwrap_or_default();
     state.gateway.unregister_session(&session_id);
     sender_task.abort();
 
+    let visibility = presence_visibility(user_id, &state).await;
+    match state
+        .session_cache
+        .remove_session_presence(user_id, &session_id, guild_ids.clone(), visibility)
+        .await
+    {
+        Ok(effective) => state.gateway.dispatch_presence(&effective, &guild_ids),
+        Err(e) => {
+            tracing::warn!(session_id = %session_id, error = %e, "Failed to clear session presence")
+        }
+    }
+
+    match voice_service(&state)
+        .disconnect_session(user_id, &session_id, &guild_ids)
+        .await
+    {
+        Ok(cleared) => {
+            for voice_state in cleared {
+                state
+                    .gateway
+                    .dispatch(GatewayEvent::VoiceStateUpdate(voice_state.into()));
+            }
+        }
+        Err(e) => {
+            tracing::warn!(session_id = %session_id, error = %e, "Failed to clear voice state")
+        }
+    }
+
     tracing::info!(
         user_id = user_id,
         session_id = %session_id,
         "User disconnected"
     );
+
+    reason
+}
+
+/// How long the sender task gets to flush the final message and close frame
+const CLOSE_FLUSH_DELAY: Duration = Duration::from_millis(100);
+
+/// Largest share of the configured heartbeat interval taken off per connection
+const HEARTBEAT_JITTER: f64 = 0.1;
+
+/// Heartbeat interval for one connection.
+///
+/// Connections get slightly shorter intervals than configured, spread by
+/// `jitter` (in `[0, 1)`), so clients that connected together don't keep
+/// heartbeating in lockstep.
+fn jittered_heartbeat_interval(configured_ms: u64, jitter: f64) -> u64 {
+    let reduction = configured_ms as f64 * HEARTBEAT_JITTER * jitter.clamp(0.0, 1.0);
+    configured_ms - reduction as u64
+}
+
+/// Send the Hello message that opens every connection
+async fn send_hello<S>(sender: &mut S, heartbeat_interval_ms: u64) -> Result<(), S::Error>
+where
+    S: Sink<Message> + Unpin,
+{
+    let hello = serde_json::to_string(&GatewaySend::hello(heartbeat_interval_ms))
+        .expect("Hello serializes to JSON");
+
+    sender.send(Message::Text(hello.into())).await
+}
+
+/// First session frame from the client
+enum Handshake {
+    Identify(IdentifyPayload),
+    Resume,
+}
+
+/// Close frame that follows a message, if the message ends the session
+fn close_frame(message: &GatewaySend) -> Option<CloseFrame> {
+    GatewayClose::from_gateway_send(message).map(|close| CloseFrame {
+        code: close.close_code() as u16,
+        reason: close.reason().into(),
+    })
+}
+
+/// Close frame for a client that fell too far behind reading its events
+fn slow_client_close_frame() -> CloseFrame {
+    CloseFrame {
+        code: CloseCode::SessionTimedOut as u16,
+        reason: "Session timed out".into(),
+    }
+}
+
+/// Close a client that fell too far behind reading its messages, ahead of
+/// anything still queued
+async fn close_slow_client(
+    session_id: &str,
+    close_now: &mut Option<oneshot::Sender<CloseFrame>>,
+) -> DisconnectReason {
+    tracing::warn!(session_id = %session_id, "Client can't keep up with its events, closing connection");
+    record_slow_client_disconnect();
+    if let Some(close_now) = close_now.take() {
+        let _ = close_now.send(slow_client_close_frame());
+    }
+    tokio::time::sleep(CLOSE_FLUSH_DELAY).await;
+    DisconnectReason::SlowClient
+}
+
+/// End a session before it was registered with the gateway
+async fn end_session(
+    tx: &OutboundQueue<GatewaySend>,
+    sender_task: JoinHandle<()>,
+    close: GatewayClose,
+) {
+    let _ = tx.send(close.to_gateway_send()).await;
+    tokio::time::sleep(CLOSE_FLUSH_DELAY).await;
+    sender_task.abort();
+}
+
+/// Errors from handling a client frame
+#[derive(Debug, thiserror::Error)]
+enum FrameError {
+    /// Malformed frame, reported back to the client
+    #[error(transparent)]
+    Decode(#[from] GatewayDecodeError),
+
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl From<String> for FrameError {
+    fn from(message: String) -> Self {
+        Self::Internal(message)
+    }
+}
+
+/// Tell the client its frame could not be decoded, keeping the connection open
+async fn report_decode_error(
+    tx: &OutboundQueue<GatewaySend>,
+    session_id: &str,
+    error: &GatewayDecodeError,
+) {
+    tracing::debug!(session_id = %session_id, error = %error, "Invalid gateway frame");
+    let _ = tx.send(error.to_gateway_send()).await;
 }
 
 /// Handle incoming WebSocket message
 async fn handle_message(
     text: &str,
     session_state: &mut SessionState,
-    tx: &mpsc::UnboundedSender<GatewaySend>,
-    _gateway: &Arc<Gateway>,
-) -> Result<(), String> {
-    let payload: serde_json::Value =
-        serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    tx: &OutboundQueue<GatewaySend>,
+    state: &AppState,
+) -> Result<(), FrameError> {
+    let frame = GatewayReceive::decode(text)?;
 
-    let op = payload
-        .get("op")
-        .and_then(|v| v.as_u64())
-        .ok_or("Missing op field")?;
+    if frame.op != OpCode::Heartbeat {
+        if let Some(suppressed) = session_state.allow_op_log(Instant::now()) {
+            tracing::debug!(op = ?frame.op, suppressed, "Gateway op received");
+        }
+    }
 
-    match op {
-        op if op == OpCode::Heartbeat as u64 => {
+    match frame.op {
+        OpCode::Heartbeat => {
             session_state.heartbeat();
-            let _ = tx.send(GatewaySend {
-                op: OpCode::HeartbeatAck as u8,
-                d: None,
-                s: None,
-                t: None,
-            });
+            let _ = tx
+                .send(GatewaySend {
+                    op: OpCode::HeartbeatAck as u8,
+                    d: None,
+                    s: None,
+                    t: None,
+                })
+                .await;
             tracing::trace!(
                 session_id = %session_state.session_id,
                 "Heartbeat received"
             );
         }
 
-        op if op == OpCode::PresenceUpdate as u64 => {
+        OpCode::PresenceUpdate => {
             // Handle presence update
-            if let Some(d) = payload.get("d") {
-                tracing::debug!(
-                    session_id = %session_state.session_id,
-                    presence = ?d,
-                    "Presence update"
-                );
-                // TODO: Broadcast presence update to guild members
-            }
+            let presence: SessionPresence = frame.payload()?;
+            tracing::debug!(
+                session_id = %session_state.session_id,
+                presence = ?presence,
+                "Presence update"
+            );
+            update_presence(session_state, &presence, state).await?;
         }
 
-        op if op == OpCode::RequestGuildMembers as u64 => {
+        OpCode::VoiceStateUpdate => {
+            let update: VoiceStateUpdatePayload = frame.payload()?;
+            update_voice_state(session_state, update, state).await?;
+        }
+
+        OpCode::RequestGuildMembers => {
             // Handle guild members request
-            if let Some(d) = payload.get("d") {
+            if let Some(d) = &frame.d {
                 tracing::debug!(
                     session_id = %session_state.session_id,
                     request = ?d,
@@ -427,7 +639,7 @@ async fn handle_message(
             }
         }
 
-        op if op == OpCode::Resume as u64 => {
+        OpCode::Resume => {
             // Handle resume (reconnection with sequence number)
             tracing::debug!(
                 session_id = %session_state.session_id,
@@ -436,34 +648,132 @@ async fn handle_message(
             // TODO: Implement session resumption
         }
 
-        _ => {
+        OpCode::Identify => {
             tracing::debug!(
                 session_id = %session_state.session_id,
-                op = op,
-                "Unknown opcode"
+                "Ignoring Identify on an identified session"
             );
         }
+
+        // Server-only opcodes are rejected by `GatewayReceive::decode`
+        op => return Err(GatewayDecodeError::UnknownOpcode(op as u64).into()),
     }
 
     Ok(())
 }
 
-/// Validate JWT token and return user ID
-async fn validate_token(token: &str, state: &AppState) -> Result<i64, String> {
-    let secret = &state.settings.jwt.secret;
+/// Store a session's presence and broadcast the user's effective presence
+async fn update_presence(
+    session_state: &SessionState,
+    presence: &SessionPresence,
+    state: &AppState,
+) -> Result<(), String> {
+    let guild_ids = state
+        .gateway
+        .get_session_guilds(&session_state.session_id)
+        .unwrap_or_default();
+
+    let visibility = presence_visibility(session_state.user_id, state).await;
+    let effective = state
+        .session_cache
+        .set_presence(
+            session_state.user_id,
+            &session_state.session_id,
+            presence,
+            guild_ids.clone(),
+            visibility,
+        )
+        .await
+        .map_err(|e| format!("Failed to set presence: {}", e))?;
+
+    state.gateway.dispatch_presence(&effective, &guild_ids);
+    Ok(())
+}
 
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &Validation::default(),
+/// Build a voice service for the gateway
+fn voice_service(
+    state: &AppState,
+) -> VoiceServiceImpl<PgServerRepository, PgChannelRepository, PgMemberRepository, PgRoleRepository>
+{
+    VoiceServiceImpl::new(
+        Arc::new(PgServerRepository::new(state.db.clone())),
+        Arc::new(PgChannelRepository::new(state.db.clone())),
+        Arc::new(PgMemberRepository::new(state.db.clone())),
+        Arc::new(PgRoleRepository::new(state.db.clone())),
+        VoiceStateCacheService::new(state.redis.clone()),
     )
-    .map_err(|e| format!("Invalid token: {}", e))?;
+}
+
+/// Apply a session's voice state update and fan it out to the guild
+async fn update_voice_state(
+    session_state: &SessionState,
+    update: VoiceStateUpdatePayload,
+    state: &AppState,
+) -> Result<(), String> {
+    let server_id: i64 = update
+        .guild_id
+        .parse()
+        .map_err(|_| "Invalid guild ID".to_string())?;
+    let channel_id = match update.channel_id {
+        Some(id) => Some(id.parse::<i64>().map_err(|_| "Invalid channel ID".to_string())?),
+        None => None,
+    };
+
+    let request = UpdateVoiceStateDto {
+        server_id,
+        channel_id,
+        self_mute: update.self_mute,
+        self_deaf: update.self_deaf,
+    };
+
+    let voice_state = voice_service(state)
+        .update_own_state(session_state.user_id, &session_state.session_id, request)
+        .await
+        .map_err(|e| format!("Voice state update rejected: {}", e))?;
+
+    state
+        .gateway
+        .dispatch(GatewayEvent::VoiceStateUpdate(voice_state.into()));
+    Ok(())
+}
+
+/// Who may see a user's presence, read from their cached profile, which is
+/// dropped whenever they change it.
+///
+/// If the setting can't be loaded the presence is hidden, so a failure
+/// never reveals a user who chose to appear offline.
+async fn presence_visibility(user_id: i64, state: &AppState) -> PresenceVisibility {
+    let repo = PgUserRepository::new(state.db.clone());
+    match UserProfileCache::new(state.redis.clone()).get_or_load(&repo, user_id).await {
+        Ok(Some(profile)) => profile.presence_visibility,
+        Ok(None) => PresenceVisibility::None,
+        Err(e) => {
+            tracing::warn!(user_id, error = %e, "Failed to load presence visibility");
+            PresenceVisibility::None
+        }
+    }
+}
+
+/// Validate JWT token and return user ID and auth session ID
+async fn validate_token(
+    token: &str,
+    state: &AppState,
+) -> Result<(i64, Option<String>), String> {
+    let token_data = decode_token::<Claims>(&state.settings.jwt, token)
+        .map_err(|e| format!("Invalid token: {}", e))?;
 
-    token_data
+    let user_id = token_data
         .claims
         .sub
         .parse::<i64>()
-        .map_err(|e| format!("Invalid user ID in token: {}", e))
+        .map_err(|e| format!("Invalid user ID in token: {}", e))?;
+
+    let auth_session_id = token_data.claims.sid;
+    if is_session_revoked(state, auth_session_id.as_deref()).await {
+        return Err("Session revoked".to_string());
+    }
+
+    Ok((user_id, auth_session_id))
 }
 
 /// Get user info and guilds for READY payload
@@ -508,3 +818,59 @@ async fn get_user_data(
 
     Ok((user_info, guild_values))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_code(close: GatewayClose) -> Option<u16> {
+        close_frame(&close.to_gateway_send()).map(|frame| frame.code)
+    }
+
+    #[test]
+    fn test_close_path_emits_code_for_each_scenario() {
+        // Graceful shutdown
+        assert_eq!(close_code(GatewayClose::Reconnect), Some(4000));
+        // Failed resume, revoked session, rejected token
+        assert_eq!(close_code(GatewayClose::InvalidSession { resumable: false }), Some(4009));
+        assert_eq!(close_code(GatewayClose::InvalidSession { resumable: true }), Some(4008));
+        // Client too slow to read its events
+        assert_eq!(slow_client_close_frame().code, 4010);
+    }
+
+    #[test]
+    fn test_other_messages_keep_connection_open() {
+        let ack = GatewaySend {
+            op: OpCode::HeartbeatAck as u8,
+            d: None,
+            s: None,
+            t: None,
+        };
+        assert!(close_frame(&ack).is_none());
+        assert!(close_frame(&GatewayDecodeError::UnknownOpcode(42).to_gateway_send()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_first_frame_is_hello_with_interval() {
+        let (mut sender, mut frames) = futures::channel::mpsc::unbounded::<Message>();
+
+        send_hello(&mut sender, 45_000).await.unwrap();
+
+        let Some(Message::Text(text)) = frames.next().await else {
+            panic!("expected a text frame");
+        };
+        let hello: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(hello["op"], OpCode::Hello as u8);
+        assert_eq!(hello["d"]["heartbeat_interval"], 45_000);
+    }
+
+    #[test]
+    fn test_heartbeat_interval_jitter_stays_below_configured() {
+        assert_eq!(jittered_heartbeat_interval(45_000, 0.0), 45_000);
+        assert_eq!(jittered_heartbeat_interval(45_000, 0.5), 42_750);
+        assert!(jittered_heartbeat_interval(45_000, 0.999) > 40_500);
+
+        // Out-of-range jitter is clamped
+        assert_eq!(jittered_heartbeat_interval(45_000, 2.0), 40_500);
+    }
+}
@@ -1,11 +1,29 @@
 //! WebSocket Message Types
 //!
 //! Discord-compatible gateway message formats.
+//!
+//! # Closing sessions
+//!
+//! When the server ends a session it first sends an opcode telling the
+//! client what to do next, then closes the socket with a matching
+//! [`CloseCode`]:
+//!
+//! | Reason | Message | Close code | Client should |
+//! |---|---|---|---|
+//! | Server restart or migration | op 7 `Reconnect` | 4000 | reconnect and Resume |
+//! | Invalid session, resumable | op 9 `InvalidSession`, `d: true` | 4008 | reconnect and Resume |
+//! | Invalid session | op 9 `InvalidSession`, `d: false` | 4009 | reconnect and Identify |
+//!
+//! Sessions are invalid when the token is rejected or revoked, Identify
+//! times out, or a Resume fails.
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
 /// Gateway opcodes
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "u8")]
 #[repr(u8)]
 pub enum OpCode {
     /// Event dispatch
@@ -30,17 +48,204 @@ pub enum OpCode {
     Hello = 10,
     /// Heartbeat ACK
     HeartbeatAck = 11,
+    /// Recoverable protocol error (sent by the server, connection stays open)
+    ProtocolError = 12,
+}
+
+impl OpCode {
+    /// Whether clients may send this opcode
+    pub fn is_client_op(self) -> bool {
+        matches!(
+            self,
+            Self::Heartbeat
+                | Self::Identify
+                | Self::PresenceUpdate
+                | Self::VoiceStateUpdate
+                | Self::Resume
+                | Self::RequestGuildMembers
+        )
+    }
+}
+
+impl TryFrom<u8> for OpCode {
+    type Error = u8;
+
+    fn try_from(op: u8) -> Result<Self, u8> {
+        Ok(match op {
+            0 => Self::Dispatch,
+            1 => Self::Heartbeat,
+            2 => Self::Identify,
+            3 => Self::PresenceUpdate,
+            4 => Self::VoiceStateUpdate,
+            6 => Self::Resume,
+            7 => Self::Reconnect,
+            8 => Self::RequestGuildMembers,
+            9 => Self::InvalidSession,
+            10 => Self::Hello,
+            11 => Self::HeartbeatAck,
+            12 => Self::ProtocolError,
+            op => return Err(op),
+        })
+    }
+}
+
+/// Close codes sent when the server ends a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u16)]
+pub enum CloseCode {
+    /// Server is going away; resume on a new connection
+    Reconnect = 4000,
+    /// Session is invalid but may be resumed
+    InvalidSessionResumable = 4008,
+    /// Session is invalid; identify again
+    InvalidSession = 4009,
+    /// Client fell too far behind reading its events; identify again
+    SessionTimedOut = 4010,
+}
+
+/// Why the server is ending a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GatewayClose {
+    /// The client should reconnect and resume (op 7)
+    Reconnect,
+    /// The session is invalid (op 9); the client may resume only if
+    /// `resumable` is set and must identify again otherwise
+    InvalidSession { resumable: bool },
+}
+
+impl GatewayClose {
+    /// Close code for the socket
+    pub fn close_code(self) -> CloseCode {
+        match self {
+            Self::Reconnect => CloseCode::Reconnect,
+            Self::InvalidSession { resumable: true } => CloseCode::InvalidSessionResumable,
+            Self::InvalidSession { resumable: false } => CloseCode::InvalidSession,
+        }
+    }
+
+    /// Close reason for the socket
+    pub fn reason(self) -> &'static str {
+        match self {
+            Self::Reconnect => "Reconnect",
+            Self::InvalidSession { .. } => "Invalid session",
+        }
+    }
+
+    /// Build the message sent to the client before closing
+    pub fn to_gateway_send(self) -> GatewaySend {
+        let (op, d) = match self {
+            Self::Reconnect => (OpCode::Reconnect, None),
+            Self::InvalidSession { resumable } => (OpCode::InvalidSession, Some(json!(resumable))),
+        };
+
+        GatewaySend {
+            op: op as u8,
+            d,
+            s: None,
+            t: None,
+        }
+    }
+
+    /// The close a message announces, if it ends the session
+    pub fn from_gateway_send(message: &GatewaySend) -> Option<Self> {
+        match OpCode::try_from(message.op).ok()? {
+            OpCode::Reconnect => Some(Self::Reconnect),
+            OpCode::InvalidSession => Some(Self::InvalidSession {
+                resumable: message.d.as_ref().and_then(|d| d.as_bool()).unwrap_or(false),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Errors decoding a client frame
+///
+/// These are recoverable: the client is told what went wrong with an
+/// [`OpCode::ProtocolError`] message and the connection stays open.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum GatewayDecodeError {
+    /// Not valid JSON or not a gateway frame
+    #[error("Invalid frame: {0}")]
+    InvalidFrame(String),
+
+    /// Opcode is unknown or may not be sent by clients
+    #[error("Unknown opcode: {0}")]
+    UnknownOpcode(u64),
+
+    /// Frame is well formed but `d` does not match the opcode
+    #[error("Invalid payload for opcode {}: {reason}", *.op as u8)]
+    InvalidPayload { op: OpCode, reason: String },
+}
+
+impl GatewayDecodeError {
+    /// Error code sent to the client
+    pub fn code(&self) -> u16 {
+        match self {
+            Self::UnknownOpcode(_) => 4001,
+            Self::InvalidFrame(_) => 4002,
+            Self::InvalidPayload { .. } => 4003,
+        }
+    }
+
+    /// Build the error message sent to the client
+    pub fn to_gateway_send(&self) -> GatewaySend {
+        GatewaySend {
+            op: OpCode::ProtocolError as u8,
+            d: Some(json!({
+                "code": self.code(),
+                "message": self.to_string(),
+            })),
+            s: None,
+            t: None,
+        }
+    }
 }
 
 /// Incoming gateway message
 #[derive(Debug, Deserialize)]
 pub struct GatewayReceive {
-    pub op: u8,
+    pub op: OpCode,
     pub d: Option<serde_json::Value>,
     pub s: Option<u64>,
     pub t: Option<String>,
 }
 
+impl GatewayReceive {
+    /// Decode a client text frame, validating the opcode
+    pub fn decode(text: &str) -> Result<Self, GatewayDecodeError> {
+        let value: serde_json::Value = serde_json::from_str(text)
+            .map_err(|e| GatewayDecodeError::InvalidFrame(e.to_string()))?;
+
+        let op = value
+            .get("op")
+            .ok_or_else(|| GatewayDecodeError::InvalidFrame("missing field `op`".to_string()))?
+            .as_u64()
+            .ok_or_else(|| {
+                GatewayDecodeError::InvalidFrame("`op` must be an unsigned integer".to_string())
+            })?;
+
+        let valid = u8::try_from(op)
+            .ok()
+            .and_then(|op| OpCode::try_from(op).ok())
+            .is_some_and(OpCode::is_client_op);
+        if !valid {
+            return Err(GatewayDecodeError::UnknownOpcode(op));
+        }
+
+        serde_json::from_value(value).map_err(|e| GatewayDecodeError::InvalidFrame(e.to_string()))
+    }
+
+    /// Deserialize the `d` payload for this frame's opcode
+    pub fn payload<T: DeserializeOwned>(&self) -> Result<T, GatewayDecodeError> {
+        let d = self.d.clone().unwrap_or(serde_json::Value::Null);
+
+        serde_json::from_value(d).map_err(|e| GatewayDecodeError::InvalidPayload {
+            op: self.op,
+            reason: e.to_string(),
+        })
+    }
+}
+
 /// Outgoing gateway message
 #[derive(Debug, Clone, Serialize)]
 pub struct GatewaySend {
@@ -53,9 +258,25 @@ pub struct GatewaySend {
     pub t: Option<String>,
 }
 
+impl GatewaySend {
+    /// Hello (op 10), the first message of every connection, telling the
+    /// client how often to heartbeat
+    pub fn hello(heartbeat_interval_ms: u64) -> Self {
+        Self {
+            op: OpCode::Hello as u8,
+            d: Some(json!(HelloPayload {
+                heartbeat_interval: heartbeat_interval_ms,
+            })),
+            s: None,
+            t: None,
+        }
+    }
+}
+
 /// Hello payload (op 10)
 #[derive(Debug, Serialize)]
 pub struct HelloPayload {
+    /// Milliseconds between client heartbeats
     pub heartbeat_interval: u64,
 }
 
@@ -85,3 +306,96 @@ pub struct IdentifyProperties {
     pub browser: String,
     pub device: String,
 }
+
+/// Voice state update payload (op 4)
+#[derive(Debug, Deserialize)]
+pub struct VoiceStateUpdatePayload {
+    pub guild_id: String,
+    /// Voice channel to join (null to leave)
+    pub channel_id: Option<String>,
+    #[serde(default)]
+    pub self_mute: bool,
+    #[serde(default)]
+    pub self_deaf: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_malformed_json_yields_error_op() {
+        let error = GatewayReceive::decode("{\"op\": 1,").unwrap_err();
+        assert!(matches!(error, GatewayDecodeError::InvalidFrame(_)));
+
+        let message = error.to_gateway_send();
+        assert_eq!(message.op, OpCode::ProtocolError as u8);
+        let d = message.d.unwrap();
+        assert_eq!(d["code"], 4002);
+        assert!(d["message"].as_str().unwrap().starts_with("Invalid frame"));
+
+        let error = GatewayReceive::decode("{\"d\": null}").unwrap_err();
+        assert!(matches!(error, GatewayDecodeError::InvalidFrame(_)));
+    }
+
+    #[test]
+    fn test_unknown_opcode_reported() {
+        let error = GatewayReceive::decode(r#"{"op": 42, "d": null}"#).unwrap_err();
+        assert_eq!(error, GatewayDecodeError::UnknownOpcode(42));
+        assert_eq!(error.to_gateway_send().d.unwrap()["code"], 4001);
+
+        // Server-only opcodes are not accepted from clients
+        let error = GatewayReceive::decode(r#"{"op": 10}"#).unwrap_err();
+        assert_eq!(error, GatewayDecodeError::UnknownOpcode(10));
+    }
+
+    #[test]
+    fn test_invalid_payload_for_opcode() {
+        let frame = GatewayReceive::decode(r#"{"op": 4, "d": {"self_mute": true}}"#).unwrap();
+        assert_eq!(frame.op, OpCode::VoiceStateUpdate);
+
+        let error = frame.payload::<VoiceStateUpdatePayload>().unwrap_err();
+        assert!(matches!(
+            error,
+            GatewayDecodeError::InvalidPayload { op: OpCode::VoiceStateUpdate, .. }
+        ));
+        assert_eq!(error.code(), 4003);
+    }
+
+    #[test]
+    fn test_close_messages_round_trip() {
+        let reconnect = GatewayClose::Reconnect.to_gateway_send();
+        assert_eq!(serde_json::to_value(&reconnect).unwrap(), json!({"op": 7}));
+
+        let invalid = GatewayClose::InvalidSession { resumable: false }.to_gateway_send();
+        assert_eq!(serde_json::to_value(&invalid).unwrap(), json!({"op": 9, "d": false}));
+
+        for close in [
+            GatewayClose::Reconnect,
+            GatewayClose::InvalidSession { resumable: true },
+            GatewayClose::InvalidSession { resumable: false },
+        ] {
+            assert_eq!(GatewayClose::from_gateway_send(&close.to_gateway_send()), Some(close));
+        }
+
+        let ack = GatewaySend { op: OpCode::HeartbeatAck as u8, d: None, s: None, t: None };
+        assert_eq!(GatewayClose::from_gateway_send(&ack), None);
+    }
+
+    #[test]
+    fn test_hello_carries_heartbeat_interval() {
+        let hello = GatewaySend::hello(41_250);
+
+        assert_eq!(
+            serde_json::to_value(&hello).unwrap(),
+            json!({"op": 10, "d": {"heartbeat_interval": 41_250}})
+        );
+    }
+
+    #[test]
+    fn test_valid_frame_decodes() {
+        let frame = GatewayReceive::decode(r#"{"op": 1, "d": 7}"#).unwrap();
+        assert_eq!(frame.op, OpCode::Heartbeat);
+        assert_eq!(frame.payload::<u64>().unwrap(), 7);
+    }
+}
@@ -2,12 +2,22 @@
 //!
 //! Real-time communication via WebSocket connections.
 
+pub mod backpressure;
+pub mod disconnect;
 pub mod gateway;
 pub mod handler;
 pub mod messages;
+pub mod outbox_relay;
 pub mod session;
 
-pub use gateway::{Gateway, GatewayEvent, RoutedEvent};
+pub use gateway::{
+    ChannelOverrideUpdateEvent, ChannelPinsUpdateEvent, Gateway, GatewayEvent,
+    GuildMemberAddEvent, GuildMemberRemoveEvent, GuildMemberUpdateEvent, GuildRoleUpdateEvent,
+    GuildUpdateEvent,
+    RoutedEvent, UserObject, UserUpdateEvent, VoiceStateUpdateEvent,
+};
+pub use disconnect::DisconnectReason;
 pub use handler::ws_handler;
-pub use messages::{GatewayReceive, GatewaySend, OpCode};
+pub use outbox_relay::{spawn_subscriber, EventPublisher, OutboxRelay, PubSubPublisher};
+pub use messages::{CloseCode, GatewayClose, GatewayDecodeError, GatewayReceive, GatewaySend, OpCode};
 pub use session::SessionState;
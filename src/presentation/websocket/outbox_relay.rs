@@ -0,0 +1,533 @@
+//! Outbox Relay
+//!
+//! Background task publishing gateway events from the event outbox.
+//!
+//! Events are written to the outbox in the same transaction as the change
+//! they describe, so a crash between commit and publish no longer loses
+//! them: the relay claims unsent rows, publishes them and marks them sent.
+//! Every instance runs a relay; claims keep them from publishing the same
+//! row, and events go out over Redis pub/sub so each instance's
+//! [`spawn_subscriber`] hands them to its local gateway.
+//!
+//! Delivery is at-least-once; dedup keys of recently published events are
+//! remembered so a batch that could not be marked sent is not published a
+//! second time by the same relay. Sent rows are deleted after a retention
+//! period.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::StreamExt;
+use tokio::task::JoinHandle;
+
+use super::gateway::{Gateway, GatewayEvent};
+use crate::infrastructure::cache::Cache;
+use crate::infrastructure::repositories::{OutboxEvent, OutboxRepository};
+use crate::shared::error::AppError;
+
+/// Events fetched per relay pass
+pub const DEFAULT_BATCH_SIZE: i64 = 100;
+
+/// Delay between passes when the outbox is drained
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long a relay holds its claim on a batch before others may take it
+pub const DEFAULT_CLAIM_LEASE: Duration = Duration::from_secs(30);
+
+/// How long sent events are kept before the retention sweep deletes them
+pub const DEFAULT_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Delay between retention sweeps
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Pub/sub channel relayed events are published on
+pub const GATEWAY_EVENTS_CHANNEL: &str = "gateway:events";
+
+/// Delay before resubscribing after the pub/sub connection drops
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+/// Published dedup keys remembered by the relay
+const RECENT_KEYS_CAPACITY: usize = 10_000;
+
+/// Destination for relayed events
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    async fn publish(&self, event: &GatewayEvent) -> Result<(), AppError>;
+}
+
+/// Publishes events on [`GATEWAY_EVENTS_CHANNEL`] for every instance's
+/// subscriber to pick up.
+pub struct PubSubPublisher<C: Cache> {
+    cache: C,
+}
+
+impl<C: Cache> PubSubPublisher<C> {
+    pub fn new(cache: C) -> Self {
+        Self { cache }
+    }
+}
+
+#[async_trait]
+impl<C: Cache> EventPublisher for PubSubPublisher<C> {
+    async fn publish(&self, event: &GatewayEvent) -> Result<(), AppError> {
+        let message = serde_json::to_string(event).map_err(|e| AppError::Internal(e.to_string()))?;
+        self.cache.publish(GATEWAY_EVENTS_CHANNEL, &message).await?;
+        Ok(())
+    }
+}
+
+/// Decode an outbox row into the gateway event it describes.
+pub fn decode_event(event: &OutboxEvent) -> Result<GatewayEvent, serde_json::Error> {
+    serde_json::from_value(serde_json::json!({
+        "t": event.event_type,
+        "d": event.payload,
+    }))
+}
+
+/// Hand an event received over pub/sub to the local gateway.
+///
+/// An event whose recipients can't be resolved is dropped rather than
+/// sent to members who may not be allowed to see it.
+async fn dispatch_published(gateway: &Gateway, message: &str) {
+    let event = match serde_json::from_str::<GatewayEvent>(message) {
+        Ok(event) => event,
+        Err(e) => {
+            tracing::error!(error = %e, "Dropping undecodable published event");
+            return;
+        }
+    };
+    if let Err(e) = gateway.dispatch_to_viewers(event).await {
+        tracing::error!(error = %e, "Dropping published event whose recipients could not be resolved");
+    }
+}
+
+/// Subscribe to [`GATEWAY_EVENTS_CHANNEL`] and dispatch every event
+/// published there to `gateway`, resubscribing if the connection drops.
+pub fn spawn_subscriber(client: redis::Client, gateway: Arc<Gateway>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            match client.get_async_pubsub().await {
+                Ok(mut pubsub) => match pubsub.subscribe(GATEWAY_EVENTS_CHANNEL).await {
+                    Ok(()) => {
+                        let mut messages = pubsub.on_message();
+                        while let Some(message) = messages.next().await {
+                            match message.get_payload::<String>() {
+                                Ok(payload) => dispatch_published(&gateway, &payload).await,
+                                Err(e) => tracing::error!(error = %e, "Unreadable published event"),
+                            }
+                        }
+                        tracing::warn!("Gateway event subscription closed");
+                    }
+                    Err(e) => tracing::warn!(error = %e, "Failed to subscribe to gateway events"),
+                },
+                Err(e) => tracing::warn!(error = %e, "Failed to open gateway event subscription"),
+            }
+            tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+        }
+    })
+}
+
+/// Bounded set of recently published dedup keys, oldest evicted first
+struct RecentKeys {
+    order: VecDeque<String>,
+    keys: HashSet<String>,
+    capacity: usize,
+}
+
+impl RecentKeys {
+    fn new(capacity: usize) -> Self {
+        Self {
+            order: VecDeque::new(),
+            keys: HashSet::new(),
+            capacity,
+        }
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.keys.contains(key)
+    }
+
+    fn insert(&mut self, key: &str) {
+        if !self.keys.insert(key.to_string()) {
+            return;
+        }
+        self.order.push_back(key.to_string());
+
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Relay from the event outbox to an event publisher
+pub struct OutboxRelay<O: OutboxRepository, P: EventPublisher> {
+    outbox: Arc<O>,
+    publisher: Arc<P>,
+    batch_size: i64,
+    poll_interval: Duration,
+    claim_lease: Duration,
+    retention: Duration,
+    published: RecentKeys,
+}
+
+impl<O: OutboxRepository, P: EventPublisher> OutboxRelay<O, P> {
+    /// Create a relay with the default batch size, poll interval, claim
+    /// lease and retention
+    pub fn new(outbox: Arc<O>, publisher: Arc<P>) -> Self {
+        Self {
+            outbox,
+            publisher,
+            batch_size: DEFAULT_BATCH_SIZE,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            claim_lease: DEFAULT_CLAIM_LEASE,
+            retention: DEFAULT_RETENTION,
+            published: RecentKeys::new(RECENT_KEYS_CAPACITY),
+        }
+    }
+
+    /// Set how many events are fetched per pass
+    pub fn with_batch_size(mut self, batch_size: i64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set the delay between passes when the outbox is drained
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Set how long sent events are kept
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Publish one batch of unsent events and mark them sent.
+    ///
+    /// Events whose dedup key was already published are only marked sent.
+    /// Events that cannot be decoded are logged and marked sent so they do
+    /// not block the outbox. If publishing fails, the events handled so far
+    /// are marked sent and the rest are retried once the claim lapses.
+    /// Returns the number of events handled.
+    pub async fn relay_once(&mut self) -> Result<usize, AppError> {
+        let events = self.outbox.claim_unsent(self.batch_size, self.claim_lease).await?;
+        let mut handled = Vec::with_capacity(events.len());
+
+        for event in &events {
+            if !self.published.contains(&event.dedup_key) {
+                match decode_event(event) {
+                    Ok(gateway_event) => {
+                        if let Err(e) = self.publisher.publish(&gateway_event).await {
+                            self.outbox.mark_sent(&handled).await?;
+                            return Err(e);
+                        }
+                        self.published.insert(&event.dedup_key);
+                    }
+                    Err(e) => {
+                        tracing::error!(
+                            outbox_id = event.id,
+                            event_type = %event.event_type,
+                            error = %e,
+                            "Dropping undecodable outbox event"
+                        );
+                    }
+                }
+            }
+            handled.push(event.id);
+        }
+
+        self.outbox.mark_sent(&handled).await?;
+        Ok(handled.len())
+    }
+
+    /// Delete sent events older than the retention period.
+    pub async fn sweep_once(&self) -> Result<u64, AppError> {
+        let retention = chrono::Duration::from_std(self.retention).map_err(|e| AppError::Internal(e.to_string()))?;
+        self.outbox.delete_sent_before(Utc::now() - retention).await
+    }
+
+    /// Run the relay in a background task until the runtime shuts down.
+    ///
+    /// Full batches are followed immediately by another pass so a backlog
+    /// drains quickly; otherwise the relay waits for the poll interval.
+    /// Sent events are swept periodically.
+    pub fn spawn(mut self) -> JoinHandle<()>
+    where
+        O: 'static,
+        P: 'static,
+    {
+        tokio::spawn(async move {
+            let mut last_sweep: Option<Instant> = None;
+            loop {
+                if last_sweep.map_or(true, |at| at.elapsed() >= SWEEP_INTERVAL) {
+                    match self.sweep_once().await {
+                        Ok(deleted) => tracing::debug!(deleted, "Swept sent outbox events"),
+                        Err(e) => tracing::warn!(error = %e, "Outbox retention sweep failed"),
+                    }
+                    last_sweep = Some(Instant::now());
+                }
+
+                match self.relay_once().await {
+                    Ok(handled) if handled as i64 >= self.batch_size => continue,
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!(error = %e, "Outbox relay pass failed"),
+                }
+                tokio::time::sleep(self.poll_interval).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::cache::InMemoryCache;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct InMemoryOutbox {
+        events: Mutex<Vec<OutboxEvent>>,
+        fail_mark_sent: AtomicBool,
+    }
+
+    impl InMemoryOutbox {
+        fn push(&self, id: i64, event_type: &str, payload: serde_json::Value) {
+            self.events.lock().unwrap().push(OutboxEvent {
+                id,
+                dedup_key: format!("{}:{}", event_type, id),
+                event_type: event_type.to_string(),
+                payload,
+                created_at: Utc::now(),
+                sent_at: None,
+            });
+        }
+
+        fn unsent_ids(&self) -> Vec<i64> {
+            let events = self.events.lock().unwrap();
+            events.iter().filter(|e| e.sent_at.is_none()).map(|e| e.id).collect()
+        }
+    }
+
+    #[async_trait]
+    impl OutboxRepository for InMemoryOutbox {
+        async fn claim_unsent(&self, limit: i64, _lease: Duration) -> Result<Vec<OutboxEvent>, AppError> {
+            let events = self.events.lock().unwrap();
+            Ok(events
+                .iter()
+                .filter(|e| e.sent_at.is_none())
+                .take(limit as usize)
+                .cloned()
+                .collect())
+        }
+
+        async fn mark_sent(&self, ids: &[i64]) -> Result<u64, AppError> {
+            if self.fail_mark_sent.load(Ordering::SeqCst) {
+                return Err(AppError::Internal("database unavailable".into()));
+            }
+
+            let mut marked = 0;
+            for event in self.events.lock().unwrap().iter_mut() {
+                if ids.contains(&event.id) && event.sent_at.is_none() {
+                    event.sent_at = Some(Utc::now());
+                    marked += 1;
+                }
+            }
+            Ok(marked)
+        }
+
+        async fn delete_sent_before(&self, cutoff: chrono::DateTime<Utc>) -> Result<u64, AppError> {
+            let mut events = self.events.lock().unwrap();
+            let before = events.len();
+            events.retain(|e| e.sent_at.map_or(true, |sent_at| sent_at >= cutoff));
+            Ok((before - events.len()) as u64)
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingPublisher {
+        events: Mutex<Vec<GatewayEvent>>,
+        /// Publishing fails once this many events went out
+        fail_after: Option<usize>,
+    }
+
+    impl RecordingPublisher {
+        fn names(&self) -> Vec<&'static str> {
+            self.events.lock().unwrap().iter().map(|e| e.event_name()).collect()
+        }
+    }
+
+    #[async_trait]
+    impl EventPublisher for RecordingPublisher {
+        async fn publish(&self, event: &GatewayEvent) -> Result<(), AppError> {
+            let mut events = self.events.lock().unwrap();
+            if self.fail_after.is_some_and(|limit| events.len() >= limit) {
+                return Err(AppError::Internal("redis unavailable".into()));
+            }
+            events.push(event.clone());
+            Ok(())
+        }
+    }
+
+    fn message_create(id: i64) -> serde_json::Value {
+        serde_json::json!({
+            "id": id.to_string(),
+            "channel_id": "10",
+            "guild_id": 20,
+            "author": {
+                "id": "30",
+                "username": "alice",
+                "display_name": null,
+                "avatar_url": null,
+            },
+            "content": "hello",
+            "timestamp": "2024-12-17T00:00:00+00:00",
+            "reply_to": null,
+        })
+    }
+
+    fn relay(
+        outbox: &Arc<InMemoryOutbox>,
+        publisher: &Arc<RecordingPublisher>,
+    ) -> OutboxRelay<InMemoryOutbox, RecordingPublisher> {
+        OutboxRelay::new(outbox.clone(), publisher.clone())
+    }
+
+    #[tokio::test]
+    async fn test_relay_publishes_unsent_events_and_marks_them_sent() {
+        let outbox = Arc::new(InMemoryOutbox::default());
+        let publisher = Arc::new(RecordingPublisher::default());
+        outbox.push(1, "MESSAGE_CREATE", message_create(1));
+        outbox.push(2, "MESSAGE_CREATE", message_create(2));
+        let mut relay = relay(&outbox, &publisher);
+
+        assert_eq!(relay.relay_once().await.unwrap(), 2);
+        assert_eq!(publisher.names(), vec!["MESSAGE_CREATE", "MESSAGE_CREATE"]);
+        assert!(outbox.unsent_ids().is_empty());
+
+        // Sent events are not picked up again
+        assert_eq!(relay.relay_once().await.unwrap(), 0);
+        assert_eq!(publisher.names().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_relay_works_through_backlog_in_batches() {
+        let outbox = Arc::new(InMemoryOutbox::default());
+        let publisher = Arc::new(RecordingPublisher::default());
+        for id in 1..=3 {
+            outbox.push(id, "MESSAGE_CREATE", message_create(id));
+        }
+        let mut relay = relay(&outbox, &publisher).with_batch_size(2);
+
+        assert_eq!(relay.relay_once().await.unwrap(), 2);
+        assert_eq!(outbox.unsent_ids(), vec![3]);
+
+        assert_eq!(relay.relay_once().await.unwrap(), 1);
+        assert!(outbox.unsent_ids().is_empty());
+        assert_eq!(publisher.names().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_unmarked_batch_is_not_published_twice() {
+        let outbox = Arc::new(InMemoryOutbox::default());
+        let publisher = Arc::new(RecordingPublisher::default());
+        outbox.push(1, "MESSAGE_CREATE", message_create(1));
+        let mut relay = relay(&outbox, &publisher);
+
+        outbox.fail_mark_sent.store(true, Ordering::SeqCst);
+        assert!(relay.relay_once().await.is_err());
+        assert_eq!(outbox.unsent_ids(), vec![1]);
+
+        // The retry only marks the already-published event as sent
+        outbox.fail_mark_sent.store(false, Ordering::SeqCst);
+        assert_eq!(relay.relay_once().await.unwrap(), 1);
+        assert_eq!(publisher.names().len(), 1);
+        assert!(outbox.unsent_ids().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_undecodable_event_does_not_block_outbox() {
+        let outbox = Arc::new(InMemoryOutbox::default());
+        let publisher = Arc::new(RecordingPublisher::default());
+        outbox.push(1, "UNKNOWN_EVENT", serde_json::json!({}));
+        outbox.push(2, "MESSAGE_CREATE", message_create(2));
+        let mut relay = relay(&outbox, &publisher);
+
+        assert_eq!(relay.relay_once().await.unwrap(), 2);
+        assert_eq!(publisher.names(), vec!["MESSAGE_CREATE"]);
+        assert!(outbox.unsent_ids().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_publish_leaves_the_rest_unsent() {
+        let outbox = Arc::new(InMemoryOutbox::default());
+        let publisher = Arc::new(RecordingPublisher {
+            fail_after: Some(1),
+            ..Default::default()
+        });
+        for id in 1..=3 {
+            outbox.push(id, "MESSAGE_CREATE", message_create(id));
+        }
+        let mut relay = relay(&outbox, &publisher);
+
+        assert!(relay.relay_once().await.is_err());
+        assert_eq!(publisher.names().len(), 1);
+        assert_eq!(outbox.unsent_ids(), vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_sweep_deletes_only_expired_sent_events() {
+        let outbox = Arc::new(InMemoryOutbox::default());
+        let publisher = Arc::new(RecordingPublisher::default());
+        for id in 1..=3 {
+            outbox.push(id, "MESSAGE_CREATE", message_create(id));
+        }
+        {
+            let mut events = outbox.events.lock().unwrap();
+            events[0].sent_at = Some(Utc::now() - chrono::Duration::hours(2));
+            events[1].sent_at = Some(Utc::now());
+        }
+        let relay = relay(&outbox, &publisher).with_retention(Duration::from_secs(60 * 60));
+
+        assert_eq!(relay.sweep_once().await.unwrap(), 1);
+        let remaining: Vec<i64> = outbox.events.lock().unwrap().iter().map(|e| e.id).collect();
+        assert_eq!(remaining, vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_published_events_reach_subscribed_gateways() {
+        let cache = InMemoryCache::new();
+        let mut subscriber = cache.subscribe();
+        let outbox = Arc::new(InMemoryOutbox::default());
+        outbox.push(1, "MESSAGE_CREATE", message_create(1));
+        let publisher = Arc::new(PubSubPublisher::new(cache));
+
+        OutboxRelay::new(outbox, publisher).relay_once().await.unwrap();
+
+        let (channel, message) = subscriber.recv().await.unwrap();
+        assert_eq!(channel, GATEWAY_EVENTS_CHANNEL);
+
+        let gateway = Gateway::new();
+        let mut events = gateway.subscribe();
+        dispatch_published(&gateway, &message).await;
+        assert_eq!(events.recv().await.unwrap().event.event_name(), "MESSAGE_CREATE");
+    }
+
+    #[test]
+    fn test_recent_keys_evict_oldest() {
+        let mut keys = RecentKeys::new(2);
+        keys.insert("a");
+        keys.insert("b");
+        keys.insert("c");
+
+        assert!(!keys.contains("a"));
+        assert!(keys.contains("b"));
+        assert!(keys.contains("c"));
+    }
+}
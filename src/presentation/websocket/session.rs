@@ -1,28 +1,102 @@
 //! WebSocket Session Management
 
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use tracing::field;
+use uuid::Uuid;
+
+/// Op events logged per session per second; the rest are counted and
+/// reported with the next logged op
+pub const MAX_OP_LOGS_PER_SECOND: u32 = 20;
 
 /// WebSocket session state
 #[derive(Debug)]
 pub struct SessionState {
     pub user_id: i64,
     pub session_id: String,
+    /// Unique ID of the underlying WebSocket connection
+    pub connection_id: String,
     pub sequence: u64,
     pub last_heartbeat: Instant,
     pub identified: bool,
+    pub connected_at: Instant,
+    op_log_window: Instant,
+    op_logs: u32,
+    suppressed_op_logs: u32,
+}
+
+/// Fields recorded on a gateway session's tracing span
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSpanFields {
+    pub session_id: String,
+    pub connection_id: String,
+    /// None until the session has identified
+    pub user_id: Option<i64>,
 }
 
 impl SessionState {
     pub fn new(session_id: String) -> Self {
+        let now = Instant::now();
         Self {
             user_id: 0,
             session_id,
+            connection_id: Uuid::new_v4().to_string(),
             sequence: 0,
-            last_heartbeat: Instant::now(),
+            last_heartbeat: now,
             identified: false,
+            connected_at: now,
+            op_log_window: now,
+            op_logs: 0,
+            suppressed_op_logs: 0,
+        }
+    }
+
+    /// Fields identifying this session in traces
+    pub fn span_fields(&self) -> SessionSpanFields {
+        SessionSpanFields {
+            session_id: self.session_id.clone(),
+            connection_id: self.connection_id.clone(),
+            user_id: self.identified.then_some(self.user_id),
         }
     }
 
+    /// Span wrapping everything logged for this session.
+    ///
+    /// `user_id` is left empty until identify; record it with
+    /// `span.record("user_id", user_id)`.
+    pub fn span(&self) -> tracing::Span {
+        let fields = self.span_fields();
+        let span = tracing::info_span!(
+            "gateway_session",
+            session_id = %fields.session_id,
+            connection_id = %fields.connection_id,
+            user_id = field::Empty,
+        );
+        if let Some(user_id) = fields.user_id {
+            span.record("user_id", user_id);
+        }
+        span
+    }
+
+    /// Whether an op event may be logged now.
+    ///
+    /// Returns the number of events suppressed since the last logged one,
+    /// or None once this second's budget is used up.
+    pub fn allow_op_log(&mut self, now: Instant) -> Option<u32> {
+        if now.duration_since(self.op_log_window) >= Duration::from_secs(1) {
+            self.op_log_window = now;
+            self.op_logs = 0;
+        }
+
+        if self.op_logs >= MAX_OP_LOGS_PER_SECOND {
+            self.suppressed_op_logs += 1;
+            return None;
+        }
+
+        self.op_logs += 1;
+        Some(std::mem::take(&mut self.suppressed_op_logs))
+    }
+
     pub fn next_sequence(&mut self) -> u64 {
         self.sequence += 1;
         self.sequence
@@ -36,3 +110,43 @@ impl SessionState {
         self.last_heartbeat.elapsed().as_millis() < timeout_ms as u128
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span_fields_from_session_state() {
+        let mut state = SessionState::new("session-1".to_string());
+
+        let fields = state.span_fields();
+        assert_eq!(fields.session_id, "session-1");
+        assert_eq!(fields.connection_id, state.connection_id);
+        assert!(Uuid::parse_str(&fields.connection_id).is_ok());
+        assert_eq!(fields.user_id, None);
+
+        state.user_id = 42;
+        state.identified = true;
+        assert_eq!(state.span_fields().user_id, Some(42));
+
+        // Each connection gets its own ID
+        let other = SessionState::new("session-1".to_string());
+        assert_ne!(other.connection_id, state.connection_id);
+    }
+
+    #[test]
+    fn test_op_logs_are_rate_limited() {
+        let mut state = SessionState::new("session-1".to_string());
+        let start = Instant::now();
+
+        for _ in 0..MAX_OP_LOGS_PER_SECOND {
+            assert_eq!(state.allow_op_log(start), Some(0));
+        }
+        assert_eq!(state.allow_op_log(start), None);
+        assert_eq!(state.allow_op_log(start), None);
+
+        // The next window reports what was dropped
+        assert_eq!(state.allow_op_log(start + Duration::from_secs(1)), Some(2));
+        assert_eq!(state.allow_op_log(start + Duration::from_secs(1)), Some(0));
+    }
+}
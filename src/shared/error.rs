@@ -3,12 +3,14 @@
 //! Centralized error handling with Axum integration.
 
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde::Serialize;
 
+use super::validation::ValidationError;
+
 /// Application error type
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
@@ -27,8 +29,13 @@ pub enum AppError {
     #[error("Conflict: {0}")]
     Conflict(String),
 
+    /// A new record's unique field is already in use
+    #[error("{0} already exists")]
+    AlreadyExists(UniqueField),
+
+    /// Too many requests; `retry_after` is sent as the `Retry-After` header
     #[error("Rate limited")]
-    RateLimited,
+    RateLimited { retry_after: Option<u64> },
 
     #[error("Internal error: {0}")]
     Internal(String),
@@ -41,6 +48,41 @@ pub enum AppError {
 
     #[error("Validation error: {0}")]
     Validation(String),
+
+    #[error("Validation error: {}", summarize_fields(.0))]
+    InvalidFields(Vec<ValidationError>),
+
+    #[error("Unprocessable entity: {0}")]
+    Unprocessable(String),
+
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("Unsupported media type: {0}")]
+    UnsupportedMediaType(String),
+}
+
+impl AppError {
+    /// Whether the failure is transient, e.g. no database connection was free in time
+    pub fn is_unavailable(&self) -> bool {
+        matches!(self, AppError::Unavailable(_) | AppError::Database(sqlx::Error::PoolTimedOut))
+    }
+}
+
+/// Unique field a new record can conflict on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UniqueField {
+    Email,
+    Username,
+}
+
+impl std::fmt::Display for UniqueField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            UniqueField::Email => "Email",
+            UniqueField::Username => "Username",
+        })
+    }
 }
 
 /// Error response body
@@ -49,30 +91,57 @@ pub struct ErrorResponse {
     pub code: u16,
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub errors: Option<Vec<FieldError>>,
+    pub errors: Option<Vec<ValidationError>>,
 }
 
-/// Field-level validation error
-#[derive(Debug, Serialize)]
-pub struct FieldError {
-    pub field: String,
-    pub message: String,
+/// First field error, or a generic message if there are none
+fn summarize_fields(errors: &[ValidationError]) -> String {
+    errors
+        .first()
+        .map(|e| e.to_string())
+        .unwrap_or_else(|| "Validation failed".into())
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        if let AppError::InvalidFields(errors) = self {
+            let body = ErrorResponse {
+                code: 10007,
+                message: summarize_fields(&errors),
+                errors: Some(errors),
+            };
+            return (StatusCode::BAD_REQUEST, Json(body)).into_response();
+        }
+
         let (status, code, message) = match &self {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, 10001, msg.clone()),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, 10002, msg.clone()),
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, 10003, msg.clone()),
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, 10004, msg.clone()),
             AppError::Conflict(msg) => (StatusCode::CONFLICT, 10005, msg.clone()),
-            AppError::RateLimited => (StatusCode::TOO_MANY_REQUESTS, 10006, "Rate limited".into()),
+            AppError::AlreadyExists(_) => (StatusCode::CONFLICT, 10005, self.to_string()),
+            AppError::RateLimited { retry_after } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                10006,
+                match retry_after {
+                    Some(seconds) => format!("Rate limited. Try again in {} seconds.", seconds),
+                    None => "Rate limited".into(),
+                },
+            ),
             AppError::Validation(msg) => (StatusCode::BAD_REQUEST, 10007, msg.clone()),
+            AppError::InvalidFields(errors) => (StatusCode::BAD_REQUEST, 10007, summarize_fields(errors)),
+            AppError::Unprocessable(msg) => (StatusCode::UNPROCESSABLE_ENTITY, 10008, msg.clone()),
+            AppError::Unavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, 10009, msg.clone()),
+            AppError::UnsupportedMediaType(msg) => (StatusCode::UNSUPPORTED_MEDIA_TYPE, 10010, msg.clone()),
             AppError::Internal(msg) => {
                 tracing::error!("Internal error: {}", msg);
                 (StatusCode::INTERNAL_SERVER_ERROR, 10000, "Internal server error".into())
             }
+            // Every connection is busy; the request may succeed on retry
+            AppError::Database(sqlx::Error::PoolTimedOut) => {
+                tracing::warn!("Timed out acquiring a database connection");
+                (StatusCode::SERVICE_UNAVAILABLE, 10009, "Service temporarily unavailable".into())
+            }
             AppError::Database(e) => {
                 tracing::error!("Database error: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, 10000, "Internal server error".into())
@@ -89,6 +158,43 @@ impl IntoResponse for AppError {
             errors: None,
         };
 
-        (status, Json(body)).into_response()
+        let mut response = (status, Json(body)).into_response();
+        if let AppError::RateLimited { retry_after: Some(seconds) } = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_timeout_is_service_unavailable() {
+        let response = AppError::Database(sqlx::Error::PoolTimedOut).into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let response = AppError::Database(sqlx::Error::RowNotFound).into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_taken_field_is_a_conflict_naming_it() {
+        let error = AppError::AlreadyExists(UniqueField::Email);
+        assert_eq!(error.to_string(), "Email already exists");
+        assert_eq!(error.into_response().status(), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn test_rate_limited_sends_retry_after() {
+        let response = AppError::RateLimited { retry_after: Some(30) }.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "30");
+
+        let response = AppError::RateLimited { retry_after: None }.into_response();
+        assert!(!response.headers().contains_key(header::RETRY_AFTER));
     }
 }
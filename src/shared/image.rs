@@ -0,0 +1,176 @@
+//! Image Headers
+//!
+//! Reads the format and dimensions of PNG, GIF, JPEG and WebP images from
+//! their headers, without decoding pixel data. Malformed or truncated
+//! input yields `None`.
+
+/// Format and size of an image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageInfo {
+    /// MIME type of the detected format
+    pub content_type: &'static str,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Detect an image's format and dimensions from its leading bytes
+pub fn image_info(data: &[u8]) -> Option<ImageInfo> {
+    let (content_type, (width, height)) = if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        ("image/png", png_size(data)?)
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        ("image/gif", gif_size(data)?)
+    } else if data.starts_with(&[0xFF, 0xD8]) {
+        ("image/jpeg", jpeg_size(data)?)
+    } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        ("image/webp", webp_size(data)?)
+    } else {
+        return None;
+    };
+
+    (width > 0 && height > 0).then_some(ImageInfo {
+        content_type,
+        width,
+        height,
+    })
+}
+
+fn u16_be(data: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?) as u32)
+}
+
+fn u16_le(data: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?) as u32)
+}
+
+fn u24_le(data: &[u8], at: usize) -> Option<u32> {
+    let bytes = data.get(at..at + 3)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]))
+}
+
+/// Size from the IHDR chunk, which must come first
+fn png_size(data: &[u8]) -> Option<(u32, u32)> {
+    if data.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(data.get(16..20)?.try_into().ok()?);
+    let height = u32::from_be_bytes(data.get(20..24)?.try_into().ok()?);
+    Some((width, height))
+}
+
+/// Size from the logical screen descriptor
+fn gif_size(data: &[u8]) -> Option<(u32, u32)> {
+    Some((u16_le(data, 6)?, u16_le(data, 8)?))
+}
+
+/// Size from the first start-of-frame segment
+fn jpeg_size(data: &[u8]) -> Option<(u32, u32)> {
+    let mut at = 2;
+    loop {
+        if *data.get(at)? != 0xFF {
+            return None;
+        }
+        let marker = *data.get(at + 1)?;
+        match marker {
+            // Fill byte before a marker
+            0xFF => at += 1,
+            // Markers without a payload
+            0x01 | 0xD0..=0xD7 => at += 2,
+            // End of image, or the scan starts without a frame header
+            0xD9 | 0xDA => return None,
+            // Start of frame; DHT, JPG and DAC share the range
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                return Some((u16_be(data, at + 7)?, u16_be(data, at + 5)?));
+            },
+            _ => at += 2 + u16_be(data, at + 2)? as usize,
+        }
+    }
+}
+
+/// Size from the first chunk of a lossy, lossless or extended WebP
+fn webp_size(data: &[u8]) -> Option<(u32, u32)> {
+    match data.get(12..16)? {
+        b"VP8X" => Some((u24_le(data, 24)? + 1, u24_le(data, 27)? + 1)),
+        b"VP8L" => {
+            if *data.get(20)? != 0x2F {
+                return None;
+            }
+            let bits = u32::from_le_bytes(data.get(21..25)?.try_into().ok()?);
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+        },
+        b"VP8 " => {
+            if data.get(23..26)? != [0x9D, 0x01, 0x2A] {
+                return None;
+            }
+            Some((u16_le(data, 26)? & 0x3FFF, u16_le(data, 28)? & 0x3FFF))
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A complete 1x1 RGBA PNG
+    const TINY_PNG: &[u8] = &[
+        0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F,
+        0x15, 0xC4, 0x89, 0x00, 0x00, 0x00, 0x0A, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0x00,
+        0x01, 0x00, 0x00, 0x05, 0x00, 0x01, 0x0D, 0x0A, 0x2D, 0xB4, 0x00, 0x00, 0x00, 0x00, 0x49,
+        0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
+    ];
+
+    #[test]
+    fn test_png_dimensions() {
+        assert_eq!(
+            image_info(TINY_PNG),
+            Some(ImageInfo {
+                content_type: "image/png",
+                width: 1,
+                height: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_gif_jpeg_and_webp_dimensions() {
+        let gif = b"GIF89a\x40\x01\xF0\x00\x00\x00\x00";
+        assert_eq!(
+            image_info(gif).map(|i| (i.content_type, i.width, i.height)),
+            Some(("image/gif", 320, 240))
+        );
+
+        // SOI, an APP0 segment to skip, then SOF0 for 640x480
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01,
+            0xE0, 0x02, 0x80, 0x03,
+        ];
+        assert_eq!(
+            image_info(&jpeg).map(|i| (i.width, i.height)),
+            Some((640, 480))
+        );
+
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\0\0\0\0".to_vec();
+        webp.extend_from_slice(&[0x7F, 0x02, 0x00, 0xDF, 0x01, 0x00]);
+        assert_eq!(
+            image_info(&webp).map(|i| (i.width, i.height)),
+            Some((640, 480))
+        );
+    }
+
+    #[test]
+    fn test_corrupt_images_have_no_dimensions() {
+        // Truncated before the size
+        assert_eq!(image_info(&TINY_PNG[..18]), None);
+        // IHDR missing
+        let mut png = TINY_PNG.to_vec();
+        png[12..16].copy_from_slice(b"IDAT");
+        assert_eq!(image_info(&png), None);
+        // JPEG segments running past the end
+        assert_eq!(image_info(&[0xFF, 0xD8, 0xFF, 0xE0, 0x40, 0x00]), None);
+        // Zero-sized
+        assert_eq!(image_info(b"GIF89a\x00\x00\x10\x00"), None);
+        assert_eq!(image_info(b"not an image"), None);
+        assert_eq!(image_info(&[]), None);
+    }
+}
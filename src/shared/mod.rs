@@ -3,5 +3,6 @@
 //! Common utilities used across all layers.
 
 pub mod error;
+pub mod image;
 pub mod snowflake;
 pub mod validation;
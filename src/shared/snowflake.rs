@@ -8,6 +8,12 @@ use std::time::{SystemTime, UNIX_EPOCH};
 /// Discord epoch (2015-01-01T00:00:00.000Z)
 const DISCORD_EPOCH: u64 = 1420070400000;
 
+/// Bits of an ID identifying the generating worker (machine and node IDs)
+pub const WORKER_ID_BITS: u32 = 10;
+
+/// Largest worker ID that fits in [`WORKER_ID_BITS`]
+pub const MAX_WORKER_ID: u16 = (1 << WORKER_ID_BITS) - 1;
+
 /// Snowflake ID generator
 pub struct SnowflakeGenerator {
     machine_id: u64,
@@ -27,6 +33,20 @@ impl SnowflakeGenerator {
         }
     }
 
+    /// Create a generator for a 10-bit worker ID.
+    ///
+    /// The high five bits become the machine ID and the low five the node
+    /// ID, so every worker ID in `0..=MAX_WORKER_ID` yields distinct IDs.
+    pub fn from_worker_id(worker_id: u16) -> Self {
+        let worker_id = u64::from(worker_id & MAX_WORKER_ID);
+        Self::new(worker_id >> 5, worker_id)
+    }
+
+    /// The worker ID embedded in generated IDs
+    pub fn worker_id(&self) -> u16 {
+        ((self.machine_id << 5) | self.node_id) as u16
+    }
+
     /// Generate a new snowflake ID
     pub fn generate(&self) -> i64 {
         let timestamp = self.current_timestamp();
@@ -108,4 +128,15 @@ mod tests {
         assert!(ts <= now);
         assert!(ts > now - 1000); // Within 1 second
     }
+
+    #[test]
+    fn test_worker_id_is_embedded_in_ids() {
+        for worker_id in [0, 1, 31, 32, 517, MAX_WORKER_ID] {
+            let gen = SnowflakeGenerator::from_worker_id(worker_id);
+            assert_eq!(gen.worker_id(), worker_id);
+
+            let embedded = (gen.generate() as u64 >> 12) & u64::from(MAX_WORKER_ID);
+            assert_eq!(embedded, u64::from(worker_id));
+        }
+    }
 }
@@ -1,10 +1,98 @@
 //! Validation Utilities
+//!
+//! Validators report problems as [`ValidationError`]s: the offending field,
+//! a machine-readable [`ValidationCode`] and a human-readable message. They
+//! are returned to clients in `ErrorResponse.errors`.
 
+use std::fmt;
+
+use serde::Serialize;
 use validator::ValidationErrors;
 
-use super::error::{AppError, FieldError};
+use super::error::AppError;
+
+/// Machine-readable reason a field failed validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ValidationCode {
+    /// A required value is missing or empty
+    Required,
+    /// Shorter than the minimum length
+    TooShort,
+    /// Longer than the maximum length
+    TooLong,
+    /// Not in the expected format (email, URL, character classes)
+    InvalidFormat,
+    /// A number outside the allowed range
+    OutOfRange,
+    /// Any other invalid value
+    Invalid,
+}
+
+impl ValidationCode {
+    /// Code as sent to clients
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Required => "REQUIRED",
+            Self::TooShort => "TOO_SHORT",
+            Self::TooLong => "TOO_LONG",
+            Self::InvalidFormat => "INVALID_FORMAT",
+            Self::OutOfRange => "OUT_OF_RANGE",
+            Self::Invalid => "INVALID",
+        }
+    }
+
+    /// Parse a code as sent to clients
+    pub fn parse(code: &str) -> Option<Self> {
+        match code {
+            "REQUIRED" => Some(Self::Required),
+            "TOO_SHORT" => Some(Self::TooShort),
+            "TOO_LONG" => Some(Self::TooLong),
+            "INVALID_FORMAT" => Some(Self::InvalidFormat),
+            "OUT_OF_RANGE" => Some(Self::OutOfRange),
+            "INVALID" => Some(Self::Invalid),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ValidationCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A field that failed validation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationError {
+    pub field: String,
+    pub code: ValidationCode,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(field: impl Into<String>, code: ValidationCode, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl From<Vec<ValidationError>> for AppError {
+    fn from(errors: Vec<ValidationError>) -> Self {
+        AppError::InvalidFields(errors)
+    }
+}
 
-/// Validate password strength
+/// Check password strength
 ///
 /// Requirements:
 /// - At least 8 characters
@@ -12,18 +100,23 @@ use super::error::{AppError, FieldError};
 /// - At least one lowercase letter
 /// - At least one digit
 /// - At least one special character
-pub fn validate_password_strength(password: &str) -> Result<(), validator::ValidationError> {
+///
+/// A password that is too short is not checked further; otherwise every
+/// missing character class is reported.
+pub fn check_password_strength(field: &str, password: &str) -> Vec<ValidationError> {
+    if password.len() < 8 {
+        return vec![ValidationError::new(
+            field,
+            ValidationCode::TooShort,
+            "Password must be at least 8 characters",
+        )];
+    }
+
     let mut has_uppercase = false;
     let mut has_lowercase = false;
     let mut has_digit = false;
     let mut has_special = false;
 
-    if password.len() < 8 {
-        let mut err = validator::ValidationError::new("password_too_short");
-        err.message = Some("Password must be at least 8 characters".into());
-        return Err(err);
-    }
-
     for ch in password.chars() {
         if ch.is_uppercase() {
             has_uppercase = true;
@@ -36,50 +129,159 @@ pub fn validate_password_strength(password: &str) -> Result<(), validator::Valid
         }
     }
 
-    if !has_uppercase {
-        let mut err = validator::ValidationError::new("password_no_uppercase");
-        err.message = Some("Password must contain at least one uppercase letter".into());
-        return Err(err);
-    }
+    [
+        (has_uppercase, "Password must contain at least one uppercase letter"),
+        (has_lowercase, "Password must contain at least one lowercase letter"),
+        (has_digit, "Password must contain at least one digit"),
+        (has_special, "Password must contain at least one special character"),
+    ]
+    .into_iter()
+    .filter(|(present, _)| !present)
+    .map(|(_, message)| ValidationError::new(field, ValidationCode::InvalidFormat, message))
+    .collect()
+}
 
-    if !has_lowercase {
-        let mut err = validator::ValidationError::new("password_no_lowercase");
-        err.message = Some("Password must contain at least one lowercase letter".into());
-        return Err(err);
+/// Validate password strength for `#[validate(custom)]` fields
+///
+/// Reports the first failed requirement of [`check_password_strength`],
+/// keeping its code.
+pub fn validate_password_strength(password: &str) -> Result<(), validator::ValidationError> {
+    match check_password_strength("password", password).into_iter().next() {
+        Some(error) => {
+            let mut err = validator::ValidationError::new(error.code.as_str());
+            err.message = Some(error.message.into());
+            Err(err)
+        }
+        None => Ok(()),
     }
+}
 
-    if !has_digit {
-        let mut err = validator::ValidationError::new("password_no_digit");
-        err.message = Some("Password must contain at least one digit".into());
-        return Err(err);
+/// Map an error from the `validator` crate to a validation code.
+fn validator_code(error: &validator::ValidationError) -> ValidationCode {
+    if let Some(code) = ValidationCode::parse(&error.code) {
+        return code;
     }
 
-    if !has_special {
-        let mut err = validator::ValidationError::new("password_no_special");
-        err.message = Some("Password must contain at least one special character".into());
-        return Err(err);
-    }
+    match error.code.as_ref() {
+        "length" => {
+            let bound = |name: &str| error.params.get(name).and_then(|v| v.as_u64());
+            let (min, max) = (bound("min"), bound("max"));
+            let length = error
+                .params
+                .get("value")
+                .and_then(|v| v.as_str())
+                .map(|s| s.chars().count() as u64);
 
-    Ok(())
+            match length {
+                Some(length) if min.is_some_and(|min| length < min) => ValidationCode::TooShort,
+                Some(length) if max.is_some_and(|max| length > max) => ValidationCode::TooLong,
+                // Without the value, a lone bound tells which side failed
+                None if max.is_none() && min.is_some() => ValidationCode::TooShort,
+                None if min.is_none() && max.is_some() => ValidationCode::TooLong,
+                _ => ValidationCode::Invalid,
+            }
+        }
+        "email" | "url" | "regex" | "contains" | "does_not_contain" => ValidationCode::InvalidFormat,
+        "range" => ValidationCode::OutOfRange,
+        "required" => ValidationCode::Required,
+        _ => ValidationCode::Invalid,
+    }
 }
 
-/// Convert validation errors to AppError
-pub fn validation_error(errors: ValidationErrors) -> AppError {
-    let field_errors: Vec<FieldError> = errors
-        .field_errors()
-        .iter()
+/// Convert errors from `#[derive(Validate)]` into validation errors.
+///
+/// Fields are sorted by name so the order is stable across requests.
+pub fn validation_errors(errors: &ValidationErrors) -> Vec<ValidationError> {
+    let mut fields: Vec<_> = errors.field_errors().into_iter().collect();
+    fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    fields
+        .into_iter()
         .flat_map(|(field, errs)| {
-            errs.iter().map(move |e| FieldError {
-                field: field.to_string(),
-                message: e.message.clone().map(|m| m.to_string()).unwrap_or_default(),
+            errs.iter().map(move |e| {
+                ValidationError::new(
+                    field.to_string(),
+                    validator_code(e),
+                    e.message
+                        .as_ref()
+                        .map(|m| m.to_string())
+                        .unwrap_or_else(|| "Invalid value".to_string()),
+                )
             })
         })
-        .collect();
+        .collect()
+}
+
+/// Convert validation errors to AppError
+pub fn validation_error(errors: ValidationErrors) -> AppError {
+    validation_errors(&errors).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::dto::request::RegisterRequest;
+    use validator::Validate;
+
+    fn register(username: &str, email: &str, password: &str) -> RegisterRequest {
+        RegisterRequest {
+            username: username.to_string(),
+            email: email.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_short_username_is_too_short() {
+        let errors = register("a", "a@example.com", "Passw0rd!").validate().unwrap_err();
 
-    let message = field_errors
-        .first()
-        .map(|e| format!("{}: {}", e.field, e.message))
-        .unwrap_or_else(|| "Validation failed".into());
+        assert_eq!(
+            validation_errors(&errors),
+            vec![ValidationError::new(
+                "username",
+                ValidationCode::TooShort,
+                "Username must be 2-32 characters",
+            )]
+        );
+    }
+
+    #[test]
+    fn test_each_field_gets_its_own_code() {
+        let errors = register(&"a".repeat(33), "not-an-email", "short")
+            .validate()
+            .unwrap_err();
+
+        let codes: Vec<(String, ValidationCode)> = validation_errors(&errors)
+            .into_iter()
+            .map(|e| (e.field, e.code))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                ("email".to_string(), ValidationCode::InvalidFormat),
+                ("password".to_string(), ValidationCode::TooShort),
+                ("username".to_string(), ValidationCode::TooLong),
+            ]
+        );
+    }
 
-    AppError::Validation(message)
+    #[test]
+    fn test_password_strength_reports_every_missing_class() {
+        let errors = check_password_strength("new_password", "alllowercase");
+        assert_eq!(errors.len(), 3);
+        assert!(errors
+            .iter()
+            .all(|e| e.field == "new_password" && e.code == ValidationCode::InvalidFormat));
+
+        assert!(check_password_strength("password", "Passw0rd!").is_empty());
+    }
+
+    #[test]
+    fn test_codes_serialize_in_screaming_snake_case() {
+        let error = ValidationError::new("username", ValidationCode::TooShort, "Too short");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({"field": "username", "code": "TOO_SHORT", "message": "Too short"})
+        );
+    }
 }
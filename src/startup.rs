@@ -2,36 +2,86 @@
 //!
 //! Application building and server initialization.
 
+use std::future::IntoFuture;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::Result;
 use axum::Router;
 use sqlx::PgPool;
 use tokio::net::TcpListener;
-use redis::aio::ConnectionManager;
 
-use crate::config::Settings;
-use crate::infrastructure::{database, cache};
+use crate::application::services::{GuildRecipientPermissions, MessageBatchConfig, MessageWriteBatcher};
+use crate::config::{DynamicSettings, Settings, SharedDynamicSettings};
+use crate::infrastructure::{database, cache, storage};
+use crate::infrastructure::cache::{RedisCache, RedisPool, SessionCacheService, WorkerIdAllocator};
+use crate::infrastructure::repositories::{
+    PgChannelRepository, PgMemberRepository, PgMessageRepository, PgOutboxRepository, PgRoleRepository,
+    PgServerRepository, PgSessionRepository, PgUserRepository,
+};
+use crate::infrastructure::storage::FileStorage;
 use crate::presentation::http::routes;
 use crate::presentation::middleware::{cors, logging};
 use crate::presentation::websocket::gateway::Gateway;
+use crate::presentation::websocket::{spawn_subscriber, OutboxRelay, PubSubPublisher};
 use crate::shared::snowflake::SnowflakeGenerator;
 
 /// Application state shared across handlers
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
-    pub redis: ConnectionManager,
+    pub redis: RedisPool,
     pub snowflake: Arc<SnowflakeGenerator>,
     pub gateway: Arc<Gateway>,
+    /// Session and presence cache, shared so its circuit breaker is too
+    pub session_cache: SessionCacheService,
     pub settings: Arc<Settings>,
+    /// Hot-reloadable settings, swapped on `SIGHUP`
+    pub dynamic: SharedDynamicSettings,
+    /// Set once migrations have run and dependencies are verified
+    pub readiness: Readiness,
+    /// Batches message inserts when `message_batch.enabled` is set
+    pub message_batcher: Option<MessageWriteBatcher>,
+    /// Attachment file storage selected by `[storage]`
+    pub storage: Arc<dyn FileStorage>,
+    /// When the application was built, for uptime reporting
+    pub started_at: Instant,
+}
+
+/// Whether startup work (migrations, dependency checks) has finished.
+///
+/// Cloning is cheap and clones share the same flag. Until it is set, API
+/// and gateway traffic is rejected and `/health/ready` reports not ready.
+#[derive(Debug, Clone, Default)]
+pub struct Readiness(Arc<AtomicBool>);
+
+impl Readiness {
+    /// Create a flag in the not-ready state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if startup work has finished
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    /// Mark startup work as finished
+    pub fn mark_ready(&self) {
+        self.0.store(true, Ordering::Release);
+    }
 }
 
 /// Application instance
 pub struct Application {
     listener: TcpListener,
     router: Router,
+    db: PgPool,
+    redis: RedisPool,
+    readiness: Readiness,
+    gateway: Arc<Gateway>,
 }
 
 impl Application {
@@ -45,22 +95,66 @@ impl Application {
         let redis = cache::create_redis_client(&settings.redis).await?;
         tracing::info!("Redis connection established");
 
-        // Create snowflake generator
-        let snowflake = Arc::new(SnowflakeGenerator::new(
-            settings.snowflake.machine_id as u64,
-            0u64, // Default node_id
-        ));
+        // Create snowflake generator with a worker ID unique to this instance
+        let worker_id = if settings.snowflake.auto_assign_worker_id {
+            WorkerIdAllocator::new(redis.clone()).assign().await?
+        } else {
+            settings.snowflake.configured_worker_id()
+        };
+        let snowflake = Arc::new(SnowflakeGenerator::from_worker_id(worker_id));
+        tracing::info!(worker_id, "Snowflake generator created");
 
         // Create WebSocket gateway
-        let gateway = Arc::new(Gateway::new());
+        let recipient_permissions = GuildRecipientPermissions::new(
+            Arc::new(PgServerRepository::new(db.clone())),
+            Arc::new(PgMemberRepository::new(db.clone())),
+            Arc::new(PgRoleRepository::new(db.clone())),
+            Arc::new(PgChannelRepository::new(db.clone())),
+            Arc::new(PgUserRepository::new(db.clone())),
+        );
+        let gateway = Arc::new(Gateway::new().with_recipient_permissions(Arc::new(recipient_permissions)));
+
+        // Publish gateway events queued in the event outbox to every
+        // instance, and hand those published by any instance to this gateway
+        let event_publisher = PubSubPublisher::new(RedisCache::new(redis.clone()));
+        OutboxRelay::new(Arc::new(PgOutboxRepository::new(db.clone())), Arc::new(event_publisher)).spawn();
+        spawn_subscriber(redis::Client::open(settings.redis.url.as_str())?, gateway.clone());
+
+        // Create hot-reloadable settings and listen for reload signals
+        let dynamic = DynamicSettings::from_settings(&settings).into_shared();
+        spawn_reload_handler(dynamic.clone());
+
+        // Create session cache with a database fallback for session reads
+        let session_cache = SessionCacheService::new(redis.clone())
+            .with_session_repository(Arc::new(PgSessionRepository::new(db.clone())));
+
+        // Batch message inserts if configured
+        let message_batcher = settings.message_batch.enabled.then(|| {
+            MessageWriteBatcher::spawn(
+                Arc::new(PgMessageRepository::new(db.clone())),
+                MessageBatchConfig::from_settings(&settings.message_batch),
+            )
+        });
+
+        // Create attachment file storage
+        let storage = storage::create_file_storage(&settings.storage)?;
+
+        // Traffic is gated until migrations have run
+        let readiness = Readiness::new();
 
         // Create app state
         let state = AppState {
-            db,
-            redis,
+            db: db.clone(),
+            redis: redis.clone(),
             snowflake,
-            gateway,
+            gateway: gateway.clone(),
+            session_cache,
             settings: Arc::new(settings.clone()),
+            dynamic,
+            readiness: readiness.clone(),
+            message_batcher,
+            storage,
+            started_at: Instant::now(),
         };
 
         // Build router with middleware
@@ -73,12 +167,39 @@ impl Application {
         let listener = TcpListener::bind(addr).await?;
         tracing::info!("Listening on {}", addr);
 
-        Ok(Self { listener, router })
+        Ok(Self {
+            listener,
+            router,
+            db,
+            redis,
+            readiness,
+            gateway,
+        })
     }
 
-    /// Run the server until stopped
+    /// Run the server until stopped.
+    ///
+    /// Health probes are served right away while migrations run; other
+    /// routes answer 503 until the server is ready. Fails if migrations or
+    /// the dependency checks fail.
+    ///
+    /// On `SIGTERM` or Ctrl-C, gateway clients are asked to reconnect and
+    /// resume elsewhere before the server drains its connections.
     pub async fn run_until_stopped(self) -> Result<()> {
-        axum::serve(self.listener, self.router).await?;
+        let server = axum::serve(
+            self.listener,
+            self.router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+            .with_graceful_shutdown(shutdown_signal(self.gateway))
+            .into_future();
+        tokio::pin!(server);
+
+        tokio::select! {
+            result = &mut server => return Ok(result?),
+            result = prepare(&self.db, &self.redis, &self.readiness) => result?,
+        }
+
+        server.await?;
         Ok(())
     }
 
@@ -87,3 +208,129 @@ impl Application {
         self.listener.local_addr()
     }
 }
+
+/// Wait for a shutdown signal, then ask gateway sessions to reconnect.
+async fn shutdown_signal(gateway: Arc<Gateway>) {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::warn!(error = %e, "Failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to install SIGTERM handler");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+
+    let sessions = gateway.reconnect_all();
+    tracing::info!(sessions, "Shutting down; asked gateway sessions to reconnect");
+}
+
+/// Run migrations and verify dependencies, then mark the server ready.
+async fn prepare(db: &PgPool, redis: &RedisPool, readiness: &Readiness) -> Result<()> {
+    database::run_migrations(db).await?;
+    tracing::info!("Database migrations applied");
+
+    sqlx::query("SELECT 1").execute(db).await?;
+    let mut conn = redis.clone();
+    redis::cmd("PING").query_async::<String>(&mut conn).await?;
+
+    readiness.mark_ready();
+    tracing::info!("Dependencies verified; accepting traffic");
+
+    Ok(())
+}
+
+/// Re-read the configuration and swap in the hot-reloadable subset.
+///
+/// Returns the list of changed values. Settings outside [`DynamicSettings`]
+/// (pools, bind address, secrets) are ignored until the next restart.
+pub fn reload_dynamic_settings(dynamic: &SharedDynamicSettings) -> Result<Vec<String>> {
+    let settings = Settings::load()?;
+    let next = DynamicSettings::from_settings(&settings);
+    let current = dynamic.load();
+    let changes = current.changes(&next);
+
+    if current.log.filter != next.log.filter {
+        crate::telemetry::set_log_filter(&next.log.filter)?;
+    }
+
+    if !changes.is_empty() {
+        dynamic.store(Arc::new(next));
+    }
+
+    Ok(changes)
+}
+
+/// Spawn a task that reloads dynamic settings whenever `SIGHUP` is received.
+#[cfg(unix)]
+fn spawn_reload_handler(dynamic: SharedDynamicSettings) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to install SIGHUP handler; config reload disabled");
+            return;
+        }
+    };
+
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            match reload_dynamic_settings(&dynamic) {
+                Ok(changes) if changes.is_empty() => {
+                    tracing::info!("SIGHUP received; configuration unchanged");
+                }
+                Ok(changes) => {
+                    for change in &changes {
+                        tracing::info!(change = %change, "Configuration value reloaded");
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to reload configuration; keeping current values");
+                }
+            }
+        }
+    });
+}
+
+/// Signal-based reload is only available on Unix platforms.
+#[cfg(not(unix))]
+fn spawn_reload_handler(_dynamic: SharedDynamicSettings) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readiness_starts_unready_and_is_shared_by_clones() {
+        let readiness = Readiness::new();
+        let state_copy = readiness.clone();
+        assert!(!readiness.is_ready());
+        assert!(!state_copy.is_ready());
+
+        readiness.mark_ready();
+
+        assert!(readiness.is_ready());
+        assert!(state_copy.is_ready());
+    }
+}
@@ -1,29 +1,194 @@
 //! Telemetry and Observability
 //!
 //! Structured logging and distributed tracing setup.
+//!
+//! Logs are written as JSON, pretty or compact text depending on
+//! `log.format`. The log filter is installed behind a reload handle so it
+//! can be changed at runtime (see [`set_log_filter`]).
 
+use once_cell::sync::OnceCell;
+use tracing::Subscriber;
 use tracing_subscriber::{
-    fmt,
+    fmt::{self, MakeWriter},
     layer::SubscriberExt,
+    registry::LookupSpan,
+    reload,
     util::SubscriberInitExt,
     EnvFilter,
+    Layer,
+    Registry,
 };
 
-/// Initialize tracing subscriber
-pub fn init_tracing() {
+use crate::config::{LogFormat, LogSettings};
+
+/// Handle used to swap the active `EnvFilter`
+static LOG_FILTER_HANDLE: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+
+/// Errors returned when changing the log filter at runtime
+#[derive(Debug, thiserror::Error)]
+pub enum LogFilterError {
+    #[error("Invalid log filter: {0}")]
+    Invalid(String),
+
+    #[error("Log filter reloading is not available")]
+    Unavailable,
+}
+
+/// Initialize tracing subscriber.
+///
+/// `RUST_LOG` takes precedence over the configured filter. The output
+/// format follows [`LogSettings::format_for`] in `environment`.
+pub fn init_tracing(log: &LogSettings, environment: &str) {
     let env_filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("info,chat_server=debug,sqlx=warn,tower_http=debug"));
+        .unwrap_or_else(|_| EnvFilter::new(&log.filter));
+    let (filter_layer, handle) = reload::Layer::new(env_filter);
+    let format = log.format_for(environment);
+
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(fmt_layer(format, std::io::stdout))
+        .init();
+
+    let _ = LOG_FILTER_HANDLE.set(handle);
 
-    let fmt_layer = fmt::layer()
+    tracing::info!(format = ?format, "Tracing initialized");
+}
+
+/// Formatting layer writing events to `writer` in `format`
+fn fmt_layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::layer()
+        .with_writer(writer)
         .with_target(true)
         .with_thread_ids(true)
         .with_file(true)
         .with_line_number(true);
 
-    tracing_subscriber::registry()
-        .with(env_filter)
-        .with(fmt_layer)
-        .init();
+    match format {
+        LogFormat::Json => layer.json().boxed(),
+        LogFormat::Pretty => layer.pretty().boxed(),
+        LogFormat::Compact => layer.compact().boxed(),
+    }
+}
+
+/// Parse filter directives without applying them.
+pub fn parse_log_filter(filter: &str) -> Result<EnvFilter, LogFilterError> {
+    EnvFilter::builder()
+        .parse(filter)
+        .map_err(|e| LogFilterError::Invalid(e.to_string()))
+}
+
+/// Get the currently active filter directives.
+pub fn current_log_filter() -> Option<String> {
+    LOG_FILTER_HANDLE
+        .get()?
+        .with_current(|filter| filter.to_string())
+        .ok()
+}
+
+/// Replace the active log filter, returning the previous directives.
+///
+/// The filter is validated before anything is swapped, so an invalid
+/// string leaves the current filter untouched.
+pub fn set_log_filter(filter: &str) -> Result<String, LogFilterError> {
+    let new_filter = parse_log_filter(filter)?;
+    let handle = LOG_FILTER_HANDLE.get().ok_or(LogFilterError::Unavailable)?;
+
+    let previous = handle
+        .with_current(|current| current.to_string())
+        .map_err(|_| LogFilterError::Unavailable)?;
+    handle
+        .reload(new_filter)
+        .map_err(|_| LogFilterError::Unavailable)?;
+
+    tracing::info!(previous = %previous, filter = %filter, "Log filter reloaded");
+
+    Ok(previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Writer collecting everything logged to it
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Log one event in `format` and return the output
+    fn render(format: LogFormat) -> String {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber =
+            tracing_subscriber::registry().with(fmt_layer(format, move || writer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || tracing::info!(user_id = 7, "hello"));
+
+        let output = captured.0.lock().unwrap();
+        String::from_utf8(output.clone()).unwrap()
+    }
+
+    #[test]
+    fn test_format_defaults_by_environment() {
+        let mut log = LogSettings {
+            filter: "info".to_string(),
+            format: None,
+        };
+        assert_eq!(log.format_for("development"), LogFormat::Pretty);
+        assert_eq!(log.format_for("production"), LogFormat::Json);
+        assert_eq!(log.format_for("staging"), LogFormat::Json);
+
+        log.format = Some(LogFormat::Compact);
+        assert_eq!(log.format_for("production"), LogFormat::Compact);
+    }
+
+    #[test]
+    fn test_layer_writes_the_selected_format() {
+        let json = render(LogFormat::Json);
+        let line: serde_json::Value = serde_json::from_str(json.trim()).unwrap();
+        assert_eq!(line["fields"]["message"], "hello");
+        assert_eq!(line["fields"]["user_id"], 7);
+
+        let compact = render(LogFormat::Compact);
+        assert_eq!(compact.trim_end().lines().count(), 1);
+        assert!(compact.contains("hello") && compact.contains("user_id"));
+
+        // Pretty output puts the location and fields on lines of their own
+        let pretty = render(LogFormat::Pretty);
+        assert!(pretty.trim_end().lines().count() > 1);
+        assert!(pretty.contains("hello"));
+    }
+
+    #[test]
+    fn test_parse_log_filter_accepts_directives() {
+        assert!(parse_log_filter("info,chat_server=debug").is_ok());
+    }
+
+    #[test]
+    fn test_parse_log_filter_rejects_invalid_level() {
+        let result = parse_log_filter("chat_server=loud");
+        assert!(matches!(result, Err(LogFilterError::Invalid(_))));
+    }
 
-    tracing::info!("Tracing initialized");
+    #[test]
+    fn test_set_log_filter_validates_before_reloading() {
+        // No subscriber is installed in unit tests, so a valid filter would
+        // report `Unavailable`; an invalid one must fail validation first.
+        let result = set_log_filter("chat_server=[");
+        assert!(matches!(result, Err(LogFilterError::Invalid(_))));
+    }
 }